@@ -126,6 +126,10 @@ pub fn run() -> sc_cli::Result<()> {
 			}
 		},
 		None => {
+			// Aura verifies blocks with the authorities of their parent, which a warp synced node doesn't have.
+			if cli.run.network_params.sync == sc_cli::SyncMode::Warp {
+				return Err("Warp sync is not supported by Aura, use `--sync full` or `--sync fast`".into());
+			}
 			let runner = cli.create_runner(&cli.run)?;
 			runner.run_node_until_exit(|config| async move {
 				match config.role {
@@ -76,4 +76,7 @@ pub enum Subcommand {
 
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
+
+	/// Database utilities.
+	Db(sc_cli::DbSubcommand),
 }
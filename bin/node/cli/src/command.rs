@@ -71,6 +71,10 @@ pub fn run() -> Result<()> {
 
 	match &cli.subcommand {
 		None => {
+			// BABE needs the epoch data of a block's ancestry, which a warp synced node doesn't have.
+			if cli.run.network_params.sync == sc_cli::SyncMode::Warp {
+				return Err("Warp sync is not supported by BABE, use `--sync full` or `--sync fast`".into());
+			}
 			let runner = cli.create_runner(&cli.run)?;
 			runner.run_node_until_exit(|config| async move {
 				match config.role {
@@ -146,5 +150,17 @@ pub fn run() -> Result<()> {
 				Ok((cmd.run(client, backend), task_manager))
 			})
 		},
+		Some(Subcommand::Db(sc_cli::DbSubcommand::Migrate(cmd))) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| cmd.run::<Block>(config))
+		},
+		Some(Subcommand::Db(sc_cli::DbSubcommand::Snapshot(cmd))) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| cmd.run::<Block>(config.database))
+		},
+		Some(Subcommand::Db(sc_cli::DbSubcommand::CheckState(cmd))) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| cmd.run::<Block>(config))
+		},
 	}
 }
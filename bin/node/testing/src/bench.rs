@@ -27,7 +27,7 @@ use std::{sync::Arc, path::{Path, PathBuf}, collections::BTreeMap};
 use node_primitives::Block;
 use crate::client::{Client, Backend};
 use crate::keyring::*;
-use sc_client_db::PruningMode;
+use sc_client_db::{BlocksPruning, PruningMode, TransactionStorageMode};
 use sc_executor::{NativeExecutor, WasmExecutionMethod};
 use sp_consensus::{
 	BlockOrigin, BlockImport, BlockImportParams,
@@ -410,7 +410,10 @@ impl BenchDb {
 		let db_config = sc_client_db::DatabaseSettings {
 			state_cache_size: 16*1024*1024,
 			state_cache_child_ratio: Some((0, 100)),
+			trie_cache_size: 0,
 			pruning: PruningMode::ArchiveAll,
+			keep_blocks: BlocksPruning::KeepAll,
+			transaction_storage: TransactionStorageMode::BlockBody,
 			source: database_type.into_settings(dir.into()),
 		};
 		let task_executor = TaskExecutor::new();
@@ -42,6 +42,41 @@ pub trait ProofProvider<Block: BlockT> {
 		keys: &mut dyn Iterator<Item=&[u8]>,
 	) -> sp_blockchain::Result<StorageProof>;
 
+	/// Reads child storage value at a given block + storage_key + key, returning a read proof
+	/// that also covers the child trie root stored in the top trie. The proof can be checked
+	/// against the state root of the block alone, even when no child key is given.
+	fn read_child_proof_with_root(
+		&self,
+		id: &BlockId<Block>,
+		child_info: &ChildInfo,
+		keys: &mut dyn Iterator<Item=&[u8]>,
+	) -> sp_blockchain::Result<StorageProof>;
+
+	/// Reads the key-value pairs that follow `start_key` at a given block, in the top trie or in
+	/// the child trie given by `child_info`, until the encoded size of the proof reaches `size_limit`.
+	/// Reading starts from the first key if `start_key` is `None`.
+	///
+	/// Returns the read proof and the number of pairs it covers.
+	fn read_proof_collection(
+		&self,
+		id: &BlockId<Block>,
+		child_info: Option<&ChildInfo>,
+		start_key: Option<&[u8]>,
+		size_limit: usize,
+	) -> sp_blockchain::Result<(StorageProof, u32)>;
+
+	/// Checks a proof produced by `read_proof_collection` against the state root of a block.
+	///
+	/// Returns the key-value pairs covered by the proof and whether they end with the last key
+	/// of the trie.
+	fn verify_range_proof(
+		&self,
+		root: Block::Hash,
+		proof: StorageProof,
+		child_info: Option<&ChildInfo>,
+		start_key: Option<&[u8]>,
+	) -> sp_blockchain::Result<(Vec<(Vec<u8>, Vec<u8>)>, bool)>;
+
 	/// Execute a call to a contract on top of state in a block of given hash
 	/// AND returning execution proof.
 	///
@@ -0,0 +1,56 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Schema for BEEFY state persisted in the aux-db.
+//!
+//! Signed commitments themselves are stored as block justifications, tagged with
+//! [`BEEFY_ENGINE_ID`](sp_beefy::BEEFY_ENGINE_ID).
+
+use parity_scale_codec::{Decode, Encode};
+use sc_client_api::backend::AuxStore;
+use sp_blockchain::{Error as ClientError, Result as ClientResult};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+const BEST_BEEFY_BLOCK_KEY: &[u8] = b"beefy_best_block";
+
+fn load_decode<B: AuxStore, T: Decode>(backend: &B, key: &[u8]) -> ClientResult<Option<T>> {
+	match backend.get_aux(key)? {
+		None => Ok(None),
+		Some(t) => T::decode(&mut &t[..])
+			.map_err(|e| ClientError::Backend(format!("BEEFY DB is corrupted: {}", e)))
+			.map(Some),
+	}
+}
+
+/// Mark the block with given `hash` and `number` as the latest block with a signed commitment.
+pub(crate) fn write_best_beefy_block<Block: BlockT, B: AuxStore>(
+	backend: &B,
+	hash: &Block::Hash,
+	number: NumberFor<Block>,
+) -> ClientResult<()> {
+	let best = (hash, number).encode();
+
+	backend.insert_aux(&[(BEST_BEEFY_BLOCK_KEY, &best[..])], &[])
+}
+
+/// Load the hash and number of the latest block with a signed commitment, if any.
+pub fn load_best_beefy_block<Block: BlockT, B: AuxStore>(
+	backend: &B,
+) -> ClientResult<Option<(Block::Hash, NumberFor<Block>)>> {
+	load_decode(backend, BEST_BEEFY_BLOCK_KEY)
+}
@@ -0,0 +1,42 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! BEEFY gadget specific errors.
+
+use sp_beefy::crypto::AuthorityId;
+
+/// BEEFY gadget error.
+#[derive(Debug, derive_more::Display, derive_more::From)]
+pub enum Error {
+	/// Keystore error.
+	#[display(fmt = "Keystore error: {}", _0)]
+	Keystore(String),
+	/// The local authority key could not be found in the keystore.
+	#[display(fmt = "Missing key in the keystore: {:?}", _0)]
+	#[from(ignore)]
+	MissingKey(AuthorityId),
+	/// Client error.
+	#[display(fmt = "Client error: {}", _0)]
+	Client(sp_blockchain::Error),
+	/// Signature could not be decoded or is invalid.
+	#[display(fmt = "Invalid signature: {}", _0)]
+	#[from(ignore)]
+	Signature(String),
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,232 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use parity_scale_codec::{Decode, Encode};
+use parking_lot::RwLock;
+
+use sc_network::{PeerId, ReputationChange};
+use sc_network_gossip::{MessageIntent, ValidationResult, Validator, ValidatorContext};
+use sp_runtime::traits::{Block as BlockT, Hash, Header, NumberFor};
+
+use sp_beefy::{
+	crypto::{AuthorityId, AuthoritySignature},
+	MmrRootHash, VoteMessage,
+};
+
+use crate::keystore::BeefyKeystore;
+
+/// Reputation changes applied to peers sending us BEEFY votes.
+mod cost {
+	use super::ReputationChange;
+
+	/// A vote with an invalid signature.
+	pub(super) const BAD_SIGNATURE: ReputationChange = ReputationChange::new(-100, "BEEFY: Bad signature");
+}
+
+/// A BEEFY vote message as gossiped between peers.
+pub(crate) type BeefyVoteMessage<Block> =
+	VoteMessage<MmrRootHash, NumberFor<Block>, AuthorityId, AuthoritySignature>;
+
+/// Gossip engine messages topic
+pub(crate) fn topic<B: BlockT>() -> B::Hash
+where
+	B: BlockT,
+{
+	<<B::Header as Header>::Hashing as Hash>::hash(b"beefy")
+}
+
+/// BEEFY gossip validator
+///
+/// Validate BEEFY gossip messages and limit the number of live BEEFY voting rounds.
+///
+/// Allows messages for rounds newer than the best concluded round to pass through.
+/// Messages for older rounds are considered expired and are dropped.
+pub(crate) struct GossipValidator<B>
+where
+	B: BlockT,
+{
+	topic: B::Hash,
+	best_concluded: RwLock<Option<NumberFor<B>>>,
+}
+
+impl<B> GossipValidator<B>
+where
+	B: BlockT,
+{
+	pub fn new() -> GossipValidator<B> {
+		GossipValidator {
+			topic: topic::<B>(),
+			best_concluded: RwLock::new(None),
+		}
+	}
+
+	/// Note a concluded voting round.
+	///
+	/// Votes for this round and all rounds before it will be considered expired.
+	pub(crate) fn note_round(&self, round: NumberFor<B>) {
+		let mut best = self.best_concluded.write();
+		if best.map(|b| round > b).unwrap_or(true) {
+			*best = Some(round);
+		}
+	}
+
+	fn is_live(&self, round: &NumberFor<B>) -> bool {
+		self.best_concluded.read().map(|best| *round > best).unwrap_or(true)
+	}
+}
+
+impl<B> Validator<B> for GossipValidator<B>
+where
+	B: BlockT,
+{
+	fn validate(
+		&self,
+		context: &mut dyn ValidatorContext<B>,
+		sender: &PeerId,
+		mut data: &[u8],
+	) -> ValidationResult<B::Hash> {
+		if let Ok(msg) = BeefyVoteMessage::<B>::decode(&mut data) {
+			if !self.is_live(&msg.commitment.block_number) {
+				return ValidationResult::Discard;
+			}
+
+			if BeefyKeystore::verify(&msg.id, &msg.signature, &msg.commitment.encode()) {
+				return ValidationResult::ProcessAndKeep(self.topic);
+			} else {
+				log::debug!(target: "beefy", "🥩 Bad signature on message: {:?}, from: {:?}", msg, sender);
+				context.report_peer(sender, cost::BAD_SIGNATURE);
+			}
+		}
+
+		ValidationResult::Discard
+	}
+
+	fn message_expired<'a>(&'a self) -> Box<dyn FnMut(B::Hash, &[u8]) -> bool + 'a> {
+		Box::new(move |_topic, mut data| {
+			let msg = match BeefyVoteMessage::<B>::decode(&mut data) {
+				Ok(vote) => vote,
+				Err(_) => return true,
+			};
+
+			!self.is_live(&msg.commitment.block_number)
+		})
+	}
+
+	fn message_allowed<'a>(&'a self) -> Box<dyn FnMut(&PeerId, MessageIntent, &B::Hash, &[u8]) -> bool + 'a> {
+		Box::new(move |_who, _intent, _topic, mut data| {
+			let msg = match BeefyVoteMessage::<B>::decode(&mut data) {
+				Ok(vote) => vote,
+				Err(_) => return true,
+			};
+
+			self.is_live(&msg.commitment.block_number)
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use sc_network_gossip::Validator as _;
+	use sp_beefy::Commitment;
+	use sp_core::{ecdsa, Pair};
+	use substrate_test_runtime_client::runtime::Block;
+
+	use super::*;
+
+	#[derive(Default)]
+	struct TestContext {
+		reports: Vec<(PeerId, ReputationChange)>,
+	}
+
+	impl ValidatorContext<Block> for TestContext {
+		fn broadcast_topic(&mut self, _: <Block as BlockT>::Hash, _: bool) {}
+		fn broadcast_message(&mut self, _: <Block as BlockT>::Hash, _: Vec<u8>, _: bool) {}
+		fn send_message(&mut self, _: &PeerId, _: Vec<u8>) {}
+		fn send_topic(&mut self, _: &PeerId, _: <Block as BlockT>::Hash, _: bool) {}
+
+		fn report_peer(&mut self, who: &PeerId, reputation: ReputationChange) {
+			self.reports.push((who.clone(), reputation));
+		}
+	}
+
+	fn vote(pair: &ecdsa::Pair, block_number: u64) -> BeefyVoteMessage<Block> {
+		let commitment = Commitment {
+			payload: MmrRootHash::repeat_byte(1),
+			block_number,
+			validator_set_id: 0,
+		};
+		let signature = pair.sign(&commitment.encode()).into();
+
+		VoteMessage {
+			commitment,
+			id: pair.public().into(),
+			signature,
+		}
+	}
+
+	#[test]
+	fn should_validate_signature() {
+		// given
+		let alice = ecdsa::Pair::from_string("//Alice", None).unwrap();
+		let bob = ecdsa::Pair::from_string("//Bob", None).unwrap();
+		let validator = GossipValidator::<Block>::new();
+		let sender = PeerId::random();
+		let mut context = TestContext::default();
+
+		let good = vote(&alice, 1);
+		let mut bad = vote(&alice, 1);
+		bad.id = bob.public().into();
+
+		// then
+		assert!(matches!(
+			validator.validate(&mut context, &sender, &good.encode()),
+			ValidationResult::ProcessAndKeep(_)
+		));
+		assert!(context.reports.is_empty());
+
+		assert!(matches!(
+			validator.validate(&mut context, &sender, &bad.encode()),
+			ValidationResult::Discard
+		));
+		assert_eq!(context.reports, vec![(sender.clone(), cost::BAD_SIGNATURE)]);
+
+		assert!(matches!(
+			validator.validate(&mut context, &sender, &[1, 2, 3]),
+			ValidationResult::Discard
+		));
+	}
+
+	#[test]
+	fn should_expire_votes_of_concluded_rounds() {
+		// given
+		let alice = ecdsa::Pair::from_string("//Alice", None).unwrap();
+		let validator = GossipValidator::<Block>::new();
+		let topic = topic::<Block>();
+
+		// when
+		validator.note_round(5);
+		validator.note_round(3);
+
+		// then
+		let mut expired = validator.message_expired();
+		assert!(expired(topic, &vote(&alice, 4).encode()));
+		assert!(expired(topic, &vote(&alice, 5).encode()));
+		assert!(!expired(topic, &vote(&alice, 6).encode()));
+		assert!(expired(topic, &[1, 2, 3]));
+	}
+}
@@ -0,0 +1,153 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A thin wrapper around the keystore for BEEFY specific operations.
+
+use parity_scale_codec::Decode;
+use sp_application_crypto::{Pair as _, Public as _};
+use sp_core::ecdsa;
+use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
+
+use sp_beefy::{crypto::{AuthorityId, AuthoritySignature, Pair}, KEY_TYPE};
+
+use crate::error::Error;
+
+/// A BEEFY specific keystore implemented as a `Newtype`. This is basically a
+/// wrapper around [`sp_keystore::SyncCryptoStore`] and allows to customize
+/// common cryptographic functionality.
+#[derive(Clone)]
+pub struct BeefyKeystore(Option<SyncCryptoStorePtr>);
+
+impl BeefyKeystore {
+	/// Check if the keystore contains a private key for one of the public keys
+	/// contained in `keys`. A public key with a matching private key is known
+	/// as a local authority id.
+	///
+	/// Return the public key for which we also do have a private key. If no
+	/// matching private key is found, `None` will be returned.
+	pub fn authority_id(&self, keys: &[AuthorityId]) -> Option<AuthorityId> {
+		let store = self.0.clone()?;
+
+		for key in keys {
+			if SyncCryptoStore::has_keys(&*store, &[(key.to_raw_vec(), KEY_TYPE)]) {
+				return Some(key.clone());
+			}
+		}
+
+		None
+	}
+
+	/// Sign `message` with the `public` key.
+	///
+	/// Note that `message` usually will be pre-hashed before being signed.
+	///
+	/// Return the message signature or an error in case of failure.
+	pub fn sign(&self, public: &AuthorityId, message: &[u8]) -> Result<AuthoritySignature, Error> {
+		let store = self.0.clone().ok_or_else(|| Error::Keystore("no Keystore".into()))?;
+
+		let public: ecdsa::Public = public.clone().into();
+		let encoded = SyncCryptoStore::sign_with(
+			&*store,
+			KEY_TYPE,
+			&public.to_public_crypto_pair(),
+			message,
+		).map_err(|e| Error::Keystore(e.to_string()))?;
+
+		let sig = ecdsa::Signature::decode(&mut &encoded[..])
+			.map_err(|_| Error::Signature("invalid signature encoding".into()))?;
+
+		Ok(sig.into())
+	}
+
+	/// Returns a vector of [`sp_beefy::crypto::AuthorityId`] public keys which are
+	/// currently supported (i.e. found in the keystore).
+	pub fn public_keys(&self) -> Result<Vec<AuthorityId>, Error> {
+		let store = self.0.clone().ok_or_else(|| Error::Keystore("no Keystore".into()))?;
+
+		let pk: Vec<AuthorityId> = SyncCryptoStore::ecdsa_public_keys(&*store, KEY_TYPE)
+			.into_iter()
+			.map(AuthorityId::from)
+			.collect();
+
+		Ok(pk)
+	}
+
+	/// Use the `public` key to verify that `sig` is a valid signature for `message`.
+	///
+	/// Return `true` if the signature is authentic, `false` otherwise.
+	pub fn verify(public: &AuthorityId, sig: &AuthoritySignature, message: &[u8]) -> bool {
+		Pair::verify(sig, message, public)
+	}
+}
+
+impl From<Option<SyncCryptoStorePtr>> for BeefyKeystore {
+	fn from(store: Option<SyncCryptoStorePtr>) -> BeefyKeystore {
+		BeefyKeystore(store)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use sc_keystore::LocalKeystore;
+	use sp_keystore::SyncCryptoStore;
+
+	use super::*;
+
+	fn keystore() -> SyncCryptoStorePtr {
+		Arc::new(LocalKeystore::in_memory())
+	}
+
+	#[test]
+	fn authority_id_works() {
+		let store = keystore();
+
+		let alice: AuthorityId = SyncCryptoStore::ecdsa_generate_new(&*store, KEY_TYPE, Some("//Alice"))
+			.unwrap()
+			.into();
+		let bob: AuthorityId = ecdsa::Pair::from_string("//Bob", None).unwrap().public().into();
+
+		let store: BeefyKeystore = Some(store).into();
+
+		assert_eq!(store.authority_id(&[bob.clone()]), None);
+		assert_eq!(store.authority_id(&[bob, alice.clone()]), Some(alice));
+		assert_eq!(BeefyKeystore::from(None).authority_id(&[]), None);
+	}
+
+	#[test]
+	fn sign_and_verify_works() {
+		let store = keystore();
+
+		let alice: AuthorityId = SyncCryptoStore::ecdsa_generate_new(&*store, KEY_TYPE, Some("//Alice"))
+			.unwrap()
+			.into();
+		let bob: AuthorityId = ecdsa::Pair::from_string("//Bob", None).unwrap().public().into();
+
+		let store: BeefyKeystore = Some(store).into();
+
+		let msg = b"are you involved or commited?";
+		let sig = store.sign(&alice, msg).unwrap();
+
+		assert!(BeefyKeystore::verify(&alice, &sig, msg));
+		assert!(!BeefyKeystore::verify(&alice, &sig, b"another message"));
+		assert!(!BeefyKeystore::verify(&bob, &sig, msg));
+		assert!(store.sign(&bob, msg).is_err());
+		assert_eq!(store.public_keys().unwrap(), vec![alice]);
+	}
+}
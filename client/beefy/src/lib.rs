@@ -0,0 +1,117 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! BEEFY gadget.
+//!
+//! BEEFY is a secondary finality gadget running alongside GRANDPA. Once GRANDPA finalizes a
+//! block, every BEEFY authority signs a [`Commitment`](sp_beefy::Commitment) to the MMR root
+//! found in the block's BEEFY digest and gossips its vote. As soon as votes of more than 2/3
+//! of the validator set are collected, the resulting signed commitment is stored as a
+//! justification of the committed block and sent to all [`BeefySignedCommitmentStream`]
+//! subscribers.
+
+#![warn(missing_docs)]
+
+use std::sync::Arc;
+
+use log::debug;
+
+use sc_client_api::{AuxStore, Backend, BlockchainEvents};
+use sc_network_gossip::{GossipEngine, Network as GossipNetwork};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_keystore::SyncCryptoStorePtr;
+use sp_runtime::traits::Block as BlockT;
+
+use sp_beefy::BeefyApi;
+
+mod aux_schema;
+mod error;
+mod gossip;
+mod keystore;
+mod notification;
+mod round;
+mod worker;
+
+pub use aux_schema::load_best_beefy_block;
+pub use error::Error;
+pub use notification::{BeefySignedCommitment, BeefySignedCommitmentSender, BeefySignedCommitmentStream};
+
+/// The protocol name used for BEEFY gossip.
+pub const BEEFY_PROTOCOL_NAME: &str = "/paritytech/beefy/1";
+
+/// BEEFY gadget initialization parameters.
+pub struct BeefyParams<B, BE, C, N>
+where
+	B: BlockT,
+	BE: Backend<B>,
+{
+	/// BEEFY client
+	pub client: Arc<C>,
+	/// Client Backend
+	pub backend: Arc<BE>,
+	/// Local key store
+	pub key_store: Option<SyncCryptoStorePtr>,
+	/// Gossip network
+	pub network: N,
+	/// BEEFY signed commitment sender
+	pub signed_commitment_sender: BeefySignedCommitmentSender<B>,
+	/// Minimal delta between blocks, BEEFY should vote for
+	pub min_block_delta: u32,
+}
+
+/// Start the BEEFY gadget.
+///
+/// This is a thin shim around running and awaiting a BEEFY worker. The gossip
+/// protocol given by [`BEEFY_PROTOCOL_NAME`] needs to be registered with the
+/// network configuration beforehand.
+pub async fn start_beefy_gadget<B, BE, C, N>(beefy_params: BeefyParams<B, BE, C, N>)
+where
+	B: BlockT,
+	BE: Backend<B>,
+	C: BlockchainEvents<B> + HeaderBackend<B> + AuxStore + ProvideRuntimeApi<B>,
+	C::Api: BeefyApi<B>,
+	N: GossipNetwork<B> + Clone + Send + 'static,
+{
+	let BeefyParams {
+		client,
+		backend,
+		key_store,
+		network,
+		signed_commitment_sender,
+		min_block_delta,
+	} = beefy_params;
+
+	let gossip_validator = Arc::new(gossip::GossipValidator::new());
+	let gossip_engine = GossipEngine::new(network, BEEFY_PROTOCOL_NAME, gossip_validator.clone());
+
+	let worker = worker::BeefyWorker::new(
+		client.clone(),
+		backend,
+		key_store.into(),
+		signed_commitment_sender,
+		gossip_engine,
+		gossip_validator,
+		min_block_delta,
+		client.finality_notification_stream(),
+	);
+
+	debug!(target: "beefy", "🥩 BEEFY gadget started");
+
+	worker.run().await
+}
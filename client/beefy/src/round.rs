@@ -0,0 +1,195 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use sp_beefy::{
+	crypto::{AuthorityId, AuthoritySignature},
+	ValidatorSet, ValidatorSetId,
+};
+
+/// Votes collected for a single `(payload, block number)` round.
+#[derive(Debug, Default)]
+struct RoundTracker {
+	votes: Vec<(AuthorityId, AuthoritySignature)>,
+}
+
+impl RoundTracker {
+	fn add_vote(&mut self, vote: (AuthorityId, AuthoritySignature)) -> bool {
+		// this needs to handle equivocations in the future
+		if self.votes.iter().any(|(id, _)| id == &vote.0) {
+			return false;
+		}
+
+		self.votes.push(vote);
+		true
+	}
+
+	fn is_done(&self, threshold: usize) -> bool {
+		self.votes.len() >= threshold
+	}
+}
+
+/// The BEEFY rounds currently in progress for one validator set.
+#[derive(Debug)]
+pub(crate) struct Rounds<Payload, Number> {
+	rounds: BTreeMap<(Payload, Number), RoundTracker>,
+	validator_set: ValidatorSet<AuthorityId>,
+}
+
+impl<Payload, Number> Rounds<Payload, Number>
+where
+	Payload: Ord,
+	Number: Ord + Copy,
+{
+	/// Create rounds tracking votes of the given `validator_set`.
+	pub(crate) fn new(validator_set: ValidatorSet<AuthorityId>) -> Self {
+		Rounds {
+			rounds: BTreeMap::new(),
+			validator_set,
+		}
+	}
+
+	/// Return the id of the validator set votes are collected for.
+	pub(crate) fn validator_set_id(&self) -> ValidatorSetId {
+		self.validator_set.id
+	}
+
+	/// Return the authorities of the validator set votes are collected for.
+	pub(crate) fn validators(&self) -> &[AuthorityId] {
+		&self.validator_set.validators
+	}
+
+	/// Add a vote for given `round`.
+	///
+	/// Votes from authorities not part of the current validator set and duplicate
+	/// votes are ignored. Returns `true` if the vote was added.
+	pub(crate) fn add_vote(&mut self, round: (Payload, Number), vote: (AuthorityId, AuthoritySignature)) -> bool {
+		if !self.validator_set.validators.contains(&vote.0) {
+			return false;
+		}
+
+		self.rounds.entry(round).or_default().add_vote(vote)
+	}
+
+	/// Check whether enough votes have been collected for given `round`.
+	pub(crate) fn is_done(&self, round: &(Payload, Number)) -> bool {
+		self.rounds
+			.get(round)
+			.map(|tracker| tracker.is_done(self.validator_set.threshold()))
+			.unwrap_or(false)
+	}
+
+	/// Remove the given `round` and every round for an older block.
+	///
+	/// Returns the signatures collected for `round`, ordered as the validator set,
+	/// or `None` if no votes have been collected for it.
+	pub(crate) fn drop(&mut self, round: &(Payload, Number)) -> Option<Vec<Option<AuthoritySignature>>> {
+		let number = round.1;
+		let tracker = self.rounds.remove(round)?;
+		self.rounds.retain(|(_, n), _| *n > number);
+
+		let signatures = self.validator_set.validators
+			.iter()
+			.map(|authority_id| {
+				tracker.votes.iter().find_map(|(id, sig)| {
+					if id == authority_id {
+						Some(sig.clone())
+					} else {
+						None
+					}
+				})
+			})
+			.collect();
+
+		Some(signatures)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use sp_core::{ecdsa, Pair};
+
+	use super::*;
+
+	fn pair(seed: &str) -> (AuthorityId, ecdsa::Pair) {
+		let pair = ecdsa::Pair::from_string(seed, None).unwrap();
+		(pair.public().into(), pair)
+	}
+
+	fn sign(pair: &ecdsa::Pair, msg: &[u8]) -> AuthoritySignature {
+		pair.sign(msg).into()
+	}
+
+	#[test]
+	fn round_completes_once_threshold_is_reached() {
+		// given
+		let (alice, alice_pair) = pair("//Alice");
+		let (bob, bob_pair) = pair("//Bob");
+		let (charlie, charlie_pair) = pair("//Charlie");
+		let (dave, _) = pair("//Dave");
+
+		let mut rounds = Rounds::<u8, u64>::new(ValidatorSet {
+			validators: vec![alice.clone(), bob.clone(), charlie.clone()],
+			id: 42,
+		});
+		assert_eq!(rounds.validator_set_id(), 42);
+
+		// when
+		assert!(rounds.add_vote((1, 1), (alice.clone(), sign(&alice_pair, b"1"))));
+		assert!(!rounds.add_vote((1, 1), (alice.clone(), sign(&alice_pair, b"1"))));
+		assert!(!rounds.add_vote((1, 1), (dave, sign(&charlie_pair, b"1"))));
+		assert!(rounds.add_vote((1, 1), (bob.clone(), sign(&bob_pair, b"1"))));
+
+		// then
+		assert!(!rounds.is_done(&(1, 1)));
+
+		assert!(rounds.add_vote((1, 1), (charlie, sign(&charlie_pair, b"1"))));
+		assert!(rounds.is_done(&(1, 1)));
+		assert!(!rounds.is_done(&(2, 1)));
+	}
+
+	#[test]
+	fn drop_returns_signatures_in_validator_set_order() {
+		// given
+		let (alice, alice_pair) = pair("//Alice");
+		let (bob, _) = pair("//Bob");
+		let (charlie, charlie_pair) = pair("//Charlie");
+
+		let mut rounds = Rounds::<u8, u64>::new(ValidatorSet {
+			validators: vec![alice.clone(), bob, charlie.clone()],
+			id: 0,
+		});
+
+		let alice_sig = sign(&alice_pair, b"1");
+		let charlie_sig = sign(&charlie_pair, b"1");
+
+		rounds.add_vote((1, 1), (charlie, charlie_sig.clone()));
+		rounds.add_vote((1, 1), (alice.clone(), alice_sig.clone()));
+		rounds.add_vote((2, 0), (alice.clone(), alice_sig.clone()));
+		rounds.add_vote((3, 2), (alice, alice_sig.clone()));
+
+		// when
+		let signatures = rounds.drop(&(1, 1));
+
+		// then
+		assert_eq!(signatures, Some(vec![Some(alice_sig), None, Some(charlie_sig)]));
+		assert_eq!(rounds.drop(&(2, 0)), None);
+		assert!(rounds.drop(&(3, 2)).is_some());
+	}
+}
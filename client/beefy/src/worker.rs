@@ -0,0 +1,344 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use futures::{future, FutureExt, StreamExt};
+use log::{debug, error, info, trace, warn};
+use parking_lot::Mutex;
+use parity_scale_codec::{Decode, Encode};
+
+use sc_client_api::{AuxStore, Backend, FinalityNotification, FinalityNotifications};
+use sc_network_gossip::GossipEngine;
+use sp_api::{BlockId, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	generic::OpaqueDigestItemId,
+	traits::{Block as BlockT, Header, NumberFor, Zero},
+};
+
+use sp_beefy::{
+	crypto::{AuthorityId, AuthoritySignature},
+	BeefyApi, Commitment, ConsensusLog, MmrRootHash, SignedCommitment, ValidatorSet,
+	VoteMessage, BEEFY_ENGINE_ID,
+};
+
+use crate::{
+	aux_schema,
+	gossip::{topic, BeefyVoteMessage, GossipValidator},
+	keystore::BeefyKeystore,
+	notification::BeefySignedCommitmentSender,
+	round::Rounds,
+};
+
+/// A BEEFY worker plays the BEEFY protocol
+pub(crate) struct BeefyWorker<B, BE, C>
+where
+	B: BlockT,
+	BE: Backend<B>,
+{
+	client: Arc<C>,
+	backend: Arc<BE>,
+	key_store: BeefyKeystore,
+	signed_commitment_sender: BeefySignedCommitmentSender<B>,
+	gossip_engine: Arc<Mutex<GossipEngine<B>>>,
+	gossip_validator: Arc<GossipValidator<B>>,
+	/// Min delta in block numbers between two blocks, BEEFY should vote on
+	min_block_delta: u32,
+	rounds: Option<Rounds<MmrRootHash, NumberFor<B>>>,
+	finality_notifications: FinalityNotifications<B>,
+	/// Best block we received a GRANDPA notification for
+	best_grandpa_block: NumberFor<B>,
+	/// Best block a BEEFY voting round has been concluded for
+	best_beefy_block: Option<NumberFor<B>>,
+}
+
+impl<B, BE, C> BeefyWorker<B, BE, C>
+where
+	B: BlockT,
+	BE: Backend<B>,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + AuxStore,
+	C::Api: BeefyApi<B>,
+{
+	/// Return a new BEEFY worker instance.
+	///
+	/// Note that a BEEFY worker is only fully functional if a corresponding
+	/// BEEFY pallet has been deployed on-chain.
+	///
+	/// The BEEFY pallet is needed in order to keep track of the BEEFY authority set.
+	pub(crate) fn new(
+		client: Arc<C>,
+		backend: Arc<BE>,
+		key_store: BeefyKeystore,
+		signed_commitment_sender: BeefySignedCommitmentSender<B>,
+		gossip_engine: GossipEngine<B>,
+		gossip_validator: Arc<GossipValidator<B>>,
+		min_block_delta: u32,
+		finality_notifications: FinalityNotifications<B>,
+	) -> Self {
+		let best_beefy_block = aux_schema::load_best_beefy_block::<B, _>(&*client)
+			.unwrap_or_else(|e| {
+				warn!(target: "beefy", "🥩 Failed to load best BEEFY block: {:?}", e);
+				None
+			})
+			.map(|(_, number)| number);
+
+		if let Some(number) = best_beefy_block {
+			gossip_validator.note_round(number);
+		}
+
+		BeefyWorker {
+			client,
+			backend,
+			key_store,
+			signed_commitment_sender,
+			gossip_engine: Arc::new(Mutex::new(gossip_engine)),
+			gossip_validator,
+			min_block_delta,
+			rounds: None,
+			finality_notifications,
+			best_grandpa_block: Zero::zero(),
+			best_beefy_block,
+		}
+	}
+
+	/// Return `true`, if we should vote on block `number`
+	fn should_vote_on(&self, number: NumberFor<B>) -> bool {
+		match self.best_beefy_block {
+			None => true,
+			Some(best) => number >= best + self.min_block_delta.max(1).into(),
+		}
+	}
+
+	/// Return the current validator set at the finalized block with given `hash`.
+	fn validator_set(&self, hash: B::Hash) -> Option<ValidatorSet<AuthorityId>> {
+		self.client
+			.runtime_api()
+			.validator_set(&BlockId::hash(hash))
+			.map_err(|e| debug!(target: "beefy", "🥩 Failed to fetch validator set at {:?}: {:?}", hash, e))
+			.ok()
+	}
+
+	fn handle_finality_notification(&mut self, notification: FinalityNotification<B>) {
+		trace!(target: "beefy", "🥩 Finality notification: {:?}", notification);
+
+		let number = *notification.header.number();
+		if number <= self.best_grandpa_block {
+			return;
+		}
+		self.best_grandpa_block = number;
+
+		let validator_set = match self.validator_set(notification.hash) {
+			Some(set) if !set.validators.is_empty() => set,
+			_ => return,
+		};
+
+		let set_changed = self.rounds.as_ref().map(|r| r.validator_set_id() != validator_set.id).unwrap_or(true);
+		if set_changed {
+			debug!(target: "beefy", "🥩 New active validator set id: {:?}", validator_set.id);
+			self.rounds = Some(Rounds::new(validator_set));
+		}
+
+		if !set_changed && !self.should_vote_on(number) {
+			return;
+		}
+
+		let payload = match find_mmr_root_digest::<B>(&notification.header) {
+			Some(root) => root,
+			None => {
+				debug!(target: "beefy", "🥩 No MMR root digest found for: {:?}", notification.hash);
+				return;
+			}
+		};
+
+		let rounds = self.rounds.as_ref().expect("rounds are set above; qed");
+		let authority_id = match self.key_store.authority_id(rounds.validators()) {
+			Some(id) => id,
+			None => {
+				trace!(target: "beefy", "🥩 Missing validator id - can't vote for: {:?}", notification.hash);
+				return;
+			}
+		};
+
+		let commitment = Commitment {
+			payload,
+			block_number: number,
+			validator_set_id: rounds.validator_set_id(),
+		};
+
+		let signature = match self.key_store.sign(&authority_id, &commitment.encode()) {
+			Ok(sig) => sig,
+			Err(err) => {
+				warn!(target: "beefy", "🥩 Error signing commitment: {:?}", err);
+				return;
+			}
+		};
+
+		let message = VoteMessage {
+			commitment,
+			id: authority_id,
+			signature,
+		};
+
+		let encoded = message.encode();
+		self.gossip_engine.lock().gossip_message(topic::<B>(), encoded, false);
+
+		debug!(target: "beefy", "🥩 Sent vote message: {:?}", message);
+
+		self.handle_vote(message);
+	}
+
+	fn handle_vote(&mut self, vote: BeefyVoteMessage<B>) {
+		let rounds = match self.rounds.as_mut() {
+			Some(rounds) => rounds,
+			None => return,
+		};
+
+		if vote.commitment.validator_set_id != rounds.validator_set_id() {
+			trace!(target: "beefy", "🥩 Vote for a different validator set: {:?}", vote);
+			return;
+		}
+
+		if self.best_beefy_block.map(|best| vote.commitment.block_number <= best).unwrap_or(false) {
+			return;
+		}
+
+		let round = (vote.commitment.payload, vote.commitment.block_number);
+		if !rounds.add_vote(round, (vote.id, vote.signature)) || !rounds.is_done(&round) {
+			return;
+		}
+
+		let signatures = match rounds.drop(&round) {
+			Some(signatures) => signatures,
+			None => return,
+		};
+
+		let signed_commitment = SignedCommitment::<NumberFor<B>, MmrRootHash, AuthoritySignature> {
+			commitment: vote.commitment,
+			signatures,
+		};
+		let number = signed_commitment.commitment.block_number;
+
+		info!(target: "beefy", "🥩 Round #{} concluded, committed: {:?}.", number, signed_commitment);
+
+		// the committed block is already finalized by GRANDPA, so the signed commitment
+		// can be stored alongside the GRANDPA justification of the block.
+		match self.client.hash(number) {
+			Ok(Some(hash)) => {
+				let justification = (BEEFY_ENGINE_ID, signed_commitment.encode());
+				if let Err(e) = self.backend.append_justification(BlockId::Hash(hash), justification) {
+					error!(target: "beefy", "🥩 Failed to store signed commitment for {:?}: {:?}", hash, e);
+				} else if let Err(e) = aux_schema::write_best_beefy_block::<B, _>(&*self.client, &hash, number) {
+					error!(target: "beefy", "🥩 Failed to store best BEEFY block {:?}: {:?}", hash, e);
+				}
+			},
+			Ok(None) => warn!(target: "beefy", "🥩 Unknown block concluded: {:?}", number),
+			Err(e) => error!(target: "beefy", "🥩 Failed to fetch hash of block #{}: {:?}", number, e),
+		}
+
+		self.best_beefy_block = Some(number);
+		self.gossip_validator.note_round(number);
+		self.signed_commitment_sender.notify(signed_commitment);
+	}
+
+	pub(crate) async fn run(mut self) {
+		let mut votes = Box::pin(self.gossip_engine.lock().messages_for(topic::<B>()).filter_map(
+			|notification| async move {
+				trace!(target: "beefy", "🥩 Got vote message: {:?}", notification);
+
+				BeefyVoteMessage::<B>::decode(&mut &notification.message[..]).ok()
+			},
+		));
+
+		loop {
+			let engine = self.gossip_engine.clone();
+			let gossip_engine = future::poll_fn(|cx| engine.lock().poll_unpin(cx));
+
+			futures::select! {
+				notification = self.finality_notifications.next().fuse() => {
+					if let Some(notification) = notification {
+						self.handle_finality_notification(notification);
+					} else {
+						return;
+					}
+				},
+				vote = votes.next().fuse() => {
+					if let Some(vote) = vote {
+						self.handle_vote(vote);
+					} else {
+						return;
+					}
+				},
+				_ = gossip_engine.fuse() => {
+					error!(target: "beefy", "🥩 Gossip engine has terminated.");
+					return;
+				}
+			}
+		}
+	}
+}
+
+/// Extract the MMR root hash from a digest in the given header, if it exists.
+fn find_mmr_root_digest<B>(header: &B::Header) -> Option<MmrRootHash>
+where
+	B: BlockT,
+{
+	let id = OpaqueDigestItemId::Consensus(&BEEFY_ENGINE_ID);
+
+	let filter = |log: ConsensusLog<AuthorityId>| match log {
+		ConsensusLog::MmrRoot(root) => Some(root),
+		_ => None,
+	};
+
+	header.digest().convert_first(|l| l.try_to(id).and_then(filter))
+}
+
+#[cfg(test)]
+mod tests {
+	use sp_core::H256;
+	use sp_runtime::{generic::DigestItem, traits::Header as _};
+	use substrate_test_runtime_client::runtime::{Block, Header};
+
+	use super::*;
+
+	#[test]
+	fn should_find_mmr_root_digest() {
+		// given
+		let mut header = Header::new(
+			1,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		);
+		assert_eq!(find_mmr_root_digest::<Block>(&header), None);
+
+		// when
+		header.digest_mut().push(DigestItem::Consensus(
+			BEEFY_ENGINE_ID,
+			ConsensusLog::<AuthorityId>::OnDisabled(0).encode(),
+		));
+		header.digest_mut().push(DigestItem::Consensus(
+			BEEFY_ENGINE_ID,
+			ConsensusLog::<AuthorityId>::MmrRoot(H256::repeat_byte(1)).encode(),
+		));
+
+		// then
+		assert_eq!(find_mmr_root_digest::<Block>(&header), Some(H256::repeat_byte(1)));
+	}
+}
@@ -0,0 +1,287 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::arg_enums::Database;
+use crate::error;
+use crate::params::{DatabaseParams, PruningParams, SharedParams};
+use crate::CliConfiguration;
+use log::info;
+use sc_service::{Configuration, DatabaseConfig, DatabaseType, Role, db_check, db_migration, db_snapshot};
+use sp_core::bytes::to_hex;
+use sp_runtime::traits::Block as BlockT;
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// Database utilities for the cli.
+#[derive(Debug, StructOpt)]
+pub enum DbSubcommand {
+	/// Copy the database into a different backend.
+	Migrate(MigrateDbCmd),
+
+	/// Take a consistent copy of the database that a new node can be started from.
+	///
+	/// RocksDB databases can be copied while the node is running. ParityDB databases can
+	/// only be copied while the node is stopped, the command fails if the database is in use.
+	Snapshot(SnapshotDbCmd),
+
+	/// Check that the retained states are complete and find unreferenced state nodes.
+	CheckState(CheckStateDbCmd),
+}
+
+/// The `db migrate` command used to move the database to a different backend.
+#[derive(Debug, StructOpt)]
+pub struct MigrateDbCmd {
+	/// Backend of the existing database.
+	#[structopt(long, value_name = "DB", case_insensitive = true)]
+	pub from: Database,
+
+	/// Backend to migrate the database to.
+	#[structopt(long, value_name = "DB", case_insensitive = true)]
+	pub to: Database,
+
+	/// Path of the migrated database.
+	///
+	/// Defaults to the path the node uses for the target backend. An interrupted migration
+	/// is resumed when it is started again with the same target.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub target_path: Option<PathBuf>,
+
+	/// Compare the value hashes of every n-th entry of each column after migration.
+	///
+	/// With `0` only the presence of the keys is verified.
+	#[structopt(long, value_name = "N", default_value = "1000")]
+	pub verify_sample_rate: u64,
+
+	/// Migrate the database of a light client.
+	#[structopt(long)]
+	pub light: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl MigrateDbCmd {
+	/// Run the migrate command
+	pub fn run<B: BlockT>(&self, config: Configuration) -> error::Result<()> {
+		let source = match (self.from, self.to, &config.database) {
+			(Database::RocksDb, Database::ParityDb, DatabaseConfig::RocksDb { path, .. }) => path.clone(),
+			(from, to, _) => return Err(error::Error::Input(
+				format!("Migrating the database from {} to {} is not supported", from, to),
+			)),
+		};
+		let target = match &self.target_path {
+			Some(path) => path.clone(),
+			None => {
+				let base_path = source.parent().map(PathBuf::from).unwrap_or_default();
+				self.database_config(&base_path, 0, self.to)?
+					.path()
+					.map(PathBuf::from)
+					.ok_or_else(|| error::Error::Input("Target database has no path".into()))?
+			},
+		};
+		let db_type = match config.role {
+			Role::Light => DatabaseType::Light,
+			_ => DatabaseType::Full,
+		};
+
+		info!("Migrating {:?} to {:?}", source, target);
+		db_migration::migrate_rocksdb_to_parity_db::<B>(&source, &target, db_type, &mut |progress| {
+			info!(
+				"Column {}/{}: {} keys migrated",
+				progress.column + 1,
+				progress.num_columns,
+				progress.keys,
+			);
+		})?;
+
+		info!("Verifying {:?}", target);
+		let verification = db_migration::verify_rocksdb_to_parity_db::<B>(
+			&source,
+			&target,
+			db_type,
+			self.verify_sample_rate,
+		)?;
+		let mut failed = false;
+		for column in verification.iter() {
+			println!(
+				"Column {}: {} keys, {} found, {} sampled, {} mismatched",
+				column.column,
+				column.source_keys,
+				column.target_keys,
+				column.sampled,
+				column.mismatched,
+			);
+			failed |= !column.is_ok();
+		}
+		if failed {
+			return Err(sp_blockchain::Error::Backend(
+				"Migrated database does not match the source".into(),
+			).into());
+		}
+
+		println!("{:?} migrated to {:?}.", source, target);
+		Ok(())
+	}
+}
+
+impl CliConfiguration for MigrateDbCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn role(&self, _is_dev: bool) -> error::Result<Role> {
+		Ok(if self.light { Role::Light } else { Role::Full })
+	}
+
+	fn database(&self) -> error::Result<Option<Database>> {
+		Ok(Some(self.from))
+	}
+}
+
+/// The `db snapshot` command used to take a point-in-time copy of the database.
+///
+/// A RocksDB database can be copied while the node is running, a ParityDB database
+/// requires the node to be stopped.
+#[derive(Debug, StructOpt)]
+pub struct SnapshotDbCmd {
+	/// Directory to write the snapshot to. It must not exist or be empty.
+	#[structopt(value_name = "PATH", parse(from_os_str))]
+	pub path: PathBuf,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl SnapshotDbCmd {
+	/// Run the snapshot command
+	pub fn run<B: BlockT>(&self, database_config: DatabaseConfig) -> error::Result<()> {
+		let manifest = db_snapshot::create_snapshot::<B>(&database_config, &self.path)?;
+		println!("Snapshot written to {:?}.", self.path);
+		println!("Genesis: {}", manifest.genesis_hash);
+		println!("Best: #{} ({})", manifest.best_number, manifest.best_hash);
+		println!("Finalized: #{} ({}), state root {}", manifest.finalized_number, manifest.finalized_hash, manifest.state_root);
+		Ok(())
+	}
+}
+
+impl CliConfiguration for SnapshotDbCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+/// The `db check-state` command used to audit the state stored in the database.
+///
+/// The node must be stopped. The pruning mode has to match the one the database was
+/// written with.
+#[derive(Debug, StructOpt)]
+pub struct CheckStateDbCmd {
+	/// Remove the state nodes that no retained state refers to.
+	///
+	/// Only supported for pruned RocksDB databases.
+	#[structopt(long)]
+	pub collect: bool,
+
+	/// File to write the JSON report to. Defaults to stdout.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub report: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+}
+
+impl CheckStateDbCmd {
+	/// Run the check-state command
+	pub fn run<B: BlockT>(&self, config: Configuration) -> error::Result<()> {
+		info!("Checking the state of {}", config.database);
+		let report = db_check::check_state::<B>(&config.database, &config.pruning, self.collect)?;
+		let nodes = |nodes: &Vec<db_check::ReferencedNode<B::Hash, _>>| nodes.iter()
+			.map(|node| serde_json::json!({
+				"block": node.block.to_string(),
+				"number": node.number.to_string(),
+				"key": to_hex(&node.key, false),
+			}))
+			.collect::<Vec<_>>();
+		let json = serde_json::json!({
+			"blocks": report.blocks.iter()
+				.map(|(number, hash)| serde_json::json!({
+					"number": number.to_string(),
+					"hash": hash.to_string(),
+				}))
+				.collect::<Vec<_>>(),
+			"nodes": report.nodes,
+			"missing": nodes(&report.missing),
+			"corrupted": nodes(&report.corrupted),
+			"unreferenced": report.unreferenced.as_ref()
+				.map(|keys| keys.iter().map(|key| to_hex(key, false)).collect::<Vec<_>>()),
+			"collected": report.collected,
+		});
+		let json = serde_json::to_string_pretty(&json)
+			.map_err(|e| error::Error::Input(format!("Error encoding report: {}", e)))?;
+		match &self.report {
+			Some(path) => fs::write(path, json)?,
+			None => println!("{}", json),
+		}
+
+		info!(
+			"Checked {} states with {} nodes: {} missing, {} corrupted, {} unreferenced",
+			report.blocks.len(),
+			report.nodes,
+			report.missing.len(),
+			report.corrupted.len(),
+			report.unreferenced.as_ref().map_or("unknown".into(), |keys| keys.len().to_string()),
+		);
+		if !report.is_ok() {
+			return Err(sp_blockchain::Error::Backend("Retained states are incomplete".into()).into());
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for CheckStateDbCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+}
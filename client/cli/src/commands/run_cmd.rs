@@ -32,6 +32,7 @@ use sc_service::{
 };
 use sc_telemetry::TelemetryEndpoints;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 /// The `run` command used to run a node.
@@ -265,6 +266,13 @@ pub struct RunCmd {
 	/// which includes: database, node key and keystore.
 	#[structopt(long, conflicts_with = "base-path")]
 	pub tmp: bool,
+
+	/// Restore the database from a snapshot taken with `db snapshot` before starting.
+	///
+	/// The snapshot must belong to the same chain. An existing database is kept if it already
+	/// contains the snapshot, so the flag may be left in place across restarts.
+	#[structopt(long, value_name = "PATH", parse(from_os_str), conflicts_with = "light")]
+	pub restore_snapshot: Option<PathBuf>,
 }
 
 impl RunCmd {
@@ -476,6 +484,10 @@ impl CliConfiguration for RunCmd {
 			self.shared_params().base_path()
 		})
 	}
+
+	fn restore_snapshot(&self) -> Result<Option<PathBuf>> {
+		Ok(self.restore_snapshot.clone())
+	}
 }
 
 /// Check whether a node name is considered as valid.
@@ -17,6 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::arg_enums::Database;
+use sc_service::TransactionStorageMode;
 use structopt::StructOpt;
 
 /// Parameters for block import.
@@ -34,6 +35,15 @@ pub struct DatabaseParams {
 	/// Limit the memory the database cache can use.
 	#[structopt(long = "db-cache", value_name = "MiB")]
 	pub database_cache_size: Option<usize>,
+
+	/// Enable storage chain mode
+	///
+	/// This changes the storage format for blocks bodies.
+	/// If this is enabled, each transaction is stored separately in the
+	/// transaction database column and is only referenced by hash
+	/// in the block body column.
+	#[structopt(long)]
+	pub storage_chain: bool,
 }
 
 impl DatabaseParams {
@@ -46,4 +56,13 @@ impl DatabaseParams {
 	pub fn database_cache_size(&self) -> Option<usize> {
 		self.database_cache_size
 	}
+
+	/// Transaction storage scheme.
+	pub fn transaction_storage(&self) -> TransactionStorageMode {
+		if self.storage_chain {
+			TransactionStorageMode::StorageChain
+		} else {
+			TransactionStorageMode::BlockBody
+		}
+	}
 }
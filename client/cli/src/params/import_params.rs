@@ -73,6 +73,18 @@ pub struct ImportParams {
 		default_value = "67108864"
 	)]
 	pub state_cache_size: usize,
+
+	/// Specify the size of the trie node cache shared between blocks.
+	///
+	/// Trie nodes are cached below the state cache, so lookups that miss the state cache
+	/// (key iteration, root calculation, proofs) don't have to go to the database.
+	/// `0` disables the cache.
+	#[structopt(
+		long = "trie-cache-size",
+		value_name = "Bytes",
+		default_value = "67108864"
+	)]
+	pub trie_cache_size: usize,
 }
 
 impl ImportParams {
@@ -82,6 +94,11 @@ impl ImportParams {
 		self.state_cache_size
 	}
 
+	/// Specify the trie node cache size.
+	pub fn trie_cache_size(&self) -> usize {
+		self.trie_cache_size
+	}
+
 	/// Get the WASM execution method from the parameters
 	pub fn wasm_method(&self) -> sc_service::config::WasmExecutionMethod {
 		self.wasm_method.into()
@@ -17,7 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::error;
-use sc_service::{PruningMode, Role};
+use sc_service::{ArchiveExceptions, BlocksPruning, Constraints, PruningMode, Role};
 use structopt::StructOpt;
 
 /// Parameters to define the pruning mode
@@ -30,6 +30,25 @@ pub struct PruningParams {
 	/// 256 blocks.
 	#[structopt(long = "pruning", value_name = "PRUNING_MODE")]
 	pub pruning: Option<String>,
+	/// Keep the state of every block with a number divisible by the given value when
+	/// the state is pruned.
+	#[structopt(long = "pruning-keep-every", value_name = "BLOCKS")]
+	pub pruning_keep_every: Option<u64>,
+	/// Keep the states of the given comma separated block numbers when the state is pruned.
+	#[structopt(long = "pruning-keep-blocks", value_name = "NUMBERS", use_delimiter = true)]
+	pub pruning_keep_blocks: Vec<u64>,
+	/// Keep the states of blocks that the runtime marked as checkpoints when the state
+	/// is pruned.
+	#[structopt(long = "pruning-keep-checkpoints")]
+	pub pruning_keep_checkpoints: bool,
+	/// Specify the block body pruning mode, a number of finalized blocks to keep,
+	/// 'archive' or 'archive-canonical'.
+	///
+	/// 'archive' keeps the bodies of all blocks, 'archive-canonical' keeps the bodies of
+	/// all finalized blocks only. Headers and justifications are always kept.
+	/// Default is 'archive'.
+	#[structopt(long = "blocks-pruning", value_name = "BLOCKS_PRUNING_MODE")]
+	pub blocks_pruning: Option<String>,
 }
 
 impl PruningParams {
@@ -39,7 +58,7 @@ impl PruningParams {
 		// `ArchiveAll`), otherwise we keep state for the last 256 blocks. if the
 		// node is an authority and pruning is enabled explicitly, then we error
 		// unless `unsafe_pruning` is set.
-		Ok(match &self.pruning {
+		let mode = match &self.pruning {
 			Some(ref s) if s == "archive" => PruningMode::ArchiveAll,
 			None if role.is_network_authority() => PruningMode::ArchiveAll,
 			None => PruningMode::default(),
@@ -56,6 +75,33 @@ impl PruningParams {
 					error::Error::Input("Invalid pruning mode specified".to_string())
 				})?)
 			}
+		};
+
+		let keep = ArchiveExceptions {
+			every: self.pruning_keep_every,
+			blocks: self.pruning_keep_blocks.iter().cloned().collect(),
+			checkpoints: self.pruning_keep_checkpoints,
+		};
+		Ok(match mode {
+			PruningMode::Constrained(constraints) => PruningMode::Constrained(Constraints {
+				keep,
+				..constraints
+			}),
+			mode if keep.is_empty() => mode,
+			_ => return Err(error::Error::Input(
+				"Archive exceptions can only be specified when the state is pruned".to_string(),
+			)),
+		})
+	}
+
+	/// Get the block body pruning value from the parameters
+	pub fn keep_blocks(&self) -> error::Result<BlocksPruning> {
+		Ok(match self.blocks_pruning.as_ref().map(|s| s.as_str()) {
+			None | Some("archive") => BlocksPruning::KeepAll,
+			Some("archive-canonical") => BlocksPruning::KeepFinalized,
+			Some(s) => BlocksPruning::Some(s.parse().map_err(|_| {
+				error::Error::Input("Invalid blocks pruning mode specified".to_string())
+			})?),
 		})
 	}
 }
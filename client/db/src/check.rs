@@ -0,0 +1,490 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Offline consistency check of the state stored in a full node database.
+//!
+//! The tries of all retained states are walked from their roots. These are the states of the
+//! non-canonical blocks, of the canonical blocks in the pruning window and of the blocks kept
+//! as archive exceptions. In archive mode the states of all canonical blocks are walked.
+//! Subtries that were already visited from another root are skipped.
+//!
+//! Nodes that a retained state refers to but that are not in the database are reported as
+//! missing. For a pruned RocksDB database the state column is listed afterwards and the nodes
+//! that no retained state refers to are reported as unreferenced and may be removed. ParityDB
+//! counts references itself and can not list its nodes. An `ArchiveAll` database keeps the
+//! states of abandoned forks, which can not be told apart from leaked nodes.
+
+use std::collections::HashSet;
+use std::io;
+
+use codec::Decode;
+use sp_blockchain::{Error as ClientError, Result as ClientResult};
+use sp_core::storage::{ChildInfo, well_known_keys};
+use sp_database::Database;
+use sp_runtime::generic::BlockId;
+use sp_runtime::traits::{
+	Block as BlockT, Hash as HashT, HashFor, Header as HeaderT, NumberFor, SaturatedConversion,
+};
+use sp_trie::prefixed_key;
+use trie_db::{NibbleVec, NodeCodec as _, node::{Node, NodeHandle}};
+use sc_state_db::StateDb;
+use crate::utils::{self, DatabaseType};
+use crate::{
+	columns, BlocksPruning, DatabaseSettings, DatabaseSettingsSrc, DbHash, PruningMode,
+	StateMetaDb, TransactionStorageMode,
+};
+
+/// A state node that a retained state refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferencedNode<Hash, Number> {
+	/// Hash of the block whose state refers to the node.
+	pub block: Hash,
+	/// Number of that block.
+	pub number: Number,
+	/// Database key of the node.
+	pub key: Vec<u8>,
+}
+
+/// Result of checking the state of a database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateCheckReport<Hash, Number> {
+	/// Numbers and hashes of the blocks whose states were walked.
+	pub blocks: Vec<(Number, Hash)>,
+	/// Number of distinct nodes visited.
+	pub nodes: u64,
+	/// Nodes that are referenced but missing from the database.
+	pub missing: Vec<ReferencedNode<Hash, Number>>,
+	/// Nodes that are stored but can not be decoded.
+	pub corrupted: Vec<ReferencedNode<Hash, Number>>,
+	/// Database keys of stored nodes that no retained state refers to. `None` if the
+	/// database can not tell them apart.
+	pub unreferenced: Option<Vec<Vec<u8>>>,
+	/// Were the unreferenced nodes removed from the database?
+	pub collected: bool,
+}
+
+impl<Hash, Number> StateCheckReport<Hash, Number> {
+	/// Returns `true` if all retained states are complete.
+	pub fn is_ok(&self) -> bool {
+		self.missing.is_empty() && self.corrupted.is_empty()
+	}
+}
+
+/// Report type for the given block type.
+pub type ReportFor<Block> = StateCheckReport<<Block as BlockT>::Hash, NumberFor<Block>>;
+
+/// Walks all retained states of the full node database described by `source`, which was
+/// written with the `pruning` mode.
+///
+/// If `collect` is set, nodes that no retained state refers to are removed. This is only
+/// supported for pruned RocksDB databases. The node must not be running.
+pub fn check_state<Block: BlockT>(
+	source: &DatabaseSettingsSrc,
+	pruning: &PruningMode,
+	collect: bool,
+) -> ClientResult<ReportFor<Block>> {
+	let lists_unreferenced = match source {
+		DatabaseSettingsSrc::RocksDb { .. } => *pruning != PruningMode::ArchiveAll,
+		_ => false,
+	};
+	if collect && !lists_unreferenced {
+		return Err(ClientError::Backend(
+			"Unreferenced nodes can only be collected from a pruned RocksDB database".into(),
+		));
+	}
+
+	let settings = DatabaseSettings {
+		state_cache_size: 0,
+		state_cache_child_ratio: None,
+		trie_cache_size: 0,
+		pruning: pruning.clone(),
+		keep_blocks: BlocksPruning::KeepAll,
+		transaction_storage: TransactionStorageMode::BlockBody,
+		source: source.clone(),
+	};
+	let db = utils::open_database::<Block>(&settings, DatabaseType::Full)?;
+	let state_db: StateDb<Block::Hash, Vec<u8>> = StateDb::new(
+		pruning.clone(),
+		!source.supports_ref_counting(),
+		&StateMetaDb(&*db),
+	).map_err(|e: sc_state_db::Error<io::Error>| ClientError::from_state_db(e))?;
+
+	let blocks = retained_blocks::<Block>(&*db, &state_db, pruning)?;
+	let mut walker = Walker::<Block> {
+		db: &*db,
+		state_db: &state_db,
+		prefix_keys: !source.supports_ref_counting(),
+		visited: HashSet::new(),
+		missing: Vec::new(),
+		corrupted: Vec::new(),
+	};
+	for (number, hash) in blocks.iter() {
+		let header = utils::require_header::<Block>(
+			&*db,
+			columns::KEY_LOOKUP,
+			columns::HEADER,
+			BlockId::Hash(*hash),
+		)?;
+		walker.walk_state(*hash, *number, *header.state_root())?;
+	}
+	let Walker { visited, missing, corrupted, .. } = walker;
+	drop(state_db);
+	drop(db);
+
+	let unreferenced = match source {
+		DatabaseSettingsSrc::RocksDb { path, .. } if lists_unreferenced =>
+			Some(unreferenced_rocksdb(path, &visited, collect)?),
+		_ => None,
+	};
+
+	Ok(StateCheckReport {
+		blocks,
+		nodes: visited.len() as u64,
+		missing,
+		corrupted,
+		unreferenced,
+		collected: collect,
+	})
+}
+
+/// Numbers and hashes of the blocks whose state is retained.
+fn retained_blocks<Block: BlockT>(
+	db: &dyn Database<DbHash>,
+	state_db: &StateDb<Block::Hash, Vec<u8>>,
+	pruning: &PruningMode,
+) -> ClientResult<Vec<(NumberFor<Block>, Block::Hash)>> {
+	let meta = utils::read_meta::<Block>(db, columns::HEADER)?;
+	if meta.genesis_hash == Default::default() {
+		return Ok(Vec::new())
+	}
+
+	let (first, last) = match (pruning, state_db.best_canonical()) {
+		// Nothing is canonicalized, every imported block is retained.
+		(PruningMode::ArchiveAll, _) => (0, meta.best_number.saturated_into::<u64>()),
+		// The block before the window has not been pruned yet.
+		(PruningMode::Constrained(_), Some(last)) =>
+			(state_db.pruning_window_start().unwrap_or(0).saturating_sub(1), last),
+		(PruningMode::ArchiveCanonical, Some(last)) => (0, last),
+		// Nothing is canonicalized yet.
+		(_, None) => (1, 0),
+	};
+
+	let mut blocks = Vec::new();
+	for number in first..=last {
+		let number = number.saturated_into::<NumberFor<Block>>();
+		let header = utils::require_header::<Block>(
+			db,
+			columns::KEY_LOOKUP,
+			columns::HEADER,
+			BlockId::Number(number),
+		)?;
+		blocks.push((number, header.hash()));
+	}
+	for (hash, number) in state_db.kept_states().into_iter().chain(state_db.non_canonical_blocks()) {
+		blocks.push((number.saturated_into(), hash));
+	}
+	Ok(blocks)
+}
+
+/// A node that is still to be visited.
+enum Pending<Hash> {
+	/// Node stored in the database under its hash.
+	Hash(Hash),
+	/// Node inlined in its parent.
+	Inline(Vec<u8>),
+}
+
+/// Walks state tries and records the visited, missing and corrupted nodes.
+struct Walker<'a, Block: BlockT> {
+	db: &'a dyn Database<DbHash>,
+	state_db: &'a StateDb<Block::Hash, Vec<u8>>,
+	prefix_keys: bool,
+	/// Database keys of all visited nodes.
+	visited: HashSet<Vec<u8>>,
+	missing: Vec<ReferencedNode<Block::Hash, NumberFor<Block>>>,
+	corrupted: Vec<ReferencedNode<Block::Hash, NumberFor<Block>>>,
+}
+
+impl<'a, Block: BlockT> Walker<'a, Block> {
+	/// Walks the top trie with the given root and all default child tries it refers to.
+	fn walk_state(
+		&mut self,
+		block: Block::Hash,
+		number: NumberFor<Block>,
+		root: Block::Hash,
+	) -> ClientResult<()> {
+		for (storage_key, child_root) in self.walk_trie(block, number, root, &[])? {
+			let child_info = ChildInfo::new_default(&storage_key);
+			self.walk_trie(block, number, child_root, child_info.keyspace())?;
+		}
+		Ok(())
+	}
+
+	/// Walks the trie with the given root. Returns the storage keys and roots of the default
+	/// child tries found in the top trie.
+	fn walk_trie(
+		&mut self,
+		block: Block::Hash,
+		number: NumberFor<Block>,
+		root: Block::Hash,
+		keyspace: &[u8],
+	) -> ClientResult<Vec<(Vec<u8>, Block::Hash)>> {
+		let mut child_roots = Vec::new();
+		if root == <HashFor<Block> as HashT>::trie_root(Vec::new()) {
+			return Ok(child_roots)
+		}
+
+		let mut stack = vec![(Pending::Hash(root), NibbleVec::new())];
+		while let Some((pending, path)) = stack.pop() {
+			let (key, data) = match pending {
+				Pending::Inline(data) => (None, data),
+				Pending::Hash(hash) => {
+					let prefix = path.as_prefix();
+					let key = if self.prefix_keys {
+						let mut keyspaced = keyspace.to_vec();
+						keyspaced.extend_from_slice(prefix.0);
+						prefixed_key::<HashFor<Block>>(&hash, (&keyspaced, prefix.1))
+					} else {
+						hash.as_ref().to_vec()
+					};
+					if !self.visited.insert(key.clone()) {
+						continue
+					}
+					match self.state_db.get(&key, &StateNodeDb(self.db))
+						.map_err(|e: sc_state_db::Error<io::Error>| ClientError::from_state_db(e))?
+					{
+						Some(data) => (Some(key), data),
+						None => {
+							self.missing.push(ReferencedNode { block, number, key });
+							continue
+						},
+					}
+				},
+			};
+
+			let node = match sp_trie::NodeCodec::<HashFor<Block>>::decode(&data) {
+				Ok(node) => node,
+				Err(_) => {
+					let key = key.unwrap_or_else(|| data.clone());
+					self.corrupted.push(ReferencedNode { block, number, key });
+					continue
+				},
+			};
+			let (partial, children, value) = match node {
+				Node::Empty => continue,
+				Node::Leaf(partial, value) => (Some(partial), None, Some(value)),
+				Node::Extension(partial, child) => {
+					let mut path = path.clone();
+					path.append_partial(partial.right());
+					stack.push((pending_child::<Block>(child)?, path));
+					continue
+				},
+				Node::Branch(children, value) => (None, Some(children), value),
+				Node::NibbledBranch(partial, children, value) => (Some(partial), Some(children), value),
+			};
+
+			let mut path = path;
+			if let Some(partial) = partial {
+				path.append_partial(partial.right());
+			}
+			// Child trie roots are stored in the top trie under prefixed storage keys.
+			match value {
+				Some(value) if keyspace.is_empty() && path.len() % 2 == 0 &&
+					path.inner().starts_with(well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX) =>
+				{
+					let child_root = Decode::decode(&mut &value[..]).map_err(|e: codec::Error| {
+						ClientError::Backend(format!("Invalid child trie root: {}", e.what()))
+					})?;
+					let storage_key = path.inner()[well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX.len()..].to_vec();
+					child_roots.push((storage_key, child_root));
+				},
+				_ => {},
+			}
+			for (index, child) in children.iter().flat_map(|c| c.iter()).enumerate() {
+				if let Some(child) = child {
+					let mut path = path.clone();
+					path.push(index as u8);
+					stack.push((pending_child::<Block>(child.clone())?, path));
+				}
+			}
+		}
+		Ok(child_roots)
+	}
+}
+
+fn pending_child<Block: BlockT>(handle: NodeHandle) -> ClientResult<Pending<Block::Hash>> {
+	Ok(match handle {
+		NodeHandle::Hash(hash) => Pending::Hash(Decode::decode(&mut &hash[..]).map_err(|e: codec::Error| {
+			ClientError::Backend(format!("Invalid trie node hash: {}", e.what()))
+		})?),
+		NodeHandle::Inline(data) => Pending::Inline(data.to_vec()),
+	})
+}
+
+/// Read access to the state column for the state-db.
+struct StateNodeDb<'a>(&'a dyn Database<DbHash>);
+
+impl<'a> sc_state_db::NodeDb for StateNodeDb<'a> {
+	type Error = io::Error;
+	type Key = [u8];
+
+	fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+		Ok(self.0.get(columns::STATE, key))
+	}
+}
+
+/// Lists the keys of the state column of the RocksDB database at `path` that are not in
+/// `visited` and removes them if `collect` is set.
+#[cfg(any(feature = "with-kvdb-rocksdb", test))]
+fn unreferenced_rocksdb(
+	path: &std::path::Path,
+	visited: &HashSet<Vec<u8>>,
+	collect: bool,
+) -> ClientResult<Vec<Vec<u8>>> {
+	use kvdb::KeyValueDB;
+
+	let path = path.to_str().ok_or_else(|| ClientError::Backend("Invalid database path".into()))?;
+	let config = kvdb_rocksdb::DatabaseConfig::with_columns(utils::NUM_COLUMNS);
+	let db = kvdb_rocksdb::Database::open(&config, path)
+		.map_err(|err| ClientError::Backend(format!("{}", err)))?;
+	let unreferenced: Vec<_> = db.iter(columns::STATE)
+		.map(|(key, _)| key.into_vec())
+		.filter(|key| !visited.contains(key))
+		.collect();
+	if collect {
+		let mut transaction = db.transaction();
+		for key in unreferenced.iter() {
+			transaction.delete(columns::STATE, key);
+		}
+		db.write(transaction).map_err(|err| ClientError::Backend(format!("{}", err)))?;
+	}
+	Ok(unreferenced)
+}
+
+#[cfg(not(any(feature = "with-kvdb-rocksdb", test)))]
+fn unreferenced_rocksdb(
+	_path: &std::path::Path,
+	_visited: &HashSet<Vec<u8>>,
+	_collect: bool,
+) -> ClientResult<Vec<Vec<u8>>> {
+	Err(ClientError::Backend("`with-kvdb-rocksdb` feature not enabled, database can not be opened".into()))
+}
+
+#[cfg(test)]
+mod tests {
+	use kvdb::KeyValueDB;
+	use sc_client_api::backend::{Backend as _, BlockImportOperation as _, NewBlockState};
+	use sp_core::H256;
+	use sp_runtime::Storage;
+	use sp_runtime::testing::Header;
+	use sp_state_machine::Backend as _;
+	use crate::Backend;
+	use crate::tests::Block;
+	use super::*;
+
+	fn source(path: &std::path::Path) -> DatabaseSettingsSrc {
+		DatabaseSettingsSrc::RocksDb { path: path.to_owned(), cache_size: 16 }
+	}
+
+	fn create_db(path: &std::path::Path) {
+		let settings = DatabaseSettings {
+			state_cache_size: 0,
+			state_cache_child_ratio: None,
+			trie_cache_size: 0,
+			pruning: PruningMode::keep_blocks(2),
+			keep_blocks: BlocksPruning::KeepAll,
+			transaction_storage: TransactionStorageMode::BlockBody,
+			source: source(path),
+		};
+		let backend = Backend::<Block>::new(settings, 0).unwrap();
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, BlockId::Hash(Default::default())).unwrap();
+		let storage: Vec<_> = (0u8..32).map(|i| (vec![i, 1, 2], vec![i; 40])).collect();
+		let state_root = op.old_state.storage_root(storage.iter().map(|(k, v)| (&k[..], Some(&v[..])))).0;
+		let header = Header {
+			number: 0,
+			parent_hash: Default::default(),
+			state_root,
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		};
+		op.reset_storage(Storage {
+			top: storage.into_iter().collect(),
+			children_default: Default::default(),
+		}).unwrap();
+		op.set_block_data(header, Some(vec![]), None, NewBlockState::Final).unwrap();
+		backend.commit_operation(op).unwrap();
+	}
+
+	fn open_rocksdb(path: &std::path::Path) -> kvdb_rocksdb::Database {
+		let config = kvdb_rocksdb::DatabaseConfig::with_columns(utils::NUM_COLUMNS);
+		kvdb_rocksdb::Database::open(&config, path.to_str().unwrap()).unwrap()
+	}
+
+	#[test]
+	fn finds_and_collects_unreferenced_nodes() {
+		let dir = tempfile::TempDir::new().unwrap();
+		create_db(dir.path());
+		let pruning = PruningMode::keep_blocks(2);
+
+		let report = check_state::<Block>(&source(dir.path()), &pruning, false).unwrap();
+		assert!(report.is_ok());
+		assert_eq!(report.blocks.len(), 1);
+		assert!(report.nodes > 1);
+		assert_eq!(report.unreferenced, Some(Vec::new()));
+
+		let leaked = H256::random().as_ref().to_vec();
+		{
+			let db = open_rocksdb(dir.path());
+			let mut transaction = db.transaction();
+			transaction.put(columns::STATE, &leaked, &[1, 2, 3]);
+			db.write(transaction).unwrap();
+		}
+		let report = check_state::<Block>(&source(dir.path()), &pruning, true).unwrap();
+		assert_eq!(report.unreferenced, Some(vec![leaked]));
+		assert!(report.collected);
+
+		let report = check_state::<Block>(&source(dir.path()), &pruning, false).unwrap();
+		assert_eq!(report.unreferenced, Some(Vec::new()));
+	}
+
+	#[test]
+	fn reports_missing_nodes() {
+		let dir = tempfile::TempDir::new().unwrap();
+		create_db(dir.path());
+		{
+			let db = open_rocksdb(dir.path());
+			let mut transaction = db.transaction();
+			for (key, _) in db.iter(columns::STATE) {
+				transaction.delete(columns::STATE, &key);
+			}
+			db.write(transaction).unwrap();
+		}
+
+		let report = check_state::<Block>(&source(dir.path()), &PruningMode::keep_blocks(2), false).unwrap();
+		assert!(!report.is_ok());
+		assert_eq!(report.missing.len(), 1);
+		assert_eq!(report.missing[0].number, 0);
+	}
+
+	#[test]
+	fn refuses_to_collect_from_archive() {
+		let dir = tempfile::TempDir::new().unwrap();
+		assert!(check_state::<Block>(&source(dir.path()), &PruningMode::ArchiveAll, true).is_err());
+	}
+}
@@ -30,6 +30,9 @@
 
 pub mod light;
 pub mod offchain;
+pub mod migration;
+pub mod snapshot;
+pub mod check;
 
 #[cfg(any(feature = "with-kvdb-rocksdb", test))]
 pub mod bench;
@@ -38,7 +41,7 @@ mod children;
 mod cache;
 mod changes_tries_storage;
 mod storage_cache;
-#[cfg(any(feature = "with-kvdb-rocksdb", test))]
+#[cfg(any(feature = "with-kvdb-rocksdb", feature = "with-parity-db", test))]
 mod upgrade;
 mod utils;
 mod stats;
@@ -55,7 +58,7 @@ use log::{trace, debug, warn};
 
 use sc_client_api::{
 	UsageInfo, MemoryInfo, IoInfo, MemorySize,
-	backend::{NewBlockState, PrunableStateChangesTrieStorage, ProvideChtRoots},
+	backend::{NewBlockState, PrunableStateChangesTrieStorage, ProvideChtRoots, AvailableStates},
 	leaves::{LeafSet, FinalizationDisplaced}, cht,
 };
 use sp_blockchain::{
@@ -70,16 +73,17 @@ use sp_core::ChangesTrieConfiguration;
 use sp_core::offchain::storage::{OffchainOverlayedChange, OffchainOverlayedChanges};
 use sp_core::storage::{well_known_keys, ChildInfo};
 use sp_arithmetic::traits::Saturating;
-use sp_runtime::{generic::{DigestItem, BlockId}, Justification, Storage};
+use sp_runtime::{generic::{DigestItem, BlockId}, Justification, Justifications, Storage};
 use sp_runtime::traits::{
 	Block as BlockT, Header as HeaderT, NumberFor, Zero, One, SaturatedConversion, HashFor,
 };
 use sp_state_machine::{
 	DBValue, ChangesTrieTransaction, ChangesTrieCacheAction, UsageInfo as StateUsageInfo,
 	StorageCollection, ChildStorageCollection,
-	backend::Backend as StateBackend, StateMachineStats,
+	backend::Backend as StateBackend, StateMachineStats, IndexOperation,
+	SharedTrieNodeCache, new_shared_trie_node_cache,
 };
-use crate::utils::{DatabaseType, Meta, meta_keys, read_db, read_meta};
+use crate::utils::{Meta, meta_keys, read_db, read_meta};
 use crate::changes_tries_storage::{DbChangesTrieStorage, DbChangesTrieStorageTransaction};
 use sc_state_db::StateDb;
 use sp_blockchain::{CachedHeaderMetadata, HeaderMetadata, HeaderMetadataCache};
@@ -88,7 +92,8 @@ use crate::stats::StateUsageStats;
 
 // Re-export the Database trait so that one can pass an implementation of it.
 pub use sp_database::Database;
-pub use sc_state_db::PruningMode;
+pub use sc_state_db::{PruningMode, Constraints, ArchiveExceptions};
+pub use utils::DatabaseType;
 
 #[cfg(any(feature = "with-kvdb-rocksdb", test))]
 pub use bench::BenchmarkingState;
@@ -264,12 +269,66 @@ pub struct DatabaseSettings {
 	pub state_cache_size: usize,
 	/// Ratio of cache size dedicated to child tries.
 	pub state_cache_child_ratio: Option<(usize, usize)>,
-	/// Pruning mode.
+	/// Size of the trie node cache shared between blocks. `0` disables it.
+	pub trie_cache_size: usize,
+	/// State pruning mode.
 	pub pruning: PruningMode,
+	/// Block body pruning mode.
+	pub keep_blocks: BlocksPruning,
+	/// Block body/Transaction storage scheme.
+	pub transaction_storage: TransactionStorageMode,
 	/// Where to find the database.
 	pub source: DatabaseSettingsSrc,
 }
 
+/// Block body and transaction storage scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionStorageMode {
+	/// Store block body as an encoded list of full transactions in the BODY column.
+	BlockBody,
+	/// Store the data indexed by the runtime in a separate, reference counted TRANSACTION
+	/// column, keyed by content hash. Block bodies only keep references to that data.
+	StorageChain,
+}
+
+impl Default for TransactionStorageMode {
+	fn default() -> Self {
+		TransactionStorageMode::BlockBody
+	}
+}
+
+/// Block body entry as stored in the BODY column in `StorageChain` mode.
+#[derive(Debug, Encode, Decode)]
+struct DbExtrinsic {
+	/// The encoded extrinsic, without the indexed data.
+	data: Vec<u8>,
+	/// Content hash of the indexed data that completes `data`, if any.
+	indexed: Option<DbHash>,
+	/// Content hashes of the indexed data renewed by this extrinsic.
+	renewed: Vec<DbHash>,
+}
+
+/// Block body pruning mode.
+///
+/// Headers are always kept. Justifications are kept alongside them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlocksPruning {
+	/// Keep the bodies of all blocks that were ever imported.
+	KeepAll,
+	/// Keep the bodies of all finalized blocks, discarding the bodies of
+	/// non-canonical blocks once they are displaced by finality.
+	KeepFinalized,
+	/// Keep the bodies of the last `n` finalized blocks, discarding the bodies of
+	/// non-canonical blocks once they are displaced by finality.
+	Some(u32),
+}
+
+impl Default for BlocksPruning {
+	fn default() -> Self {
+		BlocksPruning::KeepAll
+	}
+}
+
 /// Where to find the database..
 #[derive(Debug, Clone)]
 pub enum DatabaseSettingsSrc {
@@ -328,17 +387,19 @@ pub(crate) mod columns {
 	pub const KEY_LOOKUP: u32 = 3;
 	pub const HEADER: u32 = 4;
 	pub const BODY: u32 = 5;
-	pub const JUSTIFICATION: u32 = 6;
+	pub const JUSTIFICATIONS: u32 = 6;
 	pub const CHANGES_TRIE: u32 = 7;
 	pub const AUX: u32 = 8;
 	/// Offchain workers local storage
 	pub const OFFCHAIN: u32 = 9;
 	pub const CACHE: u32 = 10;
+	/// Transactions
+	pub const TRANSACTION: u32 = 11;
 }
 
 struct PendingBlock<Block: BlockT> {
 	header: Block::Header,
-	justification: Option<Justification>,
+	justifications: Option<Justifications>,
 	body: Option<Vec<Block::Extrinsic>>,
 	leaf_state: NewBlockState,
 }
@@ -372,10 +433,16 @@ pub struct BlockchainDb<Block: BlockT> {
 	leaves: RwLock<LeafSet<Block::Hash, NumberFor<Block>>>,
 	header_metadata_cache: Arc<HeaderMetadataCache<Block>>,
 	header_cache: Mutex<LinkedHashMap<Block::Hash, Option<Block::Header>>>,
+	keep_blocks: BlocksPruning,
+	transaction_storage: TransactionStorageMode,
 }
 
 impl<Block: BlockT> BlockchainDb<Block> {
-	fn new(db: Arc<dyn Database<DbHash>>) -> ClientResult<Self> {
+	fn new(
+		db: Arc<dyn Database<DbHash>>,
+		keep_blocks: BlocksPruning,
+		transaction_storage: TransactionStorageMode,
+	) -> ClientResult<Self> {
 		let meta = read_meta::<Block>(&*db, columns::HEADER)?;
 		let leaves = LeafSet::read_from_db(&*db, columns::META, meta_keys::LEAF_PREFIX)?;
 		Ok(BlockchainDb {
@@ -384,6 +451,8 @@ impl<Block: BlockT> BlockchainDb<Block> {
 			meta: Arc::new(RwLock::new(meta)),
 			header_metadata_cache: Arc::new(HeaderMetadataCache::default()),
 			header_cache: Default::default(),
+			keep_blocks,
+			transaction_storage,
 		})
 	}
 
@@ -418,6 +487,25 @@ impl<Block: BlockT> BlockchainDb<Block> {
 				header.digest().log(DigestItem::as_changes_trie_root)
 					.cloned()))
 	}
+
+	// Returns `true` if the body of the block with the given header was removed by block
+	// pruning, assuming that it isn't in the database.
+	fn is_body_pruned(&self, header: &Block::Header) -> ClientResult<bool> {
+		let number = *header.number();
+		let finalized_number = self.meta.read().finalized_number;
+		if number > finalized_number {
+			return Ok(false);
+		}
+
+		match self.keep_blocks {
+			BlocksPruning::KeepAll => Ok(false),
+			BlocksPruning::Some(keep_blocks)
+				if number.saturating_add(keep_blocks.max(1).into()) <= finalized_number => Ok(true),
+			// Within the window only the bodies of non-canonical blocks are pruned.
+			BlocksPruning::KeepFinalized | BlocksPruning::Some(_) =>
+				Ok(self.hash(number)? != Some(header.hash())),
+		}
+	}
 }
 
 impl<Block: BlockT> sc_client_api::blockchain::HeaderBackend<Block> for BlockchainDb<Block> {
@@ -476,22 +564,51 @@ impl<Block: BlockT> sc_client_api::blockchain::HeaderBackend<Block> for Blockcha
 impl<Block: BlockT> sc_client_api::blockchain::Backend<Block> for BlockchainDb<Block> {
 	fn body(&self, id: BlockId<Block>) -> ClientResult<Option<Vec<Block::Extrinsic>>> {
 		match read_db(&*self.db, columns::KEY_LOOKUP, columns::BODY, id)? {
-			Some(body) => match Decode::decode(&mut &body[..]) {
-				Ok(body) => Ok(Some(body)),
-				Err(err) => return Err(sp_blockchain::Error::Backend(
-					format!("Error decoding body: {}", err)
-				)),
-			}
-			None => Ok(None),
+			Some(body) => match self.transaction_storage {
+				TransactionStorageMode::BlockBody => match Decode::decode(&mut &body[..]) {
+					Ok(body) => Ok(Some(body)),
+					Err(err) => return Err(sp_blockchain::Error::Backend(
+						format!("Error decoding body: {}", err)
+					)),
+				},
+				TransactionStorageMode::StorageChain => {
+					let index: Vec<DbExtrinsic> = Decode::decode(&mut &body[..])
+						.map_err(|err| sp_blockchain::Error::Backend(
+							format!("Error decoding body list: {}", err)
+						))?;
+					let mut body = Vec::with_capacity(index.len());
+					for DbExtrinsic { mut data, indexed, .. } in index {
+						if let Some(hash) = indexed {
+							match self.db.get(columns::TRANSACTION, hash.as_ref()) {
+								Some(t) => data.extend(t),
+								None => return Err(sp_blockchain::Error::Backend(
+									format!("Missing indexed transaction {:?}", hash)
+								)),
+							}
+						}
+						let extrinsic = Decode::decode(&mut &data[..])
+							.map_err(|err| sp_blockchain::Error::Backend(
+								format!("Error decoding extrinsic: {}", err)
+							))?;
+						body.push(extrinsic);
+					}
+					Ok(Some(body))
+				},
+			},
+			None => match self.header(id)? {
+				Some(header) if self.is_body_pruned(&header)? =>
+					Err(sp_blockchain::Error::BodyPruned(format!("{}", id))),
+				_ => Ok(None),
+			},
 		}
 	}
 
-	fn justification(&self, id: BlockId<Block>) -> ClientResult<Option<Justification>> {
-		match read_db(&*self.db, columns::KEY_LOOKUP, columns::JUSTIFICATION, id)? {
-			Some(justification) => match Decode::decode(&mut &justification[..]) {
-				Ok(justification) => Ok(Some(justification)),
+	fn justifications(&self, id: BlockId<Block>) -> ClientResult<Option<Justifications>> {
+		match read_db(&*self.db, columns::KEY_LOOKUP, columns::JUSTIFICATIONS, id)? {
+			Some(justifications) => match Decode::decode(&mut &justifications[..]) {
+				Ok(justifications) => Ok(Some(justifications)),
 				Err(err) => return Err(sp_blockchain::Error::Backend(
-					format!("Error decoding justification: {}", err)
+					format!("Error decoding justifications: {}", err)
 				)),
 			}
 			None => Ok(None),
@@ -513,6 +630,41 @@ impl<Block: BlockT> sc_client_api::blockchain::Backend<Block> for BlockchainDb<B
 	fn children(&self, parent_hash: Block::Hash) -> ClientResult<Vec<Block::Hash>> {
 		children::read_children(&*self.db, columns::META, meta_keys::CHILDREN_PREFIX, parent_hash)
 	}
+
+	fn indexed_transaction(&self, hash: &Block::Hash) -> ClientResult<Option<Vec<u8>>> {
+		Ok(self.db.get(columns::TRANSACTION, hash.as_ref()))
+	}
+
+	fn has_indexed_transaction(&self, hash: &Block::Hash) -> ClientResult<bool> {
+		Ok(self.db.contains(columns::TRANSACTION, hash.as_ref()))
+	}
+
+	fn block_indexed_body(&self, id: BlockId<Block>) -> ClientResult<Option<Vec<Vec<u8>>>> {
+		match self.transaction_storage {
+			TransactionStorageMode::BlockBody => Ok(None),
+			TransactionStorageMode::StorageChain => {
+				let body = match read_db(&*self.db, columns::KEY_LOOKUP, columns::BODY, id)? {
+					Some(body) => body,
+					None => return Ok(None),
+				};
+				let index: Vec<DbExtrinsic> = Decode::decode(&mut &body[..])
+					.map_err(|err| sp_blockchain::Error::Backend(
+						format!("Error decoding body list: {}", err)
+					))?;
+				let mut transactions = Vec::new();
+				let hashes = index.into_iter().flat_map(|ex| ex.indexed.into_iter().chain(ex.renewed));
+				for hash in hashes {
+					match self.db.get(columns::TRANSACTION, hash.as_ref()) {
+						Some(t) => transactions.push(t),
+						None => return Err(sp_blockchain::Error::Backend(
+							format!("Missing indexed transaction {:?}", hash)
+						)),
+					}
+				}
+				Ok(Some(transactions))
+			},
+		}
+	}
 }
 
 impl<Block: BlockT> sc_client_api::blockchain::ProvideCache<Block> for BlockchainDb<Block> {
@@ -613,6 +765,8 @@ pub struct BlockImportOperation<Block: BlockT> {
 	finalized_blocks: Vec<(BlockId<Block>, Option<Justification>)>,
 	set_head: Option<BlockId<Block>>,
 	commit_state: bool,
+	reset_storage: bool,
+	index_ops: Vec<IndexOperation>,
 }
 
 impl<Block: BlockT> BlockImportOperation<Block> {
@@ -651,7 +805,7 @@ impl<Block: BlockT> sc_client_api::backend::BlockImportOperation<Block> for Bloc
 		&mut self,
 		header: Block::Header,
 		body: Option<Vec<Block::Extrinsic>>,
-		justification: Option<Justification>,
+		justifications: Option<Justifications>,
 		leaf_state: NewBlockState,
 	) -> ClientResult<()> {
 		assert!(self.pending_block.is_none(), "Only one block per operation is allowed");
@@ -661,7 +815,7 @@ impl<Block: BlockT> sc_client_api::backend::BlockImportOperation<Block> for Bloc
 		self.pending_block = Some(PendingBlock {
 			header,
 			body,
-			justification,
+			justifications,
 			leaf_state,
 		});
 		Ok(())
@@ -706,6 +860,7 @@ impl<Block: BlockT> sc_client_api::backend::BlockImportOperation<Block> for Bloc
 		self.db_updates = transaction;
 		self.changes_trie_config_update = Some(changes_trie_config);
 		self.commit_state = true;
+		self.reset_storage = true;
 		Ok(root)
 	}
 
@@ -757,6 +912,11 @@ impl<Block: BlockT> sc_client_api::backend::BlockImportOperation<Block> for Bloc
 		self.set_head = Some(block);
 		Ok(())
 	}
+
+	fn update_transaction_index(&mut self, index_ops: Vec<IndexOperation>) -> ClientResult<()> {
+		self.index_ops = index_ops;
+		Ok(())
+	}
 }
 
 struct StorageDb<Block: BlockT> {
@@ -786,6 +946,11 @@ impl<Block: BlockT> sc_state_db::NodeDb for StorageDb<Block> {
 	}
 }
 
+/// Did the runtime mark the state of the block as a checkpoint?
+fn is_state_checkpoint<Header: HeaderT>(header: &Header) -> bool {
+	header.digest().logs().iter().any(|log| log.is_state_checkpoint())
+}
+
 struct DbGenesisStorage<Block: BlockT>(pub Block::Hash);
 
 impl<Block: BlockT> DbGenesisStorage<Block> {
@@ -853,6 +1018,7 @@ pub struct Backend<Block: BlockT> {
 	blockchain: BlockchainDb<Block>,
 	canonicalization_delay: u64,
 	shared_cache: SharedCache<Block>,
+	trie_cache: Option<SharedTrieNodeCache<HashFor<Block>>>,
 	import_lock: Arc<RwLock<()>>,
 	is_archive: bool,
 	io_stats: FrozenForDuration<(kvdb::IoStats, StateUsageInfo)>,
@@ -871,12 +1037,55 @@ impl<Block: BlockT> Backend<Block> {
 	/// Create new memory-backed client backend for tests.
 	#[cfg(any(test, feature = "test-helpers"))]
 	pub fn new_test(keep_blocks: u32, canonicalization_delay: u64) -> Self {
+		Self::new_test_with_blocks_pruning(keep_blocks, BlocksPruning::KeepAll, canonicalization_delay)
+	}
+
+	/// Create new memory-backed client backend for tests that prunes block bodies.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn new_test_with_blocks_pruning(
+		keep_blocks: u32,
+		blocks_pruning: BlocksPruning,
+		canonicalization_delay: u64,
+	) -> Self {
+		Self::new_test_with_settings(
+			keep_blocks,
+			blocks_pruning,
+			TransactionStorageMode::BlockBody,
+			canonicalization_delay,
+		)
+	}
+
+	/// Create new memory-backed client backend for tests with the given transaction storage mode.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn new_test_with_tx_storage(
+		keep_blocks: u32,
+		canonicalization_delay: u64,
+		transaction_storage: TransactionStorageMode,
+	) -> Self {
+		Self::new_test_with_settings(
+			keep_blocks,
+			BlocksPruning::KeepAll,
+			transaction_storage,
+			canonicalization_delay,
+		)
+	}
+
+	#[cfg(any(test, feature = "test-helpers"))]
+	fn new_test_with_settings(
+		keep_blocks: u32,
+		blocks_pruning: BlocksPruning,
+		transaction_storage: TransactionStorageMode,
+		canonicalization_delay: u64,
+	) -> Self {
 		let db = kvdb_memorydb::create(crate::utils::NUM_COLUMNS);
 		let db = sp_database::as_database(db);
 		let db_setting = DatabaseSettings {
 			state_cache_size: 16777216,
 			state_cache_child_ratio: Some((50, 100)),
+			trie_cache_size: 0,
 			pruning: PruningMode::keep_blocks(keep_blocks),
+			keep_blocks: blocks_pruning,
+			transaction_storage,
 			source: DatabaseSettingsSrc::Custom(db),
 		};
 
@@ -889,7 +1098,11 @@ impl<Block: BlockT> Backend<Block> {
 		config: &DatabaseSettings,
 	) -> ClientResult<Self> {
 		let is_archive_pruning = config.pruning.is_archive();
-		let blockchain = BlockchainDb::new(db.clone())?;
+		let blockchain = BlockchainDb::new(
+			db.clone(),
+			config.keep_blocks,
+			config.transaction_storage,
+		)?;
 		let meta = blockchain.meta.clone();
 		let map_e = |e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from_state_db(e);
 		let state_db: StateDb<_, _> = StateDb::new(
@@ -929,6 +1142,11 @@ impl<Block: BlockT> Backend<Block> {
 				config.state_cache_size,
 				config.state_cache_child_ratio.unwrap_or(DEFAULT_CHILD_RATIO),
 			),
+			trie_cache: if config.trie_cache_size > 0 {
+				Some(new_shared_trie_node_cache(config.trie_cache_size))
+			} else {
+				None
+			},
 			import_lock: Default::default(),
 			is_archive: is_archive_pruning,
 			io_stats: FrozenForDuration::new(std::time::Duration::from_secs(1)),
@@ -1045,9 +1263,9 @@ impl<Block: BlockT> Backend<Block> {
 
 		if let Some(justification) = justification {
 			transaction.set_from_vec(
-				columns::JUSTIFICATION,
+				columns::JUSTIFICATIONS,
 				&utils::number_and_hash_to_lookup_key(number, hash)?,
-				justification.encode(),
+				Justifications::from(justification).encode(),
 			);
 		}
 		Ok((*hash, number, false, true))
@@ -1058,11 +1276,11 @@ impl<Block: BlockT> Backend<Block> {
 		&self,
 		transaction: &mut Transaction<DbHash>,
 		hash: Block::Hash,
-		number: NumberFor<Block>,
+		header: &Block::Header,
 	)
 		-> ClientResult<()>
 	{
-		let number_u64 = number.saturated_into::<u64>();
+		let number_u64 = header.number().saturated_into::<u64>();
 		if number_u64 > self.canonicalization_delay {
 			let new_canonical = number_u64 - self.canonicalization_delay;
 
@@ -1070,16 +1288,22 @@ impl<Block: BlockT> Backend<Block> {
 				return Ok(())
 			}
 
-			let hash = if new_canonical == number_u64 {
-				hash
+			let (hash, checkpoint) = if new_canonical == number_u64 {
+				(hash, is_state_checkpoint(header))
 			} else {
-				::sc_client_api::blockchain::HeaderBackend::hash(&self.blockchain, new_canonical.saturated_into())?
+				let hash = ::sc_client_api::blockchain::HeaderBackend::hash(&self.blockchain, new_canonical.saturated_into())?
 					.expect("existence of block with number `new_canonical` \
-						implies existence of blocks with all numbers before it; qed")
+						implies existence of blocks with all numbers before it; qed");
+				(hash, is_state_checkpoint(&self.blockchain.expect_header(BlockId::Hash(hash))?))
 			};
 
+			// Blocks imported without state are not tracked by the state db.
+			if self.storage.state_db.is_pruned(&hash, new_canonical) {
+				return Ok(())
+			}
+
 			trace!(target: "db", "Canonicalize block #{} ({:?})", new_canonical, hash);
-			let commit = self.storage.state_db.canonicalize_block(&hash)
+			let commit = self.storage.state_db.canonicalize_block(&hash, checkpoint, &*self.storage)
 				.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from_state_db(e))?;
 			apply_state_commit(transaction, commit);
 		};
@@ -1125,8 +1349,26 @@ impl<Block: BlockT> Backend<Block> {
 			// blocks are keyed by number + hash.
 			let lookup_key = utils::number_and_hash_to_lookup_key(number, hash)?;
 
-			let (enacted, retracted) = if pending_block.leaf_state.is_best() {
+			// A block with imported state may be inserted without its ancestry. It starts a new
+			// best chain that is not connected to the blocks below it.
+			let disconnected = operation.reset_storage && !number.is_zero() &&
+				sc_client_api::blockchain::HeaderBackend::header(
+					&self.blockchain,
+					BlockId::Hash(parent_hash),
+				)?.is_none();
+
+			let (enacted, retracted) = if pending_block.leaf_state.is_best() && !disconnected {
 				self.set_head_with_transaction(&mut transaction, parent_hash, (number, hash))?
+			} else if pending_block.leaf_state.is_best() {
+				let lookup_key = utils::number_and_hash_to_lookup_key(number, hash)?;
+				transaction.set_from_vec(columns::META, meta_keys::BEST_BLOCK, lookup_key);
+				utils::insert_number_to_key_mapping(
+					&mut transaction,
+					columns::KEY_LOOKUP,
+					number,
+					hash,
+				)?;
+				(Default::default(), Default::default())
 			} else {
 				(Default::default(), Default::default())
 			};
@@ -1140,10 +1382,22 @@ impl<Block: BlockT> Backend<Block> {
 
 			transaction.set_from_vec(columns::HEADER, &lookup_key, pending_block.header.encode());
 			if let Some(body) = &pending_block.body {
-				transaction.set_from_vec(columns::BODY, &lookup_key, body.encode());
+				match self.blockchain.transaction_storage {
+					TransactionStorageMode::BlockBody => {
+						transaction.set_from_vec(columns::BODY, &lookup_key, body.encode());
+					},
+					TransactionStorageMode::StorageChain => {
+						let body = self.apply_index_ops(
+							&mut transaction,
+							body,
+							std::mem::take(&mut operation.index_ops),
+						);
+						transaction.set_from_vec(columns::BODY, &lookup_key, body);
+					},
+				}
 			}
-			if let Some(justification) = pending_block.justification {
-				transaction.set_from_vec(columns::JUSTIFICATION, &lookup_key, justification.encode());
+			if let Some(justifications) = pending_block.justifications {
+				transaction.set_from_vec(columns::JUSTIFICATIONS, &lookup_key, justifications.encode());
 			}
 
 			if number.is_zero() {
@@ -1205,12 +1459,22 @@ impl<Block: BlockT> Backend<Block> {
 				}
 				self.state_usage.tally_writes(ops, bytes);
 				let number_u64 = number.saturated_into::<u64>();
-				let commit = self.storage.state_db.insert_block(
-					&hash,
-					number_u64,
-					&pending_block.header.parent_hash(),
-					changeset,
-				).map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from_state_db(e))?;
+				let commit = if operation.reset_storage && number_u64 != 0 {
+					// The complete state was imported, the parent state is not required.
+					self.storage.state_db.import_state(
+						&hash,
+						number_u64,
+						&pending_block.header.parent_hash(),
+						changeset,
+					)
+				} else {
+					self.storage.state_db.insert_block(
+						&hash,
+						number_u64,
+						&pending_block.header.parent_hash(),
+						changeset,
+					)
+				}.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from_state_db(e))?;
 				apply_state_commit(&mut transaction, commit);
 
 				// Check if need to finalize. Genesis is always finalized instantly.
@@ -1243,7 +1507,9 @@ impl<Block: BlockT> Backend<Block> {
 
 			if finalized {
 				// TODO: ensure best chain contains this block.
-				self.ensure_sequential_finalization(header, Some(last_finalized_hash))?;
+				if !disconnected {
+					self.ensure_sequential_finalization(header, Some(last_finalized_hash))?;
+				}
 				self.note_finalized(
 					&mut transaction,
 					true,
@@ -1254,7 +1520,7 @@ impl<Block: BlockT> Backend<Block> {
 				)?;
 			} else {
 				// canonicalize blocks which are old enough, regardless of finality.
-				self.force_delayed_canonicalize(&mut transaction, hash, *header.number())?
+				self.force_delayed_canonicalize(&mut transaction, hash, header)?
 			}
 
 			debug!(target: "db", "DB Commit {:?} ({}), best = {}", hash, number, is_best);
@@ -1374,9 +1640,13 @@ impl<Block: BlockT> Backend<Block> {
 			let lookup_key = utils::number_and_hash_to_lookup_key(f_num, f_hash.clone())?;
 			transaction.set_from_vec(columns::META, meta_keys::FINALIZED_BLOCK, lookup_key);
 
-			let commit = self.storage.state_db.canonicalize_block(&f_hash)
-				.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from_state_db(e))?;
-			apply_state_commit(transaction, commit);
+			// Blocks imported without state are not tracked by the state db.
+			if !self.storage.state_db.is_pruned(&f_hash, f_num.saturated_into::<u64>()) {
+				let checkpoint = is_state_checkpoint(f_header);
+				let commit = self.storage.state_db.canonicalize_block(&f_hash, checkpoint, &*self.storage)
+					.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from_state_db(e))?;
+				apply_state_commit(transaction, commit);
+			}
 
 			if !f_num.is_zero() {
 				let new_changes_trie_cache_ops = self.changes_tries_storage.finalize(
@@ -1392,6 +1662,7 @@ impl<Block: BlockT> Backend<Block> {
 		}
 
 		let new_displaced = self.blockchain.leaves.write().finalize_height(f_num);
+		self.prune_blocks(transaction, f_num, &new_displaced)?;
 		match displaced {
 			x @ &mut None => *x = Some(new_displaced),
 			&mut Some(ref mut displaced) => displaced.merge(new_displaced),
@@ -1399,6 +1670,135 @@ impl<Block: BlockT> Backend<Block> {
 
 		Ok(())
 	}
+
+	// remove the bodies of blocks that fell out of the block pruning window after finalizing
+	// `finalized`, as well as the bodies of all blocks on branches that were displaced by it.
+	fn prune_blocks(
+		&self,
+		transaction: &mut Transaction<DbHash>,
+		finalized: NumberFor<Block>,
+		displaced: &FinalizationDisplaced<Block::Hash, NumberFor<Block>>,
+	) -> ClientResult<()> {
+		match self.blockchain.keep_blocks {
+			BlocksPruning::KeepAll => return Ok(()),
+			BlocksPruning::KeepFinalized => {},
+			BlocksPruning::Some(keep_blocks) => {
+				// always keep the body of the last finalized block.
+				let keep = keep_blocks.max(1).into();
+				if finalized >= keep {
+					let number = finalized.saturating_sub(keep);
+					self.prune_block(transaction, BlockId::<Block>::number(number))?;
+				}
+			},
+		}
+
+		for leaf in displaced.leaves() {
+			let mut hash = *leaf;
+			// follow the displaced branch back to the canonical chain. Leaves are only displaced
+			// once finality has passed them, so every block on the branch is below the newly
+			// finalized block and we stop once we reach a canonical one.
+			loop {
+				let header = match self.blockchain.header(BlockId::Hash(hash))? {
+					Some(header) => header,
+					None => break,
+				};
+				if self.blockchain.hash(*header.number())? == Some(hash) {
+					break;
+				}
+				self.prune_block(transaction, BlockId::Hash(hash))?;
+				hash = *header.parent_hash();
+			}
+		}
+
+		Ok(())
+	}
+
+	fn prune_block(
+		&self,
+		transaction: &mut Transaction<DbHash>,
+		id: BlockId<Block>,
+	) -> ClientResult<()> {
+		debug!(target: "db", "Removing block body #{}", id);
+		if let Some(lookup_key) = utils::block_id_to_lookup_key(
+			&*self.storage.db,
+			columns::KEY_LOOKUP,
+			id,
+		)? {
+			if self.blockchain.transaction_storage == TransactionStorageMode::StorageChain {
+				if let Some(body) = self.storage.db.get(columns::BODY, lookup_key.as_ref()) {
+					let index: Vec<DbExtrinsic> = Decode::decode(&mut &body[..])
+						.map_err(|err| sp_blockchain::Error::Backend(
+							format!("Error decoding body list: {}", err)
+						))?;
+					for ex in index {
+						if let Some(hash) = ex.indexed {
+							transaction.release(columns::TRANSACTION, hash);
+						}
+						for hash in ex.renewed {
+							transaction.release(columns::TRANSACTION, hash);
+						}
+					}
+				}
+			}
+			transaction.remove(columns::BODY, lookup_key.as_ref());
+		}
+		Ok(())
+	}
+
+	// Splits the indexed data off the extrinsics they were submitted with, stores it by
+	// content hash and returns the encoded list of `DbExtrinsic` to be stored as the block body.
+	fn apply_index_ops(
+		&self,
+		transaction: &mut Transaction<DbHash>,
+		body: &[Block::Extrinsic],
+		ops: Vec<IndexOperation>,
+	) -> Vec<u8> {
+		let mut extrinsics: Vec<DbExtrinsic> = body.iter()
+			.map(|ex| DbExtrinsic { data: ex.encode(), indexed: None, renewed: Vec::new() })
+			.collect();
+		let mut inserted = HashSet::new();
+		for op in ops {
+			match op {
+				IndexOperation::Insert { extrinsic, hash, size } => {
+					let ex = match extrinsics.get_mut(extrinsic as usize) {
+						Some(ex) if ex.indexed.is_none()
+							&& size as usize <= ex.data.len()
+							&& hash.len() == DbHash::len_bytes() => ex,
+						_ => {
+							warn!(target: "db", "Ignoring invalid transaction index insert #{}", extrinsic);
+							continue;
+						},
+					};
+					let hash = DbHash::from_slice(&hash);
+					let offset = ex.data.len() - size as usize;
+					transaction.store(columns::TRANSACTION, hash, ex.data.split_off(offset));
+					ex.indexed = Some(hash);
+					inserted.insert(hash);
+				},
+				IndexOperation::Renew { extrinsic, hash } => {
+					let ex = match extrinsics.get_mut(extrinsic as usize) {
+						Some(ex) if hash.len() == DbHash::len_bytes() => ex,
+						_ => {
+							warn!(target: "db", "Ignoring invalid transaction index renewal #{}", extrinsic);
+							continue;
+						},
+					};
+					let hash = DbHash::from_slice(&hash);
+					// Data that was already pruned can't be renewed. Not recording the
+					// reference keeps the counter balanced when the block is pruned.
+					if !inserted.contains(&hash)
+						&& !self.storage.db.contains(columns::TRANSACTION, hash.as_ref())
+					{
+						debug!(target: "db", "Renewing missing indexed transaction {:?}", hash);
+						continue;
+					}
+					transaction.reference(columns::TRANSACTION, hash);
+					ex.renewed.push(hash);
+				},
+			}
+		}
+		extrinsics.encode()
+	}
 }
 
 fn apply_state_commit(transaction: &mut Transaction<DbHash>, commit: sc_state_db::CommitSet<Vec<u8>>) {
@@ -1464,6 +1864,8 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 			finalized_blocks: Vec::new(),
 			set_head: None,
 			commit_state: false,
+			reset_storage: false,
+			index_ops: Default::default(),
 		})
 	}
 
@@ -1524,6 +1926,45 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 		Ok(())
 	}
 
+	fn append_justification(
+		&self,
+		block: BlockId<Block>,
+		justification: Justification,
+	) -> ClientResult<()> {
+		let mut transaction = Transaction::new();
+		let hash = self.blockchain.expect_block_hash_from_id(&block)?;
+		let header = self.blockchain.expect_header(block)?;
+		let number = *header.number();
+
+		// Only blocks of the finalized chain may get additional justifications.
+		let finalized_number = self.blockchain.meta.read().finalized_number;
+		if number > finalized_number
+			|| ::sc_client_api::blockchain::HeaderBackend::hash(&self.blockchain, number)? != Some(hash)
+		{
+			return Err(ClientError::NotInFinalizedChain);
+		}
+
+		let justifications = match self.blockchain.justifications(block)? {
+			Some(mut stored_justifications) => {
+				if !stored_justifications.append(justification) {
+					return Err(ClientError::BadJustification(
+						"Duplicate consensus engine ID".into()
+					));
+				}
+				stored_justifications
+			},
+			None => Justifications::from(justification),
+		};
+
+		transaction.set_from_vec(
+			columns::JUSTIFICATIONS,
+			&utils::number_and_hash_to_lookup_key(number, hash)?,
+			justifications.encode(),
+		);
+		self.storage.db.commit(transaction)?;
+		Ok(())
+	}
+
 	fn changes_trie_storage(&self) -> Option<&dyn PrunableStateChangesTrieStorage<Block>> {
 		Some(&self.changes_tries_storage)
 	}
@@ -1545,10 +1986,14 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 			(*&self.shared_cache).lock().used_storage_cache_size(),
 		);
 		let state_db = self.storage.state_db.memory_info();
+		let trie_cache_stats = self.trie_cache.as_ref()
+			.map(|cache| cache.lock().stats())
+			.unwrap_or_default();
 
 		Some(UsageInfo {
 			memory: MemoryInfo {
 				state_cache,
+				trie_cache: MemorySize::from_bytes(trie_cache_stats.used_size),
 				database_cache,
 				state_db,
 			},
@@ -1564,6 +2009,8 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 				state_writes_cache: state_stats.overlay_writes.ops,
 				state_reads_cache: state_stats.cache_reads.ops,
 				state_writes_nodes: state_stats.nodes_writes.ops,
+				trie_cache_hits: trie_cache_stats.hits,
+				trie_cache_misses: trie_cache_stats.misses,
 			},
 		})
 	}
@@ -1713,7 +2160,8 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 				}
 				if let Ok(()) = self.storage.state_db.pin(&hash) {
 					let root = hdr.state_root;
-					let db_state = DbState::<Block>::new(self.storage.clone(), root);
+					let db_state = DbState::<Block>::new(self.storage.clone(), root)
+						.with_trie_node_cache(self.trie_cache.clone());
 					let state = RefTrackingState::new(
 						db_state,
 						self.storage.clone(),
@@ -1742,6 +2190,16 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 		}
 	}
 
+	fn available_states(&self) -> AvailableStates<Block> {
+		AvailableStates {
+			window_start: self.storage.state_db.pruning_window_start().map(|n| n.saturated_into()),
+			kept: self.storage.state_db.kept_states()
+				.into_iter()
+				.map(|(hash, number)| (number.saturated_into(), hash))
+				.collect(),
+		}
+	}
+
 	fn have_state_at(&self, hash: &Block::Hash, number: NumberFor<Block>) -> bool {
 		if self.is_archive {
 			match self.blockchain.header_metadata(hash.clone()) {
@@ -1779,6 +2237,10 @@ pub(crate) mod tests {
 	use sp_runtime::generic::DigestItem;
 	use sp_state_machine::{TrieMut, TrieDBMut};
 	use sp_blockchain::{lowest_common_ancestor, tree_route};
+	use sp_runtime::ConsensusEngineId;
+
+	const CONS0_ENGINE_ID: ConsensusEngineId = *b"CON0";
+	const CONS1_ENGINE_ID: ConsensusEngineId = *b"CON1";
 
 	pub(crate) type Block = RawBlock<ExtrinsicWrapper<u64>>;
 
@@ -1882,7 +2344,10 @@ pub(crate) mod tests {
 		let backend = Backend::<Block>::new(DatabaseSettings {
 			state_cache_size: 16777216,
 			state_cache_child_ratio: Some((50, 100)),
+			trie_cache_size: 0,
 			pruning: PruningMode::keep_blocks(1),
+			keep_blocks: BlocksPruning::KeepAll,
+			transaction_storage: TransactionStorageMode::BlockBody,
 			source: DatabaseSettingsSrc::Custom(backing),
 		}, 0).unwrap();
 		assert_eq!(backend.blockchain().info().best_number, 9);
@@ -1979,6 +2444,117 @@ pub(crate) mod tests {
 		}
 	}
 
+	#[test]
+	fn import_state_on_top_of_headers() {
+		let backend = Backend::<Block>::new_test(10, 0);
+		let genesis = insert_header(&backend, 0, Default::default(), None, Default::default());
+
+		let mut parent_hash = genesis;
+		let mut headers = Vec::new();
+		for number in 1..3 {
+			let mut op = backend.begin_operation().unwrap();
+			let header = Header {
+				number,
+				parent_hash,
+				state_root: Default::default(),
+				digest: Default::default(),
+				extrinsics_root: Default::default(),
+			};
+			parent_hash = header.hash();
+			headers.push(parent_hash);
+			op.set_block_data(header, None, None, NewBlockState::Best).unwrap();
+			backend.commit_operation(op).unwrap();
+		}
+		assert!(!backend.have_state_at(&parent_hash, 2));
+
+		let storage = vec![
+			(vec![1, 3, 5], vec![2, 4, 6]),
+			(vec![1, 2, 3], vec![9, 9, 9]),
+		];
+		let mut op = backend.begin_operation().unwrap();
+		for header in headers {
+			op.mark_finalized(BlockId::Hash(header), None).unwrap();
+		}
+		let root = op.reset_storage(Storage {
+			top: storage.into_iter().collect(),
+			children_default: Default::default(),
+		}).unwrap();
+		let header = Header {
+			number: 3,
+			parent_hash,
+			state_root: root,
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		};
+		let hash = header.hash();
+		op.set_block_data(header, None, None, NewBlockState::Final).unwrap();
+		backend.commit_operation(op).unwrap();
+
+		assert_eq!(backend.blockchain().info().finalized_hash, hash);
+		assert!(backend.have_state_at(&hash, 3));
+		let state = backend.state_at(BlockId::Hash(hash)).unwrap();
+		assert_eq!(state.storage(&[1, 3, 5]).unwrap(), Some(vec![2, 4, 6]));
+		assert_eq!(state.storage(&[1, 2, 3]).unwrap(), Some(vec![9, 9, 9]));
+
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, BlockId::Hash(hash)).unwrap();
+		let changes = vec![(vec![5, 5, 5], Some(vec![4, 5, 6]))];
+		let (root, overlay) = op.old_state.storage_root(
+			changes.iter().map(|(k, v)| (&k[..], v.as_ref().map(|v| &v[..])))
+		);
+		op.update_db_storage(overlay).unwrap();
+		op.update_storage(changes, Vec::new()).unwrap();
+		let header = Header {
+			number: 4,
+			parent_hash: hash,
+			state_root: root,
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		};
+		let hash = header.hash();
+		op.set_block_data(header, Some(vec![]), None, NewBlockState::Best).unwrap();
+		backend.commit_operation(op).unwrap();
+
+		let state = backend.state_at(BlockId::Hash(hash)).unwrap();
+		assert_eq!(state.storage(&[1, 3, 5]).unwrap(), Some(vec![2, 4, 6]));
+		assert_eq!(state.storage(&[5, 5, 5]).unwrap(), Some(vec![4, 5, 6]));
+	}
+
+	#[test]
+	fn import_state_without_ancestry() {
+		let backend = Backend::<Block>::new_test(10, 0);
+		let genesis = insert_header(&backend, 0, Default::default(), None, Default::default());
+
+		let storage = vec![(vec![1, 3, 5], vec![2, 4, 6])];
+		let mut op = backend.begin_operation().unwrap();
+		let root = op.reset_storage(Storage {
+			top: storage.into_iter().collect(),
+			children_default: Default::default(),
+		}).unwrap();
+		let header = Header {
+			number: 10,
+			parent_hash: [9u8; 32].into(),
+			state_root: root,
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		};
+		let hash = header.hash();
+		op.set_block_data(header, None, None, NewBlockState::Final).unwrap();
+		backend.commit_operation(op).unwrap();
+
+		let info = backend.blockchain().info();
+		assert_eq!(info.best_hash, hash);
+		assert_eq!(info.finalized_hash, hash);
+		assert_eq!(backend.blockchain().hash(10).unwrap(), Some(hash));
+		assert_eq!(backend.blockchain().hash(0).unwrap(), Some(genesis));
+		assert_eq!(backend.blockchain().hash(9).unwrap(), None);
+		let state = backend.state_at(BlockId::Hash(hash)).unwrap();
+		assert_eq!(state.storage(&[1, 3, 5]).unwrap(), Some(vec![2, 4, 6]));
+
+		let hash = insert_header(&backend, 11, hash, None, Default::default());
+		assert_eq!(backend.blockchain().info().best_hash, hash);
+	}
+
 	#[test]
 	fn delete_only_when_negative_rc() {
 		sp_tracing::try_init_simple();
@@ -2354,12 +2930,51 @@ pub(crate) mod tests {
 		let block0 = insert_header(&backend, 0, Default::default(), None, Default::default());
 		let _ = insert_header(&backend, 1, block0, None, Default::default());
 
-		let justification = Some(vec![1, 2, 3]);
+		let justification = Some((CONS0_ENGINE_ID, vec![1, 2, 3]));
 		backend.finalize_block(BlockId::Number(1), justification.clone()).unwrap();
 
 		assert_eq!(
-			backend.blockchain().justification(BlockId::Number(1)).unwrap(),
-			justification,
+			backend.blockchain().justifications(BlockId::Number(1)).unwrap(),
+			justification.map(Justifications::from),
+		);
+	}
+
+	#[test]
+	fn test_append_justification_to_finalized_block() {
+		use sc_client_api::blockchain::{Backend as BlockChainBackend};
+
+		let backend = Backend::<Block>::new_test(10, 10);
+
+		let block0 = insert_header(&backend, 0, Default::default(), None, Default::default());
+		let block1 = insert_header(&backend, 1, block0, None, Default::default());
+		let _ = insert_header(&backend, 2, block1, None, Default::default());
+
+		// the block must be finalized first.
+		assert!(matches!(
+			backend.append_justification(BlockId::Number(1), (CONS1_ENGINE_ID, vec![4, 5, 6])),
+			Err(ClientError::NotInFinalizedChain),
+		));
+
+		let just0 = (CONS0_ENGINE_ID, vec![1, 2, 3]);
+		backend.finalize_block(BlockId::Number(1), Some(just0.clone())).unwrap();
+
+		let just1 = (CONS1_ENGINE_ID, vec![4, 5, 6]);
+		backend.append_justification(BlockId::Number(1), just1.clone()).unwrap();
+
+		let just2 = (CONS1_ENGINE_ID, vec![7, 8, 9]);
+		assert!(matches!(
+			backend.append_justification(BlockId::Number(1), just2),
+			Err(ClientError::BadJustification(_)),
+		));
+
+		let justifications = {
+			let mut just = Justifications::from(just0);
+			just.append(just1);
+			just
+		};
+		assert_eq!(
+			backend.blockchain().justifications(BlockId::Number(1)).unwrap(),
+			Some(justifications),
 		);
 	}
 
@@ -2403,6 +3018,284 @@ pub(crate) mod tests {
 		}
 	}
 
+	fn insert_block(
+		backend: &Backend<Block>,
+		number: u64,
+		parent_hash: H256,
+		body: Vec<ExtrinsicWrapper<u64>>,
+		state: NewBlockState,
+	) -> H256 {
+		insert_block_with_index(backend, number, parent_hash, body, Vec::new(), state)
+	}
+
+	fn insert_block_with_index(
+		backend: &Backend<Block>,
+		number: u64,
+		parent_hash: H256,
+		body: Vec<ExtrinsicWrapper<u64>>,
+		index: Vec<IndexOperation>,
+		state: NewBlockState,
+	) -> H256 {
+		let header = Header {
+			number,
+			parent_hash,
+			state_root: BlakeTwo256::trie_root(Vec::new()),
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		};
+		let header_hash = header.hash();
+
+		let block_id = if number == 0 {
+			BlockId::Hash(Default::default())
+		} else {
+			BlockId::Hash(parent_hash)
+		};
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, block_id).unwrap();
+		op.set_block_data(header, Some(body), None, state).unwrap();
+		op.update_transaction_index(index).unwrap();
+		backend.commit_operation(op).unwrap();
+
+		header_hash
+	}
+
+	#[test]
+	fn indexed_data_block_body() {
+		let backend = Backend::<Block>::new_test_with_tx_storage(
+			1,
+			0,
+			TransactionStorageMode::StorageChain,
+		);
+
+		let x0 = ExtrinsicWrapper::from(0u64).encode();
+		let x1 = ExtrinsicWrapper::from(1u64).encode();
+		let x0_hash = <HashFor<Block> as sp_core::Hasher>::hash(&x0[1..]);
+		let x1_hash = <HashFor<Block> as sp_core::Hasher>::hash(&x1[1..]);
+		let index = vec![
+			IndexOperation::Insert {
+				extrinsic: 0,
+				hash: x0_hash.as_ref().to_vec(),
+				size: (x0.len() - 1) as u32,
+			},
+			IndexOperation::Insert {
+				extrinsic: 1,
+				hash: x1_hash.as_ref().to_vec(),
+				size: (x1.len() - 1) as u32,
+			},
+		];
+		let hash = insert_block_with_index(
+			&backend,
+			0,
+			Default::default(),
+			vec![0u64.into(), 1u64.into()],
+			index,
+			NewBlockState::Best,
+		);
+
+		let bc = backend.blockchain();
+		assert_eq!(bc.indexed_transaction(&x0_hash).unwrap().unwrap(), &x0[1..]);
+		assert_eq!(bc.indexed_transaction(&x1_hash).unwrap().unwrap(), &x1[1..]);
+		assert_eq!(
+			bc.block_indexed_body(BlockId::Hash(hash)).unwrap().unwrap(),
+			vec![x0[1..].to_vec(), x1[1..].to_vec()],
+		);
+		assert_eq!(
+			bc.body(BlockId::Hash(hash)).unwrap().unwrap(),
+			vec![ExtrinsicWrapper::from(0u64), ExtrinsicWrapper::from(1u64)],
+		);
+	}
+
+	#[test]
+	fn renew_transaction_storage() {
+		let backend = Backend::<Block>::new_test_with_settings(
+			10,
+			BlocksPruning::Some(2),
+			TransactionStorageMode::StorageChain,
+			0,
+		);
+		let mut blocks = Vec::new();
+		let mut prev_hash = Default::default();
+		let x1 = ExtrinsicWrapper::from(0u64).encode();
+		let x1_hash = <HashFor<Block> as sp_core::Hasher>::hash(&x1[1..]);
+		for i in 0..10 {
+			let mut index = Vec::new();
+			if i == 1 {
+				index.push(IndexOperation::Insert {
+					extrinsic: 0,
+					hash: x1_hash.as_ref().to_vec(),
+					size: (x1.len() - 1) as u32,
+				});
+			} else if i < 5 {
+				// keep renewing 1st
+				index.push(IndexOperation::Renew {
+					extrinsic: 0,
+					hash: x1_hash.as_ref().to_vec(),
+				});
+			}
+			let hash = insert_block_with_index(
+				&backend,
+				i,
+				prev_hash,
+				vec![i.into()],
+				index,
+				NewBlockState::Best,
+			);
+			blocks.push(hash);
+			prev_hash = hash;
+		}
+
+		for i in 1..10 {
+			backend.finalize_block(BlockId::Hash(blocks[i]), None).unwrap();
+			let bc = backend.blockchain();
+			// Block 4 is the last one to renew the data, its body is pruned at block 6.
+			if i < 6 {
+				assert!(bc.indexed_transaction(&x1_hash).unwrap().is_some());
+			} else {
+				assert!(bc.indexed_transaction(&x1_hash).unwrap().is_none());
+			}
+		}
+	}
+
+	fn check_store_and_renew_in_one_block(db: Arc<dyn Database<DbHash>>) {
+		let backend = Backend::<Block>::new(DatabaseSettings {
+			state_cache_size: 16777216,
+			state_cache_child_ratio: Some((50, 100)),
+			trie_cache_size: 0,
+			pruning: PruningMode::keep_blocks(10),
+			keep_blocks: BlocksPruning::Some(1),
+			transaction_storage: TransactionStorageMode::StorageChain,
+			source: DatabaseSettingsSrc::Custom(db),
+		}, 0).unwrap();
+		let x0 = ExtrinsicWrapper::from(0u64).encode();
+		let x0_hash = <HashFor<Block> as sp_core::Hasher>::hash(&x0[1..]);
+		let mut blocks = Vec::new();
+		let mut prev_hash = Default::default();
+		for i in 0..4 {
+			let index = match i {
+				// The data is stored and renewed by the same block.
+				1 => vec![
+					IndexOperation::Insert {
+						extrinsic: 0,
+						hash: x0_hash.as_ref().to_vec(),
+						size: (x0.len() - 1) as u32,
+					},
+					IndexOperation::Renew { extrinsic: 1, hash: x0_hash.as_ref().to_vec() },
+				],
+				2 => vec![IndexOperation::Renew { extrinsic: 0, hash: x0_hash.as_ref().to_vec() }],
+				_ => Vec::new(),
+			};
+			let hash = insert_block_with_index(
+				&backend,
+				i,
+				prev_hash,
+				vec![0u64.into(), 1u64.into()],
+				index,
+				NewBlockState::Best,
+			);
+			blocks.push(hash);
+			prev_hash = hash;
+		}
+
+		// Pruning block 1 releases both of its references, block 2 still holds one.
+		backend.finalize_block(BlockId::Hash(blocks[1]), None).unwrap();
+		backend.finalize_block(BlockId::Hash(blocks[2]), None).unwrap();
+		assert!(backend.blockchain().body(BlockId::Hash(blocks[1])).unwrap().is_none());
+		assert!(backend.blockchain().indexed_transaction(&x0_hash).unwrap().is_some());
+
+		backend.finalize_block(BlockId::Hash(blocks[3]), None).unwrap();
+		assert!(backend.blockchain().indexed_transaction(&x0_hash).unwrap().is_none());
+	}
+
+	#[test]
+	fn store_and_renew_in_one_block() {
+		check_store_and_renew_in_one_block(sp_database::as_database(
+			kvdb_memorydb::create(crate::utils::NUM_COLUMNS),
+		));
+	}
+
+	#[cfg(feature = "with-parity-db")]
+	#[test]
+	fn store_and_renew_in_one_block_parity_db() {
+		let dir = tempfile::TempDir::new().unwrap();
+		check_store_and_renew_in_one_block(
+			crate::parity_db::open(dir.path(), crate::utils::DatabaseType::Full).unwrap(),
+		);
+	}
+
+	#[test]
+	fn prune_blocks_on_finalize() {
+		let backend = Backend::<Block>::new_test_with_blocks_pruning(10, BlocksPruning::Some(2), 0);
+		let mut blocks = Vec::new();
+		let mut prev_hash = Default::default();
+		for i in 0..5 {
+			let hash = insert_block(&backend, i, prev_hash, vec![i.into()], NewBlockState::Best);
+			blocks.push(hash);
+			prev_hash = hash;
+		}
+
+		{
+			let mut op = backend.begin_operation().unwrap();
+			backend.begin_state_operation(&mut op, BlockId::Hash(blocks[4])).unwrap();
+			for hash in &blocks[1..] {
+				op.mark_finalized(BlockId::Hash(*hash), None).unwrap();
+			}
+			backend.commit_operation(op).unwrap();
+		}
+
+		let blockchain = backend.blockchain();
+		for hash in &blocks[..3] {
+			assert!(matches!(
+				blockchain.body(BlockId::Hash(*hash)),
+				Err(sp_blockchain::Error::BodyPruned(_)),
+			));
+			assert!(blockchain.header(BlockId::Hash(*hash)).unwrap().is_some());
+		}
+		assert_eq!(Some(vec![3.into()]), blockchain.body(BlockId::Hash(blocks[3])).unwrap());
+		assert_eq!(Some(vec![4.into()]), blockchain.body(BlockId::Hash(blocks[4])).unwrap());
+	}
+
+	#[test]
+	fn prune_displaced_blocks_on_finalize() {
+		let backend = Backend::<Block>::new_test_with_blocks_pruning(
+			10,
+			BlocksPruning::KeepFinalized,
+			0,
+		);
+		let block0 = insert_block(
+			&backend,
+			0,
+			Default::default(),
+			vec![0.into()],
+			NewBlockState::Best,
+		);
+		let block1 = insert_block(&backend, 1, block0, vec![1.into()], NewBlockState::Best);
+		let block2 = insert_block(&backend, 2, block1, vec![2.into()], NewBlockState::Best);
+		let fork1 = insert_block(&backend, 1, block0, vec![42.into()], NewBlockState::Normal);
+		let fork2 = insert_block(&backend, 2, fork1, vec![43.into()], NewBlockState::Normal);
+
+		backend.finalize_block(BlockId::Hash(block1), None).unwrap();
+		// the fork is only displaced once finality has passed its leaf.
+		assert_eq!(
+			Some(vec![42.into()]),
+			backend.blockchain().body(BlockId::Hash(fork1)).unwrap(),
+		);
+
+		let block3 = insert_block(&backend, 3, block2, vec![3.into()], NewBlockState::Best);
+		backend.finalize_block(BlockId::Hash(block2), None).unwrap();
+		backend.finalize_block(BlockId::Hash(block3), None).unwrap();
+
+		let blockchain = backend.blockchain();
+		for hash in &[fork1, fork2] {
+			assert!(matches!(
+				blockchain.body(BlockId::Hash(*hash)),
+				Err(sp_blockchain::Error::BodyPruned(_)),
+			));
+		}
+		assert_eq!(Some(vec![0.into()]), blockchain.body(BlockId::Hash(block0)).unwrap());
+		assert_eq!(Some(vec![1.into()]), blockchain.body(BlockId::Hash(block1)).unwrap());
+		assert_eq!(Some(vec![2.into()]), blockchain.body(BlockId::Hash(block2)).unwrap());
+	}
+
 	#[test]
 	fn header_cht_root_works() {
 		use sc_client_api::ProvideChtRoots;
@@ -2427,4 +3320,66 @@ pub(crate) mod tests {
 		assert_eq!(cht_root_1, cht_root_2);
 		assert_eq!(cht_root_2, cht_root_3);
 	}
+
+	#[test]
+	fn trie_node_cache_is_shared_between_forks() {
+		let backend = Backend::<Block>::new(DatabaseSettings {
+			state_cache_size: 0,
+			state_cache_child_ratio: None,
+			trie_cache_size: 1 << 20,
+			pruning: PruningMode::keep_blocks(10),
+			keep_blocks: BlocksPruning::KeepAll,
+			transaction_storage: TransactionStorageMode::BlockBody,
+			source: DatabaseSettingsSrc::Custom(
+				sp_database::as_database(kvdb_memorydb::create(crate::utils::NUM_COLUMNS)),
+			),
+		}, 10).unwrap();
+
+		let insert = |number: u64, parent: BlockId<Block>, parent_hash: H256, value: u8| {
+			let mut op = backend.begin_operation().unwrap();
+			backend.begin_state_operation(&mut op, parent).unwrap();
+			let changes = (0..16u8)
+				.map(|k| (vec![k, k], Some(vec![value; 64])))
+				.collect::<Vec<_>>();
+			let (root, overlay) = op.old_state.storage_root(
+				changes.iter().map(|(k, v)| (&k[..], v.as_ref().map(|v| &v[..])))
+			);
+			op.update_db_storage(overlay).unwrap();
+			op.update_storage(changes, Vec::new()).unwrap();
+			let header = Header {
+				number,
+				parent_hash,
+				state_root: root,
+				digest: Default::default(),
+				extrinsics_root: Default::default(),
+			};
+			let hash = header.hash();
+			op.set_block_data(header, Some(vec![]), None, NewBlockState::Normal).unwrap();
+			backend.commit_operation(op).unwrap();
+			hash
+		};
+
+		let genesis = insert(0, BlockId::Hash(Default::default()), Default::default(), 0);
+		let block_a = insert(1, BlockId::Hash(genesis), genesis, 1);
+		let block_b = insert(1, BlockId::Hash(genesis), genesis, 2);
+
+		let check = |hash: H256, value: u8| {
+			let state = backend.state_at(BlockId::Hash(hash)).unwrap();
+			let pairs = state.pairs();
+			assert_eq!(pairs.len(), 16);
+			assert!(pairs.iter().all(|(_, v)| v == &vec![value; 64]));
+		};
+		check(genesis, 0);
+		check(block_a, 1);
+		check(block_b, 2);
+		let misses = backend.usage_info().unwrap().io.trie_cache_misses;
+		check(block_b, 2);
+		check(genesis, 0);
+		check(block_a, 1);
+
+		let usage = backend.usage_info().unwrap();
+		assert_eq!(usage.io.trie_cache_misses, misses);
+		assert!(usage.io.trie_cache_hits > 0);
+		assert!(usage.memory.trie_cache.as_bytes() > 0);
+	}
 }
@@ -0,0 +1,448 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Migration of a database from RocksDB to ParityDB.
+//!
+//! Columns are streamed from the source in key order and written to the target in batches.
+//! The position reached is committed to the target together with each batch, so a migration
+//! that was interrupted continues where it stopped when it is started again.
+//!
+//! ParityDB counts references for state and indexed transaction data instead of prefixing
+//! state keys, so state keys are reduced to their hash, the state-db journals are rewritten
+//! accordingly and indexed transactions are stored as many times as they are referenced.
+
+use std::path::Path;
+
+use sp_runtime::traits::Block as BlockT;
+use sp_blockchain::Result as ClientResult;
+use crate::utils::DatabaseType;
+
+/// Number of source keys written to the target in one commit.
+#[cfg(all(any(feature = "with-kvdb-rocksdb", test), feature = "with-parity-db"))]
+const BATCH_SIZE: usize = 10_000;
+
+/// Progress of a running migration, reported after every committed batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationProgress {
+	/// Column being migrated.
+	pub column: u32,
+	/// Total number of columns.
+	pub num_columns: u32,
+	/// Number of source keys of the column processed so far.
+	pub keys: u64,
+}
+
+/// Result of verifying a single migrated column.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnVerification {
+	/// Verified column.
+	pub column: u32,
+	/// Number of entries the source expects in the target.
+	pub source_keys: u64,
+	/// Number of those entries found in the target.
+	pub target_keys: u64,
+	/// Number of entries whose value hashes were compared.
+	pub sampled: u64,
+	/// Number of compared entries whose value hash differs.
+	pub mismatched: u64,
+}
+
+impl ColumnVerification {
+	/// Returns `true` if every entry was found and all sampled values match.
+	pub fn is_ok(&self) -> bool {
+		self.source_keys == self.target_keys && self.mismatched == 0
+	}
+}
+
+/// Copies the RocksDB database at `source` into a ParityDB database at `target`.
+///
+/// The source is upgraded to the current database version first. The target must either be
+/// empty or contain an unfinished migration, which is then resumed. `on_progress` is called
+/// after every committed batch.
+#[cfg(all(any(feature = "with-kvdb-rocksdb", test), feature = "with-parity-db"))]
+pub fn migrate_rocksdb_to_parity_db<Block: BlockT>(
+	source: &Path,
+	target: &Path,
+	db_type: DatabaseType,
+	on_progress: &mut dyn FnMut(MigrationProgress),
+) -> ClientResult<()> {
+	imp::migrate::<Block>(source, target, db_type, BATCH_SIZE, on_progress)
+}
+
+/// Copies the RocksDB database at `source` into a ParityDB database at `target`.
+#[cfg(not(all(any(feature = "with-kvdb-rocksdb", test), feature = "with-parity-db")))]
+pub fn migrate_rocksdb_to_parity_db<Block: BlockT>(
+	_source: &Path,
+	_target: &Path,
+	_db_type: DatabaseType,
+	_on_progress: &mut dyn FnMut(MigrationProgress),
+) -> ClientResult<()> {
+	Err(features_error())
+}
+
+/// Checks that the ParityDB database at `target` holds every entry of the RocksDB database
+/// at `source` after migration.
+///
+/// Every entry is looked up in the target and the value hashes of every `sample_rate`-th
+/// entry of a column are compared. A `sample_rate` of `0` only compares the keys.
+#[cfg(all(any(feature = "with-kvdb-rocksdb", test), feature = "with-parity-db"))]
+pub fn verify_rocksdb_to_parity_db<Block: BlockT>(
+	source: &Path,
+	target: &Path,
+	db_type: DatabaseType,
+	sample_rate: u64,
+) -> ClientResult<Vec<ColumnVerification>> {
+	imp::verify::<Block>(source, target, db_type, sample_rate)
+}
+
+/// Checks that the ParityDB database at `target` holds every entry of the RocksDB database
+/// at `source` after migration.
+#[cfg(not(all(any(feature = "with-kvdb-rocksdb", test), feature = "with-parity-db")))]
+pub fn verify_rocksdb_to_parity_db<Block: BlockT>(
+	_source: &Path,
+	_target: &Path,
+	_db_type: DatabaseType,
+	_sample_rate: u64,
+) -> ClientResult<Vec<ColumnVerification>> {
+	Err(features_error())
+}
+
+#[cfg(not(all(any(feature = "with-kvdb-rocksdb", test), feature = "with-parity-db")))]
+fn features_error() -> sp_blockchain::Error {
+	sp_blockchain::Error::Backend(
+		"`with-kvdb-rocksdb` and `with-parity-db` features are required to migrate the database".into(),
+	)
+}
+
+#[cfg(all(any(feature = "with-kvdb-rocksdb", test), feature = "with-parity-db"))]
+mod imp {
+	use std::collections::HashMap;
+	use std::io;
+	use std::path::Path;
+
+	use codec::{Decode, Encode};
+	use kvdb::KeyValueDB;
+	use sp_core::hashing::blake2_256;
+	use sp_database::{Database, Transaction};
+	use sp_runtime::traits::Block as BlockT;
+	use sp_blockchain::{Error as ClientError, Result as ClientResult};
+	use crate::utils::{DatabaseType, NUM_COLUMNS, meta_keys};
+	use crate::{columns, DbHash, DB_HASH_LEN};
+	use super::{ColumnVerification, MigrationProgress};
+
+	/// Read access to the state-db meta column of the source.
+	struct StateMetaDb<'a>(&'a kvdb_rocksdb::Database);
+
+	impl<'a> sc_state_db::MetaDb for StateMetaDb<'a> {
+		type Error = io::Error;
+
+		fn get_meta(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+			self.0.get(columns::STATE_META, key)
+		}
+	}
+
+	/// The source database together with everything needed to translate its entries.
+	struct Source {
+		db: kvdb_rocksdb::Database,
+		db_type: DatabaseType,
+		/// State-db journal records rewritten for the target, by meta key.
+		journals: HashMap<Vec<u8>, Vec<u8>>,
+	}
+
+	impl Source {
+		fn open<Block: BlockT>(path: &Path, db_type: DatabaseType) -> ClientResult<Self> {
+			crate::upgrade::upgrade_db::<Block>(path, db_type)?;
+			let path = path.to_str()
+				.ok_or_else(|| ClientError::Backend("Invalid database path".into()))?;
+			let config = kvdb_rocksdb::DatabaseConfig::with_columns(NUM_COLUMNS);
+			let db = kvdb_rocksdb::Database::open(&config, path)
+				.map_err(|err| ClientError::Backend(format!("{}", err)))?;
+			let journals = match db_type {
+				DatabaseType::Full => sc_state_db::ref_counted_journals::<Block::Hash, Vec<u8>, _>(
+					&StateMetaDb(&db),
+					|key| state_key(key).to_vec(),
+				).map_err(|e: sc_state_db::Error<io::Error>| ClientError::from_state_db(e))?
+					.into_iter()
+					.collect(),
+				DatabaseType::Light => HashMap::new(),
+			};
+			Ok(Source { db, db_type, journals })
+		}
+
+		/// Translates a source entry into the key and value it has in the target and the number
+		/// of times it is stored there. Returns `None` for entries that are not migrated.
+		fn translate(
+			&self,
+			column: u32,
+			key: &[u8],
+			value: Vec<u8>,
+		) -> ClientResult<Option<(Vec<u8>, Vec<u8>, u32)>> {
+			if self.db_type == DatabaseType::Light {
+				return Ok(Some((key.to_vec(), value, 1)));
+			}
+			Ok(match column {
+				columns::STATE => Some((state_key(key).to_vec(), value, 1)),
+				columns::STATE_META => {
+					let value = self.journals.get(key).cloned().unwrap_or(value);
+					Some((key.to_vec(), value, 1))
+				},
+				// Reference counters are stored next to the values under a longer key.
+				columns::TRANSACTION if key.len() != DB_HASH_LEN => None,
+				columns::TRANSACTION => {
+					let mut counter_key = key.to_vec();
+					counter_key.push(0);
+					let count = match self.db.get(column, &counter_key).map_err(db_err)? {
+						Some(counter) => {
+							let mut counter_data = [0; 4];
+							if counter.len() != 4 {
+								return Err(ClientError::Backend(
+									format!("Unexpected counter len {}", counter.len()),
+								));
+							}
+							counter_data.copy_from_slice(&counter);
+							u32::from_le_bytes(counter_data)
+						},
+						None => 1,
+					};
+					Some((key.to_vec(), value, count))
+				},
+				_ => Some((key.to_vec(), value, 1)),
+			})
+		}
+	}
+
+	/// Strips the trie prefix from a state key.
+	fn state_key(key: &[u8]) -> &[u8] {
+		&key[key.len().saturating_sub(DB_HASH_LEN)..]
+	}
+
+	fn db_err(err: io::Error) -> ClientError {
+		ClientError::Backend(format!("{}", err))
+	}
+
+	fn open_target(path: &Path, db_type: DatabaseType) -> ClientResult<std::sync::Arc<dyn Database<DbHash>>> {
+		crate::parity_db::open(path, db_type)
+			.map_err(|e| ClientError::Backend(format!("{:?}", e)))
+	}
+
+	/// Column and last key committed to the target. A column without a key has not
+	/// been started yet.
+	type Progress = (u32, Option<Vec<u8>>);
+
+	fn read_progress(target: &dyn Database<DbHash>) -> ClientResult<Option<Progress>> {
+		target.get(columns::META, meta_keys::MIGRATION_PROGRESS)
+			.map(|progress| Progress::decode(&mut progress.as_slice()))
+			.transpose()
+			.map_err(|e| ClientError::Backend(format!("Error decoding migration progress: {}", e.what())))
+	}
+
+	pub(super) fn migrate<Block: BlockT>(
+		source: &Path,
+		target: &Path,
+		db_type: DatabaseType,
+		batch_size: usize,
+		on_progress: &mut dyn FnMut(MigrationProgress),
+	) -> ClientResult<()> {
+		let target_is_empty = target.read_dir().map_or(true, |mut d| d.next().is_none());
+		let source = Source::open::<Block>(source, db_type)?;
+		let target_db = open_target(target, db_type)?;
+		let (first_column, mut last_key) = match read_progress(&*target_db)? {
+			Some(progress) => {
+				log::info!(target: "db", "Resuming migration at column {}", progress.0);
+				progress
+			},
+			None if target_is_empty => (0, None),
+			None => return Err(ClientError::Backend(
+				format!("Migration target {} is not empty", target.display()),
+			)),
+		};
+
+		for column in first_column..NUM_COLUMNS {
+			let mut transaction = Transaction::new();
+			let mut keys = 0u64;
+			let mut batch = 0;
+			for (key, value) in source.db.iter(column) {
+				keys += 1;
+				if last_key.as_ref().map_or(false, |last| key[..] <= last[..]) {
+					continue;
+				}
+				if let Some((key, value, count)) = source.translate(column, &key, value.into_vec())? {
+					for _ in 0..count {
+						transaction.set(column, &key, &value);
+					}
+				}
+				batch += 1;
+				if batch == batch_size {
+					let progress: Progress = (column, Some(key.into_vec()));
+					transaction.set_from_vec(columns::META, meta_keys::MIGRATION_PROGRESS, progress.encode());
+					target_db.commit(std::mem::take(&mut transaction))?;
+					batch = 0;
+					on_progress(MigrationProgress { column, num_columns: NUM_COLUMNS, keys });
+				}
+			}
+			let progress: Progress = (column + 1, None);
+			transaction.set_from_vec(columns::META, meta_keys::MIGRATION_PROGRESS, progress.encode());
+			target_db.commit(transaction)?;
+			on_progress(MigrationProgress { column, num_columns: NUM_COLUMNS, keys });
+			last_key = None;
+		}
+
+		let mut transaction = Transaction::new();
+		transaction.remove(columns::META, meta_keys::MIGRATION_PROGRESS);
+		target_db.commit(transaction)?;
+		Ok(())
+	}
+
+	pub(super) fn verify<Block: BlockT>(
+		source: &Path,
+		target: &Path,
+		db_type: DatabaseType,
+		sample_rate: u64,
+	) -> ClientResult<Vec<ColumnVerification>> {
+		let source = Source::open::<Block>(source, db_type)?;
+		let target_db = open_target(target, db_type)?;
+		if read_progress(&*target_db)?.is_some() {
+			return Err(ClientError::Backend(
+				format!("Migration to {} has not finished", target.display()),
+			));
+		}
+
+		let mut result = Vec::with_capacity(NUM_COLUMNS as usize);
+		for column in 0..NUM_COLUMNS {
+			let mut verification = ColumnVerification { column, ..Default::default() };
+			for (key, value) in source.db.iter(column) {
+				let (key, value) = match source.translate(column, &key, value.into_vec())? {
+					Some((key, value, _)) => (key, value),
+					None => continue,
+				};
+				verification.source_keys += 1;
+				if sample_rate != 0 && verification.source_keys % sample_rate == 0 {
+					verification.sampled += 1;
+					if let Some(target_value) = target_db.get(column, &key) {
+						verification.target_keys += 1;
+						if blake2_256(&target_value) != blake2_256(&value) {
+							verification.mismatched += 1;
+						}
+					}
+				} else if target_db.contains(column, &key) {
+					verification.target_keys += 1;
+				}
+			}
+			result.push(verification);
+		}
+		Ok(result)
+	}
+
+	#[cfg(test)]
+	mod tests {
+		use sp_blockchain::HeaderBackend;
+		use sc_client_api::backend::Backend as _;
+		use crate::{Backend, BlocksPruning, DatabaseSettings, DatabaseSettingsSrc, PruningMode, TransactionStorageMode};
+		use crate::tests::{Block, insert_header};
+		use super::*;
+
+		fn settings(source: DatabaseSettingsSrc) -> DatabaseSettings {
+			DatabaseSettings {
+				state_cache_size: 16777216,
+				state_cache_child_ratio: Some((50, 100)),
+				trie_cache_size: 0,
+				pruning: PruningMode::keep_blocks(2),
+				keep_blocks: BlocksPruning::KeepAll,
+				transaction_storage: TransactionStorageMode::BlockBody,
+				source,
+			}
+		}
+
+		fn create_rocksdb(path: &Path) -> Vec<sp_core::H256> {
+			let backend = Backend::<Block>::new(
+				settings(DatabaseSettingsSrc::RocksDb { path: path.to_owned(), cache_size: 16 }),
+				0,
+			).unwrap();
+			let mut hashes = Vec::new();
+			let mut parent = Default::default();
+			for number in 0..8 {
+				parent = insert_header(&backend, number, parent, None, Default::default());
+				hashes.push(parent);
+			}
+			hashes
+		}
+
+		#[test]
+		fn migrates_and_verifies() {
+			let source = tempfile::TempDir::new().unwrap();
+			let target = tempfile::TempDir::new().unwrap();
+			let hashes = create_rocksdb(source.path());
+
+			let mut reports = 0;
+			migrate::<Block>(source.path(), target.path(), DatabaseType::Full, 2, &mut |_| reports += 1)
+				.unwrap();
+			assert!(reports > NUM_COLUMNS as usize);
+
+			let verification = verify::<Block>(source.path(), target.path(), DatabaseType::Full, 1).unwrap();
+			assert_eq!(verification.len(), NUM_COLUMNS as usize);
+			assert!(verification.iter().all(ColumnVerification::is_ok));
+			assert!(verification[columns::HEADER as usize].sampled >= hashes.len() as u64);
+
+			let backend = Backend::<Block>::new(
+				settings(DatabaseSettingsSrc::ParityDb { path: target.path().to_owned() }),
+				0,
+			).unwrap();
+			let info = backend.blockchain().info();
+			assert_eq!(info.best_number, 7);
+			assert_eq!(info.best_hash, hashes[7]);
+			assert_eq!(backend.blockchain().hash(3).unwrap(), Some(hashes[3]));
+		}
+
+		#[test]
+		fn resumes_interrupted_migration() {
+			let source = tempfile::TempDir::new().unwrap();
+			let target = tempfile::TempDir::new().unwrap();
+			create_rocksdb(source.path());
+
+			{
+				// Leave the target as if the migration stopped before the header column.
+				let target_db = open_target(target.path(), DatabaseType::Full).unwrap();
+				let mut transaction = Transaction::new();
+				let progress: Progress = (columns::HEADER, None);
+				transaction.set_from_vec(columns::META, meta_keys::MIGRATION_PROGRESS, progress.encode());
+				target_db.commit(transaction).unwrap();
+			}
+			assert!(verify::<Block>(source.path(), target.path(), DatabaseType::Full, 0).is_err());
+
+			let mut columns_done = Vec::new();
+			migrate::<Block>(source.path(), target.path(), DatabaseType::Full, 100, &mut |p| columns_done.push(p.column))
+				.unwrap();
+			assert_eq!(columns_done.first(), Some(&columns::HEADER));
+
+			let verification = verify::<Block>(source.path(), target.path(), DatabaseType::Full, 0).unwrap();
+			for v in verification.iter() {
+				assert_eq!(v.is_ok(), v.column >= columns::HEADER || v.source_keys == 0);
+			}
+		}
+
+		#[test]
+		fn refuses_non_empty_target() {
+			let source = tempfile::TempDir::new().unwrap();
+			let target = tempfile::TempDir::new().unwrap();
+			create_rocksdb(source.path());
+
+			migrate::<Block>(source.path(), target.path(), DatabaseType::Full, 100, &mut |_| ()).unwrap();
+			assert!(migrate::<Block>(source.path(), target.path(), DatabaseType::Full, 100, &mut |_| ()).is_err());
+		}
+	}
+}
@@ -17,11 +17,18 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 /// A `Database` adapter for parity-db.
 
+use std::collections::HashMap;
 use sp_database::{Database, Change, ColumnId, Transaction, error::DatabaseError};
 use crate::utils::{DatabaseType, NUM_COLUMNS};
 use crate::columns;
 
-struct DbAdapter(parity_db::Db);
+/// Name of the file locked by the instance that has the database open.
+const LOCK_FILE_NAME: &str = "lock";
+
+/// Exclusive lock on a parity-db database directory, released when dropped.
+pub struct DbLock(std::fs::File);
+
+struct DbAdapter(parity_db::Db, DbLock);
 
 fn handle_err<T>(result: parity_db::Result<T>) -> T {
 	match result {
@@ -33,7 +40,7 @@ fn handle_err<T>(result: parity_db::Result<T>) -> T {
 }
 
 /// Wrap parity-db database into a trait object that implements `sp_database::Database`
-pub fn open<H: Clone>(path: &std::path::Path, db_type: DatabaseType)
+pub fn open<H: Clone + AsRef<[u8]>>(path: &std::path::Path, db_type: DatabaseType)
 	-> parity_db::Result<std::sync::Arc<dyn Database<H>>>
 {
 	let mut config = parity_db::Options::with_columns(path, NUM_COLUMNS as u8);
@@ -42,20 +49,97 @@ pub fn open<H: Clone>(path: &std::path::Path, db_type: DatabaseType)
 		state_col.ref_counted = true;
 		state_col.preimage = true;
 		state_col.uniform = true;
+
+		let mut tx_col = &mut config.columns[columns::TRANSACTION as usize];
+		tx_col.ref_counted = true;
+		tx_col.preimage = true;
+		tx_col.uniform = true;
 	}
+	let lock = lock(path).map_err(parity_db::Error::Io)?;
 	let db = parity_db::Db::open(&config)?;
-	Ok(std::sync::Arc::new(DbAdapter(db)))
+	Ok(std::sync::Arc::new(DbAdapter(db, lock)))
 }
 
-impl<H: Clone> Database<H> for DbAdapter {
+/// Takes the exclusive lock of the database at `path`, failing if another instance holds it.
+///
+/// The lock is tied to the open lock file and released by the OS when it is closed, so a
+/// crashed node does not leave the database locked. Fails on platforms other than unix and
+/// windows, where no such lock is available.
+pub fn lock(path: &std::path::Path) -> std::io::Result<DbLock> {
+	std::fs::create_dir_all(path)?;
+	let in_use = || std::io::Error::new(
+		std::io::ErrorKind::WouldBlock,
+		format!("Database {} is in use by another process", path.display()),
+	);
+	let mut options = std::fs::OpenOptions::new();
+	options.read(true).write(true).create(true);
+	#[cfg(unix)]
+	{
+		use std::os::unix::io::AsRawFd;
+		let file = options.open(path.join(LOCK_FILE_NAME))?;
+		if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+			let err = std::io::Error::last_os_error();
+			return Err(match err.kind() {
+				std::io::ErrorKind::WouldBlock => in_use(),
+				_ => err,
+			});
+		}
+		Ok(DbLock(file))
+	}
+	#[cfg(windows)]
+	{
+		use std::os::windows::fs::OpenOptionsExt;
+		/// `ERROR_SHARING_VIOLATION`, returned while another handle to the file is open.
+		const SHARING_VIOLATION: i32 = 32;
+		// Without any share mode the file can't be opened again until this handle is closed.
+		options.share_mode(0);
+		match options.open(path.join(LOCK_FILE_NAME)) {
+			Ok(file) => Ok(DbLock(file)),
+			Err(err) if err.raw_os_error() == Some(SHARING_VIOLATION) => Err(in_use()),
+			Err(err) => Err(err),
+		}
+	}
+	#[cfg(not(any(unix, windows)))]
+	{
+		let _ = (options, in_use);
+		Err(std::io::Error::new(
+			std::io::ErrorKind::Other,
+			"Locking ParityDB databases is not supported on this platform",
+		))
+	}
+}
+
+impl<H: Clone + AsRef<[u8]>> Database<H> for DbAdapter {
 	fn commit(&self, transaction: Transaction<H>) -> Result<(), DatabaseError> {
-		handle_err(self.0.commit(transaction.0.into_iter().map(|change|
-			match change {
+		// Values stored by this transaction, so that references to them within the same
+		// transaction are counted too.
+		let mut pending = HashMap::new();
+		let mut changes = Vec::with_capacity(transaction.0.len());
+		for change in transaction.0.into_iter() {
+			changes.push(match change {
 				Change::Set(col, key, value) => (col as u8, key, Some(value)),
 				Change::Remove(col, key) => (col as u8, key, None),
-				_ => unimplemented!(),
-			}))
-		);
+				// Reference counted columns count every insertion and removal of a value.
+				Change::Store(col, key, value) => {
+					pending.insert((col, key.as_ref().to_vec()), value.clone());
+					(col as u8, key.as_ref().to_vec(), Some(value))
+				},
+				// Like the other backends, referencing a value that isn't stored is a no-op.
+				Change::Reference(col, key) => {
+					let key = key.as_ref().to_vec();
+					let value = match pending.get(&(col, key.clone())) {
+						Some(value) => value.clone(),
+						None => match handle_err(self.0.get(col as u8, &key)) {
+							Some(value) => value,
+							None => continue,
+						},
+					};
+					(col as u8, key, Some(value))
+				},
+				Change::Release(col, key) => (col as u8, key.as_ref().to_vec(), None),
+			});
+		}
+		handle_err(self.0.commit(changes));
 
 		Ok(())
 	}
@@ -63,8 +147,4 @@ impl<H: Clone> Database<H> for DbAdapter {
 	fn get(&self, col: ColumnId, key: &[u8]) -> Option<Vec<u8>> {
 		handle_err(self.0.get(col as u8, key))
 	}
-
-	fn lookup(&self, _hash: &H) -> Option<Vec<u8>> {
-		unimplemented!();
-	}
 }
@@ -0,0 +1,392 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Point-in-time snapshots of a full node database.
+//!
+//! A snapshot is a directory holding a copy of the database next to a manifest that records
+//! the genesis hash, the best and finalized block and the state root of the finalized block.
+//!
+//! RocksDB snapshots are taken through a secondary instance, which sees the database as it
+//! was when the instance was opened, so the node may keep running. ParityDB can not be opened
+//! twice, its snapshots copy the database files under the database lock and fail while the
+//! node is running.
+
+use std::fs;
+use std::path::Path;
+
+use codec::{Decode, Encode};
+use sp_blockchain::{Error as ClientError, Result as ClientResult};
+use sp_runtime::generic::BlockId;
+use sp_runtime::traits::{Block as BlockT, Hash as HashT, HashFor, Header as HeaderT, NumberFor};
+use crate::utils::{self, DatabaseType};
+use crate::{columns, BlocksPruning, DatabaseSettings, DatabaseSettingsSrc, PruningMode, TransactionStorageMode};
+
+/// Manifest file name.
+const MANIFEST_FILE_NAME: &str = "manifest";
+
+/// Database backend a snapshot was taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum SnapshotDatabase {
+	/// RocksDB database.
+	RocksDb,
+	/// ParityDB database.
+	ParityDb,
+}
+
+impl SnapshotDatabase {
+	/// Name of the database directory within the snapshot.
+	fn dir_name(&self) -> &'static str {
+		match self {
+			SnapshotDatabase::RocksDb => "db",
+			SnapshotDatabase::ParityDb => "paritydb",
+		}
+	}
+
+	fn settings(&self, path: &Path) -> DatabaseSettings {
+		let source = match self {
+			SnapshotDatabase::RocksDb => DatabaseSettingsSrc::RocksDb { path: path.to_owned(), cache_size: 128 },
+			SnapshotDatabase::ParityDb => DatabaseSettingsSrc::ParityDb { path: path.to_owned() },
+		};
+		DatabaseSettings {
+			state_cache_size: 0,
+			state_cache_child_ratio: None,
+			trie_cache_size: 0,
+			pruning: PruningMode::ArchiveAll,
+			keep_blocks: BlocksPruning::KeepAll,
+			transaction_storage: TransactionStorageMode::BlockBody,
+			source,
+		}
+	}
+}
+
+/// Description of the chain state contained in a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct SnapshotManifest<Hash, Number> {
+	/// Database backend of the snapshot.
+	pub database: SnapshotDatabase,
+	/// Hash of the genesis block.
+	pub genesis_hash: Hash,
+	/// Hash of the best block.
+	pub best_hash: Hash,
+	/// Number of the best block.
+	pub best_number: Number,
+	/// Hash of the last finalized block.
+	pub finalized_hash: Hash,
+	/// Number of the last finalized block.
+	pub finalized_number: Number,
+	/// State root of the last finalized block.
+	pub state_root: Hash,
+}
+
+/// Manifest type for the given block type.
+pub type ManifestFor<Block> = SnapshotManifest<<Block as BlockT>::Hash, NumberFor<Block>>;
+
+/// Copies the full node database described by `source` into a new snapshot directory at
+/// `target` and writes the manifest.
+pub fn create_snapshot<Block: BlockT>(
+	source: &DatabaseSettingsSrc,
+	target: &Path,
+) -> ClientResult<ManifestFor<Block>> {
+	if !is_empty(target) {
+		return Err(ClientError::Backend(format!("Snapshot target {} is not empty", target.display())));
+	}
+	let (database, path) = match source {
+		DatabaseSettingsSrc::RocksDb { path, .. } => (SnapshotDatabase::RocksDb, path),
+		DatabaseSettingsSrc::ParityDb { path } => (SnapshotDatabase::ParityDb, path),
+		DatabaseSettingsSrc::Custom(_) => return Err(ClientError::Backend(
+			"Cannot snapshot custom database implementation".into(),
+		)),
+	};
+
+	let db_path = target.join(database.dir_name());
+	match database {
+		SnapshotDatabase::RocksDb => checkpoint_rocksdb::<Block>(path, &db_path)?,
+		SnapshotDatabase::ParityDb => {
+			// Holding the lock keeps the node from opening the database while it is copied.
+			let _lock = lock_paritydb(path)?;
+			copy_dir(path, &db_path)?
+		},
+	}
+
+	let manifest = read_manifest_from_db::<Block>(database, &db_path)?;
+	fs::write(target.join(MANIFEST_FILE_NAME), manifest.encode()).map_err(db_err)?;
+	Ok(manifest)
+}
+
+/// Reads the manifest of the snapshot at `path`.
+pub fn read_manifest<Block: BlockT>(path: &Path) -> ClientResult<ManifestFor<Block>> {
+	let manifest = fs::read(path.join(MANIFEST_FILE_NAME)).map_err(db_err)?;
+	Decode::decode(&mut manifest.as_slice())
+		.map_err(|e| ClientError::Backend(format!("Error decoding snapshot manifest: {}", e.what())))
+}
+
+/// Restores the snapshot at `snapshot` into the database described by `target`.
+///
+/// The manifest must match `genesis_hash` and the snapshot database, and the state of the
+/// finalized block must be present. If the target database already exists it must contain the
+/// finalized block of the snapshot, as it does after an earlier restore, and `None` is returned
+/// without touching it.
+pub fn restore_snapshot<Block: BlockT>(
+	snapshot: &Path,
+	target: &DatabaseSettingsSrc,
+	genesis_hash: Block::Hash,
+) -> ClientResult<Option<ManifestFor<Block>>> {
+	let manifest = read_manifest::<Block>(snapshot)?;
+	let target = match (manifest.database, target) {
+		(SnapshotDatabase::RocksDb, DatabaseSettingsSrc::RocksDb { path, .. }) => path,
+		(SnapshotDatabase::ParityDb, DatabaseSettingsSrc::ParityDb { path }) => path,
+		(database, _) => return Err(ClientError::Backend(
+			format!("Snapshot of a {:?} database can not be restored into {}", database, target),
+		)),
+	};
+	if manifest.genesis_hash != genesis_hash {
+		return Err(ClientError::Backend(format!(
+			"Snapshot genesis hash {} does not match the chain genesis hash {}",
+			manifest.genesis_hash,
+			genesis_hash,
+		)));
+	}
+	if !is_empty(target) {
+		if contains_snapshot::<Block>(&manifest, target)? {
+			return Ok(None);
+		}
+		return Err(ClientError::Backend(format!(
+			"Database {} already exists and does not contain the snapshot",
+			target.display(),
+		)));
+	}
+
+	let db_path = snapshot.join(manifest.database.dir_name());
+	let db_manifest = read_manifest_from_db::<Block>(manifest.database, &db_path)?;
+	if db_manifest != manifest {
+		return Err(ClientError::Backend("Snapshot database does not match its manifest".into()));
+	}
+
+	copy_dir(&db_path, target)?;
+	Ok(Some(manifest))
+}
+
+/// Checks whether the database at `path` has the finalized block of `manifest` in its
+/// canonical chain.
+fn contains_snapshot<Block: BlockT>(manifest: &ManifestFor<Block>, path: &Path) -> ClientResult<bool> {
+	let db = utils::open_database::<Block>(&manifest.database.settings(path), DatabaseType::Full)?;
+	let meta = utils::read_meta::<Block>(&*db, columns::HEADER)?;
+	if meta.genesis_hash != manifest.genesis_hash || meta.finalized_number < manifest.finalized_number {
+		return Ok(false);
+	}
+	let header = utils::read_header::<Block>(
+		&*db,
+		columns::KEY_LOOKUP,
+		columns::HEADER,
+		BlockId::Number(manifest.finalized_number),
+	)?;
+	Ok(header.map_or(false, |header| header.hash() == manifest.finalized_hash))
+}
+
+/// Opens the snapshot database and describes the chain state it contains.
+fn read_manifest_from_db<Block: BlockT>(
+	database: SnapshotDatabase,
+	path: &Path,
+) -> ClientResult<ManifestFor<Block>> {
+	let db = utils::open_database::<Block>(&database.settings(path), DatabaseType::Full)?;
+	let meta = utils::read_meta::<Block>(&*db, columns::HEADER)?;
+	let finalized = utils::require_header::<Block>(
+		&*db,
+		columns::KEY_LOOKUP,
+		columns::HEADER,
+		BlockId::Hash(meta.finalized_hash),
+	)?;
+	let state_root = *finalized.state_root();
+	let empty_root = <HashFor<Block> as HashT>::trie_root(Vec::new());
+	if state_root != empty_root && !db.contains(columns::STATE, state_root.as_ref()) {
+		return Err(ClientError::Backend(format!("State of the finalized block {} is missing", meta.finalized_hash)));
+	}
+
+	Ok(SnapshotManifest {
+		database,
+		genesis_hash: meta.genesis_hash,
+		best_hash: meta.best_hash,
+		best_number: meta.best_number,
+		finalized_hash: meta.finalized_hash,
+		finalized_number: meta.finalized_number,
+		state_root,
+	})
+}
+
+/// Copies the RocksDB database at `source` into a new database at `target`, reading it
+/// through a secondary instance so that the primary may stay open.
+#[cfg(any(feature = "with-kvdb-rocksdb", test))]
+fn checkpoint_rocksdb<Block: BlockT>(source: &Path, target: &Path) -> ClientResult<()> {
+	use kvdb::KeyValueDB;
+
+	/// Number of keys written to the target in one batch.
+	const BATCH_SIZE: usize = 10_000;
+
+	let secondary_path = target.with_extension("secondary");
+	let mut source_config = kvdb_rocksdb::DatabaseConfig::with_columns(utils::NUM_COLUMNS);
+	source_config.secondary = Some(path_str(&secondary_path)?.into());
+	let source_db = kvdb_rocksdb::Database::open(&source_config, path_str(source)?).map_err(db_err)?;
+	source_db.try_catch_up_with_primary().map_err(db_err)?;
+
+	crate::upgrade::upgrade_db::<Block>(target, DatabaseType::Full)?;
+	let target_config = kvdb_rocksdb::DatabaseConfig::with_columns(utils::NUM_COLUMNS);
+	let target_db = kvdb_rocksdb::Database::open(&target_config, path_str(target)?).map_err(db_err)?;
+	for column in 0..utils::NUM_COLUMNS {
+		let mut transaction = target_db.transaction();
+		let mut batch = 0;
+		for (key, value) in source_db.iter(column) {
+			transaction.put_vec(column, &key, value.into_vec());
+			batch += 1;
+			if batch == BATCH_SIZE {
+				target_db.write(std::mem::replace(&mut transaction, target_db.transaction())).map_err(db_err)?;
+				batch = 0;
+			}
+		}
+		target_db.write(transaction).map_err(db_err)?;
+	}
+
+	drop(source_db);
+	fs::remove_dir_all(&secondary_path).map_err(db_err)
+}
+
+#[cfg(not(any(feature = "with-kvdb-rocksdb", test)))]
+fn checkpoint_rocksdb<Block: BlockT>(_source: &Path, _target: &Path) -> ClientResult<()> {
+	Err(ClientError::Backend("`with-kvdb-rocksdb` feature not enabled, database can not be opened".into()))
+}
+
+/// Takes the lock of the ParityDB database at `path`, failing if it is open.
+#[cfg(feature = "with-parity-db")]
+fn lock_paritydb(path: &Path) -> ClientResult<crate::parity_db::DbLock> {
+	crate::parity_db::lock(path).map_err(db_err)
+}
+
+#[cfg(not(feature = "with-parity-db"))]
+fn lock_paritydb(_path: &Path) -> ClientResult<()> {
+	Err(ClientError::Backend("`with-parity-db` feature not enabled, database can not be opened".into()))
+}
+
+/// Recursively copies the directory at `from` to `to`.
+fn copy_dir(from: &Path, to: &Path) -> ClientResult<()> {
+	fs::create_dir_all(to).map_err(db_err)?;
+	for entry in fs::read_dir(from).map_err(db_err)? {
+		let entry = entry.map_err(db_err)?;
+		let target = to.join(entry.file_name());
+		if entry.file_type().map_err(db_err)?.is_dir() {
+			copy_dir(&entry.path(), &target)?;
+		} else {
+			fs::copy(entry.path(), target).map_err(db_err)?;
+		}
+	}
+	Ok(())
+}
+
+fn is_empty(path: &Path) -> bool {
+	path.read_dir().map_or(true, |mut d| d.next().is_none())
+}
+
+#[cfg(any(feature = "with-kvdb-rocksdb", test))]
+fn path_str(path: &Path) -> ClientResult<&str> {
+	path.to_str().ok_or_else(|| ClientError::Backend("Invalid database path".into()))
+}
+
+fn db_err(err: std::io::Error) -> ClientError {
+	ClientError::Backend(format!("{}", err))
+}
+
+#[cfg(test)]
+mod tests {
+	use sc_client_api::backend::Backend as _;
+	use sp_blockchain::HeaderBackend;
+	use crate::Backend;
+	use crate::tests::{Block, insert_header};
+	use super::*;
+
+	fn create_db(path: &Path) -> Backend<Block> {
+		let settings = SnapshotDatabase::RocksDb.settings(path);
+		let backend = Backend::<Block>::new(settings, 0).unwrap();
+		let mut parent = Default::default();
+		for number in 0..4 {
+			parent = insert_header(&backend, number, parent, None, Default::default());
+		}
+		backend
+	}
+
+	#[test]
+	fn snapshot_of_open_database_restores() {
+		let dir = tempfile::TempDir::new().unwrap();
+		let source = DatabaseSettingsSrc::RocksDb { path: dir.path().join("source"), cache_size: 16 };
+		let backend = create_db(&dir.path().join("source"));
+		let info = backend.blockchain().info();
+
+		let manifest = create_snapshot::<Block>(&source, &dir.path().join("snapshot")).unwrap();
+		assert_eq!(manifest.database, SnapshotDatabase::RocksDb);
+		assert_eq!(manifest.genesis_hash, info.genesis_hash);
+		assert_eq!((manifest.best_hash, manifest.best_number), (info.best_hash, info.best_number));
+		assert_eq!(read_manifest::<Block>(&dir.path().join("snapshot")).unwrap(), manifest);
+
+		// Blocks imported after the snapshot was taken are not part of it.
+		insert_header(&backend, 4, info.best_hash, None, Default::default());
+		drop(backend);
+
+		let target = DatabaseSettingsSrc::RocksDb { path: dir.path().join("target"), cache_size: 16 };
+		assert!(restore_snapshot::<Block>(&dir.path().join("snapshot"), &target, info.genesis_hash).unwrap().is_some());
+		assert!(restore_snapshot::<Block>(&dir.path().join("snapshot"), &target, info.genesis_hash).unwrap().is_none());
+		let restored = Backend::<Block>::new(SnapshotDatabase::RocksDb.settings(&dir.path().join("target")), 0)
+			.unwrap();
+		assert_eq!(restored.blockchain().info().best_hash, info.best_hash);
+	}
+
+	#[test]
+	fn restore_checks_manifest() {
+		let dir = tempfile::TempDir::new().unwrap();
+		let source = DatabaseSettingsSrc::RocksDb { path: dir.path().join("source"), cache_size: 16 };
+		let genesis_hash = create_db(&dir.path().join("source")).blockchain().info().genesis_hash;
+		create_snapshot::<Block>(&source, &dir.path().join("snapshot")).unwrap();
+		assert!(create_snapshot::<Block>(&source, &dir.path().join("snapshot")).is_err());
+
+		let target = DatabaseSettingsSrc::RocksDb { path: dir.path().join("target"), cache_size: 16 };
+		assert!(restore_snapshot::<Block>(&dir.path().join("snapshot"), &target, Default::default()).is_err());
+		// The source database contains the snapshot, restoring into it again is a no-op.
+		assert_eq!(restore_snapshot::<Block>(&dir.path().join("snapshot"), &source, genesis_hash).unwrap(), None);
+
+		let other = DatabaseSettingsSrc::RocksDb { path: dir.path().join("other"), cache_size: 16 };
+		let backend = Backend::<Block>::new(SnapshotDatabase::RocksDb.settings(&dir.path().join("other")), 0)
+			.unwrap();
+		insert_header(&backend, 0, Default::default(), None, [1; 32].into());
+		drop(backend);
+		assert!(restore_snapshot::<Block>(&dir.path().join("snapshot"), &other, genesis_hash).is_err());
+
+		let mut manifest = read_manifest::<Block>(&dir.path().join("snapshot")).unwrap();
+		manifest.best_number += 1;
+		fs::write(dir.path().join("snapshot").join(MANIFEST_FILE_NAME), manifest.encode()).unwrap();
+		assert!(restore_snapshot::<Block>(&dir.path().join("snapshot"), &target, genesis_hash).is_err());
+	}
+
+	#[cfg(feature = "with-parity-db")]
+	#[test]
+	fn snapshot_of_open_paritydb_fails() {
+		let dir = tempfile::TempDir::new().unwrap();
+		let source = DatabaseSettingsSrc::ParityDb { path: dir.path().join("source") };
+		let backend = Backend::<Block>::new(SnapshotDatabase::ParityDb.settings(&dir.path().join("source")), 0)
+			.unwrap();
+		assert!(create_snapshot::<Block>(&source, &dir.path().join("snapshot")).is_err());
+
+		drop(backend);
+		create_snapshot::<Block>(&source, &dir.path().join("snapshot2")).unwrap();
+	}
+}
@@ -20,23 +20,44 @@ use std::fs;
 use std::io::{Read, Write, ErrorKind};
 use std::path::{Path, PathBuf};
 
-use sp_runtime::traits::Block as BlockT;
-use crate::utils::DatabaseType;
+#[cfg(any(feature = "with-kvdb-rocksdb", test))]
+use codec::{Decode, Encode};
+#[cfg(any(feature = "with-kvdb-rocksdb", test))]
+use sp_runtime::{Justifications, traits::Block as BlockT};
+#[cfg(any(feature = "with-kvdb-rocksdb", test))]
+use crate::{columns, utils::DatabaseType};
+#[cfg(any(feature = "with-kvdb-rocksdb", test))]
+use kvdb::KeyValueDB;
+#[cfg(any(feature = "with-kvdb-rocksdb", test))]
+use kvdb_rocksdb::{Database, DatabaseConfig};
 
 /// Version file name.
 const VERSION_FILE_NAME: &'static str = "db_version";
 
 /// Current db version.
-const CURRENT_VERSION: u32 = 1;
+const CURRENT_VERSION: u32 = 3;
+
+/// Number of columns in v1.
+#[cfg(any(feature = "with-kvdb-rocksdb", test))]
+const V1_NUM_COLUMNS: u32 = 11;
+/// Number of columns in v2.
+#[cfg(any(feature = "with-kvdb-rocksdb", test))]
+const V2_NUM_COLUMNS: u32 = 12;
 
 /// Upgrade database to current version.
-pub fn upgrade_db<Block: BlockT>(db_path: &Path, _db_type: DatabaseType) -> sp_blockchain::Result<()> {
+#[cfg(any(feature = "with-kvdb-rocksdb", test))]
+pub fn upgrade_db<Block: BlockT>(db_path: &Path, db_type: DatabaseType) -> sp_blockchain::Result<()> {
 	let is_empty = db_path.read_dir().map_or(true, |mut d| d.next().is_none());
 	if !is_empty {
 		let db_version = current_version(db_path)?;
 		match db_version {
 			0 => Err(sp_blockchain::Error::Backend(format!("Unsupported database version: {}", db_version)))?,
-			1 => (),
+			1 => {
+				migrate_1_to_2::<Block>(db_path, db_type)?;
+				migrate_2_to_3::<Block>(db_path, db_type)?
+			},
+			2 => migrate_2_to_3::<Block>(db_path, db_type)?,
+			CURRENT_VERSION => (),
 			_ => Err(sp_blockchain::Error::Backend(format!("Future database version: {}", db_version)))?,
 		}
 	}
@@ -44,6 +65,62 @@ pub fn upgrade_db<Block: BlockT>(db_path: &Path, _db_type: DatabaseType) -> sp_b
 	update_version(db_path)
 }
 
+/// Check that a ParityDB database has the current version.
+///
+/// ParityDB can't iterate over the values of a column, so the values of an older database can't
+/// be migrated. Such databases, including those created before ParityDB databases were
+/// versioned, are refused and have to be synced again.
+#[cfg(feature = "with-parity-db")]
+pub fn check_parity_db_version(db_path: &Path) -> sp_blockchain::Result<()> {
+	let is_empty = db_path.read_dir().map_or(true, |mut d| d.next().is_none());
+	if !is_empty {
+		let db_version = current_version(db_path)?;
+		if db_version != CURRENT_VERSION {
+			return Err(sp_blockchain::Error::Backend(format!(
+				"Unsupported ParityDB database version: {}, expected {}. ParityDB databases can't \
+				be upgraded, remove the database and sync again.",
+				db_version,
+				CURRENT_VERSION,
+			)));
+		}
+	}
+
+	update_version(db_path)
+}
+
+/// Migration from version1 to version2:
+/// 1) the number of columns has changed from 11 to 12;
+/// 2) transactions column is added;
+#[cfg(any(feature = "with-kvdb-rocksdb", test))]
+fn migrate_1_to_2<Block: BlockT>(db_path: &Path, _db_type: DatabaseType) -> sp_blockchain::Result<()> {
+	let db_path = db_path.to_str()
+		.ok_or_else(|| sp_blockchain::Error::Backend("Invalid database path".into()))?;
+	let db_cfg = DatabaseConfig::with_columns(V1_NUM_COLUMNS);
+	let db = Database::open(&db_cfg, db_path).map_err(db_err)?;
+	db.add_column().map_err(db_err)
+}
+
+/// Migration from version2 to version3:
+/// - the format of the stored justification changed to support multiple justifications.
+#[cfg(any(feature = "with-kvdb-rocksdb", test))]
+fn migrate_2_to_3<Block: BlockT>(db_path: &Path, _db_type: DatabaseType) -> sp_blockchain::Result<()> {
+	let db_path = db_path.to_str()
+		.ok_or_else(|| sp_blockchain::Error::Backend("Invalid database path".into()))?;
+	let db_cfg = DatabaseConfig::with_columns(V2_NUM_COLUMNS);
+	let db = Database::open(&db_cfg, db_path).map_err(db_err)?;
+
+	let mut transaction = db.transaction();
+	for (key, justification) in db.iter(columns::JUSTIFICATIONS) {
+		let justification = Vec::<u8>::decode(&mut &justification[..])
+			.map_err(|_| sp_blockchain::Error::Backend("Invalid justification blob".into()))?;
+		// Tag each justification with the GRANDPA engine id, the only engine that used to store
+		// justifications, without depending on the GRANDPA crate.
+		let justifications = Justifications::from((*b"FRNK", justification));
+		transaction.put_vec(columns::JUSTIFICATIONS, &key, justifications.encode());
+	}
+	db.write(transaction).map_err(db_err)
+}
+
 
 /// Reads current database version from the file at given path.
 /// If the file does not exist returns 0.
@@ -85,7 +162,7 @@ fn version_file_path(path: &Path) -> PathBuf {
 #[cfg(test)]
 mod tests {
 	use sc_state_db::PruningMode;
-	use crate::{DatabaseSettings, DatabaseSettingsSrc};
+	use crate::{BlocksPruning, DatabaseSettings, DatabaseSettingsSrc, TransactionStorageMode};
 	use crate::tests::Block;
 	use super::*;
 
@@ -101,7 +178,10 @@ mod tests {
 		crate::utils::open_database::<Block>(&DatabaseSettings {
 			state_cache_size: 0,
 			state_cache_child_ratio: None,
+			trie_cache_size: 0,
 			pruning: PruningMode::ArchiveAll,
+			keep_blocks: BlocksPruning::KeepAll,
+			transaction_storage: TransactionStorageMode::BlockBody,
 			source: DatabaseSettingsSrc::RocksDb { path: db_path.to_owned(), cache_size: 128 },
 		}, DatabaseType::Full).map(|_| ())
 	}
@@ -113,6 +193,44 @@ mod tests {
 		assert!(open_database(db_dir.path()).is_err());
 	}
 
+	#[test]
+	fn upgrade_from_1_to_2_works() {
+		let db_dir = tempfile::TempDir::new().unwrap();
+		{
+			let db_path = db_dir.path().to_str().unwrap();
+			let db_cfg = DatabaseConfig::with_columns(V1_NUM_COLUMNS);
+			Database::open(&db_cfg, db_path).unwrap();
+		}
+		create_db(db_dir.path(), Some(1));
+		open_database(db_dir.path()).unwrap();
+		assert_eq!(current_version(db_dir.path()).unwrap(), CURRENT_VERSION);
+	}
+
+	#[test]
+	fn upgrade_from_2_to_3_works() {
+		let db_dir = tempfile::TempDir::new().unwrap();
+		let key = [1u8; 8];
+		{
+			let db_path = db_dir.path().to_str().unwrap();
+			let db_cfg = DatabaseConfig::with_columns(V2_NUM_COLUMNS);
+			let db = Database::open(&db_cfg, db_path).unwrap();
+			let mut transaction = db.transaction();
+			transaction.put_vec(columns::JUSTIFICATIONS, &key, vec![1u8, 2, 3].encode());
+			db.write(transaction).unwrap();
+		}
+		create_db(db_dir.path(), Some(2));
+		open_database(db_dir.path()).unwrap();
+		assert_eq!(current_version(db_dir.path()).unwrap(), CURRENT_VERSION);
+
+		let db_path = db_dir.path().to_str().unwrap();
+		let db = Database::open(&DatabaseConfig::with_columns(V2_NUM_COLUMNS), db_path).unwrap();
+		let stored = db.get(columns::JUSTIFICATIONS, &key).unwrap().unwrap();
+		assert_eq!(
+			Justifications::decode(&mut &stored[..]).unwrap(),
+			Justifications::from((*b"FRNK", vec![1u8, 2, 3])),
+		);
+	}
+
 	#[test]
 	fn open_empty_database_works() {
 		let db_dir = tempfile::TempDir::new().unwrap();
@@ -120,4 +238,20 @@ mod tests {
 		open_database(db_dir.path()).unwrap();
 		assert_eq!(current_version(db_dir.path()).unwrap(), CURRENT_VERSION);
 	}
+
+	#[cfg(feature = "with-parity-db")]
+	#[test]
+	fn parity_db_version_is_checked() {
+		let db_dir = tempfile::TempDir::new().unwrap();
+		check_parity_db_version(db_dir.path()).unwrap();
+		check_parity_db_version(db_dir.path()).unwrap();
+		assert_eq!(current_version(db_dir.path()).unwrap(), CURRENT_VERSION);
+
+		create_db(db_dir.path(), Some(2));
+		assert!(check_parity_db_version(db_dir.path()).is_err());
+
+		fs::remove_file(version_file_path(db_dir.path())).unwrap();
+		fs::write(db_dir.path().join("metadata"), b"").unwrap();
+		assert!(check_parity_db_version(db_dir.path()).is_err());
+	}
 }
@@ -18,8 +18,20 @@
 
 //! This module implements sandboxing support in the runtime.
 //!
-//! Sandboxing is baked by wasmi at the moment. In future, however, we would like to add/switch to
-//! a compiled execution engine.
+//! Sandboxed modules are executed by one of the backends listed in [`SandboxBackend`]. The
+//! wasmi interpreter is always available. Compiling sandboxed modules with wasmtime requires the
+//! `wasmtime-sandbox` feature.
+//!
+//! Both backends are expected to produce the same results for the same module. Memories are
+//! limited to the same number of pages and wasmtime only accepts the wasm MVP feature set, just
+//! like wasmi. Neither backend meters execution on its own: the supervisor is expected to inject
+//! gas counters and a stack height limiter into the module before instantiating it, as the
+//! contracts pallet does.
+
+#[cfg(feature = "wasmtime-sandbox")]
+mod wasmtime_backend;
+#[cfg(test)]
+mod tests;
 
 use crate::error::{Result, Error};
 use std::{collections::HashMap, rc::Rc};
@@ -27,9 +39,91 @@ use codec::{Decode, Encode};
 use sp_core::sandbox as sandbox_primitives;
 use wasmi::{
 	Externals, ImportResolver, MemoryInstance, MemoryRef, Module, ModuleInstance,
-	ModuleRef, RuntimeArgs, RuntimeValue, Trap, TrapKind, memory_units::Pages,
+	ModuleRef, RuntimeArgs, RuntimeValue, Trap, memory_units::Pages,
 };
-use sp_wasm_interface::{FunctionContext, Pointer, WordSize};
+use sp_wasm_interface::{FunctionContext, Pointer, Value, WordSize};
+
+/// The maximum number of pages a sandboxed memory can grow to.
+///
+/// This is the limit imposed by the 32-bit address space of wasm. It applies to every backend
+/// so that a memory that can grow on one backend can grow on all of them.
+pub const MAX_MEMORY_PAGES: u32 = 65536;
+
+/// The engine that executes sandboxed modules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SandboxBackend {
+	/// Interpret sandboxed modules with wasmi.
+	Wasmi,
+
+	/// Compile sandboxed modules with wasmtime.
+	#[cfg(feature = "wasmtime-sandbox")]
+	Wasmtime,
+
+	/// Compile sandboxed modules with wasmtime if the `wasmtime-sandbox` feature is enabled
+	/// and fall back to wasmi otherwise.
+	TryWasmtime,
+}
+
+impl Default for SandboxBackend {
+	fn default() -> Self {
+		SandboxBackend::Wasmi
+	}
+}
+
+/// Backend specific state that is shared between all memories and instances of a [`Store`].
+#[derive(Clone)]
+enum BackendContext {
+	Wasmi,
+	#[cfg(feature = "wasmtime-sandbox")]
+	Wasmtime(wasmtime_backend::Backend),
+}
+
+impl BackendContext {
+	fn new(backend: SandboxBackend) -> Self {
+		match backend {
+			SandboxBackend::Wasmi => BackendContext::Wasmi,
+			#[cfg(not(feature = "wasmtime-sandbox"))]
+			SandboxBackend::TryWasmtime => BackendContext::Wasmi,
+			#[cfg(feature = "wasmtime-sandbox")]
+			SandboxBackend::Wasmtime | SandboxBackend::TryWasmtime =>
+				BackendContext::Wasmtime(Default::default()),
+		}
+	}
+}
+
+/// A linear memory of a sandboxed module.
+#[derive(Clone)]
+pub enum Memory {
+	/// A memory of the wasmi backend.
+	Wasmi(MemoryRef),
+	/// A memory of the wasmtime backend.
+	#[cfg(feature = "wasmtime-sandbox")]
+	Wasmtime(wasmtime::Memory),
+}
+
+impl Memory {
+	/// Provides direct read access to the contents of the memory.
+	///
+	/// The memory must not be grown while `f` is running.
+	pub fn with_direct_access<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
+		match self {
+			Memory::Wasmi(memory) => memory.with_direct_access(f),
+			#[cfg(feature = "wasmtime-sandbox")]
+			Memory::Wasmtime(memory) => wasmtime_backend::with_direct_access(memory, f),
+		}
+	}
+
+	/// Provides direct write access to the contents of the memory.
+	///
+	/// The memory must not be grown while `f` is running.
+	pub fn with_direct_access_mut<R, F: FnOnce(&mut [u8]) -> R>(&self, f: F) -> R {
+		match self {
+			Memory::Wasmi(memory) => memory.with_direct_access_mut(f),
+			#[cfg(feature = "wasmtime-sandbox")]
+			Memory::Wasmtime(memory) => wasmtime_backend::with_direct_access_mut(memory, f),
+		}
+	}
+}
 
 /// Index of a function inside the supervisor.
 ///
@@ -48,7 +142,7 @@ impl From<SupervisorFuncIndex> for usize {
 ///
 /// This index is supposed to be used with as index for `Externals`.
 #[derive(Copy, Clone, Debug, PartialEq)]
-struct GuestFuncIndex(usize);
+pub(crate) struct GuestFuncIndex(usize);
 
 /// This struct holds a mapping from guest index space to supervisor.
 struct GuestToSupervisorFunctionMapping {
@@ -73,7 +167,25 @@ impl GuestToSupervisorFunctionMapping {
 
 struct Imports {
 	func_map: HashMap<(Vec<u8>, Vec<u8>), GuestFuncIndex>,
-	memories_map: HashMap<(Vec<u8>, Vec<u8>), MemoryRef>,
+	memories_map: HashMap<(Vec<u8>, Vec<u8>), Memory>,
+}
+
+impl Imports {
+	fn func_by_name(&self, module_name: &str, field_name: &str) -> Option<GuestFuncIndex> {
+		let key = (
+			module_name.as_bytes().to_owned(),
+			field_name.as_bytes().to_owned(),
+		);
+		self.func_map.get(&key).cloned()
+	}
+
+	fn memory_by_name(&self, module_name: &str, field_name: &str) -> Option<Memory> {
+		let key = (
+			module_name.as_bytes().to_vec(),
+			field_name.as_bytes().to_vec(),
+		);
+		self.memories_map.get(&key).cloned()
+	}
 }
 
 impl ImportResolver for Imports {
@@ -83,11 +195,7 @@ impl ImportResolver for Imports {
 		field_name: &str,
 		signature: &::wasmi::Signature,
 	) -> std::result::Result<wasmi::FuncRef, wasmi::Error> {
-		let key = (
-			module_name.as_bytes().to_owned(),
-			field_name.as_bytes().to_owned(),
-		);
-		let idx = *self.func_map.get(&key).ok_or_else(|| {
+		let idx = self.func_by_name(module_name, field_name).ok_or_else(|| {
 			wasmi::Error::Instantiation(format!(
 				"Export {}:{} not found",
 				module_name, field_name
@@ -102,20 +210,18 @@ impl ImportResolver for Imports {
 		field_name: &str,
 		_memory_type: &::wasmi::MemoryDescriptor,
 	) -> std::result::Result<MemoryRef, wasmi::Error> {
-		let key = (
-			module_name.as_bytes().to_vec(),
-			field_name.as_bytes().to_vec(),
-		);
-		let mem = self.memories_map
-			.get(&key)
-			.ok_or_else(|| {
-				wasmi::Error::Instantiation(format!(
-					"Export {}:{} not found",
-					module_name, field_name
-				))
-			})?
-			.clone();
-		Ok(mem)
+		match self.memory_by_name(module_name, field_name) {
+			Some(Memory::Wasmi(mem)) => Ok(mem),
+			#[cfg(feature = "wasmtime-sandbox")]
+			Some(Memory::Wasmtime(_)) => Err(wasmi::Error::Instantiation(format!(
+				"Export {}:{} belongs to another backend",
+				module_name, field_name
+			))),
+			None => Err(wasmi::Error::Instantiation(format!(
+				"Export {}:{} not found",
+				module_name, field_name
+			))),
+		}
 	}
 
 	fn resolve_global(
@@ -179,55 +285,47 @@ pub trait SandboxCapabilities: FunctionContext {
 /// [`Externals`]: ../wasmi/trait.Externals.html
 pub struct GuestExternals<'a, FE: SandboxCapabilities + 'a> {
 	supervisor_externals: &'a mut FE,
-	sandbox_instance: &'a SandboxInstance<FE::SupervisorFuncRef>,
+	dispatch_thunk: &'a FE::SupervisorFuncRef,
+	guest_to_supervisor_mapping: &'a GuestToSupervisorFunctionMapping,
 	state: u32,
 }
 
-fn trap(msg: &'static str) -> Trap {
-	TrapKind::Host(Box::new(Error::Other(msg.into()))).into()
-}
-
-fn deserialize_result(serialized_result: &[u8]) -> std::result::Result<Option<RuntimeValue>, Trap> {
+fn deserialize_result(serialized_result: &[u8]) -> Result<Option<Value>> {
 	use self::sandbox_primitives::HostError;
 	use sp_wasm_interface::ReturnValue;
 	let result_val = std::result::Result::<ReturnValue, HostError>::decode(&mut &serialized_result[..])
-		.map_err(|_| trap("Decoding Result<ReturnValue, HostError> failed!"))?;
+		.map_err(|_| "Decoding Result<ReturnValue, HostError> failed!")?;
 
 	match result_val {
 		Ok(return_value) => Ok(match return_value {
 			ReturnValue::Unit => None,
-			ReturnValue::Value(typed_value) => Some(RuntimeValue::from(typed_value)),
+			ReturnValue::Value(typed_value) => Some(typed_value),
 		}),
-		Err(HostError) => Err(trap("Supervisor function returned sandbox::HostError")),
+		Err(HostError) => Err("Supervisor function returned sandbox::HostError".into()),
 	}
 }
 
-impl<'a, FE: SandboxCapabilities + 'a> Externals for GuestExternals<'a, FE> {
-	fn invoke_index(
-		&mut self,
-		index: usize,
-		args: RuntimeArgs,
-	) -> std::result::Result<Option<RuntimeValue>, Trap> {
-		// Make `index` typesafe again.
-		let index = GuestFuncIndex(index);
+/// Dispatches calls of a guest to the functions of the supervisor it imports.
+///
+/// This is what the backends call into when a guest invokes one of its imports.
+pub(crate) trait GuestDispatch {
+	/// Call the supervisor function that is imported by the guest under `index`.
+	fn dispatch(&mut self, index: GuestFuncIndex, args: &[Value]) -> Result<Option<Value>>;
+}
 
-		let func_idx = self.sandbox_instance
-			.guest_to_supervisor_mapping
+impl<'a, FE: SandboxCapabilities + 'a> GuestDispatch for GuestExternals<'a, FE> {
+	fn dispatch(&mut self, index: GuestFuncIndex, args: &[Value]) -> Result<Option<Value>> {
+		let func_idx = self.guest_to_supervisor_mapping
 			.func_by_guest_index(index)
 			.expect(
-				"`invoke_index` is called with indexes registered via `FuncInstance::alloc_host`;
-					`FuncInstance::alloc_host` is called with indexes that was obtained from `guest_to_supervisor_mapping`;
+				"`dispatch` is called with indexes registered via `GuestToSupervisorFunctionMapping::define`;
+					the backends only resolve imports to indexes obtained from `guest_to_supervisor_mapping`;
 					`func_by_guest_index` called with `index` can't return `None`;
 					qed"
 			);
 
 		// Serialize arguments into a byte vector.
-		let invoke_args_data: Vec<u8> = args.as_ref()
-			.iter()
-			.cloned()
-			.map(sp_wasm_interface::Value::from)
-			.collect::<Vec<_>>()
-			.encode();
+		let invoke_args_data: Vec<u8> = args.encode();
 
 		let state = self.state;
 
@@ -237,13 +335,13 @@ impl<'a, FE: SandboxCapabilities + 'a> Externals for GuestExternals<'a, FE> {
 		let invoke_args_ptr = self
 			.supervisor_externals
 			.allocate_memory(invoke_args_len)
-			.map_err(|_| trap("Can't allocate memory in supervisor for the arguments"))?;
+			.map_err(|_| "Can't allocate memory in supervisor for the arguments")?;
 
-		let deallocate = |this: &mut GuestExternals<FE>, ptr, fail_msg| {
+		let deallocate = |this: &mut GuestExternals<FE>, ptr, fail_msg: &'static str| {
 			this
 				.supervisor_externals
 				.deallocate_memory(ptr)
-				.map_err(|_| trap(fail_msg))
+				.map_err(|_| Error::from(fail_msg))
 		};
 
 		if self
@@ -252,11 +350,11 @@ impl<'a, FE: SandboxCapabilities + 'a> Externals for GuestExternals<'a, FE> {
 			.is_err()
 		{
 			deallocate(self, invoke_args_ptr, "Failed dealloction after failed write of invoke arguments")?;
-			return Err(trap("Can't write invoke args into memory"));
+			return Err("Can't write invoke args into memory".into());
 		}
 
 		let result = self.supervisor_externals.invoke(
-			&self.sandbox_instance.dispatch_thunk,
+			self.dispatch_thunk,
 			invoke_args_ptr,
 			invoke_args_len,
 			state,
@@ -278,7 +376,7 @@ impl<'a, FE: SandboxCapabilities + 'a> Externals for GuestExternals<'a, FE> {
 
 		let serialized_result_val = self.supervisor_externals
 			.read_memory(serialized_result_val_ptr, serialized_result_val_len)
-			.map_err(|_| trap("Can't read the serialized result from dispatch thunk"));
+			.map_err(|_| Error::from("Can't read the serialized result from dispatch thunk"));
 
 		deallocate(self, serialized_result_val_ptr, "Can't deallocate memory for dispatch thunk's result")
 			.and_then(|_| serialized_result_val)
@@ -286,9 +384,31 @@ impl<'a, FE: SandboxCapabilities + 'a> Externals for GuestExternals<'a, FE> {
 	}
 }
 
+impl<'a, FE: SandboxCapabilities + 'a> Externals for GuestExternals<'a, FE> {
+	fn invoke_index(
+		&mut self,
+		index: usize,
+		args: RuntimeArgs,
+	) -> std::result::Result<Option<RuntimeValue>, Trap> {
+		// Make `index` typesafe again.
+		let index = GuestFuncIndex(index);
+
+		let args = args.as_ref()
+			.iter()
+			.cloned()
+			.map(Value::from)
+			.collect::<Vec<_>>();
+
+		self.dispatch(index, &args)
+			.map(|result| result.map(RuntimeValue::from))
+			.map_err(Trap::from)
+	}
+}
+
 fn with_guest_externals<FE, R, F>(
 	supervisor_externals: &mut FE,
-	sandbox_instance: &SandboxInstance<FE::SupervisorFuncRef>,
+	dispatch_thunk: &FE::SupervisorFuncRef,
+	guest_to_supervisor_mapping: &GuestToSupervisorFunctionMapping,
 	state: u32,
 	f: F,
 ) -> R
@@ -298,12 +418,20 @@ where
 {
 	let mut guest_externals = GuestExternals {
 		supervisor_externals,
-		sandbox_instance,
+		dispatch_thunk,
+		guest_to_supervisor_mapping,
 		state,
 	};
 	f(&mut guest_externals)
 }
 
+/// An instantiated module of one of the backends.
+enum BackendInstance {
+	Wasmi(ModuleRef),
+	#[cfg(feature = "wasmtime-sandbox")]
+	Wasmtime(wasmtime::Instance),
+}
+
 /// Sandboxed instance of a wasm module.
 ///
 /// It's primary purpose is to [`invoke`] exported functions on it.
@@ -319,7 +447,7 @@ where
 ///
 /// [`invoke`]: #method.invoke
 pub struct SandboxInstance<FR> {
-	instance: ModuleRef,
+	backend_instance: BackendInstance,
 	dispatch_thunk: FR,
 	guest_to_supervisor_mapping: GuestToSupervisorFunctionMapping,
 }
@@ -335,17 +463,26 @@ impl<FR> SandboxInstance<FR> {
 	pub fn invoke<FE: SandboxCapabilities<SupervisorFuncRef=FR>>(
 		&self,
 		export_name: &str,
-		args: &[RuntimeValue],
+		args: &[Value],
 		supervisor_externals: &mut FE,
 		state: u32,
-	) -> std::result::Result<Option<wasmi::RuntimeValue>, wasmi::Error> {
+	) -> Result<Option<Value>> {
 		with_guest_externals(
 			supervisor_externals,
-			self,
+			&self.dispatch_thunk,
+			&self.guest_to_supervisor_mapping,
 			state,
-			|guest_externals| {
-				self.instance
-					.invoke_export(export_name, args, guest_externals)
+			|guest_externals| match &self.backend_instance {
+				BackendInstance::Wasmi(instance) => {
+					let args = args.iter().cloned().map(RuntimeValue::from).collect::<Vec<_>>();
+					instance
+						.invoke_export(export_name, &args, guest_externals)
+						.map(|result| result.map(Value::from))
+						.map_err(Error::from)
+				}
+				#[cfg(feature = "wasmtime-sandbox")]
+				BackendInstance::Wasmtime(instance) =>
+					wasmtime_backend::invoke(instance, export_name, args, guest_externals),
 			},
 		)
 	}
@@ -353,13 +490,19 @@ impl<FR> SandboxInstance<FR> {
 	/// Get the value from a global with the given `name`.
 	///
 	/// Returns `Some(_)` if the global could be found.
-	pub fn get_global_val(&self, name: &str) -> Option<sp_wasm_interface::Value> {
-		let global = self.instance
-			.export_by_name(name)?
-			.as_global()?
-			.get();
-
-		Some(global.into())
+	pub fn get_global_val(&self, name: &str) -> Option<Value> {
+		match &self.backend_instance {
+			BackendInstance::Wasmi(instance) => {
+				let global = instance
+					.export_by_name(name)?
+					.as_global()?
+					.get();
+
+				Some(global.into())
+			}
+			#[cfg(feature = "wasmtime-sandbox")]
+			BackendInstance::Wasmtime(instance) => wasmtime_backend::get_global_val(instance, name),
+		}
 	}
 }
 
@@ -380,7 +523,7 @@ pub enum InstantiationError {
 
 fn decode_environment_definition(
 	raw_env_def: &[u8],
-	memories: &[Option<MemoryRef>],
+	memories: &[Option<Memory>],
 ) -> std::result::Result<(Imports, GuestToSupervisorFunctionMapping), InstantiationError> {
 	let env_def = sandbox_primitives::EnvironmentDefinition::decode(&mut &raw_env_def[..])
 		.map_err(|_| InstantiationError::EnvironmentDefinitionCorrupted)?;
@@ -423,6 +566,7 @@ fn decode_environment_definition(
 pub struct GuestEnvironment {
 	imports: Imports,
 	guest_to_supervisor_mapping: GuestToSupervisorFunctionMapping,
+	backend_context: BackendContext,
 }
 
 impl GuestEnvironment {
@@ -438,6 +582,7 @@ impl GuestEnvironment {
 		Ok(Self {
 			imports,
 			guest_to_supervisor_mapping,
+			backend_context: store.backend_context.clone(),
 		})
 	}
 }
@@ -463,7 +608,8 @@ impl<FR> UnregisteredInstance<FR> {
 ///
 /// The guest module's code is specified in `wasm`. Environment that will be available to
 /// guest module is specified in `raw_env_def` (serialized version of [`EnvironmentDefinition`]).
-/// `dispatch_thunk` is used as function that handle calls from guests.
+/// `dispatch_thunk` is used as function that handle calls from guests. The module is
+/// instantiated by the backend of the store that `host_env` was decoded with.
 ///
 /// # Errors
 ///
@@ -480,31 +626,58 @@ pub fn instantiate<'a, FE: SandboxCapabilities>(
 	host_env: GuestEnvironment,
 	state: u32,
 ) -> std::result::Result<UnregisteredInstance<FE::SupervisorFuncRef>, InstantiationError> {
-	let module = Module::from_buffer(wasm).map_err(|_| InstantiationError::ModuleDecoding)?;
-	let instance = ModuleInstance::new(&module, &host_env.imports)
-		.map_err(|_| InstantiationError::Instantiation)?;
+	let backend_instance = match &host_env.backend_context {
+		BackendContext::Wasmi => wasmi_instantiate(
+			supervisor_externals,
+			&dispatch_thunk,
+			wasm,
+			&host_env,
+			state,
+		)?,
+		#[cfg(feature = "wasmtime-sandbox")]
+		BackendContext::Wasmtime(backend) => with_guest_externals(
+			supervisor_externals,
+			&dispatch_thunk,
+			&host_env.guest_to_supervisor_mapping,
+			state,
+			|guest_externals| {
+				wasmtime_backend::instantiate(backend, wasm, &host_env.imports, guest_externals)
+			},
+		)?,
+	};
 
 	let sandbox_instance = Rc::new(SandboxInstance {
-		// In general, it's not a very good idea to use `.not_started_instance()` for anything
-		// but for extracting memory and tables. But in this particular case, we are extracting
-		// for the purpose of running `start` function which should be ok.
-		instance: instance.not_started_instance().clone(),
+		backend_instance,
 		dispatch_thunk,
 		guest_to_supervisor_mapping: host_env.guest_to_supervisor_mapping,
 	});
 
+	Ok(UnregisteredInstance { sandbox_instance })
+}
+
+fn wasmi_instantiate<FE: SandboxCapabilities>(
+	supervisor_externals: &mut FE,
+	dispatch_thunk: &FE::SupervisorFuncRef,
+	wasm: &[u8],
+	host_env: &GuestEnvironment,
+	state: u32,
+) -> std::result::Result<BackendInstance, InstantiationError> {
+	let module = Module::from_buffer(wasm).map_err(|_| InstantiationError::ModuleDecoding)?;
+	let instance = ModuleInstance::new(&module, &host_env.imports)
+		.map_err(|_| InstantiationError::Instantiation)?;
+
 	with_guest_externals(
 		supervisor_externals,
-		&sandbox_instance,
+		dispatch_thunk,
+		&host_env.guest_to_supervisor_mapping,
 		state,
 		|guest_externals| {
 			instance
 				.run_start(guest_externals)
 				.map_err(|_| InstantiationError::StartTrapped)
 		},
-	)?;
-
-	Ok(UnregisteredInstance { sandbox_instance })
+	)
+		.map(BackendInstance::Wasmi)
 }
 
 /// This struct keeps track of all sandboxed components.
@@ -513,20 +686,24 @@ pub fn instantiate<'a, FE: SandboxCapabilities>(
 pub struct Store<FR> {
 	// Memories and instances are `Some` until torn down.
 	instances: Vec<Option<Rc<SandboxInstance<FR>>>>,
-	memories: Vec<Option<MemoryRef>>,
+	memories: Vec<Option<Memory>>,
+	backend_context: BackendContext,
 }
 
 impl<FR> Store<FR> {
-	/// Create a new empty sandbox store.
-	pub fn new() -> Self {
+	/// Create a new empty sandbox store whose modules are executed by `backend`.
+	pub fn new(backend: SandboxBackend) -> Self {
 		Store {
 			instances: Vec::new(),
 			memories: Vec::new(),
+			backend_context: BackendContext::new(backend),
 		}
 	}
 
 	/// Create a new memory instance and return it's index.
 	///
+	/// A `maximum` of `MEM_UNLIMITED` allows the memory to grow up to [`MAX_MEMORY_PAGES`].
+	///
 	/// # Errors
 	///
 	/// Returns `Err` if the memory couldn't be created.
@@ -534,14 +711,18 @@ impl<FR> Store<FR> {
 	pub fn new_memory(&mut self, initial: u32, maximum: u32) -> Result<u32> {
 		let maximum = match maximum {
 			sandbox_primitives::MEM_UNLIMITED => None,
-			specified_limit => Some(Pages(specified_limit as usize)),
+			specified_limit => Some(specified_limit),
 		};
 
-		let mem =
-			MemoryInstance::alloc(
+		let mem = match &self.backend_context {
+			BackendContext::Wasmi => Memory::Wasmi(MemoryInstance::alloc(
 				Pages(initial as usize),
-				maximum,
-			)?;
+				maximum.map(|maximum| Pages(maximum as usize)),
+			)?),
+			#[cfg(feature = "wasmtime-sandbox")]
+			BackendContext::Wasmtime(backend) =>
+				Memory::Wasmtime(backend.new_memory(initial, maximum)?),
+		};
 
 		let mem_idx = self.memories.len();
 		self.memories.push(Some(mem));
@@ -568,7 +749,7 @@ impl<FR> Store<FR> {
 	///
 	/// Returns `Err` If `memory_idx` isn't a valid index of an memory or
 	/// if memory has been torn down.
-	pub fn memory(&self, memory_idx: u32) -> Result<MemoryRef> {
+	pub fn memory(&self, memory_idx: u32) -> Result<Memory> {
 		self.memories
 			.get(memory_idx as usize)
 			.cloned()
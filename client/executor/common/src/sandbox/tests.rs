@@ -0,0 +1,368 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Differential tests of the sandbox backends.
+//!
+//! Every module is run by every available backend against a supervisor that records the calls
+//! made into it and answers them with zeroes. The results, the recorded calls and the final
+//! contents of the imported memories must be the same as with wasmi.
+
+use super::*;
+use parity_wasm::elements::{External, Type, ValueType as WasmValueType};
+use sp_core::sandbox::{Entry, EnvironmentDefinition, ExternEntity, HostError};
+use sp_wasm_interface::{MemoryId, ReturnValue, Sandbox, ValueType};
+
+#[derive(Default)]
+struct MockSupervisor {
+	memory: Vec<u8>,
+	/// The return type of each function made available to the guest.
+	return_types: Vec<Option<ValueType>>,
+	calls: Vec<(usize, Vec<Value>)>,
+}
+
+impl SandboxCapabilities for MockSupervisor {
+	type SupervisorFuncRef = ();
+
+	fn invoke(
+		&mut self,
+		_dispatch_thunk: &(),
+		invoke_args_ptr: Pointer<u8>,
+		invoke_args_len: WordSize,
+		_state: u32,
+		func_idx: SupervisorFuncIndex,
+	) -> Result<i64> {
+		let args = self.read_memory(invoke_args_ptr, invoke_args_len)?;
+		let args = Vec::<Value>::decode(&mut &args[..]).map_err(|_| "invalid arguments")?;
+		let func_idx = usize::from(func_idx);
+		self.calls.push((func_idx, args));
+
+		let return_value = match self.return_types[func_idx] {
+			None => ReturnValue::Unit,
+			Some(ValueType::I32) => ReturnValue::Value(Value::I32(0)),
+			Some(ValueType::I64) => ReturnValue::Value(Value::I64(0)),
+			Some(ValueType::F32) => ReturnValue::Value(Value::F32(0)),
+			Some(ValueType::F64) => ReturnValue::Value(Value::F64(0)),
+		};
+		let result = std::result::Result::<ReturnValue, HostError>::Ok(return_value).encode();
+		let result_ptr = self.allocate_memory(result.len() as WordSize)?;
+		self.write_memory(result_ptr, &result)?;
+		Ok(((u32::from(result_ptr) as u64) << 32 | result.len() as u64) as i64)
+	}
+}
+
+impl FunctionContext for MockSupervisor {
+	fn read_memory_into(
+		&self,
+		address: Pointer<u8>,
+		dest: &mut [u8],
+	) -> sp_wasm_interface::Result<()> {
+		let start = u32::from(address) as usize;
+		let src = self.memory.get(start..start + dest.len()).ok_or("read out of bounds")?;
+		dest.copy_from_slice(src);
+		Ok(())
+	}
+
+	fn write_memory(&mut self, address: Pointer<u8>, data: &[u8]) -> sp_wasm_interface::Result<()> {
+		let start = u32::from(address) as usize;
+		let dest = self.memory.get_mut(start..start + data.len()).ok_or("write out of bounds")?;
+		dest.copy_from_slice(data);
+		Ok(())
+	}
+
+	fn allocate_memory(&mut self, size: WordSize) -> sp_wasm_interface::Result<Pointer<u8>> {
+		let ptr = self.memory.len();
+		self.memory.resize(ptr + size as usize, 0);
+		Ok(Pointer::new(ptr as u32))
+	}
+
+	fn deallocate_memory(&mut self, _ptr: Pointer<u8>) -> sp_wasm_interface::Result<()> {
+		Ok(())
+	}
+
+	fn sandbox(&mut self) -> &mut dyn Sandbox {
+		self
+	}
+}
+
+impl Sandbox for MockSupervisor {
+	fn memory_get(&mut self, _: MemoryId, _: WordSize, _: Pointer<u8>, _: WordSize)
+		-> sp_wasm_interface::Result<u32>
+	{
+		unreachable!("guests can't call into the sandbox of the supervisor")
+	}
+
+	fn memory_set(&mut self, _: MemoryId, _: WordSize, _: Pointer<u8>, _: WordSize)
+		-> sp_wasm_interface::Result<u32>
+	{
+		unreachable!("guests can't call into the sandbox of the supervisor")
+	}
+
+	fn memory_teardown(&mut self, _: MemoryId) -> sp_wasm_interface::Result<()> {
+		unreachable!("guests can't call into the sandbox of the supervisor")
+	}
+
+	fn memory_new(&mut self, _: u32, _: u32) -> sp_wasm_interface::Result<MemoryId> {
+		unreachable!("guests can't call into the sandbox of the supervisor")
+	}
+
+	fn invoke(&mut self, _: u32, _: &str, _: &[u8], _: Pointer<u8>, _: WordSize, _: u32)
+		-> sp_wasm_interface::Result<u32>
+	{
+		unreachable!("guests can't call into the sandbox of the supervisor")
+	}
+
+	fn instance_teardown(&mut self, _: u32) -> sp_wasm_interface::Result<()> {
+		unreachable!("guests can't call into the sandbox of the supervisor")
+	}
+
+	fn instance_new(&mut self, _: u32, _: &[u8], _: &[u8], _: u32)
+		-> sp_wasm_interface::Result<u32>
+	{
+		unreachable!("guests can't call into the sandbox of the supervisor")
+	}
+
+	fn get_global_val(&self, _: u32, _: &str) -> sp_wasm_interface::Result<Option<Value>> {
+		unreachable!("guests can't call into the sandbox of the supervisor")
+	}
+}
+
+/// Everything about a run of a module that must not depend on the backend.
+#[derive(Debug, PartialEq)]
+struct Outcome {
+	instantiation: std::result::Result<(), u32>,
+	results: Vec<std::result::Result<Option<Value>, ()>>,
+	calls: Vec<(usize, Vec<Value>)>,
+	memories: Vec<Vec<u8>>,
+}
+
+fn backends() -> Vec<SandboxBackend> {
+	#[allow(unused_mut)]
+	let mut backends = vec![SandboxBackend::Wasmi];
+	#[cfg(feature = "wasmtime-sandbox")]
+	backends.push(SandboxBackend::Wasmtime);
+	backends
+}
+
+/// Provide every function and memory imported by `wasm`.
+///
+/// Returns the encoded environment definition and the indices of the created memories.
+fn environment(
+	store: &mut Store<()>,
+	supervisor: &mut MockSupervisor,
+	wasm: &[u8],
+) -> (Vec<u8>, Vec<u32>) {
+	let module = parity_wasm::deserialize_buffer::<parity_wasm::elements::Module>(wasm)
+		.expect("fixtures are valid wasm; qed");
+	let types = module.type_section().map(|s| s.types()).unwrap_or(&[]);
+	let mut entries = Vec::new();
+	let mut memories = Vec::new();
+
+	for import in module.import_section().map(|s| s.entries()).unwrap_or(&[]) {
+		let entity = match import.external() {
+			External::Function(type_idx) => {
+				let Type::Function(func_ty) = &types[*type_idx as usize];
+				let return_type = func_ty.return_type().map(|ty| match ty {
+					WasmValueType::I32 => ValueType::I32,
+					WasmValueType::I64 => ValueType::I64,
+					WasmValueType::F32 => ValueType::F32,
+					WasmValueType::F64 => ValueType::F64,
+				});
+				supervisor.return_types.push(return_type);
+				ExternEntity::Function(supervisor.return_types.len() as u32 - 1)
+			}
+			External::Memory(memory_ty) => {
+				let limits = memory_ty.limits();
+				let memory_idx = store
+					.new_memory(
+						limits.initial(),
+						limits.maximum().unwrap_or(sandbox_primitives::MEM_UNLIMITED),
+					)
+					.expect("fixtures request valid memory limits; qed");
+				memories.push(memory_idx);
+				ExternEntity::Memory(memory_idx)
+			}
+			_ => continue,
+		};
+		entries.push(Entry {
+			module_name: import.module().as_bytes().to_vec(),
+			field_name: import.field().as_bytes().to_vec(),
+			entity,
+		});
+	}
+
+	(EnvironmentDefinition { entries }.encode(), memories)
+}
+
+/// Instantiate `wasm` with `backend` and invoke each of `exports` without arguments.
+fn run(backend: SandboxBackend, wasm: &[u8], exports: &[&str]) -> Outcome {
+	let mut store = Store::new(backend);
+	let mut supervisor = MockSupervisor::default();
+	let (env_def, memories) = environment(&mut store, &mut supervisor, wasm);
+	let guest_env = GuestEnvironment::decode(&store, &env_def)
+		.unwrap_or_else(|_| panic!("the environment definition is valid; qed"));
+
+	let (instantiation, results) = match instantiate(&mut supervisor, (), wasm, guest_env, 0) {
+		Ok(instance) => {
+			let instance_idx = instance.register(&mut store);
+			let instance = store.instance(instance_idx).expect("the instance was just registered");
+			let results = exports
+				.iter()
+				.map(|export| instance.invoke(export, &[], &mut supervisor, 0).map_err(|_| ()))
+				.collect();
+			(Ok(()), results)
+		}
+		Err(InstantiationError::StartTrapped) => (Err(sandbox_primitives::ERR_EXECUTION), Vec::new()),
+		Err(_) => (Err(sandbox_primitives::ERR_MODULE), Vec::new()),
+	};
+
+	let memories = memories
+		.into_iter()
+		.map(|memory_idx| {
+			store
+				.memory(memory_idx)
+				.expect("memories are never torn down by the tests")
+				.with_direct_access(|memory| memory.to_vec())
+		})
+		.collect();
+
+	Outcome {
+		instantiation,
+		results,
+		calls: supervisor.calls,
+		memories,
+	}
+}
+
+fn assert_backends_agree(name: &str, wasm: &[u8], exports: &[&str]) {
+	let reference = run(SandboxBackend::Wasmi, wasm, exports);
+	for backend in backends() {
+		assert_eq!(run(backend, wasm, exports), reference, "{} differs with {:?}", name, backend);
+	}
+}
+
+#[test]
+fn backends_agree_on_contract_fixtures() {
+	let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../frame/contracts/fixtures");
+	let mut count = 0;
+	for entry in std::fs::read_dir(fixtures).expect("the contract fixtures exist") {
+		let path = entry.expect("the fixtures directory can be read").path();
+		if path.extension().map_or(true, |extension| extension != "wat") {
+			continue;
+		}
+		let wasm = wat::parse_file(&path).expect("fixtures are valid wat");
+		assert_backends_agree(&path.display().to_string(), &wasm, &["deploy", "call"]);
+		count += 1;
+	}
+	assert!(count > 0, "no contract fixtures found in {}", fixtures);
+}
+
+#[test]
+fn backends_agree_on_traps() {
+	let wasm = wat::parse_str(r#"
+		(module
+			(import "env" "memory" (memory 1 1))
+			(func (export "unreachable") unreachable)
+			(func (export "oob") (result i32) (i32.load (i32.const 65536)))
+			(func (export "div_by_zero") (result i32) (i32.div_u (i32.const 1) (i32.const 0)))
+		)
+	"#).unwrap();
+	assert_backends_agree("traps", &wasm, &["unreachable", "oob", "div_by_zero", "missing"]);
+}
+
+#[test]
+fn backends_agree_on_memory_growth() {
+	let wasm = wat::parse_str(r#"
+		(module
+			(import "env" "memory" (memory 1 2))
+			(func (export "grow") (result i32) (memory.grow (i32.const 1)))
+			(func (export "size") (result i32) (memory.size))
+		)
+	"#).unwrap();
+	assert_backends_agree("memory growth", &wasm, &["grow", "size", "grow", "size"]);
+}
+
+#[test]
+fn memory_limits_are_the_same_for_all_backends() {
+	for backend in backends() {
+		let mut store = Store::<()>::new(backend);
+		assert!(store.new_memory(1, sandbox_primitives::MEM_UNLIMITED).is_ok());
+		assert!(store.new_memory(1, MAX_MEMORY_PAGES).is_ok());
+		assert!(store.new_memory(2, 1).is_err());
+		assert!(store.new_memory(1, MAX_MEMORY_PAGES + 1).is_err());
+		assert!(store.new_memory(MAX_MEMORY_PAGES + 1, sandbox_primitives::MEM_UNLIMITED).is_err());
+	}
+}
+
+#[test]
+fn start_function_can_call_the_supervisor() {
+	let wasm = wat::parse_str(r#"
+		(module
+			(import "env" "memory" (memory 1 1))
+			(import "env" "ping" (func $ping (param i32)))
+			(func $start (call $ping (i32.const 42)))
+			(start $start)
+		)
+	"#).unwrap();
+	assert_backends_agree("start function", &wasm, &[]);
+	assert_eq!(run(SandboxBackend::Wasmi, &wasm, &[]).calls, vec![(0, vec![Value::I32(42)])]);
+}
+
+#[test]
+fn float_results_only_differ_in_nan_bits() {
+	let wasm = wat::parse_str(r#"
+		(module
+			(import "env" "memory" (memory 1 1))
+			(func (export "nan") (result f32) (f32.div (f32.const 0) (f32.const 0)))
+			(func (export "add") (result f32) (f32.add (f32.const 1.5) (f32.const 2.25)))
+		)
+	"#).unwrap();
+
+	let is_nan = |bits: u32| f32::from_bits(bits).is_nan();
+	for backend in backends() {
+		let outcome = run(backend, &wasm, &["nan", "add"]);
+		match outcome.results[0] {
+			Ok(Some(Value::F32(bits))) => assert!(is_nan(bits), "{:?} returned {:#x}", backend, bits),
+			ref other => panic!("{:?} returned {:?}", backend, other),
+		}
+		assert_eq!(outcome.results[1], Ok(Some(Value::F32(3.75f32.to_bits()))));
+	}
+
+	// wasmtime canonicalizes NaNs, while wasmi returns the NaN of the host.
+	#[cfg(feature = "wasmtime-sandbox")]
+	assert_eq!(
+		run(SandboxBackend::Wasmtime, &wasm, &["nan"]).results,
+		vec![Ok(Some(Value::F32(0x7fc0_0000)))],
+	);
+}
+
+#[cfg(feature = "wasmtime-sandbox")]
+#[test]
+fn wasmtime_modules_are_compiled_once() {
+	let wasm = wat::parse_str(r#"
+		(module
+			(import "env" "memory" (memory 1 1))
+			(func (export "answer") (result i32) (i32.const 42))
+		)
+	"#).unwrap();
+
+	assert!(!wasmtime_backend::is_cached(&wasm));
+	let first = run(SandboxBackend::Wasmtime, &wasm, &["answer"]);
+	assert!(wasmtime_backend::is_cached(&wasm));
+	assert_eq!(run(SandboxBackend::Wasmtime, &wasm, &["answer"]), first);
+	assert_eq!(first.results, vec![Ok(Some(Value::I32(42)))]);
+}
@@ -0,0 +1,310 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Wasmtime backend for the sandbox.
+//!
+//! Guest modules are compiled to machine code with cranelift. All memories and instances of a
+//! sandbox store live in the same wasmtime store, which is required for a module to import a
+//! memory that was created by the supervisor. The stores share one engine, which allows the
+//! compiled modules to be cached by the hash of their code.
+//!
+//! Unlike wasmi, which returns whatever NaN the host produces, cranelift is told to canonicalize
+//! NaNs so that results don't depend on the host. The bits of a NaN produced by a float
+//! operation can therefore differ between the backends, which contracts can't observe since they
+//! may not use floating point.
+//!
+//! Calls from a guest into its imports are routed to the [`GuestDispatch`] of the invocation
+//! that is currently running. It is made available through an environmental for the duration
+//! of the instantiation or invocation, because wasmtime host functions must be `'static`.
+
+use super::{GuestDispatch, GuestFuncIndex, Imports, InstantiationError, MAX_MEMORY_PAGES};
+use crate::error::{Error, Result};
+use sp_wasm_interface::Value;
+use std::{cell::RefCell, collections::VecDeque, rc::Rc, sync::Mutex};
+use wasmtime::{
+	Config, Engine, Extern, ExternType, Func, FuncType, Instance, Limits, Memory, MemoryType,
+	Module, Store, Trap, Val, ValType,
+};
+
+environmental::environmental!(guest_dispatch: trait GuestDispatch);
+
+/// The number of compiled modules kept in `MODULES`.
+const MAX_MODULES: usize = 64;
+
+lazy_static::lazy_static! {
+	/// The engine of all sandbox stores.
+	static ref ENGINE: Engine = new_engine();
+
+	/// Compiled modules by the hash of their code. The most recently used is at the front.
+	static ref MODULES: Mutex<VecDeque<([u8; 32], Module)>> = Mutex::new(VecDeque::new());
+}
+
+/// Wasmtime state that is shared between all memories and instances of a sandbox store.
+///
+/// The wasmtime store is only created once it is needed, since most sandbox stores are
+/// never used.
+#[derive(Clone, Default)]
+pub struct Backend {
+	store: Rc<RefCell<Option<Store>>>,
+}
+
+impl Backend {
+	/// Returns the wasmtime store, creating it on first use.
+	fn store(&self) -> Store {
+		self.store.borrow_mut().get_or_insert_with(|| Store::new(&ENGINE)).clone()
+	}
+
+	/// Create a memory that can grow to `maximum` pages or to `MAX_MEMORY_PAGES` if `None`.
+	///
+	/// The limits are validated the same way wasmi validates them.
+	pub fn new_memory(&self, initial: u32, maximum: Option<u32>) -> Result<Memory> {
+		if initial > MAX_MEMORY_PAGES {
+			return Err(Error::Other(format!(
+				"initial memory size must be at most {} pages",
+				MAX_MEMORY_PAGES,
+			)));
+		}
+		if let Some(maximum) = maximum {
+			if initial > maximum {
+				return Err(Error::Other(format!(
+					"maximum limit {} is less than minimum {}",
+					maximum,
+					initial,
+				)));
+			}
+			if maximum > MAX_MEMORY_PAGES {
+				return Err(Error::Other(format!(
+					"maximum memory size must be at most {} pages",
+					MAX_MEMORY_PAGES,
+				)));
+			}
+		}
+
+		let maximum = maximum.unwrap_or(MAX_MEMORY_PAGES);
+		Ok(Memory::new(&self.store(), MemoryType::new(Limits::new(initial, Some(maximum)))))
+	}
+}
+
+/// Create an engine that only accepts what wasmi accepts.
+fn new_engine() -> Engine {
+	let mut config = Config::new();
+	config
+		.cranelift_opt_level(wasmtime::OptLevel::SpeedAndSize)
+		// Keep the results of float operations independent of the host.
+		.cranelift_nan_canonicalization(true)
+		.wasm_threads(false)
+		.wasm_reference_types(false)
+		.wasm_simd(false)
+		.wasm_bulk_memory(false)
+		.wasm_multi_value(false)
+		// Don't reserve address space for memories up front. They are resized on growth
+		// instead, which keeps the cost of creating many small sandboxed memories low.
+		.static_memory_maximum_size(0);
+
+	Engine::new(&config)
+}
+
+/// Compile `wasm`, or take the module compiled from the same code earlier.
+fn compile(wasm: &[u8]) -> std::result::Result<Module, InstantiationError> {
+	let hash = sp_core::hashing::blake2_256(wasm);
+	let lock = || MODULES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+	{
+		let mut modules = lock();
+		if let Some(index) = modules.iter().position(|(h, _)| *h == hash) {
+			let cached = modules.remove(index).expect("`index` was just found; qed");
+			let module = cached.1.clone();
+			modules.push_front(cached);
+			return Ok(module);
+		}
+	}
+
+	// Compile without holding the lock, other modules can be looked up in the meantime.
+	let module = Module::new(&ENGINE, wasm).map_err(|_| InstantiationError::ModuleDecoding)?;
+	let mut modules = lock();
+	if !modules.iter().any(|(h, _)| *h == hash) {
+		modules.push_front((hash, module.clone()));
+		modules.truncate(MAX_MODULES);
+	}
+	Ok(module)
+}
+
+/// Whether the module compiled from `wasm` is cached.
+#[cfg(test)]
+pub(super) fn is_cached(wasm: &[u8]) -> bool {
+	let hash = sp_core::hashing::blake2_256(wasm);
+	MODULES.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).iter().any(|(h, _)| *h == hash)
+}
+
+/// Provides direct read access to the contents of `memory`.
+pub fn with_direct_access<R, F: FnOnce(&[u8]) -> R>(memory: &Memory, f: F) -> R {
+	// This is safe since the memory can only be grown by executing its module or by the
+	// supervisor, neither of which can happen while `f` holds the slice.
+	f(unsafe { memory.data_unchecked() })
+}
+
+/// Provides direct write access to the contents of `memory`.
+pub fn with_direct_access_mut<R, F: FnOnce(&mut [u8]) -> R>(memory: &Memory, f: F) -> R {
+	// This is safe for the same reasons as `with_direct_access`. Additionally, no other slice
+	// of this memory can exist while `f` is running since the sandbox hands out access only
+	// through these closures.
+	f(unsafe { memory.data_unchecked_mut() })
+}
+
+/// Instantiate `wasm`, resolving its imports from `imports`. The module is only compiled if it
+/// isn't cached yet.
+///
+/// The start function, if any, is run as part of the instantiation and may call into the
+/// supervisor through `dispatch`.
+pub fn instantiate(
+	backend: &Backend,
+	wasm: &[u8],
+	imports: &Imports,
+	dispatch: &mut dyn GuestDispatch,
+) -> std::result::Result<super::BackendInstance, InstantiationError> {
+	let store = backend.store();
+	let module = compile(wasm)?;
+
+	let mut externs = Vec::new();
+	for import_ty in module.imports() {
+		let resolved = match import_ty.ty() {
+			ExternType::Func(func_ty) => {
+				let index = imports
+					.func_by_name(import_ty.module(), import_ty.name())
+					.ok_or(InstantiationError::Instantiation)?;
+				Extern::Func(host_func(&store, func_ty, index))
+			}
+			ExternType::Memory(memory_ty) => {
+				let memory = match imports.memory_by_name(import_ty.module(), import_ty.name()) {
+					Some(super::Memory::Wasmtime(memory)) => memory,
+					_ => return Err(InstantiationError::Instantiation),
+				};
+				if !memory_matches(&memory.ty(), &memory_ty) {
+					return Err(InstantiationError::Instantiation);
+				}
+				Extern::Memory(memory)
+			}
+			// Neither globals nor tables can be provided by the supervisor.
+			_ => return Err(InstantiationError::Instantiation),
+		};
+		externs.push(resolved);
+	}
+
+	guest_dispatch::using(dispatch, || Instance::new(&store, &module, &externs))
+		.map(super::BackendInstance::Wasmtime)
+		.map_err(|err| if err.is::<Trap>() {
+			InstantiationError::StartTrapped
+		} else {
+			InstantiationError::Instantiation
+		})
+}
+
+/// Invoke the export called `export_name` of `instance` with `args`.
+pub fn invoke(
+	instance: &Instance,
+	export_name: &str,
+	args: &[Value],
+	dispatch: &mut dyn GuestDispatch,
+) -> Result<Option<Value>> {
+	let func = instance
+		.get_export(export_name)
+		.and_then(|export| export.into_func())
+		.ok_or_else(|| Error::Other(format!("function `{}` is not exported", export_name)))?;
+	let args = args.iter().cloned().map(into_wasmtime_val).collect::<Vec<_>>();
+
+	let results = guest_dispatch::using(dispatch, || func.call(&args))
+		.map_err(|err| Error::Other(format!("sandboxed execution trapped: {}", err)))?;
+
+	match &results[..] {
+		[] => Ok(None),
+		[result] => into_value(result).map(Some),
+		_ => Err(Error::Other("sandboxed functions can't return multiple values".into())),
+	}
+}
+
+/// Get the value from a global exported by `instance` under `name`.
+pub fn get_global_val(instance: &Instance, name: &str) -> Option<Value> {
+	let global = instance.get_export(name)?.into_global()?;
+	into_value(&global.get()).ok()
+}
+
+/// Create a host function of type `func_ty` that dispatches to the supervisor function that
+/// is imported under `index`.
+fn host_func(store: &Store, func_ty: FuncType, index: GuestFuncIndex) -> Func {
+	let expected_result = func_ty.results().first().cloned();
+	Func::new(store, func_ty, move |_, params, results| {
+		let args = params.iter().map(into_value).collect::<Result<Vec<_>>>()
+			.map_err(|err| Trap::new(err.to_string()))?;
+
+		let result = guest_dispatch::with(|dispatch| dispatch.dispatch(index, &args))
+			.ok_or_else(|| Trap::new("sandboxed function called outside of an invocation"))?
+			.map_err(|err| Trap::new(err.to_string()))?;
+
+		// Like wasmi we trap if the supervisor returns a value that doesn't match the signature.
+		match (result, expected_result.as_ref()) {
+			(None, None) => Ok(()),
+			(Some(value), Some(ty)) if value_type_matches(&value, ty) => {
+				results[0] = into_wasmtime_val(value);
+				Ok(())
+			}
+			_ => Err(Trap::new("supervisor function returned a value of unexpected type")),
+		}
+	})
+}
+
+/// Returns `true` if `memory` can be imported where `requested` is expected.
+fn memory_matches(memory: &MemoryType, requested: &MemoryType) -> bool {
+	let (memory, requested) = (memory.limits(), requested.limits());
+	if memory.min() < requested.min() {
+		return false;
+	}
+	match (memory.max(), requested.max()) {
+		(_, None) => true,
+		(Some(max), Some(requested_max)) => max <= requested_max,
+		(None, Some(_)) => false,
+	}
+}
+
+fn value_type_matches(value: &Value, ty: &ValType) -> bool {
+	match (value, ty) {
+		(Value::I32(_), ValType::I32) |
+		(Value::I64(_), ValType::I64) |
+		(Value::F32(_), ValType::F32) |
+		(Value::F64(_), ValType::F64) => true,
+		_ => false,
+	}
+}
+
+fn into_value(val: &Val) -> Result<Value> {
+	match val {
+		Val::I32(v) => Ok(Value::I32(*v)),
+		Val::I64(v) => Ok(Value::I64(*v)),
+		Val::F32(f_bits) => Ok(Value::F32(*f_bits)),
+		Val::F64(f_bits) => Ok(Value::F64(*f_bits)),
+		_ => Err(Error::Other("value type is unsupported by the sandbox".into())),
+	}
+}
+
+fn into_wasmtime_val(value: Value) -> Val {
+	match value {
+		Value::I32(v) => Val::I32(v),
+		Value::I64(v) => Val::I64(v),
+		Value::F32(f_bits) => Val::F32(f_bits),
+		Value::F64(f_bits) => Val::F64(f_bits),
+	}
+}
@@ -16,12 +16,24 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-//! A set of utilities for resetting a wasm instance to its initial state.
+//! A set of utilities for resetting a wasm instance to its initial state and for accessing
+//! wasm memories.
 
 use crate::error::{self, Error};
-use std::mem;
+use std::{mem, ops::Range};
 use parity_wasm::elements::{deserialize_buffer, DataSegment, Instruction, Module as RawModule};
 
+/// Construct a range from an offset to a data length after the offset.
+/// Returns None if the end of the range would exceed some maximum offset.
+pub fn checked_range(offset: usize, len: usize, max: usize) -> Option<Range<usize>> {
+	let end = offset.checked_add(len)?;
+	if end <= max {
+		Some(offset..end)
+	} else {
+		None
+	}
+}
+
 /// A bunch of information collected from a WebAssembly module.
 pub struct WasmModuleInfo {
 	raw_module: RawModule,
@@ -546,6 +546,7 @@ fn should_trap_when_heap_exhausted(wasm_method: WasmExecutionMethod) {
 fn returns_mutable_static(wasm_method: WasmExecutionMethod) {
 	let runtime = crate::wasm_runtime::create_wasm_runtime_with_code(
 		wasm_method,
+		Default::default(),
 		1024,
 		&wasm_binary_unwrap()[..],
 		HostFunctions::host_functions(),
@@ -580,6 +581,7 @@ fn restoration_of_globals(wasm_method: WasmExecutionMethod) {
 
 	let runtime = crate::wasm_runtime::create_wasm_runtime_with_code(
 		wasm_method,
+		Default::default(),
 		REQUIRED_MEMORY_PAGES,
 		&wasm_binary_unwrap()[..],
 		HostFunctions::host_functions(),
@@ -600,6 +602,7 @@ fn restoration_of_globals(wasm_method: WasmExecutionMethod) {
 fn heap_is_reset_between_calls(wasm_method: WasmExecutionMethod) {
 	let runtime = crate::wasm_runtime::create_wasm_runtime_with_code(
 		wasm_method,
+		Default::default(),
 		1024,
 		&wasm_binary_unwrap()[..],
 		HostFunctions::host_functions(),
@@ -326,3 +326,78 @@ fn get_global_val_works(wasm_method: WasmExecutionMethod) {
 		500i64.encode(),
 	);
 }
+
+#[cfg(feature = "wasmtime-sandbox")]
+#[test_case(WasmExecutionMethod::Interpreted)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
+fn wasmtime_sandbox_backend_works(wasm_method: WasmExecutionMethod) {
+	use crate::SandboxBackend;
+	use sp_core::traits::CallInWasm;
+	use sp_wasm_interface::HostFunctions as _;
+
+	let mut ext = TestExternalities::default();
+	let mut ext = ext.ext();
+
+	let executor = crate::WasmExecutor::new(
+		wasm_method,
+		Some(1024),
+		super::HostFunctions::host_functions(),
+		8,
+	).with_sandbox_backend(SandboxBackend::Wasmtime);
+	let mut call = |function: &str, code: Vec<u8>| executor.call_in_wasm(
+		&sc_runtime_test::wasm_binary_unwrap()[..],
+		None,
+		function,
+		&code.encode(),
+		&mut ext,
+		sp_core::traits::MissingHostFunctions::Allow,
+	).unwrap();
+
+	let counter = wat::parse_str(r#"
+		(module
+			(import "env" "assert" (func $assert (param i32)))
+			(import "env" "inc_counter" (func $inc_counter (param i32) (result i32)))
+			(start $start)
+			(func $start
+				(drop (call $inc_counter (i32.const 1)))
+			)
+			(func (export "call")
+				(call $assert (i32.eq (call $inc_counter (i32.const 1)) (i32.const 2)))
+			)
+		)
+		"#).unwrap();
+	assert_eq!(call("test_sandbox", counter), true.encode());
+
+	let trap = wat::parse_str(r#"
+		(module
+			(import "env" "assert" (func $assert (param i32)))
+			(func (export "call") (call $assert (i32.const 0)))
+		)
+		"#).unwrap();
+	assert_eq!(call("test_sandbox", trap), vec![0]);
+
+	let return_val = wat::parse_str(r#"
+		(module
+			(func (export "call") (param $x i32) (result i32)
+				(i32.add (get_local $x) (i32.const 1))
+			)
+		)
+		"#).unwrap();
+	assert_eq!(call("test_sandbox_return_val", return_val), true.encode());
+
+	let start_traps = wat::parse_str(r#"
+		(module
+			(func (export "call"))
+			(func $start unreachable)
+			(start $start)
+		)
+		"#).unwrap();
+	assert_eq!(call("test_sandbox_instantiate", start_traps), 2u8.encode());
+
+	let global = wat::parse_str(r#"
+		(module
+			(global (export "test_global") i64 (i64.const 500))
+		)
+		"#).unwrap();
+	assert_eq!(call("test_sandbox_get_global_val", global), 500i64.encode());
+}
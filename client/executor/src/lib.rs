@@ -48,6 +48,7 @@ pub use sp_wasm_interface;
 pub use wasm_runtime::WasmExecutionMethod;
 
 pub use sc_executor_common::{error, sandbox};
+pub use sc_executor_common::sandbox::SandboxBackend;
 
 /// Provides runtime information.
 pub trait RuntimeInfo {
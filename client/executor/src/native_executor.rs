@@ -39,7 +39,10 @@ use sp_core::{
 };
 use log::trace;
 use sp_wasm_interface::{HostFunctions, Function};
-use sc_executor_common::wasm_runtime::{WasmInstance, WasmModule, InvokeMethod};
+use sc_executor_common::{
+	sandbox::SandboxBackend,
+	wasm_runtime::{WasmInstance, WasmModule, InvokeMethod},
+};
 use sp_externalities::ExternalitiesExt as _;
 use sp_tasks::new_async_externalities;
 
@@ -94,6 +97,8 @@ pub trait NativeExecutionDispatch: Send + Sync {
 pub struct WasmExecutor {
 	/// Method used to execute fallback Wasm code.
 	method: WasmExecutionMethod,
+	/// The backend that executes modules created by the runtime in the sandbox.
+	sandbox_backend: SandboxBackend,
 	/// The number of 64KB pages to allocate for Wasm execution.
 	default_heap_pages: u64,
 	/// The host functions registered with this instance.
@@ -121,6 +126,7 @@ impl WasmExecutor {
 	) -> Self {
 		WasmExecutor {
 			method,
+			sandbox_backend: SandboxBackend::default(),
 			default_heap_pages: default_heap_pages.unwrap_or(DEFAULT_HEAP_PAGES),
 			host_functions: Arc::new(host_functions),
 			cache: Arc::new(RuntimeCache::new(max_runtime_instances)),
@@ -128,6 +134,14 @@ impl WasmExecutor {
 		}
 	}
 
+	/// Execute the modules that the runtime creates in the sandbox with `sandbox_backend`.
+	///
+	/// Defaults to [`SandboxBackend::Wasmi`].
+	pub fn with_sandbox_backend(mut self, sandbox_backend: SandboxBackend) -> Self {
+		self.sandbox_backend = sandbox_backend;
+		self
+	}
+
 	/// Execute the given closure `f` with the latest runtime (based on `runtime_code`).
 	///
 	/// The closure `f` is expected to return `Err(_)` when there happened a `panic!` in native code
@@ -159,6 +173,7 @@ impl WasmExecutor {
 			runtime_code,
 			ext,
 			self.method,
+			self.sandbox_backend,
 			self.default_heap_pages,
 			&*self.host_functions,
 			allow_missing_host_functions,
@@ -206,6 +221,7 @@ impl sp_core::traits::CallInWasm for WasmExecutor {
 		} else {
 			let module = crate::wasm_runtime::create_wasm_runtime_with_code(
 				self.method,
+				self.sandbox_backend,
 				self.default_heap_pages,
 				&wasm_code,
 				self.host_functions.to_vec(),
@@ -275,6 +291,15 @@ impl<D: NativeExecutionDispatch> NativeExecutor<D> {
 			wasm: wasm_executor,
 		}
 	}
+
+	/// Execute the modules that the runtime creates in the sandbox with `sandbox_backend`.
+	///
+	/// This only applies when the runtime is executed as Wasm. A native runtime always
+	/// interprets sandboxed modules with wasmi. Defaults to [`SandboxBackend::Wasmi`].
+	pub fn with_sandbox_backend(mut self, sandbox_backend: SandboxBackend) -> Self {
+		self.wasm = self.wasm.with_sandbox_backend(sandbox_backend);
+		self
+	}
 }
 
 impl<D: NativeExecutionDispatch> RuntimeInfo for NativeExecutor<D> {
@@ -26,7 +26,10 @@ use codec::Decode;
 use sp_core::traits::{Externalities, RuntimeCode, FetchRuntimeCode};
 use sp_version::RuntimeVersion;
 use std::panic::AssertUnwindSafe;
-use sc_executor_common::wasm_runtime::{WasmModule, WasmInstance};
+use sc_executor_common::{
+	sandbox::SandboxBackend,
+	wasm_runtime::{WasmModule, WasmInstance},
+};
 
 use sp_wasm_interface::Function;
 
@@ -52,6 +55,8 @@ struct VersionedRuntime {
 	code_hash: Vec<u8>,
 	/// Wasm runtime type.
 	wasm_method: WasmExecutionMethod,
+	/// The backend that executes modules created by the runtime in the sandbox.
+	sandbox_backend: SandboxBackend,
 	/// Shared runtime that can spawn instances.
 	module: Arc<dyn WasmModule>,
 	/// The number of WebAssembly heap pages this instance was created with.
@@ -174,6 +179,9 @@ impl RuntimeCache {
 	///
 	/// `wasm_method` - Type of WASM backend to use.
 	///
+	/// `sandbox_backend` - The backend that executes modules created by the runtime in the
+	/// sandbox.
+	///
 	/// `host_functions` - The host functions that should be registered for the Wasm runtime.
 	///
 	/// `allow_missing_func_imports` - Ignore missing function imports.
@@ -194,6 +202,7 @@ impl RuntimeCache {
 		runtime_code: &'c RuntimeCode<'c>,
 		ext: &mut dyn Externalities,
 		wasm_method: WasmExecutionMethod,
+		sandbox_backend: SandboxBackend,
 		default_heap_pages: u64,
 		host_functions: &[&'static dyn Function],
 		allow_missing_func_imports: bool,
@@ -213,6 +222,7 @@ impl RuntimeCache {
 		let pos = runtimes.iter().position(|r| r.as_ref().map_or(
 			false,
 			|r| r.wasm_method == wasm_method &&
+				r.sandbox_backend == sandbox_backend &&
 				r.code_hash == *code_hash &&
 				r.heap_pages == heap_pages
 		));
@@ -229,6 +239,7 @@ impl RuntimeCache {
 					code_hash.clone(),
 					ext,
 					wasm_method,
+					sandbox_backend,
 					heap_pages,
 					host_functions.into(),
 					allow_missing_func_imports,
@@ -263,8 +274,11 @@ impl RuntimeCache {
 }
 
 /// Create a wasm runtime with the given `code`.
+///
+/// Modules created by the runtime in the sandbox are executed by `sandbox_backend`.
 pub fn create_wasm_runtime_with_code(
 	wasm_method: WasmExecutionMethod,
+	sandbox_backend: SandboxBackend,
 	heap_pages: u64,
 	code: &[u8],
 	host_functions: Vec<&'static dyn Function>,
@@ -276,7 +290,8 @@ pub fn create_wasm_runtime_with_code(
 				code,
 				heap_pages,
 				host_functions,
-				allow_missing_func_imports
+				allow_missing_func_imports,
+				sandbox_backend,
 			).map(|runtime| -> Arc<dyn WasmModule> { Arc::new(runtime) }),
 		#[cfg(feature = "wasmtime")]
 		WasmExecutionMethod::Compiled =>
@@ -284,7 +299,8 @@ pub fn create_wasm_runtime_with_code(
 				code,
 				heap_pages,
 				host_functions,
-				allow_missing_func_imports
+				allow_missing_func_imports,
+				sandbox_backend,
 			).map(|runtime| -> Arc<dyn WasmModule> { Arc::new(runtime) }),
 	}
 }
@@ -313,6 +329,7 @@ fn create_versioned_wasm_runtime(
 	code_hash: Vec<u8>,
 	ext: &mut dyn Externalities,
 	wasm_method: WasmExecutionMethod,
+	sandbox_backend: SandboxBackend,
 	heap_pages: u64,
 	host_functions: Vec<&'static dyn Function>,
 	allow_missing_func_imports: bool,
@@ -322,6 +339,7 @@ fn create_versioned_wasm_runtime(
 	let time = std::time::Instant::now();
 	let runtime = create_wasm_runtime_with_code(
 		wasm_method,
+		sandbox_backend,
 		heap_pages,
 		&code,
 		host_functions,
@@ -362,6 +380,7 @@ fn create_versioned_wasm_runtime(
 		version,
 		heap_pages,
 		wasm_method,
+		sandbox_backend,
 		instances,
 	})
 }
@@ -34,7 +34,7 @@ use sc_executor_common::{
 	error::{Error, WasmError},
 	sandbox,
 };
-use sc_executor_common::util::{DataSegmentsSnapshot, WasmModuleInfo};
+use sc_executor_common::util::{self, DataSegmentsSnapshot, WasmModuleInfo};
 
 struct FunctionExecutor<'a> {
 	sandbox_store: sandbox::Store<wasmi::FuncRef>,
@@ -54,9 +54,10 @@ impl<'a> FunctionExecutor<'a> {
 		host_functions: &'a [&'static dyn Function],
 		allow_missing_func_imports: bool,
 		missing_functions: &'a [String],
+		sandbox_backend: sandbox::SandboxBackend,
 	) -> Result<Self, Error> {
 		Ok(FunctionExecutor {
-			sandbox_store: sandbox::Store::new(),
+			sandbox_store: sandbox::Store::new(sandbox_backend),
 			heap: sp_allocator::FreeingBumpHeapAllocator::new(heap_base),
 			memory: m,
 			table: t,
@@ -134,16 +135,16 @@ impl<'a> Sandbox for FunctionExecutor<'a> {
 	) -> WResult<u32> {
 		let sandboxed_memory = self.sandbox_store.memory(memory_id).map_err(|e| e.to_string())?;
 
-		match MemoryInstance::transfer(
-			&sandboxed_memory,
-			offset as usize,
-			&self.memory,
-			buf_ptr.into(),
-			buf_len as usize,
-		) {
-			Ok(()) => Ok(sandbox_primitives::ERR_OK),
-			Err(_) => Ok(sandbox_primitives::ERR_OUT_OF_BOUNDS),
-		}
+		sandboxed_memory.with_direct_access(|sandboxed_memory| {
+			let src_range = match util::checked_range(offset as usize, buf_len as usize, sandboxed_memory.len()) {
+				Some(range) => range,
+				None => return Ok(sandbox_primitives::ERR_OUT_OF_BOUNDS),
+			};
+			match self.memory.set(buf_ptr.into(), &sandboxed_memory[src_range]) {
+				Ok(()) => Ok(sandbox_primitives::ERR_OK),
+				Err(_) => Ok(sandbox_primitives::ERR_OUT_OF_BOUNDS),
+			}
+		})
 	}
 
 	fn memory_set(
@@ -155,16 +156,16 @@ impl<'a> Sandbox for FunctionExecutor<'a> {
 	) -> WResult<u32> {
 		let sandboxed_memory = self.sandbox_store.memory(memory_id).map_err(|e| e.to_string())?;
 
-		match MemoryInstance::transfer(
-			&self.memory,
-			val_ptr.into(),
-			&sandboxed_memory,
-			offset as usize,
-			val_len as usize,
-		) {
-			Ok(()) => Ok(sandbox_primitives::ERR_OK),
-			Err(_) => Ok(sandbox_primitives::ERR_OUT_OF_BOUNDS),
-		}
+		sandboxed_memory.with_direct_access_mut(|sandboxed_memory| {
+			let dst_range = match util::checked_range(offset as usize, val_len as usize, sandboxed_memory.len()) {
+				Some(range) => range,
+				None => return Ok(sandbox_primitives::ERR_OUT_OF_BOUNDS),
+			};
+			match self.memory.get_into(val_ptr.into(), &mut sandboxed_memory[dst_range]) {
+				Ok(()) => Ok(sandbox_primitives::ERR_OK),
+				Err(_) => Ok(sandbox_primitives::ERR_OUT_OF_BOUNDS),
+			}
+		})
 	}
 
 	fn memory_teardown(&mut self, memory_id: MemoryId) -> WResult<()> {
@@ -190,12 +191,9 @@ impl<'a> Sandbox for FunctionExecutor<'a> {
 	) -> WResult<u32> {
 		trace!(target: "sp-sandbox", "invoke, instance_idx={}", instance_id);
 
-		// Deserialize arguments and convert them into wasmi types.
+		// Deserialize arguments.
 		let args = Vec::<sp_wasm_interface::Value>::decode(&mut &args[..])
-			.map_err(|_| "Can't decode serialized arguments for the invocation")?
-			.into_iter()
-			.map(Into::into)
-			.collect::<Vec<_>>();
+			.map_err(|_| "Can't decode serialized arguments for the invocation")?;
 
 		let instance = self.sandbox_store.instance(instance_id).map_err(|e| e.to_string())?;
 		let result = instance.invoke(export_name, &args, self, state);
@@ -204,7 +202,7 @@ impl<'a> Sandbox for FunctionExecutor<'a> {
 			Ok(None) => Ok(sandbox_primitives::ERR_OK),
 			Ok(Some(val)) => {
 				// Serialize return value and write it back into the memory.
-				sp_wasm_interface::ReturnValue::Value(val.into()).using_encoded(|val| {
+				sp_wasm_interface::ReturnValue::Value(val).using_encoded(|val| {
 					if val.len() > return_val_len as usize {
 						Err("Return value buffer is too small")?;
 					}
@@ -439,6 +437,7 @@ fn call_in_wasm_module(
 	host_functions: &[&'static dyn Function],
 	allow_missing_func_imports: bool,
 	missing_functions: &Vec<String>,
+	sandbox_backend: sandbox::SandboxBackend,
 ) -> Result<Vec<u8>, Error> {
 	// Initialize FunctionExecutor.
 	let table: Option<TableRef> = module_instance
@@ -453,6 +452,7 @@ fn call_in_wasm_module(
 		host_functions,
 		allow_missing_func_imports,
 		missing_functions,
+		sandbox_backend,
 	)?;
 
 	// Write the call data
@@ -609,6 +609,8 @@ pub struct WasmiRuntime {
 	allow_missing_func_imports: bool,
 	/// Numer of heap pages this runtime uses.
 	heap_pages: u64,
+	/// The backend that executes modules in the sandbox.
+	sandbox_backend: sandbox::SandboxBackend,
 
 	global_vals_snapshot: GlobalValsSnapshot,
 	data_segments_snapshot: DataSegmentsSnapshot,
@@ -632,17 +634,21 @@ impl WasmModule for WasmiRuntime {
 			host_functions: self.host_functions.clone(),
 			allow_missing_func_imports: self.allow_missing_func_imports,
 			missing_functions,
+			sandbox_backend: self.sandbox_backend,
 		}))
 	}
 }
 
 /// Create a new `WasmiRuntime` given the code. This function loads the module and
 /// stores it in the instance.
+///
+/// Modules created by the runtime in the sandbox are executed by `sandbox_backend`.
 pub fn create_runtime(
 	code: &[u8],
 	heap_pages: u64,
 	host_functions: Vec<&'static dyn Function>,
 	allow_missing_func_imports: bool,
+	sandbox_backend: sandbox::SandboxBackend,
 ) -> Result<WasmiRuntime, WasmError> {
 	let module = Module::from_buffer(&code).map_err(|_| WasmError::InvalidModule)?;
 
@@ -676,6 +682,7 @@ pub fn create_runtime(
 		host_functions: Arc::new(host_functions),
 		allow_missing_func_imports,
 		heap_pages,
+		sandbox_backend,
 	})
 }
 
@@ -696,6 +703,8 @@ pub struct WasmiInstance {
 	allow_missing_func_imports: bool,
 	/// List of missing functions detected during function resolution
 	missing_functions: Vec<String>,
+	/// The backend that executes modules in the sandbox.
+	sandbox_backend: sandbox::SandboxBackend,
 }
 
 // This is safe because `WasmiInstance` does not leak any references to `self.memory` and `self.instance`
@@ -730,6 +739,7 @@ impl WasmInstance for WasmiInstance {
 			self.host_functions.as_ref(),
 			self.allow_missing_func_imports,
 			self.missing_functions.as_ref(),
+			self.sandbox_backend,
 		)
 	}
 
@@ -18,13 +18,15 @@
 //! required for execution of host.
 
 use crate::instance_wrapper::InstanceWrapper;
-use crate::util;
 use std::{cell::RefCell, rc::Rc};
 use log::trace;
 use codec::{Encode, Decode};
 use sp_allocator::FreeingBumpHeapAllocator;
 use sc_executor_common::error::Result;
-use sc_executor_common::sandbox::{self, SandboxCapabilities, SupervisorFuncIndex};
+use sc_executor_common::{
+	sandbox::{self, SandboxCapabilities, SupervisorFuncIndex},
+	util,
+};
 use sp_core::sandbox as sandbox_primitives;
 use sp_wasm_interface::{FunctionContext, MemoryId, Pointer, Sandbox, WordSize};
 use wasmtime::{Func, Val};
@@ -55,10 +57,14 @@ pub struct HostState {
 }
 
 impl HostState {
-	/// Constructs a new `HostState`.
-	pub fn new(allocator: FreeingBumpHeapAllocator, instance: Rc<InstanceWrapper>) -> Self {
+	/// Constructs a new `HostState` whose sandboxed modules are executed by `sandbox_backend`.
+	pub fn new(
+		allocator: FreeingBumpHeapAllocator,
+		instance: Rc<InstanceWrapper>,
+		sandbox_backend: sandbox::SandboxBackend,
+	) -> Self {
 		HostState {
-			sandbox_store: RefCell::new(sandbox::Store::new()),
+			sandbox_store: RefCell::new(sandbox::Store::new(sandbox_backend)),
 			allocator: RefCell::new(allocator),
 			instance,
 		}
@@ -250,12 +256,9 @@ impl<'a> Sandbox for HostContext<'a> {
 	) -> sp_wasm_interface::Result<u32> {
 		trace!(target: "sp-sandbox", "invoke, instance_idx={}", instance_id);
 
-		// Deserialize arguments and convert them into wasmi types.
+		// Deserialize arguments.
 		let args = Vec::<sp_wasm_interface::Value>::decode(&mut &args[..])
-			.map_err(|_| "Can't decode serialized arguments for the invocation")?
-			.into_iter()
-			.map(Into::into)
-			.collect::<Vec<_>>();
+			.map_err(|_| "Can't decode serialized arguments for the invocation")?;
 
 		let instance = self
 			.sandbox_store
@@ -268,7 +271,7 @@ impl<'a> Sandbox for HostContext<'a> {
 			Ok(None) => Ok(sandbox_primitives::ERR_OK),
 			Ok(Some(val)) => {
 				// Serialize return value and write it back into the memory.
-				sp_wasm_interface::ReturnValue::Value(val.into()).using_encoded(|val| {
+				sp_wasm_interface::ReturnValue::Value(val).using_encoded(|val| {
 					if val.len() > return_val_len as usize {
 						Err("Return value buffer is too small")?;
 					}
@@ -21,6 +21,5 @@ mod runtime;
 mod state_holder;
 mod imports;
 mod instance_wrapper;
-mod util;
 
 pub use runtime::create_runtime;
@@ -25,6 +25,7 @@ use std::rc::Rc;
 use std::sync::Arc;
 use sc_executor_common::{
 	error::{Result, WasmError},
+	sandbox::SandboxBackend,
 	wasm_runtime::{WasmModule, WasmInstance, InvokeMethod},
 };
 use sp_allocator::FreeingBumpHeapAllocator;
@@ -40,6 +41,7 @@ pub struct WasmtimeRuntime {
 	allow_missing_func_imports: bool,
 	host_functions: Vec<&'static dyn Function>,
 	engine: Engine,
+	sandbox_backend: SandboxBackend,
 }
 
 impl WasmModule for WasmtimeRuntime {
@@ -69,6 +71,7 @@ impl WasmModule for WasmtimeRuntime {
 			globals_snapshot,
 			heap_pages: self.heap_pages,
 			heap_base,
+			sandbox_backend: self.sandbox_backend,
 		}))
 	}
 }
@@ -83,6 +86,7 @@ pub struct WasmtimeInstance {
 	imports: Imports,
 	heap_pages: u32,
 	heap_base: u32,
+	sandbox_backend: SandboxBackend,
 }
 
 // This is safe because `WasmtimeInstance` does not leak reference to `self.imports`
@@ -108,6 +112,7 @@ impl WasmInstance for WasmtimeInstance {
 			Rc::clone(&self.instance_wrapper),
 			entrypoint,
 			allocator,
+			self.sandbox_backend,
 		)
 	}
 
@@ -119,11 +124,14 @@ impl WasmInstance for WasmtimeInstance {
 
 /// Create a new `WasmtimeRuntime` given the code. This function performs translation from Wasm to
 /// machine code, which can be computationally heavy.
+///
+/// Modules created by the runtime in the sandbox are executed by `sandbox_backend`.
 pub fn create_runtime(
 	code: &[u8],
 	heap_pages: u64,
 	host_functions: Vec<&'static dyn Function>,
 	allow_missing_func_imports: bool,
+	sandbox_backend: SandboxBackend,
 ) -> std::result::Result<WasmtimeRuntime, WasmError> {
 	// Create the engine, store and finally the module from the given code.
 	let mut config = Config::new();
@@ -140,6 +148,7 @@ pub fn create_runtime(
 		allow_missing_func_imports,
 		host_functions,
 		engine,
+		sandbox_backend,
 	})
 }
 
@@ -148,10 +157,11 @@ fn perform_call(
 	instance_wrapper: Rc<InstanceWrapper>,
 	entrypoint: EntryPoint,
 	mut allocator: FreeingBumpHeapAllocator,
+	sandbox_backend: SandboxBackend,
 ) -> Result<Vec<u8>> {
 	let (data_ptr, data_len) = inject_input_data(&instance_wrapper, &mut allocator, data)?;
 
-	let host_state = HostState::new(allocator, instance_wrapper.clone());
+	let host_state = HostState::new(allocator, instance_wrapper.clone(), sandbox_backend);
 	let ret = state_holder::with_initialized_state(&host_state, || -> Result<_> {
 		Ok(unpack_ptr_and_len(entrypoint.call(data_ptr, data_len)?))
 	});
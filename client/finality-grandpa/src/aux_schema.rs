@@ -36,6 +36,8 @@ const VERSION_KEY: &[u8] = b"grandpa_schema_version";
 const SET_STATE_KEY: &[u8] = b"grandpa_completed_round";
 const CONCLUDED_ROUNDS: &[u8] = b"grandpa_concluded_rounds";
 const AUTHORITY_SET_KEY: &[u8] = b"grandpa_voters";
+const SET_CHANGE_PREFIX: &[u8] = b"grandpa_set_change";
+const BEST_JUSTIFICATION: &[u8] = b"grandpa_best_justification";
 
 const CURRENT_VERSION: u32 = 2;
 
@@ -411,6 +413,55 @@ pub(crate) fn write_concluded_round<Block: BlockT, B: AuxStore>(
 	backend.insert_aux(&[(&key[..], round_data.encode().as_slice())], &[])
 }
 
+fn set_change_key(set_id: SetId) -> Vec<u8> {
+	let mut key = SET_CHANGE_PREFIX.to_vec();
+	set_id.using_encoded(|id| key.extend(id));
+	key
+}
+
+/// Record the last block finalized by the given authority set, which enacted a standard change
+/// to the next set. The justification of this block is kept and proves the change.
+pub(crate) fn write_authority_set_change<Block: BlockT, F, R>(
+	set_id: SetId,
+	block: (Block::Hash, NumberFor<Block>),
+	write_aux: F,
+) -> R where
+	F: FnOnce(&[(&[u8], &[u8])]) -> R,
+{
+	let key = set_change_key(set_id);
+	let encoded = block.encode();
+
+	write_aux(&[(&key[..], &encoded[..])])
+}
+
+/// Load the last block finalized by the given authority set, if the set was changed with a
+/// standard change that has been finalized by this node.
+pub(crate) fn load_authority_set_change<Block: BlockT, B: AuxStore>(
+	backend: &B,
+	set_id: SetId,
+) -> ClientResult<Option<(Block::Hash, NumberFor<Block>)>> {
+	load_decode(backend, &set_change_key(set_id))
+}
+
+/// Write the hash of the latest finalized block with a stored justification.
+pub(crate) fn write_best_justification<Block: BlockT, F, R>(
+	block: (Block::Hash, NumberFor<Block>),
+	write_aux: F,
+) -> R where
+	F: FnOnce(&[(&'static [u8], &[u8])]) -> R,
+{
+	let encoded = block.encode();
+
+	write_aux(&[(BEST_JUSTIFICATION, &encoded[..])])
+}
+
+/// Load the hash of the latest finalized block with a stored justification.
+pub(crate) fn load_best_justification<Block: BlockT, B: AuxStore>(
+	backend: &B,
+) -> ClientResult<Option<(Block::Hash, NumberFor<Block>)>> {
+	load_decode(backend, BEST_JUSTIFICATION)
+}
+
 #[cfg(test)]
 pub(crate) fn load_authorities<B: AuxStore, H: Decode, N: Decode>(backend: &B)
 	-> Option<AuthoritySet<H, N>> {
@@ -91,6 +91,10 @@ impl sc_network_gossip::ValidatorContext<Block> for TestNetwork {
 	}
 
 	fn send_topic(&mut self, _: &sc_network::PeerId, _: Hash, _: bool) { }
+
+	fn report_peer(&mut self, who: &sc_network::PeerId, cost_benefit: sc_network::ReputationChange) {
+		<Self as sc_network_gossip::Network<Block>>::report_peer(self, who.clone(), cost_benefit);
+	}
 }
 
 pub(crate) struct Tester {
@@ -209,6 +213,7 @@ impl sc_network_gossip::ValidatorContext<Block> for NoopContext {
 	fn broadcast_message(&mut self, _: Hash, _: Vec<u8>, _: bool) { }
 	fn send_message(&mut self, _: &sc_network::PeerId, _: Vec<u8>) { }
 	fn send_topic(&mut self, _: &sc_network::PeerId, _: Hash, _: bool) { }
+	fn report_peer(&mut self, _: &sc_network::PeerId, _: sc_network::ReputationChange) { }
 }
 
 #[test]
@@ -56,7 +56,7 @@ use crate::until_imported::UntilVoteTargetImported;
 use crate::voting_rule::VotingRule;
 use sp_finality_grandpa::{
 	AuthorityId, AuthoritySignature, Equivocation, EquivocationProof,
-	GrandpaApi, RoundNumber, SetId,
+	GrandpaApi, RoundNumber, SetId, GRANDPA_ENGINE_ID,
 };
 use prometheus_endpoint::{register, Counter, Gauge, PrometheusError, U64};
 
@@ -1241,7 +1241,7 @@ where
 		let justification = match justification_or_commit {
 			JustificationOrCommit::Justification(justification) => {
 				notify_justification(justification_sender, || Ok(justification.clone()));
-				Some(justification.encode())
+				Some((GRANDPA_ENGINE_ID, justification.encode()))
 			},
 			JustificationOrCommit::Commit((round_number, commit)) => {
 				let mut justification_required =
@@ -1276,7 +1276,7 @@ where
 					let justification = justification()?;
 					notify_justification(justification_sender, || Ok(justification.clone()));
 
-					Some(justification.encode())
+					Some((GRANDPA_ENGINE_ID, justification.encode()))
 				} else {
 					notify_justification(justification_sender, justification);
 
@@ -1287,6 +1287,14 @@ where
 
 		debug!(target: "afg", "Finalizing blocks up to ({:?}, {})", number, hash);
 
+		// remember the latest justified block, it is the target of warp sync proofs.
+		if justification.is_some() {
+			crate::aux_schema::write_best_justification::<Block, _, _>(
+				(hash, number),
+				|insert| apply_aux(import_op, insert, &[]),
+			)?;
+		}
+
 		// ideally some handle to a synchronization oracle would be used
 		// to avoid unconditionally notifying.
 		client.apply_finality(import_op, BlockId::Hash(hash), justification, true).map_err(|e| {
@@ -1318,6 +1326,13 @@ where
 				"authorities" => ?set_ref.to_vec(),
 				"set_id" => ?new_id,
 			);
+
+			// the justification of this block proves the handoff to the new set.
+			crate::aux_schema::write_authority_set_change::<Block, _, _>(
+				new_id - 1,
+				(canon_hash, canon_number),
+				|insert| apply_aux(import_op, insert, &[]),
+			)?;
 			Some(NewAuthoritySet {
 				canon_hash,
 				canon_number,
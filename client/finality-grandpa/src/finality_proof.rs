@@ -51,12 +51,14 @@ use sc_client_api::{
 use parity_scale_codec::{Encode, Decode};
 use finality_grandpa::BlockNumberOps;
 use sp_runtime::{
-	Justification, generic::BlockId,
+	EncodedJustification, generic::BlockId,
 	traits::{NumberFor, Block as BlockT, Header as HeaderT, One},
 };
 use sp_core::storage::StorageKey;
 use sc_telemetry::{telemetry, CONSENSUS_INFO};
-use sp_finality_grandpa::{AuthorityId, AuthorityList, VersionedAuthorityList, GRANDPA_AUTHORITIES_KEY};
+use sp_finality_grandpa::{
+	AuthorityId, AuthorityList, VersionedAuthorityList, GRANDPA_AUTHORITIES_KEY, GRANDPA_ENGINE_ID,
+};
 
 use crate::justification::GrandpaJustification;
 use crate::VoterSet;
@@ -331,7 +333,9 @@ pub(crate) fn prove_finality<Block: BlockT, B: BlockchainBackend<Block>, J>(
 			unknown_headers.push(unknown_header);
 		}
 
-		if let Some(justification) = blockchain.justification(current_id)? {
+		let justification = blockchain.justifications(current_id)?
+			.and_then(|justifications| justifications.into_justification(GRANDPA_ENGINE_ID));
+		if let Some(justification) = justification {
 			// check if the current block enacts new GRANDPA authorities set
 			let new_authorities = authorities_provider.authorities(&current_id)?;
 			let new_authorities_proof = if current_authorities != new_authorities {
@@ -558,7 +562,7 @@ pub(crate) trait ProvableJustification<Header: HeaderT>: Encode + Decode {
 
 	/// Decode and verify justification.
 	fn decode_and_verify(
-		justification: &Justification,
+		justification: &EncodedJustification,
 		set_id: u64,
 		authorities: &[(AuthorityId, u64)],
 	) -> ClientResult<Self> {
@@ -587,6 +591,7 @@ pub(crate) mod tests {
 	use substrate_test_runtime_client::runtime::{Block, Header, H256};
 	use sc_client_api::NewBlockState;
 	use sc_client_api::in_mem::Blockchain as InMemoryBlockchain;
+	use sp_runtime::Justifications;
 	use super::*;
 	use sp_core::crypto::Public;
 
@@ -665,10 +670,28 @@ pub(crate) mod tests {
 
 	fn test_blockchain() -> InMemoryBlockchain<Block> {
 		let blockchain = InMemoryBlockchain::<Block>::new();
-		blockchain.insert(header(0).hash(), header(0), Some(vec![0]), None, NewBlockState::Final).unwrap();
-		blockchain.insert(header(1).hash(), header(1), Some(vec![1]), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(0).hash(),
+			header(0),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, vec![0]))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
+		blockchain.insert(
+			header(1).hash(),
+			header(1),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, vec![1]))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 		blockchain.insert(header(2).hash(), header(2), None, None, NewBlockState::Best).unwrap();
-		blockchain.insert(header(3).hash(), header(3), Some(vec![3]), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(3).hash(),
+			header(3),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, vec![3]))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 		blockchain
 	}
 
@@ -719,7 +742,13 @@ pub(crate) mod tests {
 		blockchain.insert(side_header(4).hash(), side_header(4), None, None, NewBlockState::Best).unwrap();
 		blockchain.insert(second_side_header(5).hash(), second_side_header(5), None, None, NewBlockState::Best)
 			.unwrap();
-		blockchain.insert(header(5).hash(), header(5), Some(vec![5]), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(5).hash(),
+			header(5),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, vec![5]))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 
 		// chain is 1 -> 2 -> 3 -> 4 -> 5
 		//                      \> 4' -> 5'
@@ -763,8 +792,20 @@ pub(crate) mod tests {
 		let authorities = vec![(AuthorityId::from_slice(&[1u8; 32]), 1u64)];
 		let just4 = TestJustification((0, authorities.clone()), vec![4]).encode();
 		let just5 = TestJustification((0, authorities.clone()), vec![5]).encode();
-		blockchain.insert(header(4).hash(), header(4), Some(just4), None, NewBlockState::Final).unwrap();
-		blockchain.insert(header(5).hash(), header(5), Some(just5.clone()), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(4).hash(),
+			header(4),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, just4))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
+		blockchain.insert(
+			header(5).hash(),
+			header(5),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, just5.clone()))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 
 		// blocks 4 && 5 are finalized with justification
 		// => since authorities are the same, we only need justification for 5
@@ -789,7 +830,13 @@ pub(crate) mod tests {
 	#[test]
 	fn finality_proof_finalized_earlier_block_if_no_justification_for_target_is_known() {
 		let blockchain = test_blockchain();
-		blockchain.insert(header(4).hash(), header(4), Some(vec![4]), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(4).hash(),
+			header(4),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, vec![4]))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 		blockchain.insert(header(5).hash(), header(5), None, None, NewBlockState::Final).unwrap();
 
 		// block 4 is finalized with justification + we request for finality of 5
@@ -821,10 +868,28 @@ pub(crate) mod tests {
 		let just4 = TestJustification((0, auth3.clone()), vec![4]).encode();
 		let just5 = TestJustification((0, auth3.clone()), vec![5]).encode();
 		let just7 = TestJustification((1, auth5.clone()), vec![7]).encode();
-		blockchain.insert(header(4).hash(), header(4), Some(just4), None, NewBlockState::Final).unwrap();
-		blockchain.insert(header(5).hash(), header(5), Some(just5.clone()), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(4).hash(),
+			header(4),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, just4))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
+		blockchain.insert(
+			header(5).hash(),
+			header(5),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, just5.clone()))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 		blockchain.insert(header(6).hash(), header(6), None, None, NewBlockState::Final).unwrap();
-		blockchain.insert(header(7).hash(), header(7), Some(just7.clone()), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(7).hash(),
+			header(7),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, just7.clone()))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 
 		// when querying for finality of 6, we assume that the #3 is the last block known to the requester
 		// => since we only have justification for #7, we provide #7
@@ -1013,7 +1078,13 @@ pub(crate) mod tests {
 		// finality proof at all
 		let blockchain = test_blockchain();
 		let just4 = TestJustification((0, vec![(AuthorityId::from_slice(&[42u8; 32]), 1u64)]), vec![4]).encode();
-		blockchain.insert(header(4).hash(), header(4), Some(just4), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(4).hash(),
+			header(4),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, just4))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 
 		let proof_of_4 = prove_finality::<_, _, TestJustification>(
 			&blockchain,
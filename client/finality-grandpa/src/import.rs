@@ -19,20 +19,20 @@
 use std::{sync::Arc, collections::HashMap};
 
 use log::debug;
-use parity_scale_codec::Encode;
+use parity_scale_codec::{Decode, Encode};
 use parking_lot::RwLockWriteGuard;
 
 use sp_blockchain::{BlockStatus, well_known_cache_keys};
 use sc_client_api::{backend::Backend, utils::is_descendent_of};
 use sp_utils::mpsc::TracingUnboundedSender;
-use sp_api::{TransactionFor};
+use sp_api::TransactionFor;
 
 use sp_consensus::{
 	BlockImport, Error as ConsensusError,
 	BlockCheckParams, BlockImportParams, BlockOrigin, ImportResult, JustificationImport,
 	SelectChain,
 };
-use sp_finality_grandpa::{ConsensusLog, ScheduledChange, SetId, GRANDPA_ENGINE_ID};
+use sp_finality_grandpa::{AuthorityList, ConsensusLog, ScheduledChange, SetId, GRANDPA_ENGINE_ID};
 use sp_runtime::Justification;
 use sp_runtime::generic::{BlockId, OpaqueDigestItemId};
 use sp_runtime::traits::{
@@ -182,7 +182,7 @@ impl<'a, Block: 'a + BlockT> Drop for PendingSetChanges<'a, Block> {
 	}
 }
 
-fn find_scheduled_change<B: BlockT>(header: &B::Header)
+pub(crate) fn find_scheduled_change<B: BlockT>(header: &B::Header)
 	-> Option<ScheduledChange<NumberFor<B>>>
 {
 	let id = OpaqueDigestItemId::Consensus(&GRANDPA_ENGINE_ID);
@@ -399,6 +399,74 @@ where
 
 		Ok(PendingSetChanges { just_in_case, applied_changes, do_pause })
 	}
+
+	/// Import a block along with its state, downloaded by warp sync. Its finality has already
+	/// been proven by the sync module, so the block is imported as finalized and the authority
+	/// set is reset to the one verified along with the proof.
+	fn import_state(
+		&mut self,
+		mut block: BlockImportParams<Block, TransactionFor<Client, Block>>,
+		new_cache: HashMap<well_known_cache_keys::Id, Vec<u8>>,
+	) -> Result<ImportResult, ConsensusError> where
+		for<'a> &'a Client:
+			BlockImport<Block, Error = ConsensusError, Transaction = TransactionFor<Client, Block>>,
+	{
+		let hash = block.post_hash();
+		let number = *block.header.number();
+		block.finalized = true;
+
+		// the runtime state is not trusted to describe the authorities, the set proven by the
+		// warp sync proof is.
+		let verified_set = block.imported_state.as_ref()
+			.and_then(|state| state.authority_set.as_ref())
+			.filter(|(engine_id, _)| *engine_id == GRANDPA_ENGINE_ID)
+			.ok_or_else(|| ConsensusError::ClientImport(
+				format!("Missing verified authority set for imported state of block #{}", number)
+			))?;
+		let (set_id, authorities) = <(SetId, AuthorityList)>::decode(&mut &verified_set.1[..])
+			.map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+
+		let authority_set = AuthoritySet::new(
+			authorities.clone(),
+			set_id,
+			fork_tree::ForkTree::new(),
+			Vec::new(),
+		).ok_or_else(|| ConsensusError::ClientImport(
+			format!("Invalid verified authority set for imported block #{}", number)
+		))?;
+
+		let imported_aux = match (&*self.inner).import_block(block, new_cache) {
+			Ok(ImportResult::Imported(aux)) => aux,
+			Ok(r) => return Ok(r),
+			Err(e) => return Err(ConsensusError::ClientImport(e.to_string())),
+		};
+		let new_set = NewAuthoritySet {
+			canon_number: number,
+			canon_hash: hash,
+			set_id,
+			authorities,
+		};
+
+		let mut authority_set_lock = self.authority_set.inner().write();
+		crate::aux_schema::update_authority_set::<Block, _, _>(
+			&authority_set,
+			Some(&new_set),
+			|insert| self.inner.insert_aux(insert, &[]),
+		).map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+		*authority_set_lock = authority_set;
+		drop(authority_set_lock);
+
+		debug!(
+			target: "afg",
+			"Imported state of block #{} ({}), authority set id is now {}",
+			number,
+			hash,
+			set_id,
+		);
+		let _ = self.send_voter_commands.unbounded_send(VoterCommand::ChangeAuthorities(new_set));
+
+		Ok(ImportResult::Imported(imported_aux))
+	}
 }
 
 impl<BE, Block: BlockT, Client, SC> BlockImport<Block>
@@ -429,13 +497,22 @@ impl<BE, Block: BlockT, Client, SC> BlockImport<Block>
 			Err(e) => return Err(ConsensusError::ClientImport(e.to_string())),
 		}
 
+		// the state of a warp synced block is imported without its ancestry.
+		if block.imported_state.is_some() {
+			let parent_known = self.inner.status(BlockId::Hash(*block.header.parent_hash()))
+				.map_err(|e| ConsensusError::ClientImport(e.to_string()))? == BlockStatus::InChain;
+			if !parent_known {
+				return self.import_state(block, new_cache);
+			}
+		}
+
 		// on initial sync we will restrict logging under info to avoid spam.
 		let initial_sync = block.origin == BlockOrigin::NetworkInitialSync;
 
 		let pending_changes = self.make_authorities_changes(&mut block, hash, initial_sync)?;
 
 		// we don't want to finalize on `inner.import_block`
-		let mut justification = block.justification.take();
+		let mut justifications = block.justifications.take();
 		let import_result = (&*self.inner).import_block(block, new_cache);
 
 		let mut imported_aux = {
@@ -497,17 +574,17 @@ impl<BE, Block: BlockT, Client, SC> BlockImport<Block>
 				// need to apply first, drop any justification that might have been provided with
 				// the block to make sure we request them from `sync` which will ensure they'll be
 				// applied in-order.
-				justification.take();
+				justifications.take();
 			},
 			_ => {},
 		}
 
-		match justification {
+		match justifications.and_then(|js| js.into_justification(GRANDPA_ENGINE_ID)) {
 			Some(justification) => {
 				let import_res = self.import_justification(
 					hash,
 					number,
-					justification,
+					(GRANDPA_ENGINE_ID, justification),
 					needs_justification,
 					initial_sync,
 				);
@@ -619,8 +696,15 @@ where
 		enacts_change: bool,
 		initial_sync: bool,
 	) -> Result<(), ConsensusError> {
+		if justification.0 != GRANDPA_ENGINE_ID {
+			// the import queue hands every justification of a block to the single
+			// `JustificationImport` it was built with, justifications of other engines
+			// (e.g. BEEFY) are not ours to verify.
+			return Ok(());
+		}
+
 		let justification = GrandpaJustification::decode_and_verify_finalizes(
-			&justification,
+			&justification.1,
 			(hash, number),
 			self.authority_set.set_id(),
 			&self.authority_set.current_authorities(),
@@ -120,6 +120,7 @@ mod notification;
 mod observer;
 mod until_imported;
 mod voting_rule;
+pub mod warp_proof;
 
 pub use authorities::{SharedAuthoritySet, AuthoritySet};
 pub use communication::GRANDPA_PROTOCOL_NAME;
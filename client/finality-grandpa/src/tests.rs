@@ -185,6 +185,10 @@ sp_api::mock_impl_runtime_apis! {
 			self.inner.genesis_authorities.clone()
 		}
 
+		fn current_set_id(&self) -> SetId {
+			0
+		}
+
 		fn submit_report_equivocation_unsigned_extrinsic(
 			_equivocation_proof: EquivocationProof<Hash, BlockNumber>,
 			_key_owner_proof: OpaqueKeyOwnershipProof,
@@ -411,7 +415,7 @@ fn finalize_3_voters_no_observers() {
 
 	// normally there's no justification for finalized blocks
 	assert!(
-		net.lock().peer(0).client().justification(&BlockId::Number(20)).unwrap().is_none(),
+		net.lock().peer(0).client().justifications(&BlockId::Number(20)).unwrap().is_none(),
 		"Extra justification for block#1",
 	);
 }
@@ -653,7 +657,7 @@ fn justification_is_generated_periodically() {
 	// when block#32 (justification_period) is finalized, justification
 	// is required => generated
 	for i in 0..3 {
-		assert!(net.lock().peer(i).client().justification(&BlockId::Number(32)).unwrap().is_some());
+		assert!(net.lock().peer(i).client().justifications(&BlockId::Number(32)).unwrap().is_some());
 	}
 }
 
@@ -698,12 +702,12 @@ fn sync_justifications_on_change_blocks() {
 	// the first 3 peers are grandpa voters and therefore have already finalized
 	// block 21 and stored a justification
 	for i in 0..3 {
-		assert!(net.lock().peer(i).client().justification(&BlockId::Number(21)).unwrap().is_some());
+		assert!(net.lock().peer(i).client().justifications(&BlockId::Number(21)).unwrap().is_some());
 	}
 
 	// the last peer should get the justification by syncing from other peers
 	futures::executor::block_on(futures::future::poll_fn(move |cx| {
-		if net.lock().peer(3).client().justification(&BlockId::Number(21)).unwrap().is_none() {
+		if net.lock().peer(3).client().justifications(&BlockId::Number(21)).unwrap().is_none() {
 			net.lock().poll(cx);
 			Poll::Pending
 		} else {
@@ -908,7 +912,7 @@ fn test_bad_justification() {
 	let block = || {
 		let block = block.clone();
 		let mut import = BlockImportParams::new(BlockOrigin::File, block.header);
-		import.justification = Some(Vec::new());
+		import.justifications = Some((GRANDPA_ENGINE_ID, Vec::new()).into());
 		import.body = Some(block.extrinsics);
 		import.fork_choice = Some(ForkChoiceStrategy::LongestChain);
 
@@ -1614,7 +1618,7 @@ fn imports_justification_for_regular_blocks_on_import() {
 
 	// we import the block with justification attached
 	let mut import = BlockImportParams::new(BlockOrigin::File, block.header);
-	import.justification = Some(justification.encode());
+	import.justifications = Some((GRANDPA_ENGINE_ID, justification.encode()).into());
 	import.body = Some(block.extrinsics);
 	import.fork_choice = Some(ForkChoiceStrategy::LongestChain);
 
@@ -1631,7 +1635,7 @@ fn imports_justification_for_regular_blocks_on_import() {
 
 	// the justification should be imported and available from the client
 	assert!(
-		client.justification(&BlockId::Hash(block_hash)).unwrap().is_some(),
+		client.justifications(&BlockId::Hash(block_hash)).unwrap().is_some(),
 	);
 }
 
@@ -1686,3 +1690,51 @@ fn grandpa_environment_doesnt_send_equivocation_reports_for_itself() {
 	let equivocation_proof = sp_finality_grandpa::Equivocation::Prevote(equivocation);
 	assert!(environment.report_equivocation(equivocation_proof).is_ok());
 }
+
+#[test]
+fn imported_state_resets_authority_set_to_verified_set() {
+	let api = TestApi::new(make_ids(&[Ed25519Keyring::Alice]));
+	let mut net = GrandpaTestNet::new(api, 2);
+	net.peer(1).push_blocks(3, false);
+
+	// the state of block #3 is imported into the first peer, which doesn't know its ancestry.
+	let source = net.peer(1).client().as_full().unwrap();
+	let header = source.header(&BlockId::Number(3)).unwrap().unwrap();
+	let top = sc_client_api::StorageProvider::storage_pairs(
+		&*source,
+		&BlockId::Number(3),
+		&sp_core::storage::StorageKey(Vec::new()),
+	).unwrap().into_iter().map(|(key, value)| (key.0, value.0)).collect();
+	let state = sp_runtime::Storage { top, children_default: Default::default() };
+
+	let client = net.peer(0).client().clone();
+	let (mut block_import, _, link) = net.make_block_import::<
+		TransactionFor<substrate_test_runtime_client::Backend, Block>
+	>(client);
+	let make_import = |authority_set: Option<(sp_runtime::ConsensusEngineId, Vec<u8>)>| {
+		let mut import = BlockImportParams::new(BlockOrigin::NetworkInitialSync, header.clone());
+		import.imported_state = Some(sp_consensus::ImportedState {
+			block: header.hash(),
+			state: state.clone(),
+			authority_set,
+		});
+		import.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+		import
+	};
+
+	// the authorities in the runtime state are not trusted.
+	assert!(block_import.import_block(make_import(None), HashMap::new()).is_err());
+
+	let voters = make_ids(&[Ed25519Keyring::Bob, Ed25519Keyring::Charlie]);
+	let verified_set = Some((GRANDPA_ENGINE_ID, (3u64, voters.clone()).encode()));
+	assert_matches!(
+		block_import.import_block(make_import(verified_set), HashMap::new()),
+		Ok(ImportResult::Imported(_))
+	);
+
+	let link = link.lock().take().unwrap();
+	let authority_set = link.shared_authority_set().inner().read();
+	assert_eq!(authority_set.set_id, 3);
+	assert_eq!(authority_set.current_authorities, voters);
+	assert_eq!(net.peer(0).client().info().finalized_hash, header.hash());
+}
@@ -0,0 +1,413 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! GRANDPA warp sync proof generation and verification.
+//!
+//! Finality of the latest finalized block is proved, starting from a block known to be final,
+//! by providing:
+//! 1) for each authority set change since the start block, the header of the block that enacts
+//!    the change and its justification, signed by the set that is being replaced;
+//! 2) the header and justification of the latest finalized block that has one.
+//!
+//! Headers of set change blocks carry the scheduled change digest, which gives the authorities
+//! that must have signed the next justification. Only changes that are scheduled with no delay
+//! can be proven this way.
+
+use std::sync::Arc;
+
+use finality_grandpa::BlockNumberOps;
+use parity_scale_codec::{Encode, Decode};
+use sc_client_api::Backend as ClientBackend;
+use sc_network::config::{EncodedProof, VerificationResult, WarpSyncProvider};
+use sp_blockchain::{Backend as BlockchainBackend, HeaderBackend};
+use sp_finality_grandpa::{AuthorityList, SetId, GRANDPA_ENGINE_ID};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, NumberFor},
+};
+
+use crate::{
+	aux_schema::{load_authority_set_change, load_best_justification},
+	import::find_scheduled_change,
+	justification::GrandpaJustification,
+	SharedAuthoritySet, VoterSet,
+};
+
+/// Maximum size of a warp sync proof, in bytes. Must be below the network response size limit.
+const MAX_WARP_SYNC_PROOF_SIZE: usize = 8 * 1024 * 1024;
+
+/// Warp proof processing error.
+#[derive(Debug, derive_more::Display, derive_more::From)]
+pub enum Error {
+	/// Decoding error.
+	#[display(fmt = "Failed to decode warp proof: {}", _0)]
+	Decode(parity_scale_codec::Error),
+	/// Client backend error.
+	Client(sp_blockchain::Error),
+	/// Invalid request data.
+	#[from(ignore)]
+	#[display(fmt = "Invalid warp proof request: {}", _0)]
+	InvalidRequest(String),
+	/// Invalid warp proof.
+	#[from(ignore)]
+	#[display(fmt = "Invalid warp proof: {}", _0)]
+	InvalidProof(String),
+	/// Missing header, justification or authority set change data.
+	#[from(ignore)]
+	#[display(fmt = "Missing data to generate the warp proof: {}", _0)]
+	MissingData(String),
+}
+
+impl std::error::Error for Error {}
+
+/// A proof of an authority set change, or of the finality of the latest finalized block.
+#[derive(Decode, Encode, Debug)]
+pub struct WarpSyncFragment<Block: BlockT> {
+	/// The last block that the given authority set finalized. This block should contain a digest
+	/// signaling an authority set change from which we can fetch the next authority set, unless
+	/// it is the last fragment of the proof.
+	pub header: Block::Header,
+	/// A justification for the header above which proves its finality. In order to validate it
+	/// the verifier must be aware of the authorities and set id for which the justification
+	/// refers to.
+	pub justification: GrandpaJustification<Block>,
+}
+
+/// An accumulated proof of multiple authority set changes.
+#[derive(Decode, Encode, Debug)]
+pub struct WarpSyncProof<Block: BlockT> {
+	proofs: Vec<WarpSyncFragment<Block>>,
+	is_finished: bool,
+}
+
+impl<Block: BlockT> WarpSyncProof<Block> {
+	/// Generates a warp sync proof starting at the given block. It will generate authority set
+	/// change proofs for all changes that happened from `begin` until the current authority set,
+	/// followed by the latest justification, unless the proof size limit is reached first.
+	fn generate<Backend>(backend: &Backend, begin: Block::Hash) -> Result<Self, Error> where
+		Backend: ClientBackend<Block>,
+	{
+		let blockchain = backend.blockchain();
+
+		let begin_number = blockchain.block_number_from_id(&BlockId::Hash(begin))?
+			.ok_or_else(|| Error::InvalidRequest("Missing start block".to_string()))?;
+
+		if begin_number > blockchain.info().finalized_number {
+			return Err(Error::InvalidRequest("Start block is not finalized".to_string()));
+		}
+
+		let canon_hash = blockchain.hash(begin_number)?
+			.expect("begin number is lower than finalized number; qed");
+		if canon_hash != begin {
+			return Err(Error::InvalidRequest(
+				"Start block is not in the finalized chain".to_string(),
+			));
+		}
+
+		let mut proofs = Vec::new();
+		let mut proofs_encoded_len = 0;
+		let mut proof_limit_reached = false;
+
+		let mut set_id: SetId = 0;
+		while let Some((hash, number)) = load_authority_set_change::<Block, _>(backend, set_id)? {
+			set_id += 1;
+
+			if number <= begin_number {
+				continue;
+			}
+
+			let header = blockchain.expect_header(BlockId::Hash(hash))?;
+			if find_scheduled_change::<Block>(&header).is_none() {
+				// the next authority set can only be proven from the header of the block that
+				// enacts the change.
+				return Err(Error::MissingData(
+					format!("No scheduled change digest in set change block #{}", number),
+				));
+			}
+
+			let proof = WarpSyncFragment {
+				justification: load_justification(blockchain, hash, number)?,
+				header,
+			};
+			let proof_size = proof.encoded_size();
+
+			// leave some room for the encoding of the fragment count and the final flag.
+			if proofs_encoded_len + proof_size >= MAX_WARP_SYNC_PROOF_SIZE - 50 {
+				proof_limit_reached = true;
+				break;
+			}
+
+			proofs_encoded_len += proof_size;
+			proofs.push(proof);
+		}
+
+		let is_finished = if proof_limit_reached {
+			false
+		} else {
+			if let Some((hash, number)) = load_best_justification::<Block, _>(backend)? {
+				let already_proven = proofs.last()
+					.map_or(false, |proof| proof.header.hash() == hash);
+
+				if number > begin_number && !already_proven {
+					proofs.push(WarpSyncFragment {
+						justification: load_justification(blockchain, hash, number)?,
+						header: blockchain.expect_header(BlockId::Hash(hash))?,
+					});
+				}
+			}
+
+			true
+		};
+
+		if proofs.is_empty() {
+			return Err(Error::MissingData("No finality proof after the start block".to_string()));
+		}
+
+		Ok(WarpSyncProof { proofs, is_finished })
+	}
+
+	/// Verifies the warp sync proof starting at the given set id and with the given authorities.
+	/// If the proof is valid the new set id and authorities is returned.
+	fn verify(
+		&self,
+		set_id: SetId,
+		authorities: AuthorityList,
+	) -> Result<(SetId, AuthorityList), Error> where
+		NumberFor<Block>: BlockNumberOps,
+	{
+		let mut current_set_id = set_id;
+		let mut current_authorities = authorities;
+
+		for (fragment_num, proof) in self.proofs.iter().enumerate() {
+			let voters = VoterSet::new(current_authorities.iter().cloned())
+				.ok_or_else(|| Error::InvalidProof("Invalid authority set".to_string()))?;
+
+			proof.justification.verify(current_set_id, &voters)
+				.map_err(|e| Error::InvalidProof(e.to_string()))?;
+
+			let commit = &proof.justification.commit;
+			if (commit.target_hash, commit.target_number) != (proof.header.hash(), *proof.header.number()) {
+				return Err(Error::InvalidProof("Mismatch between header and justification".to_string()));
+			}
+
+			if let Some(scheduled_change) = find_scheduled_change::<Block>(&proof.header) {
+				current_authorities = scheduled_change.next_authorities;
+				current_set_id += 1;
+			} else if fragment_num != self.proofs.len() - 1 {
+				// only the last fragment may not contain an authority set change.
+				return Err(Error::InvalidProof(
+					"Header is missing authority set change digest".to_string(),
+				));
+			}
+		}
+
+		Ok((current_set_id, current_authorities))
+	}
+}
+
+fn load_justification<Block: BlockT, B: BlockchainBackend<Block>>(
+	blockchain: &B,
+	hash: Block::Hash,
+	number: NumberFor<Block>,
+) -> Result<GrandpaJustification<Block>, Error> {
+	let justification = blockchain.justifications(BlockId::Hash(hash))?
+		.and_then(|justifications| justifications.into_justification(GRANDPA_ENGINE_ID))
+		.ok_or_else(|| Error::MissingData(format!("No justification for block #{}", number)))?;
+
+	Ok(GrandpaJustification::<Block>::decode(&mut &justification[..])?)
+}
+
+/// Implements network API for warp sync.
+pub struct NetworkProvider<Block: BlockT, Backend: ClientBackend<Block>> {
+	backend: Arc<Backend>,
+	authority_set: SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
+}
+
+impl<Block: BlockT, Backend: ClientBackend<Block>> NetworkProvider<Block, Backend> {
+	/// Create a new instance for a given backend and authority set.
+	pub fn new(
+		backend: Arc<Backend>,
+		authority_set: SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
+	) -> Self {
+		NetworkProvider { backend, authority_set }
+	}
+}
+
+impl<Block: BlockT, Backend: ClientBackend<Block> + 'static> WarpSyncProvider<Block>
+	for NetworkProvider<Block, Backend>
+where
+	NumberFor<Block>: BlockNumberOps,
+{
+	fn generate(
+		&self,
+		begin: Block::Hash,
+	) -> Result<EncodedProof, Box<dyn std::error::Error + Send + Sync>> {
+		let proof = WarpSyncProof::<Block>::generate(&*self.backend, begin)?;
+		Ok(EncodedProof(proof.encode()))
+	}
+
+	fn verify(
+		&self,
+		proof: &EncodedProof,
+		set_id: SetId,
+		authorities: AuthorityList,
+	) -> Result<VerificationResult<Block>, Box<dyn std::error::Error + Send + Sync>> {
+		let EncodedProof(proof) = proof;
+		let proof = WarpSyncProof::<Block>::decode(&mut proof.as_slice())
+			.map_err(Error::Decode)?;
+		let last_header = proof.proofs.last()
+			.map(|p| p.header.clone())
+			.ok_or_else(|| Error::InvalidProof("Empty proof".to_string()))?;
+		let (next_set_id, next_authorities) = proof.verify(set_id, authorities)?;
+		if proof.is_finished {
+			Ok(VerificationResult::Complete(next_set_id, next_authorities, last_header))
+		} else {
+			Ok(VerificationResult::Partial(next_set_id, next_authorities, last_header.hash()))
+		}
+	}
+
+	fn current_authorities(&self) -> AuthorityList {
+		let authority_set = self.authority_set.inner().read();
+		if authority_set.set_id != 0 {
+			log::warn!(
+				target: "afg",
+				"Warp sync started with authority set {} instead of the genesis set",
+				authority_set.set_id,
+			);
+		}
+		authority_set.current_authorities.clone()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_block_builder::BlockBuilderProvider;
+	use sc_client_api::AuxStore;
+	use sp_consensus::BlockOrigin;
+	use sp_finality_grandpa::{ConsensusLog, ScheduledChange};
+	use sp_keyring::Ed25519Keyring;
+	use sp_runtime::generic::{Digest, DigestItem};
+	use substrate_test_runtime_client::{
+		ClientBlockImportExt, ClientExt, DefaultTestClientBuilderExt, TestClientBuilder,
+		TestClientBuilderExt, runtime::Block,
+	};
+
+	#[test]
+	fn warp_sync_proof_generate_verify() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let mut client = Arc::new(builder.build());
+
+		let available_authorities = Ed25519Keyring::iter().collect::<Vec<_>>();
+		let genesis_authorities: AuthorityList = vec![(Ed25519Keyring::Alice.public().into(), 1)];
+
+		let mut current_authorities = vec![Ed25519Keyring::Alice];
+		let mut current_set_id = 0;
+
+		for n in 1..=100u64 {
+			let mut digest = Digest::default();
+
+			// trigger an authority set change every 10 blocks
+			let new_authorities = if n % 10 == 0 {
+				let count = 1 + (n as usize / 10) % available_authorities.len();
+				let next_authorities = available_authorities[..count].to_vec();
+
+				digest.push(DigestItem::Consensus(
+					GRANDPA_ENGINE_ID,
+					ConsensusLog::ScheduledChange(ScheduledChange {
+						delay: 0u64,
+						next_authorities: next_authorities.iter()
+							.map(|keyring| (keyring.public().into(), 1))
+							.collect(),
+					}).encode(),
+				));
+
+				Some(next_authorities)
+			} else {
+				None
+			};
+
+			let block = client.new_block(digest).unwrap().build().unwrap().block;
+			client.import(BlockOrigin::Own, block).unwrap();
+
+			if let Some(new_authorities) = new_authorities {
+				// finalize the block with a justification and note the authority set change.
+				let info = client.info();
+				let (target_hash, target_number) = (info.best_hash, info.best_number);
+
+				let precommits = current_authorities.iter().map(|keyring| {
+					let precommit = finality_grandpa::Precommit { target_hash, target_number };
+					let msg = finality_grandpa::Message::Precommit(precommit.clone());
+					let encoded = sp_finality_grandpa::localized_payload(42, current_set_id, &msg);
+
+					finality_grandpa::SignedPrecommit {
+						precommit,
+						signature: keyring.sign(&encoded[..]).into(),
+						id: keyring.public().into(),
+					}
+				}).collect();
+
+				let commit = finality_grandpa::Commit { target_hash, target_number, precommits };
+				let justification = GrandpaJustification::from_commit(&client, 42, commit).unwrap();
+
+				client.finalize_block(
+					BlockId::Hash(target_hash),
+					Some((GRANDPA_ENGINE_ID, justification.encode())),
+				).unwrap();
+
+				crate::aux_schema::write_authority_set_change::<Block, _, _>(
+					current_set_id,
+					(target_hash, target_number),
+					|insert| client.insert_aux(insert, &[]),
+				).unwrap();
+				crate::aux_schema::write_best_justification::<Block, _, _>(
+					(target_hash, target_number),
+					|insert| client.insert_aux(insert, &[]),
+				).unwrap();
+
+				current_set_id += 1;
+				current_authorities = new_authorities;
+			}
+		}
+
+		let genesis_hash = client.hash(0).unwrap().unwrap();
+		let warp_sync_proof = WarpSyncProof::<Block>::generate(&*backend, genesis_hash).unwrap();
+		assert!(warp_sync_proof.is_finished);
+		assert_eq!(warp_sync_proof.proofs.len(), 10);
+
+		// verifying the proof should yield the last set id and authorities.
+		let (new_set_id, new_authorities) = warp_sync_proof.verify(0, genesis_authorities.clone())
+			.unwrap();
+
+		let expected_authorities: AuthorityList = current_authorities.iter()
+			.map(|keyring| (keyring.public().into(), 1))
+			.collect();
+
+		assert_eq!(new_set_id, current_set_id);
+		assert_eq!(new_authorities, expected_authorities);
+
+		// a proof starting at a later set change only contains the remaining changes.
+		let begin = client.hash(50).unwrap().unwrap();
+		let warp_sync_proof = WarpSyncProof::<Block>::generate(&*backend, begin).unwrap();
+		assert_eq!(warp_sync_proof.proofs.len(), 5);
+
+		// verifying it with the wrong authority set fails.
+		assert!(warp_sync_proof.verify(0, genesis_authorities).is_err());
+	}
+}
@@ -96,6 +96,11 @@ impl<'g, 'p, B: BlockT> ValidatorContext<B> for NetworkContext<'g, 'p, B> {
 	fn send_topic(&mut self, who: &PeerId, topic: B::Hash, force: bool) {
 		self.gossip.send_topic(self.network, who, topic, force);
 	}
+
+	/// Adjust the reputation of a peer.
+	fn report_peer(&mut self, who: &PeerId, reputation: sc_network::ReputationChange) {
+		self.network.report_peer(who.clone(), reputation);
+	}
 }
 
 fn propagate<'a, B: BlockT, I>(
@@ -15,7 +15,7 @@
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
-	config::{ProtocolId, Role}, block_requests, light_client_handler,
+	config::{ProtocolId, Role}, block_requests, state_requests, warp_requests, light_client_handler,
 	peer_info, request_responses, discovery::{DiscoveryBehaviour, DiscoveryConfig, DiscoveryOut},
 	protocol::{message::Roles, CustomMessageOutcome, NotificationsSink, Protocol},
 	ObservedRole, DhtEvent, ExHashT,
@@ -30,7 +30,7 @@ use libp2p::kad::record;
 use libp2p::swarm::{NetworkBehaviourAction, NetworkBehaviourEventProcess, PollParameters};
 use log::debug;
 use sp_consensus::{BlockOrigin, import_queue::{IncomingBlock, Origin}};
-use sp_runtime::{traits::{Block as BlockT, NumberFor}, Justification};
+use sp_runtime::{traits::{Block as BlockT, NumberFor}, Justifications};
 use std::{
 	borrow::Cow,
 	collections::{HashSet, VecDeque},
@@ -58,6 +58,10 @@ pub struct Behaviour<B: BlockT, H: ExHashT> {
 	request_responses: request_responses::RequestResponsesBehaviour,
 	/// Block request handling.
 	block_requests: block_requests::BlockRequests<B>,
+	/// State request handling.
+	state_requests: state_requests::StateRequests<B>,
+	/// Answers and sends warp sync proof requests.
+	warp_requests: warp_requests::WarpRequests<B>,
 	/// Light client request handling.
 	light_client_handler: light_client_handler::LightClientHandler<B>,
 
@@ -73,7 +77,7 @@ pub struct Behaviour<B: BlockT, H: ExHashT> {
 /// Event generated by `Behaviour`.
 pub enum BehaviourOut<B: BlockT> {
 	BlockImport(BlockOrigin, Vec<IncomingBlock<B>>),
-	JustificationImport(Origin, B::Hash, NumberFor<B>, Justification),
+	JustificationImport(Origin, B::Hash, NumberFor<B>, Justifications),
 
 	/// Started a random iterative Kademlia discovery query.
 	RandomKademliaStarted(ProtocolId),
@@ -179,6 +183,8 @@ impl<B: BlockT, H: ExHashT> Behaviour<B, H> {
 		user_agent: String,
 		local_public_key: PublicKey,
 		block_requests: block_requests::BlockRequests<B>,
+		state_requests: state_requests::StateRequests<B>,
+		warp_requests: warp_requests::WarpRequests<B>,
 		light_client_handler: light_client_handler::LightClientHandler<B>,
 		disco_config: DiscoveryConfig,
 		request_response_protocols: Vec<request_responses::ProtocolConfig>,
@@ -190,6 +196,8 @@ impl<B: BlockT, H: ExHashT> Behaviour<B, H> {
 			request_responses:
 				request_responses::RequestResponsesBehaviour::new(request_response_protocols.into_iter())?,
 			block_requests,
+			state_requests,
+			warp_requests,
 			light_client_handler,
 			events: VecDeque::new(),
 			role,
@@ -327,8 +335,8 @@ Behaviour<B, H> {
 		match event {
 			CustomMessageOutcome::BlockImport(origin, blocks) =>
 				self.events.push_back(BehaviourOut::BlockImport(origin, blocks)),
-			CustomMessageOutcome::JustificationImport(origin, hash, nb, justification) =>
-				self.events.push_back(BehaviourOut::JustificationImport(origin, hash, nb, justification)),
+			CustomMessageOutcome::JustificationImport(origin, hash, nb, justifications) =>
+				self.events.push_back(BehaviourOut::JustificationImport(origin, hash, nb, justifications)),
 			CustomMessageOutcome::BlockRequest { target, request } => {
 				match self.block_requests.send_request(&target, request) {
 					block_requests::SendRequestOutcome::Ok => {
@@ -352,6 +360,51 @@ Behaviour<B, H> {
 					block_requests::SendRequestOutcome::EncodeError(_) => {},
 				}
 			},
+			CustomMessageOutcome::StateRequest { target, request } => {
+				match self.state_requests.send_request(&target, request) {
+					state_requests::SendRequestOutcome::Ok => {
+						self.events.push_back(BehaviourOut::OpaqueRequestStarted {
+							peer: target,
+							protocol: self.state_requests.protocol_name().to_owned(),
+						});
+					},
+					state_requests::SendRequestOutcome::Replaced { request_duration, .. } => {
+						self.events.push_back(BehaviourOut::OpaqueRequestFinished {
+							peer: target.clone(),
+							protocol: self.state_requests.protocol_name().to_owned(),
+							request_duration,
+						});
+						self.events.push_back(BehaviourOut::OpaqueRequestStarted {
+							peer: target,
+							protocol: self.state_requests.protocol_name().to_owned(),
+						});
+					}
+					state_requests::SendRequestOutcome::NotConnected |
+					state_requests::SendRequestOutcome::EncodeError(_) => {},
+				}
+			},
+			CustomMessageOutcome::WarpSyncRequest { target, request } => {
+				match self.warp_requests.send_request(&target, request) {
+					warp_requests::SendRequestOutcome::Ok => {
+						self.events.push_back(BehaviourOut::OpaqueRequestStarted {
+							peer: target,
+							protocol: self.warp_requests.protocol_name().to_owned(),
+						});
+					},
+					warp_requests::SendRequestOutcome::Replaced { request_duration, .. } => {
+						self.events.push_back(BehaviourOut::OpaqueRequestFinished {
+							peer: target.clone(),
+							protocol: self.warp_requests.protocol_name().to_owned(),
+							request_duration,
+						});
+						self.events.push_back(BehaviourOut::OpaqueRequestStarted {
+							peer: target,
+							protocol: self.warp_requests.protocol_name().to_owned(),
+						});
+					}
+					warp_requests::SendRequestOutcome::NotConnected => {},
+				}
+			},
 			CustomMessageOutcome::NotificationStreamOpened { remote, protocols, roles, notifications_sink } => {
 				let role = reported_roles_to_observed_role(&self.role, &remote, roles);
 				for protocol in protocols {
@@ -444,6 +497,70 @@ impl<B: BlockT, H: ExHashT> NetworkBehaviourEventProcess<block_requests::Event<B
 	}
 }
 
+impl<B: BlockT, H: ExHashT> NetworkBehaviourEventProcess<state_requests::Event> for Behaviour<B, H> {
+	fn inject_event(&mut self, event: state_requests::Event) {
+		match event {
+			state_requests::Event::AnsweredRequest { peer, total_handling_time } => {
+				self.events.push_back(BehaviourOut::InboundRequest {
+					peer,
+					protocol: self.state_requests.protocol_name().to_owned().into(),
+					result: Ok(Some(total_handling_time)),
+				});
+			},
+			state_requests::Event::Response { peer, response, request_duration } => {
+				self.events.push_back(BehaviourOut::OpaqueRequestFinished {
+					peer: peer.clone(),
+					protocol: self.state_requests.protocol_name().to_owned(),
+					request_duration,
+				});
+				let ev = self.substrate.on_state_response(peer, response);
+				self.inject_event(ev);
+			}
+			state_requests::Event::RequestCancelled { peer, request_duration, .. } |
+			state_requests::Event::RequestTimeout { peer, request_duration, .. } => {
+				self.events.push_back(BehaviourOut::OpaqueRequestFinished {
+					peer: peer.clone(),
+					protocol: self.state_requests.protocol_name().to_owned(),
+					request_duration,
+				});
+				self.substrate.on_state_request_failed(&peer);
+			}
+		}
+	}
+}
+
+impl<B: BlockT, H: ExHashT> NetworkBehaviourEventProcess<warp_requests::Event> for Behaviour<B, H> {
+	fn inject_event(&mut self, event: warp_requests::Event) {
+		match event {
+			warp_requests::Event::AnsweredRequest { peer, total_handling_time } => {
+				self.events.push_back(BehaviourOut::InboundRequest {
+					peer,
+					protocol: self.warp_requests.protocol_name().to_owned().into(),
+					result: Ok(Some(total_handling_time)),
+				});
+			},
+			warp_requests::Event::Response { peer, response, request_duration } => {
+				self.events.push_back(BehaviourOut::OpaqueRequestFinished {
+					peer: peer.clone(),
+					protocol: self.warp_requests.protocol_name().to_owned(),
+					request_duration,
+				});
+				let ev = self.substrate.on_warp_sync_response(peer, response);
+				self.inject_event(ev);
+			}
+			warp_requests::Event::RequestCancelled { peer, request_duration, .. } |
+			warp_requests::Event::RequestTimeout { peer, request_duration, .. } => {
+				self.events.push_back(BehaviourOut::OpaqueRequestFinished {
+					peer: peer.clone(),
+					protocol: self.warp_requests.protocol_name().to_owned(),
+					request_duration,
+				});
+				self.substrate.on_warp_sync_request_failed(&peer);
+			}
+		}
+	}
+}
+
 impl<B: BlockT, H: ExHashT> NetworkBehaviourEventProcess<peer_info::PeerInfoEvent>
 	for Behaviour<B, H> {
 	fn inject_event(&mut self, event: peer_info::PeerInfoEvent) {
@@ -53,7 +53,8 @@ use libp2p::{
 	}
 };
 use prost::Message;
-use sp_runtime::{generic::BlockId, traits::{Block, Header, One, Zero}};
+use sp_finality_grandpa::GRANDPA_ENGINE_ID;
+use sp_runtime::{generic::BlockId, traits::{Block, Header, One, Zero}, Justifications};
 use std::{
 	cmp::min,
 	collections::{HashMap, VecDeque},
@@ -387,6 +388,7 @@ where
 		let get_header = attributes.contains(BlockAttributes::HEADER);
 		let get_body = attributes.contains(BlockAttributes::BODY);
 		let get_justification = attributes.contains(BlockAttributes::JUSTIFICATION);
+		let support_multiple_justifications = request.support_multiple_justifications;
 
 		let mut blocks = Vec::new();
 		let mut block_id = from_block_id;
@@ -401,22 +403,45 @@ where
 			let number = *header.number();
 			let hash = header.hash();
 			let parent_hash = *header.parent_hash();
-			let justification = if get_justification {
-				self.chain.justification(&BlockId::Hash(hash))?
+			let justifications = if get_justification {
+				self.chain.justifications(&BlockId::Hash(hash))?
 			} else {
 				None
 			};
-			let is_empty_justification = justification.as_ref().map(|j| j.is_empty()).unwrap_or(false);
+
+			// Requesters that don't support multiple justifications only get the GRANDPA one.
+			let (justifications, justification, is_empty_justification) =
+				if support_multiple_justifications {
+					let justifications = match justifications {
+						Some(v) => v.encode(),
+						None => Vec::new(),
+					};
+					(justifications, Vec::new(), false)
+				} else {
+					let justification = justifications
+						.and_then(|just| just.into_justification(GRANDPA_ENGINE_ID));
+					let is_empty_justification = justification.as_ref()
+						.map(|j| j.is_empty())
+						.unwrap_or(false);
+					(Vec::new(), justification.unwrap_or_default(), is_empty_justification)
+				};
 
 			let body = if get_body {
-				match self.chain.block_body(&BlockId::Hash(hash))? {
-					Some(mut extrinsics) => extrinsics.iter_mut()
+				match self.chain.block_body(&BlockId::Hash(hash)) {
+					Ok(Some(mut extrinsics)) => extrinsics.iter_mut()
 						.map(|extrinsic| extrinsic.encode())
 						.collect(),
-					None => {
+					Ok(None) => {
 						log::trace!(target: "sync", "Missing data for block request.");
 						break;
 					}
+					// Don't send the blocks from here on, the requester has to get their bodies
+					// from a peer that still has them.
+					Err(sp_blockchain::Error::BodyPruned(_)) => {
+						log::trace!(target: "sync", "Body of block {} has been pruned.", hash);
+						break;
+					}
+					Err(err) => return Err(err.into()),
 				}
 			} else {
 				Vec::new()
@@ -432,8 +457,9 @@ where
 				body,
 				receipt: Vec::new(),
 				message_queue: Vec::new(),
-				justification: justification.unwrap_or_default(),
+				justification,
 				is_empty_justification,
+				justifications,
 			};
 
 			total_size += block_data.body.len();
@@ -648,10 +674,12 @@ where
 						} else {
 							None
 						},
-						justification: if !block_data.justification.is_empty() {
-							Some(block_data.justification)
+						justifications: if !block_data.justifications.is_empty() {
+							Some(Justifications::decode(&mut block_data.justifications.as_ref())?)
+						} else if !block_data.justification.is_empty() {
+							Some((GRANDPA_ENGINE_ID, block_data.justification).into())
 						} else if block_data.is_empty_justification {
-							Some(Vec::new())
+							Some((GRANDPA_ENGINE_ID, Vec::new()).into())
 						} else {
 							None
 						},
@@ -853,5 +881,6 @@ pub(crate) fn build_protobuf_block_request<Hash: Encode, Number: Encode>(
 			message::Direction::Descending => schema::v1::Direction::Descending as i32,
 		},
 		max_blocks: max_blocks.unwrap_or(0),
+		support_multiple_justifications: true,
 	}
 }
@@ -24,6 +24,9 @@
 pub use crate::chain::Client;
 pub use crate::on_demand_layer::{AlwaysBadChecker, OnDemand};
 pub use crate::request_responses::{IncomingRequest, ProtocolConfig as RequestResponseConfig};
+pub use crate::warp_requests::{
+	EncodedProof, VerificationResult, WarpProofRequest, WarpSyncProvider,
+};
 pub use libp2p::{identity, core::PublicKey, wasm_ext::ExtTransport, build_multiaddr};
 
 // Note: this re-export shouldn't be part of the public API of the crate and will be removed in
@@ -95,6 +98,10 @@ pub struct Params<B: BlockT, H: ExHashT> {
 
 	/// Registry for recording prometheus metrics to.
 	pub metrics_registry: Option<Registry>,
+
+	/// Generates and verifies warp sync proofs. If `None`, warp sync requests from other nodes
+	/// are not answered and the node can't warp sync itself.
+	pub warp_sync: Option<Arc<dyn WarpSyncProvider<B>>>,
 }
 
 /// Role of the local node.
@@ -357,6 +364,20 @@ impl From<multiaddr::Error> for ParseErr {
 	}
 }
 
+/// Sync operation mode.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SyncMode {
+	/// Full block download and verification.
+	Full,
+	/// Download headers up to a recent finalized block, then download the full state of that
+	/// block instead of executing the history. Only works on chains that provide justifications
+	/// for finalized blocks, e.g. GRANDPA with a justification period.
+	Fast,
+	/// Download a proof of finality of the latest finalized block, then download the full state
+	/// of that block, skipping the headers below it. Requires a `WarpSyncProvider`.
+	Warp,
+}
+
 /// Network service configuration.
 #[derive(Clone, Debug)]
 pub struct NetworkConfiguration {
@@ -390,6 +411,8 @@ pub struct NetworkConfiguration {
 	pub transport: TransportConfig,
 	/// Maximum number of peers to ask the same blocks in parallel.
 	pub max_parallel_downloads: u32,
+	/// Initial syncing mode.
+	pub sync_mode: SyncMode,
 	/// Should we insert non-global addresses into the DHT?
 	pub allow_non_globals_in_dht: bool,
 	/// Require iterative Kademlia DHT queries to use disjoint paths for increased resiliency in the
@@ -425,6 +448,7 @@ impl NetworkConfiguration {
 				wasm_external_transport: None,
 			},
 			max_parallel_downloads: 5,
+			sync_mode: SyncMode::Full,
 			allow_non_globals_in_dht: false,
 			kademlia_disjoint_query_paths: false,
 		}
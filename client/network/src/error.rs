@@ -67,6 +67,12 @@ pub enum Error {
 		/// Name of the protocol registered multiple times.
 		protocol: Cow<'static, str>,
 	},
+	/// Warp sync was requested but this node can't use it.
+	#[display(fmt = "Warp sync is not supported: {}", reason)]
+	WarpSyncNotSupported {
+		/// Why warp sync can't be used.
+		reason: &'static str,
+	},
 }
 
 // Make `Debug` use the `Display` implementation.
@@ -85,6 +91,7 @@ impl std::error::Error for Error {
 			Error::Prometheus(ref err) => Some(err),
 			Error::AddressesForAnotherTransport { .. } => None,
 			Error::DuplicateRequestResponseProtocol { .. } => None,
+			Error::WarpSyncNotSupported { .. } => None,
 		}
 	}
 }
@@ -129,6 +129,14 @@
 //! requests for information about blocks. Each request is the encoding of a `BlockRequest` and
 //! each response is the encoding of a `BlockResponse`, as defined in the `api.v1.proto` file in
 //! this source tree.
+//! - **`/<protocol-id>/state/1`** is a request-response protocol (see below) that lets one
+//! download the state of a block, one range of keys at a time. Each request is the encoding of a
+//! `StateRequest` and each response is the encoding of a `StateResponse`, as defined in the
+//! `api.v1.proto` file in this source tree.
+//! - **`/<protocol-id>/sync/warp`** is a request-response protocol (see below) that lets one
+//! download a proof of finality of the latest finalized block, made of the justifications of the
+//! authority set changes. Each request is the SCALE encoding of a `WarpProofRequest` and each
+//! response is a proof as encoded by the `WarpSyncProvider` of the chain.
 //! - **`/<protocol-id>/light/2`** is a request-response protocol (see below) that lets one perform
 //! light-client-related requests for information about the state. Each request is the encoding of
 //! a `light::Request` and each response is the encoding of a `light::Response`, as defined in the
@@ -247,6 +255,8 @@
 
 mod behaviour;
 mod block_requests;
+mod state_requests;
+mod warp_requests;
 mod chain;
 mod peer_info;
 mod discovery;
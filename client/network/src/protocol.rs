@@ -19,9 +19,10 @@
 use crate::{
 	ExHashT,
 	chain::Client,
-	config::{ProtocolId, TransactionPool, TransactionImportFuture, TransactionImport},
+	config::{ProtocolId, SyncMode, TransactionPool, TransactionImportFuture, TransactionImport},
 	error,
 	utils::{interval, LruHashSet},
+	warp_requests::{EncodedProof, WarpProofRequest, WarpSyncProvider},
 };
 
 use bytes::{Bytes, BytesMut};
@@ -37,7 +38,7 @@ use sp_consensus::{
 	import_queue::{BlockImportResult, BlockImportError, IncomingBlock, Origin}
 };
 use codec::{Decode, DecodeAll, Encode};
-use sp_runtime::{generic::BlockId, Justification};
+use sp_runtime::{generic::BlockId, Justifications};
 use sp_runtime::traits::{
 	Block as BlockT, Header as HeaderT, NumberFor, Zero, CheckedSub
 };
@@ -282,6 +283,8 @@ pub struct ProtocolConfig {
 	pub roles: Roles,
 	/// Maximum number of peers to ask the same blocks in parallel.
 	pub max_parallel_downloads: u32,
+	/// Initial syncing mode.
+	pub sync_mode: SyncMode,
 }
 
 impl Default for ProtocolConfig {
@@ -289,6 +292,7 @@ impl Default for ProtocolConfig {
 		ProtocolConfig {
 			roles: Roles::FULL,
 			max_parallel_downloads: 5,
+			sync_mode: SyncMode::Full,
 		}
 	}
 }
@@ -363,6 +367,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		protocol_id: ProtocolId,
 		peerset_config: sc_peerset::PeersetConfig,
 		block_announce_validator: Box<dyn BlockAnnounceValidator<B> + Send>,
+		warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
 		metrics_registry: Option<&Registry>,
 		boot_node_ids: Arc<HashSet<PeerId>>,
 	) -> error::Result<(Protocol<B, H>, sc_peerset::PeersetHandle)> {
@@ -373,6 +378,8 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 			&info,
 			block_announce_validator,
 			config.max_parallel_downloads,
+			config.sync_mode,
+			warp_sync_provider,
 		);
 
 		let important_peers = {
@@ -721,8 +728,8 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		if request.fields == message::BlockAttributes::JUSTIFICATION {
 			match self.sync.on_block_justification(peer, response) {
 				Ok(sync::OnBlockJustification::Nothing) => CustomMessageOutcome::None,
-				Ok(sync::OnBlockJustification::Import { peer, hash, number, justification }) =>
-					CustomMessageOutcome::JustificationImport(peer, hash, number, justification),
+				Ok(sync::OnBlockJustification::Import { peer, hash, number, justifications }) =>
+					CustomMessageOutcome::JustificationImport(peer, hash, number, justifications),
 				Err(sync::BadPeer(id, repu)) => {
 					self.behaviour.disconnect_peer(&id);
 					self.peerset_handle.report_peer(id, repu);
@@ -758,6 +765,64 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		self.behaviour.disconnect_peer(peer);
 	}
 
+	/// Must be called in response to a [`CustomMessageOutcome::StateRequest`] being emitted.
+	/// Must contain the same `PeerId` and request that have been emitted.
+	pub fn on_state_response(
+		&mut self,
+		peer: PeerId,
+		response: message::StateResponse,
+	) -> CustomMessageOutcome<B> {
+		match self.sync.on_state_data(&peer, response) {
+			Ok(sync::OnStateData::Import(origin, block)) =>
+				CustomMessageOutcome::BlockImport(origin, vec![block]),
+			Ok(sync::OnStateData::Request(peer, req)) =>
+				CustomMessageOutcome::StateRequest {
+					target: peer,
+					request: req,
+				},
+			Err(sync::BadPeer(id, repu)) => {
+				self.behaviour.disconnect_peer(&id);
+				self.peerset_handle.report_peer(id, repu);
+				CustomMessageOutcome::None
+			}
+		}
+	}
+
+	/// Must be called in response to a [`CustomMessageOutcome::StateRequest`] if it has failed.
+	pub fn on_state_request_failed(
+		&mut self,
+		peer: &PeerId,
+	) {
+		self.peerset_handle.report_peer(peer.clone(), rep::TIMEOUT);
+		self.behaviour.disconnect_peer(peer);
+	}
+
+	/// Must be called in response to a [`CustomMessageOutcome::WarpSyncRequest`] being emitted.
+	pub fn on_warp_sync_response(
+		&mut self,
+		peer: PeerId,
+		response: EncodedProof,
+	) -> CustomMessageOutcome<B> {
+		match self.sync.on_warp_sync_data(&peer, response) {
+			Ok(()) => CustomMessageOutcome::None,
+			Err(sync::BadPeer(id, repu)) => {
+				self.behaviour.disconnect_peer(&id);
+				self.peerset_handle.report_peer(id, repu);
+				CustomMessageOutcome::None
+			}
+		}
+	}
+
+	/// Must be called in response to a [`CustomMessageOutcome::WarpSyncRequest`] if it has
+	/// failed.
+	pub fn on_warp_sync_request_failed(
+		&mut self,
+		peer: &PeerId,
+	) {
+		self.peerset_handle.report_peer(peer.clone(), rep::TIMEOUT);
+		self.behaviour.disconnect_peer(peer);
+	}
+
 	/// Perform time based maintenance.
 	///
 	/// > **Note**: This method normally doesn't have to be called except for testing purposes.
@@ -1200,7 +1265,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 						body: None,
 						receipt: None,
 						message_queue: None,
-						justification: None,
+						justifications: None,
 					},
 				],
 			},
@@ -1348,7 +1413,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 #[must_use]
 pub enum CustomMessageOutcome<B: BlockT> {
 	BlockImport(BlockOrigin, Vec<IncomingBlock<B>>),
-	JustificationImport(Origin, B::Hash, NumberFor<B>, Justification),
+	JustificationImport(Origin, B::Hash, NumberFor<B>, Justifications),
 	/// Notification protocols have been opened with a remote.
 	NotificationStreamOpened {
 		remote: PeerId,
@@ -1373,6 +1438,20 @@ pub enum CustomMessageOutcome<B: BlockT> {
 	/// must be silently discarded.
 	/// It is the responsibility of the handler to ensure that a timeout exists.
 	BlockRequest { target: PeerId, request: message::BlockRequest<B> },
+	/// A new state request must be emitted.
+	/// You must later call either [`Protocol::on_state_response`] or
+	/// [`Protocol::on_state_request_failed`].
+	/// Each peer can only have one active request. If a request already exists for this peer, it
+	/// must be silently discarded.
+	/// It is the responsibility of the handler to ensure that a timeout exists.
+	StateRequest { target: PeerId, request: message::StateRequest<B> },
+	/// A new warp sync proof request must be emitted.
+	/// You must later call either [`Protocol::on_warp_sync_response`] or
+	/// [`Protocol::on_warp_sync_request_failed`].
+	/// Each peer can only have one active request. If a request already exists for this peer, it
+	/// must be silently discarded.
+	/// It is the responsibility of the handler to ensure that a timeout exists.
+	WarpSyncRequest { target: PeerId, request: WarpProofRequest<B> },
 	/// Peer has a reported a new head of chain.
 	PeerNewBest(PeerId, NumberFor<B>),
 	None,
@@ -1469,6 +1548,20 @@ impl<B: BlockT, H: ExHashT> NetworkBehaviour for Protocol<B, H> {
 			};
 			self.pending_messages.push_back(event);
 		}
+		if let Some((id, r)) = self.sync.state_request() {
+			let event = CustomMessageOutcome::StateRequest {
+				target: id,
+				request: r,
+			};
+			self.pending_messages.push_back(event);
+		}
+		if let Some((id, r)) = self.sync.warp_sync_request() {
+			let event = CustomMessageOutcome::WarpSyncRequest {
+				target: id,
+				request: r,
+			};
+			self.pending_messages.push_back(event);
+		}
 		if let Poll::Ready(Some((tx_hash, result))) = self.pending_transactions.poll_next_unpin(cx) {
 			if let Some(peers) = self.pending_transactions_peers.remove(&tx_hash) {
 				peers.into_iter().for_each(|p| self.on_handle_transaction_import(p, result));
@@ -60,6 +60,9 @@ pub type BlockResponse<B> = generic::BlockResponse<
 	<B as BlockT>::Extrinsic,
 >;
 
+/// Type alias for using the state request type using block type parameters.
+pub type StateRequest<B> = generic::StateRequest<<B as BlockT>::Hash>;
+
 /// A set of transactions.
 pub type Transactions<E> = Vec<E>;
 
@@ -144,11 +147,19 @@ pub struct RemoteReadResponse {
 	pub proof: StorageProof,
 }
 
+/// Response to `StateRequest`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StateResponse {
+	/// Proof of the key-value pairs of the requested range. The pairs are read from the
+	/// proof, starting after the requested key and stopping where the proof ends.
+	pub proof: StorageProof,
+}
+
 /// Generic types.
 pub mod generic {
 	use bitflags::bitflags;
 	use codec::{Encode, Decode, Input, Output};
-	use sp_runtime::Justification;
+	use sp_runtime::Justifications;
 	use super::{
 		RemoteReadResponse, Transactions, Direction,
 		RequestId, BlockAttributes, RemoteCallResponse, ConsensusEngineId,
@@ -233,8 +244,8 @@ pub mod generic {
 		pub receipt: Option<Vec<u8>>,
 		/// Block message queue if requested.
 		pub message_queue: Option<Vec<u8>>,
-		/// Justification if requested.
-		pub justification: Option<Justification>,
+		/// Justifications if requested.
+		pub justifications: Option<Justifications>,
 	}
 
 	/// Identifies starting point of a block sequence.
@@ -411,6 +422,17 @@ pub mod generic {
 		pub blocks: Vec<BlockData<Header, Hash, Extrinsic>>,
 	}
 
+	/// Request a range of the state of a block from a peer.
+	#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+	pub struct StateRequest<Hash> {
+		/// Block to read the state of.
+		pub block: Hash,
+		/// Prefixed storage key of the child trie to read, or `None` for the top trie.
+		pub child: Option<Vec<u8>>,
+		/// Read the keys after this one. Starts at the first key when `None`.
+		pub start: Option<Vec<u8>>,
+	}
+
 	/// Announce a new complete relay chain block on the network.
 	#[derive(Debug, PartialEq, Eq, Clone)]
 	pub struct BlockAnnounce<H> {
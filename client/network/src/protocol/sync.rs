@@ -34,15 +34,21 @@ use sp_consensus::{BlockOrigin, BlockStatus,
 	block_validation::{BlockAnnounceValidator, Validation},
 	import_queue::{IncomingBlock, BlockImportResult, BlockImportError}
 };
+use crate::config::SyncMode;
 use crate::protocol::message::{
 	self, BlockAnnounce, BlockAttributes, BlockRequest, BlockResponse, Roles,
+	StateRequest, StateResponse,
 };
+use crate::warp_requests::{EncodedProof, WarpProofRequest, WarpSyncProvider};
+use sp_finality_grandpa::GRANDPA_ENGINE_ID;
 use either::Either;
 use extra_requests::ExtraRequests;
+use state::StateSync;
+use warp::WarpSync;
 use libp2p::PeerId;
 use log::{debug, trace, warn, info, error};
 use sp_runtime::{
-	Justification,
+	Justifications,
 	generic::BlockId,
 	traits::{
 		Block as BlockT, Header as HeaderT, NumberFor, Zero, One, CheckedSub, SaturatedConversion,
@@ -57,6 +63,8 @@ use futures::{task::Poll, Future, stream::FuturesUnordered, FutureExt, StreamExt
 
 mod blocks;
 mod extra_requests;
+mod state;
+mod warp;
 
 /// Maximum blocks to request in a single packet.
 const MAX_BLOCKS_TO_REQUEST: usize = 128;
@@ -207,6 +215,16 @@ pub struct ChainSync<B: BlockT> {
 	>,
 	/// Stats per peer about the number of concurrent block announce validations.
 	block_announce_validation_per_peer_stats: HashMap<PeerId, usize>,
+	/// Whether we are downloading headers up to a finalized block with a justification, to
+	/// download its state afterwards.
+	fast_sync: bool,
+	/// State sync in progress, if any.
+	state_sync: Option<StateSync<B>>,
+	/// Warp sync proof download in progress, if any. The state of the proven block is downloaded
+	/// afterwards.
+	warp_sync: Option<WarpSync<B>>,
+	/// Used to start warp sync again if the import of the state fails.
+	warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
 }
 
 /// All the data we have about a Peer that we are trying to sync with
@@ -279,6 +297,10 @@ pub enum PeerSyncState<B: BlockT> {
 	DownloadingStale(B::Hash),
 	/// Downloading justification for given block hash.
 	DownloadingJustification(B::Hash),
+	/// Downloading state.
+	DownloadingState,
+	/// Downloading warp sync proof.
+	DownloadingWarpProof,
 }
 
 impl<B: BlockT> PeerSyncState<B> {
@@ -342,6 +364,15 @@ impl<B: BlockT> OnBlockData<B> {
 	}
 }
 
+/// Result of [`ChainSync::on_state_data`].
+#[derive(Debug)]
+pub enum OnStateData<B: BlockT> {
+	/// The block and state that should be imported.
+	Import(BlockOrigin, IncomingBlock<B>),
+	/// A new state request needs to be made to the given peer.
+	Request(PeerId, StateRequest<B>)
+}
+
 /// Result of [`ChainSync::poll_block_announce_validation`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PollBlockAnnounceValidation<H> {
@@ -417,7 +448,7 @@ pub enum OnBlockJustification<B: BlockT> {
 		peer: PeerId,
 		hash: B::Hash,
 		number: NumberFor<B>,
-		justification: Justification
+		justifications: Justifications
 	}
 }
 
@@ -439,10 +470,29 @@ impl<B: BlockT> ChainSync<B> {
 		info: &BlockchainInfo<B>,
 		block_announce_validator: Box<dyn BlockAnnounceValidator<B> + Send>,
 		max_parallel_downloads: u32,
+		mode: SyncMode,
+		warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
 	) -> Self {
+		// Fast sync only makes sense when there is no state history to build upon, i.e. at
+		// genesis or when an earlier fast sync was interrupted before the state was imported.
+		let fast_sync = mode == SyncMode::Fast && role.is_full() && (
+			info.best_number.is_zero() ||
+			client.block_status(&BlockId::Hash(info.best_hash))
+				.map_or(false, |s| s == BlockStatus::InChainPruned)
+		);
+
+		// Warp sync skips the headers, so it can only start from genesis.
+		let warp_sync_provider = match warp_sync_provider {
+			Some(provider) if mode == SyncMode::Warp && role.is_full() && info.best_number.is_zero() =>
+				Some(provider),
+			_ => None,
+		};
+		let warp_sync = warp_sync_provider.as_ref()
+			.map(|provider| WarpSync::new(provider.clone(), info.genesis_hash));
+
 		let mut required_block_attributes = BlockAttributes::HEADER | BlockAttributes::JUSTIFICATION;
 
-		if role.is_full() {
+		if role.is_full() && !fast_sync && warp_sync.is_none() {
 			required_block_attributes |= BlockAttributes::BODY
 		}
 
@@ -463,6 +513,10 @@ impl<B: BlockT> ChainSync<B> {
 			downloaded_blocks: 0,
 			block_announce_validation: Default::default(),
 			block_announce_validation_per_peer_stats: Default::default(),
+			fast_sync,
+			state_sync: None,
+			warp_sync,
+			warp_sync_provider,
 		}
 	}
 
@@ -704,7 +758,7 @@ impl<B: BlockT> ChainSync<B> {
 
 	/// Get an iterator over all block requests of all peers.
 	pub fn block_requests(&mut self) -> impl Iterator<Item = (&PeerId, BlockRequest<B>)> + '_ {
-		if self.pending_requests.is_empty() {
+		if self.pending_requests.is_empty() || self.state_sync.is_some() || self.warp_sync.is_some() {
 			return Either::Left(std::iter::empty())
 		}
 		if self.queue_blocks.len() > MAX_IMPORTING_BLOCKS {
@@ -781,6 +835,49 @@ impl<B: BlockT> ChainSync<B> {
 		Either::Right(iter)
 	}
 
+	/// Get a state request, if any.
+	pub fn state_request(&mut self) -> Option<(PeerId, StateRequest<B>)> {
+		if self.peers.iter().any(|(_, peer)| peer.state == PeerSyncState::DownloadingState) {
+			// Only one pending state request is allowed.
+			return None;
+		}
+		if let Some(sync) = &self.state_sync {
+			if sync.is_complete() {
+				return None;
+			}
+			for (id, peer) in self.peers.iter_mut() {
+				if peer.state.is_available() && peer.best_number >= sync.target_block_num() {
+					trace!(target: "sync", "New state request to {}", id);
+					peer.state = PeerSyncState::DownloadingState;
+					let request = sync.next_request();
+					return Some((id.clone(), request))
+				}
+			}
+		}
+		None
+	}
+
+	/// Get a warp sync proof request, if any.
+	pub fn warp_sync_request(&mut self) -> Option<(PeerId, WarpProofRequest<B>)> {
+		if self.peers.iter().any(|(_, peer)| peer.state == PeerSyncState::DownloadingWarpProof) {
+			// Only one pending warp proof request is allowed.
+			return None;
+		}
+		if let Some(sync) = &self.warp_sync {
+			// Ask the peer with the highest best block, it is the most likely to know the latest
+			// finalized block.
+			let target = self.peers.iter_mut()
+				.filter(|(_, peer)| peer.state.is_available())
+				.max_by_key(|(_, peer)| peer.best_number);
+			if let Some((id, peer)) = target {
+				trace!(target: "sync", "New warp proof request to {}", id);
+				peer.state = PeerSyncState::DownloadingWarpProof;
+				return Some((id.clone(), sync.next_request()))
+			}
+		}
+		None
+	}
+
 	/// Handle a response from the remote to a block request that we made.
 	///
 	/// `request` must be the original request that triggered `response`.
@@ -810,21 +907,31 @@ impl<B: BlockT> ChainSync<B> {
 							let start_block = *start_block;
 							peer.state = PeerSyncState::Available;
 							validate_blocks::<B>(&blocks, who, Some(request))?;
+							if self.state_sync.is_some() {
+								// The state of a block is being downloaded, blocks above it are
+								// downloaded again once it is imported.
+								trace!(target: "sync", "Ignoring block response during state sync");
+								return Ok(OnBlockData::Import(BlockOrigin::NetworkInitialSync, Vec::new()));
+							}
 							self.blocks.insert(start_block, blocks, who.clone());
-							self.blocks
-								.drain(self.best_queued_number + One::one())
-								.into_iter()
-								.map(|block_data| {
-									IncomingBlock {
-										hash: block_data.block.hash,
-										header: block_data.block.header,
-										body: block_data.block.body,
-										justification: block_data.block.justification,
-										origin: block_data.origin,
-										allow_missing_state: true,
-										import_existing: false,
-									}
-								}).collect()
+							let ready = self.blocks.drain(self.best_queued_number + One::one());
+							if self.fast_sync {
+								self.fast_sync_blocks(ready)
+							} else {
+								ready.into_iter()
+									.map(|block_data| {
+										IncomingBlock {
+											hash: block_data.block.hash,
+											header: block_data.block.header,
+											body: block_data.block.body,
+											justifications: block_data.block.justifications,
+											origin: block_data.origin,
+											allow_missing_state: true,
+											import_existing: false,
+											state: None,
+										}
+									}).collect()
+							}
 						}
 						PeerSyncState::DownloadingStale(_) => {
 							peer.state = PeerSyncState::Available;
@@ -838,10 +945,11 @@ impl<B: BlockT> ChainSync<B> {
 									hash: b.hash,
 									header: b.header,
 									body: b.body,
-									justification: b.justification,
+									justifications: b.justifications,
 									origin: Some(who.clone()),
 									allow_missing_state: true,
 									import_existing: false,
+									state: None,
 								}
 							}).collect()
 						}
@@ -937,7 +1045,9 @@ impl<B: BlockT> ChainSync<B> {
 						}
 
 						| PeerSyncState::Available
-						| PeerSyncState::DownloadingJustification(..) => Vec::new()
+						| PeerSyncState::DownloadingJustification(..)
+						| PeerSyncState::DownloadingState
+						| PeerSyncState::DownloadingWarpProof => Vec::new()
 					}
 				} else {
 					// When request.is_none() this is a block announcement. Just accept blocks.
@@ -947,10 +1057,11 @@ impl<B: BlockT> ChainSync<B> {
 							hash: b.hash,
 							header: b.header,
 							body: b.body,
-							justification: b.justification,
+							justifications: b.justifications,
 							origin: Some(who.clone()),
 							allow_missing_state: true,
 							import_existing: false,
+							state: None,
 						}
 					}).collect()
 				}
@@ -987,6 +1098,152 @@ impl<B: BlockT> ChainSync<B> {
 		Ok(OnBlockData::Import(origin, new_blocks))
 	}
 
+	/// Handle a response from the remote to a state request that we made.
+	///
+	/// Returns the block with its state to import once the state is complete, or the next
+	/// request to make otherwise.
+	pub fn on_state_data(
+		&mut self,
+		who: &PeerId,
+		response: StateResponse,
+	) -> Result<OnStateData<B>, BadPeer> {
+		if let Some(peer) = self.peers.get_mut(who) {
+			if let PeerSyncState::DownloadingState = peer.state {
+				peer.state = PeerSyncState::Available;
+			} else {
+				return Err(BadPeer(who.clone(), rep::NOT_REQUESTED));
+			}
+		} else {
+			return Err(BadPeer(who.clone(), rep::NOT_REQUESTED));
+		}
+
+		let import_result = if let Some(sync) = &mut self.state_sync {
+			debug!(target: "sync", "Importing state data from {}", who);
+			sync.import(response)
+		} else {
+			debug!(target: "sync", "Ignored obsolete state response from {}", who);
+			return Err(BadPeer(who.clone(), rep::NOT_REQUESTED));
+		};
+
+		match import_result {
+			state::ImportResult::Import(block) => {
+				self.queue_blocks.insert(block.hash);
+				Ok(OnStateData::Import(BlockOrigin::NetworkInitialSync, block))
+			},
+			state::ImportResult::Continue(request) => {
+				if let Some(peer) = self.peers.get_mut(who) {
+					peer.state = PeerSyncState::DownloadingState;
+				}
+				Ok(OnStateData::Request(who.clone(), request))
+			},
+			state::ImportResult::BadResponse => {
+				debug!(target: "sync", "Bad state data received from {}", who);
+				Err(BadPeer(who.clone(), rep::BAD_RESPONSE))
+			},
+		}
+	}
+
+	/// Handle a response from the remote to a warp sync proof request that we made.
+	///
+	/// Once the proof is complete, the state of the proven block is downloaded.
+	pub fn on_warp_sync_data(
+		&mut self,
+		who: &PeerId,
+		response: EncodedProof,
+	) -> Result<(), BadPeer> {
+		if let Some(peer) = self.peers.get_mut(who) {
+			if let PeerSyncState::DownloadingWarpProof = peer.state {
+				peer.state = PeerSyncState::Available;
+			} else {
+				return Err(BadPeer(who.clone(), rep::NOT_REQUESTED));
+			}
+		} else {
+			return Err(BadPeer(who.clone(), rep::NOT_REQUESTED));
+		}
+
+		let import_result = if let Some(sync) = &mut self.warp_sync {
+			debug!(target: "sync", "Importing warp proof data from {}, {} bytes.", who, response.0.len());
+			sync.import(&response)
+		} else {
+			debug!(target: "sync", "Ignored obsolete warp proof response from {}", who);
+			return Err(BadPeer(who.clone(), rep::NOT_REQUESTED));
+		};
+
+		match import_result {
+			warp::ImportResult::Complete(header, set_id, authorities) => {
+				info!(
+					target: "sync",
+					"Warp proof of #{} ({}) verified, downloading its state.",
+					header.number(),
+					header.hash(),
+				);
+				self.warp_sync = None;
+				self.state_sync = Some(StateSync::new(
+					self.client.clone(),
+					header,
+					None,
+					Some((GRANDPA_ENGINE_ID, (set_id, authorities).encode())),
+					Some(who.clone()),
+				));
+				Ok(())
+			},
+			warp::ImportResult::Continue(_) => Ok(()),
+			warp::ImportResult::BadResponse => {
+				debug!(target: "sync", "Bad warp proof data received from {}", who);
+				Err(BadPeer(who.clone(), rep::BAD_RESPONSE))
+			},
+		}
+	}
+
+	/// Split blocks drained during fast sync into the headers to import and the target of the
+	/// state download.
+	///
+	/// The target is the first block with a justification that is close enough to the best
+	/// block seen on the network. Blocks below the target are imported without state. The
+	/// target and the blocks above it are dropped, the target is imported together with its
+	/// state once the download completes.
+	fn fast_sync_blocks(&mut self, ready: Vec<blocks::BlockData<B>>) -> Vec<IncomingBlock<B>> {
+		let best_seen = self.peers.values().map(|p| p.best_number).max().unwrap_or_else(Zero::zero);
+		let mut result = Vec::with_capacity(ready.len());
+		for block_data in ready {
+			let target = match (&block_data.block.header, &block_data.block.justifications) {
+				(Some(header), Some(_)) =>
+					*header.number() + MAX_DOWNLOAD_AHEAD.into() >= best_seen,
+				_ => false,
+			};
+			if target {
+				let header = block_data.block.header
+					.expect("Target blocks are selected with a header; qed");
+				debug!(
+					target: "sync",
+					"Starting state sync for #{} ({})",
+					header.number(),
+					block_data.block.hash,
+				);
+				self.state_sync = Some(StateSync::new(
+					self.client.clone(),
+					header,
+					block_data.block.justifications,
+					None,
+					block_data.origin,
+				));
+				self.blocks.clear();
+				break;
+			}
+			result.push(IncomingBlock {
+				hash: block_data.block.hash,
+				header: block_data.block.header,
+				body: block_data.block.body,
+				justifications: block_data.block.justifications,
+				origin: block_data.origin,
+				allow_missing_state: true,
+				import_existing: false,
+				state: None,
+			});
+		}
+		result
+	}
+
 	/// Handle a response from the remote to a justification request that we made.
 	///
 	/// `request` must be the original request that triggered `response`.
@@ -1009,7 +1266,7 @@ impl<B: BlockT> ChainSync<B> {
 			peer.state = PeerSyncState::Available;
 
 			// We only request one justification at a time
-			let justification = if let Some(block) = response.blocks.into_iter().next() {
+			let justifications = if let Some(block) = response.blocks.into_iter().next() {
 				if hash != block.hash {
 					info!(
 						target: "sync",
@@ -1018,7 +1275,7 @@ impl<B: BlockT> ChainSync<B> {
 					return Err(BadPeer(who, rep::BAD_JUSTIFICATION));
 				}
 
-				block.justification
+				block.justifications
 			} else {
 				// we might have asked the peer for a justification on a block that we assumed it
 				// had but didn't (regardless of whether it had a justification for it or not).
@@ -1031,8 +1288,8 @@ impl<B: BlockT> ChainSync<B> {
 				None
 			};
 
-			if let Some((peer, hash, number, j)) = self.extra_justifications.on_response(who, justification) {
-				return Ok(OnBlockJustification::Import { peer, hash, number, justification: j })
+			if let Some((peer, hash, number, j)) = self.extra_justifications.on_response(who, justifications) {
+				return Ok(OnBlockJustification::Import { peer, hash, number, justifications: j })
 			}
 		}
 
@@ -1068,6 +1325,25 @@ impl<B: BlockT> ChainSync<B> {
 				has_error = true;
 			}
 
+			if self.state_sync.as_ref().map_or(false, |s| s.target() == hash) {
+				self.state_sync = None;
+				if result.is_ok() {
+					info!(target: "sync", "State sync complete, continuing with full block import.");
+					self.fast_sync = false;
+					self.warp_sync_provider = None;
+					if self.role.is_full() {
+						self.required_block_attributes |= BlockAttributes::BODY;
+					}
+				} else {
+					debug!(target: "sync", "State import of {:?} failed, restarting sync.", hash);
+					if let Some(provider) = &self.warp_sync_provider {
+						let genesis_hash = self.client.info().genesis_hash;
+						self.warp_sync = Some(WarpSync::new(provider.clone(), genesis_hash));
+					}
+				}
+				output.extend(self.restart());
+			}
+
 			match result {
 				Ok(BlockImportResult::ImportedKnown(number, who)) => {
 					if let Some(peer) = who.and_then(|p| self.peers.get_mut(&p)) {
@@ -1881,6 +2157,8 @@ mod test {
 			&info,
 			block_announce_validator,
 			1,
+			SyncMode::Full,
+			None,
 		);
 
 		let (a1_hash, a1_number) = {
@@ -1952,6 +2230,8 @@ mod test {
 			&info,
 			Box::new(DefaultBlockAnnounceValidator),
 			1,
+			SyncMode::Full,
+			None,
 		);
 
 		let peer_id1 = PeerId::random();
@@ -2049,7 +2329,7 @@ mod test {
 					body: Some(b.deconstruct().1),
 					receipt: None,
 					message_queue: None,
-					justification: None,
+					justifications: None,
 				}
 			).collect(),
 		}
@@ -2118,6 +2398,8 @@ mod test {
 			&info,
 			Box::new(DefaultBlockAnnounceValidator),
 			5,
+			SyncMode::Full,
+			None,
 		);
 
 		let peer_id1 = PeerId::random();
@@ -2232,6 +2514,8 @@ mod test {
 			&info,
 			Box::new(DefaultBlockAnnounceValidator),
 			5,
+			SyncMode::Full,
+			None,
 		);
 
 		let peer_id1 = PeerId::random();
@@ -2351,6 +2635,8 @@ mod test {
 			&info,
 			Box::new(DefaultBlockAnnounceValidator),
 			5,
+			SyncMode::Full,
+			None,
 		);
 
 		let finalized_block = blocks[MAX_BLOCKS_TO_LOOK_BACKWARDS as usize * 2 - 1].clone();
@@ -0,0 +1,199 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2017-2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use codec::Encode;
+use libp2p::PeerId;
+use log::{debug, trace};
+use sp_consensus::{ImportedState, import_queue::IncomingBlock};
+use sp_core::storage::{ChildInfo, Storage, StorageChild, well_known_keys};
+use sp_runtime::{ConsensusEngineId, Justifications, traits::{Block as BlockT, Header as HeaderT}};
+use crate::chain::Client;
+use crate::protocol::message::{self, StateRequest, StateResponse};
+
+/// Downloads the full state of a single block, one range of keys at a time.
+///
+/// The top trie is downloaded first. Child tries found in the top trie are downloaded after it,
+/// one after another.
+pub struct StateSync<B: BlockT> {
+	target_block: B::Hash,
+	target_header: B::Header,
+	target_justifications: Option<Justifications>,
+	/// Authority set verified along with the finality of the target, passed on to the import.
+	target_authority_set: Option<(ConsensusEngineId, Vec<u8>)>,
+	/// Peer that provided the target header.
+	origin: Option<PeerId>,
+	/// Child trie that is being downloaded, or `None` for the top trie.
+	current_child: Option<ChildInfo>,
+	/// Last key received for the trie that is being downloaded.
+	last_key: Option<Vec<u8>>,
+	/// Child tries that are still to be downloaded.
+	pending_children: VecDeque<ChildInfo>,
+	state: Storage,
+	complete: bool,
+	client: Arc<dyn Client<B>>,
+	imported_bytes: u64,
+}
+
+/// Import state chunk result.
+pub enum ImportResult<B: BlockT> {
+	/// State is complete and ready for import.
+	Import(IncomingBlock<B>),
+	/// Continue downloading.
+	Continue(StateRequest<B>),
+	/// Bad state chunk.
+	BadResponse,
+}
+
+impl<B: BlockT> StateSync<B> {
+	/// Create a new instance.
+	pub fn new(
+		client: Arc<dyn Client<B>>,
+		target: B::Header,
+		justifications: Option<Justifications>,
+		authority_set: Option<(ConsensusEngineId, Vec<u8>)>,
+		origin: Option<PeerId>,
+	) -> Self {
+		StateSync {
+			client,
+			target_block: target.hash(),
+			target_header: target,
+			target_justifications: justifications,
+			target_authority_set: authority_set,
+			origin,
+			current_child: None,
+			last_key: None,
+			pending_children: VecDeque::new(),
+			state: Storage::default(),
+			complete: false,
+			imported_bytes: 0,
+		}
+	}
+
+	/// Validate and import a state response.
+	pub fn import(&mut self, response: StateResponse) -> ImportResult<B> {
+		if self.complete {
+			debug!(target: "sync", "Unexpected state response after completion");
+			return ImportResult::BadResponse;
+		}
+		let proof_size = response.proof.encoded_size() as u64;
+		let (values, complete) = match self.client.verify_range_proof(
+			*self.target_header.state_root(),
+			response.proof,
+			self.current_child.as_ref(),
+			self.last_key.as_ref().map(|k| k.as_slice()),
+		) {
+			Err(e) => {
+				debug!(target: "sync", "StateResponse failed proof verification: {:?}", e);
+				return ImportResult::BadResponse;
+			},
+			Ok(values) => values,
+		};
+		if values.is_empty() && !complete {
+			debug!(target: "sync", "Empty StateResponse for an incomplete range");
+			return ImportResult::BadResponse;
+		}
+		trace!(
+			target: "sync",
+			"Importing {} state entries, complete: {}",
+			values.len(),
+			complete,
+		);
+		self.imported_bytes += proof_size;
+
+		if let Some((last, _)) = values.last() {
+			self.last_key = Some(last.clone());
+		}
+		match &self.current_child {
+			None => for (key, value) in values {
+				if key.starts_with(well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX) {
+					// Child trie roots are recomputed on import from the child trie content.
+					let storage_key = &key[well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX.len()..];
+					self.pending_children.push_back(ChildInfo::new_default(storage_key));
+				} else {
+					self.state.top.insert(key, value);
+				}
+			},
+			Some(child_info) => {
+				let child = self.state.children_default
+					.entry(child_info.storage_key().to_vec())
+					.or_insert_with(|| StorageChild {
+						data: Default::default(),
+						child_info: child_info.clone(),
+					});
+				child.data.extend(values);
+			},
+		}
+
+		if complete {
+			self.last_key = None;
+			self.current_child = self.pending_children.pop_front();
+			if self.current_child.is_none() {
+				self.complete = true;
+				debug!(
+					target: "sync",
+					"State sync of {} complete: {} top keys, {} child tries, {} bytes",
+					self.target_block,
+					self.state.top.len(),
+					self.state.children_default.len(),
+					self.imported_bytes,
+				);
+				return ImportResult::Import(IncomingBlock {
+					hash: self.target_block,
+					header: Some(self.target_header.clone()),
+					body: None,
+					justifications: self.target_justifications.clone(),
+					origin: self.origin.clone(),
+					allow_missing_state: true,
+					import_existing: false,
+					state: Some(ImportedState {
+						block: self.target_block,
+						state: std::mem::take(&mut self.state),
+						authority_set: self.target_authority_set.clone(),
+					}),
+				});
+			}
+		}
+		ImportResult::Continue(self.next_request())
+	}
+
+	/// Produce the next state request.
+	pub fn next_request(&self) -> StateRequest<B> {
+		message::generic::StateRequest {
+			block: self.target_block,
+			child: self.current_child.as_ref().map(|c| c.prefixed_storage_key().into_inner()),
+			start: self.last_key.clone(),
+		}
+	}
+
+	/// Check if the state is complete.
+	pub fn is_complete(&self) -> bool {
+		self.complete
+	}
+
+	/// Returns target block number.
+	pub fn target_block_num(&self) -> <B::Header as HeaderT>::Number {
+		*self.target_header.number()
+	}
+
+	/// Returns target block hash.
+	pub fn target(&self) -> B::Hash {
+		self.target_block
+	}
+}
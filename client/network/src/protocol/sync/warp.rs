@@ -0,0 +1,94 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2017-2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use log::debug;
+use sp_finality_grandpa::{AuthorityList, SetId};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use crate::warp_requests::{EncodedProof, VerificationResult, WarpProofRequest, WarpSyncProvider};
+
+/// Downloads and verifies the proof of finality of the latest finalized block, one authority
+/// set change after another.
+pub struct WarpSync<B: BlockT> {
+	provider: Arc<dyn WarpSyncProvider<B>>,
+	/// Authority set that finalized `last_hash`, used to verify the next proof.
+	set_id: SetId,
+	authorities: AuthorityList,
+	/// Last block proven final.
+	last_hash: B::Hash,
+}
+
+/// Import warp proof result.
+pub enum ImportResult<B: BlockT> {
+	/// The finality of the given header is proven, its state should be downloaded. The authority
+	/// set in effect after it is given along.
+	Complete(B::Header, SetId, AuthorityList),
+	/// Continue downloading.
+	Continue(WarpProofRequest<B>),
+	/// Bad proof.
+	BadResponse,
+}
+
+impl<B: BlockT> WarpSync<B> {
+	/// Create a new instance, starting from the genesis block.
+	pub fn new(provider: Arc<dyn WarpSyncProvider<B>>, genesis_hash: B::Hash) -> Self {
+		let authorities = provider.current_authorities();
+		WarpSync {
+			provider,
+			set_id: 0,
+			authorities,
+			last_hash: genesis_hash,
+		}
+	}
+
+	/// Validate and import a warp proof response.
+	pub fn import(&mut self, response: &EncodedProof) -> ImportResult<B> {
+		match self.provider.verify(response, self.set_id, self.authorities.clone()) {
+			Err(e) => {
+				debug!(target: "sync", "Bad warp proof response: {}", e);
+				ImportResult::BadResponse
+			},
+			Ok(VerificationResult::Partial(_, _, hash)) if hash == self.last_hash => {
+				debug!(target: "sync", "Warp proof response doesn't make any progress");
+				ImportResult::BadResponse
+			},
+			Ok(VerificationResult::Partial(set_id, authorities, hash)) => {
+				debug!(target: "sync", "Verified partial warp proof up to {}, set id {}", hash, set_id);
+				self.set_id = set_id;
+				self.authorities = authorities;
+				self.last_hash = hash;
+				ImportResult::Continue(self.next_request())
+			},
+			Ok(VerificationResult::Complete(set_id, authorities, header)) => {
+				debug!(
+					target: "sync",
+					"Verified complete warp proof of #{} ({}), set id {}",
+					header.number(),
+					header.hash(),
+					set_id,
+				);
+				ImportResult::Complete(header, set_id, authorities)
+			},
+		}
+	}
+
+	/// Produce the next warp proof request.
+	pub fn next_request(&self) -> WarpProofRequest<B> {
+		WarpProofRequest { begin: self.last_hash }
+	}
+}
@@ -30,7 +30,7 @@
 use crate::{
 	ExHashT, NetworkStateInfo, NetworkStatus,
 	behaviour::{self, Behaviour, BehaviourOut},
-	config::{parse_str_addr, NonReservedPeerMode, Params, Role, TransportConfig},
+	config::{parse_str_addr, NonReservedPeerMode, Params, Role, SyncMode, TransportConfig},
 	DhtEvent,
 	discovery::DiscoveryConfig,
 	error::Error,
@@ -38,7 +38,7 @@ use crate::{
 		NetworkState, NotConnectedPeer as NetworkStateNotConnectedPeer, Peer as NetworkStatePeer,
 	},
 	on_demand_layer::AlwaysBadChecker,
-	light_client_handler, block_requests,
+	light_client_handler, block_requests, state_requests, warp_requests,
 	protocol::{
 		self,
 		NotifsHandlerError,
@@ -156,6 +156,16 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			&params.network_config.transport,
 		)?;
 
+		// Refuse warp sync rather than silently falling back to another sync mode.
+		if params.network_config.sync_mode == SyncMode::Warp {
+			if let Role::Light = params.role {
+				return Err(Error::WarpSyncNotSupported { reason: "light clients can't import the state" });
+			}
+			if params.warp_sync.is_none() {
+				return Err(Error::WarpSyncNotSupported { reason: "the chain has no warp sync provider" });
+			}
+		}
+
 		let (to_worker, from_service) = tracing_unbounded("mpsc_network_worker");
 
 		if let Some(path) = params.network_config.net_config_path {
@@ -269,6 +279,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			protocol::ProtocolConfig {
 				roles: From::from(&params.role),
 				max_parallel_downloads: params.network_config.max_parallel_downloads,
+				sync_mode: params.network_config.sync_mode,
 			},
 			local_peer_id.clone(),
 			params.chain.clone(),
@@ -276,6 +287,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			params.protocol_id.clone(),
 			peerset_config,
 			params.block_announce_validator,
+			params.warp_sync.clone(),
 			params.metrics_registry.as_ref(),
 			boot_node_ids.clone(),
 		)?;
@@ -291,6 +303,14 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 				let config = block_requests::Config::new(&params.protocol_id);
 				block_requests::BlockRequests::new(config, params.chain.clone())
 			};
+			let state_requests = {
+				let config = state_requests::Config::new(&params.protocol_id);
+				state_requests::StateRequests::new(config, params.chain.clone())
+			};
+			let warp_requests = {
+				let config = warp_requests::Config::new(&params.protocol_id);
+				warp_requests::WarpRequests::new(config, params.warp_sync)
+			};
 			let light_client_handler = {
 				let config = light_client_handler::Config::new(&params.protocol_id);
 				light_client_handler::LightClientHandler::new(
@@ -330,6 +350,8 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 					user_agent,
 					local_public,
 					block_requests,
+					state_requests,
+					warp_requests,
 					light_client_handler,
 					discovery_config,
 					params.network_config.request_response_protocols,
@@ -1364,11 +1386,11 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 					}
 					this.import_queue.import_blocks(origin, blocks);
 				},
-				Poll::Ready(SwarmEvent::Behaviour(BehaviourOut::JustificationImport(origin, hash, nb, justification))) => {
+				Poll::Ready(SwarmEvent::Behaviour(BehaviourOut::JustificationImport(origin, hash, nb, justifications))) => {
 					if let Some(metrics) = this.metrics.as_ref() {
 						metrics.import_queue_justifications_submitted.inc();
 					}
-					this.import_queue.import_justification(origin, hash, nb, justification);
+					this.import_queue.import_justifications(origin, hash, nb, justifications);
 				},
 				Poll::Ready(SwarmEvent::Behaviour(BehaviourOut::InboundRequest { protocol, result, .. })) => {
 					if let Some(metrics) = this.metrics.as_ref() {
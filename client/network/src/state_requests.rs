@@ -0,0 +1,691 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+//
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! `NetworkBehaviour` implementation which handles state requests.
+//!
+//! A state request asks for the key-value pairs of the top trie or of a child trie of a block,
+//! starting after a given key. The response is a storage proof of as many pairs as fit into the
+//! configured size limit, which the requester checks against the state root of the block.
+//!
+//! Every request is coming in on a separate connection substream which gets
+//! closed after we have sent the response back. Requests and responses are encoded
+//! as protocol buffers (cf. `api.v1.proto`).
+
+use bytes::Bytes;
+use codec::{Encode, Decode};
+use crate::{
+	chain::Client,
+	config::ProtocolId,
+	protocol::message,
+	schema,
+};
+use futures::{future::BoxFuture, prelude::*, stream::FuturesUnordered};
+use futures_timer::Delay;
+use libp2p::{
+	core::{
+		ConnectedPoint,
+		Multiaddr,
+		PeerId,
+		connection::ConnectionId,
+		upgrade::{InboundUpgrade, OutboundUpgrade, ReadOneError, UpgradeInfo},
+		upgrade::{read_one, write_one}
+	},
+	swarm::{
+		NegotiatedSubstream,
+		NetworkBehaviour,
+		NetworkBehaviourAction,
+		NotifyHandler,
+		OneShotHandler,
+		OneShotHandlerConfig,
+		PollParameters,
+		SubstreamProtocol
+	}
+};
+use prost::Message;
+use sc_client_api::StorageProof;
+use sp_core::storage::{ChildInfo, ChildType, PrefixedStorageKey};
+use sp_runtime::{generic::BlockId, traits::Block};
+use std::{
+	collections::{HashMap, VecDeque},
+	io,
+	iter,
+	marker::PhantomData,
+	pin::Pin,
+	sync::Arc,
+	time::Duration,
+	task::{Context, Poll}
+};
+use wasm_timer::Instant;
+
+// Type alias for convenience.
+pub type Error = Box<dyn std::error::Error + 'static>;
+
+/// Event generated by the state requests behaviour.
+#[derive(Debug)]
+pub enum Event {
+	/// A request came and we have successfully answered it.
+	AnsweredRequest {
+		/// Peer which has emitted the request.
+		peer: PeerId,
+		/// Time elapsed between when we received the request and when we sent back the response.
+		total_handling_time: Duration,
+	},
+
+	/// A response to a state request has arrived.
+	Response {
+		peer: PeerId,
+		response: message::StateResponse,
+		/// Time elapsed between the start of the request and the response.
+		request_duration: Duration,
+	},
+
+	/// A request has been cancelled because the peer has disconnected.
+	/// Disconnects can also happen as a result of violating the network protocol.
+	RequestCancelled {
+		peer: PeerId,
+		/// Time elapsed between the start of the request and the cancellation.
+		request_duration: Duration,
+	},
+
+	/// A request has timed out.
+	RequestTimeout {
+		peer: PeerId,
+		/// Time elapsed between the start of the request and the timeout.
+		request_duration: Duration,
+	}
+}
+
+/// Configuration options for `StateRequests`.
+#[derive(Debug, Clone)]
+pub struct Config {
+	max_response_proof_bytes: usize,
+	max_request_len: usize,
+	max_response_len: usize,
+	inactivity_timeout: Duration,
+	request_timeout: Duration,
+	protocol: String,
+}
+
+impl Config {
+	/// Create a fresh configuration with the following options:
+	///
+	/// - max. proof size in response = 2 MiB
+	/// - max. request size = 1 MiB
+	/// - max. response size = 16 MiB
+	/// - inactivity timeout = 15s
+	/// - request timeout = 40s
+	pub fn new(id: &ProtocolId) -> Self {
+		let mut c = Config {
+			max_response_proof_bytes: 2 * 1024 * 1024,
+			max_request_len: 1024 * 1024,
+			max_response_len: 16 * 1024 * 1024,
+			inactivity_timeout: Duration::from_secs(15),
+			request_timeout: Duration::from_secs(40),
+			protocol: String::new(),
+		};
+		c.set_protocol(id);
+		c
+	}
+
+	/// Limit the size of the proof sent in a response. At least one key-value pair is always
+	/// sent regardless of the limit. This should be lower than the value specified in
+	/// `set_max_response_len`, accounting for the size of the last pair.
+	pub fn set_max_response_proof_bytes(&mut self, v: usize) -> &mut Self {
+		self.max_response_proof_bytes = v;
+		self
+	}
+
+	/// Limit the max. length of incoming state request bytes.
+	pub fn set_max_request_len(&mut self, v: usize) -> &mut Self {
+		self.max_request_len = v;
+		self
+	}
+
+	/// Limit the max. size of responses to our state requests.
+	pub fn set_max_response_len(&mut self, v: usize) -> &mut Self {
+		self.max_response_len = v;
+		self
+	}
+
+	/// Limit the max. duration the substream may remain inactive before closing it.
+	pub fn set_inactivity_timeout(&mut self, v: Duration) -> &mut Self {
+		self.inactivity_timeout = v;
+		self
+	}
+
+	/// Set protocol to use for upgrade negotiation.
+	pub fn set_protocol(&mut self, id: &ProtocolId) -> &mut Self {
+		let mut s = String::new();
+		s.push_str("/");
+		s.push_str(id.as_ref());
+		s.push_str("/state/1");
+		self.protocol = s;
+		self
+	}
+}
+
+/// The state request handling behaviour.
+pub struct StateRequests<B: Block> {
+	/// This behaviour's configuration.
+	config: Config,
+	/// Blockchain client.
+	chain: Arc<dyn Client<B>>,
+	/// List of all active connections and the requests we've sent.
+	peers: HashMap<PeerId, Vec<Connection<B>>>,
+	/// Futures sending back the state request response. Returns the `PeerId` we sent back to, and
+	/// the total time the handling of this request took.
+	outgoing: FuturesUnordered<BoxFuture<'static, (PeerId, Duration)>>,
+	/// Events to return as soon as possible from `poll`.
+	pending_events: VecDeque<NetworkBehaviourAction<OutboundProtocol<B>, Event>>,
+}
+
+/// Local tracking of a libp2p connection.
+#[derive(Debug)]
+struct Connection<B: Block> {
+	id: ConnectionId,
+	ongoing_request: Option<OngoingRequest<B>>,
+}
+
+#[derive(Debug)]
+struct OngoingRequest<B: Block> {
+	/// `Instant` when the request has been emitted. Used for diagnostic purposes.
+	emitted: Instant,
+	request: message::StateRequest<B>,
+	timeout: Delay,
+}
+
+/// Outcome of calling `send_request`.
+#[derive(Debug)]
+#[must_use]
+pub enum SendRequestOutcome<B: Block> {
+	/// Request has been emitted.
+	Ok,
+	/// The request has been emitted and has replaced an existing request.
+	Replaced {
+		/// The previously-emitted request.
+		previous: message::StateRequest<B>,
+		/// Time that had elapsed since `previous` has been emitted.
+		request_duration: Duration,
+	},
+	/// Didn't start a request because we have no connection to this node.
+	/// If `send_request` returns that, it is as if the function had never been called.
+	NotConnected,
+	/// Error while serializing the request.
+	EncodeError(prost::EncodeError),
+}
+
+impl<B> StateRequests<B>
+where
+	B: Block,
+{
+	pub fn new(cfg: Config, chain: Arc<dyn Client<B>>) -> Self {
+		StateRequests {
+			config: cfg,
+			chain,
+			peers: HashMap::new(),
+			outgoing: FuturesUnordered::new(),
+			pending_events: VecDeque::new(),
+		}
+	}
+
+	/// Returns the libp2p protocol name used on the wire (e.g. `/foo/state/1`).
+	pub fn protocol_name(&self) -> &str {
+		&self.config.protocol
+	}
+
+	/// Issue a new state request.
+	///
+	/// Cancels any existing request targeting the same `PeerId`.
+	pub fn send_request(&mut self, target: &PeerId, req: message::StateRequest<B>) -> SendRequestOutcome<B> {
+		// Determine which connection to send the request to.
+		let connection = if let Some(peer) = self.peers.get_mut(target) {
+			// We don't want to have multiple requests for any given node, so in priority try to
+			// find a connection with an existing request, to override it.
+			if let Some(entry) = peer.iter_mut().find(|c| c.ongoing_request.is_some()) {
+				entry
+			} else if let Some(entry) = peer.get_mut(0) {
+				entry
+			} else {
+				log::error!(
+					target: "sync",
+					"State inconsistency: empty list of peer connections"
+				);
+				return SendRequestOutcome::NotConnected;
+			}
+		} else {
+			return SendRequestOutcome::NotConnected;
+		};
+
+		let protobuf_rq = schema::v1::StateRequest {
+			block: req.block.encode(),
+			child: req.child.clone().unwrap_or_default(),
+			start: req.start.clone().unwrap_or_default(),
+		};
+
+		let mut buf = Vec::with_capacity(protobuf_rq.encoded_len());
+		if let Err(err) = protobuf_rq.encode(&mut buf) {
+			log::warn!(
+				target: "sync",
+				"Failed to encode state request {:?}: {:?}",
+				protobuf_rq,
+				err
+			);
+			return SendRequestOutcome::EncodeError(err);
+		}
+
+		let previous_request = connection.ongoing_request.take();
+		connection.ongoing_request = Some(OngoingRequest {
+			emitted: Instant::now(),
+			request: req.clone(),
+			timeout: Delay::new(self.config.request_timeout),
+		});
+
+		log::trace!(target: "sync", "Enqueueing state request to {:?}: {:?}", target, req);
+		self.pending_events.push_back(NetworkBehaviourAction::NotifyHandler {
+			peer_id: target.clone(),
+			handler: NotifyHandler::One(connection.id),
+			event: OutboundProtocol {
+				request: buf,
+				original_request: req,
+				max_response_size: self.config.max_response_len,
+				protocol: self.config.protocol.as_bytes().to_vec().into(),
+			},
+		});
+
+		if let Some(previous_request) = previous_request {
+			log::debug!(
+				target: "sync",
+				"Replacing existing state request on connection {:?}",
+				connection.id
+			);
+			SendRequestOutcome::Replaced {
+				previous: previous_request.request,
+				request_duration: previous_request.emitted.elapsed(),
+			}
+		} else {
+			SendRequestOutcome::Ok
+		}
+	}
+
+	/// Callback, invoked when a new state request has been received from remote.
+	fn on_state_request
+		( &mut self
+		, peer: &PeerId
+		, request: &schema::v1::StateRequest
+		) -> Result<schema::v1::StateResponse, Error>
+	{
+		let block: B::Hash = Decode::decode(&mut request.block.as_ref())?;
+
+		log::trace!(
+			target: "sync",
+			"State request from peer {}: block {}, child {:?}, from key {:?}",
+			peer,
+			block,
+			sp_core::hexdisplay::HexDisplay::from(&request.child),
+			sp_core::hexdisplay::HexDisplay::from(&request.start),
+		);
+
+		let child_info = if request.child.is_empty() {
+			None
+		} else {
+			let prefixed_key = PrefixedStorageKey::new_ref(&request.child);
+			match ChildType::from_prefixed_key(prefixed_key) {
+				Some((ChildType::ParentKeyId, storage_key)) => Some(ChildInfo::new_default(storage_key)),
+				None => return Err(sp_blockchain::Error::InvalidChildStorageKey.into()),
+			}
+		};
+		let start = if request.start.is_empty() {
+			None
+		} else {
+			Some(&request.start[..])
+		};
+
+		let (proof, count) = self.chain.read_proof_collection(
+			&BlockId::Hash(block),
+			child_info.as_ref(),
+			start,
+			self.config.max_response_proof_bytes,
+		)?;
+
+		log::trace!(target: "sync", "Sending {} state entries to {}", count, peer);
+		Ok(schema::v1::StateResponse { proof: proof.encode() })
+	}
+}
+
+impl<B> NetworkBehaviour for StateRequests<B>
+where
+	B: Block
+{
+	type ProtocolsHandler = OneShotHandler<InboundProtocol<B>, OutboundProtocol<B>, NodeEvent<B, NegotiatedSubstream>>;
+	type OutEvent = Event;
+
+	fn new_handler(&mut self) -> Self::ProtocolsHandler {
+		let p = InboundProtocol {
+			max_request_len: self.config.max_request_len,
+			protocol: self.config.protocol.as_bytes().to_owned().into(),
+			marker: PhantomData,
+		};
+		let mut cfg = OneShotHandlerConfig::default();
+		cfg.keep_alive_timeout = self.config.inactivity_timeout;
+		cfg.outbound_substream_timeout = self.config.request_timeout;
+		OneShotHandler::new(SubstreamProtocol::new(p, ()), cfg)
+	}
+
+	fn addresses_of_peer(&mut self, _: &PeerId) -> Vec<Multiaddr> {
+		Vec::new()
+	}
+
+	fn inject_connected(&mut self, _peer: &PeerId) {
+	}
+
+	fn inject_disconnected(&mut self, _peer: &PeerId) {
+	}
+
+	fn inject_connection_established(&mut self, peer_id: &PeerId, id: &ConnectionId, _: &ConnectedPoint) {
+		self.peers.entry(peer_id.clone())
+			.or_default()
+			.push(Connection {
+				id: *id,
+				ongoing_request: None,
+			});
+	}
+
+	fn inject_connection_closed(&mut self, peer_id: &PeerId, id: &ConnectionId, _: &ConnectedPoint) {
+		let mut needs_remove = false;
+		if let Some(entry) = self.peers.get_mut(peer_id) {
+			if let Some(pos) = entry.iter().position(|i| i.id == *id) {
+				let ongoing_request = entry.remove(pos).ongoing_request;
+				if let Some(ongoing_request) = ongoing_request {
+					log::debug!(
+						target: "sync",
+						"Connection {:?} with {} closed with ongoing state request: {:?}",
+						id,
+						peer_id,
+						ongoing_request
+					);
+					let ev = Event::RequestCancelled {
+						peer: peer_id.clone(),
+						request_duration: ongoing_request.emitted.elapsed(),
+					};
+					self.pending_events.push_back(NetworkBehaviourAction::GenerateEvent(ev));
+				}
+				if entry.is_empty() {
+					needs_remove = true;
+				}
+			} else {
+				log::error!(
+					target: "sync",
+					"State inconsistency: connection id not found in list"
+				);
+			}
+		} else {
+			log::error!(
+				target: "sync",
+				"State inconsistency: peer_id not found in list of connections"
+			);
+		}
+		if needs_remove {
+			self.peers.remove(peer_id);
+		}
+	}
+
+	fn inject_event(
+		&mut self,
+		peer: PeerId,
+		connection_id: ConnectionId,
+		node_event: NodeEvent<B, NegotiatedSubstream>
+	) {
+		match node_event {
+			NodeEvent::Request(request, mut stream, handling_start) => {
+				match self.on_state_request(&peer, &request) {
+					Ok(res) => {
+						let mut data = Vec::with_capacity(res.encoded_len());
+						if let Err(e) = res.encode(&mut data) {
+							log::debug!(
+								target: "sync",
+								"Error encoding state response for peer {}: {}",
+								peer, e
+							)
+						} else {
+							self.outgoing.push(async move {
+								if let Err(e) = write_one(&mut stream, data).await {
+									log::debug!(
+										target: "sync",
+										"Error writing state response: {}",
+										e
+									);
+								}
+								(peer, handling_start.elapsed())
+							}.boxed());
+						}
+					}
+					Err(e) => log::debug!(
+						target: "sync",
+						"Error handling state request from peer {}: {}", peer, e
+					)
+				}
+			}
+			NodeEvent::Response(original_request, response) => {
+				let request_duration = if let Some(connections) = self.peers.get_mut(&peer) {
+					if let Some(connection) = connections.iter_mut().find(|c| c.id == connection_id) {
+						match &mut connection.ongoing_request {
+							Some(ongoing_request) if ongoing_request.request == original_request => {
+								let request_duration = ongoing_request.emitted.elapsed();
+								connection.ongoing_request = None;
+								request_duration
+							}
+							_ => {
+								// We're no longer interested in that request.
+								log::debug!(
+									target: "sync",
+									"Received response from {} to obsolete state request {:?}",
+									peer,
+									original_request
+								);
+								return;
+							}
+						}
+					} else {
+						log::error!(
+							target: "sync",
+							"State inconsistency: response on non-existing connection {:?}",
+							connection_id
+						);
+						return;
+					}
+				} else {
+					log::error!(
+						target: "sync",
+						"State inconsistency: response on non-connected peer {}",
+						peer
+					);
+					return;
+				};
+
+				match StorageProof::decode(&mut response.proof.as_ref()) {
+					Ok(proof) => {
+						log::trace!(
+							target: "sync",
+							"Received state response from peer {} ({} bytes)",
+							peer, response.proof.len()
+						);
+						let ev = Event::Response {
+							peer,
+							response: message::StateResponse { proof },
+							request_duration,
+						};
+						self.pending_events.push_back(NetworkBehaviourAction::GenerateEvent(ev));
+					}
+					Err(err) => {
+						log::debug!(
+							target: "sync",
+							"Failed to decode state response from peer {}: {}", peer, err
+						);
+					}
+				}
+			}
+		}
+	}
+
+	fn poll(&mut self, cx: &mut Context, _: &mut impl PollParameters)
+		-> Poll<NetworkBehaviourAction<OutboundProtocol<B>, Event>>
+	{
+		if let Some(ev) = self.pending_events.pop_front() {
+			return Poll::Ready(ev);
+		}
+
+		// Check the request timeouts.
+		for (peer, connections) in &mut self.peers {
+			for connection in connections {
+				let ongoing_request = match &mut connection.ongoing_request {
+					Some(rq) => rq,
+					None => continue,
+				};
+
+				if let Poll::Ready(_) = Pin::new(&mut ongoing_request.timeout).poll(cx) {
+					let original_request = ongoing_request.request.clone();
+					let request_duration = ongoing_request.emitted.elapsed();
+					connection.ongoing_request = None;
+					log::debug!(
+						target: "sync",
+						"State request timeout for {}: {:?}",
+						peer, original_request
+					);
+					let ev = Event::RequestTimeout {
+						peer: peer.clone(),
+						request_duration,
+					};
+					return Poll::Ready(NetworkBehaviourAction::GenerateEvent(ev));
+				}
+			}
+		}
+
+		if let Poll::Ready(Some((peer, total_handling_time))) = self.outgoing.poll_next_unpin(cx) {
+			let ev = Event::AnsweredRequest {
+				peer,
+				total_handling_time,
+			};
+			return Poll::Ready(NetworkBehaviourAction::GenerateEvent(ev));
+		}
+
+		Poll::Pending
+	}
+}
+
+/// Output type of inbound and outbound substream upgrades.
+#[derive(Debug)]
+pub enum NodeEvent<B: Block, T> {
+	/// Incoming request from remote, substream to use for the response, and when we started
+	/// handling this request.
+	Request(schema::v1::StateRequest, T, Instant),
+	/// Incoming response from remote.
+	Response(message::StateRequest<B>, schema::v1::StateResponse),
+}
+
+/// Substream upgrade protocol.
+///
+/// We attempt to parse an incoming protobuf encoded request which will be handled
+/// by the `StateRequests` behaviour.
+#[derive(Debug, Clone)]
+pub struct InboundProtocol<B> {
+	/// The max. request length in bytes.
+	max_request_len: usize,
+	/// The protocol to use during upgrade negotiation.
+	protocol: Bytes,
+	/// Type of the block.
+	marker: PhantomData<B>,
+}
+
+impl<B: Block> UpgradeInfo for InboundProtocol<B> {
+	type Info = Bytes;
+	type InfoIter = iter::Once<Self::Info>;
+
+	fn protocol_info(&self) -> Self::InfoIter {
+		iter::once(self.protocol.clone())
+	}
+}
+
+impl<B, T> InboundUpgrade<T> for InboundProtocol<B>
+where
+	B: Block,
+	T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+	type Output = NodeEvent<B, T>;
+	type Error = ReadOneError;
+	type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+	fn upgrade_inbound(self, mut s: T, _: Self::Info) -> Self::Future {
+		// This `Instant` will be passed around until the processing of this request is done.
+		let handling_start = Instant::now();
+
+		let future = async move {
+			let len = self.max_request_len;
+			let vec = read_one(&mut s, len).await?;
+			match schema::v1::StateRequest::decode(&vec[..]) {
+				Ok(r) => Ok(NodeEvent::Request(r, s, handling_start)),
+				Err(e) => Err(ReadOneError::Io(io::Error::new(io::ErrorKind::Other, e)))
+			}
+		};
+		future.boxed()
+	}
+}
+
+/// Substream upgrade protocol.
+///
+/// Sends a request to remote and awaits the response.
+#[derive(Debug, Clone)]
+pub struct OutboundProtocol<B: Block> {
+	/// The serialized protobuf request.
+	request: Vec<u8>,
+	/// The original request. Passed back through the API when the response comes back.
+	original_request: message::StateRequest<B>,
+	/// The max. response length in bytes.
+	max_response_size: usize,
+	/// The protocol to use for upgrade negotiation.
+	protocol: Bytes,
+}
+
+impl<B: Block> UpgradeInfo for OutboundProtocol<B> {
+	type Info = Bytes;
+	type InfoIter = iter::Once<Self::Info>;
+
+	fn protocol_info(&self) -> Self::InfoIter {
+		iter::once(self.protocol.clone())
+	}
+}
+
+impl<B, T> OutboundUpgrade<T> for OutboundProtocol<B>
+where
+	B: Block,
+	T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+	type Output = NodeEvent<B, T>;
+	type Error = ReadOneError;
+	type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+	fn upgrade_outbound(self, mut s: T, _: Self::Info) -> Self::Future {
+		async move {
+			write_one(&mut s, &self.request).await?;
+			let vec = read_one(&mut s, self.max_response_size).await?;
+
+			schema::v1::StateResponse::decode(&vec[..])
+				.map(|r| NodeEvent::Response(self.original_request, r))
+				.map_err(|e| {
+					ReadOneError::Io(io::Error::new(io::ErrorKind::Other, e))
+				})
+		}.boxed()
+	}
+}
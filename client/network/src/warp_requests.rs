@@ -0,0 +1,643 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+//
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! `NetworkBehaviour` implementation which handles warp sync requests.
+//!
+//! A warp sync request asks for a proof of the finality of the latest finalized block, starting
+//! from a block that the requester knows to be final. The proof is made of one justification per
+//! authority set change, followed by a justification of the latest finalized block. The proof is
+//! generated and verified by a [`WarpSyncProvider`], which is implemented by the finality gadget.
+//!
+//! Every request is coming in on a separate connection substream which gets
+//! closed after we have sent the response back. Requests are SCALE-encoded
+//! [`WarpProofRequest`]s, responses are the proofs as encoded by the provider.
+
+use bytes::Bytes;
+use codec::{Encode, Decode};
+use crate::config::ProtocolId;
+use futures::{future::BoxFuture, prelude::*, stream::FuturesUnordered};
+use futures_timer::Delay;
+use libp2p::{
+	core::{
+		ConnectedPoint,
+		Multiaddr,
+		PeerId,
+		connection::ConnectionId,
+		upgrade::{InboundUpgrade, OutboundUpgrade, ReadOneError, UpgradeInfo},
+		upgrade::{read_one, write_one}
+	},
+	swarm::{
+		NegotiatedSubstream,
+		NetworkBehaviour,
+		NetworkBehaviourAction,
+		NotifyHandler,
+		OneShotHandler,
+		OneShotHandlerConfig,
+		PollParameters,
+		SubstreamProtocol
+	}
+};
+use sp_finality_grandpa::{AuthorityList, SetId};
+use sp_runtime::traits::Block;
+use std::{
+	collections::{HashMap, VecDeque},
+	io,
+	iter,
+	marker::PhantomData,
+	pin::Pin,
+	sync::Arc,
+	time::Duration,
+	task::{Context, Poll}
+};
+use wasm_timer::Instant;
+
+/// Warp sync proof, encoded by the [`WarpSyncProvider`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedProof(pub Vec<u8>);
+
+/// Request for a warp sync proof.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct WarpProofRequest<B: Block> {
+	/// Finalized block to start the proof from.
+	pub begin: B::Hash,
+}
+
+/// Result of the verification of a warp sync proof.
+pub enum VerificationResult<B: Block> {
+	/// The proof is valid, but doesn't reach the latest finalized block. Another proof should be
+	/// requested, starting from the given block, with the given authority set.
+	Partial(SetId, AuthorityList, B::Hash),
+	/// The proof is valid and proves the finality of the given header. The given authority set
+	/// is the one in effect after that header.
+	Complete(SetId, AuthorityList, B::Header),
+}
+
+/// Generates and verifies warp sync proofs.
+pub trait WarpSyncProvider<B: Block>: Send + Sync {
+	/// Generate a proof of the finality of the latest finalized block, starting from the given
+	/// finalized block. The proof may be incomplete if it would be too large.
+	fn generate(
+		&self,
+		begin: B::Hash,
+	) -> Result<EncodedProof, Box<dyn std::error::Error + Send + Sync>>;
+
+	/// Verify a proof generated by a remote, starting with the given authority set.
+	fn verify(
+		&self,
+		proof: &EncodedProof,
+		set_id: SetId,
+		authorities: AuthorityList,
+	) -> Result<VerificationResult<B>, Box<dyn std::error::Error + Send + Sync>>;
+
+	/// The authority set to start verifying proofs from the genesis block with.
+	fn current_authorities(&self) -> AuthorityList;
+}
+
+/// Event generated by the warp requests behaviour.
+#[derive(Debug)]
+pub enum Event {
+	/// A request came and we have successfully answered it.
+	AnsweredRequest {
+		/// Peer which has emitted the request.
+		peer: PeerId,
+		/// Time elapsed between when we received the request and when we sent back the response.
+		total_handling_time: Duration,
+	},
+
+	/// A response to a warp sync request has arrived.
+	Response {
+		peer: PeerId,
+		response: EncodedProof,
+		/// Time elapsed between the start of the request and the response.
+		request_duration: Duration,
+	},
+
+	/// A request has been cancelled because the peer has disconnected.
+	/// Disconnects can also happen as a result of violating the network protocol.
+	RequestCancelled {
+		peer: PeerId,
+		/// Time elapsed between the start of the request and the cancellation.
+		request_duration: Duration,
+	},
+
+	/// A request has timed out.
+	RequestTimeout {
+		peer: PeerId,
+		/// Time elapsed between the start of the request and the timeout.
+		request_duration: Duration,
+	}
+}
+
+/// Configuration options for `WarpRequests`.
+#[derive(Debug, Clone)]
+pub struct Config {
+	max_request_len: usize,
+	max_response_len: usize,
+	inactivity_timeout: Duration,
+	request_timeout: Duration,
+	protocol: String,
+}
+
+impl Config {
+	/// Create a fresh configuration with the following options:
+	///
+	/// - max. request size = 1 KiB
+	/// - max. response size = 16 MiB
+	/// - inactivity timeout = 15s
+	/// - request timeout = 40s
+	pub fn new(id: &ProtocolId) -> Self {
+		let mut c = Config {
+			max_request_len: 1024,
+			max_response_len: 16 * 1024 * 1024,
+			inactivity_timeout: Duration::from_secs(15),
+			request_timeout: Duration::from_secs(40),
+			protocol: String::new(),
+		};
+		c.set_protocol(id);
+		c
+	}
+
+	/// Limit the max. length of incoming warp sync request bytes.
+	pub fn set_max_request_len(&mut self, v: usize) -> &mut Self {
+		self.max_request_len = v;
+		self
+	}
+
+	/// Limit the max. size of responses to our warp sync requests.
+	pub fn set_max_response_len(&mut self, v: usize) -> &mut Self {
+		self.max_response_len = v;
+		self
+	}
+
+	/// Limit the max. duration the substream may remain inactive before closing it.
+	pub fn set_inactivity_timeout(&mut self, v: Duration) -> &mut Self {
+		self.inactivity_timeout = v;
+		self
+	}
+
+	/// Set protocol to use for upgrade negotiation.
+	pub fn set_protocol(&mut self, id: &ProtocolId) -> &mut Self {
+		let mut s = String::new();
+		s.push_str("/");
+		s.push_str(id.as_ref());
+		s.push_str("/sync/warp");
+		self.protocol = s;
+		self
+	}
+}
+
+/// The warp sync request handling behaviour.
+pub struct WarpRequests<B: Block> {
+	/// This behaviour's configuration.
+	config: Config,
+	/// Generates the proofs we answer requests with. Requests are ignored if `None`.
+	provider: Option<Arc<dyn WarpSyncProvider<B>>>,
+	/// List of all active connections and the requests we've sent.
+	peers: HashMap<PeerId, Vec<Connection<B>>>,
+	/// Futures sending back the warp sync response. Returns the `PeerId` we sent back to, and
+	/// the total time the handling of this request took.
+	outgoing: FuturesUnordered<BoxFuture<'static, (PeerId, Duration)>>,
+	/// Events to return as soon as possible from `poll`.
+	pending_events: VecDeque<NetworkBehaviourAction<OutboundProtocol<B>, Event>>,
+}
+
+/// Local tracking of a libp2p connection.
+#[derive(Debug)]
+struct Connection<B: Block> {
+	id: ConnectionId,
+	ongoing_request: Option<OngoingRequest<B>>,
+}
+
+#[derive(Debug)]
+struct OngoingRequest<B: Block> {
+	/// `Instant` when the request has been emitted. Used for diagnostic purposes.
+	emitted: Instant,
+	request: WarpProofRequest<B>,
+	timeout: Delay,
+}
+
+/// Outcome of calling `send_request`.
+#[derive(Debug)]
+#[must_use]
+pub enum SendRequestOutcome<B: Block> {
+	/// Request has been emitted.
+	Ok,
+	/// The request has been emitted and has replaced an existing request.
+	Replaced {
+		/// The previously-emitted request.
+		previous: WarpProofRequest<B>,
+		/// Time that had elapsed since `previous` has been emitted.
+		request_duration: Duration,
+	},
+	/// Didn't start a request because we have no connection to this node.
+	/// If `send_request` returns that, it is as if the function had never been called.
+	NotConnected,
+}
+
+impl<B> WarpRequests<B>
+where
+	B: Block,
+{
+	pub fn new(cfg: Config, provider: Option<Arc<dyn WarpSyncProvider<B>>>) -> Self {
+		WarpRequests {
+			config: cfg,
+			provider,
+			peers: HashMap::new(),
+			outgoing: FuturesUnordered::new(),
+			pending_events: VecDeque::new(),
+		}
+	}
+
+	/// Returns the libp2p protocol name used on the wire (e.g. `/foo/sync/warp`).
+	pub fn protocol_name(&self) -> &str {
+		&self.config.protocol
+	}
+
+	/// Issue a new warp sync request.
+	///
+	/// Cancels any existing request targeting the same `PeerId`.
+	pub fn send_request(&mut self, target: &PeerId, req: WarpProofRequest<B>) -> SendRequestOutcome<B> {
+		// Determine which connection to send the request to.
+		let connection = if let Some(peer) = self.peers.get_mut(target) {
+			// We don't want to have multiple requests for any given node, so in priority try to
+			// find a connection with an existing request, to override it.
+			if let Some(entry) = peer.iter_mut().find(|c| c.ongoing_request.is_some()) {
+				entry
+			} else if let Some(entry) = peer.get_mut(0) {
+				entry
+			} else {
+				log::error!(
+					target: "sync",
+					"State inconsistency: empty list of peer connections"
+				);
+				return SendRequestOutcome::NotConnected;
+			}
+		} else {
+			return SendRequestOutcome::NotConnected;
+		};
+
+		let previous_request = connection.ongoing_request.take();
+		connection.ongoing_request = Some(OngoingRequest {
+			emitted: Instant::now(),
+			request: req.clone(),
+			timeout: Delay::new(self.config.request_timeout),
+		});
+
+		log::trace!(target: "sync", "Enqueueing warp sync request to {:?}: {:?}", target, req);
+		self.pending_events.push_back(NetworkBehaviourAction::NotifyHandler {
+			peer_id: target.clone(),
+			handler: NotifyHandler::One(connection.id),
+			event: OutboundProtocol {
+				request: req.encode(),
+				original_request: req,
+				max_response_size: self.config.max_response_len,
+				protocol: self.config.protocol.as_bytes().to_vec().into(),
+			},
+		});
+
+		if let Some(previous_request) = previous_request {
+			log::debug!(
+				target: "sync",
+				"Replacing existing warp sync request on connection {:?}",
+				connection.id
+			);
+			SendRequestOutcome::Replaced {
+				previous: previous_request.request,
+				request_duration: previous_request.emitted.elapsed(),
+			}
+		} else {
+			SendRequestOutcome::Ok
+		}
+	}
+
+	/// Callback, invoked when a new warp sync request has been received from remote.
+	fn on_warp_request(
+		&mut self,
+		peer: &PeerId,
+		request: &[u8],
+	) -> Result<EncodedProof, Box<dyn std::error::Error + Send + Sync>> {
+		let provider = match &self.provider {
+			Some(provider) => provider,
+			None => return Err("Warp sync is not supported by this node".into()),
+		};
+		let request = WarpProofRequest::<B>::decode(&mut &request[..])?;
+
+		log::trace!(target: "sync", "Warp sync request from peer {}: begin {}", peer, request.begin);
+
+		let proof = provider.generate(request.begin)?;
+
+		log::trace!(target: "sync", "Sending warp sync proof of {} bytes to {}", proof.0.len(), peer);
+		Ok(proof)
+	}
+}
+
+impl<B> NetworkBehaviour for WarpRequests<B>
+where
+	B: Block
+{
+	type ProtocolsHandler = OneShotHandler<InboundProtocol<B>, OutboundProtocol<B>, NodeEvent<B, NegotiatedSubstream>>;
+	type OutEvent = Event;
+
+	fn new_handler(&mut self) -> Self::ProtocolsHandler {
+		let p = InboundProtocol {
+			max_request_len: self.config.max_request_len,
+			protocol: self.config.protocol.as_bytes().to_owned().into(),
+			marker: PhantomData,
+		};
+		let mut cfg = OneShotHandlerConfig::default();
+		cfg.keep_alive_timeout = self.config.inactivity_timeout;
+		cfg.outbound_substream_timeout = self.config.request_timeout;
+		OneShotHandler::new(SubstreamProtocol::new(p, ()), cfg)
+	}
+
+	fn addresses_of_peer(&mut self, _: &PeerId) -> Vec<Multiaddr> {
+		Vec::new()
+	}
+
+	fn inject_connected(&mut self, _peer: &PeerId) {
+	}
+
+	fn inject_disconnected(&mut self, _peer: &PeerId) {
+	}
+
+	fn inject_connection_established(&mut self, peer_id: &PeerId, id: &ConnectionId, _: &ConnectedPoint) {
+		self.peers.entry(peer_id.clone())
+			.or_default()
+			.push(Connection {
+				id: *id,
+				ongoing_request: None,
+			});
+	}
+
+	fn inject_connection_closed(&mut self, peer_id: &PeerId, id: &ConnectionId, _: &ConnectedPoint) {
+		let mut needs_remove = false;
+		if let Some(entry) = self.peers.get_mut(peer_id) {
+			if let Some(pos) = entry.iter().position(|i| i.id == *id) {
+				let ongoing_request = entry.remove(pos).ongoing_request;
+				if let Some(ongoing_request) = ongoing_request {
+					log::debug!(
+						target: "sync",
+						"Connection {:?} with {} closed with ongoing warp sync request: {:?}",
+						id,
+						peer_id,
+						ongoing_request
+					);
+					let ev = Event::RequestCancelled {
+						peer: peer_id.clone(),
+						request_duration: ongoing_request.emitted.elapsed(),
+					};
+					self.pending_events.push_back(NetworkBehaviourAction::GenerateEvent(ev));
+				}
+				if entry.is_empty() {
+					needs_remove = true;
+				}
+			} else {
+				log::error!(
+					target: "sync",
+					"State inconsistency: connection id not found in list"
+				);
+			}
+		} else {
+			log::error!(
+				target: "sync",
+				"State inconsistency: peer_id not found in list of connections"
+			);
+		}
+		if needs_remove {
+			self.peers.remove(peer_id);
+		}
+	}
+
+	fn inject_event(
+		&mut self,
+		peer: PeerId,
+		connection_id: ConnectionId,
+		node_event: NodeEvent<B, NegotiatedSubstream>
+	) {
+		match node_event {
+			NodeEvent::Request(request, mut stream, handling_start) => {
+				match self.on_warp_request(&peer, &request) {
+					Ok(proof) => {
+						self.outgoing.push(async move {
+							if let Err(e) = write_one(&mut stream, proof.0).await {
+								log::debug!(
+									target: "sync",
+									"Error writing warp sync response: {}",
+									e
+								);
+							}
+							(peer, handling_start.elapsed())
+						}.boxed());
+					}
+					Err(e) => log::debug!(
+						target: "sync",
+						"Error handling warp sync request from peer {}: {}", peer, e
+					)
+				}
+			}
+			NodeEvent::Response(original_request, response) => {
+				let request_duration = if let Some(connections) = self.peers.get_mut(&peer) {
+					if let Some(connection) = connections.iter_mut().find(|c| c.id == connection_id) {
+						match &mut connection.ongoing_request {
+							Some(ongoing_request) if ongoing_request.request == original_request => {
+								let request_duration = ongoing_request.emitted.elapsed();
+								connection.ongoing_request = None;
+								request_duration
+							}
+							_ => {
+								// We're no longer interested in that request.
+								log::debug!(
+									target: "sync",
+									"Received response from {} to obsolete warp sync request {:?}",
+									peer,
+									original_request
+								);
+								return;
+							}
+						}
+					} else {
+						log::error!(
+							target: "sync",
+							"State inconsistency: response on non-existing connection {:?}",
+							connection_id
+						);
+						return;
+					}
+				} else {
+					log::error!(
+						target: "sync",
+						"State inconsistency: response on non-connected peer {}",
+						peer
+					);
+					return;
+				};
+
+				log::trace!(
+					target: "sync",
+					"Received warp sync response from peer {} ({} bytes)",
+					peer, response.len()
+				);
+				let ev = Event::Response {
+					peer,
+					response: EncodedProof(response),
+					request_duration,
+				};
+				self.pending_events.push_back(NetworkBehaviourAction::GenerateEvent(ev));
+			}
+		}
+	}
+
+	fn poll(&mut self, cx: &mut Context, _: &mut impl PollParameters)
+		-> Poll<NetworkBehaviourAction<OutboundProtocol<B>, Event>>
+	{
+		if let Some(ev) = self.pending_events.pop_front() {
+			return Poll::Ready(ev);
+		}
+
+		// Check the request timeouts.
+		for (peer, connections) in &mut self.peers {
+			for connection in connections {
+				let ongoing_request = match &mut connection.ongoing_request {
+					Some(rq) => rq,
+					None => continue,
+				};
+
+				if let Poll::Ready(_) = Pin::new(&mut ongoing_request.timeout).poll(cx) {
+					let original_request = ongoing_request.request.clone();
+					let request_duration = ongoing_request.emitted.elapsed();
+					connection.ongoing_request = None;
+					log::debug!(
+						target: "sync",
+						"Warp sync request timeout for {}: {:?}",
+						peer, original_request
+					);
+					let ev = Event::RequestTimeout {
+						peer: peer.clone(),
+						request_duration,
+					};
+					return Poll::Ready(NetworkBehaviourAction::GenerateEvent(ev));
+				}
+			}
+		}
+
+		if let Poll::Ready(Some((peer, total_handling_time))) = self.outgoing.poll_next_unpin(cx) {
+			let ev = Event::AnsweredRequest {
+				peer,
+				total_handling_time,
+			};
+			return Poll::Ready(NetworkBehaviourAction::GenerateEvent(ev));
+		}
+
+		Poll::Pending
+	}
+}
+
+/// Output type of inbound and outbound substream upgrades.
+#[derive(Debug)]
+pub enum NodeEvent<B: Block, T> {
+	/// Incoming request from remote, substream to use for the response, and when we started
+	/// handling this request.
+	Request(Vec<u8>, T, Instant),
+	/// Incoming response from remote.
+	Response(WarpProofRequest<B>, Vec<u8>),
+}
+
+/// Substream upgrade protocol.
+///
+/// We read an incoming SCALE encoded request which will be handled by the `WarpRequests`
+/// behaviour.
+#[derive(Debug, Clone)]
+pub struct InboundProtocol<B> {
+	/// The max. request length in bytes.
+	max_request_len: usize,
+	/// The protocol to use during upgrade negotiation.
+	protocol: Bytes,
+	/// Type of the block.
+	marker: PhantomData<B>,
+}
+
+impl<B: Block> UpgradeInfo for InboundProtocol<B> {
+	type Info = Bytes;
+	type InfoIter = iter::Once<Self::Info>;
+
+	fn protocol_info(&self) -> Self::InfoIter {
+		iter::once(self.protocol.clone())
+	}
+}
+
+impl<B, T> InboundUpgrade<T> for InboundProtocol<B>
+where
+	B: Block,
+	T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+	type Output = NodeEvent<B, T>;
+	type Error = ReadOneError;
+	type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+	fn upgrade_inbound(self, mut s: T, _: Self::Info) -> Self::Future {
+		// This `Instant` will be passed around until the processing of this request is done.
+		let handling_start = Instant::now();
+
+		let future = async move {
+			let len = self.max_request_len;
+			let vec = read_one(&mut s, len).await?;
+			Ok(NodeEvent::Request(vec, s, handling_start))
+		};
+		future.boxed()
+	}
+}
+
+/// Substream upgrade protocol.
+///
+/// Sends a request to remote and awaits the response.
+#[derive(Debug, Clone)]
+pub struct OutboundProtocol<B: Block> {
+	/// The SCALE encoded request.
+	request: Vec<u8>,
+	/// The original request. Passed back through the API when the response comes back.
+	original_request: WarpProofRequest<B>,
+	/// The max. response length in bytes.
+	max_response_size: usize,
+	/// The protocol to use for upgrade negotiation.
+	protocol: Bytes,
+}
+
+impl<B: Block> UpgradeInfo for OutboundProtocol<B> {
+	type Info = Bytes;
+	type InfoIter = iter::Once<Self::Info>;
+
+	fn protocol_info(&self) -> Self::InfoIter {
+		iter::once(self.protocol.clone())
+	}
+}
+
+impl<B, T> OutboundUpgrade<T> for OutboundProtocol<B>
+where
+	B: Block,
+	T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+	type Output = NodeEvent<B, T>;
+	type Error = ReadOneError;
+	type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+	fn upgrade_outbound(self, mut s: T, _: Self::Info) -> Self::Future {
+		async move {
+			write_one(&mut s, &self.request).await?;
+			let vec = read_one(&mut s, self.max_response_size).await?;
+			Ok(NodeEvent::Response(self.original_request, vec))
+		}.boxed()
+	}
+}
@@ -50,14 +50,17 @@ use sp_consensus::Error as ConsensusError;
 use sp_consensus::{BlockOrigin, ForkChoiceStrategy, BlockImportParams, BlockCheckParams, JustificationImport};
 use futures::prelude::*;
 use sc_network::{NetworkWorker, NetworkService, config::ProtocolId};
-use sc_network::config::{NetworkConfiguration, TransportConfig};
+use sc_network::config::{NetworkConfiguration, SyncMode, TransportConfig};
+use sc_network::config::{EncodedProof, VerificationResult, WarpSyncProvider};
+use sp_finality_grandpa::{AuthorityList, SetId};
+use codec::{Decode, Encode};
 use libp2p::PeerId;
 use parking_lot::Mutex;
 use sp_core::H256;
 use sc_network::config::ProtocolConfig;
 use sp_runtime::generic::{BlockId, OpaqueDigestItemId};
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
-use sp_runtime::Justification;
+use sp_runtime::{Justification, Justifications};
 use substrate_test_runtime_client::{self, AccountKeyring};
 use sc_service::client::Client;
 pub use sc_network::config::EmptyTransactionPool;
@@ -103,7 +106,7 @@ impl<B: BlockT> Verifier<B> for PassThroughVerifier {
 		&mut self,
 		origin: BlockOrigin,
 		header: B::Header,
-		justification: Option<Justification>,
+		justifications: Option<Justifications>,
 		body: Option<Vec<B::Extrinsic>>
 	) -> Result<(BlockImportParams<B, ()>, Option<Vec<(CacheKeyId, Vec<u8>)>>), String> {
 		let maybe_keys = header.digest()
@@ -114,7 +117,7 @@ impl<B: BlockT> Verifier<B> for PassThroughVerifier {
 		let mut import = BlockImportParams::new(origin, header);
 		import.body = body;
 		import.finalized = self.finalized;
-		import.justification = justification;
+		import.justifications = justifications;
 		import.fork_choice = Some(self.fork_choice.clone());
 
 		Ok((import, maybe_keys))
@@ -178,10 +181,10 @@ impl PeersClient {
 		}
 	}
 
-	pub fn justification(&self, block: &BlockId<Block>) -> ClientResult<Option<Justification>> {
+	pub fn justifications(&self, block: &BlockId<Block>) -> ClientResult<Option<Justifications>> {
 		match *self {
-			PeersClient::Full(ref client, ref _backend) => client.justification(block),
-			PeersClient::Light(ref client, ref _backend) => client.justification(block),
+			PeersClient::Full(ref client, ref _backend) => client.justifications(block),
+			PeersClient::Light(ref client, ref _backend) => client.justifications(block),
 		}
 	}
 
@@ -554,11 +557,11 @@ impl<B: BlockT> Verifier<B> for VerifierAdapter<B> {
 		&mut self,
 		origin: BlockOrigin,
 		header: B::Header,
-		justification: Option<Justification>,
+		justifications: Option<Justifications>,
 		body: Option<Vec<B::Extrinsic>>
 	) -> Result<(BlockImportParams<B, ()>, Option<Vec<(CacheKeyId, Vec<u8>)>>), String> {
 		let hash = header.hash();
-		self.verifier.lock().verify(origin, header, justification, body).map_err(|e| {
+		self.verifier.lock().verify(origin, header, justifications, body).map_err(|e| {
 			self.failed_verifications.lock().insert(hash, e.clone());
 			e
 		})
@@ -583,6 +586,41 @@ pub struct FullPeerConfig {
 	pub block_announce_validator: Option<Box<dyn BlockAnnounceValidator<Block> + Send + Sync>>,
 	/// List of notification protocols that the network must support.
 	pub notifications_protocols: Vec<Cow<'static, str>>,
+	/// Sync mode. Full sync when `None`.
+	pub sync_mode: Option<SyncMode>,
+	/// Answer and verify warp sync proofs with a `TestWarpSyncProvider`.
+	pub warp_sync: bool,
+}
+
+/// Warp sync provider that "proves" the finality of the latest finalized block by sending its
+/// header, and trusts any header it receives. Allows testing warp sync without a finality gadget.
+pub struct TestWarpSyncProvider(Arc<PeersFullClient>);
+
+impl WarpSyncProvider<Block> for TestWarpSyncProvider {
+	fn generate(
+		&self,
+		_begin: Hash,
+	) -> Result<EncodedProof, Box<dyn std::error::Error + Send + Sync>> {
+		let finalized_hash = self.0.info().finalized_hash;
+		let header = self.0.header(&BlockId::Hash(finalized_hash))
+			.map_err(|e| e.to_string())?
+			.ok_or("Missing finalized header")?;
+		Ok(EncodedProof(header.encode()))
+	}
+
+	fn verify(
+		&self,
+		proof: &EncodedProof,
+		set_id: SetId,
+		authorities: AuthorityList,
+	) -> Result<VerificationResult<Block>, Box<dyn std::error::Error + Send + Sync>> {
+		let header = Decode::decode(&mut proof.0.as_slice()).map_err(|e| e.what())?;
+		Ok(VerificationResult::Complete(set_id, authorities, header))
+	}
+
+	fn current_authorities(&self) -> AuthorityList {
+		Vec::new()
+	}
 }
 
 pub trait TestNetFactory: Sized {
@@ -681,6 +719,7 @@ pub trait TestNetFactory: Sized {
 		network_config.listen_addresses = vec![listen_addr.clone()];
 		network_config.allow_non_globals_in_dht = true;
 		network_config.notifications_protocols = config.notifications_protocols;
+		network_config.sync_mode = config.sync_mode.unwrap_or(SyncMode::Full);
 
 		let network = NetworkWorker::new(sc_network::config::Params {
 			role: Role::Full,
@@ -693,6 +732,11 @@ pub trait TestNetFactory: Sized {
 			import_queue,
 			block_announce_validator: config.block_announce_validator
 				.unwrap_or_else(|| Box::new(DefaultBlockAnnounceValidator)),
+			warp_sync: if config.warp_sync {
+				Some(Arc::new(TestWarpSyncProvider(client.clone())) as Arc<_>)
+			} else {
+				None
+			},
 			metrics_registry: None,
 		}).unwrap();
 
@@ -767,6 +811,7 @@ pub trait TestNetFactory: Sized {
 			protocol_id: ProtocolId::from("test-protocol-name"),
 			import_queue,
 			block_announce_validator: Box::new(DefaultBlockAnnounceValidator),
+			warp_sync: None,
 			metrics_registry: None,
 		}).unwrap();
 
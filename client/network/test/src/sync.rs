@@ -23,6 +23,8 @@ use super::*;
 use sp_consensus::block_validation::Validation;
 use substrate_test_runtime::Header;
 
+const TEST_ENGINE_ID: sp_runtime::ConsensusEngineId = *b"TEST";
+
 fn test_ancestor_search_when_common_is(n: usize) {
 	sp_tracing::try_init_simple();
 	let mut net = TestNet::new(3);
@@ -248,13 +250,13 @@ fn sync_justifications() {
 	net.block_until_sync();
 
 	// there's currently no justification for block #10
-	assert_eq!(net.peer(0).client().justification(&BlockId::Number(10)).unwrap(), None);
-	assert_eq!(net.peer(1).client().justification(&BlockId::Number(10)).unwrap(), None);
+	assert_eq!(net.peer(0).client().justifications(&BlockId::Number(10)).unwrap(), None);
+	assert_eq!(net.peer(1).client().justifications(&BlockId::Number(10)).unwrap(), None);
 
 	// we finalize block #10, #15 and #20 for peer 0 with a justification
-	net.peer(0).client().finalize_block(BlockId::Number(10), Some(Vec::new()), true).unwrap();
-	net.peer(0).client().finalize_block(BlockId::Number(15), Some(Vec::new()), true).unwrap();
-	net.peer(0).client().finalize_block(BlockId::Number(20), Some(Vec::new()), true).unwrap();
+	net.peer(0).client().finalize_block(BlockId::Number(10), Some((TEST_ENGINE_ID, Vec::new())), true).unwrap();
+	net.peer(0).client().finalize_block(BlockId::Number(15), Some((TEST_ENGINE_ID, Vec::new())), true).unwrap();
+	net.peer(0).client().finalize_block(BlockId::Number(20), Some((TEST_ENGINE_ID, Vec::new())), true).unwrap();
 
 	let h1 = net.peer(1).client().header(&BlockId::Number(10)).unwrap().unwrap();
 	let h2 = net.peer(1).client().header(&BlockId::Number(15)).unwrap().unwrap();
@@ -265,14 +267,15 @@ fn sync_justifications() {
 	net.peer(1).request_justification(&h2.hash().into(), 15);
 	net.peer(1).request_justification(&h3.hash().into(), 20);
 
+	let justifications = Some(Justifications::from((TEST_ENGINE_ID, Vec::new())));
 	block_on(futures::future::poll_fn::<(), _>(|cx| {
 		net.poll(cx);
 
 		for height in (10..21).step_by(5) {
-			if net.peer(0).client().justification(&BlockId::Number(height)).unwrap() != Some(Vec::new()) {
+			if net.peer(0).client().justifications(&BlockId::Number(height)).unwrap() != justifications {
 				return Poll::Pending;
 			}
-			if net.peer(1).client().justification(&BlockId::Number(height)).unwrap() != Some(Vec::new()) {
+			if net.peer(1).client().justifications(&BlockId::Number(height)).unwrap() != justifications {
 				return Poll::Pending;
 			}
 		}
@@ -295,16 +298,17 @@ fn sync_justifications_across_forks() {
 	// for both and finalize the small fork instead.
 	net.block_until_sync();
 
-	net.peer(0).client().finalize_block(BlockId::Hash(f1_best), Some(Vec::new()), true).unwrap();
+	net.peer(0).client().finalize_block(BlockId::Hash(f1_best), Some((TEST_ENGINE_ID, Vec::new())), true).unwrap();
 
 	net.peer(1).request_justification(&f1_best, 10);
 	net.peer(1).request_justification(&f2_best, 11);
 
+	let justifications = Some(Justifications::from((TEST_ENGINE_ID, Vec::new())));
 	block_on(futures::future::poll_fn::<(), _>(|cx| {
 		net.poll(cx);
 
-		if net.peer(0).client().justification(&BlockId::Number(10)).unwrap() == Some(Vec::new()) &&
-			net.peer(1).client().justification(&BlockId::Number(10)).unwrap() == Some(Vec::new())
+		if net.peer(0).client().justifications(&BlockId::Number(10)).unwrap() == justifications &&
+			net.peer(1).client().justifications(&BlockId::Number(10)).unwrap() == justifications
 		{
 			Poll::Ready(())
 		} else {
@@ -600,6 +604,51 @@ fn syncs_header_only_forks() {
 	assert!(net.peer(1).has_block(&small_hash));
 }
 
+#[test]
+fn fast_sync_imports_state_of_justified_block() {
+	sp_tracing::try_init_simple();
+	let mut net = TestNet::new(0);
+	net.add_full_peer_with_config(Default::default());
+	net.add_full_peer_with_config(FullPeerConfig {
+		sync_mode: Some(SyncMode::Fast),
+		..Default::default()
+	});
+	net.peer(0).push_blocks(64, false);
+	// The state is downloaded for the only block with a justification.
+	net.peer(0).client().finalize_block(BlockId::Number(60), Some((TEST_ENGINE_ID, Vec::new())), true).unwrap();
+	net.block_until_sync();
+
+	assert_eq!(net.peer(1).client().info().best_number, 64);
+	assert_eq!(
+		net.peer(1).client().justifications(&BlockId::Number(60)).unwrap(),
+		Some(Justifications::from((TEST_ENGINE_ID, Vec::new()))),
+	);
+	let client = net.peer(1).client().as_full().unwrap();
+	assert!(client.state_at(&BlockId::Number(60)).is_ok());
+	assert!(client.state_at(&BlockId::Number(30)).is_err());
+}
+
+#[test]
+fn warp_sync_imports_state_of_finalized_block() {
+	sp_tracing::try_init_simple();
+	let mut net = TestNet::new(0);
+	net.add_full_peer_with_config(FullPeerConfig { warp_sync: true, ..Default::default() });
+	net.add_full_peer_with_config(FullPeerConfig {
+		sync_mode: Some(SyncMode::Warp),
+		warp_sync: true,
+		..Default::default()
+	});
+	net.peer(0).push_blocks(64, false);
+	net.peer(0).client().finalize_block(BlockId::Number(60), None, true).unwrap();
+	net.block_until_sync();
+
+	// The headers below the proven block are skipped, the blocks above it are imported.
+	assert_eq!(net.peer(1).client().info().best_number, 64);
+	let client = net.peer(1).client().as_full().unwrap();
+	assert!(client.state_at(&BlockId::Number(60)).is_ok());
+	assert!(client.header(&BlockId::Number(30)).unwrap().is_none());
+}
+
 #[test]
 fn does_not_sync_announced_old_best_block() {
 	sp_tracing::try_init_simple();
@@ -696,8 +745,8 @@ fn can_sync_to_peers_with_wrong_common_block() {
 	net.block_until_connected();
 
 	// both peers re-org to the same fork without notifying each other
-	net.peer(0).client().finalize_block(BlockId::Hash(fork_hash), Some(Vec::new()), true).unwrap();
-	net.peer(1).client().finalize_block(BlockId::Hash(fork_hash), Some(Vec::new()), true).unwrap();
+	net.peer(0).client().finalize_block(BlockId::Hash(fork_hash), Some((TEST_ENGINE_ID, Vec::new())), true).unwrap();
+	net.peer(1).client().finalize_block(BlockId::Hash(fork_hash), Some((TEST_ENGINE_ID, Vec::new())), true).unwrap();
 	let final_hash = net.peer(0).push_blocks(1, false);
 
 	net.block_until_sync();
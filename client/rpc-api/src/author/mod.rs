@@ -23,12 +23,23 @@ pub mod hash;
 
 use jsonrpc_derive::rpc;
 use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
+use serde::{Serialize, Deserialize};
 use sp_core::Bytes;
 use sp_transaction_pool::TransactionStatus;
 use self::error::{FutureResult, Result};
 
 pub use self::gen_client::Client as AuthorClient;
 
+/// Session keys generated by `author_rotateKeysWithOwner`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedSessionKeys {
+	/// The SCALE encoded public session keys.
+	pub keys: Bytes,
+	/// The proof of ownership of the keys, to be passed to `session.setKeys`.
+	pub proof: Bytes,
+}
+
 /// Substrate authoring RPC API
 #[rpc]
 pub trait AuthorApi<Hash, BlockHash> {
@@ -52,6 +63,13 @@ pub trait AuthorApi<Hash, BlockHash> {
 	#[rpc(name = "author_rotateKeys")]
 	fn rotate_keys(&self) -> Result<Bytes>;
 
+	/// Generate new session keys and returns the corresponding public keys, together with
+	/// a proof of their ownership for `owner`.
+	///
+	/// `owner` is the SCALE encoded account id that will register the keys on-chain.
+	#[rpc(name = "author_rotateKeysWithOwner")]
+	fn rotate_keys_with_owner(&self, owner: Bytes) -> Result<GeneratedSessionKeys>;
+
 	/// Checks if the keystore has private keys for the given session public keys.
 	///
 	/// `session_keys` is the SCALE encoded session keys object from the runtime.
@@ -18,7 +18,7 @@
 
 //! Substrate state API helpers.
 
-use sp_core::Bytes;
+use sp_core::{Bytes, storage::StorageData};
 use serde::{Serialize, Deserialize};
 
 /// ReadProof struct returned by the RPC
@@ -30,3 +30,31 @@ pub struct ReadProof<Hash> {
 	/// A proof used to prove that storage entries are included in the storage trie
 	pub proof: Vec<Bytes>,
 }
+
+/// The events of a block that were deposited with one of the requested topics.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventsByTopic<Hash> {
+	/// Hash of the block that emitted the events.
+	pub block: Hash,
+	/// Indices of the matching records within `events` in ascending order.
+	pub indices: Vec<u32>,
+	/// The encoded `Vec<EventRecord>` that `frame_system` stored for the block.
+	///
+	/// The node does not know the event type of the runtime and can therefore not split
+	/// the records. Clients decode them using the runtime metadata and only need to look
+	/// at the records listed in `indices`.
+	pub events: StorageData,
+}
+
+/// Historical states of finalized blocks that the node can serve.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailableStates<Hash> {
+	/// Number of the first finalized block whose state is kept by the pruning window.
+	/// The states of all later blocks are available as well. `None` if the states of all
+	/// finalized blocks are kept.
+	pub window_start: Option<u64>,
+	/// Numbers and hashes of the blocks before the window whose state is kept.
+	pub kept: Vec<(u64, Hash)>,
+}
@@ -25,12 +25,12 @@ use jsonrpc_core::Result as RpcResult;
 use jsonrpc_derive::rpc;
 use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
 use sp_core::Bytes;
-use sp_core::storage::{StorageKey, StorageData, StorageChangeSet};
+use sp_core::storage::{StorageKey, PrefixedStorageKey, StorageData, StorageChangeSet};
 use sp_version::RuntimeVersion;
 use self::error::FutureResult;
 
 pub use self::gen_client::Client as StateClient;
-pub use self::helpers::ReadProof;
+pub use self::helpers::{ReadProof, EventsByTopic, AvailableStates};
 
 /// Substrate state API
 #[rpc]
@@ -107,6 +107,35 @@ pub trait StateApi<Hash> {
 	#[rpc(name = "state_getReadProof")]
 	fn read_proof(&self, keys: Vec<StorageKey>, hash: Option<Hash>) -> FutureResult<ReadProof<Hash>>;
 
+	/// Returns proof of child storage entries at a specific block's state, combined with the
+	/// proof of the child trie root in the top trie. The proof can be checked against the state
+	/// root of the block.
+	#[rpc(name = "state_getChildReadProof")]
+	fn child_read_proof(
+		&self,
+		child_storage_key: PrefixedStorageKey,
+		keys: Vec<StorageKey>,
+		hash: Option<Hash>,
+	) -> FutureResult<ReadProof<Hash>>;
+
+	/// Query the events that were deposited with `topic` in the blocks between `from` and `to`
+	/// (inclusive). `to` defaults to the best block.
+	///
+	/// Only blocks with at least one matching event are returned. The range is limited to
+	/// 1000 blocks. Longer ranges need to be queried in multiple pages. Querying blocks
+	/// whose state was pruned fails. Use an archive node in order to query old blocks.
+	#[rpc(name = "state_queryEventsByTopic")]
+	fn query_events_by_topic(
+		&self,
+		topic: Hash,
+		from: Hash,
+		to: Option<Hash>,
+	) -> FutureResult<Vec<EventsByTopic<Hash>>>;
+
+	/// Returns the historical states that are available on this node.
+	#[rpc(name = "state_getAvailableStates")]
+	fn available_states(&self) -> FutureResult<AvailableStates<Hash>>;
+
 	/// New runtime version subscription
 	#[pubsub(
 		subscription = "state_runtimeVersion",
@@ -136,4 +165,26 @@ pub trait StateApi<Hash> {
 	fn unsubscribe_storage(
 		&self, metadata: Option<Self::Metadata>, id: SubscriptionId
 	) -> RpcResult<bool>;
+
+	/// New subscription to the events that are deposited with any of the given topics.
+	///
+	/// A notification is sent for every imported block with at least one matching event.
+	#[pubsub(
+		subscription = "state_eventsByTopic",
+		subscribe,
+		name = "state_subscribeEventsByTopic"
+	)]
+	fn subscribe_events_by_topic(
+		&self, metadata: Self::Metadata, subscriber: Subscriber<EventsByTopic<Hash>>, topics: Vec<Hash>
+	);
+
+	/// Unsubscribe from events by topic subscription
+	#[pubsub(
+		subscription = "state_eventsByTopic",
+		unsubscribe,
+		name = "state_unsubscribeEventsByTopic"
+	)]
+	fn unsubscribe_events_by_topic(
+		&self, metadata: Option<Self::Metadata>, id: SubscriptionId
+	) -> RpcResult<bool>;
 }
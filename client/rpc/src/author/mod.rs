@@ -115,10 +115,27 @@ impl<P, Client> AuthorApi<TxHash<P>, BlockHash<P>> for Author<P, Client>
 		self.deny_unsafe.check_if_safe()?;
 
 		let best_block_hash = self.client.info().best_hash;
-		self.client.runtime_api().generate_session_keys(
+		sp_session::generate_session_keys(
+			&*self.client,
 			&generic::BlockId::Hash(best_block_hash),
+			Vec::new(),
 			None,
-		).map(Into::into).map_err(|e| Error::Client(Box::new(e)))
+		).map(|generated| generated.keys.into()).map_err(|e| Error::Client(Box::new(e)))
+	}
+
+	fn rotate_keys_with_owner(&self, owner: Bytes) -> Result<GeneratedSessionKeys> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let best_block_hash = self.client.info().best_hash;
+		sp_session::generate_session_keys(
+			&*self.client,
+			&generic::BlockId::Hash(best_block_hash),
+			owner.to_vec(),
+			None,
+		).map(|generated| GeneratedSessionKeys {
+			keys: generated.keys.into(),
+			proof: generated.proof.into(),
+		}).map_err(|e| Error::Client(Box::new(e)))
 	}
 
 	fn has_session_keys(&self, session_keys: Bytes) -> Result<bool> {
@@ -258,6 +258,29 @@ fn should_rotate_keys() {
 	assert!(sr25519_public_keys.contains(&CryptoTypePublicPair(sr25519::CRYPTO_ID, session_keys.sr25519.to_raw_vec())));
 }
 
+#[test]
+fn should_rotate_keys_with_owner() {
+	use sp_runtime::traits::OpaqueKeys;
+
+	let setup = TestSetup::default();
+	let p = setup.author();
+
+	let owner = AccountKeyring::Alice.to_account_id().encode();
+	let generated = p.rotate_keys_with_owner(owner.clone().into()).expect("Rotates the keys");
+
+	let session_keys = SessionKeys::decode(&mut &generated.keys[..])
+		.expect("SessionKeys decode successfully");
+
+	let ed25519_public_keys = SyncCryptoStore::keys(&*setup.keystore, ED25519).unwrap();
+	let sr25519_public_keys = SyncCryptoStore::keys(&*setup.keystore, SR25519).unwrap();
+
+	assert!(ed25519_public_keys.contains(&CryptoTypePublicPair(ed25519::CRYPTO_ID, session_keys.ed25519.to_raw_vec())));
+	assert!(sr25519_public_keys.contains(&CryptoTypePublicPair(sr25519::CRYPTO_ID, session_keys.sr25519.to_raw_vec())));
+
+	assert!(session_keys.ownership_proof_is_valid(&owner, &generated.proof));
+	assert!(!session_keys.ownership_proof_is_valid(&AccountKeyring::Bob.to_account_id().encode(), &generated.proof));
+}
+
 #[test]
 fn test_has_session_keys() {
 	let setup = TestSetup::default();
@@ -16,25 +16,28 @@
 
 //! State API backend for full nodes.
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
 use std::ops::Range;
+use codec::{Encode, Decode};
 use futures::{future, StreamExt as _, TryStreamExt as _};
 use log::warn;
 use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId, manager::SubscriptionManager};
 use rpc::{Result as RpcResult, futures::{stream, Future, Sink, Stream, future::result}};
 
-use sc_rpc_api::state::ReadProof;
+use sc_rpc_api::state::{ReadProof, EventsByTopic, AvailableStates};
 use sc_client_api::backend::Backend;
 use sp_blockchain::{Result as ClientResult, Error as ClientError, HeaderMetadata, CachedHeaderMetadata, HeaderBackend};
 use sc_client_api::BlockchainEvents;
 use sp_core::{
 	Bytes, storage::{well_known_keys, StorageKey, StorageData, StorageChangeSet,
 	ChildInfo, ChildType, PrefixedStorageKey},
+	hashing::{twox_128, blake2_128},
 };
 use sp_version::RuntimeVersion;
 use sp_runtime::{
-	generic::BlockId, traits::{Block as BlockT, NumberFor, SaturatedConversion, CheckedSub},
+	generic::BlockId,
+	traits::{Block as BlockT, NumberFor, SaturatedConversion, Saturating, CheckedSub},
 };
 
 use sp_api::{Metadata, ProvideRuntimeApi, CallApiAt};
@@ -43,6 +46,9 @@ use super::{StateBackend, ChildStateBackend, error::{FutureResult, Error, Result
 use std::marker::PhantomData;
 use sc_client_api::{CallExecutor, StorageProvider, ExecutorProvider, ProofProvider};
 
+/// Maximum number of blocks that can be queried with a single `state_queryEventsByTopic`.
+const QUERY_EVENTS_MAX_BLOCKS: u32 = 1000;
+
 /// Ranges to query in state_queryStorage.
 struct QueryStorageRange<Block: BlockT> {
 	/// Hashes of all the blocks in the range.
@@ -401,6 +407,75 @@ impl<BE, Block, Client> StateBackend<Block, Client> for FullState<BE, Block, Cli
 		))
 	}
 
+	fn child_read_proof(
+		&self,
+		block: Option<Block::Hash>,
+		storage_key: PrefixedStorageKey,
+		keys: Vec<StorageKey>,
+	) -> FutureResult<ReadProof<Block::Hash>> {
+		Box::new(result(
+			self.block_or_best(block)
+				.and_then(|block| {
+					let child_info = match ChildType::from_prefixed_key(&storage_key) {
+						Some((ChildType::ParentKeyId, storage_key)) => ChildInfo::new_default(storage_key),
+						None => return Err(sp_blockchain::Error::InvalidChildStorageKey),
+					};
+					self.client
+						.read_child_proof_with_root(
+							&BlockId::Hash(block),
+							&child_info,
+							&mut keys.iter().map(|key| key.0.as_ref()),
+						)
+						.map(|proof| proof.iter_nodes().map(|node| node.into()).collect())
+						.map(|proof| ReadProof { at: block, proof })
+				})
+				.map_err(client_err),
+		))
+	}
+
+	fn query_events_by_topic(
+		&self,
+		topic: Block::Hash,
+		from: Block::Hash,
+		to: Option<Block::Hash>,
+	) -> FutureResult<Vec<EventsByTopic<Block::Hash>>> {
+		let call_fn = move || {
+			let to = self.block_or_best(to)
+				.map_err(|e| invalid_block::<Block>(from, to, e.to_string()))?;
+			let invalid_block_err = |e: ClientError| invalid_block::<Block>(from, Some(to), e.to_string());
+			let from_meta = self.client.header_metadata(from).map_err(invalid_block_err)?;
+			let to_meta = self.client.header_metadata(to).map_err(invalid_block_err)?;
+			if to_meta.number.saturating_sub(from_meta.number) >= QUERY_EVENTS_MAX_BLOCKS.into() {
+				return Err(invalid_block_range(
+					&from_meta,
+					&to_meta,
+					format!("range exceeds the maximum of {} blocks", QUERY_EVENTS_MAX_BLOCKS),
+				))
+			}
+
+			let range = self.split_query_storage_range(from, Some(to))?;
+			let topic_keys = [event_topic_key(&topic)];
+			range.hashes
+				.into_iter()
+				.filter_map(|block| {
+					events_by_topic_at::<BE, Block, _>(&*self.client, block, &topic_keys).transpose()
+				})
+				.collect::<Result<Vec<_>>>()
+		};
+		Box::new(result(call_fn()))
+	}
+
+	fn available_states(&self) -> FutureResult<AvailableStates<Block::Hash>> {
+		let states = self.client.available_states();
+		Box::new(result(Ok(AvailableStates {
+			window_start: states.window_start.map(|number| number.saturated_into()),
+			kept: states.kept
+				.into_iter()
+				.map(|(number, hash)| (number.saturated_into(), hash))
+				.collect(),
+		})))
+	}
+
 	fn subscribe_runtime_version(
 		&self,
 		_meta: crate::Metadata,
@@ -519,12 +594,65 @@ impl<BE, Block, Client> StateBackend<Block, Client> for FullState<BE, Block, Cli
 	) -> RpcResult<bool> {
 		Ok(self.subscriptions.cancel(id))
 	}
+
+	fn subscribe_events_by_topic(
+		&self,
+		_meta: crate::Metadata,
+		subscriber: Subscriber<EventsByTopic<Block::Hash>>,
+		topics: Vec<Block::Hash>,
+	) {
+		let topic_keys: Vec<_> = topics.iter().map(event_topic_key).collect();
+		let stream = match self.client.storage_changes_notification_stream(
+			Some(&topic_keys[..]),
+			None,
+		) {
+			Ok(stream) => stream,
+			Err(err) => {
+				let _ = subscriber.reject(client_err(err).into());
+				return;
+			},
+		};
+
+		self.subscriptions.add(subscriber, |sink| {
+			let client = self.client.clone();
+			let stream = stream
+				.filter_map(move |(block, changes)| {
+					// The topics are cleared at the beginning of each block. A removal
+					// therefore only means that the block did not emit a matching event.
+					let has_events = changes.iter()
+						.any(|(o_sk, _, v)| o_sk.is_none() && v.is_some());
+					let events = if has_events {
+						events_by_topic_at::<BE, Block, _>(&*client, block, &topic_keys)
+							.transpose()
+							.map(|events| events.map_err(rpc::Error::from))
+					} else {
+						None
+					};
+					future::ready(events.map(Ok::<_, ()>))
+				})
+				.compat();
+
+			sink
+				.sink_map_err(|e| warn!("Error sending notifications: {:?}", e))
+				.send_all(stream)
+				// we ignore the resulting Stream (if the first stream is over we are unsubscribed)
+				.map(|_| ())
+		});
+	}
+
+	fn unsubscribe_events_by_topic(
+		&self,
+		_meta: Option<crate::Metadata>,
+		id: SubscriptionId,
+	) -> RpcResult<bool> {
+		Ok(self.subscriptions.cancel(id))
+	}
 }
 
 impl<BE, Block, Client> ChildStateBackend<Block, Client> for FullState<BE, Block, Client> where
 	Block: BlockT + 'static,
 	BE: Backend<Block> + 'static,
-	Client: ExecutorProvider<Block> + StorageProvider<Block, BE> + HeaderBackend<Block>
+	Client: ExecutorProvider<Block> + StorageProvider<Block, BE> + ProofProvider<Block> + HeaderBackend<Block>
 		+ HeaderMetadata<Block, Error = sp_blockchain::Error> + BlockchainEvents<Block>
 		+ CallApiAt<Block, Error = sp_blockchain::Error> + ProvideRuntimeApi<Block>
 		+ Send + Sync + 'static,
@@ -552,6 +680,32 @@ impl<BE, Block, Client> ChildStateBackend<Block, Client> for FullState<BE, Block
 				.map_err(client_err)))
 	}
 
+	fn storage_keys_paged(
+		&self,
+		block: Option<Block::Hash>,
+		storage_key: PrefixedStorageKey,
+		prefix: Option<StorageKey>,
+		count: u32,
+		start_key: Option<StorageKey>,
+	) -> FutureResult<Vec<StorageKey>> {
+		Box::new(result(
+			self.block_or_best(block)
+				.and_then(|block| {
+					let child_info = match ChildType::from_prefixed_key(&storage_key) {
+						Some((ChildType::ParentKeyId, storage_key)) => ChildInfo::new_default(storage_key),
+						None => return Err(sp_blockchain::Error::InvalidChildStorageKey),
+					};
+					self.client.child_storage_keys_iter(
+						&BlockId::Hash(block),
+						child_info,
+						prefix.as_ref(),
+						start_key.as_ref(),
+					)
+				})
+				.map(|v| v.take(count as usize).collect())
+				.map_err(client_err)))
+	}
+
 	fn storage(
 		&self,
 		block: Option<Block::Hash>,
@@ -574,6 +728,28 @@ impl<BE, Block, Client> ChildStateBackend<Block, Client> for FullState<BE, Block
 				.map_err(client_err)))
 	}
 
+	fn storage_entries(
+		&self,
+		block: Option<Block::Hash>,
+		storage_key: PrefixedStorageKey,
+		keys: Vec<StorageKey>,
+	) -> FutureResult<Vec<Option<StorageData>>> {
+		Box::new(result(
+			self.block_or_best(block)
+				.and_then(|block| {
+					let child_info = match ChildType::from_prefixed_key(&storage_key) {
+						Some((ChildType::ParentKeyId, storage_key)) => ChildInfo::new_default(storage_key),
+						None => return Err(sp_blockchain::Error::InvalidChildStorageKey),
+					};
+					keys.iter().map(|key| self.client.child_storage(
+						&BlockId::Hash(block),
+						&child_info,
+						key,
+					)).collect::<ClientResult<Vec<_>>>()
+				})
+				.map_err(client_err)))
+	}
+
 	fn storage_hash(
 		&self,
 		block: Option<Block::Hash>,
@@ -595,6 +771,32 @@ impl<BE, Block, Client> ChildStateBackend<Block, Client> for FullState<BE, Block
 				})
 				.map_err(client_err)))
 	}
+
+	fn read_child_proof(
+		&self,
+		block: Option<Block::Hash>,
+		storage_key: PrefixedStorageKey,
+		keys: Vec<StorageKey>,
+	) -> FutureResult<ReadProof<Block::Hash>> {
+		Box::new(result(
+			self.block_or_best(block)
+				.and_then(|block| {
+					let child_info = match ChildType::from_prefixed_key(&storage_key) {
+						Some((ChildType::ParentKeyId, storage_key)) => ChildInfo::new_default(storage_key),
+						None => return Err(sp_blockchain::Error::InvalidChildStorageKey),
+					};
+					self.client
+						.read_child_proof(
+							&BlockId::Hash(block),
+							&child_info,
+							&mut keys.iter().map(|key| key.0.as_ref()),
+						)
+						.map(|proof| proof.iter_nodes().map(|node| node.into()).collect())
+						.map(|proof| ReadProof { at: block, proof })
+				})
+				.map_err(client_err),
+		))
+	}
 }
 
 /// Splits passed range into two subranges where:
@@ -618,6 +820,69 @@ pub(crate) fn split_range(size: usize, middle: Option<usize>) -> (Range<usize>,
 	(range1, range2)
 }
 
+/// Returns the storage key of `frame_system::Events`.
+///
+/// The node has no access to the storage layout of the runtime. This assumes that the
+/// runtime uses `frame_system` under the name `System`.
+pub(crate) fn events_key() -> StorageKey {
+	let mut key = twox_128(b"System").to_vec();
+	key.extend_from_slice(&twox_128(b"Events"));
+	StorageKey(key)
+}
+
+/// Returns the storage key of `frame_system::EventTopics` for the given `topic`.
+pub(crate) fn event_topic_key<Hash: Encode>(topic: &Hash) -> StorageKey {
+	let encoded_topic = topic.encode();
+	let mut key = twox_128(b"System").to_vec();
+	key.extend_from_slice(&twox_128(b"EventTopics"));
+	key.extend_from_slice(&blake2_128(&encoded_topic));
+	key.extend_from_slice(&encoded_topic);
+	StorageKey(key)
+}
+
+/// Returns the events of `block` that were deposited with any of the topics of `topic_keys`.
+///
+/// Returns `None` if no event of the block matches.
+fn events_by_topic_at<BE, Block, Client>(
+	client: &Client,
+	block: Block::Hash,
+	topic_keys: &[StorageKey],
+) -> Result<Option<EventsByTopic<Block::Hash>>>
+	where
+		BE: Backend<Block>,
+		Block: BlockT,
+		Client: StorageProvider<Block, BE> + HeaderBackend<Block>,
+{
+	let id = BlockId::Hash(block);
+	let number = client.number(block)
+		.map_err(client_err)?
+		.ok_or_else(|| client_err(ClientError::UnknownBlock(format!("{:?}", block))))?;
+
+	let mut indices = BTreeSet::new();
+	for key in topic_keys {
+		if let Some(data) = client.storage(&id, key).map_err(client_err)? {
+			let entries = <Vec<(NumberFor<Block>, u32)>>::decode(&mut &data.0[..])
+				.map_err(|e| client_err(ClientError::Storage(format!("Invalid event topics: {}", e))))?;
+			// Only the topics of the current block are kept in storage but it does not
+			// hurt to double check.
+			indices.extend(entries.into_iter().filter(|(n, _)| *n == number).map(|(_, i)| i));
+		}
+	}
+
+	if indices.is_empty() {
+		return Ok(None)
+	}
+
+	let events = client.storage(&id, &events_key())
+		.map_err(client_err)?
+		.unwrap_or_else(|| StorageData(Vec::new()));
+	Ok(Some(EventsByTopic {
+		block,
+		indices: indices.into_iter().collect(),
+		events,
+	}))
+}
+
 fn invalid_block_range<B: BlockT>(
 	from: &CachedHeaderMetadata<B>,
 	to: &CachedHeaderMetadata<B>,
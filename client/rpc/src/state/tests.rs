@@ -17,11 +17,12 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use super::*;
-use super::state_full::split_range;
+use super::state_full::{split_range, events_key, event_topic_key};
 use self::error::Error;
 
 use std::sync::Arc;
 use assert_matches::assert_matches;
+use codec::Encode;
 use futures01::stream::Stream;
 use sp_core::{storage::ChildInfo, ChangesTrieConfiguration};
 use sp_core::hash::H256;
@@ -33,7 +34,7 @@ use substrate_test_runtime_client::{
 	runtime,
 };
 use sc_rpc_api::DenyUnsafe;
-use sp_runtime::generic::BlockId;
+use sp_runtime::{generic::BlockId, traits::{BlakeTwo256, Header as _}};
 use crate::testing::TaskExecutor;
 use futures::{executor, compat::Future01CompatExt};
 
@@ -136,6 +137,71 @@ fn should_return_child_storage() {
 	);
 }
 
+#[test]
+fn should_page_and_prove_child_storage() {
+	let child_info = ChildInfo::new_default(STORAGE_KEY);
+	let client = Arc::new(substrate_test_runtime_client::TestClientBuilder::new()
+		.add_child_storage(&child_info, "key1", vec![1_u8])
+		.add_child_storage(&child_info, "key2", vec![2_u8])
+		.add_child_storage(&child_info, "key3", vec![3_u8])
+		.build());
+	let genesis_hash = client.genesis_hash();
+	let state_root = *client.header(&BlockId::Hash(genesis_hash)).unwrap().unwrap().state_root();
+	let (state, child) = new_full(
+		client,
+		SubscriptionManager::new(Arc::new(TaskExecutor)),
+		DenyUnsafe::No,
+	);
+	let child_key = prefixed_storage_key();
+	let key = |k: &[u8]| StorageKey(k.to_vec());
+
+	assert_eq!(
+		child.storage_keys_paged(child_key.clone(), Some(key(b"key")), 2, None, None).wait().unwrap(),
+		vec![key(b"key1"), key(b"key2")],
+	);
+	assert_eq!(
+		child.storage_keys_paged(child_key.clone(), Some(key(b"key")), 2, Some(key(b"key2")), None)
+			.wait().unwrap(),
+		vec![key(b"key3")],
+	);
+	assert_matches!(
+		child.storage_keys_paged(child_key.clone(), None, STORAGE_KEYS_PAGED_MAX_COUNT + 1, None, None)
+			.wait(),
+		Err(Error::InvalidCount { .. })
+	);
+
+	assert_eq!(
+		child.storage_entries(child_key.clone(), vec![key(b"key3"), key(b"missing")], None)
+			.wait().unwrap(),
+		vec![Some(StorageData(vec![3_u8])), None],
+	);
+
+	let proof = child.read_child_proof(child_key.clone(), vec![key(b"key1")], None).wait().unwrap();
+	assert_eq!(proof.at, genesis_hash);
+	let proof = sp_state_machine::StorageProof::new(proof.proof.into_iter().map(|node| node.0).collect());
+	assert_eq!(
+		sp_state_machine::read_child_proof_check::<BlakeTwo256, _>(
+			state_root,
+			proof,
+			&child_info,
+			&[b"key1"],
+		).unwrap().remove(&b"key1"[..]),
+		Some(Some(vec![1_u8])),
+	);
+
+	// the combined proof also proves the child trie root, even without any child key.
+	let proof = state.child_read_proof(child_key.clone(), Vec::new(), None).wait().unwrap();
+	let proof = sp_state_machine::StorageProof::new(proof.proof.into_iter().map(|node| node.0).collect());
+	assert_matches!(
+		sp_state_machine::read_proof_check::<BlakeTwo256, _>(
+			state_root,
+			proof,
+			&[child_key.as_slice()],
+		).unwrap().remove(child_key.as_slice()),
+		Some(Some(_))
+	);
+}
+
 #[test]
 fn should_call_contract() {
 	let client = Arc::new(substrate_test_runtime_client::new());
@@ -152,6 +218,21 @@ fn should_call_contract() {
 	)
 }
 
+#[test]
+fn should_return_available_states() {
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let (client, _child) = new_full(
+		client,
+		SubscriptionManager::new(Arc::new(TaskExecutor)),
+		DenyUnsafe::No,
+	);
+
+	assert_eq!(
+		client.available_states().wait().unwrap(),
+		AvailableStates { window_start: Some(0), kept: Vec::new() },
+	);
+}
+
 #[test]
 fn should_notify_about_storage_changes() {
 	let (subscriber, id, transport) = Subscriber::new_test("test");
@@ -446,6 +527,88 @@ fn should_query_storage() {
 	);
 }
 
+#[test]
+fn should_query_events_by_topic() {
+	let mut client = Arc::new(substrate_test_runtime_client::new());
+	let (api, _child) = new_full(
+		client.clone(),
+		SubscriptionManager::new(Arc::new(TaskExecutor)),
+		DenyUnsafe::No,
+	);
+	let genesis_hash = client.genesis_hash();
+	let topic = H256::repeat_byte(1);
+
+	// block 1 emits the records 0 and 2 with `topic`
+	let mut builder = client.new_block(Default::default()).unwrap();
+	builder.push_storage_change(
+		event_topic_key(&topic).0,
+		Some(vec![(1u64, 2u32), (1, 0)].encode()),
+	).unwrap();
+	builder.push_storage_change(events_key().0, Some(vec![42])).unwrap();
+	let block1 = builder.build().unwrap().block;
+	let block1_hash = block1.header.hash();
+	client.import(BlockOrigin::Own, block1).unwrap();
+
+	// block 2 does not touch the topics which still belong to block 1
+	let block2 = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	client.import(BlockOrigin::Own, block2).unwrap();
+
+	let result = api.query_events_by_topic(topic, genesis_hash, None);
+	assert_eq!(
+		result.wait().unwrap(),
+		vec![EventsByTopic {
+			block: block1_hash,
+			indices: vec![0, 2],
+			events: StorageData(vec![42]),
+		}],
+	);
+
+	let result = api.query_events_by_topic(H256::repeat_byte(2), genesis_hash, None);
+	assert_eq!(result.wait().unwrap(), vec![]);
+}
+
+#[test]
+fn should_notify_about_events_by_topic() {
+	let (subscriber, id, transport) = Subscriber::new_test("test");
+	let topic = H256::repeat_byte(1);
+
+	{
+		let mut client = Arc::new(substrate_test_runtime_client::new());
+		let (api, _child) = new_full(
+			client.clone(),
+			SubscriptionManager::new(Arc::new(TaskExecutor)),
+			DenyUnsafe::No,
+		);
+
+		api.subscribe_events_by_topic(Default::default(), subscriber, vec![topic]);
+
+		// assert id assigned
+		assert!(matches!(
+			executor::block_on(id.compat()),
+			Ok(Ok(SubscriptionId::String(_)))
+		));
+
+		// a block without matching events does not trigger a notification
+		let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+		client.import(BlockOrigin::Own, block).unwrap();
+
+		let mut builder = client.new_block(Default::default()).unwrap();
+		builder.push_storage_change(
+			event_topic_key(&topic).0,
+			Some(vec![(2u64, 0u32)].encode()),
+		).unwrap();
+		builder.push_storage_change(events_key().0, Some(vec![42])).unwrap();
+		let block = builder.build().unwrap().block;
+		client.import(BlockOrigin::Own, block).unwrap();
+	}
+
+	// assert notification sent to transport
+	let (notification, next) = executor::block_on(transport.into_future().compat()).unwrap();
+	assert!(notification.is_some());
+	// no more notifications on this channel
+	assert_eq!(executor::block_on(next.into_future().compat()).unwrap().0, None);
+}
+
 #[test]
 fn should_split_ranges() {
 	assert_eq!(split_range(1, None), (0..1, None));
@@ -41,7 +41,7 @@ use futures::{
 };
 use sc_keystore::LocalKeystore;
 use log::{info, warn};
-use sc_network::config::{Role, OnDemand};
+use sc_network::config::{Role, OnDemand, WarpSyncProvider};
 use sc_network::NetworkService;
 use sp_runtime::generic::BlockId;
 use sp_runtime::traits::{
@@ -320,12 +320,38 @@ pub fn new_full_parts<TBl, TRtApi, TExecDisp>(
 		.cloned()
 		.unwrap_or_default();
 
+	if let Some(snapshot) = &config.restore_snapshot {
+		let genesis_hash = crate::client::genesis::genesis_hash::<TBl>(chain_spec.as_storage_builder())?;
+		let restored = sc_client_db::snapshot::restore_snapshot::<TBl>(
+			snapshot,
+			&config.database,
+			genesis_hash,
+		)?;
+		match restored {
+			Some(manifest) => info!(
+				"Restored database snapshot {}: best #{} ({}), finalized #{} ({})",
+				snapshot.display(),
+				manifest.best_number,
+				manifest.best_hash,
+				manifest.finalized_number,
+				manifest.finalized_hash,
+			),
+			None => info!(
+				"Database already contains snapshot {}, skipping restore",
+				snapshot.display(),
+			),
+		}
+	}
+
 	let (client, backend) = {
 		let db_config = sc_client_db::DatabaseSettings {
 			state_cache_size: config.state_cache_size,
 			state_cache_child_ratio:
 			config.state_cache_child_ratio.map(|v| (v, 100)),
+			trie_cache_size: config.trie_cache_size,
 			pruning: config.pruning.clone(),
+			keep_blocks: config.keep_blocks,
+			transaction_storage: config.transaction_storage,
 			source: config.database.clone(),
 		};
 
@@ -383,7 +409,10 @@ pub fn new_light_parts<TBl, TRtApi, TExecDisp>(
 			state_cache_size: config.state_cache_size,
 			state_cache_child_ratio:
 				config.state_cache_child_ratio.map(|v| (v, 100)),
+			trie_cache_size: config.trie_cache_size,
 			pruning: config.pruning.clone(),
+			keep_blocks: config.keep_blocks,
+			transaction_storage: config.transaction_storage,
 			source: config.database.clone(),
 		};
 		sc_client_db::light::LightStorage::new(db_settings)?
@@ -856,6 +885,8 @@ pub struct BuildNetworkParams<'a, TBl: BlockT, TExPool, TImpQu, TCl> {
 	pub block_announce_validator_builder: Option<Box<
 		dyn FnOnce(Arc<TCl>) -> Box<dyn BlockAnnounceValidator<TBl> + Send> + Send
 	>>,
+	/// An optional warp sync provider, used to answer and verify warp sync proofs.
+	pub warp_sync: Option<Arc<dyn WarpSyncProvider<TBl>>>,
 }
 
 /// Build the network service, the network status sinks and an RPC sender.
@@ -880,7 +911,7 @@ pub fn build_network<TBl, TExPool, TImpQu, TCl>(
 {
 	let BuildNetworkParams {
 		config, client, transaction_pool, spawn_handle, import_queue, on_demand,
-		block_announce_validator_builder,
+		block_announce_validator_builder, warp_sync,
 	} = params;
 
 	let transaction_pool_adapter = Arc::new(TransactionPoolAdapter {
@@ -923,6 +954,7 @@ pub fn build_network<TBl, TExPool, TImpQu, TCl>(
 		import_queue: Box::new(import_queue),
 		protocol_id,
 		block_announce_validator,
+		warp_sync,
 		metrics_registry: config.prometheus_config.as_ref().map(|config| config.registry.clone())
 	};
 
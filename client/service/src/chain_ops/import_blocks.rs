@@ -168,10 +168,11 @@ fn import_block_to_queue<TBl, TImpQu>(
 			hash,
 			header: Some(header),
 			body: Some(extrinsics),
-			justification: signed_block.justification,
+			justifications: signed_block.justifications,
 			origin: None,
 			allow_missing_state: false,
 			import_existing: force,
+			state: None,
 		}
 	]);
 }
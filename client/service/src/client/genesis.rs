@@ -18,6 +18,8 @@
 
 //! Tool for creating the genesis block.
 
+use codec::Encode;
+use sp_runtime::BuildStorage;
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT, Hash as HashT, Zero};
 
 /// Create a genesis block, given the initial storage.
@@ -41,3 +43,24 @@ pub fn construct_genesis_block<
 		Default::default()
 	)
 }
+
+/// Compute the hash of the genesis block for the given initial storage, without writing it to
+/// a backend.
+pub fn genesis_hash<Block: BlockT>(
+	build_genesis_storage: &dyn BuildStorage,
+) -> sp_blockchain::Result<Block::Hash> {
+	let storage = build_genesis_storage.build_storage()
+		.map_err(sp_blockchain::Error::Storage)?;
+	let child_roots = storage.children_default.iter()
+		.filter(|(_, child_content)| !child_content.data.is_empty())
+		.map(|(_, child_content)| {
+			let state_root = <<<Block as BlockT>::Header as HeaderT>::Hashing as HashT>::trie_root(
+				child_content.data.clone().into_iter().collect(),
+			);
+			(child_content.child_info.prefixed_storage_key().into_inner(), state_root.encode())
+		});
+	let state_root = <<<Block as BlockT>::Header as HeaderT>::Hashing as HashT>::trie_root(
+		storage.top.into_iter().chain(child_roots).collect(),
+	);
+	Ok(construct_genesis_block::<Block>(state_root).header().hash())
+}
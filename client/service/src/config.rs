@@ -18,7 +18,10 @@
 
 //! Service configuration.
 
-pub use sc_client_db::{Database, PruningMode, DatabaseSettingsSrc as DatabaseConfig};
+pub use sc_client_db::{
+	Database, PruningMode, Constraints, ArchiveExceptions, BlocksPruning, TransactionStorageMode,
+	DatabaseSettingsSrc as DatabaseConfig,
+};
 pub use sc_network::Multiaddr;
 pub use sc_network::config::{ExtTransport, MultiaddrWithPeerId, NetworkConfiguration, Role, NodeKeyConfig};
 pub use sc_executor::WasmExecutionMethod;
@@ -58,8 +61,16 @@ pub struct Configuration {
 	pub state_cache_size: usize,
 	/// Size in percent of cache size dedicated to child tries
 	pub state_cache_child_ratio: Option<usize>,
-	/// Pruning settings.
+	/// Size of the trie node cache shared between blocks in Bytes
+	pub trie_cache_size: usize,
+	/// State pruning settings.
 	pub pruning: PruningMode,
+	/// Block body pruning settings.
+	pub keep_blocks: BlocksPruning,
+	/// Block body/Transaction storage scheme.
+	pub transaction_storage: TransactionStorageMode,
+	/// Snapshot to restore the database from before the node starts.
+	pub restore_snapshot: Option<PathBuf>,
 	/// Chain configuration.
 	pub chain_spec: Box<dyn ChainSpec>,
 	/// Wasm execution method.
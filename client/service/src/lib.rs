@@ -59,7 +59,11 @@ pub use self::builder::{
 	TFullCallExecutor, TLightCallExecutor, RpcExtensionBuilder, NoopRpcExtensionBuilder,
 };
 pub use config::{
-	BasePath, Configuration, DatabaseConfig, PruningMode, Role, RpcMethods, TaskExecutor, TaskType,
+	ArchiveExceptions, BasePath, BlocksPruning, Configuration, Constraints, DatabaseConfig,
+	PruningMode, Role, RpcMethods, TaskExecutor, TaskType, TransactionStorageMode,
+};
+pub use sc_client_db::{
+	DatabaseType, migration as db_migration, snapshot as db_snapshot, check as db_check,
 };
 pub use sc_chain_spec::{
 	ChainSpec, GenericChainSpec, Properties, RuntimeGenesis, Extension as ChainSpecExtension,
@@ -20,7 +20,7 @@ use std::{convert::TryFrom, time::SystemTime};
 
 use crate::{NetworkStatus, NetworkState, NetworkStatusSinks, config::Configuration};
 use futures_timer::Delay;
-use prometheus_endpoint::{register, Gauge, U64, Registry, PrometheusError, Opts, GaugeVec};
+use prometheus_endpoint::{register, Gauge, U64, Registry, PrometheusError, Opts, GaugeVec, CounterVec};
 use sc_telemetry::{telemetry, SUBSTRATE_INFO};
 use sp_api::ProvideRuntimeApi;
 use sp_runtime::traits::{NumberFor, Block, SaturatedConversion, UniqueSaturatedInto};
@@ -43,6 +43,8 @@ struct PrometheusMetrics {
 	database_cache: Gauge<U64>,
 	state_cache: Gauge<U64>,
 	state_db: GaugeVec<U64>,
+	trie_cache: Gauge<U64>,
+	trie_cache_lookups: CounterVec<U64>,
 }
 
 impl PrometheusMetrics {
@@ -100,6 +102,13 @@ impl PrometheusMetrics {
 				Opts::new("state_db_cache_bytes", "State DB cache in bytes"),
 				&["subtype"]
 			)?, registry)?,
+			trie_cache: register(Gauge::new(
+				"trie_cache_bytes", "Trie node cache size in bytes",
+			)?, registry)?,
+			trie_cache_lookups: register(CounterVec::new(
+				Opts::new("trie_cache_lookups", "Total trie node cache lookups"),
+				&["result"]
+			)?, registry)?,
 		})
 	}
 }
@@ -287,6 +296,13 @@ impl MetricsService {
 				metrics.state_db.with_label_values(&["pinned"]).set(
 					info.memory.state_db.pinned.as_bytes() as u64,
 				);
+
+				metrics.trie_cache.set(info.memory.trie_cache.as_bytes() as u64);
+				// The backend reports running totals, the counters advance by the difference.
+				for (result, total) in &[("hit", info.io.trie_cache_hits), ("miss", info.io.trie_cache_misses)] {
+					let counter = metrics.trie_cache_lookups.with_label_values(&[result]);
+					counter.inc_by(total.saturating_sub(counter.get()));
+				}
 			}
 		}
 
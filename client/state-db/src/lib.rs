@@ -39,7 +39,7 @@ mod test;
 use std::fmt;
 use parking_lot::RwLock;
 use codec::Codec;
-use std::collections::{HashMap, hash_map::Entry};
+use std::collections::{BTreeSet, HashMap, hash_map::Entry};
 use noncanonical::NonCanonicalOverlay;
 use pruning::RefWindow;
 use log::trace;
@@ -141,6 +141,33 @@ pub struct Constraints {
 	pub max_blocks: Option<u32>,
 	/// Maximum memory in the pruning overlay.
 	pub max_mem: Option<usize>,
+	/// Canonical states that are kept after they leave the pruning window.
+	pub keep: ArchiveExceptions,
+}
+
+/// Canonical states that are exempt from pruning.
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct ArchiveExceptions {
+	/// Keep the state of every block with a number divisible by this.
+	pub every: Option<u64>,
+	/// Keep the states of these blocks.
+	pub blocks: BTreeSet<u64>,
+	/// Keep the states of blocks marked as checkpoints by the runtime.
+	pub checkpoints: bool,
+}
+
+impl ArchiveExceptions {
+	/// Should the state of the given canonical block be kept?
+	pub fn keeps(&self, number: u64, checkpoint: bool) -> bool {
+		self.every.map_or(false, |every| every != 0 && number % every == 0) ||
+			self.blocks.contains(&number) ||
+			(self.checkpoints && checkpoint)
+	}
+
+	/// Are there no exceptions?
+	pub fn is_empty(&self) -> bool {
+		self.every.is_none() && self.blocks.is_empty() && !self.checkpoints
+	}
 }
 
 /// Pruning mode.
@@ -160,6 +187,7 @@ impl PruningMode {
 		PruningMode::Constrained(Constraints {
 			max_blocks: Some(n),
 			max_mem: None,
+			keep: Default::default(),
 		})
 	}
 
@@ -274,24 +302,52 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDbSync<Block
 		}
 	}
 
-	fn canonicalize_block<E: fmt::Debug>(
+	fn import_state<E: fmt::Debug>(
 		&mut self,
 		hash: &BlockHash,
+		number: u64,
+		parent_hash: &BlockHash,
+		changeset: ChangeSet<Key>,
 	) -> Result<CommitSet<Key>, Error<E>> {
+		if self.mode == PruningMode::ArchiveAll {
+			return self.insert_block(hash, number, parent_hash, changeset)
+		}
+		let mut commit = self.non_canonical.reset(parent_hash, number)?;
+		if let Some(ref mut pruning) = self.pruning {
+			pruning.reset(number, &mut commit);
+		}
+		let inserted = self.insert_block(hash, number, parent_hash, changeset)?;
+		commit.data.inserted.extend(inserted.data.inserted);
+		commit.data.deleted.extend(inserted.data.deleted);
+		commit.meta.inserted.extend(inserted.meta.inserted);
+		commit.meta.deleted.extend(inserted.meta.deleted);
+		Ok(commit)
+	}
+
+	fn canonicalize_block<D: NodeDb>(
+		&mut self,
+		hash: &BlockHash,
+		checkpoint: bool,
+		db: &D,
+	) -> Result<CommitSet<Key>, Error<D::Error>>
+		where Key: AsRef<D::Key>
+	{
 		let mut commit = CommitSet::default();
 		if self.mode == PruningMode::ArchiveAll {
 			return Ok(commit)
 		}
-		match self.non_canonical.canonicalize(&hash, &mut commit) {
-			Ok(()) => {
+		let number = match self.non_canonical.canonicalize(&hash, &mut commit) {
+			Ok(number) => {
 				if self.mode == PruningMode::ArchiveCanonical {
 					commit.data.deleted.clear();
 				}
+				number
 			}
 			Err(e) => return Err(e),
 		};
-		if let Some(ref mut pruning) = self.pruning {
-			pruning.note_canonical(&hash, &mut commit);
+		if let (Some(ref mut pruning), PruningMode::Constrained(constraints)) = (&mut self.pruning, &self.mode) {
+			let keep = if constraints.keep.keeps(number, checkpoint) { Some(number) } else { None };
+			pruning.note_canonical(&hash, keep, &mut commit, db)?;
 		}
 		self.prune(&mut commit);
 		Ok(commit)
@@ -312,7 +368,8 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDbSync<Block
 						.as_ref()
 						.map_or(
 							false,
-							|pruning| number < pruning.pending() || !pruning.have_block(hash),
+							|pruning| (number < pruning.pending() || !pruning.have_block(hash)) &&
+								!pruning.is_kept(hash),
 						)
 				}
 			}
@@ -358,7 +415,7 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDbSync<Block
 			PruningMode::ArchiveAll => Ok(()),
 			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) => {
 				if self.non_canonical.have_block(hash) ||
-					self.pruning.as_ref().map_or(false, |pruning| pruning.have_block(hash))
+					self.pruning.as_ref().map_or(false, |pruning| pruning.have_block(hash) || pruning.is_kept(hash))
 				{
 					let refs = self.pinned.entry(hash.clone()).or_default();
 					if *refs == 0 {
@@ -402,6 +459,18 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDbSync<Block
 		db.get(key.as_ref()).map_err(|e| Error::Db(e))
 	}
 
+	fn kept_states(&self) -> Vec<(BlockHash, u64)> {
+		self.pruning.as_ref().map_or(Vec::new(), |pruning| pruning.kept_states().to_vec())
+	}
+
+	fn pruning_window_start(&self) -> Option<u64> {
+		self.pruning.as_ref().map(|pruning| pruning.pending())
+	}
+
+	fn non_canonical_blocks(&self) -> Vec<(BlockHash, u64)> {
+		self.non_canonical.blocks()
+	}
+
 	fn apply_pending(&mut self) {
 		self.non_canonical.apply_pending();
 		if let Some(pruning) = &mut self.pruning {
@@ -463,12 +532,33 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDb<BlockHash
 		self.db.write().insert_block(hash, number, parent_hash, changeset)
 	}
 
-	/// Finalize a previously inserted block.
-	pub fn canonicalize_block<E: fmt::Debug>(
+	/// Add a new non-canonical block with the complete state in `changeset`, which does not
+	/// depend on the state of the parent. Earlier states are no longer tracked, so the
+	/// non-canonical overlay must be empty and `number` must be above the last canonical block.
+	/// The nodes of the earlier states are left in the database.
+	pub fn import_state<E: fmt::Debug>(
 		&self,
 		hash: &BlockHash,
+		number: u64,
+		parent_hash: &BlockHash,
+		changeset: ChangeSet<Key>,
 	) -> Result<CommitSet<Key>, Error<E>> {
-		self.db.write().canonicalize_block(hash)
+		self.db.write().import_state(hash, number, parent_hash, changeset)
+	}
+
+	/// Finalize a previously inserted block.
+	///
+	/// `checkpoint` tells whether the runtime marked the block as a checkpoint. `db` is used
+	/// to look up state nodes when the state of the block or an earlier one is kept.
+	pub fn canonicalize_block<D: NodeDb>(
+		&self,
+		hash: &BlockHash,
+		checkpoint: bool,
+		db: &D,
+	) -> Result<CommitSet<Key>, Error<D::Error>>
+		where Key: AsRef<D::Key>
+	{
+		self.db.write().canonicalize_block(hash, checkpoint, db)
 	}
 
 	/// Prevents pruning of specified block and its descendants.
@@ -508,6 +598,23 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDb<BlockHash
 		return self.db.read().is_pruned(hash, number)
 	}
 
+	/// Returns hashes and numbers of the canonical blocks whose state is kept
+	/// outside of the pruning window.
+	pub fn kept_states(&self) -> Vec<(BlockHash, u64)> {
+		self.db.read().kept_states()
+	}
+
+	/// Returns the number of the first canonical block in the pruning window.
+	/// `None` if nothing is pruned.
+	pub fn pruning_window_start(&self) -> Option<u64> {
+		self.db.read().pruning_window_start()
+	}
+
+	/// Returns hashes and numbers of the blocks that have not been canonicalized yet.
+	pub fn non_canonical_blocks(&self) -> Vec<(BlockHash, u64)> {
+		self.db.read().non_canonical_blocks()
+	}
+
 	/// Apply all pending changes
 	pub fn apply_pending(&self) {
 		self.db.write().apply_pending();
@@ -524,11 +631,26 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDb<BlockHash
 	}
 }
 
+/// Reads the canonicalization and pruning journals of a database that does not count references
+/// and returns them rewritten for a database that does, as `(meta key, value)` pairs.
+///
+/// Every state node key in the journals is passed through `map_key`. Pruning journal records no
+/// longer track insertions, so a node that is inserted again within the pruning window is removed
+/// from the record that would have deleted it.
+pub fn ref_counted_journals<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+	map_key: impl Fn(&Key) -> Key,
+) -> Result<Vec<(Vec<u8>, DBValue)>, Error<D::Error>> {
+	let mut journals = noncanonical::ref_counted_journals::<BlockHash, Key, _>(db, &map_key)?;
+	journals.extend(pruning::ref_counted_journals::<BlockHash, Key, _>(db, &map_key)?);
+	Ok(journals)
+}
+
 #[cfg(test)]
 mod tests {
 	use std::io;
 	use sp_core::H256;
-	use crate::{StateDb, PruningMode, Constraints};
+	use crate::{StateDb, PruningMode, Constraints, ArchiveExceptions};
 	use crate::test::{make_db, make_changeset, TestDb};
 
 	fn make_test_db(settings: PruningMode) -> (TestDb, StateDb<H256, H256>) {
@@ -576,7 +698,7 @@ mod tests {
 				.unwrap(),
 		);
 		state_db.apply_pending();
-		db.commit(&state_db.canonicalize_block::<io::Error>(&H256::from_low_u64_be(1)).unwrap());
+		db.commit(&state_db.canonicalize_block(&H256::from_low_u64_be(1), false, &db).unwrap());
 		state_db.apply_pending();
 		db.commit(
 			&state_db
@@ -589,9 +711,9 @@ mod tests {
 				.unwrap(),
 		);
 		state_db.apply_pending();
-		db.commit(&state_db.canonicalize_block::<io::Error>(&H256::from_low_u64_be(21)).unwrap());
+		db.commit(&state_db.canonicalize_block(&H256::from_low_u64_be(21), false, &db).unwrap());
 		state_db.apply_pending();
-		db.commit(&state_db.canonicalize_block::<io::Error>(&H256::from_low_u64_be(3)).unwrap());
+		db.commit(&state_db.canonicalize_block(&H256::from_low_u64_be(3), false, &db).unwrap());
 		state_db.apply_pending();
 
 		(db, state_db)
@@ -610,11 +732,18 @@ mod tests {
 		assert!(db.data_eq(&make_db(&[1, 21, 3, 91, 921, 922, 93, 94])));
 	}
 
+	#[test]
+	fn lists_non_canonical_blocks() {
+		let (_, sdb) = make_test_db(PruningMode::ArchiveCanonical);
+		assert_eq!(sdb.non_canonical_blocks(), vec![(H256::from_low_u64_be(4), 4)]);
+	}
+
 	#[test]
 	fn prune_window_0() {
 		let (db, _) = make_test_db(PruningMode::Constrained(Constraints {
 			max_blocks: Some(0),
 			max_mem: None,
+			keep: Default::default(),
 		}));
 		assert!(db.data_eq(&make_db(&[21, 3, 922, 94])));
 	}
@@ -624,6 +753,7 @@ mod tests {
 		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
 			max_blocks: Some(1),
 			max_mem: None,
+			keep: Default::default(),
 		}));
 		assert!(sdb.is_pruned(&H256::from_low_u64_be(0), 0));
 		assert!(sdb.is_pruned(&H256::from_low_u64_be(1), 1));
@@ -637,6 +767,7 @@ mod tests {
 		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
 			max_blocks: Some(2),
 			max_mem: None,
+			keep: Default::default(),
 		}));
 		assert!(sdb.is_pruned(&H256::from_low_u64_be(0), 0));
 		assert!(sdb.is_pruned(&H256::from_low_u64_be(1), 1));
@@ -645,6 +776,34 @@ mod tests {
 		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
 	}
 
+	#[test]
+	fn prune_window_keeps_exceptions() {
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(0),
+			max_mem: None,
+			keep: ArchiveExceptions { blocks: vec![1].into_iter().collect(), ..Default::default() },
+		}));
+		assert!(!sdb.is_pruned(&H256::from_low_u64_be(1), 1));
+		assert!(sdb.is_pruned(&H256::from_low_u64_be(21), 2));
+		assert_eq!(sdb.kept_states(), vec![(H256::from_low_u64_be(1), 1)]);
+		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
+	}
+
+	#[test]
+	fn archive_exceptions_match_blocks() {
+		let keep = ArchiveExceptions {
+			every: Some(10),
+			blocks: vec![15].into_iter().collect(),
+			checkpoints: true,
+		};
+		assert!(keep.keeps(0, false));
+		assert!(keep.keeps(20, false));
+		assert!(keep.keeps(15, false));
+		assert!(keep.keeps(17, true));
+		assert!(!keep.keeps(17, false));
+		assert!(!ArchiveExceptions { every: Some(0), ..Default::default() }.keeps(10, true));
+	}
+
 	#[test]
 	fn detects_incompatible_mode() {
 		let mut db = make_db(&[]);
@@ -659,8 +818,122 @@ mod tests {
 			)
 			.unwrap(),
 		);
-		let new_mode = PruningMode::Constrained(Constraints { max_blocks: Some(2), max_mem: None });
+		let new_mode = PruningMode::Constrained(Constraints { max_blocks: Some(2), max_mem: None, keep: Default::default() });
 		let state_db: Result<StateDb<H256, H256>, _> = StateDb::new(new_mode, false, &db);
 		assert!(state_db.is_err());
 	}
+
+	#[test]
+	fn import_state_requires_empty_overlay() {
+		let (_, sdb) = make_test_db(PruningMode::ArchiveCanonical);
+		assert!(sdb.import_state::<io::Error>(
+			&H256::from_low_u64_be(10),
+			10,
+			&H256::from_low_u64_be(9),
+			make_changeset(&[10], &[]),
+		).is_err());
+	}
+
+	#[test]
+	fn import_state_restarts_window() {
+		let mode = PruningMode::Constrained(Constraints {
+			max_blocks: Some(1),
+			max_mem: None,
+			keep: Default::default(),
+		});
+		let (mut db, sdb) = make_test_db(mode.clone());
+		db.commit(&sdb.canonicalize_block(&H256::from_low_u64_be(4), false, &db).unwrap());
+		sdb.apply_pending();
+
+		db.commit(
+			&sdb.import_state::<io::Error>(
+				&H256::from_low_u64_be(10),
+				10,
+				&H256::from_low_u64_be(9),
+				make_changeset(&[10, 100], &[]),
+			)
+			.unwrap(),
+		);
+		sdb.apply_pending();
+		assert_eq!(sdb.best_canonical(), Some(9));
+		assert!(!sdb.is_pruned(&H256::from_low_u64_be(10), 10));
+		assert!(sdb.is_pruned(&H256::from_low_u64_be(4), 4));
+		assert!(sdb.is_pruned(&H256::from_low_u64_be(9), 9));
+
+		db.commit(
+			&sdb.insert_block::<io::Error>(
+				&H256::from_low_u64_be(11),
+				11,
+				&H256::from_low_u64_be(10),
+				make_changeset(&[11], &[100]),
+			)
+			.unwrap(),
+		);
+		sdb.apply_pending();
+		db.commit(&sdb.canonicalize_block(&H256::from_low_u64_be(10), false, &db).unwrap());
+		sdb.apply_pending();
+		db.commit(
+			&sdb.insert_block::<io::Error>(
+				&H256::from_low_u64_be(12),
+				12,
+				&H256::from_low_u64_be(11),
+				make_changeset(&[12], &[]),
+			)
+			.unwrap(),
+		);
+		sdb.apply_pending();
+		db.commit(&sdb.canonicalize_block(&H256::from_low_u64_be(11), false, &db).unwrap());
+		sdb.apply_pending();
+		db.commit(&sdb.canonicalize_block(&H256::from_low_u64_be(12), false, &db).unwrap());
+		sdb.apply_pending();
+		assert!(sdb.is_pruned(&H256::from_low_u64_be(11), 11));
+		assert!(!sdb.is_pruned(&H256::from_low_u64_be(12), 12));
+		// the nodes deleted by the dropped blocks are left in place
+		assert!(db.data_eq(&make_db(&[10, 11, 12, 21, 3, 4, 922, 94])));
+
+		let restored: StateDb<H256, H256> = StateDb::new(mode, false, &db).unwrap();
+		assert_eq!(restored.best_canonical(), Some(12));
+		assert_eq!(restored.pruning_window_start(), Some(12));
+	}
+
+	#[test]
+	fn import_state_can_be_reverted() {
+		let mode = PruningMode::Constrained(Constraints {
+			max_blocks: Some(1),
+			max_mem: None,
+			keep: Default::default(),
+		});
+		let (mut db, sdb) = make_test_db(mode);
+		db.commit(&sdb.canonicalize_block(&H256::from_low_u64_be(4), false, &db).unwrap());
+		sdb.apply_pending();
+		let window_start = sdb.pruning_window_start();
+
+		sdb.import_state::<io::Error>(
+			&H256::from_low_u64_be(10),
+			10,
+			&H256::from_low_u64_be(9),
+			make_changeset(&[10, 100], &[]),
+		).unwrap();
+		sdb.revert_pending();
+		assert_eq!(sdb.best_canonical(), Some(4));
+		assert_eq!(sdb.pruning_window_start(), window_start);
+		assert!(sdb.non_canonical_blocks().is_empty());
+		assert!(!sdb.is_pruned(&H256::from_low_u64_be(4), 4));
+
+		// the chain continues from the last canonical block
+		db.commit(
+			&sdb.insert_block::<io::Error>(
+				&H256::from_low_u64_be(5),
+				5,
+				&H256::from_low_u64_be(4),
+				make_changeset(&[5], &[]),
+			)
+			.unwrap(),
+		);
+		sdb.apply_pending();
+		db.commit(&sdb.canonicalize_block(&H256::from_low_u64_be(5), false, &db).unwrap());
+		sdb.apply_pending();
+		assert_eq!(sdb.best_canonical(), Some(5));
+		assert!(sdb.is_pruned(&H256::from_low_u64_be(4), 4));
+	}
 }
@@ -39,6 +39,8 @@ pub struct NonCanonicalOverlay<BlockHash: Hash, Key: Hash> {
 	parents: HashMap<BlockHash, BlockHash>,
 	pending_canonicalizations: Vec<BlockHash>,
 	pending_insertions: Vec<BlockHash>,
+	/// `last_canonicalized` before a pending `reset`.
+	pending_reset: Option<Option<(BlockHash, u64)>>,
 	values: HashMap<Key, (u32, DBValue)>, //ref counted
 	//would be deleted but kept around because block is pinned, ref counted.
 	pinned: HashMap<BlockHash, u32>,
@@ -57,6 +59,46 @@ fn to_journal_key(block: u64, index: u64) -> Vec<u8> {
 	to_meta_key(NON_CANONICAL_JOURNAL, &(block, index))
 }
 
+/// Rewrites the journal records of all non-canonical blocks, passing every node key through
+/// `map_key`. See `crate::ref_counted_journals`.
+pub(crate) fn ref_counted_journals<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+	map_key: &dyn Fn(&Key) -> Key,
+) -> Result<Vec<(Vec<u8>, DBValue)>, Error<D::Error>> {
+	let last_canonicalized = db.get_meta(&to_meta_key(LAST_CANONICAL, &()))
+		.map_err(|e| Error::Db(e))?;
+	let mut block = match last_canonicalized {
+		Some(buffer) => <(BlockHash, u64)>::decode(&mut buffer.as_slice())?.1 + 1,
+		None => return Ok(Vec::new()),
+	};
+	let mut journals = Vec::new();
+	loop {
+		let mut index: u64 = 0;
+		loop {
+			let journal_key = to_journal_key(block, index);
+			match db.get_meta(&journal_key).map_err(|e| Error::Db(e))? {
+				Some(record) => {
+					let record: JournalRecord<BlockHash, Key> = Decode::decode(&mut record.as_slice())?;
+					let record = JournalRecord {
+						hash: record.hash,
+						parent_hash: record.parent_hash,
+						inserted: record.inserted.into_iter().map(|(k, v)| (map_key(&k), v)).collect(),
+						deleted: record.deleted.iter().map(|k| map_key(k)).collect(),
+					};
+					journals.push((journal_key, record.encode()));
+					index += 1;
+				},
+				None => break,
+			}
+		}
+		if index == 0 {
+			break;
+		}
+		block += 1;
+	}
+	Ok(journals)
+}
+
 #[cfg_attr(test, derive(PartialEq, Debug))]
 #[derive(parity_util_mem_derive::MallocSizeOf)]
 struct BlockOverlay<BlockHash: Hash, Key: Hash> {
@@ -200,6 +242,7 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 			parents,
 			pending_canonicalizations: Default::default(),
 			pending_insertions: Default::default(),
+			pending_reset: None,
 			pinned: Default::default(),
 			pinned_insertions: Default::default(),
 			values: values,
@@ -266,6 +309,24 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 		Ok(commit)
 	}
 
+	/// Continue the overlay from block `number` with the given parent, which is assumed to be
+	/// canonicalized. Used when the state of the block is imported as a whole. Expects the
+	/// overlay to be empty. The reset is pending until `apply_pending` is called.
+	pub fn reset<E: fmt::Debug>(&mut self, parent_hash: &BlockHash, number: u64) -> Result<CommitSet<Key>, Error<E>> {
+		if !self.levels.is_empty() || !self.pending_canonicalizations.is_empty() || number == 0 ||
+			self.last_canonicalized.as_ref().map_or(false, |&(_, n)| number <= n)
+		{
+			return Err(Error::InvalidBlockNumber);
+		}
+		let mut commit = CommitSet::default();
+		let last_canonicalized = (parent_hash.clone(), number - 1);
+		commit.meta.inserted.push((to_meta_key(LAST_CANONICAL, &()), last_canonicalized.encode()));
+		trace!(target: "state-db", "Reset overlay to block {}", number);
+		let previous = std::mem::replace(&mut self.last_canonicalized, Some(last_canonicalized));
+		self.pending_reset.get_or_insert(previous);
+		Ok(commit)
+	}
+
 	fn discard_journals(
 		&self,
 		level_index: usize,
@@ -311,11 +372,12 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 
 	/// Select a top-level root and canonicalized it. Discards all sibling subtrees and the root.
 	/// Returns a set of changes that need to be added to the DB.
+	/// Returns the number of the canonicalized block.
 	pub fn canonicalize<E: fmt::Debug>(
 		&mut self,
 		hash: &BlockHash,
 		commit: &mut CommitSet<Key>,
-	) -> Result<(), Error<E>> {
+	) -> Result<u64, Error<E>> {
 		trace!(target: "state-db", "Canonicalizing {:?}", hash);
 		let level = self.levels.get(self.pending_canonicalizations.len()).ok_or_else(|| Error::InvalidBlock)?;
 		let index = level
@@ -349,7 +411,7 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 		commit.meta.inserted.push((to_meta_key(LAST_CANONICAL, &()), canonicalized.encode()));
 		trace!(target: "state-db", "Discarding {} records", commit.meta.deleted.len());
 		self.pending_canonicalizations.push(hash.clone());
-		Ok(())
+		Ok(canonicalized.1)
 	}
 
 	fn apply_canonicalizations(&mut self) {
@@ -406,6 +468,14 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 		None
 	}
 
+	/// Hashes and numbers of all non-canonical blocks.
+	pub fn blocks(&self) -> Vec<(BlockHash, u64)> {
+		let front_block_number = self.front_block_number();
+		self.levels.iter().enumerate().flat_map(|(index, level)| {
+			level.iter().map(move |overlay| (overlay.hash.clone(), front_block_number + index as u64))
+		}).collect()
+	}
+
 	/// Check if the block is in the canonicalization queue.
 	pub fn have_block(&self, hash: &BlockHash) -> bool {
 		(self.parents.contains_key(hash) || self.pending_insertions.contains(hash))
@@ -448,12 +518,16 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 	pub fn apply_pending(&mut self) {
 		self.apply_canonicalizations();
 		self.pending_insertions.clear();
+		self.pending_reset = None;
 	}
 
 	/// Revert all pending changes
 	pub fn revert_pending(&mut self) {
 		self.pending_canonicalizations.clear();
 		self.revert_insertions();
+		if let Some(last_canonicalized) = self.pending_reset.take() {
+			self.last_canonicalized = last_canonicalized;
+		}
 	}
 
 	/// Pin state values in memory
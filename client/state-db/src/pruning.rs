@@ -23,14 +23,23 @@
 //! If a node is re-inserted into the window it gets removed from
 //! the death list.
 //! The changes are journaled in the DB.
+//!
+//! Canonical states can be kept after they leave the window. A node deleted after the most
+//! recently kept state belongs to that state, unless it was created afterwards. The window
+//! tracks the nodes created since the last kept state and skips the deletion of all other
+//! nodes. Tracking restarts at each kept state, as the nodes of older kept states that are
+//! still alive belong to the newer one as well.
 
 use std::collections::{HashMap, HashSet, VecDeque};
 use codec::{Encode, Decode};
-use crate::{CommitSet, Error, MetaDb, to_meta_key, Hash};
+use crate::{CommitSet, DBValue, Error, MetaDb, NodeDb, to_meta_key, Hash};
 use log::{trace, warn};
 
 const LAST_PRUNED: &[u8] = b"last_pruned";
 const PRUNING_JOURNAL: &[u8] = b"pruning_journal";
+const KEPT_STATES: &[u8] = b"kept_states";
+const LAST_KEPT: &[u8] = b"last_kept";
+const CREATED_JOURNAL: &[u8] = b"created_journal";
 
 /// See module documentation.
 #[derive(parity_util_mem_derive::MallocSizeOf)]
@@ -51,6 +60,34 @@ pub struct RefWindow<BlockHash: Hash, Key: Hash> {
 	/// Setting this to false requires backend that supports reference
 	/// counting.
 	count_insertions: bool,
+	/// Hashes and numbers of the blocks whose state is kept, in canonicalization order.
+	kept_states: Vec<(BlockHash, u64)>,
+	/// Number of entries of `kept_states` added after
+	/// last call `apply_pending` or `revert_pending`
+	pending_kept_states: usize,
+	/// Journal index of the block with the most recently kept state.
+	last_kept: Option<u64>,
+	/// Set by a pending canonicalization that keeps its state. Invalidates `created`.
+	pending_last_kept: Option<u64>,
+	/// Nodes created after the most recently kept state, with the number of
+	/// references that may still be released.
+	created: HashMap<Key, u32>,
+	/// Entries of `created` changed by pending canonicalizations.
+	pending_created: HashMap<Key, u32>,
+	/// Keys inserted by pending canonicalizations. These are not in the database yet.
+	pending_inserted: HashSet<Key>,
+	/// The window dropped by a pending `reset`.
+	pending_reset: Option<DroppedWindow<BlockHash, Key>>,
+}
+
+/// The parts of a `RefWindow` that are replaced by `reset`.
+#[derive(parity_util_mem_derive::MallocSizeOf)]
+struct DroppedWindow<BlockHash: Hash, Key: Hash> {
+	death_rows: VecDeque<DeathRow<BlockHash, Key>>,
+	death_index: HashMap<Key, u64>,
+	pending_number: u64,
+	last_kept: Option<u64>,
+	created: HashMap<Key, u32>,
 }
 
 #[derive(Debug, PartialEq, Eq, parity_util_mem_derive::MallocSizeOf)]
@@ -67,10 +104,92 @@ struct JournalRecord<BlockHash: Hash, Key: Hash> {
 	deleted: Vec<Key>,
 }
 
+/// Changes of the created nodes by a block canonicalized after the most recently kept state.
+#[derive(Encode, Decode)]
+struct CreatedRecord<Key: Hash> {
+	created: Vec<Key>,
+	released: Vec<Key>,
+}
+
 fn to_journal_key(block: u64) -> Vec<u8> {
 	to_meta_key(PRUNING_JOURNAL, &block)
 }
 
+fn to_created_journal_key(block: u64) -> Vec<u8> {
+	to_meta_key(CREATED_JOURNAL, &block)
+}
+
+/// Rewrites the journal records of all blocks in the pruning window for a database that counts
+/// references, passing every node key through `map_key`. See `crate::ref_counted_journals`.
+pub(crate) fn ref_counted_journals<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+	map_key: &dyn Fn(&Key) -> Key,
+) -> Result<Vec<(Vec<u8>, DBValue)>, Error<D::Error>> {
+	let last_pruned = db.get_meta(&to_meta_key(LAST_PRUNED, &()))
+		.map_err(|e| Error::Db(e))?;
+	let mut block: u64 = match last_pruned {
+		Some(buffer) => u64::decode(&mut buffer.as_slice())? + 1,
+		None => 0,
+	};
+	let mut rows: Vec<(Vec<u8>, BlockHash, HashSet<Key>)> = Vec::new();
+	let mut death_index: HashMap<Key, usize> = HashMap::new();
+	loop {
+		let journal_key = to_journal_key(block);
+		match db.get_meta(&journal_key).map_err(|e| Error::Db(e))? {
+			Some(record) => {
+				let record: JournalRecord<BlockHash, Key> = Decode::decode(&mut record.as_slice())?;
+				for k in record.inserted {
+					if let Some(row) = death_index.remove(&k) {
+						rows[row].2.remove(&k);
+					}
+				}
+				// A node is stored once, so it must only be released by the first row deleting it.
+				let row = rows.len();
+				let mut deleted = HashSet::new();
+				for k in record.deleted {
+					if !death_index.contains_key(&k) {
+						death_index.insert(k.clone(), row);
+						deleted.insert(k);
+					}
+				}
+				rows.push((journal_key, record.hash, deleted));
+			},
+			None => break,
+		}
+		block += 1;
+	}
+	let mut journals: Vec<_> = rows.into_iter().map(|(journal_key, hash, deleted)| {
+		let record = JournalRecord::<BlockHash, Key> {
+			hash,
+			inserted: Vec::new(),
+			deleted: deleted.iter().map(|k| map_key(k)).collect(),
+		};
+		(journal_key, record.encode())
+	}).collect();
+
+	let last_kept = db.get_meta(&to_meta_key(LAST_KEPT, &()))
+		.map_err(|e| Error::Db(e))?;
+	if let Some(buffer) = last_kept {
+		let mut block = u64::decode(&mut buffer.as_slice())? + 1;
+		loop {
+			let journal_key = to_created_journal_key(block);
+			match db.get_meta(&journal_key).map_err(|e| Error::Db(e))? {
+				Some(record) => {
+					let record: CreatedRecord<Key> = Decode::decode(&mut record.as_slice())?;
+					let record = CreatedRecord {
+						created: record.created.iter().map(|k| map_key(k)).collect(),
+						released: record.released.iter().map(|k| map_key(k)).collect(),
+					};
+					journals.push((journal_key, record.encode()));
+				},
+				None => break,
+			}
+			block += 1;
+		}
+	}
+	Ok(journals)
+}
+
 impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 	pub fn new<D: MetaDb>(db: &D, count_insertions: bool) -> Result<RefWindow<BlockHash, Key>, Error<D::Error>> {
 		let last_pruned = db.get_meta(&to_meta_key(LAST_PRUNED, &()))
@@ -87,6 +206,14 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 			pending_canonicalizations: 0,
 			pending_prunings: 0,
 			count_insertions,
+			kept_states: Default::default(),
+			pending_kept_states: 0,
+			last_kept: None,
+			pending_last_kept: None,
+			created: Default::default(),
+			pending_created: Default::default(),
+			pending_inserted: Default::default(),
+			pending_reset: None,
 		};
 		// read the journal
 		trace!(target: "state-db", "Reading pruning journal. Pending #{}", pending_number);
@@ -102,6 +229,36 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 			}
 			block += 1;
 		}
+
+		if let Some(kept_states) = db.get_meta(&to_meta_key(KEPT_STATES, &())).map_err(|e| Error::Db(e))? {
+			pruning.kept_states = Decode::decode(&mut kept_states.as_slice())?;
+		}
+		if let Some(last_kept) = db.get_meta(&to_meta_key(LAST_KEPT, &())).map_err(|e| Error::Db(e))? {
+			let last_kept = u64::decode(&mut last_kept.as_slice())?;
+			trace!(target: "state-db", "Reading created nodes journal. Last kept #{}", last_kept);
+			pruning.last_kept = Some(last_kept);
+			let mut block = last_kept + 1;
+			loop {
+				match db.get_meta(&to_created_journal_key(block)).map_err(|e| Error::Db(e))? {
+					Some(record) => {
+						let record: CreatedRecord<Key> = Decode::decode(&mut record.as_slice())?;
+						for k in record.created {
+							*pruning.created.entry(k).or_default() += 1;
+						}
+						for k in record.released {
+							if let std::collections::hash_map::Entry::Occupied(mut entry) = pruning.created.entry(k) {
+								*entry.get_mut() -= 1;
+								if *entry.get() == 0 {
+									entry.remove();
+								}
+							}
+						}
+					},
+					None => break,
+				}
+				block += 1;
+			}
+		}
 		Ok(pruning)
 	}
 
@@ -149,6 +306,77 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 		self.death_rows.iter().skip(self.pending_prunings).any(|r| r.hash == *hash)
 	}
 
+	/// Is the state of the block kept after it leaves the window?
+	pub fn is_kept(&self, hash: &BlockHash) -> bool {
+		self.kept_states.iter().any(|(h, _)| h == hash)
+	}
+
+	/// Hashes and numbers of the blocks whose state is kept.
+	pub fn kept_states(&self) -> &[(BlockHash, u64)] {
+		&self.kept_states
+	}
+
+	fn tracked_last_kept(&self) -> Option<u64> {
+		self.pending_last_kept.or(self.last_kept)
+	}
+
+	fn created_count(&self, key: &Key) -> u32 {
+		match self.pending_created.get(key) {
+			Some(count) => *count,
+			None if self.pending_last_kept.is_some() => 0,
+			None => self.created.get(key).cloned().unwrap_or(0),
+		}
+	}
+
+	/// Update the created nodes with the changes of the block that is being canonicalized and
+	/// remove the deletions of nodes that belong to the most recently kept state from `commit`.
+	fn track_created<D: NodeDb>(
+		&mut self,
+		block: u64,
+		commit: &mut CommitSet<Key>,
+		db: &D,
+	) -> Result<CreatedRecord<Key>, Error<D::Error>>
+		where Key: AsRef<D::Key>
+	{
+		let mut record = CreatedRecord { created: Vec::new(), released: Vec::new() };
+		for (k, _) in commit.data.inserted.iter() {
+			let count = self.created_count(k);
+			let created = if self.count_insertions {
+				// A node is stored once. It is created unless it is in the database already
+				// and not about to be pruned.
+				count == 0 && (
+					self.death_index.contains_key(k) || !(
+						self.pending_inserted.contains(k) ||
+						db.get(k.as_ref()).map_err(|e| Error::Db(e))?.is_some()
+					)
+				)
+			} else {
+				true
+			};
+			if created {
+				self.pending_created.insert(k.clone(), count + 1);
+				record.created.push(k.clone());
+			}
+		}
+		let deleted = ::std::mem::take(&mut commit.data.deleted);
+		for k in deleted {
+			let count = self.created_count(&k);
+			if count > 0 {
+				self.pending_created.insert(k.clone(), count - 1);
+				record.released.push(k.clone());
+				commit.data.deleted.push(k);
+			}
+		}
+		trace!(
+			target: "state-db",
+			"Block {}: {} nodes created, {} released after last kept state",
+			block,
+			record.created.len(),
+			record.released.len(),
+		);
+		Ok(record)
+	}
+
 	/// Prune next block. Expects at least one block in the window. Adds changes to `commit`.
 	pub fn prune_one(&mut self, commit: &mut CommitSet<Key>) {
 		if let Some(pruned) = self.death_rows.get(self.pending_prunings) {
@@ -163,9 +391,43 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 		}
 	}
 
-	/// Add a change set to the window. Creates a journal record and pushes it to `commit`
-	pub fn note_canonical(&mut self, hash: &BlockHash, commit: &mut CommitSet<Key>) {
+	/// Add a change set to the window. Creates a journal record and pushes it to `commit`.
+	///
+	/// If `keep` is set to the block number, the state of the block is kept after it leaves
+	/// the window. `db` is used to look up nodes that are inserted again.
+	pub fn note_canonical<D: NodeDb>(
+		&mut self,
+		hash: &BlockHash,
+		keep: Option<u64>,
+		commit: &mut CommitSet<Key>,
+		db: &D,
+	) -> Result<(), Error<D::Error>>
+		where Key: AsRef<D::Key>
+	{
 		trace!(target: "state-db", "Adding to pruning window: {:?} ({} inserted, {} deleted)", hash, commit.data.inserted.len(), commit.data.deleted.len());
+		let block = self.pending_number + self.death_rows.len() as u64;
+		if let Some(last_kept) = self.tracked_last_kept() {
+			let record = self.track_created(block, commit, db)?;
+			match keep {
+				// Tracking restarts below, the record would be deleted right away.
+				Some(_) => for b in last_kept + 1 .. block {
+					commit.meta.deleted.push(to_created_journal_key(b));
+				},
+				None => commit.meta.inserted.push((to_created_journal_key(block), record.encode())),
+			}
+		}
+		if let Some(number) = keep {
+			trace!(target: "state-db", "Keeping state of {:?} (#{})", hash, number);
+			self.pending_last_kept = Some(block);
+			self.pending_created.clear();
+			self.kept_states.push((hash.clone(), number));
+			self.pending_kept_states += 1;
+			commit.meta.inserted.push((to_meta_key(LAST_KEPT, &()), block.encode()));
+			commit.meta.inserted.push((to_meta_key(KEPT_STATES, &()), self.kept_states.encode()));
+		}
+		if self.count_insertions && self.tracked_last_kept().is_some() {
+			self.pending_inserted.extend(commit.data.inserted.iter().map(|(k, _)| k.clone()));
+		}
 		let inserted = if self.count_insertions {
 			commit.data.inserted.iter().map(|(k, _)| k.clone()).collect()
 		} else {
@@ -177,11 +439,44 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 			inserted,
 			deleted,
 		};
-		let block = self.pending_number + self.death_rows.len() as u64;
 		let journal_key = to_journal_key(block);
 		commit.meta.inserted.push((journal_key.clone(), journal_record.encode()));
 		self.import(&journal_record.hash, journal_key, journal_record.inserted.into_iter(), journal_record.deleted);
 		self.pending_canonicalizations += 1;
+		Ok(())
+	}
+
+	/// Drop the window and continue with block `number`, whose state is imported as a whole
+	/// instead of being derived from the states in the window. Adds changes to `commit`.
+	///
+	/// The nodes of the dropped states are not deleted. Nodes created after the most recently
+	/// kept state are no longer told apart from the nodes of that state, so they are not
+	/// deleted either.
+	///
+	/// Expects no other pending changes. The reset is pending until `apply_pending` is called.
+	pub fn reset(&mut self, number: u64, commit: &mut CommitSet<Key>) {
+		debug_assert!(self.pending_canonicalizations == 0 && self.pending_prunings == 0);
+		trace!(target: "state-db", "Resetting pruning window to #{} ({} blocks dropped)", number, self.death_rows.len());
+		let next = self.pending_number + self.death_rows.len() as u64;
+		for row in self.death_rows.iter() {
+			commit.meta.deleted.push(row.journal_key.clone());
+		}
+		commit.meta.inserted.push((to_meta_key(LAST_PRUNED, &()), (number - 1).encode()));
+		let last_kept = self.last_kept.map(|last_kept| {
+			for b in last_kept + 1 .. next {
+				commit.meta.deleted.push(to_created_journal_key(b));
+			}
+			commit.meta.inserted.push((to_meta_key(LAST_KEPT, &()), (number - 1).encode()));
+			number - 1
+		});
+		let dropped = DroppedWindow {
+			death_rows: std::mem::take(&mut self.death_rows),
+			death_index: std::mem::take(&mut self.death_index),
+			pending_number: std::mem::replace(&mut self.pending_number, number),
+			last_kept: std::mem::replace(&mut self.last_kept, last_kept),
+			created: std::mem::take(&mut self.created),
+		};
+		self.pending_reset.get_or_insert(dropped);
 	}
 
 	/// Apply all pending changes
@@ -198,6 +493,20 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 			self.pending_number += 1;
 		}
 		self.pending_prunings = 0;
+		if let Some(last_kept) = self.pending_last_kept.take() {
+			self.last_kept = Some(last_kept);
+			self.created.clear();
+		}
+		for (k, count) in self.pending_created.drain() {
+			if count == 0 {
+				self.created.remove(&k);
+			} else {
+				self.created.insert(k, count);
+			}
+		}
+		self.pending_kept_states = 0;
+		self.pending_inserted.clear();
+		self.pending_reset = None;
 	}
 
 	/// Revert all pending changes
@@ -213,12 +522,24 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 		}
 		self.pending_canonicalizations = 0;
 		self.pending_prunings = 0;
+		self.kept_states.truncate(self.kept_states.len() - self.pending_kept_states);
+		self.pending_kept_states = 0;
+		self.pending_last_kept = None;
+		self.pending_created.clear();
+		self.pending_inserted.clear();
+		if let Some(dropped) = self.pending_reset.take() {
+			self.death_rows = dropped.death_rows;
+			self.death_index = dropped.death_index;
+			self.pending_number = dropped.pending_number;
+			self.last_kept = dropped.last_kept;
+			self.created = dropped.created;
+		}
 	}
 }
 
 #[cfg(test)]
 mod tests {
-	use super::RefWindow;
+	use super::{RefWindow, PRUNING_JOURNAL, ref_counted_journals, to_created_journal_key};
 	use sp_core::H256;
 	use crate::CommitSet;
 	use crate::test::{make_db, make_commit, TestDb};
@@ -228,6 +549,9 @@ mod tests {
 		assert_eq!(pruning.pending_number, restored.pending_number);
 		assert_eq!(pruning.death_rows, restored.death_rows);
 		assert_eq!(pruning.death_index, restored.death_index);
+		assert_eq!(pruning.kept_states, restored.kept_states);
+		assert_eq!(pruning.last_kept, restored.last_kept);
+		assert_eq!(pruning.created, restored.created);
 	}
 
 	#[test]
@@ -258,7 +582,7 @@ mod tests {
 		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, true).unwrap();
 		let mut commit = make_commit(&[4, 5], &[1, 3]);
 		let h = H256::random();
-		pruning.note_canonical(&h, &mut commit);
+		pruning.note_canonical(&h, None, &mut commit, &db).unwrap();
 		db.commit(&commit);
 		assert!(pruning.have_block(&h));
 		pruning.apply_pending();
@@ -286,10 +610,10 @@ mod tests {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, true).unwrap();
 		let mut commit = make_commit(&[4], &[1]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[5], &[2]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
 		db.commit(&commit);
 		pruning.apply_pending();
 		assert!(db.data_eq(&make_db(&[1, 2, 3, 4, 5])));
@@ -314,10 +638,10 @@ mod tests {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, true).unwrap();
 		let mut commit = make_commit(&[4], &[1]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[5], &[2]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3, 4, 5])));
 		let mut commit = CommitSet::default();
@@ -337,13 +661,13 @@ mod tests {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, true).unwrap();
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[2], &[]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3])));
 		pruning.apply_pending();
@@ -370,13 +694,13 @@ mod tests {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, true).unwrap();
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[2], &[]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3])));
 
@@ -400,13 +724,13 @@ mod tests {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, false).unwrap();
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[2], &[]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3])));
 		pruning.apply_pending();
@@ -420,4 +744,131 @@ mod tests {
 		assert!(pruning.death_index.is_empty());
 	}
 
+	#[test]
+	fn ref_counted_journals_drop_reinserted() {
+		let mut db = make_db(&[1, 2, 3]);
+		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, true).unwrap();
+		let mut commit = make_commit(&[], &[2]);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
+		db.commit(&commit);
+		let mut commit = make_commit(&[2], &[3]);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
+		db.commit(&commit);
+		let mut commit = make_commit(&[], &[3]);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
+		db.commit(&commit);
+		pruning.apply_pending();
+
+		let mut converted = TestDb::default();
+		converted.meta.extend(db.meta.iter().filter(|(k, _)| !k.ends_with(PRUNING_JOURNAL)).map(|(k, v)| (k.clone(), v.clone())));
+		converted.meta.extend(ref_counted_journals::<H256, H256, _>(&db, &|k: &H256| *k).unwrap());
+		let restored: RefWindow<H256, H256> = RefWindow::new(&converted, false).unwrap();
+		assert_eq!(restored.death_rows.len(), 3);
+		assert!(restored.death_rows[0].deleted.is_empty());
+		assert!(restored.death_rows[1].deleted.contains(&H256::from_low_u64_be(3)));
+		assert!(restored.death_rows[2].deleted.is_empty());
+	}
+
+	#[test]
+	fn kept_state_survives_pruning() {
+		let mut db = make_db(&[1, 2, 3]);
+		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, true).unwrap();
+		let kept = H256::random();
+		let mut commit = make_commit(&[4], &[1]);
+		pruning.note_canonical(&kept, Some(0), &mut commit, &db).unwrap();
+		db.commit(&commit);
+		let mut commit = make_commit(&[5], &[2]);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
+		assert!(commit.data.deleted.is_empty());
+		db.commit(&commit);
+		let mut commit = make_commit(&[6], &[5]);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
+		db.commit(&commit);
+		pruning.apply_pending();
+
+		check_journal(&pruning, &db);
+
+		for _ in 0 .. 3 {
+			let mut commit = CommitSet::default();
+			pruning.prune_one(&mut commit);
+			db.commit(&commit);
+		}
+		pruning.apply_pending();
+		assert!(db.data_eq(&make_db(&[2, 3, 4, 6])));
+		assert!(!pruning.have_block(&kept));
+		assert!(pruning.is_kept(&kept));
+		assert_eq!(pruning.kept_states(), &[(kept, 0)]);
+	}
+
+	#[test]
+	fn tracking_restarts_at_kept_state() {
+		let mut db = make_db(&[1, 2, 3]);
+		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, true).unwrap();
+		let mut commit = make_commit(&[], &[1]);
+		pruning.note_canonical(&H256::random(), Some(0), &mut commit, &db).unwrap();
+		db.commit(&commit);
+		let mut commit = make_commit(&[4], &[]);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
+		db.commit(&commit);
+		pruning.apply_pending();
+		assert!(db.meta.contains_key(&to_created_journal_key(1)));
+
+		// Node 4 was created after the first kept state, but belongs to the second one.
+		let mut commit = make_commit(&[5], &[4]);
+		pruning.note_canonical(&H256::random(), Some(2), &mut commit, &db).unwrap();
+		db.commit(&commit);
+		let mut commit = make_commit(&[], &[4, 5]);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
+		assert!(commit.data.deleted.is_empty());
+		db.commit(&commit);
+		pruning.apply_pending();
+		assert!(!db.meta.contains_key(&to_created_journal_key(1)));
+
+		check_journal(&pruning, &db);
+
+		for _ in 0 .. 4 {
+			let mut commit = CommitSet::default();
+			pruning.prune_one(&mut commit);
+			db.commit(&commit);
+		}
+		pruning.apply_pending();
+		assert!(db.data_eq(&make_db(&[2, 3, 5])));
+		assert_eq!(pruning.kept_states().len(), 2);
+	}
+
+	#[test]
+	fn reinserted_node_is_not_created() {
+		let mut db = make_db(&[1, 2]);
+		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, true).unwrap();
+		let mut commit = make_commit(&[], &[]);
+		pruning.note_canonical(&H256::random(), Some(0), &mut commit, &db).unwrap();
+		db.commit(&commit);
+		// Node 2 is part of the kept state, inserting it again does not transfer it.
+		let mut commit = make_commit(&[2], &[]);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
+		db.commit(&commit);
+		let mut commit = make_commit(&[], &[2]);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
+		assert!(commit.data.deleted.is_empty());
+		db.commit(&commit);
+		pruning.apply_pending();
+		assert!(pruning.created.is_empty());
+	}
+
+	#[test]
+	fn revert_pending_drops_kept_state() {
+		let db = make_db(&[1, 2]);
+		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, true).unwrap();
+		let kept = H256::random();
+		let mut commit = make_commit(&[3], &[1]);
+		pruning.note_canonical(&kept, Some(0), &mut commit, &db).unwrap();
+		assert!(pruning.is_kept(&kept));
+		pruning.revert_pending();
+		assert!(!pruning.is_kept(&kept));
+		assert_eq!(pruning.last_kept, None);
+
+		let mut commit = make_commit(&[3], &[2]);
+		pruning.note_canonical(&H256::random(), None, &mut commit, &db).unwrap();
+		assert_eq!(commit.data.deleted, vec![H256::from_low_u64_be(2)]);
+	}
 }
@@ -38,10 +38,10 @@ impl MetaDb for TestDb {
 
 impl NodeDb for TestDb {
 	type Error = ();
-	type Key = H256;
+	type Key = [u8];
 
-	fn get(&self, key: &H256) -> Result<Option<DBValue>, ()> {
-		Ok(self.data.get(key).cloned())
+	fn get(&self, key: &[u8]) -> Result<Option<DBValue>, ()> {
+		Ok(self.data.get(&H256::from_slice(key)).cloned())
 	}
 }
 
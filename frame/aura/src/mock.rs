@@ -19,15 +19,21 @@
 
 #![cfg(test)]
 
-use crate::{Config, Module, GenesisConfig};
+use crate::{
+	AuraEquivocationOffence, Config, EquivocationProof, HandleEquivocation, Module, GenesisConfig,
+};
 use sp_consensus_aura::ed25519::AuthorityId;
 use sp_runtime::{
+	DispatchResult, KeyTypeId,
 	traits::IdentityLookup,
 	testing::{Header, UintAuthorityId},
 };
-use frame_support::{impl_outer_origin, parameter_types};
+use sp_session::MembershipProof;
+use sp_staking::offence::{Offence, OffenceError};
+use frame_support::{impl_outer_origin, parameter_types, traits::KeyOwnerProofSystem};
 use sp_io;
 use sp_core::H256;
+use std::cell::RefCell;
 
 impl_outer_origin!{
 	pub enum Origin for Test where system = frame_system {}
@@ -77,12 +83,101 @@ impl pallet_timestamp::Config for Test {
 
 impl Config for Test {
 	type AuthorityId = AuthorityId;
+	type KeyOwnerProof = MembershipProof;
+	type KeyOwnerIdentification = u64;
+	type KeyOwnerProofSystem = TestKeyOwnerProofSystem;
+	type HandleEquivocation = TestEquivocationHandler;
+	type WeightInfo = ();
+}
+
+/// A key ownership proof system which identifies an authority by its index in
+/// the current authority set, ignoring the trie nodes of the proof.
+pub struct TestKeyOwnerProofSystem;
+
+impl KeyOwnerProofSystem<(KeyTypeId, AuthorityId)> for TestKeyOwnerProofSystem {
+	type Proof = MembershipProof;
+	type IdentificationTuple = u64;
+
+	fn prove(key: (KeyTypeId, AuthorityId)) -> Option<Self::Proof> {
+		if !Aura::authorities().contains(&key.1) {
+			return None;
+		}
+
+		Some(MembershipProof {
+			session: 0,
+			trie_nodes: Vec::new(),
+			validator_count: Aura::authorities().len() as u32,
+		})
+	}
+
+	fn check_proof(key: (KeyTypeId, AuthorityId), _proof: Self::Proof) -> Option<u64> {
+		if key.0 != sp_application_crypto::key_types::AURA {
+			return None;
+		}
+
+		Aura::authorities()
+			.iter()
+			.position(|id| *id == key.1)
+			.map(|index| index as u64)
+	}
+}
+
+thread_local! {
+	/// The offences reported so far, as `(reporters, offenders, slot)`.
+	pub static OFFENCES: RefCell<Vec<(Vec<u64>, Vec<u64>, u64)>> = RefCell::new(Vec::new());
+}
+
+/// An equivocation handler which records reported offences in `OFFENCES`.
+pub struct TestEquivocationHandler;
+
+impl HandleEquivocation<Test> for TestEquivocationHandler {
+	fn report_offence(
+		reporters: Vec<u64>,
+		offence: AuraEquivocationOffence<u64>,
+	) -> Result<(), OffenceError> {
+		let offenders = offence.offenders();
+		let slot = offence.time_slot();
+
+		if Self::is_known_offence(&offenders, &slot) {
+			return Err(OffenceError::DuplicateReport);
+		}
+
+		OFFENCES.with(|o| o.borrow_mut().push((reporters, offenders, slot)));
+		Ok(())
+	}
+
+	fn is_known_offence(offenders: &[u64], time_slot: &u64) -> bool {
+		OFFENCES.with(|o| {
+			o.borrow()
+				.iter()
+				.any(|(_, known, slot)| known[..] == offenders[..] && slot == time_slot)
+		})
+	}
+
+	fn submit_unsigned_equivocation_report(
+		_equivocation_proof: EquivocationProof<Test>,
+		_key_owner_proof: MembershipProof,
+	) -> DispatchResult {
+		Ok(())
+	}
+
+	fn block_author() -> Option<u64> {
+		None
+	}
 }
 
 pub fn new_test_ext(authorities: Vec<u64>) -> sp_io::TestExternalities {
+	new_test_ext_raw_authorities(
+		authorities.into_iter().map(|a| UintAuthorityId(a).to_public_key()).collect(),
+	)
+}
+
+pub fn new_test_ext_raw_authorities(authorities: Vec<AuthorityId>) -> sp_io::TestExternalities {
+	OFFENCES.with(|o| o.borrow_mut().clear());
+
 	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
 	GenesisConfig::<Test>{
-		authorities: authorities.into_iter().map(|a| UintAuthorityId(a).to_public_key()).collect(),
+		authorities,
 	}.assimilate_storage(&mut t).unwrap();
 	t.into()
 }
@@ -19,7 +19,56 @@
 
 #![cfg(test)]
 
-use crate::mock::{Aura, new_test_ext};
+use crate::{Call, Error, mock::*};
+use codec::Encode;
+use frame_support::{assert_err, assert_ok, traits::KeyOwnerProofSystem};
+use sp_consensus_aura::{
+	AURA_ENGINE_ID, AuraEquivocationProof,
+	ed25519::{AuthorityId, AuthorityPair},
+};
+use sp_core::{H256, Pair};
+use sp_runtime::{
+	generic::{Digest, DigestItem},
+	testing::Header,
+	traits::{Header as _, ValidateUnsigned},
+	transaction_validity::{InvalidTransaction, TransactionSource},
+};
+use sp_session::MembershipProof;
+
+fn authority_pairs(count: u8) -> Vec<AuthorityPair> {
+	(0..count).map(|i| AuthorityPair::from_seed(&[i; 32])).collect()
+}
+
+fn sealed_header(pair: &AuthorityPair, slot: u64, parent_hash: H256) -> Header {
+	let mut header = Header::new(
+		1,
+		Default::default(),
+		Default::default(),
+		parent_hash,
+		Digest { logs: vec![DigestItem::PreRuntime(AURA_ENGINE_ID, slot.encode())] },
+	);
+
+	let signature = pair.sign(header.hash().as_ref());
+	header.digest_mut().push(DigestItem::Seal(AURA_ENGINE_ID, signature.encode()));
+	header
+}
+
+fn generate_equivocation_proof(
+	pair: &AuthorityPair,
+	slot_number: u64,
+) -> AuraEquivocationProof<Header, AuthorityId> {
+	AuraEquivocationProof {
+		offender: pair.public(),
+		slot_number,
+		first_header: sealed_header(pair, slot_number, H256::repeat_byte(1)),
+		second_header: sealed_header(pair, slot_number, H256::repeat_byte(2)),
+	}
+}
+
+fn key_owner_proof(authority_id: &AuthorityId) -> MembershipProof {
+	TestKeyOwnerProofSystem::prove((sp_application_crypto::key_types::AURA, authority_id.clone()))
+		.unwrap()
+}
 
 #[test]
 fn initial_values() {
@@ -28,3 +77,140 @@ fn initial_values() {
 		assert_eq!(Aura::authorities().len(), 4);
 	});
 }
+
+#[test]
+fn report_equivocation_works() {
+	let pairs = authority_pairs(3);
+	new_test_ext_raw_authorities(pairs.iter().map(|p| p.public()).collect()).execute_with(|| {
+		let equivocation_proof = generate_equivocation_proof(&pairs[1], 42);
+		let key_owner_proof = key_owner_proof(&pairs[1].public());
+
+		assert_ok!(Aura::report_equivocation(
+			Origin::signed(7),
+			equivocation_proof,
+			key_owner_proof,
+		));
+
+		OFFENCES.with(|o| assert_eq!(*o.borrow(), vec![(vec![7], vec![1], 42)]));
+	});
+}
+
+#[test]
+fn report_equivocation_invalid_equivocation_proof() {
+	let pairs = authority_pairs(3);
+	new_test_ext_raw_authorities(pairs.iter().map(|p| p.public()).collect()).execute_with(|| {
+		let key_owner_proof = key_owner_proof(&pairs[1].public());
+
+		let assert_invalid_equivocation = |equivocation_proof| {
+			assert_err!(
+				Aura::report_equivocation(
+					Origin::signed(7),
+					equivocation_proof,
+					key_owner_proof.clone(),
+				),
+				Error::<Test>::InvalidEquivocationProof,
+			);
+		};
+
+		// both headers are the same
+		let mut equivocation_proof = generate_equivocation_proof(&pairs[1], 42);
+		equivocation_proof.second_header = equivocation_proof.first_header.clone();
+		assert_invalid_equivocation(equivocation_proof);
+
+		// the headers target different slots
+		let mut equivocation_proof = generate_equivocation_proof(&pairs[1], 42);
+		equivocation_proof.second_header = sealed_header(&pairs[1], 43, H256::repeat_byte(2));
+		assert_invalid_equivocation(equivocation_proof);
+
+		// the slot in the proof doesn't match the headers
+		let mut equivocation_proof = generate_equivocation_proof(&pairs[1], 42);
+		equivocation_proof.slot_number = 43;
+		assert_invalid_equivocation(equivocation_proof);
+
+		// one of the headers is signed by another authority
+		let mut equivocation_proof = generate_equivocation_proof(&pairs[1], 42);
+		equivocation_proof.second_header = sealed_header(&pairs[2], 42, H256::repeat_byte(2));
+		assert_invalid_equivocation(equivocation_proof);
+
+		// one of the headers is unsealed
+		let mut equivocation_proof = generate_equivocation_proof(&pairs[1], 42);
+		equivocation_proof.second_header.digest_mut().pop();
+		assert_invalid_equivocation(equivocation_proof);
+	});
+}
+
+#[test]
+fn report_equivocation_invalid_key_owner_proof() {
+	let pairs = authority_pairs(3);
+	let outsider = AuthorityPair::from_seed(&[42; 32]);
+	new_test_ext_raw_authorities(pairs.iter().map(|p| p.public()).collect()).execute_with(|| {
+		let equivocation_proof = generate_equivocation_proof(&outsider, 42);
+
+		assert_err!(
+			Aura::report_equivocation(
+				Origin::signed(7),
+				equivocation_proof,
+				MembershipProof::default(),
+			),
+			Error::<Test>::InvalidKeyOwnershipProof,
+		);
+	});
+}
+
+#[test]
+fn report_equivocation_waives_fee_and_rejects_duplicates() {
+	let pairs = authority_pairs(3);
+	new_test_ext_raw_authorities(pairs.iter().map(|p| p.public()).collect()).execute_with(|| {
+		let equivocation_proof = generate_equivocation_proof(&pairs[0], 42);
+		let key_owner_proof = key_owner_proof(&pairs[0].public());
+
+		let post_info = Aura::report_equivocation_unsigned(
+			Origin::none(),
+			equivocation_proof.clone(),
+			key_owner_proof.clone(),
+		).unwrap();
+
+		// the fee is waived for valid reports
+		assert_eq!(post_info.pays_fee, frame_support::weights::Pays::No);
+
+		assert_err!(
+			Aura::report_equivocation_unsigned(Origin::none(), equivocation_proof, key_owner_proof),
+			Error::<Test>::DuplicateOffenceReport,
+		);
+	});
+}
+
+#[test]
+fn report_equivocation_validate_unsigned_prevents_duplicates() {
+	let pairs = authority_pairs(3);
+	new_test_ext_raw_authorities(pairs.iter().map(|p| p.public()).collect()).execute_with(|| {
+		let equivocation_proof = generate_equivocation_proof(&pairs[2], 42);
+		let key_owner_proof = key_owner_proof(&pairs[2].public());
+
+		let call = Call::report_equivocation_unsigned(
+			equivocation_proof.clone(),
+			key_owner_proof.clone(),
+		);
+
+		// only local/inblock reports are allowed
+		assert_eq!(
+			<Aura as ValidateUnsigned>::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Call.into(),
+		);
+
+		assert_ok!(<Aura as ValidateUnsigned>::validate_unsigned(TransactionSource::Local, &call));
+		assert_ok!(<Aura as ValidateUnsigned>::pre_dispatch(&call));
+
+		// after the offence is reported the same report is considered stale
+		assert_ok!(Aura::report_equivocation_unsigned(
+			Origin::none(),
+			equivocation_proof,
+			key_owner_proof,
+		));
+
+		assert_err!(
+			<Aura as ValidateUnsigned>::pre_dispatch(&call),
+			InvalidTransaction::Stale,
+		);
+	});
+}
@@ -25,7 +25,7 @@ use codec::{Decode, Encode};
 use frame_support::{
 	decl_error, decl_module, decl_storage,
 	dispatch::DispatchResultWithPostInfo,
-	traits::{FindAuthor, Get, KeyOwnerProofSystem, Randomness as RandomnessT},
+	traits::{FindAuthor, Get, KeyOwnerProofSystem},
 	weights::{Pays, Weight},
 	Parameter,
 };
@@ -33,7 +33,7 @@ use frame_system::{ensure_none, ensure_signed};
 use sp_application_crypto::Public;
 use sp_runtime::{
 	generic::DigestItem,
-	traits::{Hash, IsMember, One, SaturatedConversion, Saturating},
+	traits::{IsMember, One, SaturatedConversion, Saturating},
 	ConsensusEngineId, KeyTypeId,
 };
 use sp_session::{GetSessionNumber, GetValidatorCount};
@@ -52,6 +52,7 @@ pub use sp_consensus_babe::{AuthorityId, PUBLIC_KEY_LENGTH, RANDOMNESS_LENGTH, V
 
 mod equivocation;
 mod default_weights;
+mod randomness;
 
 #[cfg(any(feature = "runtime-benchmarks", test))]
 mod benchmarking;
@@ -61,6 +62,10 @@ mod mock;
 mod tests;
 
 pub use equivocation::{BabeEquivocationOffence, EquivocationHandler, HandleEquivocation};
+pub use randomness::{
+	CurrentBlockRandomness, ParentBlockRandomness, RandomnessFromOneEpochAgo,
+	RandomnessFromTwoEpochsAgo,
+};
 
 pub trait Config: pallet_timestamp::Config {
 	/// The amount of time, in slots, that each epoch should last.
@@ -210,11 +215,25 @@ decl_storage! {
 		/// if per-block initialization has already been called for current block.
 		Initialized get(fn initialized): Option<MaybeRandomness>;
 
-		/// Temporary value (cleared at block finalization) that includes the VRF output generated
-		/// at this block. This field should always be populated during block processing unless
-		/// secondary plain slots are enabled (which don't contain a VRF output).
+		/// The randomness derived from the VRF output generated at this block. This field
+		/// should always be populated during block processing unless secondary plain slots are
+		/// enabled (which don't contain a VRF output). It is set in `on_initialize` and kept
+		/// after block finalization, so that it can be queried for any block.
 		AuthorVrfRandomness get(fn author_vrf_randomness): MaybeRandomness;
 
+		/// The randomness derived from the VRF output generated at the parent block, i.e. the
+		/// value of `AuthorVrfRandomness` before it is set in `on_initialize`.
+		ParentAuthorVrfRandomness get(fn parent_author_vrf_randomness): MaybeRandomness;
+
+		/// The block numbers when the last and current epoch have started, respectively `N-1`
+		/// and `N`.
+		///
+		/// NOTE: We track this in order to annotate the block number when a given pool of
+		/// entropy was fixed (i.e. it was known to chain observers). Since epochs are defined
+		/// in slots, which may be skipped, the block numbers may not line up with the slot
+		/// numbers.
+		EpochStart get(fn epoch_start): (T::BlockNumber, T::BlockNumber);
+
 		/// How late the current block is compared to its parent.
 		///
 		/// This entry is populated as part of block execution and is cleaned up
@@ -260,9 +279,6 @@ decl_module! {
 				Self::deposit_randomness(&randomness);
 			}
 
-			// The stored author generated VRF output is ephemeral.
-			AuthorVrfRandomness::kill();
-
 			// remove temporary "environment" entry from storage
 			Lateness::<T>::kill();
 		}
@@ -311,31 +327,6 @@ decl_module! {
 	}
 }
 
-impl<T: Config> RandomnessT<<T as frame_system::Config>::Hash> for Module<T> {
-	/// Some BABE blocks have VRF outputs where the block producer has exactly one bit of influence,
-	/// either they make the block or they do not make the block and thus someone else makes the
-	/// next block. Yet, this randomness is not fresh in all BABE blocks.
-	///
-	/// If that is an insufficient security guarantee then two things can be used to improve this
-	/// randomness:
-	///
-	/// - Name, in advance, the block number whose random value will be used; ensure your module
-	///   retains a buffer of previous random values for its subject and then index into these in
-	///   order to obviate the ability of your user to look up the parent hash and choose when to
-	///   transact based upon it.
-	/// - Require your user to first commit to an additional value by first posting its hash.
-	///   Require them to reveal the value to determine the final result, hashing it with the
-	///   output of this random function. This reduces the ability of a cabal of block producers
-	///   from conspiring against individuals.
-	fn random(subject: &[u8]) -> T::Hash {
-		let mut subject = subject.to_vec();
-		subject.reserve(VRF_OUTPUT_LENGTH);
-		subject.extend_from_slice(&Self::randomness()[..]);
-
-		<T as frame_system::Config>::Hashing::hash(&subject[..])
-	}
-}
-
 /// A BABE public key
 pub type BabeKey = [u8; PUBLIC_KEY_LENGTH];
 
@@ -454,6 +445,13 @@ impl<T: Config> Module<T> {
 		EpochIndex::put(epoch_index);
 		Authorities::put(authorities);
 
+		// Track the block at which the epoch started, the randomness of the
+		// next epoch is fixed from this block on.
+		let now = <frame_system::Module<T>>::block_number();
+		EpochStart::<T>::mutate(|(previous_epoch_start, current_epoch_start)| {
+			*previous_epoch_start = sp_std::mem::replace(current_epoch_start, now);
+		});
+
 		// Update epoch randomness.
 		let next_epoch_index = epoch_index
 			.checked_add(1)
@@ -603,7 +601,9 @@ impl<T: Config> Module<T> {
 		Initialized::put(if is_primary { maybe_randomness } else { None });
 
 		// Place either the primary or secondary VRF output into the
-		// `AuthorVrfRandomness` storage item.
+		// `AuthorVrfRandomness` storage item, keeping the one from the
+		// parent block around.
+		ParentAuthorVrfRandomness::put(AuthorVrfRandomness::get());
 		AuthorVrfRandomness::put(maybe_randomness);
 
 		// enact epoch change, if necessary.
@@ -0,0 +1,152 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Providers of on-chain randomness based on the VRF outputs of BABE block authors.
+//!
+//! Every provider returns, along with the random value, the block number since when the
+//! value has been determinable by chain observers. The providers differ in how early the
+//! value is fixed and in how much influence block authors have over it, so callers should
+//! pick the one matching their security assumptions.
+//!
+//! If that is an insufficient security guarantee then two things can be used to improve the
+//! randomness:
+//!
+//! - Name, in advance, the block number whose random value will be used; ensure your module
+//!   retains a buffer of previous random values for its subject and then index into these in
+//!   order to obviate the ability of your user to look up the parent hash and choose when to
+//!   transact based upon it.
+//! - Require your user to first commit to an additional value by first posting its hash.
+//!   Require them to reveal the value to determine the final result, hashing it with the
+//!   output of this random function. This reduces the ability of a cabal of block producers
+//!   from conspiring against individuals.
+
+use super::{
+	AuthorVrfRandomness, Config, EpochStart, NextRandomness, ParentAuthorVrfRandomness,
+	Randomness, VRF_OUTPUT_LENGTH,
+};
+use frame_support::traits::Randomness as RandomnessT;
+use sp_runtime::traits::{Hash, One, Saturating};
+
+/// Randomness usable by consensus protocols that **depend** upon finality and take action
+/// based upon on-chain commitments made during the epoch before the previous epoch.
+///
+/// An off-chain consensus protocol requires randomness be finalized before usage, but one
+/// extra epoch delay beyond `RandomnessFromOneEpochAgo` suffices, under the assumption
+/// that finality never stalls for longer than one epoch.
+///
+/// All randomness is relative to commitments to any other inputs to the computation: If
+/// Alice samples randomness near perfectly using radioactive decay, but then afterwards
+/// Eve selects an arbitrary value with which to xor Alice's randomness, then Eve always
+/// wins whatever game they play.
+///
+/// All input commitments used with `RandomnessFromTwoEpochsAgo` should come from at least
+/// three epochs ago.
+///
+/// This is the randomness of the current epoch, which was fixed at the start of the
+/// previous epoch.
+///
+/// # Security
+///
+/// This MUST NOT be used for gambling, as it can be influenced by a malicious validator in
+/// the short term. It MAY be used in many cryptographic protocols, however, so long as one
+/// remembers that this (like everything else on-chain) is public. For example, it can be
+/// used where a number is needed that cannot have been chosen by an adversary, for purposes
+/// such as public-coin zero-knowledge proofs.
+pub struct RandomnessFromTwoEpochsAgo<T>(sp_std::marker::PhantomData<T>);
+
+/// Randomness usable by on-chain protocols that **do not depend** upon finality and take
+/// action based upon on-chain commitments made during the previous epoch.
+///
+/// All input commitments used with `RandomnessFromOneEpochAgo` should come from at least
+/// two epochs ago, so the previous epoch's randomness cannot have influenced them.
+///
+/// This is the randomness that will be used by the next epoch, which was fixed at the
+/// start of the current epoch.
+///
+/// # Security
+///
+/// This MUST NOT be used for gambling, as it can be influenced by a malicious validator in
+/// the short term. It MAY be used in many cryptographic protocols, however, so long as one
+/// remembers that this (like everything else on-chain) is public. For example, it can be
+/// used where a number is needed that cannot have been chosen by an adversary, for purposes
+/// such as public-coin zero-knowledge proofs.
+pub struct RandomnessFromOneEpochAgo<T>(sp_std::marker::PhantomData<T>);
+
+/// Randomness produced semi-freshly with each block, but inherits limitations of
+/// `RandomnessFromTwoEpochsAgo` from which it derives.
+///
+/// This is the VRF output of the author of the parent block, which is `None` if the
+/// parent block was authored in a secondary plain slot.
+///
+/// # Security
+///
+/// This MUST NOT be used for gambling, as it can be influenced by a malicious validator in
+/// the short term, who can choose not to author the parent block. It MAY be used in many
+/// cryptographic protocols, however, so long as one remembers that this (like everything
+/// else on-chain) is public.
+pub struct ParentBlockRandomness<T>(sp_std::marker::PhantomData<T>);
+
+/// Randomness produced semi-freshly with each block, but inherits limitations of
+/// `RandomnessFromTwoEpochsAgo` from which it derives.
+///
+/// This is the VRF output of the author of the current block, which is `None` if the
+/// current block is authored in a secondary plain slot.
+///
+/// # Security
+///
+/// The author of the current block knows this value before authoring the block, and can
+/// therefore decide whether to author it (and which extrinsics to include) based on it.
+/// Prefer `ParentBlockRandomness` unless this is acceptable.
+pub struct CurrentBlockRandomness<T>(sp_std::marker::PhantomData<T>);
+
+fn mix<T: Config>(subject: &[u8], randomness: &[u8]) -> T::Hash {
+	let mut subject = subject.to_vec();
+	subject.reserve(VRF_OUTPUT_LENGTH);
+	subject.extend_from_slice(randomness);
+
+	<T as frame_system::Config>::Hashing::hash(&subject[..])
+}
+
+impl<T: Config> RandomnessT<T::Hash, T::BlockNumber> for RandomnessFromTwoEpochsAgo<T> {
+	fn random(subject: &[u8]) -> (T::Hash, T::BlockNumber) {
+		(mix::<T>(subject, &Randomness::get()[..]), EpochStart::<T>::get().0)
+	}
+}
+
+impl<T: Config> RandomnessT<T::Hash, T::BlockNumber> for RandomnessFromOneEpochAgo<T> {
+	fn random(subject: &[u8]) -> (T::Hash, T::BlockNumber) {
+		(mix::<T>(subject, &NextRandomness::get()[..]), EpochStart::<T>::get().1)
+	}
+}
+
+impl<T: Config> RandomnessT<Option<T::Hash>, T::BlockNumber> for ParentBlockRandomness<T> {
+	fn random(subject: &[u8]) -> (Option<T::Hash>, T::BlockNumber) {
+		let random = ParentAuthorVrfRandomness::get()
+			.map(|randomness| mix::<T>(subject, &randomness[..]));
+
+		(random, <frame_system::Module<T>>::block_number().saturating_sub(One::one()))
+	}
+}
+
+impl<T: Config> RandomnessT<Option<T::Hash>, T::BlockNumber> for CurrentBlockRandomness<T> {
+	fn random(subject: &[u8]) -> (Option<T::Hash>, T::BlockNumber) {
+		let random = AuthorVrfRandomness::get()
+			.map(|randomness| mix::<T>(subject, &randomness[..]));
+
+		(random, <frame_system::Module<T>>::block_number())
+	}
+}
@@ -20,7 +20,7 @@
 use super::{Call, *};
 use frame_support::{
 	assert_err, assert_ok,
-	traits::{Currency, OnFinalize},
+	traits::{Currency, OnFinalize, Randomness as RandomnessT},
 	weights::{GetDispatchInfo, Pays},
 };
 use mock::*;
@@ -95,7 +95,7 @@ fn first_block_epoch_zero_start() {
 		assert_eq!(SegmentIndex::get(), 0);
 		assert_eq!(UnderConstruction::get(0), vec![vrf_randomness]);
 		assert_eq!(Babe::randomness(), [0; 32]);
-		assert_eq!(Babe::author_vrf_randomness(), None);
+		assert_eq!(Babe::author_vrf_randomness(), Some(vrf_randomness));
 		assert_eq!(NextRandomness::get(), [0; 32]);
 
 		assert_eq!(header.digest.logs.len(), 2);
@@ -137,7 +137,7 @@ fn author_vrf_output_for_primary() {
 
 		Babe::on_finalize(1);
 		System::finalize();
-		assert_eq!(Babe::author_vrf_randomness(), None);
+		assert_eq!(Babe::author_vrf_randomness(), Some(vrf_randomness));
 	})
 }
 
@@ -163,7 +163,7 @@ fn author_vrf_output_for_secondary_vrf() {
 
 		Babe::on_finalize(1);
 		System::finalize();
-		assert_eq!(Babe::author_vrf_randomness(), None);
+		assert_eq!(Babe::author_vrf_randomness(), Some(vrf_randomness));
 	})
 }
 
@@ -190,6 +190,111 @@ fn no_author_vrf_output_for_secondary_plain() {
 	})
 }
 
+#[test]
+fn author_vrf_randomness_can_be_verified() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(1);
+
+	ext.execute_with(|| {
+		let genesis_slot = 10;
+		let (vrf_output, vrf_proof, vrf_randomness) = make_vrf_output(genesis_slot, &pairs[0]);
+		let pre_digest = make_pre_digest(0, genesis_slot, vrf_output, vrf_proof);
+
+		let (_, mut data) = pre_digest.logs[0].as_pre_runtime().unwrap();
+		let pre_digest_data = sp_consensus_babe::digests::PreDigest::decode(&mut data).unwrap();
+
+		// the epoch data must be taken from the parent block.
+		let epoch = Babe::current_epoch();
+		assert_eq!(
+			sp_consensus_babe::verify_vrf_randomness(
+				&pre_digest_data,
+				&pairs[0].public(),
+				&epoch.randomness,
+				epoch.epoch_index,
+			),
+			Some(vrf_randomness),
+		);
+
+		// the VRF proof is bound to the author and to the epoch randomness.
+		assert_eq!(
+			sp_consensus_babe::verify_vrf_randomness(
+				&pre_digest_data,
+				&pairs[0].public(),
+				&[1; 32],
+				epoch.epoch_index,
+			),
+			None,
+		);
+
+		System::initialize(&1, &Default::default(), &pre_digest, Default::default());
+		Babe::do_initialize(1);
+
+		assert_eq!(Babe::author_vrf_randomness(), Some(vrf_randomness));
+	})
+}
+
+#[test]
+fn parent_block_randomness_is_derived_from_parent_author_vrf() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(1);
+
+	ext.execute_with(|| {
+		let genesis_slot = 10;
+		let (vrf_output, vrf_proof, vrf_randomness) = make_vrf_output(genesis_slot, &pairs[0]);
+		let pre_digest = make_pre_digest(0, genesis_slot, vrf_output, vrf_proof);
+
+		System::initialize(&1, &Default::default(), &pre_digest, Default::default());
+		Babe::do_initialize(1);
+
+		let (current_block_random, known_since) =
+			CurrentBlockRandomness::<Test>::random(&b"subject"[..]);
+		assert!(current_block_random.is_some());
+		assert_eq!(known_since, 1);
+		assert_eq!(ParentBlockRandomness::<Test>::random(&b"subject"[..]), (None, 0));
+
+		Babe::on_finalize(1);
+		let header = System::finalize();
+
+		// the next block is authored in a secondary plain slot, i.e. without a VRF output.
+		let pre_digest = make_secondary_plain_pre_digest(0, genesis_slot + 1);
+		System::initialize(&2, &header.hash(), &pre_digest, Default::default());
+		Babe::do_initialize(2);
+
+		assert_eq!(Babe::author_vrf_randomness(), None);
+		assert_eq!(Babe::parent_author_vrf_randomness(), Some(vrf_randomness));
+		assert_eq!(
+			ParentBlockRandomness::<Test>::random(&b"subject"[..]),
+			(current_block_random, 1),
+		);
+		assert_eq!(CurrentBlockRandomness::<Test>::random(&b"subject"[..]), (None, 2));
+	})
+}
+
+#[test]
+fn epoch_randomness_is_annotated_with_epoch_start() {
+	new_test_ext(1).execute_with(|| {
+		assert_eq!(<Test as Config>::EpochDuration::get(), 3);
+		// this sets the genesis slot to 6;
+		go_to_block(1, 6);
+		assert_eq!(Babe::epoch_start(), (0, 0));
+
+		// the first epoch change happens at slot 9.
+		progress_to_block(4);
+		assert_eq!(Babe::epoch_index(), 1);
+		assert_eq!(Babe::epoch_start(), (0, 4));
+
+		progress_to_block(7);
+		assert_eq!(Babe::epoch_index(), 2);
+		assert_eq!(Babe::epoch_start(), (4, 7));
+
+		let (random, known_since) = RandomnessFromTwoEpochsAgo::<Test>::random(&b"subject"[..]);
+		assert_eq!(known_since, 4);
+
+		let (next_random, next_known_since) =
+			RandomnessFromOneEpochAgo::<Test>::random(&b"subject"[..]);
+		assert_eq!(next_known_since, 7);
+		assert_ne!(random, next_random);
+	})
+}
+
 #[test]
 fn authority_index() {
 	new_test_ext(4).execute_with(|| {
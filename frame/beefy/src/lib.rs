@@ -0,0 +1,185 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # BEEFY Module
+//!
+//! This module tracks the BEEFY validator set and informs the `client/beefy` gadget about
+//! it and about new MMR roots through header digests.
+//!
+//! The validator set follows the session validators: whenever the session changes the queued
+//! validators' BEEFY keys (ECDSA) become the next set and a
+//! [`ConsensusLog::AuthoritiesChange`] digest is deposited.
+//!
+//! The [`DepositBeefyDigest`] type can be used as `pallet_mmr::Config::OnNewRoot` hook, so that
+//! every new MMR root ends up in a [`ConsensusLog::MmrRoot`] digest item, which is the payload
+//! BEEFY validators sign once the block is finalized by GRANDPA.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Encode;
+use frame_support::{decl_module, decl_storage, Parameter};
+use sp_runtime::{
+	generic::DigestItem,
+	traits::{IsMember, MaybeSerializeDeserialize, Member},
+	RuntimeAppPublic,
+};
+use sp_std::prelude::*;
+
+use sp_beefy::{AuthorityIndex, ConsensusLog, MmrRootHash, ValidatorSet, BEEFY_ENGINE_ID};
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+/// The module's config trait.
+pub trait Config: frame_system::Config {
+	/// Authority identifier type
+	type AuthorityId: Member + Parameter + RuntimeAppPublic + Default + MaybeSerializeDeserialize;
+}
+
+decl_storage! {
+	trait Store for Module<T: Config> as Beefy {
+		/// The current authorities set
+		Authorities get(fn authorities): Vec<T::AuthorityId>;
+
+		/// The current validator set id
+		ValidatorSetId get(fn validator_set_id): sp_beefy::ValidatorSetId;
+
+		/// Authorities set scheduled to be used with the next session
+		NextAuthorities get(fn next_authorities): Vec<T::AuthorityId>;
+	}
+	add_extra_genesis {
+		config(authorities): Vec<T::AuthorityId>;
+		build(|config| Module::<T>::initialize_authorities(&config.authorities))
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Config> for enum Call where origin: T::Origin { }
+}
+
+impl<T: Config> Module<T> {
+	/// Return the current active BEEFY validator set.
+	pub fn validator_set() -> ValidatorSet<T::AuthorityId> {
+		ValidatorSet::<T::AuthorityId> {
+			validators: Self::authorities(),
+			id: Self::validator_set_id(),
+		}
+	}
+
+	fn change_authorities(new: Vec<T::AuthorityId>, queued: Vec<T::AuthorityId>) {
+		// As in GRANDPA, we trigger a validator set change only if the validator
+		// set has actually changed.
+		if new != Self::authorities() {
+			<Authorities<T>>::put(&new);
+
+			let next_id = Self::validator_set_id() + 1u64;
+			<ValidatorSetId>::put(next_id);
+
+			let log: DigestItem<T::Hash> = DigestItem::Consensus(
+				BEEFY_ENGINE_ID,
+				ConsensusLog::AuthoritiesChange(ValidatorSet { validators: new, id: next_id }).encode(),
+			);
+			<frame_system::Module<T>>::deposit_log(log);
+		}
+
+		<NextAuthorities<T>>::put(&queued);
+	}
+
+	fn initialize_authorities(authorities: &[T::AuthorityId]) {
+		if authorities.is_empty() {
+			return;
+		}
+
+		assert!(
+			<Authorities<T>>::get().is_empty(),
+			"Authorities are already initialized!"
+		);
+
+		<Authorities<T>>::put(authorities);
+		<ValidatorSetId>::put(sp_beefy::GENESIS_AUTHORITY_SET_ID);
+		// for consistency we initialize the next authority set too.
+		<NextAuthorities<T>>::put(authorities);
+	}
+
+	/// Deposit a digest item with given MMR root hash.
+	///
+	/// The BEEFY gadget picks up the root from finalized blocks and uses it as
+	/// the payload of the commitment.
+	pub fn deposit_mmr_root(root: MmrRootHash) {
+		let log: DigestItem<T::Hash> = DigestItem::Consensus(
+			BEEFY_ENGINE_ID,
+			ConsensusLog::<T::AuthorityId>::MmrRoot(root).encode(),
+		);
+		<frame_system::Module<T>>::deposit_log(log);
+	}
+}
+
+impl<T: Config> sp_runtime::BoundToRuntimeAppPublic for Module<T> {
+	type Public = T::AuthorityId;
+}
+
+impl<T: Config> pallet_session::OneSessionHandler<T::AccountId> for Module<T> {
+	type Key = T::AuthorityId;
+
+	fn on_genesis_session<'a, I: 'a>(validators: I)
+	where
+		I: Iterator<Item = (&'a T::AccountId, T::AuthorityId)>,
+	{
+		let authorities = validators.map(|(_, k)| k).collect::<Vec<_>>();
+		Self::initialize_authorities(&authorities);
+	}
+
+	fn on_new_session<'a, I: 'a>(changed: bool, validators: I, queued_validators: I)
+	where
+		I: Iterator<Item = (&'a T::AccountId, T::AuthorityId)>,
+	{
+		if changed {
+			let next_authorities = validators.map(|(_, k)| k).collect::<Vec<_>>();
+			let next_queued_authorities = queued_validators.map(|(_, k)| k).collect::<Vec<_>>();
+
+			Self::change_authorities(next_authorities, next_queued_authorities);
+		}
+	}
+
+	fn on_disabled(i: usize) {
+		let log: DigestItem<T::Hash> = DigestItem::Consensus(
+			BEEFY_ENGINE_ID,
+			ConsensusLog::<T::AuthorityId>::OnDisabled(i as AuthorityIndex).encode(),
+		);
+
+		<frame_system::Module<T>>::deposit_log(log);
+	}
+}
+
+impl<T: Config> IsMember<T::AuthorityId> for Module<T> {
+	fn is_member(authority_id: &T::AuthorityId) -> bool {
+		Self::authorities().iter().any(|id| id == authority_id)
+	}
+}
+
+/// A MMR root hook depositing the root in a BEEFY digest item.
+///
+/// Use it as `pallet_mmr::Config::OnNewRoot` to make the MMR root available to the BEEFY gadget.
+pub struct DepositBeefyDigest<T>(sp_std::marker::PhantomData<T>);
+
+impl<T: Config> pallet_mmr_primitives::OnNewRoot<MmrRootHash> for DepositBeefyDigest<T> {
+	fn on_new_root(root: &MmrRootHash) {
+		Module::<T>::deposit_mmr_root(*root);
+	}
+}
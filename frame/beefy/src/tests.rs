@@ -0,0 +1,149 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use codec::Encode;
+use pallet_mmr_primitives::OnNewRoot;
+use pallet_session::OneSessionHandler;
+use sp_beefy::{ConsensusLog, ValidatorSet, BEEFY_ENGINE_ID};
+use sp_core::H256;
+use sp_runtime::{generic::DigestItem, traits::IsMember};
+
+use crate::mock::*;
+
+type BeefyId = sp_beefy::crypto::AuthorityId;
+
+fn with_account_refs(authorities: &[(u64, BeefyId)]) -> Vec<(&u64, BeefyId)> {
+	authorities.iter().map(|(acc, id)| (acc, id.clone())).collect()
+}
+
+fn init_block(block: u64) {
+	frame_system::Module::<Test>::initialize(
+		&block,
+		&Default::default(),
+		&Default::default(),
+		frame_system::InitKind::Full,
+	);
+}
+
+pub fn beefy_log(log: ConsensusLog<BeefyId>) -> DigestItem<H256> {
+	DigestItem::Consensus(BEEFY_ENGINE_ID, log.encode())
+}
+
+#[test]
+fn genesis_session_initializes_authorities() {
+	let want = vec![mock_beefy_id(1), mock_beefy_id(2), mock_beefy_id(3), mock_beefy_id(4)];
+
+	new_test_ext(vec![1, 2, 3, 4]).execute_with(|| {
+		let authorities = Beefy::authorities();
+
+		assert_eq!(authorities.len(), 4);
+		assert_eq!(want[0], authorities[0]);
+		assert_eq!(want[1], authorities[1]);
+
+		assert_eq!(Beefy::validator_set_id(), 0);
+
+		let next_authorities = Beefy::next_authorities();
+
+		assert_eq!(next_authorities.len(), 4);
+		assert_eq!(want[0], next_authorities[0]);
+		assert_eq!(want[1], next_authorities[1]);
+	});
+}
+
+#[test]
+fn session_change_updates_authorities() {
+	new_test_ext(vec![1, 2, 3, 4]).execute_with(|| {
+		init_block(1);
+
+		let next = mock_authorities(vec![3, 4]);
+		let queued = mock_authorities(vec![5]);
+		Beefy::on_new_session(
+			true,
+			with_account_refs(&next).into_iter(),
+			with_account_refs(&queued).into_iter(),
+		);
+
+		assert_eq!(Beefy::validator_set_id(), 1);
+		assert_eq!(Beefy::authorities(), vec![mock_beefy_id(3), mock_beefy_id(4)]);
+		assert_eq!(Beefy::next_authorities(), vec![mock_beefy_id(5)]);
+
+		let want = beefy_log(ConsensusLog::AuthoritiesChange(ValidatorSet {
+			validators: vec![mock_beefy_id(3), mock_beefy_id(4)],
+			id: 1,
+		}));
+
+		let log = frame_system::Module::<Test>::digest().logs[0].clone();
+		assert_eq!(want, log);
+	});
+}
+
+#[test]
+fn session_change_without_authority_change_does_not_bump_set_id() {
+	new_test_ext(vec![1, 2]).execute_with(|| {
+		init_block(1);
+
+		let same = mock_authorities(vec![1, 2]);
+		Beefy::on_new_session(
+			true,
+			with_account_refs(&same).into_iter(),
+			with_account_refs(&same).into_iter(),
+		);
+
+		// when `changed` is false nothing is updated at all
+		let other = mock_authorities(vec![3]);
+		Beefy::on_new_session(
+			false,
+			with_account_refs(&other).into_iter(),
+			with_account_refs(&other).into_iter(),
+		);
+
+		assert_eq!(Beefy::validator_set_id(), 0);
+		assert_eq!(Beefy::authorities(), vec![mock_beefy_id(1), mock_beefy_id(2)]);
+		assert!(frame_system::Module::<Test>::digest().logs.is_empty());
+	});
+}
+
+#[test]
+fn validator_set_at_genesis() {
+	let want = vec![mock_beefy_id(1), mock_beefy_id(2)];
+
+	new_test_ext(vec![1, 2, 3, 4]).execute_with(|| {
+		let vs = Beefy::validator_set();
+
+		assert_eq!(vs.id, 0u64);
+		assert_eq!(vs.validators[0], want[0]);
+		assert_eq!(vs.validators[1], want[1]);
+		assert!(Beefy::is_member(&mock_beefy_id(3)));
+		assert!(!Beefy::is_member(&mock_beefy_id(5)));
+	});
+}
+
+#[test]
+fn disabled_validator_and_mmr_root_are_deposited_as_digests() {
+	new_test_ext(vec![1, 2, 3, 4]).execute_with(|| {
+		init_block(1);
+
+		Beefy::on_disabled(2);
+		crate::DepositBeefyDigest::<Test>::on_new_root(&H256::repeat_byte(7));
+
+		let logs = frame_system::Module::<Test>::digest().logs;
+		assert_eq!(logs, vec![
+			beefy_log(ConsensusLog::OnDisabled(2)),
+			beefy_log(ConsensusLog::MmrRoot(H256::repeat_byte(7))),
+		]);
+	});
+}
@@ -20,42 +20,116 @@
 
 use bitflags::bitflags;
 use codec::{Decode, Encode};
-use sp_runtime::{DispatchError, RuntimeDebug};
+use sp_runtime::{traits::{Saturating, Zero}, DispatchError, RuntimeDebug};
 use sp_std::prelude::*;
 
+/// Result type of a `bare_call` or `bare_instantiate` call.
+///
+/// It contains the execution result together with some auxiliary information.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct ContractResult<R, Balance, EventRecord> {
+	/// How much gas was consumed during execution.
+	pub gas_consumed: u64,
+	/// How much gas is required as gas limit in order to execute this call.
+	///
+	/// This value should be used to determine the gas limit for on-chain execution.
+	///
+	/// # Note
+	///
+	/// This can only be different from [`Self::gas_consumed`] when gas is set aside and
+	/// handed back later. Currently, any `seal_call` or `seal_instantiate` sets aside the
+	/// whole `gas_limit` argument for the callee when a non-zero value is supplied.
+	pub gas_required: u64,
+	/// How much balance was deposited and reserved during execution in order to pay for storage.
+	///
+	/// The storage deposit is never actually charged from the caller in case of [`Self::result`]
+	/// is `Err`. This is because on error all storage changes are rolled back.
+	pub storage_deposit: StorageDeposit<Balance>,
+	/// An optional debug message. This message is only filled when explicitly requested
+	/// by the code that calls into the contract. Otherwise it is empty.
+	///
+	/// The contained bytes are valid UTF-8. This is not declared as `String` because
+	/// this type is not allowed within the runtime.
+	///
+	/// Clients should not make any assumptions about the format of the buffer.
+	/// They should just display it as-is. It is **not** only a collection of log lines
+	/// provided by a contract but a formatted buffer with different sections.
+	///
+	/// # Note
+	///
+	/// The debug message is never generated during on-chain execution. It is reserved for
+	/// RPC calls.
+	pub debug_message: Vec<u8>,
+	/// The execution result of the wasm code.
+	pub result: R,
+	/// The events that were emitted during execution. It is an option as event collection is
+	/// only done when explicitly requested together with the debug message.
+	pub events: Option<Vec<EventRecord>>,
+}
+
+impl<R, Balance, EventRecord: Encode> ContractResult<R, Balance, EventRecord> {
+	/// Replaces the contained events by their SCALE encoding.
+	///
+	/// Used to pass events through interfaces that do not know the concrete event type.
+	pub fn encode_events(self) -> ContractResult<R, Balance, Vec<u8>> {
+		ContractResult {
+			gas_consumed: self.gas_consumed,
+			gas_required: self.gas_required,
+			storage_deposit: self.storage_deposit,
+			debug_message: self.debug_message,
+			result: self.result,
+			events: self.events.map(|events| events.iter().map(Encode::encode).collect()),
+		}
+	}
+}
+
 /// Result type of a `bare_call` call.
+pub type ContractExecResult<Balance, EventRecord> =
+	ContractResult<Result<ExecReturnValue, DispatchError>, Balance, EventRecord>;
+
+/// Result type of a `bare_instantiate` call.
+pub type ContractInstantiateResult<AccountId, Balance, EventRecord> =
+	ContractResult<Result<InstantiateReturnValue<AccountId>, DispatchError>, Balance, EventRecord>;
+
+/// Result type of a `bare_upload_code` call.
+pub type CodeUploadResult<CodeHash, Balance> =
+	Result<CodeUploadReturnValue<CodeHash, Balance>, DispatchError>;
+
+/// Result type of `call` in version 1 of the contracts runtime api.
 ///
-/// The result of a contract execution along with a gas consumed.
+/// Only used by clients to decode the result of runtimes that still implement this version.
 #[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
-pub struct ContractExecResult {
+pub struct ContractExecResultV1 {
+	/// The execution result of the wasm code.
 	pub exec_result: ExecResult,
+	/// How much gas was consumed during execution.
 	pub gas_consumed: u64,
 }
 
+impl<Balance: Zero, EventRecord> From<ContractExecResultV1>
+	for ContractExecResult<Balance, EventRecord>
+{
+	fn from(r: ContractExecResultV1) -> Self {
+		// Version 1 neither reported the required gas nor charged a storage deposit.
+		ContractResult {
+			gas_consumed: r.gas_consumed,
+			gas_required: r.gas_consumed,
+			storage_deposit: Default::default(),
+			debug_message: Vec::new(),
+			result: r.exec_result.map_err(|e| e.error),
+			events: None,
+		}
+	}
+}
+
 /// Result type of a `get_storage` call.
 pub type GetStorageResult = Result<Option<Vec<u8>>, ContractAccessError>;
 
-/// Result type of a `rent_projection` call.
-pub type RentProjectionResult<BlockNumber> =
-	Result<RentProjection<BlockNumber>, ContractAccessError>;
-
 /// The possible errors that can happen querying the storage of a contract.
 #[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
 pub enum ContractAccessError {
 	/// The given address doesn't point to a contract.
 	DoesntExist,
-	/// The specified contract is a tombstone and thus cannot have any storage.
-	IsTombstone,
-}
-
-#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
-pub enum RentProjection<BlockNumber> {
-	/// Eviction is projected to happen at the specified block number.
-	EvictionAt(BlockNumber),
-	/// No eviction is scheduled.
-	///
-	/// E.g. Contract accumulated enough funds to offset the rent storage costs.
-	NoEviction,
 }
 
 bitflags! {
@@ -67,6 +141,19 @@ bitflags! {
 	}
 }
 
+bitflags! {
+	/// Flags used by a contract to customize the behaviour of a call or delegate call.
+	#[derive(Encode, Decode)]
+	pub struct CallFlags: u32 {
+		/// Allow the callee to reenter into the current contract.
+		///
+		/// Without this flag any reentrancy into the current contract that originates from
+		/// the callee (or any of its callees) is denied. This includes the first callee:
+		/// A contract cannot call into itself without setting this flag.
+		const ALLOW_REENTRY = 0x0000_0001;
+	}
+}
+
 /// Output of a contract call or instantiation which ran to completion.
 #[derive(PartialEq, Eq, Encode, Decode, RuntimeDebug)]
 pub struct ExecReturnValue {
@@ -83,6 +170,111 @@ impl ExecReturnValue {
 	}
 }
 
+/// The result of a successful contract instantiation.
+#[derive(PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct InstantiateReturnValue<AccountId> {
+	/// The output of the called constructor.
+	pub result: ExecReturnValue,
+	/// The account id of the new contract.
+	pub account_id: AccountId,
+}
+
+/// The result of succesfully uploading a contract.
+#[derive(PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct CodeUploadReturnValue<CodeHash, Balance> {
+	/// The key under which the new code is stored.
+	pub code_hash: CodeHash,
+	/// The deposit that was reserved at the caller. Is zero when the code already existed.
+	pub deposit: Balance,
+}
+
+/// Reference to an existing code hash or a new wasm module.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum Code<Hash> {
+	/// A wasm module as raw bytes.
+	Upload(Vec<u8>),
+	/// The code hash of an on-chain wasm blob.
+	Existing(Hash),
+}
+
+/// The amount of balance that was either charged or refunded in order to pay for storage.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug, Clone)]
+pub enum StorageDeposit<Balance> {
+	/// The transaction reduced storage consumption.
+	///
+	/// This means that the specified amount of balance was transferred from the involved
+	/// contracts to the call origin.
+	Refund(Balance),
+	/// The transaction increased overall storage usage.
+	///
+	/// This means that the specified amount of balance was transferred from the call origin
+	/// to the contracts involved.
+	Charge(Balance),
+}
+
+impl<Balance: Zero> Default for StorageDeposit<Balance> {
+	fn default() -> Self {
+		Self::Charge(Zero::zero())
+	}
+}
+
+impl<Balance: Zero + Copy> StorageDeposit<Balance> {
+	/// Returns how much balance is charged or `0` in case of a refund.
+	pub fn charge_or_zero(&self) -> Balance {
+		match self {
+			Self::Charge(amount) => *amount,
+			Self::Refund(_) => Zero::zero(),
+		}
+	}
+
+	pub fn is_zero(&self) -> bool {
+		match self {
+			Self::Charge(amount) => amount.is_zero(),
+			Self::Refund(amount) => amount.is_zero(),
+		}
+	}
+}
+
+impl<Balance: Saturating + Ord + Copy> StorageDeposit<Balance> {
+	/// Add two deposits together and return the result.
+	///
+	/// A charge and a refund cancel each other out.
+	pub fn saturating_add(&self, rhs: &Self) -> Self {
+		use StorageDeposit::*;
+		match (self, rhs) {
+			(Charge(lhs), Charge(rhs)) => Charge(lhs.saturating_add(*rhs)),
+			(Refund(lhs), Refund(rhs)) => Refund(lhs.saturating_add(*rhs)),
+			(Charge(lhs), Refund(rhs)) | (Refund(rhs), Charge(lhs)) => if lhs >= rhs {
+				Charge(lhs.saturating_sub(*rhs))
+			} else {
+				Refund(rhs.saturating_sub(*lhs))
+			},
+		}
+	}
+
+	/// Subtract `rhs` from `self` and return the result.
+	///
+	/// This is the inverse of [`Self::saturating_add`].
+	pub fn saturating_sub(&self, rhs: &Self) -> Self {
+		use StorageDeposit::*;
+		let negated = match rhs {
+			Charge(amount) => Refund(*amount),
+			Refund(amount) => Charge(*amount),
+		};
+		self.saturating_add(&negated)
+	}
+
+	/// How much of the `limit` is still available after applying this deposit.
+	///
+	/// A refund increases the amount that can still be charged.
+	pub fn available(&self, limit: &Balance) -> Balance {
+		match self {
+			Self::Charge(amount) => limit.saturating_sub(*amount),
+			Self::Refund(amount) => limit.saturating_add(*amount),
+		}
+	}
+}
+
 /// Origin of the error.
 ///
 /// Call or instantiate both called into other contracts and pass through errors happening
@@ -0,0 +1,50 @@
+;; Call chain extension by passing through input and output of this contract
+(module
+	(import "seal0" "seal_call_chain_extension"
+		(func $seal_call_chain_extension (param i32 i32 i32 i32 i32) (result i32))
+	)
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 16 16))
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok (get_local 0))
+			(unreachable)
+		)
+	)
+
+	;; [0, 4) len of input buffer
+	(data (i32.const 0) "\10")
+
+	;; [4, 20) buffer for input
+
+	;; [20, 24) len of output buffer
+	(data (i32.const 20) "\10")
+
+	;; [24, 40) buffer for output
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 4) (i32.const 0))
+
+		;; the first four bytes of the input are used as func_id and the whole
+		;; input is passed to the chain extension.
+		;; the chain extension returns the func_id as converging value.
+		(call $assert
+			(i32.eq
+				(call $seal_call_chain_extension
+					(i32.load (i32.const 4))	;; func_id
+					(i32.const 4)				;; input_ptr
+					(i32.load (i32.const 0))	;; input_len
+					(i32.const 24)				;; output_ptr
+					(i32.const 20)				;; output_len_ptr
+				)
+				(i32.load (i32.const 4))
+			)
+		)
+
+		(call $seal_return (i32.const 0) (i32.const 24) (i32.load (i32.const 20)))
+	)
+)
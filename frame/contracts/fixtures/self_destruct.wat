@@ -1,7 +1,7 @@
 (module
 	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
 	(import "seal0" "seal_address" (func $seal_address (param i32 i32)))
-	(import "seal0" "seal_call" (func $seal_call (param i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_call_with_flags" (func $seal_call_with_flags (param i32 i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
 	(import "seal0" "seal_terminate" (func $seal_terminate (param i32 i32)))
 	(import "env" "memory" (memory 1 1))
 
@@ -55,7 +55,8 @@
 				;; Recursively call self with empty input data.
 				(call $assert
 					(i32.eq
-						(call $seal_call
+						(call $seal_call_with_flags
+							(i32.const 1)	;; ALLOW_REENTRY is required to call self
 							(i32.const 0)	;; Pointer to own address
 							(i32.const 32)	;; Length of own address
 							(i64.const 0)	;; How much gas to devote for the execution. 0 = all.
@@ -22,7 +22,9 @@ use std::sync::Arc;
 use codec::Codec;
 use jsonrpc_core::{Error, ErrorCode, Result};
 use jsonrpc_derive::rpc;
-use pallet_contracts_primitives::RentProjection;
+use pallet_contracts_primitives::{
+	Code, ContractExecResult, ContractInstantiateResult, ContractResult, StorageDeposit,
+};
 use serde::{Deserialize, Serialize};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
@@ -30,17 +32,15 @@ use sp_core::{Bytes, H256};
 use sp_rpc::number;
 use sp_runtime::{
 	generic::BlockId,
-	traits::{Block as BlockT, Header as HeaderT},
+	traits::{Block as BlockT, Zero},
 	DispatchError,
 };
 use std::convert::TryInto;
-use pallet_contracts_primitives::ContractExecResult;
 
 pub use pallet_contracts_rpc_runtime_api::ContractsApi as ContractsRuntimeApi;
 
 const RUNTIME_ERROR: i64 = 1;
 const CONTRACT_DOESNT_EXIST: i64 = 2;
-const CONTRACT_IS_A_TOMBSTONE: i64 = 3;
 
 /// A rough estimate of how much gas a decent hardware consumes per second,
 /// using native execution.
@@ -63,11 +63,6 @@ impl From<ContractAccessError> for Error {
 				message: "The specified contract doesn't exist.".into(),
 				data: None,
 			},
-			IsTombstone => Error {
-				code: ErrorCode::ServerError(CONTRACT_IS_A_TOMBSTONE),
-				message: "The contract is a tombstone and doesn't have any storage.".into(),
-				data: None,
-			},
 		}
 	}
 }
@@ -81,57 +76,183 @@ pub struct CallRequest<AccountId, Balance> {
 	dest: AccountId,
 	value: Balance,
 	gas_limit: number::NumberOrHex,
+	storage_deposit_limit: Option<Balance>,
 	input_data: Bytes,
 }
 
+/// A struct that encodes RPC parameters required to instantiate a new smart-contract.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct InstantiateRequest<AccountId, Balance, Hash> {
+	origin: AccountId,
+	endowment: Balance,
+	gas_limit: number::NumberOrHex,
+	storage_deposit_limit: Option<Balance>,
+	code: RpcCode<Hash>,
+	data: Bytes,
+	salt: Bytes,
+}
+
+/// A struct that encodes RPC parameters required for uploading code.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct CodeUploadRequest<AccountId, Balance> {
+	origin: AccountId,
+	code: Bytes,
+	storage_deposit_limit: Option<Balance>,
+}
+
+/// Reference to an existing code hash or a new wasm module.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub enum RpcCode<Hash> {
+	/// A wasm module as raw bytes.
+	Upload(Bytes),
+	/// The code hash of an on-chain wasm blob.
+	Existing(Hash),
+}
+
+impl<Hash> From<RpcCode<Hash>> for Code<Hash> {
+	fn from(code: RpcCode<Hash>) -> Self {
+		match code {
+			RpcCode::Upload(bytes) => Code::Upload(bytes.to_vec()),
+			RpcCode::Existing(hash) => Code::Existing(hash),
+		}
+	}
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "camelCase")]
-struct RpcContractExecSuccess {
+pub struct RpcContractExecSuccess {
 	/// The return flags. See `pallet_contracts_primitives::ReturnFlags`.
 	flags: u32,
 	/// Data as returned by the contract.
 	data: Bytes,
 }
 
-/// An RPC serializable result of contract execution
 #[derive(Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "camelCase")]
-pub struct RpcContractExecResult {
+pub struct RpcContractInstantiateSuccess<AccountId> {
+	/// The output of the called constructor.
+	result: RpcContractExecSuccess,
+	/// The account id of the new contract.
+	account_id: AccountId,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcCodeUploadSuccess<Hash, Balance> {
+	/// The key under which the new code is stored.
+	code_hash: Hash,
+	/// The deposit that was reserved at the caller. Is zero when the code already existed.
+	deposit: Balance,
+}
+
+/// The amount of balance that was either charged or refunded in order to pay for storage.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcStorageDeposit<Balance> {
+	/// The specified amount of balance was transferred to the call origin.
+	Refund(Balance),
+	/// The specified amount of balance was transferred from the call origin.
+	Charge(Balance),
+}
+
+impl<Balance> From<StorageDeposit<Balance>> for RpcStorageDeposit<Balance> {
+	fn from(deposit: StorageDeposit<Balance>) -> Self {
+		match deposit {
+			StorageDeposit::Refund(amount) => RpcStorageDeposit::Refund(amount),
+			StorageDeposit::Charge(amount) => RpcStorageDeposit::Charge(amount),
+		}
+	}
+}
+
+/// An RPC serializable result of a contract dry-run.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcContractResult<R, Balance> {
 	/// How much gas was consumed by the call. In case of an error this is the amount
 	/// that was used up until the error occurred.
 	gas_consumed: u64,
+	/// How much gas is required as gas limit in order to execute this call.
+	gas_required: u64,
+	/// How much balance was charged from or refunded to the origin in order to pay for storage.
+	storage_deposit: RpcStorageDeposit<Balance>,
 	/// Additional dynamic human readable error information for debugging. An empty string
 	/// indicates that no additional information is available.
 	debug_message: String,
+	/// The SCALE encoded events that were emitted during execution.
+	events: Option<Vec<Bytes>>,
 	/// Indicates whether the contract execution was successful or not.
-	result: std::result::Result<RpcContractExecSuccess, DispatchError>,
+	result: std::result::Result<R, DispatchError>,
 }
 
-impl From<ContractExecResult> for RpcContractExecResult {
-	fn from(r: ContractExecResult) -> Self {
-		match r.exec_result {
-			Ok(val) => RpcContractExecResult {
-				gas_consumed: r.gas_consumed,
-				debug_message: String::new(),
-				result: Ok(RpcContractExecSuccess {
-					flags: val.flags.bits(),
-					data: val.data.into(),
-				}),
-			},
-			Err(err) => RpcContractExecResult {
-				gas_consumed: r.gas_consumed,
-				debug_message: String::new(),
-				result: Err(err.error),
-			},
+/// An RPC serializable result of contract execution
+pub type RpcContractExecResult<Balance> = RpcContractResult<RpcContractExecSuccess, Balance>;
+
+/// An RPC serializable result of contract instantiation.
+pub type RpcContractInstantiateResult<AccountId, Balance> =
+	RpcContractResult<RpcContractInstantiateSuccess<AccountId>, Balance>;
+
+/// An RPC serializable result of a code upload.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcCodeUploadResult<Hash, Balance> {
+	/// Indicates whether the code upload was successful or not.
+	result: std::result::Result<RpcCodeUploadSuccess<Hash, Balance>, DispatchError>,
+}
+
+impl<R, Balance> RpcContractResult<R, Balance> {
+	fn from_result<T>(
+		r: ContractResult<std::result::Result<T, DispatchError>, Balance, Vec<u8>>,
+		map: impl FnOnce(T) -> R,
+	) -> Self {
+		RpcContractResult {
+			gas_consumed: r.gas_consumed,
+			gas_required: r.gas_required,
+			storage_deposit: r.storage_deposit.into(),
+			debug_message: String::from_utf8_lossy(&r.debug_message).into_owned(),
+			events: r.events.map(|events| events.into_iter().map(Into::into).collect()),
+			result: r.result.map(map),
 		}
 	}
 }
 
+impl<Balance> From<ContractExecResult<Balance, Vec<u8>>> for RpcContractExecResult<Balance> {
+	fn from(r: ContractExecResult<Balance, Vec<u8>>) -> Self {
+		Self::from_result(r, |val| RpcContractExecSuccess {
+			flags: val.flags.bits(),
+			data: val.data.into(),
+		})
+	}
+}
+
+impl<AccountId, Balance> From<ContractInstantiateResult<AccountId, Balance, Vec<u8>>>
+	for RpcContractInstantiateResult<AccountId, Balance>
+{
+	fn from(r: ContractInstantiateResult<AccountId, Balance, Vec<u8>>) -> Self {
+		Self::from_result(r, |val| RpcContractInstantiateSuccess {
+			result: RpcContractExecSuccess {
+				flags: val.result.flags.bits(),
+				data: val.result.data.into(),
+			},
+			account_id: val.account_id,
+		})
+	}
+}
+
 /// Contracts RPC methods.
 #[rpc]
-pub trait ContractsApi<BlockHash, BlockNumber, AccountId, Balance> {
+pub trait ContractsApi<BlockHash, AccountId, Balance, Hash> {
 	/// Executes a call to a contract.
 	///
 	/// This call is performed locally without submitting any transactions. Thus executing this
@@ -143,7 +264,33 @@ pub trait ContractsApi<BlockHash, BlockNumber, AccountId, Balance> {
 		&self,
 		call_request: CallRequest<AccountId, Balance>,
 		at: Option<BlockHash>,
-	) -> Result<RpcContractExecResult>;
+	) -> Result<RpcContractExecResult<Balance>>;
+
+	/// Instantiate a new contract.
+	///
+	/// This call is performed locally without submitting any transactions. Thus the contract
+	/// is not actually created.
+	///
+	/// This method is useful for UIs to dry-run contract instantiations.
+	#[rpc(name = "contracts_instantiate")]
+	fn instantiate(
+		&self,
+		instantiate_request: InstantiateRequest<AccountId, Balance, Hash>,
+		at: Option<BlockHash>,
+	) -> Result<RpcContractInstantiateResult<AccountId, Balance>>;
+
+	/// Upload new code without instantiating a contract from it.
+	///
+	/// This call is performed locally without submitting any transactions. Thus executing this
+	/// won't change any state.
+	///
+	/// This method is useful for UIs to dry-run code upload.
+	#[rpc(name = "contracts_upload_code")]
+	fn upload_code(
+		&self,
+		upload_request: CodeUploadRequest<AccountId, Balance>,
+		at: Option<BlockHash>,
+	) -> Result<RpcCodeUploadResult<Hash, Balance>>;
 
 	/// Returns the value under a specified storage `key` in a contract given by `address` param,
 	/// or `None` if it is not set.
@@ -154,19 +301,6 @@ pub trait ContractsApi<BlockHash, BlockNumber, AccountId, Balance> {
 		key: H256,
 		at: Option<BlockHash>,
 	) -> Result<Option<Bytes>>;
-
-	/// Returns the projected time a given contract will be able to sustain paying its rent.
-	///
-	/// The returned projection is relevant for the given block, i.e. it is as if the contract was
-	/// accessed at the beginning of that block.
-	///
-	/// Returns `None` if the contract is exempted from rent.
-	#[rpc(name = "contracts_rentProjection")]
-	fn rent_projection(
-		&self,
-		address: AccountId,
-		at: Option<BlockHash>,
-	) -> Result<Option<BlockNumber>>;
 }
 
 /// An implementation of contract specific RPC methods.
@@ -184,30 +318,26 @@ impl<C, B> Contracts<C, B> {
 		}
 	}
 }
-impl<C, Block, AccountId, Balance>
+impl<C, Block, AccountId, Balance, Hash>
 	ContractsApi<
 		<Block as BlockT>::Hash,
-		<<Block as BlockT>::Header as HeaderT>::Number,
 		AccountId,
 		Balance,
+		Hash,
 	> for Contracts<C, Block>
 where
 	Block: BlockT,
 	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
-	C::Api: ContractsRuntimeApi<
-		Block,
-		AccountId,
-		Balance,
-		<<Block as BlockT>::Header as HeaderT>::Number,
-	>,
+	C::Api: ContractsRuntimeApi<Block, AccountId, Balance, Hash>,
 	AccountId: Codec,
-	Balance: Codec,
+	Balance: Codec + Zero,
+	Hash: Codec,
 {
 	fn call(
 		&self,
 		call_request: CallRequest<AccountId, Balance>,
 		at: Option<<Block as BlockT>::Hash>,
-	) -> Result<RpcContractExecResult> {
+	) -> Result<RpcContractExecResult<Balance>> {
 		let api = self.client.runtime_api();
 		let at = BlockId::hash(at.unwrap_or_else(||
 			// If the block hash is not supplied assume the best block.
@@ -218,33 +348,112 @@ where
 			dest,
 			value,
 			gas_limit,
+			storage_deposit_limit,
 			input_data,
 		} = call_request;
 
-		// Make sure that gas_limit fits into 64 bits.
-		let gas_limit: u64 = gas_limit.try_into().map_err(|_| Error {
-			code: ErrorCode::InvalidParams,
-			message: format!("{:?} doesn't fit in 64 bit unsigned value", gas_limit),
-			data: None,
-		})?;
-
-		let max_gas_limit = 5 * GAS_PER_SECOND;
-		if gas_limit > max_gas_limit {
-			return Err(Error {
-				code: ErrorCode::InvalidParams,
-				message: format!(
-					"Requested gas limit is greater than maximum allowed: {} > {}",
-					gas_limit, max_gas_limit
-				),
-				data: None,
-			});
+		let gas_limit = limit_gas(gas_limit)?;
+
+		// runtimes before version 3 of the api have no storage deposit limit and version 1
+		// returns the original result type.
+		let version = contracts_api_version::<_, Block, AccountId, Balance, Hash>(&*api, &at)?;
+		#[allow(deprecated)]
+		let exec_result = match version {
+			1 => api
+				.call_before_version_2(&at, origin, dest, value, gas_limit, input_data.to_vec())
+				.map(Into::into),
+			2 => api
+				.call_before_version_3(&at, origin, dest, value, gas_limit, input_data.to_vec()),
+			_ => api
+				.call(&at, origin, dest, value, gas_limit, storage_deposit_limit, input_data.to_vec()),
+		}.map_err(runtime_error_into_rpc_err)?;
+
+		Ok(exec_result.into())
+	}
+
+	fn instantiate(
+		&self,
+		instantiate_request: InstantiateRequest<AccountId, Balance, Hash>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<RpcContractInstantiateResult<AccountId, Balance>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+		let InstantiateRequest {
+			origin,
+			endowment,
+			gas_limit,
+			storage_deposit_limit,
+			code,
+			data,
+			salt,
+		} = instantiate_request;
+
+		let gas_limit = limit_gas(gas_limit)?;
+
+		// runtimes before version 3 of the api have no storage deposit limit.
+		let version = contracts_api_version::<_, Block, AccountId, Balance, Hash>(&*api, &at)?;
+		#[allow(deprecated)]
+		let result = match version {
+			1 => return Err(unsupported_by_runtime("instantiate", version)),
+			2 => api.instantiate_before_version_3(
+				&at,
+				origin,
+				endowment,
+				gas_limit,
+				code.into(),
+				data.to_vec(),
+				salt.to_vec(),
+			),
+			_ => api.instantiate(
+				&at,
+				origin,
+				endowment,
+				gas_limit,
+				storage_deposit_limit,
+				code.into(),
+				data.to_vec(),
+				salt.to_vec(),
+			),
+		}.map_err(runtime_error_into_rpc_err)?;
+
+		Ok(result.into())
+	}
+
+	fn upload_code(
+		&self,
+		upload_request: CodeUploadRequest<AccountId, Balance>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<RpcCodeUploadResult<Hash, Balance>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+		let CodeUploadRequest {
+			origin,
+			code,
+			storage_deposit_limit,
+		} = upload_request;
+
+		// `upload_code` took neither an origin nor a deposit limit before version 3.
+		let version = contracts_api_version::<_, Block, AccountId, Balance, Hash>(&*api, &at)?;
+		if version < 3 {
+			return Err(unsupported_by_runtime("upload_code", version));
 		}
 
-		let exec_result = api
-			.call(&at, origin, dest, value, gas_limit, input_data.to_vec())
+		let result = api
+			.upload_code(&at, origin, code.to_vec(), storage_deposit_limit)
 			.map_err(runtime_error_into_rpc_err)?;
 
-		Ok(exec_result.into())
+		Ok(RpcCodeUploadResult {
+			result: result.map(|val| RpcCodeUploadSuccess {
+				code_hash: val.code_hash,
+				deposit: val.deposit,
+			}),
+		})
 	}
 
 	fn get_storage(
@@ -266,27 +475,62 @@ where
 
 		Ok(result)
 	}
+}
 
-	fn rent_projection(
-		&self,
-		address: AccountId,
-		at: Option<<Block as BlockT>::Hash>,
-	) -> Result<Option<<<Block as BlockT>::Header as HeaderT>::Number>> {
-		let api = self.client.runtime_api();
-		let at = BlockId::hash(at.unwrap_or_else(||
-			// If the block hash is not supplied assume the best block.
-			self.client.info().best_hash));
+/// Returns the version of the contracts api implemented by the runtime at `at`.
+fn contracts_api_version<Api, Block, AccountId, Balance, Hash>(
+	api: &Api,
+	at: &BlockId<Block>,
+) -> Result<u32>
+where
+	Block: BlockT,
+	Api: sp_api::ApiExt<Block>,
+	AccountId: Codec,
+	Balance: Codec,
+	Hash: Codec,
+{
+	let version = std::cell::Cell::new(None);
+	api.has_api_with::<dyn ContractsRuntimeApi<Block, AccountId, Balance, Hash, Error = ()>, _>(
+		at,
+		|v| { version.set(Some(v)); true },
+	).map_err(runtime_error_into_rpc_err)?;
+	version.get().ok_or_else(|| Error {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: "Runtime doesn't implement the contracts api".into(),
+		data: None,
+	})
+}
 
-		let result = api
-			.rent_projection(&at, address)
-			.map_err(runtime_error_into_rpc_err)?
-			.map_err(ContractAccessError)?;
+/// The error returned for a `method` that isn't part of `version` of the contracts api.
+fn unsupported_by_runtime(method: &str, version: u32) -> Error {
+	Error {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: format!("`{}` is not supported by version {} of the contracts api", method, version),
+		data: None,
+	}
+}
 
-		Ok(match result {
-			RentProjection::NoEviction => None,
-			RentProjection::EvictionAt(block_num) => Some(block_num),
-		})
+/// Makes sure that `gas_limit` fits into 64 bits and is not above the maximum allowed.
+fn limit_gas(gas_limit: number::NumberOrHex) -> Result<u64> {
+	let gas_limit: u64 = gas_limit.try_into().map_err(|_| Error {
+		code: ErrorCode::InvalidParams,
+		message: format!("{:?} doesn't fit in 64 bit unsigned value", gas_limit),
+		data: None,
+	})?;
+
+	let max_gas_limit = 5 * GAS_PER_SECOND;
+	if gas_limit > max_gas_limit {
+		return Err(Error {
+			code: ErrorCode::InvalidParams,
+			message: format!(
+				"Requested gas limit is greater than maximum allowed: {} > {}",
+				gas_limit, max_gas_limit
+			),
+			data: None,
+		});
 	}
+
+	Ok(gas_limit)
 }
 
 /// Converts a runtime trap into an RPC error.
@@ -316,16 +560,72 @@ mod tests {
 		}
 		"#).unwrap();
 		assert_eq!(req.gas_limit.into_u256(), U256::from(0xe8d4a51000u64));
+		assert_eq!(req.storage_deposit_limit, None);
+	}
+
+	#[test]
+	fn instantiate_request_should_serialize_deserialize_properly() {
+		type Req = InstantiateRequest<String, u128, String>;
+		let req: Req = serde_json::from_str(r#"
+		{
+			"origin": "5CiPPseXPECbkjWCa6MnjNokrgYjMqmKndv2rSnekmSK2DjL",
+			"endowment": 0,
+			"gasLimit": 1000000000000,
+			"storageDepositLimit": 5000,
+			"code": { "existing": "0x1122" },
+			"data": "0x4299",
+			"salt": "0x9988"
+		}
+		"#).unwrap();
+
+		assert_eq!(req.origin, "5CiPPseXPECbkjWCa6MnjNokrgYjMqmKndv2rSnekmSK2DjL");
+		assert_eq!(req.endowment, 0);
+		assert_eq!(req.gas_limit.into_u256(), U256::from(1000000000000u64));
+		assert_eq!(req.storage_deposit_limit, Some(5000));
+		assert_eq!(&*req.data, [0x42, 0x99].as_ref());
+		assert_eq!(&*req.salt, [0x99, 0x88].as_ref());
+		let code = match req.code {
+			RpcCode::Existing(code) => code,
+			_ => panic!("json encoded an existing code hash"),
+		};
+		assert_eq!(&code, "0x1122");
+	}
+
+	#[test]
+	fn code_upload_request_should_serialize_deserialize_properly() {
+		type Req = CodeUploadRequest<String, u128>;
+		let req: Req = serde_json::from_str(r#"
+		{
+			"origin": "5CiPPseXPECbkjWCa6MnjNokrgYjMqmKndv2rSnekmSK2DjL",
+			"code": "0x8c97db39",
+			"storageDepositLimit": 5000
+		}
+		"#).unwrap();
+		assert_eq!(req.origin, "5CiPPseXPECbkjWCa6MnjNokrgYjMqmKndv2rSnekmSK2DjL");
+		assert_eq!(&*req.code, [0x8c, 0x97, 0xdb, 0x39].as_ref());
+		assert_eq!(req.storage_deposit_limit, Some(5000));
 	}
 
 	#[test]
 	fn result_should_serialize_deserialize_properly() {
 		fn test(expected: &str) {
-			let res: RpcContractExecResult = serde_json::from_str(expected).unwrap();
+			let res: RpcContractExecResult<u128> = serde_json::from_str(expected).unwrap();
+			let actual = serde_json::to_string(&res).unwrap();
+			assert_eq!(actual, expected);
+		}
+		test(r#"{"gasConsumed":5000,"gasRequired":8000,"storageDeposit":{"charge":42000},"debugMessage":"helpOk","events":null,"result":{"Ok":{"flags":5,"data":"0x1234"}}}"#);
+		test(r#"{"gasConsumed":3400,"gasRequired":5200,"storageDeposit":{"refund":12000},"debugMessage":"helpErr","events":["0x0102"],"result":{"Err":"BadOrigin"}}"#);
+	}
+
+	#[test]
+	fn instantiate_result_should_serialize_deserialize_properly() {
+		fn test(expected: &str) {
+			let res: RpcContractInstantiateResult<String, u128> =
+				serde_json::from_str(expected).unwrap();
 			let actual = serde_json::to_string(&res).unwrap();
 			assert_eq!(actual, expected);
 		}
-		test(r#"{"gasConsumed":5000,"debugMessage":"helpOk","result":{"Ok":{"flags":5,"data":"0x1234"}}}"#);
-		test(r#"{"gasConsumed":3400,"debugMessage":"helpErr","result":{"Err":"BadOrigin"}}"#);
+		test(r#"{"gasConsumed":5000,"gasRequired":8000,"storageDeposit":{"charge":0},"debugMessage":"helpOk","events":[],"result":{"Ok":{"result":{"flags":5,"data":"0x1234"},"accountId":"5CiPP"}}}"#);
+		test(r#"{"gasConsumed":3400,"gasRequired":5200,"storageDeposit":{"charge":0},"debugMessage":"helpErr","events":null,"result":{"Err":"BadOrigin"}}"#);
 	}
 }
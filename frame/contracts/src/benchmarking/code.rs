@@ -25,7 +25,7 @@
 //! compiles it down into a `WasmModule` that can be used as a contract's code.
 
 use crate::Config;
-use crate::Module as Contracts;
+use crate::Pallet as Contracts;
 
 use parity_wasm::elements::{Instruction, Instructions, FuncBody, ValueType, BlockType};
 use pwasm_utils::stack_height::inject_limiter;
@@ -320,6 +320,49 @@ where
 		.into()
 	}
 
+	/// Creates a wasm module that calls the imported signature verification function named
+	/// `name` `repeat` times. The `signature` and `pub_key` are placed into memory in front
+	/// of a zeroed message of size `message_len`. Verification functions have the signature
+	/// (signature_ptr: u32, pub_key_ptr: u32, message_len: u32, message_ptr: u32) -> u32
+	pub fn verifier(
+		name: &'static str,
+		repeat: u32,
+		message_len: u32,
+		signature: Vec<u8>,
+		pub_key: Vec<u8>,
+	) -> Self {
+		let pub_key_offset = signature.len() as u32;
+		let message_offset = pub_key_offset + pub_key.len() as u32;
+		ModuleDefinition {
+			memory: Some(ImportedMemory::max::<T>()),
+			imported_functions: vec![ImportedFunction {
+				name,
+				params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32],
+				return_type: Some(ValueType::I32),
+			}],
+			data_segments: vec![
+				DataSegment {
+					offset: 0,
+					value: signature,
+				},
+				DataSegment {
+					offset: pub_key_offset,
+					value: pub_key,
+				},
+			],
+			call_body: Some(body::repeated(repeat, &[
+				Instruction::I32Const(0), // signature_ptr
+				Instruction::I32Const(pub_key_offset as i32), // pub_key_ptr
+				Instruction::I32Const(message_len as i32), // message_len
+				Instruction::I32Const(message_offset as i32), // message_ptr
+				Instruction::Call(0),
+				Instruction::Drop,
+			])),
+			.. Default::default()
+		}
+		.into()
+	}
+
 	/// Creates a memory instance for use in a sandbox with dimensions declared in this module
 	/// and adds it to `env`. A reference to that memory is returned so that it can be used to
 	/// access the memory contents from the supervisor.
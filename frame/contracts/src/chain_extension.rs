@@ -0,0 +1,317 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! A mechanism for runtime authors to augment the functionality of contracts.
+//!
+//! The runtime is able to call into any contract and retrieve the result using
+//! [`bare_call`](crate::Pallet::bare_call). However, often it is more straightforward
+//! to allow the reverse behaviour: The contract calls into the runtime. We call the latter
+//! a "chain extension" because it allows the chain to extend the set of functions that are
+//! callable by a contract.
+//!
+//! In order to create a chain extension the runtime author implements the [`ChainExtension`]
+//! trait and declares it in this pallet's [configuration trait](crate::Config). There is an
+//! implementation on `()` which can be used to signal that no chain extension is available.
+//!
+//! Contracts invoke the extension through the `seal_call_chain_extension` host function.
+//! Its `func_id` argument is passed to [`ChainExtension::call`] verbatim.
+//!
+//! # Composition
+//!
+//! Multiple extensions can be combined by passing a tuple of types implementing
+//! [`RegisteredChainExtension`] as the configured extension. In this case the upper 16 bits
+//! of `func_id` select the extension whose [`ID`](RegisteredChainExtension::ID) matches
+//! while the lower 16 bits are left to the extension to determine which of its functions
+//! to realize (see [`Environment::func_id`]).
+//!
+//! # Security
+//!
+//! The chain author alone is responsible for the security of the chain extension.
+//! This includes avoiding the exposure of exploitable functions and charging the
+//! appropriate amount of weight. In order to do so benchmarks must be written and the
+//! [`charge_weight`](Environment::charge_weight) function must be called **before**
+//! carrying out any action that causes the consumption of the chargeable weight.
+//!
+//! Only the overhead of calling into the extension is charged by this pallet. Its benchmark
+//! calls `func_id` 0 with empty input and output buffers, which must not fail.
+
+use crate::{
+	Config, Error,
+	exec::Ext,
+	wasm::{Runtime, RuntimeToken},
+};
+use codec::Decode;
+use frame_support::weights::Weight;
+use sp_runtime::DispatchError;
+use sp_std::prelude::*;
+
+pub use frame_system::Config as SysConfig;
+pub use pallet_contracts_primitives::ReturnFlags;
+pub use sp_core::crypto::UncheckedFrom;
+
+/// Result that returns a [`DispatchError`] on error.
+pub type Result<T> = sp_std::result::Result<T, DispatchError>;
+
+/// A trait used to extend the set of contract callable functions.
+///
+/// In order to create a custom chain extension this trait must be implemented and supplied
+/// to the pallet contracts configuration trait as the associated type of the same name.
+/// Consult the [module documentation](self) for a general explanation of chain extensions.
+pub trait ChainExtension<C: Config> {
+	/// Call the chain extension logic.
+	///
+	/// This is the only function that needs to be implemented in order to write a
+	/// chain extension. It is called whenever a contract calls the `seal_call_chain_extension`
+	/// imported wasm function.
+	///
+	/// # Parameters
+	/// - `func_id`: The first argument to `seal_call_chain_extension`. Usually used to
+	///		determine which function to realize.
+	/// - `env`: Access to the remaining arguments and the execution environment.
+	///
+	/// # Return
+	///
+	/// In case of `Err` the contract execution is immediately suspended and the passed error
+	/// is returned to the caller. Otherwise the value of [`RetVal`] determines the exit
+	/// behaviour.
+	fn call<E>(func_id: u32, env: Environment<E>) -> Result<RetVal>
+	where
+		E: Ext<T = C>,
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>;
+
+	/// Determines whether chain extensions are enabled for this chain.
+	///
+	/// The default implementation returns `true`. In case of `false` the deployment of
+	/// a contract that references `seal_call_chain_extension` will be denied and calling this
+	/// function will return [`NoChainExtension`](Error::NoChainExtension) without first calling
+	/// into [`call`](Self::call).
+	fn enabled() -> bool {
+		true
+	}
+}
+
+/// A [`ChainExtension`] that can be composed with other extensions using a tuple.
+///
+/// The `ID` is matched against the upper 16 bits of the `func_id` that is passed to
+/// `seal_call_chain_extension`. It must therefore be unique amongst all extensions
+/// that are part of the same tuple.
+pub trait RegisteredChainExtension<C: Config>: ChainExtension<C> {
+	/// The extension's identifier.
+	const ID: u16;
+}
+
+/// Implementation that indicates that no chain extension is available.
+impl<C: Config> ChainExtension<C> for () {
+	fn call<E>(_func_id: u32, _env: Environment<E>) -> Result<RetVal>
+	where
+		E: Ext<T = C>,
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+	{
+		// Never called since [`Self::enabled()`] is set to `false`. Because we want to
+		// avoid panics at all costs we supply a sensible error value here instead
+		// of an `unimplemented!`.
+		Err(Error::<E::T>::NoChainExtension.into())
+	}
+
+	fn enabled() -> bool {
+		false
+	}
+}
+
+macro_rules! impl_chain_extension_for_tuples {
+	( $( $ext:ident ),+ ) => {
+		impl<C: Config, $( $ext: RegisteredChainExtension<C> ),+> ChainExtension<C>
+			for ( $( $ext, )+ )
+		{
+			fn call<E>(func_id: u32, env: Environment<E>) -> Result<RetVal>
+			where
+				E: Ext<T = C>,
+				<E::T as SysConfig>::AccountId:
+					UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+			{
+				let id = env.ext_id();
+				$(
+					if $ext::ID == id && $ext::enabled() {
+						return $ext::call(func_id, env);
+					}
+				)+
+				Err(Error::<E::T>::NoChainExtension.into())
+			}
+
+			fn enabled() -> bool {
+				false $( || $ext::enabled() )+
+			}
+		}
+	}
+}
+
+impl_chain_extension_for_tuples!(T1);
+impl_chain_extension_for_tuples!(T1, T2);
+impl_chain_extension_for_tuples!(T1, T2, T3);
+impl_chain_extension_for_tuples!(T1, T2, T3, T4);
+impl_chain_extension_for_tuples!(T1, T2, T3, T4, T5);
+impl_chain_extension_for_tuples!(T1, T2, T3, T4, T5, T6);
+impl_chain_extension_for_tuples!(T1, T2, T3, T4, T5, T6, T7);
+impl_chain_extension_for_tuples!(T1, T2, T3, T4, T5, T6, T7, T8);
+
+/// Determines the exit behaviour and return value of a chain extension.
+pub enum RetVal {
+	/// The chain extensions returns the supplied value to its calling contract.
+	Converging(u32),
+	/// The control does **not** return to the calling contract.
+	///
+	/// Use this to stop the execution of the contract when the chain extension returns.
+	/// The semantic is the same as for calling `seal_return`: The control returns to
+	/// the caller of the currently executing contract yielding the supplied buffer and
+	/// flags.
+	Diverging{flags: ReturnFlags, data: Vec<u8>},
+}
+
+/// Grants the chain extension access to its parameters and execution environment.
+///
+/// The arguments passed to `seal_call_chain_extension` are interpreted as a pointer
+/// to an input buffer (`input_ptr`, `input_len`) and an output buffer
+/// (`output_ptr`, `output_len_ptr`) in contract memory. The functions of this type
+/// only ever access contract memory through those buffers.
+pub struct Environment<'a, 'b, E: Ext> {
+	/// The runtime contains all necessary functions to interact with the running contract.
+	runtime: &'a mut Runtime<'b, E>,
+	/// Verbatim argument passed to `seal_call_chain_extension`.
+	func_id: u32,
+	/// Verbatim argument passed to `seal_call_chain_extension`.
+	input_ptr: u32,
+	/// Verbatim argument passed to `seal_call_chain_extension`.
+	input_len: u32,
+	/// Verbatim argument passed to `seal_call_chain_extension`.
+	output_ptr: u32,
+	/// Verbatim argument passed to `seal_call_chain_extension`.
+	output_len_ptr: u32,
+}
+
+impl<'a, 'b, E: Ext> Environment<'a, 'b, E>
+where
+	<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+{
+	/// Creates a new environment for consumption by a chain extension.
+	///
+	/// It is only available to this crate because only the wasm runtime module needs to
+	/// ever create this type. Chain extensions merely consume it.
+	pub(crate) fn new(
+		runtime: &'a mut Runtime<'b, E>,
+		func_id: u32,
+		input_ptr: u32,
+		input_len: u32,
+		output_ptr: u32,
+		output_len_ptr: u32,
+	) -> Self {
+		Environment {
+			runtime,
+			func_id,
+			input_ptr,
+			input_len,
+			output_ptr,
+			output_len_ptr,
+		}
+	}
+
+	/// The function id within the extension (lower 16 bits of `func_id`).
+	pub fn func_id(&self) -> u16 {
+		(self.func_id & 0x0000_FFFF) as u16
+	}
+
+	/// The id of the extension that is addressed (upper 16 bits of `func_id`).
+	pub fn ext_id(&self) -> u16 {
+		(self.func_id >> 16) as u16
+	}
+
+	/// Charge the passed `amount` of weight from the overall limit.
+	///
+	/// It returns `Ok` when the remaining weight budget is larger than the passed
+	/// `weight`. It returns `Err` otherwise. In this case the chain extension should
+	/// abort the execution and pass through the error.
+	///
+	/// # Note
+	///
+	/// Weight is synonymous with gas in substrate.
+	pub fn charge_weight(&mut self, amount: Weight) -> Result<()> {
+		self.runtime.charge_gas(RuntimeToken::ChainExtension(amount))
+			.map_err(|_| self.runtime.take_supervisor_err())
+	}
+
+	/// Grants access to the execution environment of the current contract call.
+	///
+	/// Consult the functions on the returned type before re-implementing those functions.
+	pub fn ext(&mut self) -> &mut E {
+		self.runtime.ext()
+	}
+
+	/// The length of the input as passed in as `input_len`.
+	///
+	/// A chain extension would use this value to calculate the dynamic part of its
+	/// weight. For example a chain extension that calculates the hash of some passed in
+	/// bytes would use `in_len` to charge the costs of hashing that amount of bytes.
+	pub fn in_len(&self) -> u32 {
+		self.input_len
+	}
+
+	/// Reads `min(max_len, in_len)` from contract memory.
+	///
+	/// This does **not** charge any weight. The caller must make sure that an
+	/// appropriate amount of weight is charged **before** reading from contract memory.
+	/// A chain extension would charge the costs either using `max_len` (worst case
+	/// approximation) or using [`in_len()`](Self::in_len).
+	pub fn read(&mut self, max_len: u32) -> Result<Vec<u8>> {
+		self.runtime.read_sandbox_memory(self.input_ptr, self.input_len.min(max_len))
+			.map_err(|_| self.runtime.take_supervisor_err())
+	}
+
+	/// Reads `in_len` from contract memory and scale decodes it.
+	///
+	/// This function is secure and recommended for all input types of fixed size
+	/// as long as the cost of reading the memory is included in the overall already charged
+	/// weight of the chain extension. Non fixed size types (like everything using `Vec`)
+	/// usually need to use [`in_len()`](Self::in_len) in order to properly charge the
+	/// necessary weight.
+	pub fn read_as<T: Decode>(&mut self) -> Result<T> {
+		self.runtime.read_sandbox_memory_as(self.input_ptr, self.input_len)
+			.map_err(|_| self.runtime.take_supervisor_err())
+	}
+
+	/// Write the supplied buffer to contract memory.
+	///
+	/// If the contract supplied buffer is smaller than the passed `buffer` an `Err` is returned.
+	/// If `allow_skip` is set to true the contract is allowed to skip the copying of the buffer
+	/// by supplying the guard value of `u32::max_value()` as `out_ptr`. The
+	/// `weight_per_byte` is only charged when the write actually happens and is not skipped or
+	/// failed due to a too small output buffer.
+	pub fn write(
+		&mut self,
+		buffer: &[u8],
+		allow_skip: bool,
+		weight_per_byte: Option<Weight>,
+	) -> Result<()> {
+		self.runtime.write_sandbox_output(
+			self.output_ptr,
+			self.output_len_ptr,
+			buffer,
+			allow_skip,
+			|len| {
+				weight_per_byte.map(|w| RuntimeToken::ChainExtension(w.saturating_mul(len.into())))
+			},
+		)
+		.map_err(|_| self.runtime.take_supervisor_err())
+	}
+}
@@ -15,19 +15,21 @@
 // along with Substrate. If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
-	CodeHash, ConfigCache, Event, RawEvent, Config, Module as Contracts,
-	TrieId, BalanceOf, ContractInfo, gas::GasMeter, rent::Rent, storage::{self, Storage},
+	CodeHash, ConfigCache, Event, Config, Pallet as Contracts,
+	TrieId, BalanceOf, gas::GasMeter, storage::{Storage, meter::{Diff, StorageMeter}},
 	Error, ContractInfoOf
 };
 use sp_core::crypto::UncheckedFrom;
-use sp_std::prelude::*;
-use sp_runtime::traits::{Bounded, Zero, Convert, Saturating};
+use sp_std::{prelude::*, cell::RefCell};
+use sp_runtime::traits::{Zero, Convert, Saturating};
+use codec::Encode;
 use frame_support::{
-	dispatch::DispatchError,
-	traits::{ExistenceRequirement, Currency, Time, Randomness},
+	dispatch::{DispatchError, DispatchResultWithPostInfo, Dispatchable},
+	traits::{ExistenceRequirement, Currency, Time, Randomness, Filter, OriginTrait},
 	weights::Weight,
-	ensure, StorageMap,
+	ensure,
 };
+use frame_system::RawOrigin;
 use pallet_contracts_primitives::{ErrorOrigin, ExecError, ExecReturnValue, ExecResult, ReturnFlags};
 
 pub type AccountIdOf<T> = <T as frame_system::Config>::AccountId;
@@ -99,32 +101,52 @@ pub trait Ext {
 	) -> Result<(), DispatchError>;
 
 	/// Call (possibly transferring some amount of funds) into the specified account.
+	///
+	/// Unless `allows_reentry` is set the callee (or any of its callees) cannot call back
+	/// into the current contract.
 	fn call(
 		&mut self,
 		to: &AccountIdOf<Self::T>,
 		value: BalanceOf<Self::T>,
 		gas_meter: &mut GasMeter<Self::T>,
 		input_data: Vec<u8>,
+		allows_reentry: bool,
 	) -> ExecResult;
 
-	/// Restores the given destination contract sacrificing the current one.
+	/// Execute the code stored under `code_hash` in the context of the current contract.
 	///
-	/// Since this function removes the self contract eagerly, if succeeded, no further actions should
-	/// be performed on this `Ext` instance.
-	///
-	/// This function will fail if the same contract is present
-	/// on the contract call stack.
-	fn restore_to(
+	/// The executed code sees the same address, caller, value and storage as the current
+	/// contract. See [`Self::call`] for a description of `allows_reentry`.
+	fn delegate_call(
 		&mut self,
-		dest: AccountIdOf<Self::T>,
 		code_hash: CodeHash<Self::T>,
-		rent_allowance: BalanceOf<Self::T>,
-		delta: Vec<StorageKey>,
-	) -> Result<(), DispatchError>;
+		gas_meter: &mut GasMeter<Self::T>,
+		input_data: Vec<u8>,
+		allows_reentry: bool,
+	) -> ExecResult;
+
+	/// Replace the code of the current contract with the code stored under `hash`.
+	///
+	/// The currently executing code is not affected. The new code is used starting with
+	/// the next call into the contract.
+	fn set_code_hash(&mut self, hash: CodeHash<Self::T>) -> Result<(), DispatchError>;
 
 	/// Returns a reference to the account id of the caller.
 	fn caller(&self) -> &AccountIdOf<Self::T>;
 
+	/// Returns `true` if the caller is the origin of the call stack, i.e. a plain account
+	/// and not a contract.
+	fn caller_is_origin(&self) -> bool;
+
+	/// Returns the code hash of the contract at `address` or `None` if it is no contract.
+	fn code_hash(&self, address: &AccountIdOf<Self::T>) -> Option<CodeHash<Self::T>>;
+
+	/// Returns the code hash of the current contract.
+	///
+	/// When executing a delegate call this is the code of the contract and not the code
+	/// that is currently executing.
+	fn own_code_hash(&self) -> CodeHash<Self::T>;
+
 	/// Returns a reference to the account id of the current contract.
 	fn address(&self) -> &AccountIdOf<Self::T>;
 
@@ -142,9 +164,6 @@ pub trait Ext {
 	/// Returns the minimum balance that is required for creating an account.
 	fn minimum_balance(&self) -> BalanceOf<Self::T>;
 
-	/// Returns the deposit required to create a tombstone upon contract eviction.
-	fn tombstone_deposit(&self) -> BalanceOf<Self::T>;
-
 	/// Returns a random number for the current block with the given subject.
 	fn random(&self, subject: &[u8]) -> SeedOf<Self::T>;
 
@@ -153,12 +172,6 @@ pub trait Ext {
 	/// There should not be any duplicates in `topics`.
 	fn deposit_event(&mut self, topics: Vec<TopicOf<Self::T>>, data: Vec<u8>);
 
-	/// Set rent allowance of the contract
-	fn set_rent_allowance(&mut self, rent_allowance: BalanceOf<Self::T>);
-
-	/// Rent allowance of the contract
-	fn rent_allowance(&self) -> BalanceOf<Self::T>;
-
 	/// Returns the current block number.
 	fn block_number(&self) -> BlockNumberOf<Self::T>;
 
@@ -167,6 +180,21 @@ pub trait Ext {
 
 	/// Returns the price for the specified amount of weight.
 	fn get_weight_price(&self, weight: Weight) -> BalanceOf<Self::T>;
+
+	/// Append a string to the debug buffer.
+	///
+	/// It is added as-is without any additional new line.
+	///
+	/// This is a no-op if debug message recording is disabled which is always the case
+	/// when the code is executing on-chain.
+	///
+	/// Returns `true` if debug message recording is enabled. Otherwise `false` is returned.
+	fn append_debug_buffer(&mut self, msg: &str) -> bool;
+
+	/// Dispatch the supplied runtime call with the current contract as signed origin.
+	///
+	/// The call is subject to the [`Config::CallFilter`] of this module.
+	fn call_runtime(&self, call: <Self::T as Config>::Call) -> DispatchResultWithPostInfo;
 }
 
 /// Loader is a companion of the `Vm` trait. It loads an appropriate abstract
@@ -176,10 +204,22 @@ pub trait Loader<T: Config> {
 
 	/// Load the initializer portion of the code specified by the `code_hash`. This
 	/// executable is called upon instantiation.
-	fn load_init(&self, code_hash: &CodeHash<T>) -> Result<Self::Executable, &'static str>;
+	///
+	/// The weight of loading the code is charged from `gas_meter`.
+	fn load_init(
+		&self,
+		code_hash: &CodeHash<T>,
+		gas_meter: &mut GasMeter<T>,
+	) -> Result<Self::Executable, DispatchError>;
 	/// Load the main portion of the code specified by the `code_hash`. This executable
 	/// is called for each call to a contract.
-	fn load_main(&self, code_hash: &CodeHash<T>) -> Result<Self::Executable, &'static str>;
+	///
+	/// The weight of loading the code is charged from `gas_meter`.
+	fn load_main(
+		&self,
+		code_hash: &CodeHash<T>,
+		gas_meter: &mut GasMeter<T>,
+	) -> Result<Self::Executable, DispatchError>;
 }
 
 /// A trait that represent a virtual machine.
@@ -212,6 +252,14 @@ pub struct ExecutionContext<'a, T: Config + 'a, V, L> {
 	pub loader: &'a L,
 	pub timestamp: MomentOf<T>,
 	pub block_number: T::BlockNumber,
+	/// The buffer that `seal_debug_message` appends to. Only set when the caller of the
+	/// top level call requested a debug message (i.e. when executing via RPC).
+	pub debug_message: Option<&'a RefCell<Vec<u8>>>,
+	/// Meters the storage deposit of the contract executing in this context.
+	pub storage_meter: StorageMeter<T>,
+	/// Whether the contract executing in this context can be reentered by the callee of
+	/// the call that is currently in progress.
+	pub allows_reentry: bool,
 }
 
 impl<'a, T, E, V, L> ExecutionContext<'a, T, V, L>
@@ -224,8 +272,15 @@ where
 	/// Create the top level execution context.
 	///
 	/// The specified `origin` address will be used as `sender` for. The `origin` must be a regular
-	/// account (not a contract).
-	pub fn top_level(origin: T::AccountId, cfg: &'a ConfigCache<T>, vm: &'a V, loader: &'a L) -> Self {
+	/// account (not a contract). All storage deposits of the call stack are charged from it
+	/// within the limits of the supplied `storage_meter`.
+	pub fn top_level(
+		origin: T::AccountId,
+		cfg: &'a ConfigCache<T>,
+		vm: &'a V,
+		loader: &'a L,
+		storage_meter: StorageMeter<T>,
+	) -> Self {
 		ExecutionContext {
 			caller: None,
 			self_trie_id: None,
@@ -236,6 +291,9 @@ where
 			loader: &loader,
 			timestamp: T::Time::now(),
 			block_number: <frame_system::Module<T>>::block_number(),
+			debug_message: None,
+			storage_meter,
+			allows_reentry: true,
 		}
 	}
 
@@ -252,6 +310,9 @@ where
 			loader: self.loader,
 			timestamp: self.timestamp.clone(),
 			block_number: self.block_number.clone(),
+			debug_message: self.debug_message,
+			storage_meter: self.storage_meter.nested(),
+			allows_reentry: true,
 		}
 	}
 
@@ -267,16 +328,13 @@ where
 			Err(Error::<T>::MaxCallDepthReached)?
 		}
 
-		// Assumption: `collect` doesn't collide with overlay because
-		// `collect` will be done on first call and destination contract and balance
-		// cannot be changed before the first call
 		// We do not allow 'calling' plain accounts. For transfering value
 		// `seal_transfer` must be used.
-		let contract = if let Some(ContractInfo::Alive(info)) = Rent::<T>::collect(&dest) {
-			info
-		} else {
-			Err(Error::<T>::NotCallable)?
-		};
+		let contract = <ContractInfoOf<T>>::get(&dest).ok_or(Error::<T>::NotCallable)?;
+
+		if !self.allows_reentry(&dest) {
+			Err(Error::<T>::ReentranceDenied)?
+		}
 
 		let transactor_kind = self.transactor_kind();
 		let caller = self.self_account.clone();
@@ -293,8 +351,38 @@ where
 				)?
 			}
 
-			let executable = nested.loader.load_main(&contract.code_hash)
-				.map_err(|_| Error::<T>::CodeNotFound)?;
+			let executable = nested.loader.load_main(&contract.code_hash, gas_meter)?;
+			let output = nested.vm.execute(
+				&executable,
+				nested.new_call_context(caller, value),
+				input_data,
+				gas_meter,
+			).map_err(|e| ExecError { error: e.error, origin: ErrorOrigin::Callee })?;
+			Ok(output)
+		})
+	}
+
+	/// Execute the code stored under `code_hash` in the context of the current contract.
+	///
+	/// No balance is transferred. The `caller` and `value` are passed through from the
+	/// call that is currently executing.
+	pub fn delegate_call(
+		&mut self,
+		code_hash: CodeHash<T>,
+		caller: T::AccountId,
+		value: BalanceOf<T>,
+		gas_meter: &mut GasMeter<T>,
+		input_data: Vec<u8>,
+	) -> ExecResult {
+		if self.depth == self.config.max_depth as usize {
+			Err(Error::<T>::MaxCallDepthReached)?
+		}
+
+		let dest = self.self_account.clone();
+		let trie_id = self.self_trie_id.clone().ok_or(Error::<T>::NotCallable)?;
+
+		self.with_nested_context(dest, trie_id, |nested| {
+			let executable = nested.loader.load_main(&code_hash, gas_meter)?;
 			let output = nested.vm.execute(
 				&executable,
 				nested.new_call_context(caller, value),
@@ -327,7 +415,7 @@ where
 		let dest_trie_id = Storage::<T>::generate_trie_id(&dest);
 
 		let output = self.with_nested_context(dest.clone(), dest_trie_id, |nested| {
-			Storage::<T>::place_contract(
+			let contract = Storage::<T>::place_contract(
 				&dest,
 				nested
 					.self_trie_id
@@ -336,6 +424,13 @@ where
 				code_hash.clone()
 			)?;
 
+			// The contract info itself is a storage item that has to be paid for.
+			nested.storage_meter.record(Diff {
+				bytes_added: contract.encoded_size() as u32,
+				items_added: 1,
+				..Default::default()
+			});
+
 			// Send funds unconditionally here. If the `endowment` is below existential_deposit
 			// then error will be returned here.
 			transfer(
@@ -347,8 +442,7 @@ where
 				nested,
 			)?;
 
-			let executable = nested.loader.load_init(&code_hash)
-				.map_err(|_| Error::<T>::CodeNotFound)?;
+			let executable = nested.loader.load_init(&code_hash, gas_meter)?;
 			let output = nested.vm
 				.execute(
 					&executable,
@@ -358,14 +452,14 @@ where
 				).map_err(|e| ExecError { error: e.error, origin: ErrorOrigin::Callee })?;
 
 			// We need each contract that exists to be above the subsistence threshold
-			// in order to keep up the guarantuee that we always leave a tombstone behind
-			// with the exception of a contract that called `seal_terminate`.
+			// in order to keep it alive. The only way to remove a contract is calling
+			// `seal_terminate`.
 			if T::Currency::total_balance(&dest) < nested.config.subsistence_threshold() {
 				Err(Error::<T>::NewContractNotFunded)?
 			}
 
 			// Deposit an instantiation event.
-			deposit_event::<T>(vec![], RawEvent::Instantiated(caller.clone(), dest.clone()));
+			deposit_event::<T>(vec![], Event::Instantiated(caller.clone(), dest.clone()));
 
 			Ok(output)
 		})?;
@@ -390,19 +484,50 @@ where
 	}
 
 	/// Execute the given closure within a nested execution context.
+	///
+	/// The storage deposit of the nested contract is settled before its changes are
+	/// committed. The deposits of the nested context only count towards the limit of
+	/// this context if the nested context was committed.
 	fn with_nested_context<F>(&mut self, dest: T::AccountId, trie_id: TrieId, func: F)
 		-> ExecResult
 		where F: FnOnce(&mut ExecutionContext<T, V, L>) -> ExecResult
 	{
 		use frame_support::storage::TransactionOutcome::*;
-		let mut nested = self.nested(dest, trie_id);
-		frame_support::storage::with_transaction(|| {
-			let output = func(&mut nested);
-			match output {
-				Ok(ref rv) if !rv.flags.contains(ReturnFlags::REVERT) => Commit(output),
-				_ => Rollback(output),
-			}
-		})
+		let origin = self.origin().clone();
+		let (output, storage_meter) = {
+			let mut nested = self.nested(dest.clone(), trie_id);
+			let output = frame_support::storage::with_transaction(|| {
+				let output = func(&mut nested).and_then(|rv| {
+					if !rv.flags.contains(ReturnFlags::REVERT) {
+						nested.storage_meter.settle(&origin, &dest)?;
+					}
+					Ok(rv)
+				});
+				match output {
+					Ok(ref rv) if !rv.flags.contains(ReturnFlags::REVERT) => Commit(output),
+					_ => Rollback(output),
+				}
+			});
+			(output, nested.storage_meter)
+		};
+		if matches!(output, Ok(ref rv) if !rv.flags.contains(ReturnFlags::REVERT)) {
+			self.storage_meter.absorb(storage_meter);
+		}
+		output
+	}
+
+	/// Returns the account that initiated the top level call.
+	///
+	/// This is the account that pays for all storage deposits of the call stack.
+	fn origin(&self) -> &T::AccountId {
+		self.caller.map_or(&self.self_account, |caller| caller.origin())
+	}
+
+	/// Returns whether `account` can be entered without violating the reentrancy protection
+	/// of any of its executions that are currently live in the execution stack.
+	fn allows_reentry(&self, account: &T::AccountId) -> bool {
+		!(&self.self_account == account && !self.allows_reentry) &&
+			self.caller.map_or(true, |caller| caller.allows_reentry(account))
 	}
 
 	/// Returns whether a contract, identified by address, is currently live in the execution
@@ -517,16 +642,8 @@ where
 				expect can't fail;\
 				qed",
 		);
-		if let Err(storage::ContractAbsentError) =
-			Storage::<T>::write(&self.ctx.self_account, trie_id, &key, value)
-		{
-			panic!(
-				"the contract must be in the alive state within the `CallContext`;\
-				the contract cannot be absent in storage;
-				write cannot return `None`;
-				qed"
-			);
-		}
+		let diff = Storage::<T>::write(trie_id, &key, value);
+		self.ctx.storage_meter.record(diff);
 	}
 
 	fn instantiate(
@@ -560,12 +677,21 @@ where
 		beneficiary: &AccountIdOf<Self::T>,
 	) -> Result<(), DispatchError> {
 		let self_id = self.ctx.self_account.clone();
-		let value = T::Currency::free_balance(&self_id);
 		if let Some(caller_ctx) = self.ctx.caller {
 			if caller_ctx.is_live(&self_id) {
 				return Err(Error::<T>::ReentranceDenied.into());
 			}
 		}
+		let info = <ContractInfoOf<T>>::get(&self_id).expect(
+			"this function is only invoked by in the context of a contract;\
+				a contract in the call stack has a contract info;\
+				this can't be None; qed",
+		);
+		// The storage deposit is refunded before the free balance is transferred so that
+		// the deposit goes back to the origin rather than the beneficiary.
+		let origin = self.ctx.origin().clone();
+		self.ctx.storage_meter.terminate(&origin, &self_id, &info);
+		let value = T::Currency::free_balance(&self_id);
 		transfer(
 			TransferCause::Terminate,
 			TransactorKind::Contract,
@@ -574,12 +700,8 @@ where
 			value,
 			self.ctx,
 		)?;
-		let self_trie_id = self.ctx.self_trie_id.as_ref().expect(
-			"this function is only invoked by in the context of a contract;\
-				a contract has a trie id;\
-				this can't be None; qed",
-		);
-		Storage::<T>::destroy_contract(&self_id, self_trie_id);
+		Storage::<T>::destroy_contract(&self_id, &info);
+		deposit_event::<Self::T>(vec![], Event::Terminated(self_id, beneficiary.clone()));
 		Ok(())
 	}
 
@@ -589,44 +711,45 @@ where
 		value: BalanceOf<T>,
 		gas_meter: &mut GasMeter<T>,
 		input_data: Vec<u8>,
+		allows_reentry: bool,
 	) -> ExecResult {
-		self.ctx.call(to.clone(), value, gas_meter, input_data)
+		// The flag must be set before the callee is checked so that a contract calling
+		// into itself is covered by the reentrancy protection, too.
+		self.ctx.allows_reentry = allows_reentry;
+		let result = self.ctx.call(to.clone(), value, gas_meter, input_data);
+		self.ctx.allows_reentry = true;
+		result
 	}
 
-	fn restore_to(
+	fn delegate_call(
 		&mut self,
-		dest: AccountIdOf<Self::T>,
 		code_hash: CodeHash<Self::T>,
-		rent_allowance: BalanceOf<Self::T>,
-		delta: Vec<StorageKey>,
-	) -> Result<(), DispatchError> {
-		if let Some(caller_ctx) = self.ctx.caller {
-			if caller_ctx.is_live(&self.ctx.self_account) {
-				return Err(Error::<T>::ReentranceDenied.into());
-			}
-		}
-
-		let result = Rent::<T>::restore_to(
-			self.ctx.self_account.clone(),
-			dest.clone(),
-			code_hash.clone(),
-			rent_allowance,
-			delta,
+		gas_meter: &mut GasMeter<T>,
+		input_data: Vec<u8>,
+		allows_reentry: bool,
+	) -> ExecResult {
+		self.ctx.allows_reentry = allows_reentry;
+		let result = self.ctx.delegate_call(
+			code_hash,
+			self.caller.clone(),
+			self.value_transferred,
+			gas_meter,
+			input_data,
 		);
-		if let Ok(_) = result {
-			deposit_event::<Self::T>(
-				vec![],
-				RawEvent::Restored(
-					self.ctx.self_account.clone(),
-					dest,
-					code_hash,
-					rent_allowance,
-				),
-			);
-		}
+		self.ctx.allows_reentry = true;
 		result
 	}
 
+	fn set_code_hash(&mut self, hash: CodeHash<Self::T>) -> Result<(), DispatchError> {
+		let account = self.ctx.self_account.clone();
+		let prev_hash = Storage::<T>::set_code_hash(&account, hash)?;
+		deposit_event::<Self::T>(
+			vec![],
+			Event::ContractCodeUpdated(account, hash, prev_hash),
+		);
+		Ok(())
+	}
+
 	fn address(&self) -> &T::AccountId {
 		&self.ctx.self_account
 	}
@@ -635,6 +758,23 @@ where
 		&self.caller
 	}
 
+	fn caller_is_origin(&self) -> bool {
+		&self.caller == self.ctx.origin()
+	}
+
+	fn code_hash(&self, address: &T::AccountId) -> Option<CodeHash<Self::T>> {
+		<ContractInfoOf<T>>::get(address).map(|info| info.code_hash)
+	}
+
+	fn own_code_hash(&self) -> CodeHash<Self::T> {
+		<ContractInfoOf<T>>::get(&self.ctx.self_account)
+			.expect(
+				"`self_account` points to an alive contract within the `CallContext`;\
+					it has a contract info; qed",
+			)
+			.code_hash
+	}
+
 	fn balance(&self) -> BalanceOf<T> {
 		T::Currency::free_balance(&self.ctx.self_account)
 	}
@@ -644,7 +784,9 @@ where
 	}
 
 	fn random(&self, subject: &[u8]) -> SeedOf<T> {
-		T::Randomness::random(subject)
+		// NOTE: the block number since when the seed is known is not exposed to
+		// contracts in order to keep the `seal_random` interface unchanged.
+		T::Randomness::random(subject).0
 	}
 
 	fn now(&self) -> &MomentOf<T> {
@@ -655,33 +797,13 @@ where
 		self.ctx.config.existential_deposit
 	}
 
-	fn tombstone_deposit(&self) -> BalanceOf<T> {
-		self.ctx.config.tombstone_deposit
-	}
-
 	fn deposit_event(&mut self, topics: Vec<T::Hash>, data: Vec<u8>) {
 		deposit_event::<Self::T>(
 			topics,
-			RawEvent::ContractExecution(self.ctx.self_account.clone(), data)
+			Event::ContractExecution(self.ctx.self_account.clone(), data)
 		);
 	}
 
-	fn set_rent_allowance(&mut self, rent_allowance: BalanceOf<T>) {
-		if let Err(storage::ContractAbsentError) =
-			Storage::<T>::set_rent_allowance(&self.ctx.self_account, rent_allowance)
-		{
-			panic!(
-				"`self_account` points to an alive contract within the `CallContext`;
-					set_rent_allowance cannot return `Err`; qed"
-			);
-		}
-	}
-
-	fn rent_allowance(&self) -> BalanceOf<T> {
-		Storage::<T>::rent_allowance(&self.ctx.self_account)
-			.unwrap_or_else(|_| <BalanceOf<T>>::max_value()) // Must never be triggered actually
-	}
-
 	fn block_number(&self) -> T::BlockNumber { self.block_number }
 
 	fn max_value_size(&self) -> u32 {
@@ -691,6 +813,23 @@ where
 	fn get_weight_price(&self, weight: Weight) -> BalanceOf<Self::T> {
 		T::WeightPrice::convert(weight)
 	}
+
+	fn append_debug_buffer(&mut self, msg: &str) -> bool {
+		if let Some(buffer) = self.ctx.debug_message {
+			if !msg.is_empty() {
+				buffer.borrow_mut().extend(msg.as_bytes());
+			}
+			true
+		} else {
+			false
+		}
+	}
+
+	fn call_runtime(&self, call: <Self::T as Config>::Call) -> DispatchResultWithPostInfo {
+		let mut origin: T::Origin = RawOrigin::Signed(self.ctx.self_account.clone()).into();
+		origin.add_filter(T::CallFilter::filter);
+		call.dispatch(origin)
+	}
 }
 
 fn deposit_event<T: Config>(
@@ -711,8 +850,8 @@ fn deposit_event<T: Config>(
 #[cfg(test)]
 mod tests {
 	use super::{
-		BalanceOf, Event, ExecResult, ExecutionContext, Ext, Loader,
-		RawEvent, Vm, ReturnFlags, ExecError, ErrorOrigin, AccountIdOf,
+		Event, ExecResult, ExecutionContext, Ext, Loader,
+		Vm, ReturnFlags, ExecError, ErrorOrigin, AccountIdOf,
 	};
 	use crate::{
 		gas::GasMeter, tests::{ExtBuilder, Test, MetaEvent},
@@ -720,15 +859,22 @@ mod tests {
 		gas::Gas,
 		storage::Storage,
 		tests::{ALICE, BOB, CHARLIE},
-		Error,
+		storage::meter::StorageMeter,
+		Error, ContractInfoOf, Config, PristineCode,
 	};
 	use crate::tests::test_utils::{place_contract, set_balance, get_balance};
 	use sp_runtime::DispatchError;
+	use frame_support::traits::ReservableCurrency;
+	use pallet_contracts_primitives::StorageDeposit;
 	use assert_matches::assert_matches;
 	use std::{cell::RefCell, collections::HashMap, marker::PhantomData, rc::Rc};
 
 	const GAS_LIMIT: Gas = 10_000_000_000;
 
+	fn storage_meter(origin: &AccountIdOf<Test>) -> StorageMeter<Test> {
+		StorageMeter::new(origin, None).unwrap()
+	}
+
 	fn events() -> Vec<Event<Test>> {
 		<frame_system::Module<Test>>::events()
 			.into_iter()
@@ -790,17 +936,25 @@ mod tests {
 	impl<'a> Loader<Test> for MockLoader<'a> {
 		type Executable = MockExecutable<'a>;
 
-		fn load_init(&self, code_hash: &CodeHash<Test>) -> Result<Self::Executable, &'static str> {
+		fn load_init(
+			&self,
+			code_hash: &CodeHash<Test>,
+			_gas_meter: &mut GasMeter<Test>,
+		) -> Result<Self::Executable, DispatchError> {
 			self.map
 				.get(code_hash)
 				.cloned()
-				.ok_or_else(|| "code not found")
+				.ok_or_else(|| Error::<Test>::CodeNotFound.into())
 		}
-		fn load_main(&self, code_hash: &CodeHash<Test>) -> Result<Self::Executable, &'static str> {
+		fn load_main(
+			&self,
+			code_hash: &CodeHash<Test>,
+			_gas_meter: &mut GasMeter<Test>,
+		) -> Result<Self::Executable, DispatchError> {
 			self.map
 				.get(code_hash)
 				.cloned()
-				.ok_or_else(|| "code not found")
+				.ok_or_else(|| Error::<Test>::CodeNotFound.into())
 		}
 	}
 
@@ -844,7 +998,9 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
 			place_contract(&BOB, exec_ch);
 
 			assert_matches!(
@@ -868,8 +1024,10 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(origin.clone(), &cfg, &vm, &loader);
 			set_balance(&origin, 100);
+			let mut ctx = ExecutionContext::top_level(
+				origin.clone(), &cfg, &vm, &loader, storage_meter(&origin),
+			);
 			set_balance(&dest, 0);
 
 			super::transfer(
@@ -901,9 +1059,11 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(origin.clone(), &cfg, &vm, &loader);
-			place_contract(&BOB, return_ch);
 			set_balance(&origin, 100);
+			let mut ctx = ExecutionContext::top_level(
+				origin.clone(), &cfg, &vm, &loader, storage_meter(&origin),
+			);
+			place_contract(&BOB, return_ch);
 			set_balance(&dest, 0);
 
 			let output = ctx.call(
@@ -931,8 +1091,10 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(origin.clone(), &cfg, &vm, &loader);
 			set_balance(&origin, 0);
+			let mut ctx = ExecutionContext::top_level(
+				origin.clone(), &cfg, &vm, &loader, storage_meter(&origin),
+			);
 
 			let result = super::transfer(
 				super::TransferCause::Call,
@@ -967,7 +1129,9 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(origin, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(
+				origin.clone(), &cfg, &vm, &loader, storage_meter(&origin),
+			);
 			place_contract(&BOB, return_ch);
 
 			let result = ctx.call(
@@ -998,7 +1162,9 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(origin, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(
+				origin.clone(), &cfg, &vm, &loader, storage_meter(&origin),
+			);
 			place_contract(&BOB, return_ch);
 
 			let result = ctx.call(
@@ -1026,7 +1192,9 @@ mod tests {
 		// This one tests passing the input data into a contract via call.
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
 			place_contract(&BOB, input_data_ch);
 
 			let result = ctx.call(
@@ -1051,9 +1219,10 @@ mod tests {
 		// This one tests passing the input data into a contract via instantiate.
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
-
 			set_balance(&ALICE, 100);
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
 
 			let result = ctx.instantiate(
 				cfg.subsistence_threshold(),
@@ -1077,7 +1246,7 @@ mod tests {
 		let mut loader = MockLoader::empty();
 		let recurse_ch = loader.insert(|ctx| {
 			// Try to call into yourself.
-			let r = ctx.ext.call(&BOB, 0, ctx.gas_meter, vec![]);
+			let r = ctx.ext.call(&BOB, 0, ctx.gas_meter, vec![], true);
 
 			let mut reached_bottom = reached_bottom.borrow_mut();
 			if !*reached_bottom {
@@ -1098,7 +1267,9 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
 			set_balance(&BOB, 1);
 			place_contract(&BOB, recurse_ch);
 
@@ -1130,7 +1301,7 @@ mod tests {
 
 			// Call into CHARLIE contract.
 			assert_matches!(
-				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![]),
+				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![], true),
 				Ok(_)
 			);
 			exec_success()
@@ -1144,7 +1315,9 @@ mod tests {
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
 
-			let mut ctx = ExecutionContext::top_level(origin.clone(), &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(
+				origin.clone(), &cfg, &vm, &loader, storage_meter(&origin),
+			);
 			place_contract(&dest, bob_ch);
 			place_contract(&CHARLIE, charlie_ch);
 
@@ -1173,7 +1346,7 @@ mod tests {
 
 			// Call into charlie contract.
 			assert_matches!(
-				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![]),
+				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![], true),
 				Ok(_)
 			);
 			exec_success()
@@ -1185,7 +1358,9 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
 			place_contract(&BOB, bob_ch);
 			place_contract(&CHARLIE, charlie_ch);
 
@@ -1200,6 +1375,117 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn reentrance_is_denied_unless_allowed() {
+		let vm = MockVm::new();
+
+		let mut loader = MockLoader::empty();
+		let bob_ch = loader.insert(|ctx| {
+			// Empty input means we are reentered by CHARLIE.
+			if ctx.input_data.is_empty() {
+				return exec_success();
+			}
+			let allows_reentry = ctx.input_data[0] == 1;
+			ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, ctx.input_data.clone(), allows_reentry)
+		});
+		let charlie_ch = loader.insert(|ctx| {
+			let r = ctx.ext.call(&BOB, 0, ctx.gas_meter, vec![], true);
+			if ctx.input_data[0] == 1 {
+				assert_matches!(r, Ok(_));
+			} else {
+				assert_eq!(r, Err(Error::<Test>::ReentranceDenied.into()));
+			}
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = ConfigCache::preload();
+			place_contract(&BOB, bob_ch);
+			place_contract(&CHARLIE, charlie_ch);
+
+			for allows_reentry in &[0, 1] {
+				let mut ctx = ExecutionContext::top_level(
+					ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+				);
+				let result = ctx.call(
+					BOB,
+					0,
+					&mut GasMeter::<Test>::new(GAS_LIMIT),
+					vec![*allows_reentry],
+				);
+				assert_matches!(result, Ok(_));
+			}
+		});
+	}
+
+	#[test]
+	fn delegate_call_executes_in_callers_context() {
+		let vm = MockVm::new();
+
+		let mut loader = MockLoader::empty();
+		let delegate_ch = loader.insert(|ctx| {
+			assert_eq!(*ctx.ext.address(), BOB);
+			assert_eq!(*ctx.ext.caller(), ALICE);
+			assert!(ctx.ext.caller_is_origin());
+			ctx.ext.set_storage([1; 32], Some(vec![1, 2, 3]));
+			exec_success()
+		});
+		let bob_ch = loader.insert(move |ctx| {
+			assert_matches!(
+				ctx.ext.delegate_call(delegate_ch, ctx.gas_meter, vec![], false),
+				Ok(_)
+			);
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = ConfigCache::preload();
+			set_balance(&ALICE, 100);
+			place_contract(&BOB, bob_ch);
+
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
+			let result = ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![]);
+			assert_matches!(result, Ok(_));
+
+			let trie_id = <ContractInfoOf<Test>>::get(&BOB).unwrap().trie_id;
+			assert_eq!(Storage::<Test>::read(&trie_id, &[1; 32]), Some(vec![1, 2, 3]));
+		});
+	}
+
+	#[test]
+	fn set_code_hash_replaces_code() {
+		let vm = MockVm::new();
+
+		let mut loader = MockLoader::empty();
+		let new_ch = loader.insert(|_| exec_success());
+		let bob_ch = loader.insert(move |ctx| {
+			assert_eq!(ctx.ext.code_hash(&CHARLIE), None);
+			let own_code_hash = ctx.ext.own_code_hash();
+			assert_eq!(ctx.ext.code_hash(&BOB), Some(own_code_hash));
+			assert_eq!(ctx.ext.set_code_hash(new_ch), Ok(()));
+			// The new code is only used for subsequent calls.
+			assert_eq!(ctx.ext.own_code_hash(), new_ch);
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = ConfigCache::preload();
+			place_contract(&BOB, bob_ch);
+			<PristineCode<Test>>::insert(new_ch, Vec::<u8>::new());
+
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
+			let result = ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![]);
+			assert_matches!(result, Ok(_));
+
+			assert_eq!(<ContractInfoOf<Test>>::get(&BOB).unwrap().code_hash, new_ch);
+			assert!(events().contains(&Event::ContractCodeUpdated(BOB, new_ch, bob_ch)));
+		});
+	}
+
 	#[test]
 	fn refuse_instantiate_with_value_below_existential_deposit() {
 		let vm = MockVm::new();
@@ -1209,7 +1495,9 @@ mod tests {
 
 		ExtBuilder::default().existential_deposit(15).build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
 
 			assert_matches!(
 				ctx.instantiate(
@@ -1235,8 +1523,10 @@ mod tests {
 
 		ExtBuilder::default().existential_deposit(15).build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
 			set_balance(&ALICE, 1000);
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
 
 			let instantiated_contract_address = assert_matches!(
 				ctx.instantiate(
@@ -1253,7 +1543,7 @@ mod tests {
 			// there are instantiation event.
 			assert_eq!(Storage::<Test>::code_hash(&instantiated_contract_address).unwrap(), dummy_ch);
 			assert_eq!(&events(), &[
-				RawEvent::Instantiated(ALICE, instantiated_contract_address)
+				Event::Instantiated(ALICE, instantiated_contract_address)
 			]);
 		});
 	}
@@ -1269,8 +1559,10 @@ mod tests {
 
 		ExtBuilder::default().existential_deposit(15).build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
 			set_balance(&ALICE, 1000);
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
 
 			let instantiated_contract_address = assert_matches!(
 				ctx.instantiate(
@@ -1316,8 +1608,10 @@ mod tests {
 
 		ExtBuilder::default().existential_deposit(15).build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
 			set_balance(&ALICE, 1000);
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
 			set_balance(&BOB, 100);
 			place_contract(&BOB, instantiator_ch);
 
@@ -1332,7 +1626,7 @@ mod tests {
 			// there are instantiation event.
 			assert_eq!(Storage::<Test>::code_hash(&instantiated_contract_address).unwrap(), dummy_ch);
 			assert_eq!(&events(), &[
-				RawEvent::Instantiated(BOB, instantiated_contract_address)
+				Event::Instantiated(BOB, instantiated_contract_address)
 			]);
 		});
 	}
@@ -1369,8 +1663,10 @@ mod tests {
 
 		ExtBuilder::default().existential_deposit(15).build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
 			set_balance(&ALICE, 1000);
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
 			set_balance(&BOB, 100);
 			place_contract(&BOB, instantiator_ch);
 
@@ -1401,8 +1697,10 @@ mod tests {
 			.build()
 			.execute_with(|| {
 				let cfg = ConfigCache::preload();
-				let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
 				set_balance(&ALICE, 1000);
+				let mut ctx = ExecutionContext::top_level(
+					ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+				);
 
 				assert_eq!(
 					ctx.instantiate(
@@ -1423,25 +1721,115 @@ mod tests {
 	}
 
 	#[test]
-	fn rent_allowance() {
+	fn storage_deposit_is_charged_from_origin() {
+		let vm = MockVm::new();
+		let mut loader = MockLoader::empty();
+		let code_hash = loader.insert(|ctx| {
+			ctx.ext.set_storage([1; 32], Some(vec![1, 2, 3]));
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = ConfigCache::preload();
+			set_balance(&ALICE, 1000);
+			set_balance(&BOB, 100);
+			place_contract(&BOB, code_hash);
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![]),
+				Ok(_)
+			);
+
+			// One item (2) with three bytes (1 each).
+			assert_eq!(ctx.storage_meter.into_deposit(), StorageDeposit::Charge(5));
+			assert_eq!(get_balance(&ALICE), 995);
+			assert_eq!(<Test as Config>::Currency::reserved_balance(&BOB), 5);
+			assert_eq!(ContractInfoOf::<Test>::get(&BOB).unwrap().storage_deposit, 5);
+		});
+	}
+
+	#[test]
+	fn storage_deposit_limit_is_enforced() {
 		let vm = MockVm::new();
 		let mut loader = MockLoader::empty();
-		let rent_allowance_ch = loader.insert(|ctx| {
-			assert_eq!(ctx.ext.rent_allowance(), <BalanceOf<Test>>::max_value());
-			ctx.ext.set_rent_allowance(10);
-			assert_eq!(ctx.ext.rent_allowance(), 10);
+		let code_hash = loader.insert(|ctx| {
+			ctx.ext.set_storage([1; 32], Some(vec![1, 2, 3]));
 			exec_success()
 		});
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			set_balance(&ALICE, 1000);
+			set_balance(&BOB, 100);
+			place_contract(&BOB, code_hash);
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, StorageMeter::new(&ALICE, Some(4)).unwrap(),
+			);
+
+			assert_eq!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![]),
+				Err(Error::<Test>::StorageDepositLimitExhausted.into())
+			);
+			assert_eq!(get_balance(&ALICE), 1000);
+			assert_eq!(<Test as Config>::Currency::reserved_balance(&BOB), 0);
+		});
+	}
+
+	#[test]
+	fn debug_message_is_recorded_when_requested() {
+		let vm = MockVm::new();
+		let mut loader = MockLoader::empty();
+		let code_hash = loader.insert(|ctx| {
+			assert!(ctx.ext.append_debug_buffer("This is a test"));
+			assert!(ctx.ext.append_debug_buffer(""));
+			assert!(ctx.ext.append_debug_buffer(", more text"));
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = ConfigCache::preload();
+			let debug_message = RefCell::new(Vec::new());
 			set_balance(&ALICE, 100);
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
+			ctx.debug_message = Some(&debug_message);
+
+			let result = ctx.instantiate(
+				cfg.subsistence_threshold(),
+				&mut GasMeter::<Test>::new(GAS_LIMIT),
+				&code_hash,
+				vec![],
+				&[],
+			);
+			assert_matches!(result, Ok(_));
+			assert_eq!(&debug_message.into_inner(), b"This is a test, more text");
+		});
+	}
+
+	#[test]
+	fn debug_message_is_not_recorded_by_default() {
+		let vm = MockVm::new();
+		let mut loader = MockLoader::empty();
+		let code_hash = loader.insert(|ctx| {
+			assert!(!ctx.ext.append_debug_buffer("This is a test"));
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = ConfigCache::preload();
+			set_balance(&ALICE, 100);
+			let mut ctx = ExecutionContext::top_level(
+				ALICE, &cfg, &vm, &loader, storage_meter(&ALICE),
+			);
 
 			let result = ctx.instantiate(
 				cfg.subsistence_threshold(),
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
-				&rent_allowance_ch,
+				&code_hash,
 				vec![],
 				&[],
 			);
@@ -88,6 +88,9 @@ pub struct GasMeter<T: Config> {
 	gas_limit: Gas,
 	/// Amount of gas left from initial gas limit. Can reach zero.
 	gas_left: Gas,
+	/// Due to `refund` and `with_nested` the `gas_left` can temporarily drop and this is
+	/// the lowest value ever reached.
+	gas_left_lowest: Gas,
 	_phantom: PhantomData<T>,
 	#[cfg(test)]
 	tokens: Vec<ErasedToken>,
@@ -97,6 +100,7 @@ impl<T: Config> GasMeter<T> {
 		GasMeter {
 			gas_limit,
 			gas_left: gas_limit,
+			gas_left_lowest: gas_limit,
 			_phantom: PhantomData,
 			#[cfg(test)]
 			tokens: Vec::new(),
@@ -136,6 +140,7 @@ impl<T: Config> GasMeter<T> {
 
 		// We always consume the gas even if there is not enough gas.
 		self.gas_left = new_value.unwrap_or_else(Zero::zero);
+		self.gas_left_lowest = self.gas_left_lowest();
 
 		match new_value {
 			Some(_) => GasMeterResult::Proceed,
@@ -148,7 +153,8 @@ impl<T: Config> GasMeter<T> {
 	// This can be used after dispatching a runtime call to refund gas that was not
 	// used by the dispatchable.
 	pub fn refund(&mut self, gas: Gas) {
-		self.gas_left = self.gas_left.saturating_add(gas).max(self.gas_limit);
+		self.gas_left_lowest = self.gas_left_lowest();
+		self.gas_left = self.gas_left.saturating_add(gas).min(self.gas_limit);
 	}
 
 	/// Allocate some amount of gas and perform some work with
@@ -173,6 +179,19 @@ impl<T: Config> GasMeter<T> {
 
 			let r = f(Some(&mut nested));
 
+			if self.gas_left == 0 {
+				// All of the remaining gas was inherited by the nested gas meter. When absorbing
+				// we can therefore safely inherit the lowest gas that the nested gas meter
+				// experienced as long as it is lower than the lowest gas that was experienced
+				// by the parent. We cannot call `self.gas_left_lowest()` here because in
+				// the state that this code is run the parent gas meter has `0` gas left.
+				self.gas_left_lowest = nested.gas_left_lowest().min(self.gas_left_lowest);
+			} else {
+				// The nested gas meter was created with a fixed amount that did not consume
+				// all of the parent's remaining gas. Therefore we must always consider the
+				// whole allocated amount as required.
+				self.gas_left_lowest = self.gas_left_lowest();
+			}
 			self.gas_left = self.gas_left + nested.gas_left;
 
 			r
@@ -189,6 +208,19 @@ impl<T: Config> GasMeter<T> {
 		self.gas_left
 	}
 
+	/// Returns the amount of gas that is required to run the same call.
+	///
+	/// This can be different from `gas_spent` because due to `with_nested` and `refund`
+	/// we sometimes need more gas than was actually consumed in the end.
+	pub fn gas_required(&self) -> Gas {
+		self.gas_limit - self.gas_left_lowest()
+	}
+
+	/// The lowest amount of gas that this meter had left at any point in time.
+	fn gas_left_lowest(&self) -> Gas {
+		self.gas_left_lowest.min(self.gas_left)
+	}
+
 	/// Turn this GasMeter into a DispatchResult that contains the actually used gas.
 	pub fn into_dispatch_result<R, E>(self, result: Result<R, E>) -> DispatchResultWithPostInfo
 	where
@@ -330,6 +362,35 @@ mod tests {
 	}
 
 
+	// A refund does not lower the gas required.
+	#[test]
+	fn refund_does_not_lower_gas_required() {
+		let mut gas_meter = GasMeter::<Test>::new(100);
+		assert!(!gas_meter.charge(&(), SimpleToken(40)).is_out_of_gas());
+		gas_meter.refund(30);
+		assert_eq!(gas_meter.gas_spent(), 10);
+		assert_eq!(gas_meter.gas_required(), 40);
+	}
+
+	// A nested meter that is allocated a fixed amount counts fully towards the gas required
+	// while one that inherits all remaining gas only counts with what it used.
+	#[test]
+	fn nested_meter_gas_required() {
+		let mut gas_meter = GasMeter::<Test>::new(100);
+		gas_meter.with_nested(50, |nested| {
+			assert!(!nested.unwrap().charge(&(), SimpleToken(10)).is_out_of_gas());
+		});
+		assert_eq!(gas_meter.gas_spent(), 10);
+		assert_eq!(gas_meter.gas_required(), 50);
+
+		let mut gas_meter = GasMeter::<Test>::new(100);
+		gas_meter.with_nested(100, |nested| {
+			assert!(!nested.unwrap().charge(&(), SimpleToken(10)).is_out_of_gas());
+		});
+		assert_eq!(gas_meter.gas_spent(), 10);
+		assert_eq!(gas_meter.gas_required(), 10);
+	}
+
 	// Charging the exact amount that the user paid for should be
 	// possible.
 	#[test]
@@ -48,6 +48,17 @@
 //! then all of B's calls are reverted. Assuming correct error handling by contract A, A's other calls and state
 //! changes still persist.
 //!
+//! ### Storage Deposit
+//!
+//! Contracts do not pay rent for the storage they occupy. Instead, a deposit proportional to the
+//! number of storage items and bytes is reserved at the contract account whenever a contract adds
+//! to its storage. This deposit is paid by the origin of the transaction that causes the storage
+//! to be created and is refunded to the origin of the transaction that removes it again. Callers
+//! can cap the deposit a transaction may charge by supplying a `storage_deposit_limit`.
+//!
+//! Uploading code works the same: the uploader reserves a deposit for the storage the code
+//! occupies which is refunded when the code is removed with `remove_code`.
+//!
 //! ### Notable Scenarios
 //!
 //! Contract call failures are not always cascading. When failures occur in a sub-call, they do not "bubble up",
@@ -59,6 +70,7 @@
 //! ### Dispatchable functions
 //!
 //! * `put_code` - Stores the given binary Wasm code into the chain's storage and returns its `code_hash`.
+//! * `remove_code` - Removes code that is no longer used by any contract and refunds its deposit.
 //! * `instantiate` - Deploys a new contract from the given `code_hash`, optionally transferring some balance.
 //! This instantiates a new smart contract account and calls its contract deploy handler to
 //! initialize the contract.
@@ -85,15 +97,17 @@ mod gas;
 mod storage;
 mod exec;
 mod wasm;
-mod rent;
+mod migration;
 mod benchmarking;
 mod schedule;
 pub mod weights;
+pub mod chain_extension;
 
 #[cfg(test)]
 mod tests;
 
 pub use crate::{
+	pallet::*,
 	gas::{Gas, GasMeter},
 	wasm::ReturnCode as RuntimeReturnCode,
 	weights::WeightInfo,
@@ -102,122 +116,50 @@ pub use crate::{
 use crate::{
 	exec::ExecutionContext,
 	wasm::{WasmLoader, WasmVm},
-	rent::Rent,
-	storage::Storage,
+	storage::{Storage, meter::StorageMeter},
 };
 use sp_core::crypto::UncheckedFrom;
-use sp_std::{prelude::*, marker::PhantomData, fmt::Debug};
-use codec::{Codec, Encode, Decode};
+use sp_std::{prelude::*, cell::RefCell};
+use codec::{Encode, Decode};
 use sp_runtime::{
-	traits::{
-		Hash, StaticLookup, Zero, MaybeSerializeDeserialize, Member, Convert, Saturating,
-	},
+	traits::{Hash, StaticLookup, Convert, Saturating, Zero},
 	RuntimeDebug,
 };
 use frame_support::{
-	decl_module, decl_event, decl_storage, decl_error, ensure,
+	ensure,
 	storage::child::ChildInfo,
-	dispatch::{DispatchResult, DispatchResultWithPostInfo},
-	traits::{OnUnbalanced, Currency, Get, Time, Randomness},
+	dispatch::{DispatchError, Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	traits::{Currency, ReservableCurrency, Get, Time, Randomness, Filter},
 };
-use frame_system::{ensure_signed, ensure_root};
 use pallet_contracts_primitives::{
-	RentProjectionResult, GetStorageResult, ContractAccessError, ContractExecResult, ExecResult,
+	GetStorageResult, ContractAccessError, ContractExecResult, ExecResult,
+	ContractResult, ContractInstantiateResult, CodeUploadResult, CodeUploadReturnValue,
+	InstantiateReturnValue, StorageDeposit, Code,
 };
-use frame_support::weights::Weight;
 
 pub type CodeHash<T> = <T as frame_system::Config>::Hash;
+pub type EventRecordOf<T> = frame_system::EventRecord<
+	<T as frame_system::Config>::Event,
+	<T as frame_system::Config>::Hash,
+>;
 pub type TrieId = Vec<u8>;
 
-/// Information for managing an account and its sub trie abstraction.
-/// This is the required info to cache for an account
-#[derive(Encode, Decode, RuntimeDebug)]
-pub enum ContractInfo<T: Config> {
-	Alive(AliveContractInfo<T>),
-	Tombstone(TombstoneContractInfo<T>),
-}
-
-impl<T: Config> ContractInfo<T> {
-	/// If contract is alive then return some alive info
-	pub fn get_alive(self) -> Option<AliveContractInfo<T>> {
-		if let ContractInfo::Alive(alive) = self {
-			Some(alive)
-		} else {
-			None
-		}
-	}
-	/// If contract is alive then return some reference to alive info
-	pub fn as_alive(&self) -> Option<&AliveContractInfo<T>> {
-		if let ContractInfo::Alive(ref alive) = self {
-			Some(alive)
-		} else {
-			None
-		}
-	}
-	/// If contract is alive then return some mutable reference to alive info
-	pub fn as_alive_mut(&mut self) -> Option<&mut AliveContractInfo<T>> {
-		if let ContractInfo::Alive(ref mut alive) = self {
-			Some(alive)
-		} else {
-			None
-		}
-	}
-
-	/// If contract is tombstone then return some tombstone info
-	pub fn get_tombstone(self) -> Option<TombstoneContractInfo<T>> {
-		if let ContractInfo::Tombstone(tombstone) = self {
-			Some(tombstone)
-		} else {
-			None
-		}
-	}
-	/// If contract is tombstone then return some reference to tombstone info
-	pub fn as_tombstone(&self) -> Option<&TombstoneContractInfo<T>> {
-		if let ContractInfo::Tombstone(ref tombstone) = self {
-			Some(tombstone)
-		} else {
-			None
-		}
-	}
-	/// If contract is tombstone then return some mutable reference to tombstone info
-	pub fn as_tombstone_mut(&mut self) -> Option<&mut TombstoneContractInfo<T>> {
-		if let ContractInfo::Tombstone(ref mut tombstone) = self {
-			Some(tombstone)
-		} else {
-			None
-		}
-	}
-}
-
-pub type AliveContractInfo<T> =
-	RawAliveContractInfo<CodeHash<T>, BalanceOf<T>, <T as frame_system::Config>::BlockNumber>;
+pub type ContractInfo<T> = RawContractInfo<CodeHash<T>, BalanceOf<T>>;
 
 /// Information for managing an account and its sub trie abstraction.
 /// This is the required info to cache for an account.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
-pub struct RawAliveContractInfo<CodeHash, Balance, BlockNumber> {
+pub struct RawContractInfo<CodeHash, Balance> {
 	/// Unique ID for the subtree encoded as a bytes vector.
 	pub trie_id: TrieId,
-	/// The total number of bytes used by this contract.
-	///
-	/// It is a sum of each key-value pair stored by this contract.
-	pub storage_size: u32,
-	/// The number of key-value pairs that have values of zero length.
-	/// The condition `empty_pair_count ≤ total_pair_count` always holds.
-	pub empty_pair_count: u32,
-	/// The total number of key-value pairs in storage of this contract.
-	pub total_pair_count: u32,
 	/// The code associated with a given account.
 	pub code_hash: CodeHash,
-	/// Pay rent at most up to this value.
-	pub rent_allowance: Balance,
-	/// Last block rent has been payed.
-	pub deduct_block: BlockNumber,
-	/// Last block child storage has been written.
-	pub last_write: Option<BlockNumber>,
+	/// The amount of balance that is currently reserved at the contract account in order
+	/// to pay for the storage it occupies.
+	pub storage_deposit: Balance,
 }
 
-impl<CodeHash, Balance, BlockNumber> RawAliveContractInfo<CodeHash, Balance, BlockNumber> {
+impl<CodeHash, Balance> RawContractInfo<CodeHash, Balance> {
 	/// Associated child trie unique id is built from the hash part of the trie id.
 	pub fn child_trie_info(&self) -> ChildInfo {
 		child_trie_info(&self.trie_id[..])
@@ -229,241 +171,168 @@ pub(crate) fn child_trie_info(trie_id: &[u8]) -> ChildInfo {
 	ChildInfo::new_default(trie_id)
 }
 
-pub type TombstoneContractInfo<T> =
-	RawTombstoneContractInfo<<T as frame_system::Config>::Hash, <T as frame_system::Config>::Hashing>;
-
-#[derive(Encode, Decode, PartialEq, Eq, RuntimeDebug)]
-pub struct RawTombstoneContractInfo<H, Hasher>(H, PhantomData<Hasher>);
-
-impl<H, Hasher> RawTombstoneContractInfo<H, Hasher>
-where
-	H: Member + MaybeSerializeDeserialize+ Debug
-		+ AsRef<[u8]> + AsMut<[u8]> + Copy + Default
-		+ sp_std::hash::Hash + Codec,
-	Hasher: Hash<Output=H>,
-{
-	fn new(storage_root: &[u8], code_hash: H) -> Self {
-		let mut buf = Vec::new();
-		storage_root.using_encoded(|encoded| buf.extend_from_slice(encoded));
-		buf.extend_from_slice(code_hash.as_ref());
-		RawTombstoneContractInfo(<Hasher as Hash>::hash(&buf[..]), PhantomData)
-	}
-}
-
-impl<T: Config> From<AliveContractInfo<T>> for ContractInfo<T> {
-	fn from(alive_info: AliveContractInfo<T>) -> Self {
-		Self::Alive(alive_info)
-	}
-}
-
 pub type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
-pub type NegativeImbalanceOf<T> =
-	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::NegativeImbalance;
 
-pub trait Config: frame_system::Config {
-	type Time: Time;
-	type Randomness: Randomness<Self::Hash>;
-
-	/// The currency in which fees are paid and contract balances are held.
-	type Currency: Currency<Self::AccountId>;
-
-	/// The overarching event type.
-	type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use sp_std::prelude::*;
+	use super::{
+		migration, wasm, chain_extension, Gas, GasMeter, Schedule, WeightInfo, CodeHash,
+		ContractInfo, BalanceOf, Releases,
+		Time, Randomness, ReservableCurrency, Filter, Convert, StaticLookup,
+		Dispatchable, GetDispatchInfo, PostDispatchInfo, UncheckedFrom,
+	};
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The time implementation used to supply timestamps to contracts through `seal_now`.
+		type Time: Time;
+
+		/// The generator used to supply randomness to contracts through `seal_random`.
+		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
+
+		/// The currency in which fees are paid and contract balances are held.
+		///
+		/// Storage deposits are held as reserved balance of this currency.
+		type Currency: ReservableCurrency<Self::AccountId>;
 
-	/// Handler for rent payments.
-	type RentPayment: OnUnbalanced<NegativeImbalanceOf<Self>>;
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 
-	/// Number of block delay an extrinsic claim surcharge has.
-	///
-	/// When claim surcharge is called by an extrinsic the rent is checked
-	/// for current_block - delay
-	type SignedClaimHandicap: Get<Self::BlockNumber>;
+		/// The overarching call type.
+		type Call: Dispatchable<Origin=Self::Origin, PostInfo=PostDispatchInfo>
+			+ GetDispatchInfo
+			+ Decode
+			+ IsType<<Self as frame_system::Config>::Call>;
 
-	/// The minimum amount required to generate a tombstone.
-	type TombstoneDeposit: Get<BalanceOf<Self>>;
+		/// Filter that is applied to calls dispatched by contracts via `seal_call_runtime`.
+		///
+		/// The filter is attached to the origin of the dispatched call and therefore also
+		/// applies to any calls nested within it (e.g. inside a `batch`). Runtimes should
+		/// at least deny calls into this module itself: they would start a new call stack
+		/// that bypasses the reentrancy checks of the calling contract.
+		type CallFilter: Filter<<Self as frame_system::Config>::Call>;
 
-	/// A size offset for an contract. A just created account with untouched storage will have that
-	/// much of storage from the perspective of the state rent.
-	///
-	/// This is a simple way to ensure that contracts with empty storage eventually get deleted by
-	/// making them pay rent. This creates an incentive to remove them early in order to save rent.
-	type StorageSizeOffset: Get<u32>;
+		/// The amount of balance a caller has to pay for each storage item.
+		///
+		/// # Note
+		///
+		/// Changing this value for an existing chain might need a storage migration.
+		#[pallet::constant]
+		type DepositPerItem: Get<BalanceOf<Self>>;
 
-	/// Price of a byte of storage per one block interval. Should be greater than 0.
-	type RentByteFee: Get<BalanceOf<Self>>;
+		/// The amount of balance a caller has to pay for each byte of storage.
+		///
+		/// # Note
+		///
+		/// Changing this value for an existing chain might need a storage migration.
+		#[pallet::constant]
+		type DepositPerByte: Get<BalanceOf<Self>>;
 
-	/// The amount of funds a contract should deposit in order to offset
-	/// the cost of one byte.
-	///
-	/// Let's suppose the deposit is 1,000 BU (balance units)/byte and the rent is 1 BU/byte/day,
-	/// then a contract with 1,000,000 BU that uses 1,000 bytes of storage would pay no rent.
-	/// But if the balance reduced to 500,000 BU and the storage stayed the same at 1,000,
-	/// then it would pay 500 BU/day.
-	type RentDepositOffset: Get<BalanceOf<Self>>;
+		/// The maximum nesting level of a call/instantiate stack. A reasonable default
+		/// value is 100.
+		#[pallet::constant]
+		type MaxDepth: Get<u32>;
 
-	/// Reward that is received by the party whose touch has led
-	/// to removal of a contract.
-	type SurchargeReward: Get<BalanceOf<Self>>;
+		/// The maximum size of a storage value and event payload in bytes. A reasonable
+		/// default is 16 KiB.
+		#[pallet::constant]
+		type MaxValueSize: Get<u32>;
 
-	/// The maximum nesting level of a call/instantiate stack.
-	type MaxDepth: Get<u32>;
+		/// Used to answer contracts's queries regarding the current weight price. This is **not**
+		/// used to calculate the actual fee and is only for informational purposes.
+		type WeightPrice: Convert<Weight, BalanceOf<Self>>;
 
-	/// The maximum size of a storage value and event payload in bytes.
-	type MaxValueSize: Get<u32>;
+		/// Describes the weights of the dispatchables of this module and is also used to
+		/// construct a default cost schedule.
+		type WeightInfo: WeightInfo;
 
-	/// Used to answer contracts's queries regarding the current weight price. This is **not**
-	/// used to calculate the actual fee and is only for informational purposes.
-	type WeightPrice: Convert<Weight, BalanceOf<Self>>;
+		/// Type that allows the runtime authors to add new host functions for a contract to call.
+		type ChainExtension: chain_extension::ChainExtension<Self>;
+	}
 
-	/// Describes the weights of the dispatchables of this module and is also used to
-	/// construct a default cost schedule.
-	type WeightInfo: WeightInfo;
-}
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(PhantomData<T>);
 
-decl_error! {
-	/// Error for the contracts module.
-	pub enum Error for Module<T: Config>
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T>
 	where
 		T::AccountId: UncheckedFrom<T::Hash>,
 		T::AccountId: AsRef<[u8]>,
 	{
-		/// A new schedule must have a greater version than the current one.
-		InvalidScheduleVersion,
-		/// An origin must be signed or inherent and auxiliary sender only provided on inherent.
-		InvalidSurchargeClaim,
-		/// Cannot restore from nonexisting or tombstone contract.
-		InvalidSourceContract,
-		/// Cannot restore to nonexisting or alive contract.
-		InvalidDestinationContract,
-		/// Tombstones don't match.
-		InvalidTombstone,
-		/// An origin TrieId written in the current block.
-		InvalidContractOrigin,
-		/// The executed contract exhausted its gas limit.
-		OutOfGas,
-		/// The output buffer supplied to a contract API call was too small.
-		OutputBufferTooSmall,
-		/// Performing the requested transfer would have brought the contract below
-		/// the subsistence threshold. No transfer is allowed to do this in order to allow
-		/// for a tombstone to be created. Use `seal_terminate` to remove a contract without
-		/// leaving a tombstone behind.
-		BelowSubsistenceThreshold,
-		/// The newly created contract is below the subsistence threshold after executing
-		/// its contructor. No contracts are allowed to exist below that threshold.
-		NewContractNotFunded,
-		/// Performing the requested transfer failed for a reason originating in the
-		/// chosen currency implementation of the runtime. Most probably the balance is
-		/// too low or locks are placed on it.
-		TransferFailed,
-		/// Performing a call was denied because the calling depth reached the limit
-		/// of what is specified in the schedule.
-		MaxCallDepthReached,
-		/// The contract that was called is either no contract at all (a plain account)
-		/// or is a tombstone.
-		NotCallable,
-		/// The code supplied to `put_code` exceeds the limit specified in the current schedule.
-		CodeTooLarge,
-		/// No code could be found at the supplied code hash.
-		CodeNotFound,
-		/// A buffer outside of sandbox memory was passed to a contract API function.
-		OutOfBounds,
-		/// Input passed to a contract API function failed to decode as expected type.
-		DecodingFailed,
-		/// Contract trapped during execution.
-		ContractTrapped,
-		/// The size defined in `T::MaxValueSize` was exceeded.
-		ValueTooLarge,
-		/// The action performed is not allowed while the contract performing it is already
-		/// on the call stack. Those actions are contract self destruction and restoration
-		/// of a tombstone.
-		ReentranceDenied,
+		fn on_runtime_upgrade() -> Weight {
+			migration::migrate::<T>()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			migration::pre_upgrade::<T>()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			migration::post_upgrade::<T>()
+		}
 	}
-}
 
-decl_module! {
-	/// Contracts module.
-	pub struct Module<T: Config> for enum Call
+	#[pallet::call]
+	impl<T: Config> Pallet<T>
 	where
-		origin: T::Origin,
 		T::AccountId: UncheckedFrom<T::Hash>,
 		T::AccountId: AsRef<[u8]>,
 	{
-		type Error = Error<T>;
-
-		/// Number of block delay an extrinsic claim surcharge has.
-		///
-		/// When claim surcharge is called by an extrinsic the rent is checked
-		/// for current_block - delay
-		const SignedClaimHandicap: T::BlockNumber = T::SignedClaimHandicap::get();
-
-		/// The minimum amount required to generate a tombstone.
-		const TombstoneDeposit: BalanceOf<T> = T::TombstoneDeposit::get();
-
-		/// A size offset for an contract. A just created account with untouched storage will have that
-		/// much of storage from the perspective of the state rent.
-		///
-		/// This is a simple way to ensure that contracts with empty storage eventually get deleted
-		/// by making them pay rent. This creates an incentive to remove them early in order to save
-		/// rent.
-		const StorageSizeOffset: u32 = T::StorageSizeOffset::get();
-
-		/// Price of a byte of storage per one block interval. Should be greater than 0.
-		const RentByteFee: BalanceOf<T> = T::RentByteFee::get();
-
-		/// The amount of funds a contract should deposit in order to offset
-		/// the cost of one byte.
-		///
-		/// Let's suppose the deposit is 1,000 BU (balance units)/byte and the rent is 1 BU/byte/day,
-		/// then a contract with 1,000,000 BU that uses 1,000 bytes of storage would pay no rent.
-		/// But if the balance reduced to 500,000 BU and the storage stayed the same at 1,000,
-		/// then it would pay 500 BU/day.
-		const RentDepositOffset: BalanceOf<T> = T::RentDepositOffset::get();
-
-		/// Reward that is received by the party whose touch has led
-		/// to removal of a contract.
-		const SurchargeReward: BalanceOf<T> = T::SurchargeReward::get();
-
-		/// The maximum nesting level of a call/instantiate stack. A reasonable default
-		/// value is 100.
-		const MaxDepth: u32 = T::MaxDepth::get();
-
-		/// The maximum size of a storage value in bytes. A reasonable default is 16 KiB.
-		const MaxValueSize: u32 = T::MaxValueSize::get();
-
-		fn deposit_event() = default;
-
 		/// Updates the schedule for metering contracts.
 		///
 		/// The schedule must have a greater version than the stored schedule.
-		#[weight = T::WeightInfo::update_schedule()]
-		pub fn update_schedule(origin, schedule: Schedule<T>) -> DispatchResult {
+		#[pallet::weight(T::WeightInfo::update_schedule())]
+		pub fn update_schedule(
+			origin: OriginFor<T>,
+			schedule: Schedule<T>,
+		) -> DispatchResultWithPostInfo {
 			ensure_root(origin)?;
-			if <Module<T>>::current_schedule().version >= schedule.version {
+			if <Pallet<T>>::current_schedule().version >= schedule.version {
 				Err(Error::<T>::InvalidScheduleVersion)?
 			}
 
-			Self::deposit_event(RawEvent::ScheduleUpdated(schedule.version));
-			CurrentSchedule::put(schedule);
+			Self::deposit_event(Event::ScheduleUpdated(schedule.version));
+			<CurrentSchedule<T>>::put(schedule);
 
-			Ok(())
+			Ok(().into())
 		}
 
 		/// Stores the given binary Wasm code into the chain's storage and returns its `codehash`.
 		/// You can instantiate contracts only with stored code.
-		#[weight = T::WeightInfo::put_code(code.len() as u32 / 1024)]
+		///
+		/// The sender reserves a deposit for the storage the code occupies. It is refunded when
+		/// the code is removed by `remove_code`. The deposit cannot exceed `storage_deposit_limit`
+		/// if one is supplied. Nothing is charged when the code already exists.
+		#[pallet::weight(T::WeightInfo::put_code(code.len() as u32 / 1024))]
 		pub fn put_code(
-			origin,
-			code: Vec<u8>
-		) -> DispatchResult {
-			ensure_signed(origin)?;
-			let schedule = <Module<T>>::current_schedule();
-			ensure!(code.len() as u32 <= schedule.limits.code_size, Error::<T>::CodeTooLarge);
-			let result = wasm::save_code::<T>(code, &schedule);
-			if let Ok(code_hash) = result {
-				Self::deposit_event(RawEvent::CodeStored(code_hash));
-			}
-			result.map(|_| ()).map_err(Into::into)
+			origin: OriginFor<T>,
+			code: Vec<u8>,
+			storage_deposit_limit: Option<BalanceOf<T>>,
+		) -> DispatchResultWithPostInfo {
+			let origin = ensure_signed(origin)?;
+			Self::do_upload_code(origin, code, storage_deposit_limit)?;
+			Ok(().into())
+		}
+
+		/// Removes the code stored under `code_hash` and refunds the deposit to its owner.
+		///
+		/// A code can only be removed by its owner and only if no contract uses it.
+		#[pallet::weight(T::WeightInfo::remove_code())]
+		pub fn remove_code(
+			origin: OriginFor<T>,
+			code_hash: CodeHash<T>,
+		) -> DispatchResultWithPostInfo {
+			let origin = ensure_signed(origin)?;
+			wasm::remove_code::<T>(&origin, code_hash)?;
+			Self::deposit_event(Event::CodeRemoved(code_hash));
+			Ok(().into())
 		}
 
 		/// Makes a call to an account, optionally transferring some balance.
@@ -473,21 +342,30 @@ decl_module! {
 		/// * If the account is a regular account, any value will be transferred.
 		/// * If no account exists and the call value is not less than `existential_deposit`,
 		/// a regular account will be created and any value will be transferred.
-		#[weight = T::WeightInfo::call().saturating_add(*gas_limit)]
+		///
+		/// The storage deposit charged from the sender for any storage created during the call
+		/// cannot exceed `storage_deposit_limit`. If no limit is supplied the whole free balance
+		/// of the sender can be charged.
+		#[pallet::weight(T::WeightInfo::call().saturating_add(*gas_limit))]
 		pub fn call(
-			origin,
+			origin: OriginFor<T>,
 			dest: <T::Lookup as StaticLookup>::Source,
-			#[compact] value: BalanceOf<T>,
-			#[compact] gas_limit: Gas,
-			data: Vec<u8>
+			#[pallet::compact] value: BalanceOf<T>,
+			#[pallet::compact] gas_limit: Gas,
+			storage_deposit_limit: Option<BalanceOf<T>>,
+			data: Vec<u8>,
 		) -> DispatchResultWithPostInfo {
 			let origin = ensure_signed(origin)?;
 			let dest = T::Lookup::lookup(dest)?;
 			let mut gas_meter = GasMeter::new(gas_limit);
 
-			let result = Self::execute_wasm(origin, &mut gas_meter, |ctx, gas_meter| {
-				ctx.call(dest, value, gas_meter, data)
-			});
+			let (result, _) = Self::execute_wasm(
+				origin,
+				&mut gas_meter,
+				storage_deposit_limit,
+				None,
+				|ctx, gas_meter| ctx.call(dest, value, gas_meter, data),
+			);
 			gas_meter.into_dispatch_result(result)
 		}
 
@@ -504,16 +382,19 @@ decl_module! {
 		///   after the execution is saved as the `code` of the account. That code will be invoked
 		///   upon any call received by this account.
 		/// - The contract is initialized.
-		#[weight =
+		///
+		/// See `call` for a description of `storage_deposit_limit`.
+		#[pallet::weight(
 			T::WeightInfo::instantiate(
 				data.len() as u32 / 1024,
 				salt.len() as u32 / 1024,
 			).saturating_add(*gas_limit)
-		]
+		)]
 		pub fn instantiate(
-			origin,
-			#[compact] endowment: BalanceOf<T>,
-			#[compact] gas_limit: Gas,
+			origin: OriginFor<T>,
+			#[pallet::compact] endowment: BalanceOf<T>,
+			#[pallet::compact] gas_limit: Gas,
+			storage_deposit_limit: Option<BalanceOf<T>>,
 			code_hash: CodeHash<T>,
 			data: Vec<u8>,
 			salt: Vec<u8>,
@@ -521,50 +402,175 @@ decl_module! {
 			let origin = ensure_signed(origin)?;
 			let mut gas_meter = GasMeter::new(gas_limit);
 
-			let result = Self::execute_wasm(origin, &mut gas_meter, |ctx, gas_meter| {
-				ctx.instantiate(endowment, gas_meter, &code_hash, data, &salt)
-					.map(|(_address, output)| output)
-			});
+			let (result, _) = Self::execute_wasm(
+				origin,
+				&mut gas_meter,
+				storage_deposit_limit,
+				None,
+				|ctx, gas_meter| {
+					ctx.instantiate(endowment, gas_meter, &code_hash, data, &salt)
+						.map(|(_address, output)| output)
+				},
+			);
 			gas_meter.into_dispatch_result(result)
 		}
+	}
+
+	#[pallet::event]
+	#[pallet::metadata(T::AccountId = "AccountId", T::Hash = "Hash")]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Contract deployed by address at the specified address. \[owner, contract\]
+		Instantiated(T::AccountId, T::AccountId),
 
-		/// Allows block producers to claim a small reward for evicting a contract. If a block producer
-		/// fails to do so, a regular users will be allowed to claim the reward.
+		/// Contract has been terminated and its storage deposit was refunded.
+		/// \[contract, beneficiary\]
 		///
-		/// If contract is not evicted as a result of this call, no actions are taken and
-		/// the sender is not eligible for the reward.
-		#[weight = T::WeightInfo::claim_surcharge()]
-		fn claim_surcharge(origin, dest: T::AccountId, aux_sender: Option<T::AccountId>) {
-			let origin = origin.into();
-			let (signed, rewarded) = match (origin, aux_sender) {
-				(Ok(frame_system::RawOrigin::Signed(account)), None) => {
-					(true, account)
-				},
-				(Ok(frame_system::RawOrigin::None), Some(aux_sender)) => {
-					(false, aux_sender)
-				},
-				_ => Err(Error::<T>::InvalidSurchargeClaim)?,
-			};
+		/// # Params
+		///
+		/// - `contract`: `AccountId`: The account ID of the terminated contract.
+		/// - `beneficiary`: `AccountId`: The account that received the contract's balance.
+		Terminated(T::AccountId, T::AccountId),
 
-			// Add some advantage for block producers (who send unsigned extrinsics) by
-			// adding a handicap: for signed extrinsics we use a slightly older block number
-			// for the eviction check. This can be viewed as if we pushed regular users back in past.
-			let handicap = if signed {
-				T::SignedClaimHandicap::get()
-			} else {
-				Zero::zero()
-			};
+		/// Code with the specified hash has been stored.
+		/// \[code_hash\]
+		CodeStored(T::Hash),
+
+		/// A code with the specified hash was removed.
+		/// \[code_hash\]
+		CodeRemoved(T::Hash),
+
+		/// Triggered when the current \[schedule\] is updated.
+		ScheduleUpdated(u32),
+
+		/// An event deposited upon execution of a contract from the account.
+		/// \[account, data\]
+		ContractExecution(T::AccountId, Vec<u8>),
+
+		/// A contract's code was updated.
+		/// \[contract, new_code_hash, old_code_hash\]
+		ContractCodeUpdated(T::AccountId, T::Hash, T::Hash),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// A new schedule must have a greater version than the current one.
+		InvalidScheduleVersion,
+		/// The executed contract exhausted its gas limit.
+		OutOfGas,
+		/// The output buffer supplied to a contract API call was too small.
+		OutputBufferTooSmall,
+		/// Performing the requested transfer would have brought the contract below
+		/// the subsistence threshold. No transfer is allowed to do this. Use `seal_terminate`
+		/// to remove a contract and transfer all of its balance.
+		BelowSubsistenceThreshold,
+		/// The newly created contract is below the subsistence threshold after executing
+		/// its contructor. No contracts are allowed to exist below that threshold.
+		NewContractNotFunded,
+		/// Performing the requested transfer failed for a reason originating in the
+		/// chosen currency implementation of the runtime. Most probably the balance is
+		/// too low or locks are placed on it.
+		TransferFailed,
+		/// Performing a call was denied because the calling depth reached the limit
+		/// of what is specified in the schedule.
+		MaxCallDepthReached,
+		/// The contract that was called is no contract at all (a plain account).
+		NotCallable,
+		/// The code supplied to `put_code` exceeds the limit specified in the current schedule.
+		CodeTooLarge,
+		/// No code could be found at the supplied code hash.
+		CodeNotFound,
+		/// A buffer outside of sandbox memory was passed to a contract API function.
+		OutOfBounds,
+		/// Input passed to a contract API function failed to decode as expected type.
+		DecodingFailed,
+		/// Contract trapped during execution.
+		ContractTrapped,
+		/// The size defined in `T::MaxValueSize` was exceeded.
+		ValueTooLarge,
+		/// The action performed is not allowed while the contract performing it is already
+		/// on the call stack. This is the case for contract self destruction.
+		ReentranceDenied,
+		/// The chain does not provide a chain extension. Calling the chain extension results
+		/// in this error. Note that this usually shouldn't happen as deploying such contracts
+		/// is rejected.
+		NoChainExtension,
+		/// The contract passed a message to `seal_debug_message` that is not valid UTF-8.
+		DebugMessageInvalidUTF8,
+		/// The origin doesn't have enough free balance to pay the required storage deposit.
+		StorageDepositNotEnoughFunds,
+		/// More storage was created than allowed by the storage deposit limit.
+		StorageDepositLimitExhausted,
+		/// The flags passed to `seal_call_with_flags` or `seal_delegate_call` contain
+		/// unknown bits.
+		InvalidCallFlags,
+		/// The code is still used by at least one contract and can therefore not be removed.
+		CodeInUse,
+	}
+
+	/// Current cost schedule for contracts.
+	#[pallet::storage]
+	#[pallet::getter(fn current_schedule)]
+	pub(crate) type CurrentSchedule<T: Config> = StorageValue<_, Schedule<T>, ValueQuery>;
+
+	/// A mapping from an original code hash to the original code, untouched by instrumentation.
+	#[pallet::storage]
+	pub type PristineCode<T: Config> = StorageMap<_, Identity, CodeHash<T>, Vec<u8>>;
+
+	/// A mapping between an original code hash and instrumented wasm code, ready for execution.
+	#[pallet::storage]
+	pub type CodeStorage<T: Config> =
+		StorageMap<_, Identity, CodeHash<T>, wasm::PrefabWasmModule>;
+
+	/// A mapping between an original code hash and its owner information.
+	///
+	/// Code that was uploaded before storage deposits were introduced has no owner.
+	#[pallet::storage]
+	pub type OwnerInfoOf<T: Config> = StorageMap<_, Identity, CodeHash<T>, wasm::OwnerInfo<T>>;
+
+	/// The subtrie counter.
+	#[pallet::storage]
+	pub type AccountCounter<T> = StorageValue<_, u64, ValueQuery>;
+
+	/// The code associated with a given account.
+	///
+	/// TWOX-NOTE: SAFE since `AccountId` is a secure hash.
+	#[pallet::storage]
+	pub type ContractInfoOf<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, ContractInfo<T>>;
+
+	/// Storage version of the pallet.
+	///
+	/// New networks start with the last version.
+	#[pallet::storage]
+	pub(crate) type StorageVersion<T> = StorageValue<_, Releases, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// The cost schedule for contracts the chain starts with.
+		pub current_schedule: Schedule<T>,
+	}
 
-			// If poking the contract has lead to eviction of the contract, give out the rewards.
-			if Rent::<T>::snitch_contract_should_be_evicted(&dest, handicap) {
-				T::Currency::deposit_into_existing(&rewarded, T::SurchargeReward::get())?;
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self {
+				current_schedule: Default::default(),
 			}
 		}
 	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			<CurrentSchedule<T>>::put(&self.current_schedule);
+			<StorageVersion<T>>::put(Releases::LATEST);
+		}
+	}
 }
 
 /// Public APIs provided by the contracts module.
-impl<T: Config> Module<T>
+impl<T: Config> Pallet<T>
 where
 	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
 {
@@ -573,44 +579,123 @@ where
 	/// This function is similar to `Self::call`, but doesn't perform any address lookups and better
 	/// suitable for calling directly from Rust.
 	///
-	/// It returns the exection result and the amount of used weight.
+	/// It returns the execution result, the amount of used weight and the storage deposit.
+	///
+	/// # Note
+	///
+	/// `debug` should only ever be set to `true` when executing as an RPC because
+	/// it adds allocations and could be abused to drive the runtime into an OOM panic.
+	/// If set to `true` it returns additional human readable debugging information
+	/// and the events that were emitted during execution.
 	pub fn bare_call(
 		origin: T::AccountId,
 		dest: T::AccountId,
 		value: BalanceOf<T>,
 		gas_limit: Gas,
+		storage_deposit_limit: Option<BalanceOf<T>>,
 		input_data: Vec<u8>,
-	) -> ContractExecResult {
-		let mut gas_meter = GasMeter::new(gas_limit);
-		let exec_result = Self::execute_wasm(origin, &mut gas_meter, |ctx, gas_meter| {
-			ctx.call(dest, value, gas_meter, input_data)
-		});
-		let gas_consumed = gas_meter.gas_spent();
-		ContractExecResult {
-			exec_result,
-			gas_consumed,
-		}
+		debug: bool,
+	) -> ContractExecResult<BalanceOf<T>, EventRecordOf<T>> {
+		Self::dry_run(gas_limit, debug, |gas_meter, debug_message| {
+			let (result, deposit) = Self::execute_wasm(
+				origin,
+				gas_meter,
+				storage_deposit_limit,
+				debug_message,
+				|ctx, gas_meter| ctx.call(dest, value, gas_meter, input_data),
+			);
+			(result.map_err(|e| e.error), deposit)
+		})
+	}
+
+	/// Instantiate a new contract.
+	///
+	/// This function is similar to `Self::instantiate`, but doesn't perform any address lookups
+	/// and better suitable for calling directly from Rust.
+	///
+	/// It returns the execution result, account id and the amount of used weight.
+	///
+	/// If `code` is `Code::Upload` the code is stored before the instantiation takes
+	/// place. Its deposit is part of the returned storage deposit and counts against
+	/// `storage_deposit_limit`. The upload is rolled back together with the instantiation
+	/// in case the latter fails or reverts. See [`Self::bare_call`] for a description of `debug`.
+	pub fn bare_instantiate(
+		origin: T::AccountId,
+		endowment: BalanceOf<T>,
+		gas_limit: Gas,
+		storage_deposit_limit: Option<BalanceOf<T>>,
+		code: Code<CodeHash<T>>,
+		data: Vec<u8>,
+		salt: Vec<u8>,
+		debug: bool,
+	) -> ContractInstantiateResult<T::AccountId, BalanceOf<T>, EventRecordOf<T>> {
+		use frame_support::storage::{with_transaction, TransactionOutcome::*};
+		Self::dry_run(gas_limit, debug, |gas_meter, debug_message| with_transaction(|| {
+			let (code_hash, code_deposit) = match code {
+				Code::Upload(binary) => {
+					match Self::do_upload_code(origin.clone(), binary, storage_deposit_limit) {
+						Ok(uploaded) => uploaded,
+						Err(err) => return Rollback((Err(err), Default::default())),
+					}
+				},
+				Code::Existing(hash) => (hash, Zero::zero()),
+			};
+			let storage_deposit_limit = storage_deposit_limit
+				.map(|limit| limit.saturating_sub(code_deposit));
+			let mut account_id = None;
+			let (result, deposit) = Self::execute_wasm(
+				origin,
+				gas_meter,
+				storage_deposit_limit,
+				debug_message,
+				|ctx, gas_meter| {
+					ctx.instantiate(endowment, gas_meter, &code_hash, data, &salt)
+						.map(|(address, output)| {
+							account_id = Some(address);
+							output
+						})
+				},
+			);
+			let result = result
+				.map(|result| InstantiateReturnValue {
+					result,
+					account_id: account_id.expect("account_id is set when instantiate succeeds; qed"),
+				})
+				.map_err(|e| e.error);
+			match result {
+				Ok(ref value) if value.result.is_success() =>
+					Commit((result, deposit.saturating_add(&StorageDeposit::Charge(code_deposit)))),
+				_ => Rollback((result, Default::default())),
+			}
+		}))
+	}
+
+	/// Upload new code without instantiating a contract from it.
+	///
+	/// This function is similar to `Self::put_code`, but doesn't emit the result as
+	/// an event and is therefore better suitable for calling directly from Rust.
+	pub fn bare_upload_code(
+		origin: T::AccountId,
+		code: Vec<u8>,
+		storage_deposit_limit: Option<BalanceOf<T>>,
+	) -> CodeUploadResult<CodeHash<T>, BalanceOf<T>> {
+		let (code_hash, deposit) = Self::do_upload_code(origin, code, storage_deposit_limit)?;
+		Ok(CodeUploadReturnValue { code_hash, deposit })
 	}
 
 	/// Query storage of a specified contract under a specified key.
 	pub fn get_storage(address: T::AccountId, key: [u8; 32]) -> GetStorageResult {
 		let contract_info = ContractInfoOf::<T>::get(&address)
-			.ok_or(ContractAccessError::DoesntExist)?
-			.get_alive()
-			.ok_or(ContractAccessError::IsTombstone)?;
+			.ok_or(ContractAccessError::DoesntExist)?;
 
 		let maybe_value = Storage::<T>::read(&contract_info.trie_id, &key);
 		Ok(maybe_value)
 	}
 
-	pub fn rent_projection(address: T::AccountId) -> RentProjectionResult<T::BlockNumber> {
-		Rent::<T>::compute_projection(&address)
-	}
-
 	/// Put code for benchmarks which does not check or instrument the code.
 	#[cfg(feature = "runtime-benchmarks")]
-	pub fn put_code_raw(code: Vec<u8>) -> DispatchResult {
-		let schedule = <Module<T>>::current_schedule();
+	pub fn put_code_raw(code: Vec<u8>) -> frame_support::dispatch::DispatchResult {
+		let schedule = <Pallet<T>>::current_schedule();
 		let result = wasm::save_code_raw::<T>(code, &schedule);
 		result.map(|_| ()).map_err(Into::into)
 	}
@@ -638,83 +723,118 @@ where
 	}
 }
 
-impl<T: Config> Module<T>
+impl<T: Config> Pallet<T>
 where
 	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
 {
+	/// Runs `func` within a new top level execution context of `origin`.
+	///
+	/// Returns the result of `func` together with the storage deposit that was charged
+	/// from or refunded to the `origin`. Nothing is charged in case of an error as all
+	/// changes are rolled back.
 	fn execute_wasm(
 		origin: T::AccountId,
 		gas_meter: &mut GasMeter<T>,
+		storage_deposit_limit: Option<BalanceOf<T>>,
+		debug_message: Option<&RefCell<Vec<u8>>>,
 		func: impl FnOnce(&mut ExecutionContext<T, WasmVm<T>, WasmLoader<T>>, &mut GasMeter<T>) -> ExecResult,
-	) -> ExecResult {
+	) -> (ExecResult, StorageDeposit<BalanceOf<T>>) {
+		let storage_meter = match StorageMeter::new(&origin, storage_deposit_limit) {
+			Ok(meter) => meter,
+			Err(err) => return (Err(err.into()), Default::default()),
+		};
 		let cfg = ConfigCache::preload();
 		let vm = WasmVm::new(&cfg.schedule);
 		let loader = WasmLoader::new(&cfg.schedule);
-		let mut ctx = ExecutionContext::top_level(origin, &cfg, &vm, &loader);
-		func(&mut ctx, gas_meter)
+		let mut ctx = ExecutionContext::top_level(origin, &cfg, &vm, &loader, storage_meter);
+		ctx.debug_message = debug_message;
+		let result = func(&mut ctx, gas_meter);
+		let deposit = if result.is_ok() {
+			ctx.storage_meter.into_deposit()
+		} else {
+			Default::default()
+		};
+		(result, deposit)
 	}
-}
 
-decl_event! {
-	pub enum Event<T>
-	where
-		Balance = BalanceOf<T>,
-		<T as frame_system::Config>::AccountId,
-		<T as frame_system::Config>::Hash
-	{
-		/// Contract deployed by address at the specified address. \[owner, contract\]
-		Instantiated(AccountId, AccountId),
+	/// Stores the given code after checking and instrumenting it.
+	///
+	/// Returns the code hash and the deposit that was reserved from `origin`.
+	fn do_upload_code(
+		origin: T::AccountId,
+		code: Vec<u8>,
+		storage_deposit_limit: Option<BalanceOf<T>>,
+	) -> Result<(CodeHash<T>, BalanceOf<T>), DispatchError> {
+		let schedule = <Pallet<T>>::current_schedule();
+		ensure!(code.len() as u32 <= schedule.limits.code_size, Error::<T>::CodeTooLarge);
+		let (code_hash, deposit) =
+			wasm::save_code::<T>(code, &origin, storage_deposit_limit, &schedule)?;
+		Self::deposit_event(Event::CodeStored(code_hash));
+		Ok((code_hash, deposit))
+	}
 
-		/// Contract has been evicted and is now in tombstone state.
-		/// \[contract, tombstone\]
-		///
-		/// # Params
-		///
-		/// - `contract`: `AccountId`: The account ID of the evicted contract.
-		/// - `tombstone`: `bool`: True if the evicted contract left behind a tombstone.
-		Evicted(AccountId, bool),
+	/// Runs `func` and collects the information that is returned alongside the result of
+	/// `bare_call` and `bare_instantiate`.
+	///
+	/// `func` returns the storage deposit alongside its result. Events are only collected
+	/// when `debug` is set.
+	fn dry_run<R>(
+		gas_limit: Gas,
+		debug: bool,
+		func: impl FnOnce(
+			&mut GasMeter<T>,
+			Option<&RefCell<Vec<u8>>>,
+		) -> (Result<R, DispatchError>, StorageDeposit<BalanceOf<T>>),
+	) -> ContractResult<Result<R, DispatchError>, BalanceOf<T>, EventRecordOf<T>> {
+		let mut gas_meter = GasMeter::new(gas_limit);
+		let debug_message = RefCell::new(Vec::new());
+		let events_before = if debug {
+			<frame_system::Module<T>>::events().len()
+		} else {
+			0
+		};
 
-		/// Restoration for a contract has been successful.
-		/// \[donor, dest, code_hash, rent_allowance\]
-		///
-		/// # Params
-		///
-		/// - `donor`: `AccountId`: Account ID of the restoring contract
-		/// - `dest`: `AccountId`: Account ID of the restored contract
-		/// - `code_hash`: `Hash`: Code hash of the restored contract
-		/// - `rent_allowance: `Balance`: Rent allowance of the restored contract
-		Restored(AccountId, AccountId, Hash, Balance),
+		let (result, storage_deposit) =
+			func(&mut gas_meter, if debug { Some(&debug_message) } else { None });
 
-		/// Code with the specified hash has been stored.
-		/// \[code_hash\]
-		CodeStored(Hash),
+		let events = if debug {
+			Some(<frame_system::Module<T>>::events().into_iter().skip(events_before).collect())
+		} else {
+			None
+		};
+
+		ContractResult {
+			gas_consumed: gas_meter.gas_spent(),
+			gas_required: gas_meter.gas_required(),
+			storage_deposit,
+			debug_message: debug_message.into_inner(),
+			result,
+			events,
+		}
+	}
+}
 
-		/// Triggered when the current \[schedule\] is updated.
-		ScheduleUpdated(u32),
+// A value placed in storage that represents the current version of the contracts storage.
+// This value is used by the `on_runtime_upgrade` logic to determine whether we run
+// storage migration logic. See the `migration` module for the layout of each version.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+enum Releases {
+	/// Contracts are either alive or tombstones and pay rent for their storage.
+	V1,
+	/// Rent is replaced by storage deposits.
+	V2,
+	/// `PrefabWasmModule` stores the length of the pristine code.
+	V3,
+}
 
-		/// An event deposited upon execution of a contract from the account.
-		/// \[account, data\]
-		ContractExecution(AccountId, Vec<u8>),
-	}
+impl Releases {
+	/// The version new networks start with and all migrations lead to.
+	const LATEST: Self = Releases::V3;
 }
 
-decl_storage! {
-	trait Store for Module<T: Config> as Contracts
-	where
-		T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
-	{
-		/// Current cost schedule for contracts.
-		CurrentSchedule get(fn current_schedule) config(): Schedule<T> = Default::default();
-		/// A mapping from an original code hash to the original code, untouched by instrumentation.
-		pub PristineCode: map hasher(identity) CodeHash<T> => Option<Vec<u8>>;
-		/// A mapping between an original code hash and instrumented wasm code, ready for execution.
-		pub CodeStorage: map hasher(identity) CodeHash<T> => Option<wasm::PrefabWasmModule>;
-		/// The subtrie counter.
-		pub AccountCounter: u64 = 0;
-		/// The code associated with a given account.
-		///
-		/// TWOX-NOTE: SAFE since `AccountId` is a secure hash.
-		pub ContractInfoOf: map hasher(twox_64_concat) T::AccountId => Option<ContractInfo<T>>;
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1
 	}
 }
 
@@ -725,7 +845,6 @@ decl_storage! {
 pub struct ConfigCache<T: Config> {
 	pub schedule: Schedule<T>,
 	pub existential_deposit: BalanceOf<T>,
-	pub tombstone_deposit: BalanceOf<T>,
 	pub max_depth: u32,
 	pub max_value_size: u32,
 }
@@ -736,30 +855,27 @@ where
 {
 	fn preload() -> ConfigCache<T> {
 		ConfigCache {
-			schedule: <Module<T>>::current_schedule(),
+			schedule: <Pallet<T>>::current_schedule(),
 			existential_deposit: T::Currency::minimum_balance(),
-			tombstone_deposit: T::TombstoneDeposit::get(),
 			max_depth: T::MaxDepth::get(),
 			max_value_size: T::MaxValueSize::get(),
 		}
 	}
 
-	/// Subsistence threshold is the extension of the minimum balance (aka existential deposit) by the
-	/// tombstone deposit, required for leaving a tombstone.
+	/// Subsistence threshold is the minimum balance (aka existential deposit) a contract
+	/// must keep in order to stay alive.
 	///
-	/// Rent or any contract initiated balance transfer mechanism cannot make the balance lower
-	/// than the subsistence threshold in order to guarantee that a tombstone is created.
+	/// Any contract initiated balance transfer mechanism cannot make the balance lower
+	/// than the subsistence threshold. The storage deposit is reserved and therefore not
+	/// part of it.
 	///
-	/// The only way to completely kill a contract without a tombstone is calling `seal_terminate`.
+	/// The only way to completely kill a contract is calling `seal_terminate`.
 	pub fn subsistence_threshold(&self) -> BalanceOf<T> {
-		self.existential_deposit.saturating_add(self.tombstone_deposit)
+		self.existential_deposit
 	}
 
 	/// The same as `subsistence_threshold` but without the need for a preloaded instance.
-	///
-	/// This is for cases where this value is needed in rent calculation rather than
-	/// during contract execution.
 	pub fn subsistence_threshold_uncached() -> BalanceOf<T> {
-		T::Currency::minimum_balance().saturating_add(T::TombstoneDeposit::get())
+		T::Currency::minimum_balance()
 	}
 }
@@ -0,0 +1,463 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! Storage migrations for the contracts module.
+//!
+//! Every change to the on-chain layout of this module bumps `Releases` and adds a module
+//! here which converts the layout of the previous version. The migrations are applied in
+//! order until the storage reaches `Releases::LATEST`, so a chain can upgrade from any
+//! older version in a single runtime upgrade.
+//!
+//! - [`v2`]: `V1` stored contracts as either alive or tombstone and charged rent for their
+//!   storage. `V2` replaced rent with storage deposits and dropped the rent related weights
+//!   from the `Schedule`.
+//! - [`v3`]: `V3` added the length of the pristine code to `PrefabWasmModule`.
+//!
+//! Each migration comes with `pre_upgrade` and `post_upgrade` checks. They are meant to be
+//! run against a copy of a chain's state by testing tools and are only compiled with the
+//! `try-runtime` feature.
+
+use crate::{Config, Releases, StorageVersion};
+use frame_support::{traits::Get, weights::Weight};
+use sp_core::crypto::UncheckedFrom;
+#[cfg(feature = "try-runtime")]
+use codec::Decode;
+#[cfg(feature = "try-runtime")]
+use frame_support::{ensure, storage::unhashed};
+#[cfg(feature = "try-runtime")]
+use sp_std::prelude::*;
+
+/// Migrate the storage of the contracts module to the latest version.
+pub fn migrate<T: Config>() -> Weight
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+{
+	let mut weight = T::DbWeight::get().reads(1);
+	let version = <StorageVersion<T>>::get();
+	if version == Releases::LATEST {
+		return weight;
+	}
+	if version < Releases::V2 {
+		weight = weight.saturating_add(v2::migrate::<T>());
+	}
+	if version < Releases::V3 {
+		weight = weight.saturating_add(v3::migrate::<T>());
+	}
+	<StorageVersion<T>>::put(Releases::LATEST);
+	weight.saturating_add(T::DbWeight::get().writes(1))
+}
+
+/// Check that the storage can be migrated from its current version.
+#[cfg(feature = "try-runtime")]
+pub fn pre_upgrade<T: Config>() -> Result<(), &'static str> {
+	let version = <StorageVersion<T>>::get();
+	if version < Releases::V2 {
+		v2::pre_upgrade::<T>()?;
+	}
+	if version < Releases::V3 {
+		v3::pre_upgrade::<T>()?;
+	}
+	Ok(())
+}
+
+/// Check that the storage is in the layout of the latest version.
+#[cfg(feature = "try-runtime")]
+pub fn post_upgrade<T: Config>() -> Result<(), &'static str> {
+	ensure!(
+		<StorageVersion<T>>::get() == Releases::LATEST,
+		"Storage version was not bumped to the latest version",
+	);
+	v2::post_upgrade::<T>()?;
+	v3::post_upgrade::<T>()?;
+	Ok(())
+}
+
+/// Decode every value stored under `prefix` as `V`.
+///
+/// Returns the values together with the part of their keys that follows `prefix`. Unlike
+/// iterating a map this fails on the first value that can't be decoded instead of silently
+/// skipping it.
+#[cfg(feature = "try-runtime")]
+fn decode_all<V: Decode>(prefix: &[u8]) -> Result<Vec<(Vec<u8>, V)>, &'static str> {
+	let mut values = Vec::new();
+	let mut previous_key = prefix.to_vec();
+	while let Some(key) = sp_io::storage::next_key(&previous_key)
+		.filter(|key| key.starts_with(prefix))
+	{
+		let raw = unhashed::get_raw(&key).ok_or("Storage value vanished during iteration")?;
+		let value = V::decode(&mut &raw[..]).map_err(|_| "Failed to decode storage value")?;
+		values.push((key[prefix.len()..].to_vec(), value));
+		previous_key = key;
+	}
+	Ok(values)
+}
+
+/// Translate every alive contract into the storage deposit layout and remove all tombstones.
+pub mod v2 {
+	use crate::{
+		BalanceOf, CodeHash, Config, ContractInfo, ContractInfoOf, CurrentSchedule, Schedule,
+		TrieId,
+	};
+	use codec::Decode;
+	use frame_support::{
+		storage::{unhashed, StorageValue},
+		traits::Get,
+		weights::Weight,
+	};
+	use sp_runtime::traits::Zero;
+	use sp_std::cell::Cell;
+	#[cfg(feature = "try-runtime")]
+	use super::decode_all;
+	#[cfg(feature = "try-runtime")]
+	use frame_support::{ensure, storage::StoragePrefixedMap};
+
+	/// Used to hand the number of alive contracts from `pre_upgrade` to `post_upgrade`.
+	#[cfg(feature = "try-runtime")]
+	const ALIVE_CONTRACTS_KEY: &[u8] = b":contracts:try-runtime:v2:alive";
+
+	/// Used to hand the version of the replaced schedule from `pre_upgrade` to `post_upgrade`.
+	#[cfg(feature = "try-runtime")]
+	const SCHEDULE_VERSION_KEY: &[u8] = b":contracts:try-runtime:v2:schedule_version";
+
+	/// The layout of an alive contract before storage deposits were introduced.
+	#[derive(Decode)]
+	struct AliveContractInfo<CodeHash, Balance, BlockNumber> {
+		trie_id: TrieId,
+		_storage_size: u32,
+		_empty_pair_count: u32,
+		_total_pair_count: u32,
+		code_hash: CodeHash,
+		_rent_allowance: Balance,
+		_deduct_block: BlockNumber,
+		_last_write: Option<BlockNumber>,
+	}
+
+	/// The layout of a contract before storage deposits were introduced.
+	#[derive(Decode)]
+	enum OldContractInfo<CodeHash, Balance, BlockNumber, Hash> {
+		Alive(AliveContractInfo<CodeHash, Balance, BlockNumber>),
+		Tombstone(Hash),
+	}
+
+	type OldContractInfoOf<T> = OldContractInfo<
+		CodeHash<T>,
+		BalanceOf<T>,
+		<T as frame_system::Config>::BlockNumber,
+		<T as frame_system::Config>::Hash,
+	>;
+
+	/// The version of the stored schedule. It is the first field of the schedule and can
+	/// therefore be decoded from the old layout.
+	fn stored_schedule_version<T: Config>() -> Option<u32> {
+		unhashed::get_raw(&<CurrentSchedule<T>>::hashed_key())
+			.and_then(|raw| u32::decode(&mut &raw[..]).ok())
+	}
+
+	/// Alive contracts are migrated without any deposit as they never paid one. Tombstones
+	/// are removed since they can't be restored anymore.
+	///
+	/// The stored schedule can't be decoded anymore and is replaced by the default one. Its
+	/// version is raised above the replaced one so that all code is re-instrumented.
+	pub fn migrate<T: Config>() -> Weight {
+		let version = stored_schedule_version::<T>().map_or(0, |v| v.saturating_add(1));
+		let schedule = Schedule::<T>::default();
+		<CurrentSchedule<T>>::put(Schedule { version: version.max(schedule.version), ..schedule });
+
+		let translated = Cell::new(0 as Weight);
+		<ContractInfoOf<T>>::translate::<OldContractInfoOf<T>, _>(|_, old| {
+			translated.set(translated.get().saturating_add(1));
+			match old {
+				OldContractInfo::Alive(alive) => Some(ContractInfo::<T> {
+					trie_id: alive.trie_id,
+					code_hash: alive.code_hash,
+					storage_deposit: <BalanceOf<T>>::zero(),
+				}),
+				OldContractInfo::Tombstone(_) => None,
+			}
+		});
+		T::DbWeight::get().reads_writes(
+			translated.get().saturating_add(1),
+			translated.get().saturating_add(1),
+		)
+	}
+
+	/// All contracts must be decodable in the old layout.
+	#[cfg(feature = "try-runtime")]
+	pub fn pre_upgrade<T: Config>() -> Result<(), &'static str> {
+		if let Some(version) = stored_schedule_version::<T>() {
+			unhashed::put(SCHEDULE_VERSION_KEY, &version);
+		}
+		let alive = decode_all::<OldContractInfoOf<T>>(&<ContractInfoOf<T>>::final_prefix())?
+			.into_iter()
+			.filter(|(_, info)| matches!(info, OldContractInfo::Alive(_)))
+			.count() as u32;
+		unhashed::put(ALIVE_CONTRACTS_KEY, &alive);
+		Ok(())
+	}
+
+	/// All contracts and the schedule must be decodable in the new layout and every alive
+	/// contract must have been kept. The schedule version must have been raised.
+	#[cfg(feature = "try-runtime")]
+	pub fn post_upgrade<T: Config>() -> Result<(), &'static str> {
+		let schedule = unhashed::get_raw(&<CurrentSchedule<T>>::hashed_key())
+			.ok_or("Schedule was not written")?;
+		let schedule = Schedule::<T>::decode(&mut &schedule[..])
+			.map_err(|_| "Failed to decode schedule")?;
+		if let Some(version) = unhashed::take::<u32>(SCHEDULE_VERSION_KEY) {
+			ensure!(schedule.version > version, "Schedule version was not raised");
+		}
+		let contracts = decode_all::<ContractInfo<T>>(&<ContractInfoOf<T>>::final_prefix())?;
+		if let Some(alive) = unhashed::take::<u32>(ALIVE_CONTRACTS_KEY) {
+			ensure!(contracts.len() as u32 == alive, "Alive contracts were lost in migration");
+		}
+		Ok(())
+	}
+}
+
+/// Add the length of the pristine code to every `PrefabWasmModule`.
+pub mod v3 {
+	use crate::{CodeStorage, Config, PristineCode, wasm::PrefabWasmModule};
+	use codec::Decode;
+	use frame_support::{traits::Get, weights::Weight};
+	use sp_std::{cell::Cell, prelude::*};
+	#[cfg(feature = "try-runtime")]
+	use super::decode_all;
+	#[cfg(feature = "try-runtime")]
+	use frame_support::{ensure, storage::{unhashed, StoragePrefixedMap}};
+
+	/// Used to hand the number of stored codes from `pre_upgrade` to `post_upgrade`.
+	#[cfg(feature = "try-runtime")]
+	const CODES_KEY: &[u8] = b":contracts:try-runtime:v3:codes";
+
+	/// The layout of a `PrefabWasmModule` before the pristine code length was added.
+	#[derive(Decode)]
+	struct OldPrefabWasmModule {
+		#[codec(compact)]
+		schedule_version: u32,
+		#[codec(compact)]
+		initial: u32,
+		#[codec(compact)]
+		maximum: u32,
+		_reserved: Option<()>,
+		code: Vec<u8>,
+	}
+
+	/// The length is taken from the pristine code which is always stored alongside the
+	/// instrumented code.
+	pub fn migrate<T: Config>() -> Weight {
+		let translated = Cell::new(0 as Weight);
+		<CodeStorage<T>>::translate::<OldPrefabWasmModule, _>(|code_hash, old| {
+			translated.set(translated.get().saturating_add(1));
+			Some(PrefabWasmModule {
+				schedule_version: old.schedule_version,
+				initial: old.initial,
+				maximum: old.maximum,
+				original_code_len: <PristineCode<T>>::decode_len(&code_hash)
+					.unwrap_or_default() as u32,
+				code: old.code,
+			})
+		});
+		T::DbWeight::get().reads_writes(translated.get().saturating_mul(2), translated.get())
+	}
+
+	/// All codes must be decodable in the old layout and have a pristine version.
+	#[cfg(feature = "try-runtime")]
+	pub fn pre_upgrade<T: Config>() -> Result<(), &'static str> {
+		let codes = decode_all::<OldPrefabWasmModule>(&<CodeStorage<T>>::final_prefix())?;
+		// Both maps use the identity hasher which makes their key suffixes interchangeable.
+		let pristine_prefix = <PristineCode<T>>::final_prefix();
+		ensure!(
+			codes.iter().all(|(suffix, _)| {
+				unhashed::exists(&[&pristine_prefix[..], &suffix[..]].concat())
+			}),
+			"Instrumented code without pristine code found",
+		);
+		unhashed::put(CODES_KEY, &(codes.len() as u32));
+		Ok(())
+	}
+
+	/// All codes must be decodable in the new layout and store the correct length.
+	#[cfg(feature = "try-runtime")]
+	pub fn post_upgrade<T: Config>() -> Result<(), &'static str> {
+		let codes = decode_all::<PrefabWasmModule>(&<CodeStorage<T>>::final_prefix())?;
+		if let Some(count) = unhashed::take::<u32>(CODES_KEY) {
+			ensure!(codes.len() as u32 == count, "Codes were lost in migration");
+		}
+		for (code_hash, module) in <CodeStorage<T>>::iter() {
+			ensure!(
+				<PristineCode<T>>::decode_len(&code_hash) == Some(module.original_code_len as usize),
+				"Stored pristine code length does not match",
+			);
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		CodeStorage, ContractInfo, ContractInfoOf, CurrentSchedule, PristineCode, Schedule,
+		TrieId,
+	};
+	use crate::tests::{ExtBuilder, Test, ALICE, BOB};
+	use codec::Encode;
+	use frame_support::storage::{unhashed, StorageValue};
+
+	#[derive(Encode)]
+	struct AliveV1 {
+		trie_id: TrieId,
+		storage_size: u32,
+		empty_pair_count: u32,
+		total_pair_count: u32,
+		code_hash: sp_core::H256,
+		rent_allowance: u64,
+		deduct_block: u64,
+		last_write: Option<u64>,
+	}
+
+	#[derive(Encode)]
+	enum InfoV1 {
+		Alive(AliveV1),
+		Tombstone(sp_core::H256),
+	}
+
+	#[derive(Encode)]
+	struct PrefabV2 {
+		#[codec(compact)]
+		schedule_version: u32,
+		#[codec(compact)]
+		initial: u32,
+		#[codec(compact)]
+		maximum: u32,
+		reserved: Option<()>,
+		code: Vec<u8>,
+	}
+
+	/// Put the storage of a chain that still runs the `V1` layout.
+	fn put_v1_storage() {
+		<StorageVersion<Test>>::put(Releases::V1);
+		// A schedule with weights that no longer exist, only the version is readable.
+		unhashed::put_raw(
+			&<CurrentSchedule<Test>>::hashed_key(),
+			&(7u32, false, [0xffu8; 3]).encode(),
+		);
+		let alive = InfoV1::Alive(AliveV1 {
+			trie_id: vec![1, 2, 3],
+			storage_size: 10,
+			empty_pair_count: 0,
+			total_pair_count: 1,
+			code_hash: sp_core::H256::repeat_byte(1),
+			rent_allowance: 100,
+			deduct_block: 5,
+			last_write: Some(4),
+		});
+		unhashed::put(&<ContractInfoOf<Test>>::hashed_key_for(&ALICE), &alive);
+		unhashed::put(
+			&<ContractInfoOf<Test>>::hashed_key_for(&BOB),
+			&InfoV1::Tombstone(sp_core::H256::repeat_byte(2)),
+		);
+		<PristineCode<Test>>::insert(sp_core::H256::repeat_byte(1), vec![0u8; 42]);
+		unhashed::put(
+			&<CodeStorage<Test>>::hashed_key_for(sp_core::H256::repeat_byte(1)),
+			&PrefabV2 {
+				schedule_version: 3,
+				initial: 1,
+				maximum: 16,
+				reserved: None,
+				code: vec![1, 2, 3, 4],
+			},
+		);
+	}
+
+	#[test]
+	fn migrate_v1_to_latest_works() {
+		ExtBuilder::default().build().execute_with(|| {
+			put_v1_storage();
+
+			migrate::<Test>();
+
+			assert_eq!(<StorageVersion<Test>>::get(), Releases::LATEST);
+			assert_eq!(
+				<ContractInfoOf<Test>>::get(&ALICE),
+				Some(ContractInfo::<Test> {
+					trie_id: vec![1, 2, 3],
+					code_hash: sp_core::H256::repeat_byte(1),
+					storage_deposit: 0,
+				}),
+			);
+			assert!(!<ContractInfoOf<Test>>::contains_key(&BOB));
+			assert_eq!(
+				<CurrentSchedule<Test>>::get(),
+				Schedule { version: 8, ..Default::default() },
+			);
+			let module = <CodeStorage<Test>>::get(sp_core::H256::repeat_byte(1)).unwrap();
+			assert_eq!(module.schedule_version, 3);
+			assert_eq!(module.original_code_len, 42);
+			assert_eq!(module.code, vec![1, 2, 3, 4]);
+		});
+	}
+
+	#[test]
+	fn migrate_v2_to_latest_keeps_contracts() {
+		ExtBuilder::default().build().execute_with(|| {
+			put_v1_storage();
+			v2::migrate::<Test>();
+			<StorageVersion<Test>>::put(Releases::V2);
+
+			migrate::<Test>();
+
+			assert_eq!(<StorageVersion<Test>>::get(), Releases::LATEST);
+			assert!(<ContractInfoOf<Test>>::contains_key(&ALICE));
+			let module = <CodeStorage<Test>>::get(sp_core::H256::repeat_byte(1)).unwrap();
+			assert_eq!(module.original_code_len, 42);
+		});
+	}
+
+	#[test]
+	fn migrate_latest_is_noop() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_eq!(<StorageVersion<Test>>::get(), Releases::LATEST);
+			assert_eq!(
+				migrate::<Test>(),
+				<Test as frame_system::Config>::DbWeight::get().reads(1),
+			);
+		});
+	}
+
+	#[cfg(feature = "try-runtime")]
+	#[test]
+	fn upgrade_checks_pass() {
+		ExtBuilder::default().build().execute_with(|| {
+			put_v1_storage();
+
+			assert_eq!(pre_upgrade::<Test>(), Ok(()));
+			migrate::<Test>();
+			assert_eq!(post_upgrade::<Test>(), Ok(()));
+		});
+	}
+
+	#[cfg(feature = "try-runtime")]
+	#[test]
+	fn pre_upgrade_rejects_undecodable_contracts() {
+		ExtBuilder::default().build().execute_with(|| {
+			put_v1_storage();
+			unhashed::put_raw(&<ContractInfoOf<Test>>::hashed_key_for(&BOB), &[0xff]);
+
+			assert!(pre_upgrade::<Test>().is_err());
+		});
+	}
+}
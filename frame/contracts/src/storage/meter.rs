@@ -0,0 +1,243 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! This module contains functions to meter the storage deposit.
+//!
+//! Every storage item a contract creates has to be paid for by reserving a deposit that
+//! is proportional to the number of items and bytes it occupies. The deposit is charged
+//! from the origin of the transaction and reserved at the contract account. It is
+//! refunded to the origin of the transaction that removes the storage again.
+
+use crate::{BalanceOf, Config, ContractInfo, ContractInfoOf, Error};
+use frame_support::{
+	dispatch::{DispatchError, DispatchResult},
+	traits::{BalanceStatus, Currency, ExistenceRequirement, Get, ReservableCurrency},
+	ensure,
+};
+use pallet_contracts_primitives::StorageDeposit;
+use sp_runtime::traits::{Saturating, Zero};
+
+/// The change in storage usage caused by a contract.
+///
+/// Items and bytes are tracked separately because every item is charged a fixed deposit
+/// on top of the deposit for the bytes it occupies.
+#[derive(Default, Clone, Copy, PartialEq, Eq, sp_runtime::RuntimeDebug)]
+pub struct Diff {
+	/// How many bytes were added to storage.
+	pub bytes_added: u32,
+	/// How many bytes were removed from storage.
+	pub bytes_removed: u32,
+	/// How many storage items were added to storage.
+	pub items_added: u32,
+	/// How many storage items were removed from storage.
+	pub items_removed: u32,
+}
+
+impl Diff {
+	/// Combine two diffs into one that contains the changes of both.
+	pub fn saturating_add(&self, rhs: &Self) -> Self {
+		Self {
+			bytes_added: self.bytes_added.saturating_add(rhs.bytes_added),
+			bytes_removed: self.bytes_removed.saturating_add(rhs.bytes_removed),
+			items_added: self.items_added.saturating_add(rhs.items_added),
+			items_removed: self.items_removed.saturating_add(rhs.items_removed),
+		}
+	}
+
+	/// Calculate how much of a charge or refund results from applying this diff.
+	pub fn to_deposit<T: Config>(&self) -> StorageDeposit<BalanceOf<T>> {
+		let per_byte = T::DepositPerByte::get();
+		let per_item = T::DepositPerItem::get();
+		let charge = per_byte.saturating_mul(self.bytes_added.into())
+			.saturating_add(per_item.saturating_mul(self.items_added.into()));
+		let refund = per_byte.saturating_mul(self.bytes_removed.into())
+			.saturating_add(per_item.saturating_mul(self.items_removed.into()));
+		StorageDeposit::Charge(charge).saturating_sub(&StorageDeposit::Charge(refund))
+	}
+}
+
+/// Meters the storage deposit of a call stack.
+///
+/// Every frame of the call stack owns a meter which is created from the meter of its caller
+/// by [`Self::nested`]. The storage changes of the executing contract are recorded in the
+/// meter of its frame and settled against the origin when the frame finishes successfully.
+/// The caller then absorbs the meter of the finished frame so that its deposits count
+/// against the limit of the whole call stack.
+pub struct StorageMeter<T: Config> {
+	/// The amount of balance this meter (including all absorbed meters) may charge.
+	limit: BalanceOf<T>,
+	/// The deposits settled by this meter and all absorbed meters.
+	total: StorageDeposit<BalanceOf<T>>,
+	/// The storage changes made by the contract executing in the frame of this meter.
+	own: Diff,
+}
+
+impl<T: Config> StorageMeter<T> {
+	/// Create the meter for the top level frame.
+	///
+	/// If no `limit` is supplied the origin can use its whole free balance except the
+	/// existential deposit to pay for storage.
+	pub fn new(origin: &T::AccountId, limit: Option<BalanceOf<T>>) -> Result<Self, DispatchError> {
+		let available = T::Currency::free_balance(origin)
+			.saturating_sub(T::Currency::minimum_balance());
+		let limit = limit.unwrap_or(available);
+		ensure!(limit <= available, Error::<T>::StorageDepositNotEnoughFunds);
+		Ok(Self {
+			limit,
+			total: Default::default(),
+			own: Default::default(),
+		})
+	}
+
+	/// Create a meter for a nested frame which can use whatever is left of this meter.
+	pub fn nested(&self) -> Self {
+		Self {
+			limit: self.available(),
+			total: Default::default(),
+			own: Default::default(),
+		}
+	}
+
+	/// The amount of balance that can still be charged.
+	pub fn available(&self) -> BalanceOf<T> {
+		self.total.available(&self.limit)
+	}
+
+	/// Record a change in storage usage of the contract executing in this frame.
+	pub fn record(&mut self, diff: Diff) {
+		self.own = self.own.saturating_add(&diff);
+	}
+
+	/// Add the deposits of a nested meter whose frame was committed.
+	pub fn absorb(&mut self, nested: Self) {
+		self.total = self.total.saturating_add(&nested.total);
+	}
+
+	/// Charge or refund the deposit for the changes recorded for `contract`.
+	///
+	/// This must be called before the storage transaction of the frame is committed so that
+	/// a failure reverts the changes the deposit is charged for.
+	pub fn settle(&mut self, origin: &T::AccountId, contract: &T::AccountId) -> DispatchResult {
+		let diff = sp_std::mem::take(&mut self.own);
+		let mut info = match <ContractInfoOf<T>>::get(contract) {
+			Some(info) => info,
+			// The contract terminated itself. Its deposit was already refunded.
+			None => return Ok(()),
+		};
+		let deposit = match diff.to_deposit::<T>() {
+			StorageDeposit::Charge(amount) => {
+				if amount.is_zero() {
+					return Ok(());
+				}
+				ensure!(amount <= self.available(), Error::<T>::StorageDepositLimitExhausted);
+				T::Currency::transfer(origin, contract, amount, ExistenceRequirement::KeepAlive)
+					.and_then(|_| T::Currency::reserve(contract, amount))
+					.map_err(|_| Error::<T>::StorageDepositNotEnoughFunds)?;
+				info.storage_deposit = info.storage_deposit.saturating_add(amount);
+				StorageDeposit::Charge(amount)
+			}
+			StorageDeposit::Refund(amount) => {
+				// Contracts that were created before storage deposits were introduced never
+				// paid for their storage. Hence we can only refund what they actually paid.
+				let amount = amount.min(info.storage_deposit);
+				Self::refund(origin, contract, amount);
+				info.storage_deposit = info.storage_deposit.saturating_sub(amount);
+				StorageDeposit::Refund(amount)
+			}
+		};
+		<ContractInfoOf<T>>::insert(contract, info);
+		self.total = self.total.saturating_add(&deposit);
+		Ok(())
+	}
+
+	/// Refund the whole deposit of a contract that is about to be removed.
+	///
+	/// Any changes recorded for the contract are discarded as its storage is removed anyways.
+	pub fn terminate(
+		&mut self,
+		origin: &T::AccountId,
+		contract: &T::AccountId,
+		info: &ContractInfo<T>,
+	) {
+		self.own = Default::default();
+		Self::refund(origin, contract, info.storage_deposit);
+		self.total = self.total.saturating_add(&StorageDeposit::Refund(info.storage_deposit));
+	}
+
+	/// The deposit that was charged or refunded by this meter and all absorbed meters.
+	pub fn into_deposit(self) -> StorageDeposit<BalanceOf<T>> {
+		self.total
+	}
+
+	/// Move the deposit reserved at `contract` back to the free balance of `origin`.
+	fn refund(origin: &T::AccountId, contract: &T::AccountId, amount: BalanceOf<T>) {
+		if amount.is_zero() {
+			return;
+		}
+		// This can only fail if the origin doesn't exist which is impossible because
+		// it is the account that pays for the transaction.
+		let _ = T::Currency::repatriate_reserved(contract, origin, amount, BalanceStatus::Free);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::{ExtBuilder, Test, ALICE};
+	use crate::tests::test_utils::set_balance;
+
+	#[test]
+	fn diff_to_deposit_works() {
+		// `DepositPerByte` is 1 and `DepositPerItem` is 2 in the test runtime.
+		let diff = Diff { bytes_added: 10, items_added: 2, ..Default::default() };
+		assert_eq!(diff.to_deposit::<Test>(), StorageDeposit::Charge(14));
+
+		let diff = Diff { bytes_removed: 10, items_removed: 2, ..Default::default() };
+		assert_eq!(diff.to_deposit::<Test>(), StorageDeposit::Refund(14));
+
+		let diff = Diff { bytes_added: 5, bytes_removed: 10, items_added: 1, items_removed: 1 };
+		assert_eq!(diff.to_deposit::<Test>(), StorageDeposit::Refund(5));
+	}
+
+	#[test]
+	fn limit_cannot_exceed_free_balance() {
+		ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+			set_balance(&ALICE, 1_000);
+			assert!(StorageMeter::<Test>::new(&ALICE, Some(950)).is_ok());
+			assert!(StorageMeter::<Test>::new(&ALICE, Some(951)).is_err());
+			assert_eq!(StorageMeter::<Test>::new(&ALICE, None).unwrap().available(), 950);
+		});
+	}
+
+	#[test]
+	fn nested_meter_uses_what_is_left() {
+		ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+			set_balance(&ALICE, 1_000);
+			let mut meter = StorageMeter::<Test>::new(&ALICE, Some(100)).unwrap();
+			let mut nested = meter.nested();
+			nested.total = StorageDeposit::Charge(40);
+			meter.absorb(nested);
+			assert_eq!(meter.available(), 60);
+			assert_eq!(meter.nested().available(), 60);
+
+			let mut nested = meter.nested();
+			nested.total = StorageDeposit::Refund(10);
+			meter.absorb(nested);
+			assert_eq!(meter.available(), 70);
+			assert_eq!(meter.into_deposit(), StorageDeposit::Charge(30));
+		});
+	}
+}
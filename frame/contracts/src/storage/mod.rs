@@ -16,20 +16,22 @@
 
 //! This module contains routines for accessing and altering a contract related state.
 
+pub mod meter;
+
 use crate::{
 	exec::{AccountIdOf, StorageKey},
-	AliveContractInfo, BalanceOf, CodeHash, ContractInfo, ContractInfoOf, Config, TrieId,
-	AccountCounter,
+	BalanceOf, CodeHash, ContractInfo, ContractInfoOf, Config, TrieId, AccountCounter, wasm,
+	Error, PristineCode,
 };
+use self::meter::Diff;
 use sp_std::prelude::*;
 use sp_std::marker::PhantomData;
 use sp_io::hashing::blake2_256;
-use sp_runtime::traits::Bounded;
+use sp_runtime::traits::Zero;
 use sp_core::crypto::UncheckedFrom;
-use frame_support::{storage::child, StorageMap};
+use frame_support::{dispatch::DispatchError, ensure, storage::child};
 
-/// An error that means that the account requested either doesn't exist or represents a tombstone
-/// account.
+/// An error that means that the account requested doesn't exist.
 #[cfg_attr(test, derive(PartialEq, Eq, Debug))]
 pub struct ContractAbsentError;
 
@@ -52,26 +54,17 @@ where
 	///
 	/// If the `opt_new_value` is `None` then the kv pair is removed.
 	///
-	/// This function also updates the bookkeeping info such as: number of total non-empty pairs a
-	/// contract owns, the last block the storage was written to, etc. That's why, in contrast to
-	/// `read`, this function also requires the `account` ID.
-	///
-	/// If the contract specified by the id `account` doesn't exist `Err` is returned.`
+	/// Returns the change in storage usage that results from this write. It is up to the
+	/// caller to charge or refund the storage deposit for it.
 	pub fn write(
-		account: &AccountIdOf<T>,
 		trie_id: &TrieId,
 		key: &StorageKey,
 		opt_new_value: Option<Vec<u8>>,
-	) -> Result<(), ContractAbsentError> {
-		let mut new_info = match <ContractInfoOf<T>>::get(account) {
-			Some(ContractInfo::Alive(alive)) => alive,
-			None | Some(ContractInfo::Tombstone(_)) => return Err(ContractAbsentError),
-		};
-
+	) -> Diff {
 		let hashed_key = blake2_256(key);
 		let child_trie_info = &crate::child_trie_info(&trie_id);
 
-		// In order to correctly update the book keeping we need to fetch the previous
+		// In order to correctly calculate the storage diff we need to fetch the previous
 		// value of the key-value pair.
 		//
 		// It might be a bit more clean if we had an API that supported getting the size
@@ -82,130 +75,94 @@ where
 		// dominated by the trie traversal anyway.
 		let opt_prev_value = child::get_raw(&child_trie_info, &hashed_key);
 
-		// Update the total number of KV pairs and the number of empty pairs.
+		let mut diff = Diff::default();
 		match (&opt_prev_value, &opt_new_value) {
 			(Some(prev_value), None) => {
-				new_info.total_pair_count -= 1;
-				if prev_value.is_empty() {
-					new_info.empty_pair_count -= 1;
-				}
+				diff.items_removed = 1;
+				diff.bytes_removed = prev_value.len() as u32;
 			},
 			(None, Some(new_value)) => {
-				new_info.total_pair_count += 1;
-				if new_value.is_empty() {
-					new_info.empty_pair_count += 1;
-				}
+				diff.items_added = 1;
+				diff.bytes_added = new_value.len() as u32;
 			},
 			(Some(prev_value), Some(new_value)) => {
-				if prev_value.is_empty() {
-					new_info.empty_pair_count -= 1;
-				}
-				if new_value.is_empty() {
-					new_info.empty_pair_count += 1;
+				let (prev_len, new_len) = (prev_value.len() as u32, new_value.len() as u32);
+				if new_len > prev_len {
+					diff.bytes_added = new_len - prev_len;
+				} else {
+					diff.bytes_removed = prev_len - new_len;
 				}
 			}
 			(None, None) => {}
 		}
 
-		// Update the total storage size.
-		let prev_value_len = opt_prev_value
-			.as_ref()
-			.map(|old_value| old_value.len() as u32)
-			.unwrap_or(0);
-		let new_value_len = opt_new_value
-			.as_ref()
-			.map(|new_value| new_value.len() as u32)
-			.unwrap_or(0);
-		new_info.storage_size = new_info
-			.storage_size
-			.saturating_add(new_value_len)
-			.saturating_sub(prev_value_len);
-
-		new_info.last_write = Some(<frame_system::Module<T>>::block_number());
-		<ContractInfoOf<T>>::insert(&account, ContractInfo::Alive(new_info));
-
 		// Finally, perform the change on the storage.
 		match opt_new_value {
 			Some(new_value) => child::put_raw(&child_trie_info, &hashed_key, &new_value[..]),
 			None => child::kill(&child_trie_info, &hashed_key),
 		}
 
-		Ok(())
-	}
-
-	/// Returns the rent allowance set for the contract give by the account id.
-	pub fn rent_allowance(
-		account: &AccountIdOf<T>,
-	) -> Result<BalanceOf<T>, ContractAbsentError>
-	{
-		<ContractInfoOf<T>>::get(account)
-			.and_then(|i| i.as_alive().map(|i| i.rent_allowance))
-			.ok_or(ContractAbsentError)
-	}
-
-	/// Set the rent allowance for the contract given by the account id.
-	///
-	/// Returns `Err` if the contract doesn't exist or is a tombstone.
-	pub fn set_rent_allowance(
-		account: &AccountIdOf<T>,
-		rent_allowance: BalanceOf<T>,
-	) -> Result<(), ContractAbsentError> {
-		<ContractInfoOf<T>>::mutate(account, |maybe_contract_info| match maybe_contract_info {
-			Some(ContractInfo::Alive(ref mut alive_info)) => {
-				alive_info.rent_allowance = rent_allowance;
-				Ok(())
-			}
-			_ => Err(ContractAbsentError),
-		})
+		diff
 	}
 
 	/// Creates a new contract descriptor in the storage with the given code hash at the given address.
 	///
-	/// Returns `Err` if there is already a contract (or a tombstone) exists at the given address.
+	/// Returns `Err` if there is already a contract exists at the given address.
 	pub fn place_contract(
 		account: &AccountIdOf<T>,
 		trie_id: TrieId,
 		ch: CodeHash<T>,
-	) -> Result<(), &'static str> {
-		<ContractInfoOf<T>>::mutate(account, |maybe_contract_info| {
+	) -> Result<ContractInfo<T>, &'static str> {
+		<ContractInfoOf<T>>::try_mutate(account, |maybe_contract_info| {
 			if maybe_contract_info.is_some() {
-				return Err("Alive contract or tombstone already exists");
+				return Err("Contract already exists");
 			}
 
-			*maybe_contract_info = Some(
-				AliveContractInfo::<T> {
-					code_hash: ch,
-					storage_size: 0,
-					trie_id,
-					deduct_block: <frame_system::Module<T>>::block_number(),
-					rent_allowance: <BalanceOf<T>>::max_value(),
-					empty_pair_count: 0,
-					total_pair_count: 0,
-					last_write: None,
-				}
-				.into(),
-			);
+			let contract = ContractInfo::<T> {
+				trie_id,
+				code_hash: ch,
+				storage_deposit: <BalanceOf<T>>::zero(),
+			};
+			*maybe_contract_info = Some(contract.clone());
+			wasm::increment_refcount::<T>(&ch);
 
-			Ok(())
+			Ok(contract)
 		})
 	}
 
 	/// Removes the contract and all the storage associated with it.
 	///
 	/// This function doesn't affect the account.
-	pub fn destroy_contract(address: &AccountIdOf<T>, trie_id: &TrieId) {
+	pub fn destroy_contract(address: &AccountIdOf<T>, contract: &ContractInfo<T>) {
 		<ContractInfoOf<T>>::remove(address);
-		child::kill_storage(&crate::child_trie_info(&trie_id), None);
+		child::kill_storage(&contract.child_trie_info(), None);
+		wasm::decrement_refcount::<T>(&contract.code_hash);
+	}
+
+	/// Replaces the code hash of the contract at `account` with `new_code_hash`.
+	///
+	/// Returns the previous code hash. Fails if no code is stored under `new_code_hash`.
+	pub fn set_code_hash(
+		account: &AccountIdOf<T>,
+		new_code_hash: CodeHash<T>,
+	) -> Result<CodeHash<T>, DispatchError> {
+		ensure!(<PristineCode<T>>::contains_key(&new_code_hash), Error::<T>::CodeNotFound);
+		<ContractInfoOf<T>>::try_mutate(account, |maybe_contract_info| {
+			let contract = maybe_contract_info.as_mut().ok_or(Error::<T>::NotCallable)?;
+			let prev_code_hash = sp_std::mem::replace(&mut contract.code_hash, new_code_hash);
+			wasm::increment_refcount::<T>(&new_code_hash);
+			wasm::decrement_refcount::<T>(&prev_code_hash);
+			Ok(prev_code_hash)
+		})
 	}
 
 	/// This generator uses inner counter for account id and applies the hash over `AccountId +
 	/// accountid_counter`.
 	pub fn generate_trie_id(account_id: &AccountIdOf<T>) -> TrieId {
-		use frame_support::StorageValue;
 		use sp_runtime::traits::Hash;
 		// Note that skipping a value due to error is not an issue here.
 		// We only need uniqueness, not sequence.
-		let new_seed = AccountCounter::mutate(|v| {
+		let new_seed = <AccountCounter<T>>::mutate(|v| {
 			*v = v.wrapping_add(1);
 			*v
 		});
@@ -222,7 +179,7 @@ where
 	pub fn code_hash(account: &AccountIdOf<T>) -> Result<CodeHash<T>, ContractAbsentError>
 	{
 		<ContractInfoOf<T>>::get(account)
-			.and_then(|i| i.as_alive().map(|i| i.code_hash))
+			.map(|i| i.code_hash)
 			.ok_or(ContractAbsentError)
 	}
-}
\ No newline at end of file
+}
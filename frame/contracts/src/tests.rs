@@ -15,26 +15,34 @@
 // along with Substrate. If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
-	BalanceOf, ContractInfo, ContractInfoOf, GenesisConfig, Module,
-	RawAliveContractInfo, RawEvent, Config, Schedule, gas::Gas,
-	Error, ConfigCache, RuntimeReturnCode, storage::Storage,
-	exec::AccountIdOf,
+	BalanceOf, ContractInfo, ContractInfoOf, GenesisConfig, Pallet,
+	Event, Config, Schedule, gas::Gas,
+	Error, ConfigCache, RuntimeReturnCode, PristineCode, storage::Storage,
+	exec::{AccountIdOf, Ext},
+	weights::WeightInfo,
+	chain_extension::{
+		self, ChainExtension, RegisteredChainExtension, Environment, RetVal, ReturnFlags, SysConfig,
+		UncheckedFrom,
+	},
 };
 use assert_matches::assert_matches;
 use codec::Encode;
+use pallet_contracts_primitives::{Code, CodeUploadReturnValue, StorageDeposit};
 use sp_runtime::{
 	traits::{BlakeTwo256, Hash, IdentityLookup, Convert},
 	testing::{Header, H256},
-	AccountId32,
+	AccountId32, DispatchError,
 };
 use frame_support::{
 	assert_ok, assert_err_ignore_postinfo, impl_outer_dispatch, impl_outer_event,
-	impl_outer_origin, parameter_types, StorageMap,
-	traits::{Currency, ReservableCurrency},
+	impl_outer_origin, parameter_types,
+	traits::{Currency, ReservableCurrency, Filter, GenesisBuild},
 	weights::{Weight, PostDispatchInfo},
 	dispatch::DispatchErrorWithPostInfo,
 };
 use frame_system::{self as system, EventRecord, Phase};
+use sp_keystore::{KeystoreExt, testing::KeyStore};
+use std::{cell::RefCell, sync::Arc};
 
 mod contracts {
 	// Re-export contents of the root. This basically
@@ -58,6 +66,7 @@ impl_outer_origin! {
 }
 impl_outer_dispatch! {
 	pub enum Call for Test where origin: Origin {
+		frame_system::System,
 		balances::Balances,
 		contracts::Contracts,
 	}
@@ -71,19 +80,19 @@ pub mod test_utils {
 		storage::Storage,
 		exec::{StorageKey, AccountIdOf},
 	};
-	use frame_support::{StorageMap, traits::Currency};
+	use frame_support::traits::Currency;
 
 	pub fn set_storage(addr: &AccountIdOf<Test>, key: &StorageKey, value: Option<Vec<u8>>) {
-		let contract_info = <ContractInfoOf::<Test>>::get(&addr).unwrap().get_alive().unwrap();
-		Storage::<Test>::write(addr, &contract_info.trie_id, key, value).unwrap();
+		let contract_info = <ContractInfoOf::<Test>>::get(&addr).unwrap();
+		Storage::<Test>::write(&contract_info.trie_id, key, value);
 	}
 	pub fn get_storage(addr: &AccountIdOf<Test>, key: &StorageKey) -> Option<Vec<u8>> {
-		let contract_info = <ContractInfoOf::<Test>>::get(&addr).unwrap().get_alive().unwrap();
+		let contract_info = <ContractInfoOf::<Test>>::get(&addr).unwrap();
 		Storage::<Test>::read(&contract_info.trie_id, key)
 	}
 	pub fn place_contract(address: &AccountIdOf<Test>, code_hash: CodeHash<Test>) {
 		let trie_id = Storage::<Test>::generate_trie_id(address);
-		Storage::<Test>::place_contract(&address, trie_id, code_hash).unwrap()
+		Storage::<Test>::place_contract(&address, trie_id, code_hash).unwrap();
 	}
 	pub fn set_balance(who: &AccountIdOf<Test>, amount: u64) {
 		let imbalance = Balances::deposit_creating(who, amount);
@@ -150,12 +159,8 @@ impl pallet_timestamp::Config for Test {
 	type WeightInfo = ();
 }
 parameter_types! {
-	pub const SignedClaimHandicap: u64 = 2;
-	pub const TombstoneDeposit: u64 = 16;
-	pub const StorageSizeOffset: u32 = 8;
-	pub const RentByteFee: u64 = 4;
-	pub const RentDepositOffset: u64 = 10_000;
-	pub const SurchargeReward: u64 = 150;
+	pub const DepositPerByte: u64 = 1;
+	pub const DepositPerItem: u64 = 2;
 	pub const MaxDepth: u32 = 100;
 	pub const MaxValueSize: u32 = 16_384;
 }
@@ -175,22 +180,119 @@ impl Config for Test {
 	type Randomness = Randomness;
 	type Currency = Balances;
 	type Event = MetaEvent;
-	type RentPayment = ();
-	type SignedClaimHandicap = SignedClaimHandicap;
-	type TombstoneDeposit = TombstoneDeposit;
-	type StorageSizeOffset = StorageSizeOffset;
-	type RentByteFee = RentByteFee;
-	type RentDepositOffset = RentDepositOffset;
-	type SurchargeReward = SurchargeReward;
+	type DepositPerItem = DepositPerItem;
+	type DepositPerByte = DepositPerByte;
 	type MaxDepth = MaxDepth;
 	type MaxValueSize = MaxValueSize;
 	type WeightPrice = Self;
 	type WeightInfo = ();
+	type ChainExtension = (TestExtension, DisabledExtension);
+	type Call = Call;
+	type CallFilter = TestFilter;
+}
+
+thread_local! {
+	static TEST_EXTENSION: RefCell<TestExtension> = Default::default();
+	static CALL_FILTER: RefCell<fn(&Call) -> bool> = RefCell::new(|_| true);
+}
+
+/// A call filter whose behaviour can be changed from within a test.
+pub struct TestFilter;
+
+impl TestFilter {
+	fn set_filter(filter: fn(&Call) -> bool) {
+		CALL_FILTER.with(|f| *f.borrow_mut() = filter);
+	}
+}
+
+impl Filter<Call> for TestFilter {
+	fn filter(call: &Call) -> bool {
+		CALL_FILTER.with(|f| (f.borrow())(call))
+	}
+}
+
+pub struct TestExtension {
+	enabled: bool,
+	last_seen_buffer: Vec<u8>,
+}
+
+impl Default for TestExtension {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			last_seen_buffer: vec![],
+		}
+	}
+}
+
+/// An extension that is never enabled and therefore never called.
+pub struct DisabledExtension;
+
+impl TestExtension {
+	fn disable() {
+		TEST_EXTENSION.with(|e| e.borrow_mut().enabled = false)
+	}
+
+	fn last_seen_buffer() -> Vec<u8> {
+		TEST_EXTENSION.with(|e| e.borrow().last_seen_buffer.clone())
+	}
+}
+
+impl ChainExtension<Test> for TestExtension {
+	fn call<E>(func_id: u32, mut env: Environment<E>) -> chain_extension::Result<RetVal>
+	where
+		E: Ext<T = Test>,
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+	{
+		match env.func_id() {
+			0 => {
+				let input = env.read(16)?;
+				env.write(&input, false, None)?;
+				TEST_EXTENSION.with(|e| e.borrow_mut().last_seen_buffer = input);
+				Ok(RetVal::Converging(func_id))
+			},
+			1 => {
+				let (_, weight): (u32, u64) = env.read_as()?;
+				env.charge_weight(weight)?;
+				Ok(RetVal::Converging(func_id))
+			},
+			2 => {
+				Ok(RetVal::Diverging{flags: ReturnFlags::REVERT, data: vec![42, 99]})
+			},
+			_ => Err(DispatchError::Other("Unimplemented func_id")),
+		}
+	}
+
+	fn enabled() -> bool {
+		TEST_EXTENSION.with(|e| e.borrow().enabled)
+	}
+}
+
+impl RegisteredChainExtension<Test> for TestExtension {
+	const ID: u16 = 0;
+}
+
+impl ChainExtension<Test> for DisabledExtension {
+	fn call<E>(_func_id: u32, _env: Environment<E>) -> chain_extension::Result<RetVal>
+	where
+		E: Ext<T = Test>,
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+	{
+		panic!("Disabled chain extensions are never called")
+	}
+
+	fn enabled() -> bool {
+		false
+	}
+}
+
+impl RegisteredChainExtension<Test> for DisabledExtension {
+	const ID: u16 = 1;
 }
 
 type Balances = pallet_balances::Module<Test>;
 type Timestamp = pallet_timestamp::Module<Test>;
-type Contracts = Module<Test>;
+type Contracts = Pallet<Test>;
 type System = frame_system::Module<Test>;
 type Randomness = pallet_randomness_collective_flip::Module<Test>;
 
@@ -225,13 +327,17 @@ impl ExtBuilder {
 		pallet_balances::GenesisConfig::<Test> {
 			balances: vec![],
 		}.assimilate_storage(&mut t).unwrap();
-		GenesisConfig {
-			current_schedule: Schedule::<Test> {
-				enable_println: true,
-				..Default::default()
+		GenesisBuild::<Test>::assimilate_storage(
+			&GenesisConfig {
+				current_schedule: Schedule::<Test> {
+					enable_println: true,
+					..Default::default()
+				},
 			},
-		}.assimilate_storage(&mut t).unwrap();
+			&mut t,
+		).unwrap();
 		let mut ext = sp_io::TestExternalities::new(t);
+		ext.register_extension(KeystoreExt(Arc::new(KeyStore::new())));
 		ext.execute_with(|| System::set_block_number(1));
 		ext
 	}
@@ -263,7 +369,7 @@ fn calling_plain_account_fails() {
 		let _ = Balances::deposit_creating(&ALICE, 100_000_000);
 
 		assert_eq!(
-			Contracts::call(Origin::signed(ALICE), BOB, 0, GAS_LIMIT, Vec::new()),
+			Contracts::call(Origin::signed(ALICE), BOB, 0, GAS_LIMIT, None, Vec::new()),
 			Err(
 				DispatchErrorWithPostInfo {
 					error: Error::<Test>::NotCallable.into(),
@@ -289,31 +395,21 @@ fn account_removal_does_not_remove_storage() {
 
 		// Set up two accounts with free balance above the existential threshold.
 		{
-			let alice_contract_info = ContractInfo::Alive(RawAliveContractInfo {
+			let alice_contract_info = ContractInfo::<Test> {
 				trie_id: trie_id1.clone(),
-				storage_size: 0,
-				empty_pair_count: 0,
-				total_pair_count: 0,
-				deduct_block: System::block_number(),
 				code_hash: H256::repeat_byte(1),
-				rent_allowance: 40,
-				last_write: None,
-			});
+				storage_deposit: 0,
+			};
 			let _ = Balances::deposit_creating(&ALICE, 110);
 			ContractInfoOf::<Test>::insert(ALICE, &alice_contract_info);
 			set_storage(&ALICE, &key1, Some(b"1".to_vec()));
 			set_storage(&ALICE, &key2, Some(b"2".to_vec()));
 
-			let bob_contract_info = ContractInfo::Alive(RawAliveContractInfo {
+			let bob_contract_info = ContractInfo::<Test> {
 				trie_id: trie_id2.clone(),
-				storage_size: 0,
-				empty_pair_count: 0,
-				total_pair_count: 0,
-				deduct_block: System::block_number(),
 				code_hash: H256::repeat_byte(2),
-				rent_allowance: 40,
-				last_write: None,
-			});
+				storage_deposit: 0,
+			};
 			let _ = Balances::deposit_creating(&BOB, 110);
 			ContractInfoOf::<Test>::insert(BOB, &bob_contract_info);
 			set_storage(&BOB, &key1, Some(b"3".to_vec()));
@@ -363,13 +459,15 @@ fn instantiate_and_call_and_deposit_event() {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
 			let subsistence = super::ConfigCache::<Test>::subsistence_threshold_uncached();
 
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
+			let code_deposit = Balances::reserved_balance(&ALICE);
 
 			// Check at the end to get hash on error easily
 			let creation = Contracts::instantiate(
 				Origin::signed(ALICE),
 				subsistence,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -391,7 +489,14 @@ fn instantiate_and_call_and_deposit_event() {
 				},
 				EventRecord {
 					phase: Phase::Initialization,
-					event: MetaEvent::contracts(RawEvent::CodeStored(code_hash.into())),
+					event: MetaEvent::balances(
+						pallet_balances::RawEvent::Reserved(ALICE, code_deposit)
+					),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: MetaEvent::contracts(Event::CodeStored(code_hash)),
 					topics: vec![],
 				},
 				EventRecord {
@@ -416,15 +521,29 @@ fn instantiate_and_call_and_deposit_event() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: MetaEvent::contracts(
-						RawEvent::ContractExecution(addr.clone(), vec![1, 2, 3, 4])
+						Event::ContractExecution(addr.clone(), vec![1, 2, 3, 4])
 					),
 					topics: vec![],
 				},
 				EventRecord {
 					phase: Phase::Initialization,
-					event: MetaEvent::contracts(RawEvent::Instantiated(ALICE, addr.clone())),
+					event: MetaEvent::contracts(Event::Instantiated(ALICE, addr.clone())),
 					topics: vec![],
-				}
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: MetaEvent::balances(
+						pallet_balances::RawEvent::Transfer(ALICE, addr.clone(), info_deposit(&addr))
+					),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: MetaEvent::balances(
+						pallet_balances::RawEvent::Reserved(addr.clone(), info_deposit(&addr))
+					),
+					topics: vec![],
+				},
 			]);
 
 			assert_ok!(creation);
@@ -442,11 +561,12 @@ fn deposit_event_max_value_limit() {
 		.execute_with(|| {
 			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				30_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -454,11 +574,7 @@ fn deposit_event_max_value_limit() {
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
 			// Check creation
-			let bob_contract = ContractInfoOf::<Test>::get(addr.clone())
-				.unwrap()
-				.get_alive()
-				.unwrap();
-			assert_eq!(bob_contract.rent_allowance, <BalanceOf<Test>>::max_value());
+			assert!(ContractInfoOf::<Test>::contains_key(&addr));
 
 			// Call contract with allowed storage value.
 			assert_ok!(Contracts::call(
@@ -466,6 +582,7 @@ fn deposit_event_max_value_limit() {
 				addr.clone(),
 				0,
 				GAS_LIMIT * 2, // we are copying a huge buffer,
+				None,
 				<Test as Config>::MaxValueSize::get().encode(),
 			));
 
@@ -476,6 +593,7 @@ fn deposit_event_max_value_limit() {
 					addr,
 					0,
 					GAS_LIMIT,
+					None,
 					(<Test as Config>::MaxValueSize::get() + 1).encode(),
 				),
 				Error::<Test>::ValueTooLarge,
@@ -493,12 +611,13 @@ fn run_out_of_gas() {
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
 
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
 
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				100,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -513,6 +632,7 @@ fn run_out_of_gas() {
 					addr, // newly created account
 					0,
 					67_500_000,
+					None,
 					vec![],
 				),
 				Error::<Test>::OutOfGas,
@@ -531,127 +651,75 @@ mod call {
 	pub fn null() -> Vec<u8> { 3u32.to_le_bytes().to_vec() }
 }
 
-/// Test correspondence of set_rent code and its hash.
-/// Also test that encoded extrinsic in code correspond to the correct transfer
-#[test]
-fn test_set_rent_code_and_hash() {
-	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
-
-	ExtBuilder::default()
-		.existential_deposit(50)
-		.build()
-		.execute_with(|| {
-			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
-
-			// If you ever need to update the wasm source this test will fail
-			// and will show you the actual hash.
-			assert_eq!(System::events(), vec![
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::system(frame_system::RawEvent::NewAccount(ALICE)),
-					topics: vec![],
-				},
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::balances(pallet_balances::RawEvent::Endowed(
-						ALICE, 1_000_000
-					)),
-					topics: vec![],
-				},
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::contracts(RawEvent::CodeStored(code_hash.into())),
-					topics: vec![],
-				},
-			]);
-		});
+/// The deposit that is charged for the contract info of `addr`.
+fn info_deposit(addr: &AccountIdOf<Test>) -> u64 {
+	let info = ContractInfoOf::<Test>::get(addr).unwrap();
+	info.encoded_size() as u64 * DepositPerByte::get() + DepositPerItem::get()
 }
 
 #[test]
-fn storage_size() {
+fn storage_deposit_works() {
 	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
 
-	// Storage size
 	ExtBuilder::default()
 		.existential_deposit(50)
 		.build()
 		.execute_with(|| {
-			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
+			let code_deposit = Balances::reserved_balance(&ALICE);
+			assert!(code_deposit > 0);
+
+			// The constructor writes a 4 byte value.
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				30_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(1_000u32).encode(), // rent allowance
+				<Test as pallet_balances::Config>::Balance::from(1_000u32).encode(),
 				vec![],
 			));
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
-			let bob_contract = ContractInfoOf::<Test>::get(&addr)
-				.unwrap()
-				.get_alive()
-				.unwrap();
-			assert_eq!(
-				bob_contract.storage_size,
-				4
-			);
-			assert_eq!(
-				bob_contract.total_pair_count,
-				1,
-			);
+			let deposit = info_deposit(&addr) + 4 + 2;
+			assert_eq!(ContractInfoOf::<Test>::get(&addr).unwrap().storage_deposit, deposit);
+			assert_eq!(Balances::reserved_balance(&addr), deposit);
+			assert_eq!(Balances::free_balance(&addr), 30_000);
 			assert_eq!(
-				bob_contract.empty_pair_count,
-				0,
+				Balances::free_balance(&ALICE),
+				1_000_000 - code_deposit - 30_000 - deposit,
 			);
 
+			// Adding another value of 4 bytes charges for one more item.
 			assert_ok!(Contracts::call(
 				Origin::signed(ALICE),
 				addr.clone(),
 				0,
 				GAS_LIMIT,
+				None,
 				call::set_storage_4_byte()
 			));
-			let bob_contract = ContractInfoOf::<Test>::get(&addr)
-				.unwrap()
-				.get_alive()
-				.unwrap();
-			assert_eq!(
-				bob_contract.storage_size,
-				4 + 4
-			);
-			assert_eq!(
-				bob_contract.total_pair_count,
-				2,
-			);
+			assert_eq!(ContractInfoOf::<Test>::get(&addr).unwrap().storage_deposit, deposit + 6);
+			assert_eq!(Balances::reserved_balance(&addr), deposit + 6);
 			assert_eq!(
-				bob_contract.empty_pair_count,
-				0,
+				Balances::free_balance(&ALICE),
+				1_000_000 - code_deposit - 30_000 - deposit - 6,
 			);
 
+			// Removing it again refunds the deposit to the caller.
 			assert_ok!(Contracts::call(
 				Origin::signed(ALICE),
 				addr.clone(),
 				0,
 				GAS_LIMIT,
+				None,
 				call::remove_storage_4_byte()
 			));
-			let bob_contract = ContractInfoOf::<Test>::get(&addr)
-				.unwrap()
-				.get_alive()
-				.unwrap();
+			assert_eq!(ContractInfoOf::<Test>::get(&addr).unwrap().storage_deposit, deposit);
+			assert_eq!(Balances::reserved_balance(&addr), deposit);
 			assert_eq!(
-				bob_contract.storage_size,
-				4
-			);
-			assert_eq!(
-				bob_contract.total_pair_count,
-				1,
-			);
-			assert_eq!(
-				bob_contract.empty_pair_count,
-				0,
+				Balances::free_balance(&ALICE),
+				1_000_000 - code_deposit - 30_000 - deposit,
 			);
 		});
 }
@@ -664,698 +732,188 @@ fn empty_kv_pairs() {
 		.build()
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				30_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
 			));
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
-			let bob_contract = ContractInfoOf::<Test>::get(&addr)
-				.unwrap()
-				.get_alive()
-				.unwrap();
 
+			// An empty value still occupies a storage item.
 			assert_eq!(
-				bob_contract.storage_size,
-				0,
-			);
-			assert_eq!(
-				bob_contract.total_pair_count,
-				1,
-			);
-			assert_eq!(
-				bob_contract.empty_pair_count,
-				1,
+				ContractInfoOf::<Test>::get(&addr).unwrap().storage_deposit,
+				info_deposit(&addr) + DepositPerItem::get(),
 			);
 		});
 }
 
-fn initialize_block(number: u64) {
-	System::initialize(
-		&number,
-		&[0u8; 32].into(),
-		&Default::default(),
-		Default::default(),
-	);
-}
-
 #[test]
-fn deduct_blocks() {
+fn storage_deposit_limit_is_enforced() {
 	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
 
 	ExtBuilder::default()
 		.existential_deposit(50)
 		.build()
 		.execute_with(|| {
-			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
-			assert_ok!(Contracts::instantiate(
-				Origin::signed(ALICE),
-				30_000,
-				GAS_LIMIT, code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(1_000u32).encode(), // rent allowance
-				vec![],
-			));
-			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
-
-			// Check creation
-			let bob_contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap();
-			assert_eq!(bob_contract.rent_allowance, 1_000);
-
-			// Advance 4 blocks
-			initialize_block(5);
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
 
-			// Trigger rent through call
-			assert_ok!(
-				Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, call::null())
-			);
-
-			// Check result
-			let rent = (8 + 4 - 3) // storage size = size_offset + deploy_set_storage - deposit_offset
-				* 4 // rent byte price
-				* 4; // blocks to rent
-			let bob_contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap();
-			assert_eq!(bob_contract.rent_allowance, 1_000 - rent);
-			assert_eq!(bob_contract.deduct_block, 5);
-			assert_eq!(Balances::free_balance(&addr), 30_000 - rent);
-
-			// Advance 7 blocks more
-			initialize_block(12);
-
-			// Trigger rent through call
-			assert_ok!(
-				Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, call::null())
+			// The limit does not even cover the contract info.
+			assert_err_ignore_postinfo!(
+				Contracts::instantiate(
+					Origin::signed(ALICE),
+					30_000,
+					GAS_LIMIT,
+					Some(10),
+					code_hash.into(),
+					vec![],
+					vec![],
+				),
+				Error::<Test>::StorageDepositLimitExhausted,
 			);
 
-			// Check result
-			let rent_2 = (8 + 4 - 2) // storage size = size_offset + deploy_set_storage - deposit_offset
-				* 4 // rent byte price
-				* 7; // blocks to rent
-			let bob_contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap();
-			assert_eq!(bob_contract.rent_allowance, 1_000 - rent - rent_2);
-			assert_eq!(bob_contract.deduct_block, 12);
-			assert_eq!(Balances::free_balance(&addr), 30_000 - rent - rent_2);
-
-			// Second call on same block should have no effect on rent
-			assert_ok!(
-				Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, call::null())
+			// The limit cannot exceed what the caller is able to pay.
+			assert_err_ignore_postinfo!(
+				Contracts::instantiate(
+					Origin::signed(ALICE),
+					30_000,
+					GAS_LIMIT,
+					Some(2_000_000),
+					code_hash.into(),
+					vec![],
+					vec![],
+				),
+				Error::<Test>::StorageDepositNotEnoughFunds,
 			);
 
-			let bob_contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap();
-			assert_eq!(bob_contract.rent_allowance, 1_000 - rent - rent_2);
-			assert_eq!(bob_contract.deduct_block, 12);
-			assert_eq!(Balances::free_balance(&addr), 30_000 - rent - rent_2);
-		});
-}
-
-#[test]
-fn call_contract_removals() {
-	removals(|addr| {
-		// Call on already-removed account might fail, and this is fine.
-		let _ = Contracts::call(Origin::signed(ALICE), addr, 0, GAS_LIMIT, call::null());
-		true
-	});
-}
-
-#[test]
-fn inherent_claim_surcharge_contract_removals() {
-	removals(|addr| Contracts::claim_surcharge(Origin::none(), addr, Some(ALICE)).is_ok());
-}
-
-#[test]
-fn signed_claim_surcharge_contract_removals() {
-	removals(|addr| Contracts::claim_surcharge(Origin::signed(ALICE), addr, None).is_ok());
-}
-
-#[test]
-fn claim_surcharge_malus() {
-	// Test surcharge malus for inherent
-	claim_surcharge(4, |addr| Contracts::claim_surcharge(Origin::none(), addr, Some(ALICE)).is_ok(), true);
-	claim_surcharge(3, |addr| Contracts::claim_surcharge(Origin::none(), addr, Some(ALICE)).is_ok(), true);
-	claim_surcharge(2, |addr| Contracts::claim_surcharge(Origin::none(), addr, Some(ALICE)).is_ok(), true);
-	claim_surcharge(1, |addr| Contracts::claim_surcharge(Origin::none(), addr, Some(ALICE)).is_ok(), false);
-
-	// Test surcharge malus for signed
-	claim_surcharge(4, |addr| Contracts::claim_surcharge(Origin::signed(ALICE), addr, None).is_ok(), true);
-	claim_surcharge(3, |addr| Contracts::claim_surcharge(Origin::signed(ALICE), addr, None).is_ok(), false);
-	claim_surcharge(2, |addr| Contracts::claim_surcharge(Origin::signed(ALICE), addr, None).is_ok(), false);
-	claim_surcharge(1, |addr| Contracts::claim_surcharge(Origin::signed(ALICE), addr, None).is_ok(), false);
-}
-
-/// Claim surcharge with the given trigger_call at the given blocks.
-/// If `removes` is true then assert that the contract is a tombstone.
-fn claim_surcharge(blocks: u64, trigger_call: impl Fn(AccountIdOf<Test>) -> bool, removes: bool) {
-	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
-
-	ExtBuilder::default()
-		.existential_deposit(50)
-		.build()
-		.execute_with(|| {
-			// Create
-			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
-				100,
-				GAS_LIMIT, code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(1_000u32).encode(), // rent allowance
-				vec![],
-			));
-			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
-
-			// Advance blocks
-			initialize_block(blocks);
-
-			// Trigger rent through call
-			assert!(trigger_call(addr.clone()));
-
-			if removes {
-				assert!(ContractInfoOf::<Test>::get(&addr).unwrap().get_tombstone().is_some());
-			} else {
-				assert!(ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().is_some());
-			}
-		});
-}
-
-/// Test for all kind of removals for the given trigger:
-/// * if balance is reached and balance > subsistence threshold
-/// * if allowance is exceeded
-/// * if balance is reached and balance < subsistence threshold
-///	    * this case cannot be triggered by a contract: we check whether a tombstone is left
-fn removals(trigger_call: impl Fn(AccountIdOf<Test>) -> bool) {
-	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
-
-	// Balance reached and superior to subsistence threshold
-	ExtBuilder::default()
-		.existential_deposit(50)
-		.build()
-		.execute_with(|| {
-			// Create
-			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm.clone()));
-			assert_ok!(Contracts::instantiate(
-				Origin::signed(ALICE),
-				100,
-				GAS_LIMIT, code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(1_000u32).encode(), // rent allowance
-				vec![],
-			));
-			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
-
-			let subsistence_threshold = 50 /*existential_deposit*/ + 16 /*tombstone_deposit*/;
-
-			// Trigger rent must have no effect
-			assert!(trigger_call(addr.clone()));
-			assert_eq!(ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap().rent_allowance, 1_000);
-			assert_eq!(Balances::free_balance(&addr), 100);
-
-			// Advance blocks
-			initialize_block(10);
-
-			// Trigger rent through call
-			assert!(trigger_call(addr.clone()));
-			assert!(ContractInfoOf::<Test>::get(&addr).unwrap().get_tombstone().is_some());
-			assert_eq!(Balances::free_balance(&addr), subsistence_threshold);
-
-			// Advance blocks
-			initialize_block(20);
-
-			// Trigger rent must have no effect
-			assert!(trigger_call(addr.clone()));
-			assert!(ContractInfoOf::<Test>::get(&addr).unwrap().get_tombstone().is_some());
-			assert_eq!(Balances::free_balance(&addr), subsistence_threshold);
-		});
-
-	// Allowance exceeded
-	ExtBuilder::default()
-		.existential_deposit(50)
-		.build()
-		.execute_with(|| {
-			// Create
-			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm.clone()));
-			assert_ok!(Contracts::instantiate(
-				Origin::signed(ALICE),
-				1_000,
+				30_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(100u32).encode(), // rent allowance
 				vec![],
-			));
-			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
-
-			// Trigger rent must have no effect
-			assert!(trigger_call(addr.clone()));
-			assert_eq!(
-				ContractInfoOf::<Test>::get(&addr)
-					.unwrap()
-					.get_alive()
-					.unwrap()
-					.rent_allowance,
-				100
-			);
-			assert_eq!(Balances::free_balance(&addr), 1_000);
-
-			// Advance blocks
-			initialize_block(10);
-
-			// Trigger rent through call
-			assert!(trigger_call(addr.clone()));
-			assert!(ContractInfoOf::<Test>::get(&addr)
-				.unwrap()
-				.get_tombstone()
-				.is_some());
-			// Balance should be initial balance - initial rent_allowance
-			assert_eq!(Balances::free_balance(&addr), 900);
-
-			// Advance blocks
-			initialize_block(20);
-
-			// Trigger rent must have no effect
-			assert!(trigger_call(addr.clone()));
-			assert!(ContractInfoOf::<Test>::get(&addr)
-				.unwrap()
-				.get_tombstone()
-				.is_some());
-			assert_eq!(Balances::free_balance(&addr), 900);
-		});
-
-	// Balance reached and inferior to subsistence threshold
-	ExtBuilder::default()
-		.existential_deposit(50)
-		.build()
-		.execute_with(|| {
-			// Create
-			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			let subsistence_threshold =
-				Balances::minimum_balance() + <Test as Config>::TombstoneDeposit::get();
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm.clone()));
-			assert_ok!(Contracts::instantiate(
-				Origin::signed(ALICE),
-				50 + subsistence_threshold,
-				GAS_LIMIT,
-				code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(1_000u32).encode(), // rent allowance
 				vec![],
 			));
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+			let deposit = ContractInfoOf::<Test>::get(&addr).unwrap().storage_deposit;
 
-			// Trigger rent must have no effect
-			assert!(trigger_call(addr.clone()));
-			assert_eq!(
-				ContractInfoOf::<Test>::get(&addr)
-					.unwrap()
-					.get_alive()
-					.unwrap()
-					.rent_allowance,
-				1_000
-			);
-			assert_eq!(
-				Balances::free_balance(&addr),
-				50 + subsistence_threshold,
+			// Adding a value needs a deposit of 6 which is above the limit.
+			assert_err_ignore_postinfo!(
+				Contracts::call(
+					Origin::signed(ALICE),
+					addr.clone(),
+					0,
+					GAS_LIMIT,
+					Some(5),
+					call::set_storage_4_byte()
+				),
+				Error::<Test>::StorageDepositLimitExhausted,
 			);
+			assert_eq!(ContractInfoOf::<Test>::get(&addr).unwrap().storage_deposit, deposit);
 
-			// Transfer funds
 			assert_ok!(Contracts::call(
 				Origin::signed(ALICE),
 				addr.clone(),
 				0,
 				GAS_LIMIT,
-				call::transfer(&BOB),
+				Some(6),
+				call::set_storage_4_byte()
 			));
-			assert_eq!(
-				ContractInfoOf::<Test>::get(&addr)
-					.unwrap()
-					.get_alive()
-					.unwrap()
-					.rent_allowance,
-				1_000
-			);
-			assert_eq!(Balances::free_balance(&addr), subsistence_threshold);
-
-			// Advance blocks
-			initialize_block(10);
-
-			// Trigger rent through call
-			assert!(trigger_call(addr.clone()));
-			assert_matches!(ContractInfoOf::<Test>::get(&addr), Some(ContractInfo::Tombstone(_)));
-			assert_eq!(Balances::free_balance(&addr), subsistence_threshold);
-
-			// Advance blocks
-			initialize_block(20);
-
-			// Trigger rent must have no effect
-			assert!(trigger_call(addr.clone()));
-			assert_matches!(ContractInfoOf::<Test>::get(&addr), Some(ContractInfo::Tombstone(_)));
-			assert_eq!(Balances::free_balance(&addr), subsistence_threshold);
+			assert_eq!(ContractInfoOf::<Test>::get(&addr).unwrap().storage_deposit, deposit + 6);
 		});
 }
 
 #[test]
-fn call_removed_contract() {
-	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+fn remove_code_works() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
 
-	// Balance reached and superior to subsistence threshold
 	ExtBuilder::default()
 		.existential_deposit(50)
 		.build()
 		.execute_with(|| {
-			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm.clone()));
-			assert_ok!(Contracts::instantiate(
-				Origin::signed(ALICE),
-				100,
-				GAS_LIMIT, code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(1_000u32).encode(), // rent allowance
-				vec![],
-			));
-			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
-
-			// Calling contract should succeed.
-			assert_ok!(
-				Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, call::null())
-			);
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
+			assert!(Balances::reserved_balance(&ALICE) > 0);
 
-			// Advance blocks
-			initialize_block(10);
-
-			// Calling contract should remove contract and fail.
+			// Only the owner can remove the code.
 			assert_err_ignore_postinfo!(
-				Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, call::null()),
-				Error::<Test>::NotCallable
+				Contracts::remove_code(Origin::signed(BOB), code_hash),
+				DispatchError::BadOrigin,
 			);
-			// Calling a contract that is about to evict shall emit an event.
-			assert_eq!(System::events(), vec![
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::contracts(RawEvent::Evicted(addr.clone(), true)),
-					topics: vec![],
-				},
-			]);
-
-			// Subsequent contract calls should also fail.
-			assert_err_ignore_postinfo!(
-				Contracts::call(Origin::signed(ALICE), addr, 0, GAS_LIMIT, call::null()),
-				Error::<Test>::NotCallable
-			);
-		})
-}
-
-#[test]
-fn default_rent_allowance_on_instantiate() {
-	let (wasm, code_hash) = compile_module::<Test>("check_default_rent_allowance").unwrap();
-
-	ExtBuilder::default()
-		.existential_deposit(50)
-		.build()
-		.execute_with(|| {
-			// Create
-			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
-			assert_ok!(Contracts::instantiate(
-				Origin::signed(ALICE),
-				30_000,
-				GAS_LIMIT,
-				code_hash.into(),
-				vec![],
-				vec![],
-			));
-			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
-
-			// Check creation
-			let bob_contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap();
-			assert_eq!(bob_contract.rent_allowance, <BalanceOf<Test>>::max_value());
-
-			// Advance blocks
-			initialize_block(5);
-
-			// Trigger rent through call
-			assert_ok!(
-				Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, call::null())
-			);
-
-			// Check contract is still alive
-			let bob_contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive();
-			assert!(bob_contract.is_some())
-		});
-}
-
-#[test]
-fn restorations_dirty_storage_and_different_storage() {
-	restoration(true, true);
-}
 
-#[test]
-fn restorations_dirty_storage() {
-	restoration(false, true);
-}
+			assert_ok!(Contracts::remove_code(Origin::signed(ALICE), code_hash));
+			assert_eq!(Balances::reserved_balance(&ALICE), 0);
+			assert_eq!(Balances::free_balance(&ALICE), 1_000_000);
+			assert!(!crate::PristineCode::<Test>::contains_key(&code_hash));
+			assert!(!crate::CodeStorage::<Test>::contains_key(&code_hash));
 
-#[test]
-fn restoration_different_storage() {
-	restoration(true, false);
+			assert_err_ignore_postinfo!(
+				Contracts::remove_code(Origin::signed(ALICE), code_hash),
+				Error::<Test>::CodeNotFound,
+			);
+		});
 }
 
 #[test]
-fn restoration_success() {
-	restoration(false, false);
-}
-
-fn restoration(test_different_storage: bool, test_restore_to_with_dirty_storage: bool) {
-	let (set_rent_wasm, set_rent_code_hash) = compile_module::<Test>("set_rent").unwrap();
-	let (restoration_wasm, restoration_code_hash) = compile_module::<Test>("restoration").unwrap();
+fn remove_code_in_use() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
 
 	ExtBuilder::default()
 		.existential_deposit(50)
 		.build()
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), restoration_wasm));
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), set_rent_wasm));
-
-			// If you ever need to update the wasm source this test will fail
-			// and will show you the actual hash.
-			assert_eq!(System::events(), vec![
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::system(frame_system::RawEvent::NewAccount(ALICE)),
-					topics: vec![],
-				},
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::balances(pallet_balances::RawEvent::Endowed(ALICE, 1_000_000)),
-					topics: vec![],
-				},
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::contracts(RawEvent::CodeStored(restoration_code_hash.into())),
-					topics: vec![],
-				},
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::contracts(RawEvent::CodeStored(set_rent_code_hash.into())),
-					topics: vec![],
-				},
-			]);
-
-			// Create an account with address `BOB` with code `CODE_SET_RENT`.
-			// The input parameter sets the rent allowance to 0.
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				30_000,
 				GAS_LIMIT,
-				set_rent_code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(0u32).encode(),
+				None,
+				code_hash.into(),
+				vec![],
 				vec![],
 			));
-			let addr_bob = Contracts::contract_address(&ALICE, &set_rent_code_hash, &[]);
-
-			// Check if `BOB` was created successfully and that the rent allowance is
-			// set to 0.
-			let bob_contract = ContractInfoOf::<Test>::get(&addr_bob).unwrap().get_alive().unwrap();
-			assert_eq!(bob_contract.rent_allowance, 0);
-
-			if test_different_storage {
-				assert_ok!(Contracts::call(
-					Origin::signed(ALICE),
-					addr_bob.clone(), 0, GAS_LIMIT,
-					call::set_storage_4_byte())
-				);
-			}
 
-			// Advance 4 blocks, to the 5th.
-			initialize_block(5);
-
-			// Call `BOB`, which makes it pay rent. Since the rent allowance is set to 0
-			// we expect that it will get removed leaving tombstone.
 			assert_err_ignore_postinfo!(
-				Contracts::call(
-					Origin::signed(ALICE), addr_bob.clone(), 0, GAS_LIMIT, call::null()
-				),
-				Error::<Test>::NotCallable
+				Contracts::remove_code(Origin::signed(ALICE), code_hash),
+				Error::<Test>::CodeInUse,
 			);
-			assert!(ContractInfoOf::<Test>::get(&addr_bob).unwrap().get_tombstone().is_some());
-			assert_eq!(System::events(), vec![
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::contracts(
-						RawEvent::Evicted(addr_bob.clone(), true)
-					),
-					topics: vec![],
-				},
-			]);
-
-			// Create another account with the address `DJANGO` with `CODE_RESTORATION`.
-			//
-			// Note that we can't use `ALICE` for creating `DJANGO` so we create yet another
-			// account `CHARLIE` and create `DJANGO` with it.
-			let _ = Balances::deposit_creating(&CHARLIE, 1_000_000);
-			assert_ok!(Contracts::instantiate(
-				Origin::signed(CHARLIE),
-				30_000,
-				GAS_LIMIT,
-				restoration_code_hash.into(),
-				vec![],
-				vec![],
-			));
-			let addr_django = Contracts::contract_address(&CHARLIE, &restoration_code_hash, &[]);
-
-			// Before performing a call to `DJANGO` save its original trie id.
-			let django_trie_id = ContractInfoOf::<Test>::get(&addr_django).unwrap()
-				.get_alive().unwrap().trie_id;
+		});
+}
 
-			// The trie is regarded as 'dirty' when it was written to in the current block.
-			if !test_restore_to_with_dirty_storage {
-				// Advance 1 block, to the 6th.
-				initialize_block(6);
-			}
+#[test]
+fn code_deposit_is_charged_once() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
 
-			// Perform a call to `DJANGO`. This should either perform restoration successfully or
-			// fail depending on the test parameters.
-			let perform_the_restoration = || {
-				Contracts::call(
-					Origin::signed(ALICE),
-					addr_django.clone(),
-					0,
-					GAS_LIMIT,
-					set_rent_code_hash
-						.as_ref()
-						.iter()
-						.chain(AsRef::<[u8]>::as_ref(&addr_bob))
-						.cloned()
-						.collect(),
-				)
-			};
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			let _ = Balances::deposit_creating(&BOB, 1_000_000);
+			let deposit = Contracts::bare_upload_code(ALICE, wasm.clone(), None).unwrap().deposit;
+			assert!(deposit > 0);
+			assert_eq!(Balances::reserved_balance(&ALICE), deposit);
 
-			// The key that is used in the restorer contract but is not in the target contract.
-			// Is supplied as delta to the restoration. We need it to check whether the key
-			// is properly removed on success but still there on failure.
-			let delta_key = {
-				let mut key = [0u8; 32];
-				key[0] = 1;
-				key
-			};
+			// Uploading the same code again is free.
+			assert_eq!(
+				Contracts::bare_upload_code(BOB, wasm.clone(), None).unwrap().deposit,
+				0,
+			);
+			assert_eq!(Balances::reserved_balance(&BOB), 0);
 
-			if test_different_storage || test_restore_to_with_dirty_storage {
-				// Parametrization of the test imply restoration failure. Check that `DJANGO` aka
-				// restoration contract is still in place and also that `BOB` doesn't exist.
-				let result = perform_the_restoration();
-				assert!(ContractInfoOf::<Test>::get(&addr_bob).unwrap().get_tombstone().is_some());
-				let django_contract = ContractInfoOf::<Test>::get(&addr_django).unwrap()
-					.get_alive().unwrap();
-				assert_eq!(django_contract.storage_size, 8);
-				assert_eq!(django_contract.trie_id, django_trie_id);
-				assert_eq!(django_contract.deduct_block, System::block_number());
-				assert_eq!(
-					Storage::<Test>::read(&django_trie_id, &delta_key),
-					Some(vec![40, 0, 0, 0]),
-				);
-				match (test_different_storage, test_restore_to_with_dirty_storage) {
-					(true, false) => {
-						assert_err_ignore_postinfo!(
-							result, Error::<Test>::InvalidTombstone,
-						);
-						assert_eq!(System::events(), vec![]);
-					}
-					(_, true) => {
-						assert_err_ignore_postinfo!(
-							result, Error::<Test>::InvalidContractOrigin,
-						);
-						pretty_assertions::assert_eq!(System::events(), vec![
-							EventRecord {
-								phase: Phase::Initialization,
-								event: MetaEvent::contracts(RawEvent::Evicted(addr_bob, true)),
-								topics: vec![],
-							},
-							EventRecord {
-								phase: Phase::Initialization,
-								event: MetaEvent::system(frame_system::RawEvent::NewAccount(CHARLIE)),
-								topics: vec![],
-							},
-							EventRecord {
-								phase: Phase::Initialization,
-								event: MetaEvent::balances(pallet_balances::RawEvent::Endowed(CHARLIE, 1_000_000)),
-								topics: vec![],
-							},
-							EventRecord {
-								phase: Phase::Initialization,
-								event: MetaEvent::system(frame_system::RawEvent::NewAccount(addr_django.clone())),
-								topics: vec![],
-							},
-							EventRecord {
-								phase: Phase::Initialization,
-								event: MetaEvent::balances(pallet_balances::RawEvent::Endowed(addr_django.clone(), 30_000)),
-								topics: vec![],
-							},
-							EventRecord {
-								phase: Phase::Initialization,
-								event: MetaEvent::balances(
-									pallet_balances::RawEvent::Transfer(CHARLIE, addr_django.clone(), 30_000)
-								),
-								topics: vec![],
-							},
-							EventRecord {
-								phase: Phase::Initialization,
-								event: MetaEvent::contracts(RawEvent::Instantiated(CHARLIE, addr_django.clone())),
-								topics: vec![],
-							},
-						]);
-					}
-					_ => unreachable!(),
-				}
-			} else {
-				assert_ok!(perform_the_restoration());
-
-				// Here we expect that the restoration is succeeded. Check that the restoration
-				// contract `DJANGO` ceased to exist and that `BOB` returned back.
-				let bob_contract = ContractInfoOf::<Test>::get(&addr_bob).unwrap()
-					.get_alive().unwrap();
-				assert_eq!(bob_contract.rent_allowance, 50);
-				assert_eq!(bob_contract.storage_size, 4);
-				assert_eq!(bob_contract.trie_id, django_trie_id);
-				assert_eq!(bob_contract.deduct_block, System::block_number());
-				assert!(ContractInfoOf::<Test>::get(&addr_django).is_none());
-				assert_matches!(Storage::<Test>::read(&django_trie_id, &delta_key), None);
-				assert_eq!(System::events(), vec![
-					EventRecord {
-						phase: Phase::Initialization,
-						event: MetaEvent::system(system::RawEvent::KilledAccount(addr_django.clone())),
-						topics: vec![],
-					},
-					EventRecord {
-						phase: Phase::Initialization,
-						event: MetaEvent::contracts(
-							RawEvent::Restored(addr_django, addr_bob, bob_contract.code_hash, 50)
-						),
-						topics: vec![],
-					},
-				]);
-			}
+			// The deposit must fit into the limit.
+			assert_ok!(Contracts::remove_code(Origin::signed(ALICE), code_hash));
+			assert_eq!(
+				Contracts::bare_upload_code(BOB, wasm, Some(deposit - 1)),
+				Err(Error::<Test>::StorageDepositLimitExhausted.into()),
+			);
 		});
 }
 
@@ -1369,11 +927,12 @@ fn storage_max_value_limit() {
 		.execute_with(|| {
 			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				30_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -1381,8 +940,7 @@ fn storage_max_value_limit() {
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
 			// Check creation
-			let bob_contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap();
-			assert_eq!(bob_contract.rent_allowance, <BalanceOf<Test>>::max_value());
+			assert!(ContractInfoOf::<Test>::contains_key(&addr));
 
 			// Call contract with allowed storage value.
 			assert_ok!(Contracts::call(
@@ -1390,6 +948,7 @@ fn storage_max_value_limit() {
 				addr.clone(),
 				0,
 				GAS_LIMIT * 2, // we are copying a huge buffer
+				None,
 				<Test as Config>::MaxValueSize::get().encode(),
 			));
 
@@ -1400,6 +959,7 @@ fn storage_max_value_limit() {
 					addr,
 					0,
 					GAS_LIMIT,
+					None,
 					(<Test as Config>::MaxValueSize::get() + 1).encode(),
 				),
 				Error::<Test>::ValueTooLarge,
@@ -1418,13 +978,14 @@ fn deploy_and_call_other_contract() {
 		.execute_with(|| {
 			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), callee_wasm));
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), caller_wasm));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), callee_wasm, None));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), caller_wasm, None));
 
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				100_000,
 				GAS_LIMIT,
+				None,
 				caller_code_hash.into(),
 				vec![],
 				vec![],
@@ -1438,6 +999,7 @@ fn deploy_and_call_other_contract() {
 				addr,
 				0,
 				GAS_LIMIT,
+				None,
 				callee_code_hash.as_ref().to_vec(),
 			));
 		});
@@ -1451,13 +1013,14 @@ fn cannot_self_destruct_through_draning() {
 		.build()
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
 
 			// Instantiate the BOB contract.
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				100_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -1467,7 +1030,7 @@ fn cannot_self_destruct_through_draning() {
 			// Check that the BOB contract has been instantiated.
 			assert_matches!(
 				ContractInfoOf::<Test>::get(&addr),
-				Some(ContractInfo::Alive(_))
+				Some(_)
 			);
 
 			// Call BOB which makes it send all funds to the zero address
@@ -1478,6 +1041,7 @@ fn cannot_self_destruct_through_draning() {
 					addr,
 					0,
 					GAS_LIMIT,
+					None,
 					vec![],
 				)
 			);
@@ -1492,13 +1056,14 @@ fn cannot_self_destruct_while_live() {
 		.build()
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
 
 			// Instantiate the BOB contract.
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				100_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -1508,7 +1073,7 @@ fn cannot_self_destruct_while_live() {
 			// Check that the BOB contract has been instantiated.
 			assert_matches!(
 				ContractInfoOf::<Test>::get(&addr),
-				Some(ContractInfo::Alive(_))
+				Some(_)
 			);
 
 			// Call BOB with input data, forcing it make a recursive call to itself to
@@ -1519,6 +1084,7 @@ fn cannot_self_destruct_while_live() {
 					addr.clone(),
 					0,
 					GAS_LIMIT,
+					None,
 					vec![0],
 				),
 				Error::<Test>::ContractTrapped,
@@ -1527,7 +1093,7 @@ fn cannot_self_destruct_while_live() {
 			// Check that BOB is still alive.
 			assert_matches!(
 				ContractInfoOf::<Test>::get(&addr),
-				Some(ContractInfo::Alive(_))
+				Some(_)
 			);
 		});
 }
@@ -1540,13 +1106,14 @@ fn self_destruct_works() {
 		.build()
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
 
 			// Instantiate the BOB contract.
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				100_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -1556,8 +1123,10 @@ fn self_destruct_works() {
 			// Check that the BOB contract has been instantiated.
 			assert_matches!(
 				ContractInfoOf::<Test>::get(&addr),
-				Some(ContractInfo::Alive(_))
+				Some(_)
 			);
+			let deposit = info_deposit(&addr);
+			let alice_balance = Balances::free_balance(&ALICE);
 
 			// Call BOB without input data which triggers termination.
 			assert_matches!(
@@ -1566,6 +1135,7 @@ fn self_destruct_works() {
 					addr.clone(),
 					0,
 					GAS_LIMIT,
+					None,
 					vec![],
 				),
 				Ok(_)
@@ -1576,6 +1146,9 @@ fn self_destruct_works() {
 
 			// check that the beneficiary (django) got remaining balance
 			assert_eq!(Balances::free_balance(DJANGO), 100_000);
+
+			// check that the storage deposit was refunded to the caller
+			assert_eq!(Balances::free_balance(&ALICE), alice_balance + deposit);
 		});
 }
 
@@ -1592,8 +1165,8 @@ fn destroy_contract_and_transfer_funds() {
 		.execute_with(|| {
 			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), callee_wasm));
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), caller_wasm));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), callee_wasm, None));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), caller_wasm, None));
 
 			// This deploys the BOB contract, which in turn deploys the CHARLIE contract during
 			// construction.
@@ -1601,6 +1174,7 @@ fn destroy_contract_and_transfer_funds() {
 				Origin::signed(ALICE),
 				200_000,
 				GAS_LIMIT,
+				None,
 				caller_code_hash.into(),
 				callee_code_hash.as_ref().to_vec(),
 				vec![],
@@ -1613,7 +1187,7 @@ fn destroy_contract_and_transfer_funds() {
 			// Check that the CHARLIE contract has been instantiated.
 			assert_matches!(
 				ContractInfoOf::<Test>::get(&addr_charlie),
-				Some(ContractInfo::Alive(_))
+				Some(_)
 			);
 
 			// Call BOB, which calls CHARLIE, forcing CHARLIE to self-destruct.
@@ -1622,6 +1196,7 @@ fn destroy_contract_and_transfer_funds() {
 				addr_bob,
 				0,
 				GAS_LIMIT,
+				None,
 				addr_charlie.encode(),
 			));
 
@@ -1638,7 +1213,7 @@ fn cannot_self_destruct_in_constructor() {
 		.build()
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
 
 			// Fail to instantiate the BOB because the contructor calls seal_terminate.
 			assert_err_ignore_postinfo!(
@@ -1646,6 +1221,7 @@ fn cannot_self_destruct_in_constructor() {
 					Origin::signed(ALICE),
 					100_000,
 					GAS_LIMIT,
+					None,
 					code_hash.into(),
 					vec![],
 					vec![],
@@ -1664,13 +1240,14 @@ fn crypto_hashes() {
 		.build()
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
 
 			// Instantiate the CRYPTO_HASHES contract.
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				100_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -1697,13 +1274,15 @@ fn crypto_hashes() {
 				// We offset data in the contract tables by 1.
 				let mut params = vec![(n + 1) as u8];
 				params.extend_from_slice(input);
-				let result = <Module<Test>>::bare_call(
+				let result = <Pallet<Test>>::bare_call(
 					ALICE,
 					addr.clone(),
 					0,
 					GAS_LIMIT,
+					None,
 					params,
-				).exec_result.unwrap();
+					false,
+				).result.unwrap();
 				assert!(result.is_success());
 				let expected = hash_fn(input.as_ref());
 				assert_eq!(&result.data[..*expected_size], &*expected);
@@ -1717,13 +1296,14 @@ fn transfer_return_code() {
 	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
 		let subsistence = ConfigCache::<Test>::subsistence_threshold_uncached();
 		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
-		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
 
 		assert_ok!(
 			Contracts::instantiate(
 				Origin::signed(ALICE),
 				subsistence,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -1737,8 +1317,10 @@ fn transfer_return_code() {
 			addr.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			vec![],
-		).exec_result.unwrap();
+			false,
+		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::BelowSubsistenceThreshold);
 
 		// Contract has enough total balance in order to not go below the subsistence
@@ -1751,8 +1333,10 @@ fn transfer_return_code() {
 			addr,
 			0,
 			GAS_LIMIT,
+			None,
 			vec![],
-		).exec_result.unwrap();
+			false,
+		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::TransferFailed);
 	});
 }
@@ -1765,14 +1349,15 @@ fn call_return_code() {
 		let subsistence = ConfigCache::<Test>::subsistence_threshold_uncached();
 		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
 		let _ = Balances::deposit_creating(&CHARLIE, 10 * subsistence);
-		assert_ok!(Contracts::put_code(Origin::signed(ALICE), caller_code));
-		assert_ok!(Contracts::put_code(Origin::signed(ALICE), callee_code));
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), caller_code, None));
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), callee_code, None));
 
 		assert_ok!(
 			Contracts::instantiate(
 				Origin::signed(ALICE),
 				subsistence,
 				GAS_LIMIT,
+				None,
 				caller_hash.into(),
 				vec![0],
 				vec![],
@@ -1786,8 +1371,10 @@ fn call_return_code() {
 			addr_bob.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			AsRef::<[u8]>::as_ref(&DJANGO).to_vec(),
-		).exec_result.unwrap();
+			false,
+		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::NotCallable);
 
 		assert_ok!(
@@ -1795,6 +1382,7 @@ fn call_return_code() {
 				Origin::signed(CHARLIE),
 				subsistence,
 				GAS_LIMIT,
+				None,
 				callee_hash.into(),
 				vec![0],
 				vec![],
@@ -1808,8 +1396,10 @@ fn call_return_code() {
 			addr_bob.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			AsRef::<[u8]>::as_ref(&addr_django).iter().chain(&0u32.to_le_bytes()).cloned().collect(),
-		).exec_result.unwrap();
+			false,
+		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::BelowSubsistenceThreshold);
 
 		// Contract has enough total balance in order to not go below the subsistence
@@ -1822,8 +1412,10 @@ fn call_return_code() {
 			addr_bob.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			AsRef::<[u8]>::as_ref(&addr_django).iter().chain(&0u32.to_le_bytes()).cloned().collect(),
-		).exec_result.unwrap();
+			false,
+		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::TransferFailed);
 
 		// Contract has enough balance but callee reverts because "1" is passed.
@@ -1833,8 +1425,10 @@ fn call_return_code() {
 			addr_bob.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			AsRef::<[u8]>::as_ref(&addr_django).iter().chain(&1u32.to_le_bytes()).cloned().collect(),
-		).exec_result.unwrap();
+			false,
+		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CalleeReverted);
 
 		// Contract has enough balance but callee traps because "2" is passed.
@@ -1843,8 +1437,10 @@ fn call_return_code() {
 			addr_bob,
 			0,
 			GAS_LIMIT,
+			None,
 			AsRef::<[u8]>::as_ref(&addr_django).iter().chain(&2u32.to_le_bytes()).cloned().collect(),
-		).exec_result.unwrap();
+			false,
+		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CalleeTrapped);
 
 	});
@@ -1858,8 +1454,8 @@ fn instantiate_return_code() {
 		let subsistence = ConfigCache::<Test>::subsistence_threshold_uncached();
 		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
 		let _ = Balances::deposit_creating(&CHARLIE, 10 * subsistence);
-		assert_ok!(Contracts::put_code(Origin::signed(ALICE), caller_code));
-		assert_ok!(Contracts::put_code(Origin::signed(ALICE), callee_code));
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), caller_code, None));
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), callee_code, None));
 		let callee_hash = callee_hash.as_ref().to_vec();
 
 		assert_ok!(
@@ -1867,6 +1463,7 @@ fn instantiate_return_code() {
 				Origin::signed(ALICE),
 				subsistence,
 				GAS_LIMIT,
+				None,
 				caller_hash.into(),
 				vec![],
 				vec![],
@@ -1880,8 +1477,10 @@ fn instantiate_return_code() {
 			addr.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			vec![0; 33],
-		).exec_result.unwrap();
+			false,
+		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::BelowSubsistenceThreshold);
 
 		// Contract has enough total balance in order to not go below the subsistence
@@ -1894,8 +1493,10 @@ fn instantiate_return_code() {
 			addr.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			vec![0; 33],
-		).exec_result.unwrap();
+			false,
+		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::TransferFailed);
 
 		// Contract has enough balance but the passed code hash is invalid
@@ -1905,8 +1506,10 @@ fn instantiate_return_code() {
 			addr.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			vec![0; 33],
-		).exec_result.unwrap();
+			false,
+		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CodeNotFound);
 
 		// Contract has enough balance but callee reverts because "1" is passed.
@@ -1915,8 +1518,10 @@ fn instantiate_return_code() {
 			addr.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			callee_hash.iter().chain(&1u32.to_le_bytes()).cloned().collect(),
-		).exec_result.unwrap();
+			false,
+		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CalleeReverted);
 
 		// Contract has enough balance but callee traps because "2" is passed.
@@ -1925,9 +1530,366 @@ fn instantiate_return_code() {
 			addr,
 			0,
 			GAS_LIMIT,
+			None,
 			callee_hash.iter().chain(&2u32.to_le_bytes()).cloned().collect(),
-		).exec_result.unwrap();
+			false,
+		).result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CalleeTrapped);
 
 	});
 }
+
+#[test]
+fn disabled_chain_extension_wont_deploy() {
+	let (code, _hash) = compile_module::<Test>("chain_extension").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		TestExtension::disable();
+		assert_err_ignore_postinfo!(
+			Contracts::put_code(Origin::signed(ALICE), code, None),
+			"module uses chain extensions but chain extensions are disabled",
+		);
+	});
+}
+
+#[test]
+fn chain_extension_works() {
+	let (code, hash) = compile_module::<Test>("chain_extension").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let subsistence = ConfigCache::<Test>::subsistence_threshold_uncached();
+		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), code, None));
+		assert_ok!(
+			Contracts::instantiate(
+				Origin::signed(ALICE),
+				subsistence,
+				GAS_LIMIT,
+				None,
+				hash.into(),
+				vec![],
+				vec![],
+			),
+		);
+		let addr = Contracts::contract_address(&ALICE, &hash, &[]);
+
+		// func_id 0 copies its input to the output and stores it in the extension.
+		let input: Vec<u8> = (0u32, 42u32, 99u64).encode();
+		let result = Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			input.clone(),
+			false,
+		);
+		assert_eq!(TestExtension::last_seen_buffer(), input);
+		assert_eq!(result.result.unwrap().data, input);
+
+		// func_id 1 charges the weight passed as input.
+		let gas_consumed = Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			(1u32, 0u64).encode(),
+			false,
+		).gas_consumed;
+		let result = Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			(1u32, 100_000u64).encode(),
+			false,
+		);
+		assert_ok!(result.result);
+		assert_eq!(result.gas_consumed, gas_consumed + 100_000);
+
+		// func_id 2 diverges and reverts the call with data.
+		let result = Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			2u32.encode(),
+			false,
+		).result.unwrap();
+		assert_eq!(result.flags, ReturnFlags::REVERT);
+		assert_eq!(result.data, vec![42, 99]);
+
+		// Addressing the disabled extension is an error.
+		let result = Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			0x0001_0000u32.encode(),
+			false,
+		).result;
+		assert_eq!(result.unwrap_err(), Error::<Test>::NoChainExtension.into());
+	});
+}
+
+#[test]
+fn debug_message_works() {
+	let (wasm, code_hash) = compile_module::<Test>("debug_message_works").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let subsistence = ConfigCache::<Test>::subsistence_threshold_uncached();
+		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
+		assert_ok!(
+			Contracts::instantiate(
+				Origin::signed(ALICE),
+				subsistence,
+				GAS_LIMIT,
+				None,
+				code_hash.into(),
+				vec![],
+				vec![],
+			),
+		);
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+		let result = Contracts::bare_call(ALICE, addr.clone(), 0, GAS_LIMIT, None, vec![], true);
+		assert_matches!(result.result, Ok(_));
+		assert_eq!(std::str::from_utf8(&result.debug_message).unwrap(), "Hello World!");
+
+		// Nothing is recorded when debug message recording was not requested.
+		let result = Contracts::bare_call(ALICE, addr, 0, GAS_LIMIT, None, vec![], false);
+		assert_matches!(result.result, Ok(_));
+		assert!(result.debug_message.is_empty());
+		assert_eq!(result.events, None);
+	});
+}
+
+#[test]
+fn bare_instantiate_returns_events_and_account() {
+	let (wasm, code_hash) = compile_module::<Test>("return_with_data").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let subsistence = ConfigCache::<Test>::subsistence_threshold_uncached();
+		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
+		let events_before = System::events().len();
+
+		let result = Contracts::bare_instantiate(
+			ALICE,
+			subsistence,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![0, 0, 0, 0, 42],
+			vec![],
+			true,
+		);
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+		let output = result.result.unwrap();
+		assert_eq!(output.account_id, addr);
+		assert_eq!(output.result.data, vec![42]);
+		// The deposit covers the uploaded code and the contract info.
+		assert_eq!(
+			result.storage_deposit,
+			StorageDeposit::Charge(Balances::reserved_balance(&ALICE) + info_deposit(&addr)),
+		);
+		assert!(result.gas_consumed > 0);
+		assert!(result.gas_required >= result.gas_consumed);
+
+		// Only the events emitted by this instantiation are returned.
+		let events = result.events.unwrap();
+		assert_eq!(events, System::events()[events_before..].to_vec());
+		assert!(events.iter().any(|record| record.event ==
+			MetaEvent::contracts(Event::CodeStored(code_hash))
+		));
+		assert!(events.iter().any(|record| record.event ==
+			MetaEvent::contracts(Event::Instantiated(ALICE, addr.clone()))
+		));
+
+		// Uploading the same code again just returns its hash.
+		assert_eq!(
+			Contracts::bare_upload_code(
+				ALICE,
+				compile_module::<Test>("return_with_data").unwrap().0,
+				None,
+			),
+			Ok(CodeUploadReturnValue { code_hash, deposit: 0 }),
+		);
+	});
+}
+
+#[test]
+fn bare_instantiate_rolls_back_code_upload_on_failure() {
+	let (wasm, code_hash) = compile_module::<Test>("ok_trap_revert").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let subsistence = ConfigCache::<Test>::subsistence_threshold_uncached();
+		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
+
+		// The constructor reverts: nothing is stored and nothing is charged.
+		let result = Contracts::bare_instantiate(
+			ALICE,
+			subsistence,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm.clone()),
+			vec![1],
+			vec![],
+			false,
+		);
+		assert!(!result.result.unwrap().result.is_success());
+		assert_eq!(result.storage_deposit, StorageDeposit::default());
+		assert!(!crate::PristineCode::<Test>::contains_key(&code_hash));
+		assert!(!crate::CodeStorage::<Test>::contains_key(&code_hash));
+		assert_eq!(Balances::reserved_balance(&ALICE), 0);
+
+		// The constructor traps.
+		let result = Contracts::bare_instantiate(
+			ALICE,
+			subsistence,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![2],
+			vec![],
+			false,
+		);
+		assert_matches!(result.result, Err(_));
+		assert_eq!(result.storage_deposit, StorageDeposit::default());
+		assert!(!crate::PristineCode::<Test>::contains_key(&code_hash));
+		assert!(!crate::CodeStorage::<Test>::contains_key(&code_hash));
+		assert_eq!(Balances::reserved_balance(&ALICE), 0);
+	});
+}
+
+#[test]
+fn call_runtime_works() {
+	let (code, hash) = compile_module::<Test>("call_runtime").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let subsistence = ConfigCache::<Test>::subsistence_threshold_uncached();
+		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), code, None));
+		assert_ok!(
+			Contracts::instantiate(
+				Origin::signed(ALICE),
+				5 * subsistence,
+				GAS_LIMIT,
+				None,
+				hash.into(),
+				vec![],
+				vec![],
+			),
+		);
+		let addr = Contracts::contract_address(&ALICE, &hash, &[]);
+		let call = Call::Balances(pallet_balances::Call::transfer(CHARLIE, subsistence));
+
+		// The contract dispatches the transfer with itself as origin.
+		let result = Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			call.encode(),
+			false,
+		);
+		assert_return_code!(result.result.unwrap(), RuntimeReturnCode::Success);
+		assert_eq!(Balances::free_balance(&CHARLIE), subsistence);
+
+		// Calls that are rejected by the filter are not executed.
+		TestFilter::set_filter(|call| !matches!(call, Call::Balances(_)));
+		let result = Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			call.encode(),
+			false,
+		);
+		assert_return_code!(result.result.unwrap(), RuntimeReturnCode::CallRuntimeReturnedError);
+		assert_eq!(Balances::free_balance(&CHARLIE), subsistence);
+	});
+}
+
+#[test]
+fn reinstrument_does_charge() {
+	let (wasm, code_hash) = compile_module::<Test>("return_with_data").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let subsistence = ConfigCache::<Test>::subsistence_threshold_uncached();
+		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
+		let zero = 0u32.encode();
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm, None));
+		assert_ok!(
+			Contracts::instantiate(
+				Origin::signed(ALICE),
+				subsistence,
+				GAS_LIMIT,
+				None,
+				code_hash.into(),
+				zero.clone(),
+				vec![],
+			),
+		);
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+		// Calling a contract that was instrumented with the current schedule
+		// does not re-instrument it.
+		let result0 = Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			zero.clone(),
+			false,
+		);
+		assert_ok!(result0.result);
+		let result1 = Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			zero.clone(),
+			false,
+		);
+		assert_ok!(result1.result);
+		assert_eq!(result0.gas_consumed, result1.gas_consumed);
+
+		// Bumping the schedule version forces a re-instrumentation on the next call
+		// which is charged in proportion to the size of the pristine code.
+		let schedule = Contracts::current_schedule();
+		assert_ok!(Contracts::update_schedule(
+			Origin::root(),
+			Schedule { version: schedule.version + 1, ..schedule },
+		));
+		let code_len = <PristineCode<Test>>::get(&code_hash).unwrap().len() as u32;
+		let result2 = Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			zero.clone(),
+			false,
+		);
+		assert_ok!(result2.result);
+		assert_eq!(
+			result2.gas_consumed,
+			result1.gas_consumed + <Test as Config>::WeightInfo::reinstrument(code_len / 1024),
+		);
+
+		// The re-instrumented code is cached.
+		let result3 = Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			zero.clone(),
+			false,
+		);
+		assert_ok!(result3.result);
+		assert_eq!(result1.gas_consumed, result3.gas_consumed);
+	});
+}
@@ -25,29 +25,106 @@
 //! - When we update the schedule we want it to have strictly greater version than the current saved one:
 //! this guarantees that every instrumented contract code in cache cannot have the version equal to the current one.
 //! Thus, before executing a contract it should be reinstrument with new schedule.
+//! - The weight of loading and re-instrumenting code is charged in proportion to the size
+//! of the code before it is read from storage.
+//! - The account that uploads a code reserves a deposit for the storage it occupies. The deposit
+//! is refunded when the code is removed which is only possible when no contract uses it.
 
-use crate::wasm::{prepare, runtime::Env, PrefabWasmModule};
-use crate::{CodeHash, CodeStorage, PristineCode, Schedule, Config};
+use crate::wasm::{prepare, runtime::Env, OwnerInfo, PrefabWasmModule};
+use crate::{
+	CodeHash, CodeStorage, PristineCode, OwnerInfoOf, Schedule, Config, BalanceOf, Error,
+	gas::{Gas, GasMeter, GasMeterResult, Token},
+	storage::meter::Diff,
+	weights::WeightInfo,
+};
 use sp_std::prelude::*;
-use sp_runtime::traits::Hash;
+use sp_runtime::traits::{Hash, Zero};
 use sp_core::crypto::UncheckedFrom;
-use frame_support::StorageMap;
+use codec::Encode;
+use frame_support::{
+	dispatch::{DispatchError, DispatchResult},
+	traits::ReservableCurrency,
+	ensure,
+};
 
 /// Put code in the storage. The hash of code is used as a key and is returned
-/// as a result of this function.
+/// as a result of this function together with the deposit that was reserved from `owner`.
 ///
-/// This function instruments the given code and caches it in the storage.
+/// This function instruments the given code and caches it in the storage. Nothing is
+/// stored or charged when the code already exists.
 pub fn save<T: Config>(
 	original_code: Vec<u8>,
+	owner: &T::AccountId,
+	storage_deposit_limit: Option<BalanceOf<T>>,
 	schedule: &Schedule<T>,
-) -> Result<CodeHash<T>, &'static str> where T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]> {
-	let prefab_module = prepare::prepare_contract::<Env, T>(&original_code, schedule)?;
+) -> Result<(CodeHash<T>, BalanceOf<T>), DispatchError>
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
+{
 	let code_hash = T::Hashing::hash(&original_code);
+	if <PristineCode<T>>::contains_key(&code_hash) {
+		return Ok((code_hash, Zero::zero()));
+	}
+
+	let prefab_module = prepare::prepare_contract::<Env, T>(&original_code, schedule)?;
+	let deposit = Diff {
+		bytes_added: (original_code.len() + prefab_module.encoded_size()) as u32,
+		items_added: 2,
+		..Default::default()
+	}.to_deposit::<T>().charge_or_zero();
+	if let Some(limit) = storage_deposit_limit {
+		ensure!(deposit <= limit, Error::<T>::StorageDepositLimitExhausted);
+	}
+	T::Currency::reserve(owner, deposit)
+		.map_err(|_| Error::<T>::StorageDepositNotEnoughFunds)?;
 
 	<CodeStorage<T>>::insert(code_hash, prefab_module);
 	<PristineCode<T>>::insert(code_hash, original_code);
+	<OwnerInfoOf<T>>::insert(code_hash, OwnerInfo::<T> {
+		owner: owner.clone(),
+		deposit,
+		refcount: 0,
+	});
 
-	Ok(code_hash)
+	Ok((code_hash, deposit))
+}
+
+/// Increment the number of contracts that use the code.
+///
+/// Code that was stored before code deposits were introduced has no owner and is not
+/// reference counted.
+pub fn increment_refcount<T: Config>(code_hash: &CodeHash<T>) {
+	<OwnerInfoOf<T>>::mutate(code_hash, |existing| {
+		if let Some(info) = existing {
+			info.refcount = info.refcount.saturating_add(1);
+		}
+	});
+}
+
+/// Decrement the number of contracts that use the code.
+pub fn decrement_refcount<T: Config>(code_hash: &CodeHash<T>) {
+	<OwnerInfoOf<T>>::mutate(code_hash, |existing| {
+		if let Some(info) = existing {
+			info.refcount = info.refcount.saturating_sub(1);
+		}
+	});
+}
+
+/// Remove the code from storage and refund the deposit to its owner.
+///
+/// Only the owner can remove a code and only when no contract uses it anymore.
+pub fn try_remove<T: Config>(origin: &T::AccountId, code_hash: CodeHash<T>) -> DispatchResult
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
+{
+	let info = <OwnerInfoOf<T>>::get(&code_hash).ok_or(Error::<T>::CodeNotFound)?;
+	ensure!(info.refcount == 0, Error::<T>::CodeInUse);
+	ensure!(&info.owner == origin, DispatchError::BadOrigin);
+	T::Currency::unreserve(&info.owner, info.deposit);
+	<OwnerInfoOf<T>>::remove(&code_hash);
+	<CodeStorage<T>>::remove(&code_hash);
+	<PristineCode<T>>::remove(&code_hash);
+	Ok(())
 }
 
 /// Version of `save` to be used in runtime benchmarks.
@@ -70,25 +147,84 @@ pub fn save_raw<T: Config>(
 
 /// Load code with the given code hash.
 ///
-/// If the module was instrumented with a lower version of schedule than
-/// the current one given as an argument, then this function will perform
-/// re-instrumentation and update the cache in the storage.
+/// The weight of loading the code is charged from `gas_meter` in proportion to its size
+/// before the code is read from storage. If the module was instrumented with a lower
+/// version of schedule than the current one given as an argument, then this function
+/// will perform re-instrumentation and update the cache in the storage. The
+/// re-instrumentation is charged separately in proportion to the size of the pristine code.
 pub fn load<T: Config>(
 	code_hash: &CodeHash<T>,
 	schedule: &Schedule<T>,
-) -> Result<PrefabWasmModule, &'static str> where T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]> {
-	let mut prefab_module =
-		<CodeStorage<T>>::get(code_hash).ok_or_else(|| "code is not found")?;
+	gas_meter: &mut GasMeter<T>,
+) -> Result<PrefabWasmModule, DispatchError>
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
+{
+	charge::<T>(gas_meter, CodeToken::Load(code_len::<T>(code_hash)?))?;
+	let prefab_module = <CodeStorage<T>>::get(code_hash).ok_or(Error::<T>::CodeNotFound)?;
 
 	if prefab_module.schedule_version < schedule.version {
 		// The current schedule version is greater than the version of the one cached
 		// in the storage.
 		//
 		// We need to re-instrument the code with the latest schedule here.
-		let original_code =
-			<PristineCode<T>>::get(code_hash).ok_or_else(|| "pristine code is not found")?;
-		prefab_module = prepare::prepare_contract::<Env, T>(&original_code, schedule)?;
-		<CodeStorage<T>>::insert(&code_hash, &prefab_module);
+		charge::<T>(gas_meter, CodeToken::Reinstrument(prefab_module.original_code_len))?;
+		return reinstrument::<T>(code_hash, schedule);
 	}
 	Ok(prefab_module)
 }
+
+/// Instrument the pristine code stored under `code_hash` with the given schedule and
+/// replace the cached module with the result.
+pub fn reinstrument<T: Config>(
+	code_hash: &CodeHash<T>,
+	schedule: &Schedule<T>,
+) -> Result<PrefabWasmModule, DispatchError>
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
+{
+	let original_code = <PristineCode<T>>::get(code_hash).ok_or(Error::<T>::CodeNotFound)?;
+	let prefab_module = prepare::prepare_contract::<Env, T>(&original_code, schedule)?;
+	<CodeStorage<T>>::insert(&code_hash, &prefab_module);
+	Ok(prefab_module)
+}
+
+/// Returns the size in bytes of the instrumented code stored under `code_hash`.
+///
+/// The value itself is not copied which allows charging for its size before it is read.
+fn code_len<T: Config>(code_hash: &CodeHash<T>) -> Result<u32, DispatchError> {
+	let key = <CodeStorage<T>>::hashed_key_for(code_hash);
+	sp_io::storage::read(&key, &mut [0u8; 0], 0).ok_or_else(|| Error::<T>::CodeNotFound.into())
+}
+
+fn charge<T: Config>(gas_meter: &mut GasMeter<T>, token: CodeToken) -> DispatchResult {
+	match gas_meter.charge(&(), token) {
+		GasMeterResult::Proceed => Ok(()),
+		GasMeterResult::OutOfGas => Err(Error::<T>::OutOfGas.into()),
+	}
+}
+
+/// Costs of loading and preparing code that depend on the size of the code.
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+#[derive(Clone, Copy)]
+enum CodeToken {
+	/// Weight of loading an instrumented code of the given size in bytes.
+	Load(u32),
+	/// Weight of re-instrumenting a pristine code of the given size in bytes.
+	Reinstrument(u32),
+}
+
+impl<T: Config> Token<T> for CodeToken {
+	type Metadata = ();
+
+	fn calculate_amount(&self, _metadata: &()) -> Gas {
+		use self::CodeToken::*;
+		match *self {
+			// The size independent part of loading a code is already covered by the weight
+			// of the dispatchable that started the execution.
+			Load(len) => T::WeightInfo::code_load(len / 1024)
+				.saturating_sub(T::WeightInfo::code_load(0)),
+			Reinstrument(len) => T::WeightInfo::reinstrument(len / 1024),
+		}
+	}
+}
@@ -17,7 +17,7 @@
 //! This module provides a means for executing contracts
 //! represented in wasm.
 
-use crate::{CodeHash, Schedule, Config};
+use crate::{CodeHash, Schedule, Config, BalanceOf};
 use crate::wasm::env_def::FunctionImplProvider;
 use crate::exec::Ext;
 use crate::gas::GasMeter;
@@ -26,6 +26,7 @@ use sp_std::prelude::*;
 use sp_core::crypto::UncheckedFrom;
 use codec::{Encode, Decode};
 use sp_sandbox;
+use sp_runtime::DispatchError;
 
 #[macro_use]
 mod env_def;
@@ -33,32 +34,50 @@ mod code_cache;
 mod prepare;
 mod runtime;
 
-use self::runtime::Runtime;
-use self::code_cache::load as load_code;
 use pallet_contracts_primitives::ExecResult;
 
-pub use self::code_cache::save as save_code;
+pub use self::code_cache::{
+	save as save_code, load as load_code, try_remove as remove_code, increment_refcount,
+	decrement_refcount,
+};
 #[cfg(feature = "runtime-benchmarks")]
-pub use self::code_cache::save_raw as save_code_raw;
+pub use self::code_cache::{save_raw as save_code_raw, reinstrument as reinstrument_code};
 pub use self::runtime::ReturnCode;
+pub(crate) use self::runtime::{Runtime, RuntimeToken};
 
 /// A prepared wasm module ready for execution.
 #[derive(Clone, Encode, Decode)]
 pub struct PrefabWasmModule {
 	/// Version of the schedule with which the code was instrumented.
 	#[codec(compact)]
-	schedule_version: u32,
+	pub(crate) schedule_version: u32,
 	#[codec(compact)]
-	initial: u32,
+	pub(crate) initial: u32,
 	#[codec(compact)]
-	maximum: u32,
-	/// This field is reserved for future evolution of format.
+	pub(crate) maximum: u32,
+	/// The size of the uninstrumented code.
 	///
-	/// Basically, for now this field will be serialized as `None`. In the future
-	/// we would be able to extend this structure with.
-	_reserved: Option<()>,
+	/// Allows charging for the re-instrumentation of the code without reading its pristine
+	/// version from storage first.
+	#[codec(compact)]
+	pub(crate) original_code_len: u32,
 	/// Code instrumented with the latest schedule.
-	code: Vec<u8>,
+	pub(crate) code: Vec<u8>,
+}
+
+pub type OwnerInfo<T> = RawOwnerInfo<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+
+/// Information about the account that uploaded a code and the contracts using it.
+#[derive(Clone, Encode, Decode, PartialEq, Eq, sp_runtime::RuntimeDebug)]
+pub struct RawOwnerInfo<AccountId, Balance> {
+	/// The account that has deployed the code and reserved the deposit for it.
+	pub owner: AccountId,
+	/// The amount of balance that was reserved by the owner in order to store the code.
+	#[codec(compact)]
+	pub deposit: Balance,
+	/// The number of contracts that use this code.
+	#[codec(compact)]
+	pub refcount: u64,
 }
 
 /// Wasm executable loaded by `WasmLoader` and executed by `WasmVm`.
@@ -84,15 +103,23 @@ where
 {
 	type Executable = WasmExecutable;
 
-	fn load_init(&self, code_hash: &CodeHash<T>) -> Result<WasmExecutable, &'static str> {
-		let prefab_module = load_code::<T>(code_hash, self.schedule)?;
+	fn load_init(
+		&self,
+		code_hash: &CodeHash<T>,
+		gas_meter: &mut GasMeter<T>,
+	) -> Result<WasmExecutable, DispatchError> {
+		let prefab_module = load_code::<T>(code_hash, self.schedule, gas_meter)?;
 		Ok(WasmExecutable {
 			entrypoint_name: "deploy",
 			prefab_module,
 		})
 	}
-	fn load_main(&self, code_hash: &CodeHash<T>) -> Result<WasmExecutable, &'static str> {
-		let prefab_module = load_code::<T>(code_hash, self.schedule)?;
+	fn load_main(
+		&self,
+		code_hash: &CodeHash<T>,
+		gas_meter: &mut GasMeter<T>,
+	) -> Result<WasmExecutable, DispatchError> {
+		let prefab_module = load_code::<T>(code_hash, self.schedule, gas_meter)?;
 		Ok(WasmExecutable {
 			entrypoint_name: "call",
 			prefab_module,
@@ -162,17 +189,17 @@ where
 mod tests {
 	use super::*;
 	use crate::{
-		CodeHash, BalanceOf, Error, Module as Contracts,
+		CodeHash, BalanceOf, Error, Pallet as Contracts,
 		exec::{Ext, StorageKey, AccountIdOf},
 		gas::{Gas, GasMeter},
 		tests::{Test, Call, ALICE, BOB},
 		wasm::prepare::prepare_contract,
 	};
-	use std::collections::HashMap;
-	use sp_core::H256;
+	use std::{cell::RefCell, collections::HashMap};
+	use sp_core::{H256, Pair, ecdsa, ed25519, sr25519};
 	use hex_literal::hex;
 	use sp_runtime::DispatchError;
-	use frame_support::weights::Weight;
+	use frame_support::{dispatch::DispatchResultWithPostInfo, weights::Weight};
 	use assert_matches::assert_matches;
 	use pallet_contracts_primitives::{ExecReturnValue, ReturnFlags, ExecError, ErrorOrigin};
 
@@ -181,14 +208,6 @@ mod tests {
 	#[derive(Debug, PartialEq, Eq)]
 	struct DispatchEntry(Call);
 
-	#[derive(Debug, PartialEq, Eq)]
-	struct RestoreEntry {
-		dest: AccountIdOf<Test>,
-		code_hash: H256,
-		rent_allowance: u64,
-		delta: Vec<StorageKey>,
-	}
-
 	#[derive(Debug, PartialEq, Eq)]
 	struct InstantiateEntry {
 		code_hash: H256,
@@ -210,16 +229,25 @@ mod tests {
 		data: Vec<u8>,
 	}
 
+	#[derive(Debug, PartialEq, Eq)]
+	struct DelegateCallEntry {
+		code_hash: H256,
+		data: Vec<u8>,
+		allows_reentry: bool,
+	}
+
 	#[derive(Default)]
 	pub struct MockExt {
 		storage: HashMap<StorageKey, Vec<u8>>,
-		rent_allowance: u64,
 		instantiates: Vec<InstantiateEntry>,
 		terminations: Vec<TerminationEntry>,
 		transfers: Vec<TransferEntry>,
-		restores: Vec<RestoreEntry>,
+		delegate_calls: Vec<DelegateCallEntry>,
+		code_hashes: Vec<H256>,
 		// (topics, data)
 		events: Vec<(Vec<H256>, Vec<u8>)>,
+		runtime_calls: RefCell<Vec<DispatchEntry>>,
+		debug_buffer: Vec<u8>,
 	}
 
 	impl Ext for MockExt {
@@ -272,6 +300,7 @@ mod tests {
 			value: u64,
 			_gas_meter: &mut GasMeter<Test>,
 			data: Vec<u8>,
+			_allows_reentry: bool,
 		) -> ExecResult {
 			self.transfers.push(TransferEntry {
 				to: to.clone(),
@@ -282,33 +311,49 @@ mod tests {
 			// TODO: Add tests for different call outcomes.
 			Ok(ExecReturnValue { flags: ReturnFlags::empty(), data: Vec::new() })
 		}
-		fn terminate(
+		fn delegate_call(
 			&mut self,
-			beneficiary: &AccountIdOf<Self::T>,
-		) -> Result<(), DispatchError> {
-			self.terminations.push(TerminationEntry {
-				beneficiary: beneficiary.clone(),
+			code_hash: CodeHash<Self::T>,
+			_gas_meter: &mut GasMeter<Test>,
+			data: Vec<u8>,
+			allows_reentry: bool,
+		) -> ExecResult {
+			self.delegate_calls.push(DelegateCallEntry {
+				code_hash,
+				data,
+				allows_reentry,
 			});
+			Ok(ExecReturnValue { flags: ReturnFlags::empty(), data: Vec::new() })
+		}
+		fn set_code_hash(&mut self, hash: CodeHash<Self::T>) -> Result<(), DispatchError> {
+			self.code_hashes.push(hash);
 			Ok(())
 		}
-		fn restore_to(
+		fn terminate(
 			&mut self,
-			dest: AccountIdOf<Self::T>,
-			code_hash: H256,
-			rent_allowance: u64,
-			delta: Vec<StorageKey>,
+			beneficiary: &AccountIdOf<Self::T>,
 		) -> Result<(), DispatchError> {
-			self.restores.push(RestoreEntry {
-				dest,
-				code_hash,
-				rent_allowance,
-				delta,
+			self.terminations.push(TerminationEntry {
+				beneficiary: beneficiary.clone(),
 			});
 			Ok(())
 		}
 		fn caller(&self) -> &AccountIdOf<Self::T> {
 			&ALICE
 		}
+		fn caller_is_origin(&self) -> bool {
+			false
+		}
+		fn code_hash(&self, address: &AccountIdOf<Self::T>) -> Option<CodeHash<Self::T>> {
+			if address == &BOB {
+				Some(H256::repeat_byte(0x11))
+			} else {
+				None
+			}
+		}
+		fn own_code_hash(&self) -> CodeHash<Self::T> {
+			H256::repeat_byte(0x11)
+		}
 		fn address(&self) -> &AccountIdOf<Self::T> {
 			&BOB
 		}
@@ -327,10 +372,6 @@ mod tests {
 			666
 		}
 
-		fn tombstone_deposit(&self) -> u64 {
-			16
-		}
-
 		fn random(&self, subject: &[u8]) -> H256 {
 			H256::from_slice(subject)
 		}
@@ -339,14 +380,6 @@ mod tests {
 			self.events.push((topics, data))
 		}
 
-		fn set_rent_allowance(&mut self, rent_allowance: u64) {
-			self.rent_allowance = rent_allowance;
-		}
-
-		fn rent_allowance(&self) -> u64 {
-			self.rent_allowance
-		}
-
 		fn block_number(&self) -> u64 { 121 }
 
 		fn max_value_size(&self) -> u32 { 16_384 }
@@ -354,6 +387,15 @@ mod tests {
 		fn get_weight_price(&self, weight: Weight) -> BalanceOf<Self::T> {
 			BalanceOf::<Self::T>::from(1312_u32).saturating_mul(weight.into())
 		}
+
+		fn append_debug_buffer(&mut self, msg: &str) -> bool {
+			self.debug_buffer.extend(msg.as_bytes());
+			true
+		}
+		fn call_runtime(&self, call: Call) -> DispatchResultWithPostInfo {
+			self.runtime_calls.borrow_mut().push(DispatchEntry(call));
+			Ok(Default::default())
+		}
 	}
 
 	impl Ext for &mut MockExt {
@@ -394,26 +436,34 @@ mod tests {
 			value: u64,
 			gas_meter: &mut GasMeter<Test>,
 			input_data: Vec<u8>,
+			allows_reentry: bool,
 		) -> ExecResult {
-			(**self).call(to, value, gas_meter, input_data)
+			(**self).call(to, value, gas_meter, input_data, allows_reentry)
 		}
-		fn restore_to(
+		fn delegate_call(
 			&mut self,
-			dest: AccountIdOf<Self::T>,
-			code_hash: H256,
-			rent_allowance: u64,
-			delta: Vec<StorageKey>,
-		) -> Result<(), DispatchError> {
-			(**self).restore_to(
-				dest,
-				code_hash,
-				rent_allowance,
-				delta,
-			)
+			code_hash: CodeHash<Self::T>,
+			gas_meter: &mut GasMeter<Test>,
+			input_data: Vec<u8>,
+			allows_reentry: bool,
+		) -> ExecResult {
+			(**self).delegate_call(code_hash, gas_meter, input_data, allows_reentry)
+		}
+		fn set_code_hash(&mut self, hash: CodeHash<Self::T>) -> Result<(), DispatchError> {
+			(**self).set_code_hash(hash)
 		}
 		fn caller(&self) -> &AccountIdOf<Self::T> {
 			(**self).caller()
 		}
+		fn caller_is_origin(&self) -> bool {
+			(**self).caller_is_origin()
+		}
+		fn code_hash(&self, address: &AccountIdOf<Self::T>) -> Option<CodeHash<Self::T>> {
+			(**self).code_hash(address)
+		}
+		fn own_code_hash(&self) -> CodeHash<Self::T> {
+			(**self).own_code_hash()
+		}
 		fn address(&self) -> &AccountIdOf<Self::T> {
 			(**self).address()
 		}
@@ -429,21 +479,12 @@ mod tests {
 		fn minimum_balance(&self) -> u64 {
 			(**self).minimum_balance()
 		}
-		fn tombstone_deposit(&self) -> u64 {
-			(**self).tombstone_deposit()
-		}
 		fn random(&self, subject: &[u8]) -> H256 {
 			(**self).random(subject)
 		}
 		fn deposit_event(&mut self, topics: Vec<H256>, data: Vec<u8>) {
 			(**self).deposit_event(topics, data)
 		}
-		fn set_rent_allowance(&mut self, rent_allowance: u64) {
-			(**self).set_rent_allowance(rent_allowance)
-		}
-		fn rent_allowance(&self) -> u64 {
-			(**self).rent_allowance()
-		}
 		fn block_number(&self) -> u64 {
 			(**self).block_number()
 		}
@@ -453,6 +494,12 @@ mod tests {
 		fn get_weight_price(&self, weight: Weight) -> BalanceOf<Self::T> {
 			(**self).get_weight_price(weight)
 		}
+		fn append_debug_buffer(&mut self, msg: &str) -> bool {
+			(**self).append_debug_buffer(msg)
+		}
+		fn call_runtime(&self, call: Call) -> DispatchResultWithPostInfo {
+			(**self).call_runtime(call)
+		}
 	}
 
 	fn execute<E: Ext>(
@@ -604,6 +651,87 @@ mod tests {
 		);
 	}
 
+	const CODE_DELEGATE_CALL: &str = r#"
+(module
+	;; seal_delegate_call(
+	;;    flags: u32,
+	;;    code_hash_ptr: u32,
+	;;    code_hash_len: u32,
+	;;    gas: u64,
+	;;    input_data_ptr: u32,
+	;;    input_data_len: u32,
+	;;    output_ptr: u32,
+	;;    output_len_ptr: u32
+	;;) -> u32
+	(import "seal0" "seal_delegate_call" (func $seal_delegate_call (param i32 i32 i32 i64 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(drop
+			(call $seal_delegate_call
+				(i32.const 1) ;; ALLOW_REENTRY
+				(i32.const 4)  ;; Pointer to "code_hash".
+				(i32.const 32)  ;; Length of "code_hash".
+				(i64.const 0)  ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 36) ;; Pointer to input data buffer address
+				(i32.const 4)  ;; Length of input data buffer
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+	)
+	(func (export "deploy"))
+
+	;; Code hash to execute
+	(data (i32.const 4)
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+	)
+
+	(data (i32.const 36) "\01\02\03\04")
+)
+"#;
+
+	#[test]
+	fn contract_delegate_call() {
+		let mut mock_ext = MockExt::default();
+		let _ = execute(
+			CODE_DELEGATE_CALL,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(
+			&mock_ext.delegate_calls,
+			&[DelegateCallEntry {
+				code_hash: H256::repeat_byte(0x11),
+				data: vec![1, 2, 3, 4],
+				allows_reentry: true,
+			}]
+		);
+	}
+
+	#[test]
+	fn contract_delegate_call_invalid_flags() {
+		let mut mock_ext = MockExt::default();
+		let result = execute(
+			// Only the lowest bit is a valid flag.
+			&CODE_DELEGATE_CALL.replace("(i32.const 1) ;; ALLOW_REENTRY", "(i32.const 2)"),
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		);
+
+		assert_eq!(
+			result,
+			Err(ExecError {
+				error: Error::<Test>::InvalidCallFlags.into(),
+				origin: ErrorOrigin::Caller,
+			})
+		);
+		assert!(mock_ext.delegate_calls.is_empty());
+	}
+
 	const CODE_INSTANTIATE: &str = r#"
 (module
 	;; seal_instantiate(
@@ -926,6 +1054,206 @@ mod tests {
 		).unwrap();
 	}
 
+	/// calls `seal_caller_is_origin` and asserts that the caller is not the origin.
+	const CODE_CALLER_IS_ORIGIN: &str = r#"
+(module
+	(import "seal0" "seal_caller_is_origin" (func $seal_caller_is_origin (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		(call $assert
+			(i32.eqz
+				(call $seal_caller_is_origin)
+			)
+		)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn caller_is_origin() {
+		let _ = execute(
+			CODE_CALLER_IS_ORIGIN,
+			vec![],
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+	}
+
+	/// calls `seal_code_hash` for BOB and for ALICE (no contract) and checks the results.
+	const CODE_CODE_HASH: &str = r#"
+(module
+	(import "seal0" "seal_code_hash" (func $seal_code_hash (param i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; size of our buffer is 32 bytes
+	(data (i32.const 64) "\20")
+
+	;; BOB
+	(data (i32.const 68)
+		"\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02"
+		"\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02"
+	)
+
+	;; ALICE
+	(data (i32.const 100)
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+	)
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		;; BOB is a contract: its code hash is returned
+		(call $assert
+			(i32.eqz
+				(call $seal_code_hash (i32.const 68) (i32.const 32) (i32.const 0) (i32.const 64))
+			)
+		)
+
+		;; assert len == 32
+		(call $assert
+			(i32.eq
+				(i32.load (i32.const 64))
+				(i32.const 32)
+			)
+		)
+
+		;; assert that the first 8 bytes are the beginning of the code hash
+		(call $assert
+			(i64.eq
+				(i64.load (i32.const 0))
+				(i64.const 0x1111111111111111)
+			)
+		)
+
+		;; ALICE is no contract: KeyNotFound is returned
+		(call $assert
+			(i32.eq
+				(call $seal_code_hash (i32.const 100) (i32.const 32) (i32.const 0) (i32.const 64))
+				(i32.const 3)
+			)
+		)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn code_hash() {
+		let _ = execute(
+			CODE_CODE_HASH,
+			vec![],
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+	}
+
+	/// calls `seal_own_code_hash` and compares the result with the mocked code hash.
+	const CODE_OWN_CODE_HASH: &str = r#"
+(module
+	(import "seal0" "seal_own_code_hash" (func $seal_own_code_hash (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; size of our buffer is 32 bytes
+	(data (i32.const 32) "\20")
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		;; fill the buffer with the code hash
+		(call $seal_own_code_hash (i32.const 0) (i32.const 32))
+
+		;; assert len == 32
+		(call $assert
+			(i32.eq
+				(i32.load (i32.const 32))
+				(i32.const 32)
+			)
+		)
+
+		;; assert that the first 8 bytes are the beginning of the code hash
+		(call $assert
+			(i64.eq
+				(i64.load (i32.const 0))
+				(i64.const 0x1111111111111111)
+			)
+		)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn own_code_hash() {
+		let _ = execute(
+			CODE_OWN_CODE_HASH,
+			vec![],
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+	}
+
+	const CODE_SET_CODE_HASH: &str = r#"
+(module
+	(import "seal0" "seal_set_code_hash" (func $seal_set_code_hash (param i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(data (i32.const 0)
+		"\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22"
+		"\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22"
+	)
+
+	(func (export "call")
+		(drop
+			(call $seal_set_code_hash (i32.const 0) (i32.const 32))
+		)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn set_code_hash() {
+		let mut mock_ext = MockExt::default();
+		let _ = execute(
+			CODE_SET_CODE_HASH,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(&mock_ext.code_hashes, &[H256::repeat_byte(0x22)]);
+	}
+
 	/// calls `seal_address` and compares the result with the constant 69.
 	const CODE_ADDRESS: &str = r#"
 (module
@@ -1357,11 +1685,12 @@ mod tests {
 			)
 		)
 
-		;; assert that contents of the buffer is equal to the i64 value of 16.
+		;; assert that contents of the buffer is equal to the i64 value of 0.
+		;; contracts no longer leave a tombstone behind.
 		(call $assert
 			(i64.eq
 				(i64.load (i32.const 0))
-				(i64.const 16)
+				(i64.const 0)
 			)
 		)
 	)
@@ -1455,6 +1784,241 @@ mod tests {
 		);
 	}
 
+	const CODE_ECDSA_RECOVER: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_ecdsa_recover" (func $seal_ecdsa_recover (param i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; size of the input buffer is 128 bytes
+	(data (i32.const 256) "\80")
+
+	(func (export "call")
+		;; input: signature (65 bytes), message hash (32 bytes)
+		(call $seal_input (i32.const 0) (i32.const 256))
+
+		;; store the return code followed by the recovered public key
+		(i32.store
+			(i32.const 128)
+			(call $seal_ecdsa_recover
+				(i32.const 0) ;; Pointer to the signature.
+				(i32.const 65) ;; Pointer to the message hash.
+				(i32.const 132) ;; Pointer to the output buffer.
+			)
+		)
+
+		;; return the return code and the public key
+		(call $seal_return (i32.const 0) (i32.const 128) (i32.const 37))
+	)
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn ecdsa_recover() {
+		let pair = ecdsa::Pair::from_seed(&[1; 32]);
+		let message = b"Hello world";
+		let signature = pair.sign(message);
+		let mut input = signature.as_ref().to_vec();
+		input.extend_from_slice(&sp_io::hashing::blake2_256(message));
+
+		let output = execute(
+			CODE_ECDSA_RECOVER,
+			input.clone(),
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		let mut expected = (ReturnCode::Success as u32).encode();
+		expected.extend_from_slice(pair.public().as_ref());
+		assert_eq!(output.data, expected);
+
+		// An invalid recovery id makes the recovery fail.
+		input[64] = 4;
+		let output = execute(
+			CODE_ECDSA_RECOVER,
+			input,
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data[..4], (ReturnCode::EcdsaRecoverFailed as u32).encode()[..]);
+	}
+
+	const CODE_ECDSA_TO_ETH_ADDRESS: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_ecdsa_to_eth_address" (func $seal_ecdsa_to_eth_address (param i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; size of the input buffer is 33 bytes
+	(data (i32.const 256) "\21")
+
+	(func (export "call")
+		;; input: compressed public key (33 bytes)
+		(call $seal_input (i32.const 0) (i32.const 256))
+
+		;; store the return code followed by the address
+		(i32.store
+			(i32.const 64)
+			(call $seal_ecdsa_to_eth_address
+				(i32.const 0) ;; Pointer to the public key.
+				(i32.const 68) ;; Pointer to the output buffer.
+			)
+		)
+
+		;; return the return code and the address
+		(call $seal_return (i32.const 0) (i32.const 64) (i32.const 24))
+	)
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn ecdsa_to_eth_address() {
+		// The public key belonging to the private key `1` (the generator point).
+		let output = execute(
+			CODE_ECDSA_TO_ETH_ADDRESS,
+			hex!("0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798").to_vec(),
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		let mut expected = (ReturnCode::Success as u32).encode();
+		expected.extend_from_slice(&hex!("7E5F4552091A69125d5DfCb7b8C2659029395Bdf"));
+		assert_eq!(output.data, expected);
+
+		// Not a point on the curve.
+		let output = execute(
+			CODE_ECDSA_TO_ETH_ADDRESS,
+			[5; 33].to_vec(),
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data[..4], (ReturnCode::EcdsaRecoverFailed as u32).encode()[..]);
+	}
+
+	const CODE_SR25519_VERIFY: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_sr25519_verify" (func $seal_sr25519_verify (param i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; size of the input buffer is 1024 bytes
+	(data (i32.const 2048) "\00\04")
+
+	(func (export "call")
+		;; input: signature (64 bytes), public key (32 bytes), message (rest)
+		(call $seal_input (i32.const 0) (i32.const 2048))
+
+		;; store the return code of the verification
+		(i32.store
+			(i32.const 2052)
+			(call $seal_sr25519_verify
+				(i32.const 0) ;; Pointer to the signature.
+				(i32.const 64) ;; Pointer to the public key.
+				(i32.sub (i32.load (i32.const 2048)) (i32.const 96)) ;; Length of the message.
+				(i32.const 96) ;; Pointer to the message.
+			)
+		)
+
+		;; return the return code
+		(call $seal_return (i32.const 0) (i32.const 2052) (i32.const 4))
+	)
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn sr25519_verify() {
+		let pair = sr25519::Pair::from_seed(&[1; 32]);
+		let message = b"Hello world".to_vec();
+		let signature = pair.sign(&message);
+		let input = |message: &[u8]| {
+			let mut input = signature.as_ref().to_vec();
+			input.extend_from_slice(pair.public().as_ref());
+			input.extend_from_slice(message);
+			input
+		};
+
+		let output = execute(
+			CODE_SR25519_VERIFY,
+			input(&message),
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data, (ReturnCode::Success as u32).encode());
+
+		let output = execute(
+			CODE_SR25519_VERIFY,
+			input(b"Hello world!"),
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data, (ReturnCode::Sr25519VerifyFailed as u32).encode());
+	}
+
+	const CODE_ED25519_VERIFY: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_ed25519_verify" (func $seal_ed25519_verify (param i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; size of the input buffer is 1024 bytes
+	(data (i32.const 2048) "\00\04")
+
+	(func (export "call")
+		;; input: signature (64 bytes), public key (32 bytes), message (rest)
+		(call $seal_input (i32.const 0) (i32.const 2048))
+
+		;; store the return code of the verification
+		(i32.store
+			(i32.const 2052)
+			(call $seal_ed25519_verify
+				(i32.const 0) ;; Pointer to the signature.
+				(i32.const 64) ;; Pointer to the public key.
+				(i32.sub (i32.load (i32.const 2048)) (i32.const 96)) ;; Length of the message.
+				(i32.const 96) ;; Pointer to the message.
+			)
+		)
+
+		;; return the return code
+		(call $seal_return (i32.const 0) (i32.const 2052) (i32.const 4))
+	)
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn ed25519_verify() {
+		let pair = ed25519::Pair::from_seed(&[1; 32]);
+		let message = b"Hello world".to_vec();
+		let signature = pair.sign(&message);
+		let input = |message: &[u8]| {
+			let mut input = signature.as_ref().to_vec();
+			input.extend_from_slice(pair.public().as_ref());
+			input.extend_from_slice(message);
+			input
+		};
+
+		let output = execute(
+			CODE_ED25519_VERIFY,
+			input(&message),
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data, (ReturnCode::Success as u32).encode());
+
+		let output = execute(
+			CODE_ED25519_VERIFY,
+			input(b"Hello world!"),
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data, (ReturnCode::Ed25519VerifyFailed as u32).encode());
+	}
+
 	const CODE_DEPOSIT_EVENT: &str = r#"
 (module
 	(import "seal0" "seal_deposit_event" (func $seal_deposit_event (param i32 i32 i32 i32)))
@@ -1769,4 +2333,144 @@ mod tests {
 		);
 	}
 
+	const CODE_DEBUG_MESSAGE: &str = r#"
+(module
+	(import "seal0" "seal_debug_message" (func $seal_debug_message (param i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(data (i32.const 0) "Hello World!")
+
+	(func (export "call")
+		(call $seal_debug_message
+			(i32.const 0)	;; Pointer to the text buffer
+			(i32.const 12)	;; The size of the buffer
+		)
+		drop
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn debug_message_works() {
+		let mut ext = MockExt::default();
+		execute(
+			CODE_DEBUG_MESSAGE,
+			vec![],
+			&mut ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(std::str::from_utf8(&ext.debug_buffer).unwrap(), "Hello World!");
+	}
+
+	const CODE_DEBUG_MESSAGE_FAIL: &str = r#"
+(module
+	(import "seal0" "seal_debug_message" (func $seal_debug_message (param i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(data (i32.const 0) "\fc")
+
+	(func (export "call")
+		(call $seal_debug_message
+			(i32.const 0)	;; Pointer to the text buffer
+			(i32.const 1)	;; The size of the buffer
+		)
+		drop
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn debug_message_invalid_utf8_fails() {
+		let mut ext = MockExt::default();
+		let result = execute(
+			CODE_DEBUG_MESSAGE_FAIL,
+			vec![],
+			&mut ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		);
+		assert_eq!(
+			result,
+			Err(ExecError {
+				error: Error::<Test>::DebugMessageInvalidUTF8.into(),
+				origin: ErrorOrigin::Caller,
+			})
+		);
+	}
+
+	const CODE_CALL_RUNTIME: &str = r#"
+(module
+	(import "seal0" "seal_call_runtime" (func $seal_call_runtime (param i32 i32) (result i32)))
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; 0x1000 = 4k in little endian
+	;; size of input buffer
+	(data (i32.const 0) "\00\10")
+
+	(func (export "call")
+		;; Receive the encoded call
+		(call $seal_input
+			(i32.const 4)	;; Pointer to the input buffer
+			(i32.const 0)	;; Size of the length buffer
+		)
+		;; Just use the call passed as input and store return value back into memory
+		(i32.store
+			(i32.const 4)
+			(call $seal_call_runtime
+				(i32.const 4)				;; Pointer where the call is stored
+				(i32.load (i32.const 0))	;; Size of the call
+			)
+		)
+		(call $seal_return
+			(i32.const 0)	;; flags
+			(i32.const 4)	;; returned value
+			(i32.const 4)	;; length of returned value
+		)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn call_runtime_works() {
+		let call = Call::System(frame_system::Call::remark(b"Hello World".to_vec()));
+		let mut ext = MockExt::default();
+		let result = execute(
+			CODE_CALL_RUNTIME,
+			call.encode(),
+			&mut ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(
+			*ext.runtime_calls.borrow(),
+			vec![DispatchEntry(call)],
+		);
+		assert_eq!(result.data, (ReturnCode::Success as u32).encode());
+	}
+
+	#[test]
+	fn call_runtime_panics_on_invalid_call() {
+		let mut ext = MockExt::default();
+		let result = execute(
+			CODE_CALL_RUNTIME,
+			vec![0x42],
+			&mut ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		);
+		assert_eq!(
+			result,
+			Err(ExecError {
+				error: Error::<Test>::DecodingFailed.into(),
+				origin: ErrorOrigin::Caller,
+			})
+		);
+		assert_eq!(*ext.runtime_calls.borrow(), vec![]);
+	}
 }
@@ -20,7 +20,7 @@
 
 use crate::wasm::env_def::ImportSatisfyCheck;
 use crate::wasm::PrefabWasmModule;
-use crate::{Schedule, Config};
+use crate::{Schedule, Config, chain_extension::ChainExtension};
 
 use parity_wasm::elements::{self, Internal, External, MemoryType, Type, ValueType};
 use pwasm_utils;
@@ -354,6 +354,12 @@ impl<'a, T: Config> ContractModule<'a, T> {
 				return Err("module imports `seal_println` but debug features disabled");
 			}
 
+			if !T::ChainExtension::enabled() &&
+				import.field().as_bytes() == b"seal_call_chain_extension"
+			{
+				return Err("module uses chain extensions but chain extensions are disabled");
+			}
+
 			if import_fn_banlist.iter().any(|f| import.field().as_bytes() == *f)
 				|| !C::can_satisfy(import.field().as_bytes(), func_ty)
 			{
@@ -438,7 +444,7 @@ pub fn prepare_contract<C: ImportSatisfyCheck, T: Config>(
 		schedule_version: schedule.version,
 		initial: memory_limits.0,
 		maximum: memory_limits.1,
-		_reserved: None,
+		original_code_len: original_code.len() as u32,
 		code: contract_module.into_wasm_code()?,
 	})
 }
@@ -472,7 +478,7 @@ pub mod benchmarking {
 			schedule_version: schedule.version,
 			initial: memory_limits.0,
 			maximum: memory_limits.1,
-			_reserved: None,
+			original_code_len: original_code.len() as u32,
 			code: contract_module.into_wasm_code()?,
 		})
 	}
@@ -21,14 +21,18 @@ use crate::{
 	exec::{Ext, StorageKey, TopicOf},
 	gas::{Gas, GasMeter, Token, GasMeterResult},
 	wasm::env_def::ConvertibleToWasm,
+	chain_extension::{ChainExtension, Environment, RetVal},
 };
 use sp_sandbox;
 use parity_wasm::elements::ValueType;
 use frame_system;
-use frame_support::dispatch::DispatchError;
+use frame_support::{
+	dispatch::{DispatchError, GetDispatchInfo},
+	weights::{Weight, extract_actual_weight},
+};
 use sp_std::prelude::*;
 use codec::{Decode, Encode};
-use sp_runtime::traits::SaturatedConversion;
+use sp_runtime::traits::{Bounded, SaturatedConversion, Zero};
 use sp_core::crypto::UncheckedFrom;
 use sp_io::hashing::{
 	keccak_256,
@@ -36,7 +40,8 @@ use sp_io::hashing::{
 	blake2_128,
 	sha2_256,
 };
-use pallet_contracts_primitives::{ExecResult, ExecReturnValue, ReturnFlags, ExecError};
+use sp_core::{ed25519, sr25519};
+use pallet_contracts_primitives::{CallFlags, ExecResult, ExecReturnValue, ReturnFlags, ExecError};
 
 /// Every error that can be returned to a contract when it calls any of the host functions.
 #[repr(u32)]
@@ -62,9 +67,21 @@ pub enum ReturnCode {
 	NewContractNotFunded = 6,
 	/// No code could be found at the supplied code hash.
 	CodeNotFound = 7,
-	/// The contract that was called is either no contract at all (a plain account)
-	/// or is a tombstone.
+	/// The contract that was called is no contract at all (a plain account).
 	NotCallable = 8,
+	/// The call to `seal_debug_message` had no effect because debug message
+	/// recording was disabled.
+	LoggingDisabled = 9,
+	/// ECDSA public key recovery or conversion failed. Most probably the signature
+	/// or the public key is malformed.
+	EcdsaRecoverFailed = 10,
+	/// The sr25519 signature is not valid for the supplied message and public key.
+	Sr25519VerifyFailed = 11,
+	/// The ed25519 signature is not valid for the supplied message and public key.
+	Ed25519VerifyFailed = 12,
+	/// The call dispatched by `seal_call_runtime` was executed but returned an error
+	/// or was rejected by the call filter.
+	CallRuntimeReturnedError = 13,
 }
 
 impl ConvertibleToWasm for ReturnCode {
@@ -113,8 +130,6 @@ enum TrapReason {
 	/// Signals that a trap was generated in response to a successful call to the
 	/// `seal_terminate` host function.
 	Termination,
-	/// Signals that a trap was generated because of a successful restoration.
-	Restoration,
 }
 
 #[cfg_attr(test, derive(Debug, PartialEq, Eq))]
@@ -135,10 +150,6 @@ pub enum RuntimeToken {
 	ValueTransferred,
 	/// Weight of calling `seal_minimum_balance`.
 	MinimumBalance,
-	/// Weight of calling `seal_tombstone_deposit`.
-	TombstoneDeposit,
-	/// Weight of calling `seal_rent_allowance`.
-	RentAllowance,
 	/// Weight of calling `seal_block_number`.
 	BlockNumber,
 	/// Weight of calling `seal_now`.
@@ -153,14 +164,12 @@ pub enum RuntimeToken {
 	Return(u32),
 	/// Weight of calling `seal_terminate`.
 	Terminate,
-	/// Weight of calling `seal_restore_to` per number of supplied delta entries.
-	RestoreTo(u32),
 	/// Weight of calling `seal_random`. It includes the weight for copying the subject.
 	Random,
+	/// Weight of calling `seal_debug_message`.
+	DebugMessage,
 	/// Weight of calling `seal_reposit_event` with the given number of topics and event size.
 	DepositEvent{num_topic: u32, len: u32},
-	/// Weight of calling `seal_set_rent_allowance`.
-	SetRentAllowance,
 	/// Weight of calling `seal_set_storage` for the given storage item size.
 	SetStorage(u32),
 	/// Weight of calling `seal_clear_storage`.
@@ -173,6 +182,8 @@ pub enum RuntimeToken {
 	Transfer,
 	/// Weight of calling `seal_call` for the given input size.
 	CallBase(u32),
+	/// Weight of calling `seal_delegate_call` for the given input size.
+	DelegateCallBase(u32),
 	/// Weight of the transfer performed during a call.
 	CallSurchargeTransfer,
 	/// Weight of output received through `seal_call` for the given size.
@@ -191,6 +202,32 @@ pub enum RuntimeToken {
 	HashBlake256(u32),
 	/// Weight of calling `seal_hash_blake2_128` for the given input size.
 	HashBlake128(u32),
+	/// Weight of calling `seal_ecdsa_recover`.
+	EcdsaRecovery,
+	/// Weight of calling `seal_ecdsa_to_eth_address`.
+	EcdsaToEthAddress,
+	/// Weight of calling `seal_sr25519_verify` for the given message size.
+	Sr25519Verify(u32),
+	/// Weight of calling `seal_ed25519_verify` for the given message size.
+	Ed25519Verify(u32),
+	/// Weight of calling `seal_set_code_hash`.
+	SetCodeHash,
+	/// Weight of calling `seal_caller_is_origin`.
+	CallerIsOrigin,
+	/// Weight of calling `seal_code_hash`.
+	CodeHash,
+	/// Weight of calling `seal_own_code_hash`.
+	OwnCodeHash,
+	/// Weight of calling `seal_call_runtime` for the given call size without the
+	/// weight of the dispatched call.
+	CallRuntimeBase(u32),
+	/// Weight of the call dispatched by `seal_call_runtime` as declared by the runtime.
+	CallRuntimeDispatch(Weight),
+	/// Weight of calling `seal_call_chain_extension` without the weight charged by the
+	/// chain extension itself.
+	CallChainExtension,
+	/// Weight charged by a chain extension through its `Environment`.
+	ChainExtension(u64),
 }
 
 impl<T: Config> Token<T> for RuntimeToken
@@ -209,8 +246,6 @@ where
 			Balance => s.balance,
 			ValueTransferred => s.value_transferred,
 			MinimumBalance => s.minimum_balance,
-			TombstoneDeposit => s.tombstone_deposit,
-			RentAllowance => s.rent_allowance,
 			BlockNumber => s.block_number,
 			Now => s.now,
 			WeightToFee => s.weight_to_fee,
@@ -219,13 +254,11 @@ where
 			Return(len) => s.r#return
 				.saturating_add(s.return_per_byte.saturating_mul(len.into())),
 			Terminate => s.terminate,
-			RestoreTo(delta) => s.restore_to
-				.saturating_add(s.restore_to_per_delta.saturating_mul(delta.into())),
 			Random => s.random,
+			DebugMessage => s.debug_message,
 			DepositEvent{num_topic, len} => s.deposit_event
 				.saturating_add(s.deposit_event_per_topic.saturating_mul(num_topic.into()))
 				.saturating_add(s.deposit_event_per_byte.saturating_mul(len.into())),
-			SetRentAllowance => s.set_rent_allowance,
 			SetStorage(len) => s.set_storage
 				.saturating_add(s.set_storage_per_byte.saturating_mul(len.into())),
 			ClearStorage => s.clear_storage,
@@ -234,6 +267,8 @@ where
 			Transfer => s.transfer,
 			CallBase(len) => s.call
 				.saturating_add(s.call_per_input_byte.saturating_mul(len.into())),
+			DelegateCallBase(len) => s.delegate_call
+				.saturating_add(s.call_per_input_byte.saturating_mul(len.into())),
 			CallSurchargeTransfer => s.call_transfer_surcharge,
 			CallCopyOut(len) => s.call_per_output_byte.saturating_mul(len.into()),
 			InstantiateBase{input_data_len, salt_len} => s.instantiate
@@ -249,6 +284,21 @@ where
 				.saturating_add(s.hash_blake2_256_per_byte.saturating_mul(len.into())),
 			HashBlake128(len) => s.hash_blake2_128
 				.saturating_add(s.hash_blake2_128_per_byte.saturating_mul(len.into())),
+			EcdsaRecovery => s.ecdsa_recover,
+			EcdsaToEthAddress => s.ecdsa_to_eth_address,
+			Sr25519Verify(len) => s.sr25519_verify
+				.saturating_add(s.sr25519_verify_per_byte.saturating_mul(len.into())),
+			Ed25519Verify(len) => s.ed25519_verify
+				.saturating_add(s.ed25519_verify_per_byte.saturating_mul(len.into())),
+			SetCodeHash => s.set_code_hash,
+			CallerIsOrigin => s.caller_is_origin,
+			CodeHash => s.code_hash,
+			OwnCodeHash => s.own_code_hash,
+			CallRuntimeBase(len) => s.call_runtime
+				.saturating_add(s.call_per_input_byte.saturating_mul(len.into())),
+			CallRuntimeDispatch(weight) => weight,
+			CallChainExtension => s.call_chain_extension,
+			ChainExtension(amount) => amount,
 		}
 	}
 }
@@ -260,6 +310,18 @@ fn already_charged(_: u32) -> Option<RuntimeToken> {
 	None
 }
 
+/// Converts a compressed secp256k1 public key into the corresponding Ethereum address.
+///
+/// Returns `None` if `compressed_key` is not a valid public key.
+fn ecdsa_to_eth_address(compressed_key: &[u8; 33]) -> Option<[u8; 20]> {
+	let key = secp256k1::PublicKey::parse_compressed(compressed_key).ok()?;
+	// The first byte of the serialized uncompressed key is the `0x04` tag.
+	let hash = keccak_256(&key.serialize()[1..]);
+	let mut address = [0; 20];
+	address.copy_from_slice(&hash[12..]);
+	Some(address)
+}
+
 /// Finds duplicates in a given vector.
 ///
 /// This function has complexity of O(n log n) and no additional memory is required, although
@@ -278,6 +340,14 @@ fn has_duplicates<T: PartialEq + AsRef<[u8]>>(items: &mut Vec<T>) -> bool {
 	})
 }
 
+/// The kind of call that is performed by [`Runtime::call`].
+enum CallType {
+	/// Execute the code of another contract in its own context.
+	Call{callee_ptr: u32, callee_len: u32, value_ptr: u32, value_len: u32},
+	/// Execute the code stored under the given hash in the context of the current contract.
+	DelegateCall{code_hash_ptr: u32, code_hash_len: u32},
+}
+
 /// Can only be used for one call.
 pub struct Runtime<'a, E: Ext + 'a> {
 	ext: &'a mut E,
@@ -311,6 +381,23 @@ where
 		}
 	}
 
+	/// Grants access to the execution environment of the current contract call.
+	pub(crate) fn ext(&mut self) -> &mut E {
+		self.ext
+	}
+
+	/// Takes the error stored by a failed helper function of this type.
+	///
+	/// The helpers only return an opaque `HostError` to the sandbox. This is used to
+	/// hand out the actual `DispatchError` to callers outside of the host functions
+	/// like chain extensions.
+	pub(crate) fn take_supervisor_err(&mut self) -> DispatchError {
+		match self.trap_reason.take() {
+			Some(TrapReason::SupervisorError(err)) => err,
+			_ => Error::<E::T>::ContractTrapped.into(),
+		}
+	}
+
 	/// Converts the sandbox result and the runtime state into the execution outcome.
 	///
 	/// It evaluates information stored in the `trap_reason` variable of the runtime and
@@ -339,12 +426,6 @@ where
 						data: Vec::new(),
 					})
 				},
-				TrapReason::Restoration => {
-					Ok(ExecReturnValue {
-						flags: ReturnFlags::empty(),
-						data: Vec::new(),
-					})
-				},
 				TrapReason::SupervisorError(error) => Err(error)?,
 			}
 		}
@@ -372,7 +453,7 @@ where
 	/// Charge the gas meter with the specified token.
 	///
 	/// Returns `Err(HostError)` if there is not enough gas.
-	fn charge_gas<Tok>(&mut self, token: Tok) -> Result<(), sp_sandbox::HostError>
+	pub(crate) fn charge_gas<Tok>(&mut self, token: Tok) -> Result<(), sp_sandbox::HostError>
 	where
 		Tok: Token<E::T, Metadata=HostFnWeights<E::T>>,
 	{
@@ -392,7 +473,7 @@ where
 	/// Returns `Err` if one of the following conditions occurs:
 	///
 	/// - requested buffer is not within the bounds of the sandbox memory.
-	fn read_sandbox_memory(&mut self, ptr: u32, len: u32)
+	pub(crate) fn read_sandbox_memory(&mut self, ptr: u32, len: u32)
 	-> Result<Vec<u8>, sp_sandbox::HostError>
 	{
 		let mut buf = vec![0u8; len as usize];
@@ -418,7 +499,7 @@ where
 	///
 	/// - requested buffer is not within the bounds of the sandbox memory.
 	/// - the buffer contents cannot be decoded as the required type.
-	fn read_sandbox_memory_as<D: Decode>(&mut self, ptr: u32, len: u32)
+	pub(crate) fn read_sandbox_memory_as<D: Decode>(&mut self, ptr: u32, len: u32)
 	-> Result<D, sp_sandbox::HostError>
 	{
 		let buf = self.read_sandbox_memory(ptr, len)?;
@@ -453,7 +534,7 @@ where
 	///
 	/// In addition to the error conditions of `write_sandbox_memory` this functions returns
 	/// `Err` if the size of the buffer located at `out_ptr` is too small to fit `buf`.
-	fn write_sandbox_output(
+	pub(crate) fn write_sandbox_output(
 		&mut self,
 		out_ptr: u32,
 		out_len_ptr: u32,
@@ -517,6 +598,81 @@ where
 		Ok(())
 	}
 
+	/// Shared implementation of the host functions that call into other code.
+	///
+	/// The callees output buffer is copied to `output_ptr` and its length to `output_len_ptr`.
+	/// Unknown bits in `flags` lead to a trap of the caller.
+	fn call(
+		&mut self,
+		flags: u32,
+		call_type: CallType,
+		gas: u64,
+		input_data_ptr: u32,
+		input_data_len: u32,
+		output_ptr: u32,
+		output_len_ptr: u32,
+	) -> Result<ReturnCode, sp_sandbox::HostError> {
+		let flags = CallFlags::from_bits(flags)
+			.ok_or_else(|| self.store_err(Error::<E::T>::InvalidCallFlags))?;
+		let allows_reentry = flags.contains(CallFlags::ALLOW_REENTRY);
+
+		match call_type {
+			CallType::Call{..} => self.charge_gas(RuntimeToken::CallBase(input_data_len))?,
+			CallType::DelegateCall{..} =>
+				self.charge_gas(RuntimeToken::DelegateCallBase(input_data_len))?,
+		}
+		let input_data = self.read_sandbox_memory(input_data_ptr, input_data_len)?;
+
+		let nested_gas_limit = if gas == 0 {
+			self.gas_meter.gas_left()
+		} else {
+			gas.saturated_into()
+		};
+
+		let call_outcome = match call_type {
+			CallType::Call{callee_ptr, callee_len, value_ptr, value_len} => {
+				let callee: <<E as Ext>::T as frame_system::Config>::AccountId =
+					self.read_sandbox_memory_as(callee_ptr, callee_len)?;
+				let value: BalanceOf<<E as Ext>::T> =
+					self.read_sandbox_memory_as(value_ptr, value_len)?;
+				if value > 0u32.into() {
+					self.charge_gas(RuntimeToken::CallSurchargeTransfer)?;
+				}
+				let ext = &mut self.ext;
+				self.gas_meter.with_nested(nested_gas_limit, |nested_meter| {
+					match nested_meter {
+						Some(nested_meter) => {
+							ext.call(&callee, value, nested_meter, input_data, allows_reentry)
+						}
+						// there is not enough gas to allocate for the nested call.
+						None => Err(Error::<<E as Ext>::T>::OutOfGas.into()),
+					}
+				})
+			},
+			CallType::DelegateCall{code_hash_ptr, code_hash_len} => {
+				let code_hash: CodeHash<<E as Ext>::T> =
+					self.read_sandbox_memory_as(code_hash_ptr, code_hash_len)?;
+				let ext = &mut self.ext;
+				self.gas_meter.with_nested(nested_gas_limit, |nested_meter| {
+					match nested_meter {
+						Some(nested_meter) => {
+							ext.delegate_call(code_hash, nested_meter, input_data, allows_reentry)
+						}
+						// there is not enough gas to allocate for the nested call.
+						None => Err(Error::<<E as Ext>::T>::OutOfGas.into()),
+					}
+				})
+			},
+		};
+
+		if let Ok(output) = &call_outcome {
+			self.write_sandbox_output(output_ptr, output_len_ptr, &output.data, true, |len| {
+				Some(RuntimeToken::CallCopyOut(len))
+			})?;
+		}
+		self.map_exec_result(call_outcome)
+	}
+
 	/// Stores a DispatchError returned from an Ext function into the trap_reason.
 	///
 	/// This allows through supervisor generated errors to the caller.
@@ -724,6 +880,9 @@ define_env!(Env, <E: Ext>,
 	// The copy of the output buffer can be skipped by supplying the sentinel value
 	// of `u32::max_value()` to `output_ptr`.
 	//
+	// The callee is not allowed to call back into the current contract. Use
+	// `seal_call_with_flags` in order to allow reentrancy.
+	//
 	// # Parameters
 	//
 	// - callee_ptr: a pointer to the address of the callee contract.
@@ -761,43 +920,99 @@ define_env!(Env, <E: Ext>,
 		output_ptr: u32,
 		output_len_ptr: u32
 	) -> ReturnCode => {
-		ctx.charge_gas(RuntimeToken::CallBase(input_data_len))?;
-		let callee: <<E as Ext>::T as frame_system::Config>::AccountId =
-			ctx.read_sandbox_memory_as(callee_ptr, callee_len)?;
-		let value: BalanceOf<<E as Ext>::T> = ctx.read_sandbox_memory_as(value_ptr, value_len)?;
-		let input_data = ctx.read_sandbox_memory(input_data_ptr, input_data_len)?;
-
-		if value > 0u32.into() {
-			ctx.charge_gas(RuntimeToken::CallSurchargeTransfer)?;
-		}
+		ctx.call(
+			CallFlags::empty().bits(),
+			CallType::Call{callee_ptr, callee_len, value_ptr, value_len},
+			gas,
+			input_data_ptr,
+			input_data_len,
+			output_ptr,
+			output_len_ptr,
+		)
+	},
 
-		let nested_gas_limit = if gas == 0 {
-			ctx.gas_meter.gas_left()
-		} else {
-			gas.saturated_into()
-		};
-		let ext = &mut ctx.ext;
-		let call_outcome = ctx.gas_meter.with_nested(nested_gas_limit, |nested_meter| {
-			match nested_meter {
-				Some(nested_meter) => {
-					ext.call(
-						&callee,
-						value,
-						nested_meter,
-						input_data,
-					)
-				}
-				// there is not enough gas to allocate for the nested call.
-				None => Err(Error::<<E as Ext>::T>::OutOfGas.into()),
-			}
-		});
+	// Make a call to another contract with the supplied `CallFlags`.
+	//
+	// This is equivalent to `seal_call` except for the additional `flags` parameter.
+	//
+	// # Parameters
+	//
+	// - flags: See `CallFlags` for a documentation of the supported flags.
+	//   Traps if unknown bits are set.
+	//
+	// See `seal_call` for the remaining parameters and the possible errors.
+	seal_call_with_flags(
+		ctx,
+		flags: u32,
+		callee_ptr: u32,
+		callee_len: u32,
+		gas: u64,
+		value_ptr: u32,
+		value_len: u32,
+		input_data_ptr: u32,
+		input_data_len: u32,
+		output_ptr: u32,
+		output_len_ptr: u32
+	) -> ReturnCode => {
+		ctx.call(
+			flags,
+			CallType::Call{callee_ptr, callee_len, value_ptr, value_len},
+			gas,
+			input_data_ptr,
+			input_data_len,
+			output_ptr,
+			output_len_ptr,
+		)
+	},
 
-		if let Ok(output) = &call_outcome {
-			ctx.write_sandbox_output(output_ptr, output_len_ptr, &output.data, true, |len| {
-				Some(RuntimeToken::CallCopyOut(len))
-			})?;
-		}
-		ctx.map_exec_result(call_outcome)
+	// Execute code in the context (storage, caller, value) of the current contract.
+	//
+	// The executed code sees the same address, caller, value and storage as the current
+	// contract. No value is transferred. Only code trusted by the current contract should
+	// be executed this way because it has full access to its storage and balance.
+	//
+	// # Parameters
+	//
+	// - flags: See `CallFlags` for a documentation of the supported flags.
+	//   Traps if unknown bits are set.
+	// - code_hash_ptr: a pointer to the hash of the code that should be executed.
+	//   Should be decodable as a `T::Hash`. Traps otherwise.
+	// - code_hash_len: length of the code hash buffer.
+	// - gas: how much gas to devote to the execution.
+	// - input_data_ptr: a pointer to a buffer to be used as input data to the callee.
+	// - input_data_len: length of the input data buffer.
+	// - output_ptr: a pointer where the output buffer is copied to.
+	// - output_len_ptr: in-out pointer to where the length of the buffer is read from
+	//   and the actual length is written to.
+	//
+	// # Errors
+	//
+	// An error means that the call wasn't successful and no output buffer is returned unless
+	// stated otherwise.
+	//
+	// `ReturnCode::CalleeReverted`: Output buffer is returned.
+	// `ReturnCode::CalleeTrapped`
+	// `ReturnCode::CodeNotFound`
+	seal_delegate_call(
+		ctx,
+		flags: u32,
+		code_hash_ptr: u32,
+		code_hash_len: u32,
+		gas: u64,
+		input_data_ptr: u32,
+		input_data_len: u32,
+		output_ptr: u32,
+		output_len_ptr: u32
+	) -> ReturnCode => {
+		ctx.call(
+			flags,
+			CallType::DelegateCall{code_hash_ptr, code_hash_len},
+			gas,
+			input_data_ptr,
+			input_data_len,
+			output_ptr,
+			output_len_ptr,
+		)
 	},
 
 	// Instantiate a contract with the specified code hash.
@@ -989,6 +1204,87 @@ define_env!(Env, <E: Ext>,
 		)
 	},
 
+	// Checks whether the caller of the current contract is the origin of the whole call stack.
+	//
+	// Prefer this over `seal_caller` when checking whether your contract is being called by
+	// a contract or a plain account. The reason is that it performs better since it does not
+	// need to do any storage lookups.
+	//
+	// A return value of `1` means that the caller is the origin. `0` is returned otherwise.
+	seal_caller_is_origin(ctx) -> u32 => {
+		ctx.charge_gas(RuntimeToken::CallerIsOrigin)?;
+		Ok(ctx.ext.caller_is_origin() as u32)
+	},
+
+	// Retrieve the code hash for a specified contract address.
+	//
+	// # Parameters
+	//
+	// - `account_ptr`: a pointer to the address in question.
+	//   Should be decodable as an `T::AccountId`. Traps otherwise.
+	// - `account_len`: length of the address buffer.
+	// - `out_ptr`: pointer to the linear memory where the returning value is written to.
+	// - `out_len_ptr`: in-out pointer into linear memory where the buffer length
+	//   is read from and the value length is written to.
+	//
+	// # Errors
+	//
+	// `ReturnCode::KeyNotFound`
+	seal_code_hash(
+		ctx,
+		account_ptr: u32,
+		account_len: u32,
+		out_ptr: u32,
+		out_len_ptr: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::CodeHash)?;
+		let address: <<E as Ext>::T as frame_system::Config>::AccountId =
+			ctx.read_sandbox_memory_as(account_ptr, account_len)?;
+		if let Some(value) = ctx.ext.code_hash(&address) {
+			ctx.write_sandbox_output(out_ptr, out_len_ptr, &value.encode(), false, already_charged)?;
+			Ok(ReturnCode::Success)
+		} else {
+			Ok(ReturnCode::KeyNotFound)
+		}
+	},
+
+	// Retrieve the code hash of the currently executing contract.
+	//
+	// The value is stored to linear memory at the address pointed to by `out_ptr`.
+	// `out_len_ptr` must point to a u32 value that describes the available space at
+	// `out_ptr`. This call overwrites it with the size of the value. If the available
+	// space at `out_ptr` is less than the size of the value a trap is triggered.
+	//
+	// During a delegate call this is the code hash of the contract whose context is used
+	// and not the hash of the code that is executing.
+	seal_own_code_hash(ctx, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::OwnCodeHash)?;
+		let code_hash_encoded = &ctx.ext.own_code_hash().encode();
+		ctx.write_sandbox_output(out_ptr, out_len_ptr, code_hash_encoded, false, already_charged)
+	},
+
+	// Replace the contract code at the specified address with new code.
+	//
+	// The code stored under `code_hash` is used for all calls into the contract that
+	// start after this call returns. The currently executing code is not affected.
+	//
+	// # Parameters
+	//
+	// - code_hash_ptr: A pointer to the buffer that contains the new code hash.
+	//   Should be decodable as a `T::Hash`. Traps otherwise.
+	// - code_hash_len: length of the code hash buffer.
+	//
+	// # Errors
+	//
+	// `ReturnCode::CodeNotFound`
+	seal_set_code_hash(ctx, code_hash_ptr: u32, code_hash_len: u32) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::SetCodeHash)?;
+		let code_hash: CodeHash<<E as Ext>::T> =
+			ctx.read_sandbox_memory_as(code_hash_ptr, code_hash_len)?;
+		let result = ctx.ext.set_code_hash(code_hash);
+		ctx.map_dispatch_result(result)
+	},
+
 	// Stores the address of the current contract into the supplied buffer.
 	//
 	// The value is stored to linear memory at the address pointed to by `out_ptr`.
@@ -1111,99 +1407,35 @@ define_env!(Env, <E: Ext>,
 
 	// Stores the tombstone deposit into the supplied buffer.
 	//
-	// The value is stored to linear memory at the address pointed to by `out_ptr`.
-	// `out_len_ptr` must point to a u32 value that describes the available space at
-	// `out_ptr`. This call overwrites it with the size of the value. If the available
-	// space at `out_ptr` is less than the size of the value a trap is triggered.
-	//
-	// The data is encoded as T::Balance.
+	// # Deprecated
 	//
-	// # Note
-	//
-	// The tombstone deposit is on top of the existential deposit. So in order for
-	// a contract to leave a tombstone the balance of the contract must not go
-	// below the sum of existential deposit and the tombstone deposit. The sum
-	// is commonly referred as subsistence threshold in code.
+	// Contracts no longer leave a tombstone behind. This function only exists for
+	// backwards compatibility and always stores zero encoded as T::Balance.
 	seal_tombstone_deposit(ctx, out_ptr: u32, out_len_ptr: u32) => {
-		ctx.charge_gas(RuntimeToken::TombstoneDeposit)?;
-		ctx.write_sandbox_output(
-			out_ptr, out_len_ptr, &ctx.ext.tombstone_deposit().encode(), false, already_charged
-		)
+		ctx.charge_gas(RuntimeToken::Balance)?;
+		let deposit = <BalanceOf<E::T>>::zero().encode();
+		ctx.write_sandbox_output(out_ptr, out_len_ptr, &deposit, false, already_charged)
 	},
 
-	// Try to restore the given destination contract sacrificing the caller.
-	//
-	// This function will compute a tombstone hash from the caller's storage and the given code hash
-	// and if the hash matches the hash found in the tombstone at the specified address - kill
-	// the caller contract and restore the destination contract and set the specified `rent_allowance`.
-	// All caller's funds are transfered to the destination.
-	//
-	// If there is no tombstone at the destination address, the hashes don't match or this contract
-	// instance is already present on the contract call stack, a trap is generated.
-	//
-	// Otherwise, the destination contract is restored. This function is diverging and stops execution
-	// even on success.
+	// Was used to restore the given destination contract sacrificing the caller.
 	//
-	// `dest_ptr`, `dest_len` - the pointer and the length of a buffer that encodes `T::AccountId`
-	// with the address of the to be restored contract.
-	// `code_hash_ptr`, `code_hash_len` - the pointer and the length of a buffer that encodes
-	// a code hash of the to be restored contract.
-	// `rent_allowance_ptr`, `rent_allowance_len` - the pointer and the length of a buffer that
-	// encodes the rent allowance that must be set in the case of successful restoration.
-	// `delta_ptr` is the pointer to the start of a buffer that has `delta_count` storage keys
-	// laid out sequentially.
+	// # Deprecated
 	//
-	// # Traps
-	//
-	// - Tombstone hashes do not match
-	// - Calling cantract is live i.e is already on the call stack.
+	// Contracts can no longer be evicted and therefore never need to be restored. This
+	// function only exists for backwards compatibility and does nothing.
 	seal_restore_to(
 		ctx,
-		dest_ptr: u32,
-		dest_len: u32,
-		code_hash_ptr: u32,
-		code_hash_len: u32,
-		rent_allowance_ptr: u32,
-		rent_allowance_len: u32,
-		delta_ptr: u32,
-		delta_count: u32
+		_dest_ptr: u32,
+		_dest_len: u32,
+		_code_hash_ptr: u32,
+		_code_hash_len: u32,
+		_rent_allowance_ptr: u32,
+		_rent_allowance_len: u32,
+		_delta_ptr: u32,
+		_delta_count: u32
 	) => {
-		ctx.charge_gas(RuntimeToken::RestoreTo(delta_count))?;
-		let dest: <<E as Ext>::T as frame_system::Config>::AccountId =
-			ctx.read_sandbox_memory_as(dest_ptr, dest_len)?;
-		let code_hash: CodeHash<<E as Ext>::T> =
-			ctx.read_sandbox_memory_as(code_hash_ptr, code_hash_len)?;
-		let rent_allowance: BalanceOf<<E as Ext>::T> =
-			ctx.read_sandbox_memory_as(rent_allowance_ptr, rent_allowance_len)?;
-		let delta = {
-			// We can eagerly allocate because we charged for the complete delta count already
-			let mut delta = Vec::with_capacity(delta_count as usize);
-			let mut key_ptr = delta_ptr;
-
-			for _ in 0..delta_count {
-				const KEY_SIZE: usize = 32;
-
-				// Read the delta into the provided buffer and collect it into the buffer.
-				let mut delta_key: StorageKey = [0; KEY_SIZE];
-				ctx.read_sandbox_memory_into_buf(key_ptr, &mut delta_key)?;
-				delta.push(delta_key);
-
-				// Offset key_ptr to the next element.
-				key_ptr = key_ptr.checked_add(KEY_SIZE as u32).ok_or_else(|| sp_sandbox::HostError)?;
-			}
-
-			delta
-		};
-
-		if let Ok(()) = ctx.ext.restore_to(
-			dest,
-			code_hash,
-			rent_allowance,
-			delta,
-		).map_err(|e| ctx.store_err(e)) {
-			ctx.trap_reason = Some(TrapReason::Restoration);
-		}
-		Err(sp_sandbox::HostError)
+		ctx.charge_gas(RuntimeToken::DebugMessage)?;
+		Ok(())
 	},
 
 	// Deposit a contract event with the data buffer and optional list of topics. There is a limit
@@ -1248,33 +1480,27 @@ define_env!(Env, <E: Ext>,
 		Ok(())
 	},
 
-	// Set rent allowance of the contract
+	// Was used to set the rent allowance of the contract.
 	//
-	// - value_ptr: a pointer to the buffer with value, how much to allow for rent
-	//   Should be decodable as a `T::Balance`. Traps otherwise.
-	// - value_len: length of the value buffer.
-	seal_set_rent_allowance(ctx, value_ptr: u32, value_len: u32) => {
-		ctx.charge_gas(RuntimeToken::SetRentAllowance)?;
-		let value: BalanceOf<<E as Ext>::T> =
-			ctx.read_sandbox_memory_as(value_ptr, value_len)?;
-		ctx.ext.set_rent_allowance(value);
-
+	// # Deprecated
+	//
+	// Contracts no longer pay rent. This function only exists for backwards
+	// compatibility and does nothing.
+	seal_set_rent_allowance(ctx, _value_ptr: u32, _value_len: u32) => {
+		ctx.charge_gas(RuntimeToken::DebugMessage)?;
 		Ok(())
 	},
 
-	// Stores the rent allowance into the supplied buffer.
+	// Was used to store the rent allowance into the supplied buffer.
 	//
-	// The value is stored to linear memory at the address pointed to by `out_ptr`.
-	// `out_len_ptr` must point to a u32 value that describes the available space at
-	// `out_ptr`. This call overwrites it with the size of the value. If the available
-	// space at `out_ptr` is less than the size of the value a trap is triggered.
+	// # Deprecated
 	//
-	// The data is encoded as T::Balance.
+	// Contracts no longer pay rent. This function only exists for backwards
+	// compatibility and always stores the maximum value encoded as T::Balance.
 	seal_rent_allowance(ctx, out_ptr: u32, out_len_ptr: u32) => {
-		ctx.charge_gas(RuntimeToken::RentAllowance)?;
-		ctx.write_sandbox_output(
-			out_ptr, out_len_ptr, &ctx.ext.rent_allowance().encode(), false, already_charged
-		)
+		ctx.charge_gas(RuntimeToken::Balance)?;
+		let rent_allowance = <BalanceOf<E::T>>::max_value().encode();
+		ctx.write_sandbox_output(out_ptr, out_len_ptr, &rent_allowance, false, already_charged)
 	},
 
 	// Prints utf8 encoded string from the data buffer.
@@ -1288,6 +1514,35 @@ define_env!(Env, <E: Ext>,
 		Ok(())
 	},
 
+	// Emit a custom debug message.
+	//
+	// No newlines are added to the supplied message.
+	// Specifying invalid UTF-8 triggers a trap.
+	//
+	// This is a no-op if debug message recording is disabled which is always the case
+	// when the code is executing on-chain. The message is interpreted as UTF-8 and
+	// appended to the debug output buffer which is then supplied to the calling RPC client.
+	//
+	// # Note
+	//
+	// Even though no action is taken when debug message recording is disabled there is still
+	// a non trivial overhead (and weight cost) associated with calling this function. Contract
+	// languages should remove calls to this function (either at runtime or compile time) when
+	// not being executed as an RPC. For example, they could allow users to disable logging
+	// through compile time flags (cargo features) for on-chain deployment. Additionally, the
+	// return value of this function can be cached in order to prevent further calls at runtime.
+	seal_debug_message(ctx, str_ptr: u32, str_len: u32) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::DebugMessage)?;
+		if ctx.ext.append_debug_buffer("") {
+			let data = ctx.read_sandbox_memory(str_ptr, str_len)?;
+			let msg = core::str::from_utf8(&data)
+				.map_err(|_| ctx.store_err(Error::<E::T>::DebugMessageInvalidUTF8))?;
+			ctx.ext.append_debug_buffer(msg);
+			return Ok(ReturnCode::Success);
+		}
+		Ok(ReturnCode::LoggingDisabled)
+	},
+
 	// Stores the current block number of the current contract into the supplied buffer.
 	//
 	// The value is stored to linear memory at the address pointed to by `out_ptr`.
@@ -1400,4 +1655,226 @@ define_env!(Env, <E: Ext>,
 		ctx.charge_gas(RuntimeToken::HashBlake128(input_len))?;
 		ctx.compute_hash_on_intermediate_buffer(blake2_128, input_ptr, input_len, output_ptr)
 	},
+
+	// Recover the ECDSA public key from the given message hash and signature.
+	//
+	// Writes the public key into the given output buffer.
+	// Assumes the secp256k1 curve.
+	//
+	// # Parameters
+	//
+	// - `signature_ptr`: the pointer into the linear memory where the signature
+	//                    is placed. Should be decodable as a 65 bytes. Traps otherwise.
+	// - `message_hash_ptr`: the pointer into the linear memory where the message
+	//                       hash is placed. Should be decodable as a 32 bytes. Traps otherwise.
+	// - `output_ptr`: the pointer into the linear memory where the output
+	//                 data is placed. The buffer should be 33 bytes. The function
+	//                 will write the result directly into this buffer.
+	//
+	// # Errors
+	//
+	// `ReturnCode::EcdsaRecoverFailed`
+	seal_ecdsa_recover(
+		ctx,
+		signature_ptr: u32,
+		message_hash_ptr: u32,
+		output_ptr: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::EcdsaRecovery)?;
+		let mut signature: [u8; 65] = [0; 65];
+		ctx.read_sandbox_memory_into_buf(signature_ptr, &mut signature)?;
+		let mut message_hash: [u8; 32] = [0; 32];
+		ctx.read_sandbox_memory_into_buf(message_hash_ptr, &mut message_hash)?;
+
+		match sp_io::crypto::secp256k1_ecdsa_recover_compressed(&signature, &message_hash) {
+			Ok(pub_key) => {
+				// Write the recovered compressed ecdsa public key back into the sandboxed output
+				// buffer.
+				ctx.write_sandbox_memory(output_ptr, pub_key.as_ref())?;
+				Ok(ReturnCode::Success)
+			},
+			Err(_) => Ok(ReturnCode::EcdsaRecoverFailed),
+		}
+	},
+
+	// Calculate the Ethereum address from a compressed ECDSA public key.
+	//
+	// The address consists of the last 20 bytes of the keccak-256 hash of the
+	// uncompressed public key (without the leading `0x04` tag).
+	//
+	// # Parameters
+	//
+	// - `key_ptr`: the pointer into the linear memory where the 33 bytes compressed
+	//              public key is placed.
+	// - `out_ptr`: the pointer into the linear memory where the output data is placed.
+	//              The buffer should be 20 bytes. The function will write the result
+	//              directly into this buffer.
+	//
+	// # Errors
+	//
+	// `ReturnCode::EcdsaRecoverFailed`
+	seal_ecdsa_to_eth_address(ctx, key_ptr: u32, out_ptr: u32) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::EcdsaToEthAddress)?;
+		let mut compressed_key: [u8; 33] = [0; 33];
+		ctx.read_sandbox_memory_into_buf(key_ptr, &mut compressed_key)?;
+		match ecdsa_to_eth_address(&compressed_key) {
+			Some(address) => {
+				ctx.write_sandbox_memory(out_ptr, address.as_ref())?;
+				Ok(ReturnCode::Success)
+			},
+			None => Ok(ReturnCode::EcdsaRecoverFailed),
+		}
+	},
+
+	// Verify a sr25519 signature.
+	//
+	// # Parameters
+	//
+	// - `signature_ptr`: the pointer into the linear memory where the 64 bytes signature
+	//                    is placed.
+	// - `pub_key_ptr`: the pointer into the linear memory where the 32 bytes public key
+	//                  is placed.
+	// - `message_len`: the length of the message payload.
+	// - `message_ptr`: the pointer into the linear memory where the message is placed.
+	//
+	// # Errors
+	//
+	// `ReturnCode::Sr25519VerifyFailed`
+	seal_sr25519_verify(
+		ctx,
+		signature_ptr: u32,
+		pub_key_ptr: u32,
+		message_len: u32,
+		message_ptr: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::Sr25519Verify(message_len))?;
+		let mut signature: [u8; 64] = [0; 64];
+		ctx.read_sandbox_memory_into_buf(signature_ptr, &mut signature)?;
+		let mut pub_key: [u8; 32] = [0; 32];
+		ctx.read_sandbox_memory_into_buf(pub_key_ptr, &mut pub_key)?;
+		let message = ctx.read_sandbox_memory(message_ptr, message_len)?;
+
+		if sp_io::crypto::sr25519_verify(
+			&sr25519::Signature::from_raw(signature),
+			&message,
+			&sr25519::Public::from_raw(pub_key),
+		) {
+			Ok(ReturnCode::Success)
+		} else {
+			Ok(ReturnCode::Sr25519VerifyFailed)
+		}
+	},
+
+	// Verify an ed25519 signature.
+	//
+	// # Parameters
+	//
+	// - `signature_ptr`: the pointer into the linear memory where the 64 bytes signature
+	//                    is placed.
+	// - `pub_key_ptr`: the pointer into the linear memory where the 32 bytes public key
+	//                  is placed.
+	// - `message_len`: the length of the message payload.
+	// - `message_ptr`: the pointer into the linear memory where the message is placed.
+	//
+	// # Errors
+	//
+	// `ReturnCode::Ed25519VerifyFailed`
+	seal_ed25519_verify(
+		ctx,
+		signature_ptr: u32,
+		pub_key_ptr: u32,
+		message_len: u32,
+		message_ptr: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::Ed25519Verify(message_len))?;
+		let mut signature: [u8; 64] = [0; 64];
+		ctx.read_sandbox_memory_into_buf(signature_ptr, &mut signature)?;
+		let mut pub_key: [u8; 32] = [0; 32];
+		ctx.read_sandbox_memory_into_buf(pub_key_ptr, &mut pub_key)?;
+		let message = ctx.read_sandbox_memory(message_ptr, message_len)?;
+
+		if sp_io::crypto::ed25519_verify(
+			&ed25519::Signature::from_raw(signature),
+			&message,
+			&ed25519::Public::from_raw(pub_key),
+		) {
+			Ok(ReturnCode::Success)
+		} else {
+			Ok(ReturnCode::Ed25519VerifyFailed)
+		}
+	},
+
+	// Call some dispatchable of the runtime.
+	//
+	// The passed in data is decoded as the overarching `Call` type of the runtime and
+	// dispatched with the current contract as signed origin. The weight declared by
+	// the dispatchable is charged upfront and whatever the dispatchable reports as
+	// unused weight is refunded afterwards.
+	//
+	// The call is subject to the `CallFilter` of the contracts module. The filter is
+	// also applied to every call that is dispatched by the call itself.
+	//
+	// # Parameters
+	//
+	// - `call_ptr`: the pointer into the linear memory where the encoded call is placed.
+	// - `call_len`: the length of the encoded call in bytes.
+	//
+	// # Traps
+	//
+	// - The supplied data cannot be decoded as a runtime call.
+	// - There is not enough gas left to pay for the declared weight of the call.
+	//
+	// # Errors
+	//
+	// `ReturnCode::CallRuntimeReturnedError`
+	seal_call_runtime(ctx, call_ptr: u32, call_len: u32) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::CallRuntimeBase(call_len))?;
+		let call: <E::T as Config>::Call = ctx.read_sandbox_memory_as(call_ptr, call_len)?;
+		let dispatch_info = call.get_dispatch_info();
+		ctx.charge_gas(RuntimeToken::CallRuntimeDispatch(dispatch_info.weight))?;
+		let result = ctx.ext.call_runtime(call);
+		let actual_weight = extract_actual_weight(&result, &dispatch_info);
+		ctx.gas_meter.refund(dispatch_info.weight.saturating_sub(actual_weight));
+		match result {
+			Ok(_) => Ok(ReturnCode::Success),
+			Err(_) => Ok(ReturnCode::CallRuntimeReturnedError),
+		}
+	},
+
+	// Call into the chain extension provided by the chain if any.
+	//
+	// Handling of the input values is up to the specific chain extension and so is the
+	// return value. The extension can decide to use the inputs as primitive inputs or as
+	// in/out arguments by interpreting them as pointers. Any caller of this function
+	// must therefore coordinate with the chain that it targets.
+	//
+	// # Note
+	//
+	// If no chain extension exists the contract will trap with the `NoChainExtension`
+	// module error.
+	seal_call_chain_extension(
+		ctx,
+		func_id: u32,
+		input_ptr: u32,
+		input_len: u32,
+		output_ptr: u32,
+		output_len_ptr: u32
+	) -> u32 => {
+		ctx.charge_gas(RuntimeToken::CallChainExtension)?;
+		if !<E::T as Config>::ChainExtension::enabled() {
+			Err(ctx.store_err(Error::<E::T>::NoChainExtension))?;
+		}
+		let env = Environment::new(ctx, func_id, input_ptr, input_len, output_ptr, output_len_ptr);
+		match <E::T as Config>::ChainExtension::call(func_id, env) {
+			Ok(RetVal::Converging(val)) => Ok(val),
+			Ok(RetVal::Diverging{flags, data}) => {
+				ctx.trap_reason = Some(TrapReason::Return(ReturnData {
+					flags: flags.bits(),
+					data,
+				}));
+				Err(sp_sandbox::HostError)
+			},
+			Err(err) => Err(ctx.store_err(err)),
+		}
+	},
 );
@@ -45,17 +45,18 @@ use sp_std::marker::PhantomData;
 pub trait WeightInfo {
 	fn update_schedule() -> Weight;
 	fn put_code(n: u32, ) -> Weight;
+	fn code_load(c: u32, ) -> Weight;
+	fn reinstrument(c: u32, ) -> Weight;
 	fn instantiate(n: u32, s: u32, ) -> Weight;
 	fn call() -> Weight;
-	fn claim_surcharge() -> Weight;
+	fn remove_code() -> Weight;
 	fn seal_caller(r: u32, ) -> Weight;
+	fn seal_caller_is_origin(r: u32, ) -> Weight;
 	fn seal_address(r: u32, ) -> Weight;
 	fn seal_gas_left(r: u32, ) -> Weight;
 	fn seal_balance(r: u32, ) -> Weight;
 	fn seal_value_transferred(r: u32, ) -> Weight;
 	fn seal_minimum_balance(r: u32, ) -> Weight;
-	fn seal_tombstone_deposit(r: u32, ) -> Weight;
-	fn seal_rent_allowance(r: u32, ) -> Weight;
 	fn seal_block_number(r: u32, ) -> Weight;
 	fn seal_now(r: u32, ) -> Weight;
 	fn seal_weight_to_fee(r: u32, ) -> Weight;
@@ -65,12 +66,10 @@ pub trait WeightInfo {
 	fn seal_return(r: u32, ) -> Weight;
 	fn seal_return_per_kb(n: u32, ) -> Weight;
 	fn seal_terminate(r: u32, ) -> Weight;
-	fn seal_restore_to(r: u32, ) -> Weight;
-	fn seal_restore_to_per_delta(d: u32, ) -> Weight;
 	fn seal_random(r: u32, ) -> Weight;
+	fn seal_debug_message(r: u32, ) -> Weight;
 	fn seal_deposit_event(r: u32, ) -> Weight;
 	fn seal_deposit_event_per_topic_and_kb(t: u32, n: u32, ) -> Weight;
-	fn seal_set_rent_allowance(r: u32, ) -> Weight;
 	fn seal_set_storage(r: u32, ) -> Weight;
 	fn seal_set_storage_per_kb(n: u32, ) -> Weight;
 	fn seal_clear_storage(r: u32, ) -> Weight;
@@ -79,8 +78,14 @@ pub trait WeightInfo {
 	fn seal_transfer(r: u32, ) -> Weight;
 	fn seal_call(r: u32, ) -> Weight;
 	fn seal_call_per_transfer_input_output_kb(t: u32, i: u32, o: u32, ) -> Weight;
+	fn seal_delegate_call(r: u32, ) -> Weight;
 	fn seal_instantiate(r: u32, ) -> Weight;
 	fn seal_instantiate_per_input_output_salt_kb(i: u32, o: u32, s: u32, ) -> Weight;
+	fn seal_set_code_hash(r: u32, ) -> Weight;
+	fn seal_code_hash(r: u32, ) -> Weight;
+	fn seal_own_code_hash(r: u32, ) -> Weight;
+	fn seal_call_runtime(r: u32, ) -> Weight;
+	fn seal_call_chain_extension(r: u32, ) -> Weight;
 	fn seal_hash_sha2_256(r: u32, ) -> Weight;
 	fn seal_hash_sha2_256_per_kb(n: u32, ) -> Weight;
 	fn seal_hash_keccak_256(r: u32, ) -> Weight;
@@ -89,6 +94,12 @@ pub trait WeightInfo {
 	fn seal_hash_blake2_256_per_kb(n: u32, ) -> Weight;
 	fn seal_hash_blake2_128(r: u32, ) -> Weight;
 	fn seal_hash_blake2_128_per_kb(n: u32, ) -> Weight;
+	fn seal_ecdsa_recover(r: u32, ) -> Weight;
+	fn seal_ecdsa_to_eth_address(r: u32, ) -> Weight;
+	fn seal_sr25519_verify(r: u32, ) -> Weight;
+	fn seal_sr25519_verify_per_kb(n: u32, ) -> Weight;
+	fn seal_ed25519_verify(r: u32, ) -> Weight;
+	fn seal_ed25519_verify_per_kb(n: u32, ) -> Weight;
 	fn instr_i64const(r: u32, ) -> Weight;
 	fn instr_i64load(r: u32, ) -> Weight;
 	fn instr_i64store(r: u32, ) -> Weight;
@@ -156,6 +167,17 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(1 as Weight))
 			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 	}
+	fn code_load(c: u32, ) -> Weight {
+		(12_355_000 as Weight)
+			.saturating_add((4_322_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+	}
+	fn reinstrument(c: u32, ) -> Weight {
+		(18_617_000 as Weight)
+			.saturating_add((101_375_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
 	fn instantiate(n: u32, s: u32, ) -> Weight {
 		(195_276_000 as Weight)
 			.saturating_add((35_000 as Weight).saturating_mul(n as Weight))
@@ -168,16 +190,21 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(5 as Weight))
 			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 	}
-	fn claim_surcharge() -> Weight {
-		(489_633_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(3 as Weight))
-			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	fn remove_code() -> Weight {
+		(40_672_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
 	}
 	fn seal_caller(r: u32, ) -> Weight {
 		(136_550_000 as Weight)
 			.saturating_add((373_182_000 as Weight).saturating_mul(r as Weight))
 			.saturating_add(T::DbWeight::get().reads(4 as Weight))
 	}
+	fn seal_caller_is_origin(r: u32, ) -> Weight {
+		(134_880_000 as Weight)
+			.saturating_add((148_627_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
 	fn seal_address(r: u32, ) -> Weight {
 		(136_329_000 as Weight)
 			.saturating_add((373_392_000 as Weight).saturating_mul(r as Weight))
@@ -203,16 +230,6 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add((370_036_000 as Weight).saturating_mul(r as Weight))
 			.saturating_add(T::DbWeight::get().reads(4 as Weight))
 	}
-	fn seal_tombstone_deposit(r: u32, ) -> Weight {
-		(138_568_000 as Weight)
-			.saturating_add((370_322_000 as Weight).saturating_mul(r as Weight))
-			.saturating_add(T::DbWeight::get().reads(4 as Weight))
-	}
-	fn seal_rent_allowance(r: u32, ) -> Weight {
-		(144_431_000 as Weight)
-			.saturating_add((851_810_000 as Weight).saturating_mul(r as Weight))
-			.saturating_add(T::DbWeight::get().reads(4 as Weight))
-	}
 	fn seal_block_number(r: u32, ) -> Weight {
 		(133_237_000 as Weight)
 			.saturating_add((369_156_000 as Weight).saturating_mul(r as Weight))
@@ -260,26 +277,16 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(r as Weight)))
 			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(r as Weight)))
 	}
-	fn seal_restore_to(r: u32, ) -> Weight {
-		(233_645_000 as Weight)
-			.saturating_add((135_355_000 as Weight).saturating_mul(r as Weight))
-			.saturating_add(T::DbWeight::get().reads(5 as Weight))
-			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(r as Weight)))
-			.saturating_add(T::DbWeight::get().writes((4 as Weight).saturating_mul(r as Weight)))
-	}
-	fn seal_restore_to_per_delta(d: u32, ) -> Weight {
-		(74_573_000 as Weight)
-			.saturating_add((3_768_682_000 as Weight).saturating_mul(d as Weight))
-			.saturating_add(T::DbWeight::get().reads(7 as Weight))
-			.saturating_add(T::DbWeight::get().reads((100 as Weight).saturating_mul(d as Weight)))
-			.saturating_add(T::DbWeight::get().writes(5 as Weight))
-			.saturating_add(T::DbWeight::get().writes((100 as Weight).saturating_mul(d as Weight)))
-	}
 	fn seal_random(r: u32, ) -> Weight {
 		(140_286_000 as Weight)
 			.saturating_add((950_890_000 as Weight).saturating_mul(r as Weight))
 			.saturating_add(T::DbWeight::get().reads(5 as Weight))
 	}
+	fn seal_debug_message(r: u32, ) -> Weight {
+		(137_625_000 as Weight)
+			.saturating_add((181_523_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
 	fn seal_deposit_event(r: u32, ) -> Weight {
 		(167_735_000 as Weight)
 			.saturating_add((1_375_429_000 as Weight).saturating_mul(r as Weight))
@@ -293,12 +300,6 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads((100 as Weight).saturating_mul(t as Weight)))
 			.saturating_add(T::DbWeight::get().writes((100 as Weight).saturating_mul(t as Weight)))
 	}
-	fn seal_set_rent_allowance(r: u32, ) -> Weight {
-		(156_911_000 as Weight)
-			.saturating_add((1_006_139_000 as Weight).saturating_mul(r as Weight))
-			.saturating_add(T::DbWeight::get().reads(4 as Weight))
-			.saturating_add(T::DbWeight::get().writes(1 as Weight))
-	}
 	fn seal_set_storage(r: u32, ) -> Weight {
 		(0 as Weight)
 			.saturating_add((14_938_793_000 as Weight).saturating_mul(r as Weight))
@@ -355,6 +356,12 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads((101 as Weight).saturating_mul(t as Weight)))
 			.saturating_add(T::DbWeight::get().writes((101 as Weight).saturating_mul(t as Weight)))
 	}
+	fn seal_delegate_call(r: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((10_201_540_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+	}
 	fn seal_instantiate(r: u32, ) -> Weight {
 		(0 as Weight)
 			.saturating_add((22_365_908_000 as Weight).saturating_mul(r as Weight))
@@ -371,6 +378,35 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(207 as Weight))
 			.saturating_add(T::DbWeight::get().writes(202 as Weight))
 	}
+	fn seal_set_code_hash(r: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((4_113_624_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads((200 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((200 as Weight).saturating_mul(r as Weight)))
+	}
+	fn seal_code_hash(r: u32, ) -> Weight {
+		(138_102_000 as Weight)
+			.saturating_add((2_263_891_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+	}
+	fn seal_own_code_hash(r: u32, ) -> Weight {
+		(137_405_000 as Weight)
+			.saturating_add((529_170_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
+	fn seal_call_runtime(r: u32, ) -> Weight {
+		(139_228_000 as Weight)
+			.saturating_add((1_861_423_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
+	fn seal_call_chain_extension(r: u32, ) -> Weight {
+		(137_926_000 as Weight)
+			.saturating_add((301_542_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
 	fn seal_hash_sha2_256(r: u32, ) -> Weight {
 		(136_601_000 as Weight)
 			.saturating_add((323_373_000 as Weight).saturating_mul(r as Weight))
@@ -411,6 +447,36 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add((153_146_000 as Weight).saturating_mul(n as Weight))
 			.saturating_add(T::DbWeight::get().reads(4 as Weight))
 	}
+	fn seal_ecdsa_recover(r: u32, ) -> Weight {
+		(361_712_000 as Weight)
+			.saturating_add((49_452_960_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
+	fn seal_ecdsa_to_eth_address(r: u32, ) -> Weight {
+		(142_302_000 as Weight)
+			.saturating_add((11_914_820_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
+	fn seal_sr25519_verify(r: u32, ) -> Weight {
+		(158_120_000 as Weight)
+			.saturating_add((48_716_203_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
+	fn seal_sr25519_verify_per_kb(n: u32, ) -> Weight {
+		(2_012_339_000 as Weight)
+			.saturating_add((168_402_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
+	fn seal_ed25519_verify(r: u32, ) -> Weight {
+		(151_842_000 as Weight)
+			.saturating_add((59_207_411_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
+	fn seal_ed25519_verify_per_kb(n: u32, ) -> Weight {
+		(2_291_672_000 as Weight)
+			.saturating_add((171_305_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
 	fn instr_i64const(r: u32, ) -> Weight {
 		(26_679_000 as Weight)
 			.saturating_add((3_155_000 as Weight).saturating_mul(r as Weight))
@@ -630,6 +696,17 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
 	}
+	fn code_load(c: u32, ) -> Weight {
+		(12_355_000 as Weight)
+			.saturating_add((4_322_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+	}
+	fn reinstrument(c: u32, ) -> Weight {
+		(18_617_000 as Weight)
+			.saturating_add((101_375_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
 	fn instantiate(n: u32, s: u32, ) -> Weight {
 		(195_276_000 as Weight)
 			.saturating_add((35_000 as Weight).saturating_mul(n as Weight))
@@ -642,16 +719,21 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
 	}
-	fn claim_surcharge() -> Weight {
-		(489_633_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	fn remove_code() -> Weight {
+		(40_672_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
 	}
 	fn seal_caller(r: u32, ) -> Weight {
 		(136_550_000 as Weight)
 			.saturating_add((373_182_000 as Weight).saturating_mul(r as Weight))
 			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
 	}
+	fn seal_caller_is_origin(r: u32, ) -> Weight {
+		(134_880_000 as Weight)
+			.saturating_add((148_627_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
 	fn seal_address(r: u32, ) -> Weight {
 		(136_329_000 as Weight)
 			.saturating_add((373_392_000 as Weight).saturating_mul(r as Weight))
@@ -677,16 +759,6 @@ impl WeightInfo for () {
 			.saturating_add((370_036_000 as Weight).saturating_mul(r as Weight))
 			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
 	}
-	fn seal_tombstone_deposit(r: u32, ) -> Weight {
-		(138_568_000 as Weight)
-			.saturating_add((370_322_000 as Weight).saturating_mul(r as Weight))
-			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
-	}
-	fn seal_rent_allowance(r: u32, ) -> Weight {
-		(144_431_000 as Weight)
-			.saturating_add((851_810_000 as Weight).saturating_mul(r as Weight))
-			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
-	}
 	fn seal_block_number(r: u32, ) -> Weight {
 		(133_237_000 as Weight)
 			.saturating_add((369_156_000 as Weight).saturating_mul(r as Weight))
@@ -734,26 +806,16 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(r as Weight)))
 			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(r as Weight)))
 	}
-	fn seal_restore_to(r: u32, ) -> Weight {
-		(233_645_000 as Weight)
-			.saturating_add((135_355_000 as Weight).saturating_mul(r as Weight))
-			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
-			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(r as Weight)))
-			.saturating_add(RocksDbWeight::get().writes((4 as Weight).saturating_mul(r as Weight)))
-	}
-	fn seal_restore_to_per_delta(d: u32, ) -> Weight {
-		(74_573_000 as Weight)
-			.saturating_add((3_768_682_000 as Weight).saturating_mul(d as Weight))
-			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
-			.saturating_add(RocksDbWeight::get().reads((100 as Weight).saturating_mul(d as Weight)))
-			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
-			.saturating_add(RocksDbWeight::get().writes((100 as Weight).saturating_mul(d as Weight)))
-	}
 	fn seal_random(r: u32, ) -> Weight {
 		(140_286_000 as Weight)
 			.saturating_add((950_890_000 as Weight).saturating_mul(r as Weight))
 			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
 	}
+	fn seal_debug_message(r: u32, ) -> Weight {
+		(137_625_000 as Weight)
+			.saturating_add((181_523_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
 	fn seal_deposit_event(r: u32, ) -> Weight {
 		(167_735_000 as Weight)
 			.saturating_add((1_375_429_000 as Weight).saturating_mul(r as Weight))
@@ -767,12 +829,6 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads((100 as Weight).saturating_mul(t as Weight)))
 			.saturating_add(RocksDbWeight::get().writes((100 as Weight).saturating_mul(t as Weight)))
 	}
-	fn seal_set_rent_allowance(r: u32, ) -> Weight {
-		(156_911_000 as Weight)
-			.saturating_add((1_006_139_000 as Weight).saturating_mul(r as Weight))
-			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
-	}
 	fn seal_set_storage(r: u32, ) -> Weight {
 		(0 as Weight)
 			.saturating_add((14_938_793_000 as Weight).saturating_mul(r as Weight))
@@ -829,6 +885,12 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads((101 as Weight).saturating_mul(t as Weight)))
 			.saturating_add(RocksDbWeight::get().writes((101 as Weight).saturating_mul(t as Weight)))
 	}
+	fn seal_delegate_call(r: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((10_201_540_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+	}
 	fn seal_instantiate(r: u32, ) -> Weight {
 		(0 as Weight)
 			.saturating_add((22_365_908_000 as Weight).saturating_mul(r as Weight))
@@ -845,6 +907,35 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(207 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(202 as Weight))
 	}
+	fn seal_set_code_hash(r: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((4_113_624_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((200 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((200 as Weight).saturating_mul(r as Weight)))
+	}
+	fn seal_code_hash(r: u32, ) -> Weight {
+		(138_102_000 as Weight)
+			.saturating_add((2_263_891_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+	}
+	fn seal_own_code_hash(r: u32, ) -> Weight {
+		(137_405_000 as Weight)
+			.saturating_add((529_170_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
+	fn seal_call_runtime(r: u32, ) -> Weight {
+		(139_228_000 as Weight)
+			.saturating_add((1_861_423_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
+	fn seal_call_chain_extension(r: u32, ) -> Weight {
+		(137_926_000 as Weight)
+			.saturating_add((301_542_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
 	fn seal_hash_sha2_256(r: u32, ) -> Weight {
 		(136_601_000 as Weight)
 			.saturating_add((323_373_000 as Weight).saturating_mul(r as Weight))
@@ -885,6 +976,36 @@ impl WeightInfo for () {
 			.saturating_add((153_146_000 as Weight).saturating_mul(n as Weight))
 			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
 	}
+	fn seal_ecdsa_recover(r: u32, ) -> Weight {
+		(361_712_000 as Weight)
+			.saturating_add((49_452_960_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
+	fn seal_ecdsa_to_eth_address(r: u32, ) -> Weight {
+		(142_302_000 as Weight)
+			.saturating_add((11_914_820_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
+	fn seal_sr25519_verify(r: u32, ) -> Weight {
+		(158_120_000 as Weight)
+			.saturating_add((48_716_203_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
+	fn seal_sr25519_verify_per_kb(n: u32, ) -> Weight {
+		(2_012_339_000 as Weight)
+			.saturating_add((168_402_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
+	fn seal_ed25519_verify(r: u32, ) -> Weight {
+		(151_842_000 as Weight)
+			.saturating_add((59_207_411_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
+	fn seal_ed25519_verify_per_kb(n: u32, ) -> Weight {
+		(2_291_672_000 as Weight)
+			.saturating_add((171_305_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
 	fn instr_i64const(r: u32, ) -> Weight {
 		(26_679_000 as Weight)
 			.saturating_add((3_155_000 as Weight).saturating_mul(r as Weight))
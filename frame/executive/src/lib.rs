@@ -227,6 +227,36 @@ where
 		digest
 	}
 
+	/// Execute all `OnRuntimeUpgrade` of this runtime, and return the aggregate weight.
+	pub fn execute_on_runtime_upgrade() -> frame_support::weights::Weight {
+		let mut weight = 0;
+		// System is not part of `AllModules`, so we need to call this manually.
+		weight = weight.saturating_add(<frame_system::Module::<System> as OnRuntimeUpgrade>::on_runtime_upgrade());
+		weight = weight.saturating_add(COnRuntimeUpgrade::on_runtime_upgrade());
+		weight = weight.saturating_add(<AllModules as OnRuntimeUpgrade>::on_runtime_upgrade());
+		weight
+	}
+
+	/// Execute all `OnRuntimeUpgrade` of this runtime together with their `pre_upgrade` and
+	/// `post_upgrade` checks.
+	///
+	/// This is meant to be used by testing tools against a copy of the chain's state and must
+	/// never be called on-chain. Returns the aggregate weight or the first failed check.
+	#[cfg(feature = "try-runtime")]
+	pub fn try_runtime_upgrade() -> Result<frame_support::weights::Weight, &'static str> {
+		<frame_system::Module::<System> as OnRuntimeUpgrade>::pre_upgrade()?;
+		COnRuntimeUpgrade::pre_upgrade()?;
+		<AllModules as OnRuntimeUpgrade>::pre_upgrade()?;
+
+		let weight = Self::execute_on_runtime_upgrade();
+
+		<frame_system::Module::<System> as OnRuntimeUpgrade>::post_upgrade()?;
+		COnRuntimeUpgrade::post_upgrade()?;
+		<AllModules as OnRuntimeUpgrade>::post_upgrade()?;
+
+		Ok(weight)
+	}
+
 	fn initialize_block_impl(
 		block_number: &System::BlockNumber,
 		parent_hash: &System::Hash,
@@ -234,10 +264,7 @@ where
 	) {
 		let mut weight = 0;
 		if Self::runtime_upgraded() {
-			// System is not part of `AllModules`, so we need to call this manually.
-			weight = weight.saturating_add(<frame_system::Module::<System> as OnRuntimeUpgrade>::on_runtime_upgrade());
-			weight = weight.saturating_add(COnRuntimeUpgrade::on_runtime_upgrade());
-			weight = weight.saturating_add(<AllModules as OnRuntimeUpgrade>::on_runtime_upgrade());
+			weight = weight.saturating_add(Self::execute_on_runtime_upgrade());
 		}
 		<frame_system::Module<System>>::initialize(
 			block_number,
@@ -17,11 +17,14 @@
 
 //! Merkle Mountain Range primitive types.
 
-use frame_support::RuntimeDebug;
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+use frame_support::{debug, RuntimeDebug};
 use sp_runtime::traits;
 use sp_std::fmt;
 #[cfg(not(feature = "std"))]
-use sp_std::prelude::Vec;
+use sp_std::{vec, prelude::Vec};
 
 /// A provider of the MMR's leaf data.
 pub trait LeafDataProvider {
@@ -164,6 +167,7 @@ impl<H: traits::Hash, L: FullLeaf> DataOrHash<H, L> {
 /// you don't care about with their hashes.
 #[derive(RuntimeDebug, Clone, PartialEq)]
 pub struct Compact<H, T> {
+	/// Internal tuple representation.
 	pub tuple: T,
 	_hash: sp_std::marker::PhantomData<H>,
 }
@@ -177,6 +181,7 @@ impl<H, T> sp_std::ops::Deref for Compact<H, T> {
 }
 
 impl<H, T> Compact<H, T> {
+	/// Create a new [Compact] wrapper for a tuple.
 	pub fn new(tuple: T) -> Self {
 		Self { tuple, _hash: Default::default() }
 	}
@@ -274,15 +279,188 @@ pub struct Proof<Hash> {
 	pub items: Vec<Hash>,
 }
 
+/// A MMR proof data for a group of leaves.
+///
+/// Sibling nodes that are shared between the paths of the proven leaves are
+/// included only once, so the proof is smaller than a set of single-leaf [Proof]s.
+#[derive(codec::Encode, codec::Decode, RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct BatchProof<Hash> {
+	/// The indices of the leaves the proof is for (sorted, without duplicates).
+	pub leaf_indices: Vec<u64>,
+	/// Number of leaves in MMR, when the proof was generated.
+	pub leaf_count: u64,
+	/// Proof elements (hashes of siblings of inner nodes on the path to the leaves).
+	pub items: Vec<Hash>,
+}
+
+impl<Hash> From<Proof<Hash>> for BatchProof<Hash> {
+	fn from(proof: Proof<Hash>) -> Self {
+		BatchProof {
+			leaf_indices: vec![proof.leaf_index],
+			leaf_count: proof.leaf_count,
+			items: proof.items,
+		}
+	}
+}
+
+/// Merkle Mountain Range operation error.
+#[derive(RuntimeDebug, codec::Encode, codec::Decode, PartialEq, Eq)]
+pub enum Error {
+	/// Error while pushing new node.
+	Push,
+	/// Error getting the new root.
+	GetRoot,
+	/// Error commiting changes.
+	Commit,
+	/// Error during proof generation.
+	GenerateProof,
+	/// Proof verification error.
+	Verify,
+	/// Leaf not found in the storage.
+	LeafNotFound,
+}
+
+impl Error {
+	/// Consume given error `e` with `self` and generate a native log entry with error details.
+	pub fn log_error(self, e: impl fmt::Debug) -> Self {
+		debug::native::error!("[{:?}] MMR error: {:?}", self, e);
+		self
+	}
+
+	/// Consume given error `e` with `self` and generate a native log entry with error details.
+	pub fn log_debug(self, e: impl fmt::Debug) -> Self {
+		debug::native::debug!("[{:?}] MMR error: {:?}", self, e);
+		self
+	}
+}
+
+/// An opaque type used to represent the leaf.
+///
+/// The leaf is stored in it's compact (SCALE-encoded) form, so it's hash matches
+/// the hash of the original leaf stored in the MMR.
+#[derive(RuntimeDebug, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpaqueLeaf(
+	/// Raw bytes of the leaf type encoded in it's compact form.
+	///
+	/// NOTE it DOES NOT include length prefix (like `Vec<u8>` encoding would).
+	#[cfg_attr(feature = "std", serde(with = "sp_core::bytes"))]
+	pub Vec<u8>
+);
+
+impl OpaqueLeaf {
+	/// Convert a concrete MMR leaf into an opaque type.
+	pub fn from_leaf<T: FullLeaf>(leaf: &T) -> Self {
+		let encoded_leaf = leaf.using_encoded(|d| d.to_vec(), true);
+		OpaqueLeaf::from_encoded_leaf(encoded_leaf)
+	}
+
+	/// Create a `OpaqueLeaf` given raw bytes of compact-encoded leaf.
+	pub fn from_encoded_leaf(encoded_leaf: Vec<u8>) -> Self {
+		OpaqueLeaf(encoded_leaf)
+	}
+
+	/// Attempt to decode the leaf into expected concrete type.
+	pub fn try_decode<T: codec::Decode>(&self) -> Option<T> {
+		codec::Decode::decode(&mut &*self.0).ok()
+	}
+}
+
+impl FullLeaf for OpaqueLeaf {
+	fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F, _compact: bool) -> R {
+		f(&self.0)
+	}
+}
+
+/// A type-safe wrapper for the concrete leaf type.
+///
+/// This structure serves merely to avoid passing raw `Vec<u8>` around.
+/// It must be `Vec<u8>`-encoding compatible.
+///
+/// It is different from [`OpaqueLeaf`], because it does implement `Codec`
+/// and the encoding has to match raw `Vec<u8>` encoding.
+#[derive(codec::Encode, codec::Decode, RuntimeDebug, PartialEq, Eq)]
+pub struct EncodableOpaqueLeaf(pub Vec<u8>);
+
+impl EncodableOpaqueLeaf {
+	/// Convert a concrete leaf into encodable opaque version.
+	pub fn from_leaf<T: FullLeaf>(leaf: &T) -> Self {
+		let opaque = OpaqueLeaf::from_leaf(leaf);
+		Self::from_opaque_leaf(opaque)
+	}
+
+	/// Given an opaque leaf, make it encodable.
+	pub fn from_opaque_leaf(opaque: OpaqueLeaf) -> Self {
+		Self(opaque.0)
+	}
+
+	/// Try to convert into a [OpaqueLeaf].
+	pub fn into_opaque_leaf(self) -> OpaqueLeaf {
+		// wrap into `OpaqueLeaf` type
+		OpaqueLeaf::from_encoded_leaf(self.0)
+	}
+}
+
+sp_api::decl_runtime_apis! {
+	/// API to interact with MMR pallet.
+	pub trait MmrApi<Hash: codec::Codec> {
+		/// Generate MMR proof for a leaf under given index.
+		///
+		/// The returned leaf is encoded in it's compact form (see [OpaqueLeaf]).
+		fn generate_proof(leaf_index: u64) -> Result<(EncodableOpaqueLeaf, Proof<Hash>), Error>;
+
+		/// Verify MMR proof against on-chain MMR.
+		///
+		/// Note this function will use on-chain MMR root hash and check if the proof
+		/// matches the hash.
+		/// See [Self::verify_proof_stateless] for a stateless verifier.
+		fn verify_proof(leaf: EncodableOpaqueLeaf, proof: Proof<Hash>) -> Result<(), Error>;
+
+		/// Verify MMR proof against given root hash.
+		///
+		/// Note this function does not require any on-chain storage - the
+		/// proof is verified against given MMR root hash.
+		///
+		/// The leaf data is expected to be encoded in it's compact form.
+		fn verify_proof_stateless(root: Hash, leaf: EncodableOpaqueLeaf, proof: Proof<Hash>)
+			-> Result<(), Error>;
+
+		/// Generate MMR proof for a group of leaves under given indices.
+		///
+		/// The leaves are returned in the order of the (sorted) `leaf_indices` of the proof.
+		fn generate_batch_proof(leaf_indices: Vec<u64>)
+			-> Result<(Vec<EncodableOpaqueLeaf>, BatchProof<Hash>), Error>;
+
+		/// Verify MMR proof for a group of leaves against on-chain MMR.
+		///
+		/// The leaves must be given in the same order as `proof.leaf_indices`.
+		fn verify_batch_proof(leaves: Vec<EncodableOpaqueLeaf>, proof: BatchProof<Hash>)
+			-> Result<(), Error>;
+
+		/// Verify MMR proof for a group of leaves against given root hash.
+		///
+		/// The leaves must be given in the same order as `proof.leaf_indices`
+		/// and encoded in their compact form.
+		fn verify_batch_proof_stateless(
+			root: Hash,
+			leaves: Vec<EncodableOpaqueLeaf>,
+			proof: BatchProof<Hash>,
+		) -> Result<(), Error>;
+	}
+}
 
 #[cfg(test)]
 mod tests {
 	use super::*;
 
 	use codec::Decode;
-	use crate::tests::hex;
+	use sp_core::H256;
 	use sp_runtime::traits::Keccak256;
 
+	pub(crate) fn hex(s: &str) -> H256 {
+		s.parse().unwrap()
+	}
+
 	type Test = DataOrHash<Keccak256, String>;
 	type TestCompact = Compact<Keccak256, (Test, Test)>;
 	type TestProof = Proof<<Keccak256 as traits::Hash>::Output>;
@@ -412,4 +590,76 @@ mod tests {
 
 		assert_eq!(decoded_compact, vec![Ok(d.clone()), Ok(d.clone())]);
 	}
+
+	#[test]
+	fn opaque_leaves_should_be_scale_compatible_with_concrete_ones() {
+		// given
+		let a = Test::Data("Hello World!".into());
+		let b = Test::Data("".into());
+
+		let c: TestCompact = Compact::new((a.clone(), b.clone()));
+		let d: TestCompact = Compact::new((
+			Test::Hash(a.hash()),
+			Test::Hash(b.hash()),
+		));
+		let cases = vec![c, d.clone()];
+
+		let encoded_compact = cases
+			.iter()
+			.map(|c| c.using_encoded(|x| x.to_vec(), true))
+			.map(OpaqueLeaf::from_encoded_leaf)
+			.collect::<Vec<_>>();
+
+		let opaque = cases
+			.iter()
+			.map(OpaqueLeaf::from_leaf)
+			.collect::<Vec<_>>();
+
+		// then
+		assert_eq!(encoded_compact, opaque);
+
+		let decoded = opaque
+			.iter()
+			.map(|x| x.try_decode::<TestCompact>())
+			.collect::<Vec<_>>();
+		assert_eq!(decoded, vec![Some(d.clone()), Some(d)]);
+	}
+
+	#[test]
+	fn encodable_opaque_leaf_should_be_scale_compatible_with_vec() {
+		// given
+		let leaf = Test::Data("Hello World!".into());
+		let encodable = EncodableOpaqueLeaf::from_leaf(&leaf);
+
+		// when
+		let encoded = codec::Encode::encode(&encodable);
+		let as_vec = Vec::<u8>::decode(&mut &*encoded).unwrap();
+
+		// then
+		assert_eq!(as_vec, codec::Encode::encode(&leaf));
+		assert_eq!(encodable.into_opaque_leaf().try_decode::<Test>(), Some(leaf));
+	}
+
+	#[test]
+	fn should_convert_single_leaf_proof_into_batch_proof() {
+		// given
+		let proof: TestProof = Proof {
+			leaf_index: 5,
+			leaf_count: 10,
+			items: vec![
+				hex("c3e7ba6b511162fead58f2c8b5764ce869ed1118011ac37392522ed16720bbcd"),
+			],
+		};
+
+		// when
+		let batch = BatchProof::from(proof.clone());
+		let encoded = codec::Encode::encode(&batch);
+		let decoded = BatchProof::<H256>::decode(&mut &*encoded);
+
+		// then
+		assert_eq!(batch.leaf_indices, vec![5]);
+		assert_eq!(batch.leaf_count, proof.leaf_count);
+		assert_eq!(batch.items, proof.items);
+		assert_eq!(decoded, Ok(batch));
+	}
 }
@@ -63,6 +63,7 @@ use frame_support::{
 	weights::Weight,
 };
 use sp_runtime::traits;
+use sp_std::prelude::*;
 
 mod default_weights;
 mod mmr;
@@ -73,7 +74,7 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
-pub mod primitives;
+pub use pallet_mmr_primitives as primitives;
 
 pub trait WeightInfo {
 	fn on_initialize(peaks: u64) -> Weight;
@@ -177,11 +178,48 @@ decl_module! {
 type ModuleMmr<StorageType, T, I> = mmr::Mmr<StorageType, T, I, LeafOf<T, I>>;
 
 /// Leaf data.
-type LeafOf<T, I> = <<T as Config<I>>::LeafData as primitives::LeafDataProvider>::LeafData;
+pub type LeafOf<T, I> = <<T as Config<I>>::LeafData as primitives::LeafDataProvider>::LeafData;
 
 /// Hashing used for the pallet.
 pub(crate) type HashingOf<T, I> = <T as Config<I>>::Hashing;
 
+/// Stateless MMR proof verification.
+///
+/// This function can be used to verify received MMR proof (`proof`)
+/// for given leaf data (`leaf`) against a known MMR root hash (`root`).
+///
+/// The verification does not require any storage access.
+pub fn verify_leaf_proof<H, L>(
+	root: H::Output,
+	leaf: primitives::DataOrHash<H, L>,
+	proof: primitives::Proof<H::Output>,
+) -> Result<(), primitives::Error> where
+	H: traits::Hash,
+	L: primitives::FullLeaf,
+{
+	verify_leaves_proof(root, vec![leaf], proof.into())
+}
+
+/// Stateless MMR proof verification for a group of leaves.
+///
+/// Same as [verify_leaf_proof], but verifies a [primitives::BatchProof] generated for
+/// multiple leaves. The `leaves` must be given in the same order as `proof.leaf_indices`.
+pub fn verify_leaves_proof<H, L>(
+	root: H::Output,
+	leaves: Vec<primitives::DataOrHash<H, L>>,
+	proof: primitives::BatchProof<H::Output>,
+) -> Result<(), primitives::Error> where
+	H: traits::Hash,
+	L: primitives::FullLeaf,
+{
+	let is_valid = mmr::verify_leaves_proof(root, leaves, proof)?;
+	if is_valid {
+		Ok(())
+	} else {
+		Err(primitives::Error::Verify.log_debug(("The proof is incorrect.", root)))
+	}
+}
+
 impl<T: Config<I>, I: Instance> Module<T, I> {
 	fn offchain_key(pos: u64) -> sp_std::prelude::Vec<u8> {
 		(T::INDEXING_PREFIX, pos).encode()
@@ -195,12 +233,24 @@ impl<T: Config<I>, I: Instance> Module<T, I> {
 	/// It may return an error or panic if used incorrectly.
 	pub fn generate_proof(leaf_index: u64) -> Result<
 		(LeafOf<T, I>, primitives::Proof<<T as Config<I>>::Hash>),
-		mmr::Error,
+		primitives::Error,
 	> {
 		let mmr: ModuleMmr<mmr::storage::OffchainStorage, T, I> = mmr::Mmr::new(Self::mmr_leaves());
 		mmr.generate_proof(leaf_index)
 	}
 
+	/// Generate a MMR proof for the given group of `leaf_indices`.
+	///
+	/// The returned leaves are ordered by their index (see `leaf_indices` of the proof).
+	/// Same restrictions as for [Self::generate_proof] apply.
+	pub fn generate_batch_proof(leaf_indices: Vec<u64>) -> Result<
+		(Vec<LeafOf<T, I>>, primitives::BatchProof<<T as Config<I>>::Hash>),
+		primitives::Error,
+	> {
+		let mmr: ModuleMmr<mmr::storage::OffchainStorage, T, I> = mmr::Mmr::new(Self::mmr_leaves());
+		mmr.generate_batch_proof(leaf_indices)
+	}
+
 	/// Verify MMR proof for given `leaf`.
 	///
 	/// This method is safe to use within the runtime code.
@@ -210,22 +260,36 @@ impl<T: Config<I>, I: Instance> Module<T, I> {
 	pub fn verify_leaf(
 		leaf: LeafOf<T, I>,
 		proof: primitives::Proof<<T as Config<I>>::Hash>,
-	) -> Result<(), mmr::Error> {
+	) -> Result<(), primitives::Error> {
+		Self::verify_leaves(vec![leaf], proof.into())
+	}
+
+	/// Verify MMR proof for given group of `leaves`.
+	///
+	/// The `leaves` must be given in the same order as `proof.leaf_indices`.
+	/// Same as [Self::verify_leaf], this method is safe to use within the runtime code.
+	pub fn verify_leaves(
+		leaves: Vec<LeafOf<T, I>>,
+		proof: primitives::BatchProof<<T as Config<I>>::Hash>,
+	) -> Result<(), primitives::Error> {
+		let max_items = mmr::utils::NodesUtils::new(proof.leaf_count).depth() as usize
+			* proof.leaf_indices.len();
 		if proof.leaf_count > Self::mmr_leaves()
 			|| proof.leaf_count == 0
-			|| proof.items.len() as u32 > mmr::utils::NodesUtils::new(proof.leaf_count).depth()
+			|| proof.items.len() > max_items
+			|| proof.leaf_indices.iter().any(|index| *index >= proof.leaf_count)
 		{
-			return Err(mmr::Error::Verify.log_debug(
+			return Err(primitives::Error::Verify.log_debug(
 				"The proof has incorrect number of leaves or proof items."
 			));
 		}
 
 		let mmr: ModuleMmr<mmr::storage::RuntimeStorage, T, I> = mmr::Mmr::new(proof.leaf_count);
-		let is_valid = mmr.verify_leaf_proof(leaf, proof)?;
+		let is_valid = mmr.verify_leaves_proof(leaves, proof)?;
 		if is_valid {
 			Ok(())
 		} else {
-			Err(mmr::Error::Verify.log_debug("The proof is incorrect."))
+			Err(primitives::Error::Verify.log_debug("The proof is incorrect."))
 		}
 	}
 }
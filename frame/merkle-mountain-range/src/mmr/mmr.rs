@@ -22,13 +22,79 @@ use crate::{
 		storage::{Storage, OffchainStorage, RuntimeStorage},
 		utils::NodesUtils,
 	},
-	primitives,
+	primitives::{self, Error},
 };
-use frame_support::{debug, RuntimeDebug};
-use sp_std::fmt;
+use sp_runtime::traits;
 #[cfg(not(feature = "std"))]
 use sp_std::{vec, prelude::Vec};
 
+/// Stateless verification of the leaf proof.
+///
+/// The proof is verified against given MMR root hash, without accessing any storage.
+/// Note the leaves must be provided in the same order as `proof.leaf_indices`.
+pub fn verify_leaves_proof<H, L>(
+	root: H::Output,
+	leaves: Vec<Node<H, L>>,
+	proof: primitives::BatchProof<H::Output>,
+) -> Result<bool, Error> where
+	H: traits::Hash,
+	L: primitives::FullLeaf,
+{
+	let size = NodesUtils::new(proof.leaf_count).size();
+	let leaves_and_position = leaves_with_positions(leaves, proof.leaf_indices, proof.leaf_count)?;
+
+	let p = mmr_lib::MerkleProof::<
+		Node<H, L>,
+		Hasher<H, L>,
+	>::new(
+		size,
+		proof.items.into_iter().map(Node::Hash).collect(),
+	);
+	// We compare hashes rather than the nodes themselves, since the root of a single-leaf
+	// MMR is the leaf itself (`Node::Data`), while we are only given it's hash.
+	p.calculate_root(leaves_and_position)
+		.map(|calculated| calculated.hash() == root)
+		.map_err(|e| Error::Verify.log_debug(e))
+}
+
+/// Pair the leaves with their MMR positions, making sure there is exactly one leaf per index.
+///
+/// The pairs are returned sorted by position. Duplicated indices and indices that are out
+/// of range for an MMR with `leaf_count` leaves are rejected.
+fn leaves_with_positions<N>(
+	leaves: Vec<N>,
+	leaf_indices: Vec<u64>,
+	leaf_count: u64,
+) -> Result<Vec<(u64, N)>, Error> {
+	if leaves.is_empty() || leaves.len() != leaf_indices.len() {
+		return Err(Error::Verify.log_debug(
+			("Number of leaves does not match the proof.", leaves.len(), leaf_indices.len())
+		));
+	}
+
+	let mut leaves_and_indices = leaf_indices
+		.into_iter()
+		.zip(leaves.into_iter())
+		.collect::<Vec<_>>();
+	leaves_and_indices.sort_by_key(|(index, _)| *index);
+
+	let mut previous = None;
+	for (index, _) in &leaves_and_indices {
+		if *index >= leaf_count {
+			return Err(Error::Verify.log_debug(("Leaf index out of range.", *index, leaf_count)));
+		}
+		if previous == Some(*index) {
+			return Err(Error::Verify.log_debug(("Duplicated leaf index.", *index)));
+		}
+		previous = Some(*index);
+	}
+
+	Ok(leaves_and_indices
+		.into_iter()
+		.map(|(index, leaf)| (mmr_lib::leaf_index_to_pos(index), leaf))
+		.collect())
+}
+
 /// A wrapper around a MMR library to expose limited functionality.
 ///
 /// Available functions depend on the storage kind ([Runtime](crate::mmr::storage::RuntimeStorage)
@@ -67,6 +133,17 @@ impl<StorageType, T, I, L> Mmr<StorageType, T, I, L> where
 		&self,
 		leaf: L,
 		proof: primitives::Proof<<T as Config<I>>::Hash>,
+	) -> Result<bool, Error> {
+		self.verify_leaves_proof(vec![leaf], proof.into())
+	}
+
+	/// Verify proof of a group of leaves.
+	///
+	/// The leaves must be provided in the same order as `proof.leaf_indices`.
+	pub fn verify_leaves_proof(
+		&self,
+		leaves: Vec<L>,
+		proof: primitives::BatchProof<<T as Config<I>>::Hash>,
 	) -> Result<bool, Error> {
 		let p = mmr_lib::MerkleProof::<
 			NodeOf<T, I, L>,
@@ -75,12 +152,15 @@ impl<StorageType, T, I, L> Mmr<StorageType, T, I, L> where
 			self.mmr.mmr_size(),
 			proof.items.into_iter().map(Node::Hash).collect(),
 		);
-		let position = mmr_lib::leaf_index_to_pos(proof.leaf_index);
+		let leaves_and_position = leaves_with_positions(
+			leaves.into_iter().map(Node::Data).collect(),
+			proof.leaf_indices,
+			self.leaves,
+		)?;
 		let root = self.mmr.get_root().map_err(|e| Error::GetRoot.log_error(e))?;
-		p.verify(
-			root,
-			vec![(position, Node::Data(leaf))],
-		).map_err(|e| Error::Verify.log_debug(e))
+		p.calculate_root(leaves_and_position)
+			.map(|calculated| calculated.hash() == root.hash())
+			.map_err(|e| Error::Verify.log_debug(e))
 	}
 
 	/// Return the internal size of the MMR (number of nodes).
@@ -133,54 +213,55 @@ impl<T, I, L> Mmr<OffchainStorage, T, I, L> where
 		(L, primitives::Proof<<T as Config<I>>::Hash>),
 		Error
 	> {
-		let position = mmr_lib::leaf_index_to_pos(leaf_index);
+		let (mut leaves, proof) = self.generate_batch_proof(vec![leaf_index])?;
+		let leaf = leaves.pop().ok_or_else(|| Error::LeafNotFound.log_debug(leaf_index))?;
+		Ok((leaf, primitives::Proof {
+			leaf_index,
+			leaf_count: proof.leaf_count,
+			items: proof.items,
+		}))
+	}
+
+	/// Generate a proof for a group of leaves.
+	///
+	/// The indices are sorted and deduplicated first, the returned leaves
+	/// follow the order of `leaf_indices` in the returned proof.
+	///
+	/// Same as for [Self::generate_proof], all the nodes must be available in the storage.
+	pub fn generate_batch_proof(&self, mut leaf_indices: Vec<u64>) -> Result<
+		(Vec<L>, primitives::BatchProof<<T as Config<I>>::Hash>),
+		Error
+	> {
+		leaf_indices.sort_unstable();
+		leaf_indices.dedup();
+		if leaf_indices.is_empty() {
+			return Err(Error::GenerateProof.log_debug("No leaves requested."));
+		}
+
 		let store = <Storage<OffchainStorage, T, I, L>>::default();
-		let leaf = match mmr_lib::MMRStore::get_elem(&store, position) {
-			Ok(Some(Node::Data(leaf))) => leaf,
-			e => return Err(Error::LeafNotFound.log_debug(e)),
-		};
+		let leaves = leaf_indices
+			.iter()
+			.map(|leaf_index| {
+				let position = mmr_lib::leaf_index_to_pos(*leaf_index);
+				match mmr_lib::MMRStore::get_elem(&store, position) {
+					Ok(Some(Node::Data(leaf))) => Ok(leaf),
+					e => Err(Error::LeafNotFound.log_debug(e)),
+				}
+			})
+			.collect::<Result<Vec<_>, Error>>()?;
+
+		let positions = leaf_indices
+			.iter()
+			.map(|index| mmr_lib::leaf_index_to_pos(*index))
+			.collect();
 		let leaf_count = self.leaves;
-		self.mmr.gen_proof(vec![position])
+		self.mmr.gen_proof(positions)
 			.map_err(|e| Error::GenerateProof.log_error(e))
-			.map(|p| primitives::Proof {
-				leaf_index,
+			.map(|p| primitives::BatchProof {
+				leaf_indices,
 				leaf_count,
 				items: p.proof_items().iter().map(|x| x.hash()).collect(),
 			})
-			.map(|p| (leaf, p))
-	}
-}
-
-/// Merkle Mountain Range operation error.
-#[derive(RuntimeDebug)]
-#[cfg_attr(test, derive(PartialEq, Eq))]
-pub enum Error {
-	/// Error while pushing new node.
-	Push,
-	/// Error getting the new root.
-	GetRoot,
-	/// Error commiting changes.
-	Commit,
-	/// Error during proof generation.
-	GenerateProof,
-	/// Proof verification error.
-	Verify,
-	/// Leaf not found in the storage.
-	LeafNotFound,
-}
-
-impl Error {
-	/// Consume given error `e` with `self` and generate a native log entry with error details.
-	pub(crate) fn log_error(self, e: impl fmt::Debug) -> Self {
-		debug::native::error!("[{:?}] MMR error: {:?}", self, e);
-		self
-	}
-
-	/// Consume given error `e` with `self` and generate a native log entry with error details.
-	pub(crate) fn log_debug(self, e: impl fmt::Debug) -> Self {
-		debug::native::debug!("[{:?}] MMR error: {:?}", self, e);
-		self
+			.map(|p| (leaves, p))
 	}
-
 }
-
@@ -22,7 +22,8 @@ mod mmr;
 use crate::primitives::FullLeaf;
 use sp_runtime::traits;
 
-pub use self::mmr::{Mmr, Error};
+pub use self::mmr::{Mmr, verify_leaves_proof};
+pub use crate::primitives::Error;
 
 /// Node type for runtime `T`.
 pub type NodeOf<T, I, L> = Node<<T as crate::Config<I>>::Hashing, L>;
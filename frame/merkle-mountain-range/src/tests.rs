@@ -272,3 +272,178 @@ fn should_verify_on_the_next_block_since_there_is_no_pruning_yet() {
 		assert_eq!(crate::Module::<Test>::verify_leaf(leaf, proof5), Ok(()));
 	});
 }
+
+#[test]
+fn should_generate_batch_proof_correctly() {
+	let _ = env_logger::try_init();
+	let mut ext = new_test_ext();
+	// given
+	ext.execute_with(|| init_chain(7));
+	ext.persist_offchain_overlay();
+	register_offchain_ext(&mut ext);
+
+	ext.execute_with(|| {
+		// when
+		let (leaves, proof) = crate::Module::<Test>::generate_batch_proof(vec![5, 0, 4, 5]).unwrap();
+		let (leaf0, proof0) = crate::Module::<Test>::generate_proof(0).unwrap();
+
+		// then
+		assert_eq!(proof.leaf_indices, vec![0, 4, 5]);
+		assert_eq!(proof.leaf_count, 7);
+		assert_eq!(leaves, vec![
+			Compact::new((H256::repeat_byte(1).into(), LeafData::new(1).into())),
+			Compact::new((H256::repeat_byte(5).into(), LeafData::new(5).into())),
+			Compact::new((H256::repeat_byte(6).into(), LeafData::new(6).into())),
+		]);
+		assert_eq!(leaf0, leaves[0]);
+		// shared siblings are included only once
+		assert!(proof.items.len() < proof0.items.len() * proof.leaf_indices.len());
+		assert_eq!(
+			crate::Module::<Test>::generate_batch_proof(vec![]),
+			Err(mmr::Error::GenerateProof),
+		);
+		assert_eq!(
+			crate::Module::<Test>::generate_batch_proof(vec![7]),
+			Err(mmr::Error::LeafNotFound),
+		);
+	});
+}
+
+#[test]
+fn should_verify_batch_proof() {
+	let _ = env_logger::try_init();
+
+	let mut ext = new_test_ext();
+	ext.execute_with(|| init_chain(7));
+	ext.persist_offchain_overlay();
+	register_offchain_ext(&mut ext);
+	let (leaves, proof) = ext.execute_with(|| {
+		// when
+		crate::Module::<Test>::generate_batch_proof(vec![1, 4, 6]).unwrap()
+	});
+
+	let mut ext2 = new_test_ext();
+	ext2.execute_with(|| {
+		init_chain(7);
+		// then
+		assert_eq!(crate::Module::<Test>::verify_leaves(leaves.clone(), proof.clone()), Ok(()));
+
+		// mismatched leaves should not verify
+		let mut reordered = leaves.clone();
+		reordered.swap(0, 2);
+		assert_eq!(
+			crate::Module::<Test>::verify_leaves(reordered, proof.clone()),
+			Err(mmr::Error::Verify),
+		);
+		assert_eq!(
+			crate::Module::<Test>::verify_leaves(leaves[..2].to_vec(), proof),
+			Err(mmr::Error::Verify),
+		);
+	});
+}
+
+#[test]
+fn verification_should_be_stateless() {
+	let _ = env_logger::try_init();
+
+	let mut ext = new_test_ext();
+	ext.execute_with(|| init_chain(7));
+	ext.persist_offchain_overlay();
+	register_offchain_ext(&mut ext);
+	let (root, (leaf, proof5), (leaves, batch_proof)) = ext.execute_with(|| {
+		(
+			crate::Module::<Test>::mmr_root_hash(),
+			crate::Module::<Test>::generate_proof(5).unwrap(),
+			crate::Module::<Test>::generate_batch_proof(vec![0, 5]).unwrap(),
+		)
+	});
+
+	// Verify the proofs without relying on any on-chain data.
+	type Hashing = <Test as Config>::Hashing;
+	assert_eq!(
+		crate::verify_leaf_proof::<Hashing, _>(root, mmr::Node::Data(leaf.clone()), proof5.clone()),
+		Ok(())
+	);
+	assert_eq!(
+		crate::verify_leaves_proof::<Hashing, _>(
+			root,
+			leaves.into_iter().map(mmr::Node::Data).collect(),
+			batch_proof,
+		),
+		Ok(())
+	);
+	assert_eq!(
+		crate::verify_leaf_proof::<Hashing, _>(H256::repeat_byte(0), mmr::Node::Data(leaf), proof5),
+		Err(mmr::Error::Verify)
+	);
+}
+
+#[test]
+fn stateless_verification_should_reject_malformed_leaf_indices() {
+	let _ = env_logger::try_init();
+
+	let mut ext = new_test_ext();
+	ext.execute_with(|| init_chain(7));
+	ext.persist_offchain_overlay();
+	register_offchain_ext(&mut ext);
+	let (root, (leaves, proof)) = ext.execute_with(|| {
+		(
+			crate::Module::<Test>::mmr_root_hash(),
+			crate::Module::<Test>::generate_batch_proof(vec![1, 4, 6]).unwrap(),
+		)
+	});
+
+	type Hashing = <Test as Config>::Hashing;
+	let verify = |leaves: Vec<_>, leaf_indices: Vec<u64>| {
+		let mut proof = proof.clone();
+		proof.leaf_indices = leaf_indices;
+		crate::verify_leaves_proof::<Hashing, _>(
+			root,
+			leaves.into_iter().map(mmr::Node::Data).collect(),
+			proof,
+		)
+	};
+
+	// leaves paired with unsorted indices are put back into the order of the proof.
+	assert_eq!(
+		verify(vec![leaves[2].clone(), leaves[0].clone(), leaves[1].clone()], vec![6, 1, 4]),
+		Ok(())
+	);
+	// but the leaves must still match their indices.
+	assert_eq!(verify(leaves.clone(), vec![6, 1, 4]), Err(mmr::Error::Verify));
+	// duplicated indices
+	assert_eq!(verify(leaves.clone(), vec![1, 4, 4]), Err(mmr::Error::Verify));
+	assert_eq!(
+		verify(vec![leaves[0].clone(), leaves[0].clone()], vec![1, 1]),
+		Err(mmr::Error::Verify)
+	);
+	// indices out of range
+	assert_eq!(verify(leaves.clone(), vec![1, 4, 7]), Err(mmr::Error::Verify));
+	assert_eq!(verify(leaves, vec![1, 4, u64::max_value()]), Err(mmr::Error::Verify));
+}
+
+#[test]
+fn should_verify_single_leaf_mmr_stateless() {
+	let _ = env_logger::try_init();
+
+	let mut ext = new_test_ext();
+	ext.execute_with(|| init_chain(1));
+	ext.persist_offchain_overlay();
+	register_offchain_ext(&mut ext);
+	let (root, (leaf, proof)) = ext.execute_with(|| {
+		(
+			crate::Module::<Test>::mmr_root_hash(),
+			crate::Module::<Test>::generate_proof(0).unwrap(),
+		)
+	});
+
+	// The root of a single-leaf MMR is the hash of the leaf itself.
+	assert_eq!(
+		crate::verify_leaf_proof::<<Test as Config>::Hashing, _>(
+			root,
+			mmr::Node::Data(leaf),
+			proof,
+		),
+		Ok(())
+	);
+}
@@ -23,6 +23,9 @@
 //! adversaries. Using this pallet as a randomness source is advisable primarily in low-security
 //! situations like testing.
 //!
+//! Along with the random value, the module returns the block number since when the value was
+//! determinable by chain observers, i.e. the oldest block whose hash contributed to it.
+//!
 //! ## Public Functions
 //!
 //! See the [`Module`](./struct.Module.html) struct for details of publicly available functions.
@@ -45,7 +48,8 @@
 //! 	pub struct Module<T: Config> for enum Call where origin: T::Origin {
 //! 		#[weight = 0]
 //! 		pub fn random_module_example(origin) -> dispatch::DispatchResult {
-//! 			let _random_value = <pallet_randomness_collective_flip::Module<T>>::random(&b"my context"[..]);
+//! 			let (_random_value, _known_since) =
+//! 				<pallet_randomness_collective_flip::Module<T>>::random(&b"my context"[..]);
 //! 			Ok(())
 //! 		}
 //! 	}
@@ -56,7 +60,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use sp_std::{prelude::*, convert::TryInto};
-use sp_runtime::traits::Hash;
+use sp_runtime::traits::{Hash, Saturating};
 use frame_support::{
 	decl_module, decl_storage, traits::Randomness,
 	weights::Weight
@@ -99,7 +103,7 @@ decl_storage! {
 	}
 }
 
-impl<T: Config> Randomness<T::Hash> for Module<T> {
+impl<T: Config> Randomness<T::Hash, T::BlockNumber> for Module<T> {
 	/// This randomness uses a low-influence function, drawing upon the block hashes from the
 	/// previous 81 blocks. Its result for any given subject will be known far in advance by anyone
 	/// observing the chain. Any block producer has significant influence over their block hashes
@@ -110,12 +114,15 @@ impl<T: Config> Randomness<T::Hash> for Module<T> {
 	/// WARNING: Hashing the result of this function will remove any low-influence properties it has
 	/// and mean that all bits of the resulting value are entirely manipulatable by the author of
 	/// the parent block, who can determine the value of `parent_hash`.
-	fn random(subject: &[u8]) -> T::Hash {
+	///
+	/// The returned block number is the block from which the oldest hash in the series was
+	/// taken, i.e. the value has been determinable by chain observers since then.
+	fn random(subject: &[u8]) -> (T::Hash, T::BlockNumber) {
 		let block_number = <frame_system::Module<T>>::block_number();
 		let index = block_number_to_index::<T>(block_number);
 
 		let hash_series = <RandomMaterial<T>>::get();
-		if !hash_series.is_empty() {
+		let seed = if !hash_series.is_empty() {
 			// Always the case after block 1 is initialized.
 			hash_series.iter()
 				.cycle()
@@ -126,7 +133,9 @@ impl<T: Config> Randomness<T::Hash> for Module<T> {
 				.triplet_mix()
 		} else {
 			T::Hash::default()
-		}
+		};
+
+		(seed, block_number.saturating_sub(RANDOM_MATERIAL_LEN.into()))
 	}
 }
 
@@ -268,8 +277,9 @@ mod tests {
 			assert_eq!(CollectiveFlip::random_seed(), CollectiveFlip::random_seed());
 			assert_ne!(CollectiveFlip::random(b"random_1"), CollectiveFlip::random(b"random_2"));
 
-			let random = CollectiveFlip::random_seed();
+			let (random, known_since) = CollectiveFlip::random_seed();
 
+			assert_eq!(known_since, 162 - RANDOM_MATERIAL_LEN as u64);
 			assert_ne!(random, H256::zero());
 			assert!(!CollectiveFlip::random_material().contains(&random));
 		});
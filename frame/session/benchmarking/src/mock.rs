@@ -178,7 +178,14 @@ impl pallet_staking::Config for Test {
 	type WeightInfo = ();
 }
 
-impl crate::Config for Test {}
+impl crate::Config for Test {
+	fn generate_session_keys_and_proof(owner: AccountId) -> (SessionKeys, Vec<u8>) {
+		use codec::{Decode, Encode};
+
+		let (keys, proof) = SessionKeys::generate_with_proof(&owner.encode(), None);
+		(SessionKeys::decode(&mut &keys[..]).expect("Keys were just encoded; qed"), proof)
+	}
+}
 
 pub fn new_test_ext() -> sp_io::TestExternalities {
 	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
@@ -47,6 +47,9 @@
 //!   `ensure_signed`, which checks that the origin is a signed account. As such, the account ID of
 //!   the origin stored in `NextKeys` may not necessarily be associated with a block author or a
 //!   validator. The session keys of accounts are removed once their account balance is zero.
+//!   Along with the keys a proof of their ownership has to be provided, which consists of
+//!   signatures of the caller's account ID by each of the keys (see
+//!   `OpaqueKeys::ownership_proof_is_valid`).
 //!
 //! - **Session length:** This pallet does not assume anything about the length of each session.
 //!   Rather, it relies on an implementation of `ShouldEndSession` to dictate a new session's start.
@@ -115,7 +118,7 @@ pub mod historical;
 pub mod weights;
 
 use sp_std::{prelude::*, marker::PhantomData, ops::{Sub, Rem}};
-use codec::Decode;
+use codec::{Decode, Encode};
 use sp_runtime::{KeyTypeId, Perbill, RuntimeAppPublic, BoundToRuntimeAppPublic};
 use sp_runtime::traits::{Convert, Zero, Member, OpaqueKeys, Saturating};
 use sp_staking::SessionIndex;
@@ -511,6 +514,10 @@ decl_module! {
 		/// Allows an account to set its session key prior to becoming a validator.
 		/// This doesn't take effect until the next session.
 		///
+		/// The `proof` must prove the ownership of `keys` by the caller, i.e. contain signatures
+		/// of the SCALE encoded caller account id by each of the keys. It is created alongside
+		/// the keys by the `author_rotateKeysWithOwner` RPC.
+		///
 		/// The dispatch origin of this function must be signed.
 		///
 		/// # <weight>
@@ -525,7 +532,7 @@ decl_module! {
 		pub fn set_keys(origin, keys: T::Keys, proof: Vec<u8>) -> dispatch::DispatchResult {
 			let who = ensure_signed(origin)?;
 
-			ensure!(keys.ownership_proof_is_valid(&proof), Error::<T>::InvalidProof);
+			ensure!(keys.ownership_proof_is_valid(&who.encode(), &proof), Error::<T>::InvalidProof);
 
 			Self::do_set_keys(&who, keys)?;
 
@@ -63,6 +63,10 @@ impl OpaqueKeys for PreUpgradeMockSessionKeys {
 			_ => &[],
 		}
 	}
+
+	fn ownership_proof_is_valid(&self, _owner: &[u8], _proof: &[u8]) -> bool {
+		true
+	}
 }
 
 impl_outer_origin! {
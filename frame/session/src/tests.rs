@@ -18,7 +18,7 @@
 // Tests for the Session Pallet
 
 use super::*;
-use frame_support::{traits::OnInitialize, assert_ok};
+use frame_support::{traits::OnInitialize, assert_ok, assert_noop};
 use sp_core::crypto::key_types::DUMMY;
 use sp_runtime::testing::UintAuthorityId;
 use mock::{
@@ -28,6 +28,12 @@ use mock::{
 	PreUpgradeMockSessionKeys,
 };
 
+fn ownership_proof(owner: u64, key: u64) -> Vec<u8> {
+	use sp_runtime::RuntimeAppPublic;
+
+	UintAuthorityId(key).sign(&owner.encode()).expect("Test keys can always sign; qed").encode()
+}
+
 fn initialize_block(block: u64) {
 	SESSION_CHANGED.with(|l| *l.borrow_mut() = false);
 	System::set_block_number(block);
@@ -98,7 +104,7 @@ fn authorities_should_track_validators() {
 		reset_before_session_end_called();
 
 		set_next_validators(vec![1, 2, 4]);
-		assert_ok!(Session::set_keys(Origin::signed(4), UintAuthorityId(4).into(), vec![]));
+		assert_ok!(Session::set_keys(Origin::signed(4), UintAuthorityId(4).into(), ownership_proof(4, 4)));
 		force_new_session();
 		initialize_block(3);
 		assert_eq!(Session::queued_keys(), vec![
@@ -158,7 +164,7 @@ fn session_change_should_work() {
 
 		// Block 3: Set new key for validator 2; no visible change.
 		initialize_block(3);
-		assert_ok!(Session::set_keys(Origin::signed(2), UintAuthorityId(5).into(), vec![]));
+		assert_ok!(Session::set_keys(Origin::signed(2), UintAuthorityId(5).into(), ownership_proof(2, 5)));
 		assert_eq!(authorities(), vec![UintAuthorityId(1), UintAuthorityId(2), UintAuthorityId(3)]);
 
 		// Block 4: Session rollover; no visible change.
@@ -180,11 +186,11 @@ fn duplicates_are_not_allowed() {
 	new_test_ext().execute_with(|| {
 		System::set_block_number(1);
 		Session::on_initialize(1);
-		assert!(Session::set_keys(Origin::signed(4), UintAuthorityId(1).into(), vec![]).is_err());
-		assert!(Session::set_keys(Origin::signed(1), UintAuthorityId(10).into(), vec![]).is_ok());
+		assert!(Session::set_keys(Origin::signed(4), UintAuthorityId(1).into(), ownership_proof(4, 1)).is_err());
+		assert!(Session::set_keys(Origin::signed(1), UintAuthorityId(10).into(), ownership_proof(1, 10)).is_ok());
 
 		// is fine now that 1 has migrated off.
-		assert!(Session::set_keys(Origin::signed(4), UintAuthorityId(1).into(), vec![]).is_ok());
+		assert!(Session::set_keys(Origin::signed(4), UintAuthorityId(1).into(), ownership_proof(4, 1)).is_ok());
 	});
 }
 
@@ -226,7 +232,7 @@ fn session_changed_flag_works() {
 		assert!(before_session_end_called());
 		reset_before_session_end_called();
 
-		assert_ok!(Session::set_keys(Origin::signed(2), UintAuthorityId(5).into(), vec![]));
+		assert_ok!(Session::set_keys(Origin::signed(2), UintAuthorityId(5).into(), ownership_proof(2, 5)));
 		force_new_session();
 		initialize_block(6);
 		assert!(!session_changed());
@@ -234,7 +240,7 @@ fn session_changed_flag_works() {
 		reset_before_session_end_called();
 
 		// changing the keys of a validator leads to change.
-		assert_ok!(Session::set_keys(Origin::signed(69), UintAuthorityId(69).into(), vec![]));
+		assert_ok!(Session::set_keys(Origin::signed(69), UintAuthorityId(69).into(), ownership_proof(69, 69)));
 		force_new_session();
 		initialize_block(7);
 		assert!(session_changed());
@@ -283,17 +289,42 @@ fn periodic_session_works() {
 #[test]
 fn session_keys_generate_output_works_as_set_keys_input() {
 	new_test_ext().execute_with(|| {
-		let new_keys = mock::MockSessionKeys::generate(None);
+		let (new_keys, proof) = mock::MockSessionKeys::generate_with_proof(&2u64.encode(), None);
 		assert_ok!(
 			Session::set_keys(
 				Origin::signed(2),
 				<mock::Test as Config>::Keys::decode(&mut &new_keys[..]).expect("Decode keys"),
-				vec![],
+				proof,
 			)
 		);
 	});
 }
 
+#[test]
+fn set_keys_requires_valid_ownership_proof() {
+	new_test_ext().execute_with(|| {
+		// no proof at all.
+		assert_noop!(
+			Session::set_keys(Origin::signed(4), UintAuthorityId(4).into(), vec![]),
+			Error::<mock::Test>::InvalidProof,
+		);
+
+		// proof created for another account.
+		assert_noop!(
+			Session::set_keys(Origin::signed(4), UintAuthorityId(4).into(), ownership_proof(5, 4)),
+			Error::<mock::Test>::InvalidProof,
+		);
+
+		// proof created by another key, e.g. someone else's public key registered as ours.
+		assert_noop!(
+			Session::set_keys(Origin::signed(4), UintAuthorityId(1).into(), ownership_proof(4, 4)),
+			Error::<mock::Test>::InvalidProof,
+		);
+
+		assert_ok!(Session::set_keys(Origin::signed(4), UintAuthorityId(4).into(), ownership_proof(4, 4)));
+	});
+}
+
 #[test]
 fn return_true_if_more_than_third_is_disabled() {
 	new_test_ext().execute_with(|| {
@@ -1174,6 +1174,9 @@ pub mod pallet_prelude {
 /// NOTE: OnRuntimeUpgrade is implemented with `Hooks::on_runtime_upgrade` and some additional
 /// logic. E.g. logic to write pallet version into storage.
 ///
+/// NOTE: `Hooks::pre_upgrade` and `Hooks::post_upgrade` are only forwarded when the pallet is
+/// compiled with its own `try-runtime` feature, which must enable `frame-support/try-runtime`.
+///
 /// # Call: `#[pallet::call]` mandatory
 ///
 /// Implementation of pallet dispatchables.
@@ -0,0 +1,166 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Primitives for BEEFY protocol.
+//!
+//! BEEFY (Bridge Efficiency Enabling Finality Yielder) is a secondary finality gadget that runs
+//! alongside GRANDPA. Validators sign compact [Commitment]s to the MMR root of GRANDPA-finalized
+//! blocks using ECDSA (secp256k1) keys, which makes the resulting [SignedCommitment]s cheap to
+//! verify on other chains (e.g. in Ethereum smart contracts).
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+mod commitment;
+
+pub use commitment::{Commitment, SignedCommitment};
+
+use codec::{Codec, Decode, Encode};
+use sp_core::H256;
+use sp_runtime::{ConsensusEngineId, RuntimeDebug};
+use sp_std::prelude::*;
+
+/// Key type for BEEFY module.
+pub const KEY_TYPE: sp_application_crypto::KeyTypeId = sp_application_crypto::KeyTypeId(*b"beef");
+
+/// BEEFY cryptographic types.
+///
+/// This module basically introduces three crypto types:
+/// - `crypto::Pair`
+/// - `crypto::Public`
+/// - `crypto::Signature`
+///
+/// Your code should use the above types as concrete types for all crypto related
+/// functionality.
+pub mod crypto {
+	use sp_application_crypto::{app_crypto, ecdsa};
+	app_crypto!(ecdsa, crate::KEY_TYPE);
+
+	/// Identity of a BEEFY authority using ECDSA as its crypto.
+	pub type AuthorityId = Public;
+
+	/// Signature for a BEEFY authority using ECDSA as its crypto.
+	pub type AuthoritySignature = Signature;
+}
+
+/// The `ConsensusEngineId` of BEEFY.
+pub const BEEFY_ENGINE_ID: ConsensusEngineId = *b"BEEF";
+
+/// Authority set id starts with zero at genesis.
+pub const GENESIS_AUTHORITY_SET_ID: u64 = 0;
+
+/// A typedef for validator set id.
+pub type ValidatorSetId = u64;
+
+/// The index of an authority.
+pub type AuthorityIndex = u32;
+
+/// The type used to represent an MMR root hash.
+pub type MmrRootHash = H256;
+
+/// A set of BEEFY authorities, a.k.a. validators.
+#[derive(Decode, Encode, RuntimeDebug, PartialEq, Clone)]
+pub struct ValidatorSet<AuthorityId> {
+	/// Public keys of the validator set elements.
+	pub validators: Vec<AuthorityId>,
+	/// Identifier of the validator set.
+	pub id: ValidatorSetId,
+}
+
+impl<AuthorityId> ValidatorSet<AuthorityId> {
+	/// Return an empty validator set with id of 0.
+	pub fn empty() -> Self {
+		Self {
+			validators: Default::default(),
+			id: Default::default(),
+		}
+	}
+
+	/// Return the number of signatures required to consider a commitment
+	/// for this validator set final, i.e. more than 2/3 of the validators.
+	pub fn threshold(&self) -> usize {
+		let faulty = self.validators.len().saturating_sub(1) / 3;
+		self.validators.len() - faulty
+	}
+}
+
+/// A consensus log item for BEEFY.
+#[derive(Decode, Encode, PartialEq, Eq, Clone, RuntimeDebug)]
+pub enum ConsensusLog<AuthorityId: Codec> {
+	/// The authorities have changed.
+	#[codec(index = "1")]
+	AuthoritiesChange(ValidatorSet<AuthorityId>),
+	/// Disable the authority with given index.
+	#[codec(index = "2")]
+	OnDisabled(AuthorityIndex),
+	/// MMR root hash.
+	#[codec(index = "3")]
+	MmrRoot(MmrRootHash),
+}
+
+/// BEEFY vote message.
+///
+/// A vote message is a direct vote created by a BEEFY node on every voting round
+/// and is gossiped to its peers.
+#[derive(RuntimeDebug, Decode, Encode, PartialEq, Eq, Clone)]
+pub struct VoteMessage<Hash, Number, Id, Signature> {
+	/// Commit to information extracted from a finalized block
+	pub commitment: Commitment<Number, Hash>,
+	/// Node authority id
+	pub id: Id,
+	/// Node signature
+	pub signature: Signature,
+}
+
+sp_api::decl_runtime_apis! {
+	/// API necessary for BEEFY voters.
+	pub trait BeefyApi {
+		/// Return the current active BEEFY validator set
+		fn validator_set() -> ValidatorSet<crypto::AuthorityId>;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn threshold_should_require_supermajority() {
+		let set = |n: usize| ValidatorSet { validators: vec![0_u8; n], id: 0 };
+
+		assert_eq!(
+			vec![1, 2, 3, 4, 5, 6, 7, 10, 100]
+				.into_iter()
+				.map(|n| set(n).threshold())
+				.collect::<Vec<_>>(),
+			vec![1, 2, 3, 3, 4, 5, 5, 7, 67]
+		);
+	}
+
+	#[test]
+	fn consensus_log_should_encode_with_explicit_indices() {
+		// given
+		let log: ConsensusLog<crypto::AuthorityId> = ConsensusLog::MmrRoot(H256::repeat_byte(1));
+
+		// when
+		let encoded = log.encode();
+
+		// then
+		assert_eq!(encoded[0], 3);
+		assert_eq!(ConsensusLog::decode(&mut &*encoded), Ok(log));
+	}
+}
@@ -153,6 +153,9 @@ pub enum Error {
 	#[error("Failed to get header for hash {0}")]
 	MissingHeader(String),
 
+	#[error("Body of block {0} has been pruned")]
+	BodyPruned(String),
+
 
 	#[error("State Database error: {0}")]
 	StateDatabase(String),
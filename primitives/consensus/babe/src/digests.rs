@@ -119,6 +119,15 @@ impl PreDigest {
 			PreDigest::SecondaryPlain(_) => None,
 		}
 	}
+
+	/// Returns the VRF output and proof, if they exist.
+	pub fn vrf(&self) -> Option<(&VRFOutput, &VRFProof)> {
+		match self {
+			PreDigest::Primary(primary) => Some((&primary.vrf_output, &primary.vrf_proof)),
+			PreDigest::SecondaryVRF(secondary) => Some((&secondary.vrf_output, &secondary.vrf_proof)),
+			PreDigest::SecondaryPlain(_) => None,
+		}
+	}
 }
 
 /// Information about the next epoch. This is broadcast in the first block
@@ -103,6 +103,27 @@ pub fn make_transcript(
 	transcript
 }
 
+/// Verifies the VRF output and proof carried by the given pre-digest, which must
+/// have been produced by `author` for the given epoch randomness and index, and
+/// returns the randomness derived from it. This is the same value that the BABE
+/// pallet exposes as the block author's VRF randomness, which allows verifying it
+/// off-chain. Returns `None` if the pre-digest doesn't carry a VRF output (i.e.
+/// secondary plain slots) or if the VRF proof is invalid.
+pub fn verify_vrf_randomness(
+	pre_digest: &digests::PreDigest,
+	author: &AuthorityId,
+	epoch_randomness: &Randomness,
+	epoch_index: u64,
+) -> Option<Randomness> {
+	let (vrf_output, vrf_proof) = pre_digest.vrf()?;
+	let public = sp_consensus_vrf::schnorrkel::PublicKey::from_bytes(author.as_ref()).ok()?;
+	let transcript = make_transcript(epoch_randomness, pre_digest.slot_number(), epoch_index);
+
+	let (inout, _) = public.vrf_verify(transcript, &vrf_output.0, &vrf_proof.0).ok()?;
+
+	Some(inout.make_bytes(&BABE_VRF_INOUT_CONTEXT))
+}
+
 /// Make a VRF transcript data container
 #[cfg(feature = "std")]
 pub fn make_transcript_data(
@@ -367,7 +388,7 @@ pub struct Epoch {
 
 sp_api::decl_runtime_apis! {
 	/// API necessary for block authorship with BABE.
-	#[api_version(2)]
+	#[api_version(3)]
 	pub trait BabeApi {
 		/// Return the genesis configuration for BABE. The configuration is only read on genesis.
 		fn configuration() -> BabeGenesisConfiguration;
@@ -382,6 +403,13 @@ sp_api::decl_runtime_apis! {
 		/// Returns information regarding the current epoch.
 		fn current_epoch() -> Epoch;
 
+		/// Returns the randomness derived from the VRF output of the block author,
+		/// or `None` if the block doesn't carry a VRF output (i.e. it was authored
+		/// in a secondary plain slot). The value can be verified off-chain with
+		/// `verify_vrf_randomness`, using the pre-digest of the block and the epoch
+		/// returned by `current_epoch` at its parent.
+		fn author_vrf_randomness() -> Option<Randomness>;
+
 		/// Generates a proof of key ownership for the given authority in the
 		/// current epoch. An example usage of this module is coupled with the
 		/// session historical module to prove that a given authority key is
@@ -17,6 +17,7 @@
 
 /// A wrapper around `kvdb::Database` that implements `sp_database::Database` trait
 
+use std::collections::{HashMap, hash_map::Entry};
 use ::kvdb::{DBTransaction, KeyValueDB};
 
 use crate::{Database, Change, ColumnId, Transaction, error};
@@ -33,18 +34,92 @@ fn handle_err<T>(result: std::io::Result<T>) -> T {
 }
 
 /// Wrap RocksDb database into a trait object that implements `sp_database::Database`
-pub fn as_database<D: KeyValueDB + 'static, H: Clone>(db: D) -> std::sync::Arc<dyn Database<H>> {
+pub fn as_database<D: KeyValueDB + 'static, H: Clone + AsRef<[u8]>>(
+	db: D,
+) -> std::sync::Arc<dyn Database<H>> {
 	std::sync::Arc::new(DbAdapter(db))
 }
 
-impl<D: KeyValueDB, H: Clone> Database<H> for DbAdapter<D> {
+impl<D: KeyValueDB> DbAdapter<D> {
+	// Returns the key of the reference counter of `key` and the counter value if it exists.
+	fn read_counter(&self, col: ColumnId, key: &[u8]) -> error::Result<(Vec<u8>, Option<u32>)> {
+		// The counter is stored under the key with a suffix, so that it doesn't collide with
+		// the value itself.
+		let mut counter_key = key.to_vec();
+		counter_key.push(0);
+		let counter = match self.0.get(col, &counter_key).map_err(|e| error::DatabaseError(Box::new(e)))? {
+			Some(data) => {
+				let mut counter_data = [0; 4];
+				if data.len() != 4 {
+					return Err(error::DatabaseError(Box::new(std::io::Error::new(
+						std::io::ErrorKind::Other,
+						format!("Unexpected counter len {}", data.len()),
+					))));
+				}
+				counter_data.copy_from_slice(&data);
+				Some(u32::from_le_bytes(counter_data))
+			},
+			None => None,
+		};
+		Ok((counter_key, counter))
+	}
+
+	// Returns the counter of `key` as modified by the changes seen so far in `counters`.
+	fn counter<'a>(
+		&self,
+		counters: &'a mut HashMap<(ColumnId, Vec<u8>), (Vec<u8>, Option<u32>)>,
+		col: ColumnId,
+		key: &[u8],
+	) -> error::Result<&'a mut (Vec<u8>, Option<u32>)> {
+		Ok(match counters.entry((col, key.to_vec())) {
+			Entry::Occupied(entry) => entry.into_mut(),
+			Entry::Vacant(entry) => entry.insert(self.read_counter(col, key)?),
+		})
+	}
+}
+
+impl<D: KeyValueDB, H: Clone + AsRef<[u8]>> Database<H> for DbAdapter<D> {
 	fn commit(&self, transaction: Transaction<H>) -> error::Result<()> {
 		let mut tx = DBTransaction::new();
+		// Counters that were modified by this transaction, so that multiple changes to the same
+		// key within one transaction are counted correctly.
+		let mut counters = HashMap::new();
+
 		for change in transaction.0.into_iter() {
 			match change {
 				Change::Set(col, key, value) => tx.put_vec(col, &key, value),
 				Change::Remove(col, key) => tx.delete(col, &key),
-				_ => unimplemented!(),
+				Change::Store(col, key, value) => {
+					let (counter_key, counter) = self.counter(&mut counters, col, key.as_ref())?;
+					let new_counter = counter.map_or(1, |c| c + 1);
+					if counter.is_none() {
+						tx.put_vec(col, key.as_ref(), value);
+					}
+					*counter = Some(new_counter);
+					tx.put(col, counter_key, &new_counter.to_le_bytes());
+				},
+				Change::Reference(col, key) => {
+					let (counter_key, counter) = self.counter(&mut counters, col, key.as_ref())?;
+					if let Some(c) = *counter {
+						*counter = Some(c + 1);
+						tx.put(col, counter_key, &(c + 1).to_le_bytes());
+					}
+				},
+				Change::Release(col, key) => {
+					let (counter_key, counter) = self.counter(&mut counters, col, key.as_ref())?;
+					match *counter {
+						Some(1) => {
+							*counter = None;
+							tx.delete(col, counter_key);
+							tx.delete(col, key.as_ref());
+						},
+						Some(c) => {
+							*counter = Some(c - 1);
+							tx.put(col, counter_key, &(c - 1).to_le_bytes());
+						},
+						None => {},
+					}
+				},
 			}
 		}
 		self.0.write(tx).map_err(|e| error::DatabaseError(Box::new(e)))
@@ -53,8 +128,4 @@ impl<D: KeyValueDB, H: Clone> Database<H> for DbAdapter<D> {
 	fn get(&self, col: ColumnId, key: &[u8]) -> Option<Vec<u8>> {
 		handle_err(self.0.get(col, key))
 	}
-
-	fn lookup(&self, _hash: &H) -> Option<Vec<u8>> {
-		unimplemented!();
-	}
 }
@@ -230,6 +230,22 @@ pub trait Externalities: ExtensionStore {
 	/// no transaction is open that can be closed.
 	fn storage_commit_transaction(&mut self) -> Result<(), ()>;
 
+	/// Index specified transaction slice and store it.
+	///
+	/// The indexed data are the last `size` bytes of the encoded extrinsic at `index` in the
+	/// current block, `hash` is the hash of these data.
+	fn storage_index_transaction(&mut self, _index: u32, _hash: &[u8], _size: u32) {
+		unimplemented!("storage_index_transaction");
+	}
+
+	/// Renew existing piece of transaction storage.
+	///
+	/// Adds a reference to the data indexed under `hash` for the extrinsic at `index` in the
+	/// current block.
+	fn storage_renew_transaction_index(&mut self, _index: u32, _hash: &[u8]) {
+		unimplemented!("storage_renew_transaction_index");
+	}
+
 	/// !!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!
 	/// Benchmarking related functionality and shouldn't be used anywhere else!
 	/// !!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!
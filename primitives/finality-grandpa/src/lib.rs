@@ -496,7 +496,7 @@ sp_api::decl_runtime_apis! {
 	/// applied in the runtime after those N blocks have passed.
 	///
 	/// The consensus protocol will coordinate the handoff externally.
-	#[api_version(2)]
+	#[api_version(3)]
 	pub trait GrandpaApi {
 		/// Get the current GRANDPA authorities and weights. This should not change except
 		/// for when changes are scheduled and the corresponding delay has passed.
@@ -506,6 +506,11 @@ sp_api::decl_runtime_apis! {
 		/// is finalized by the authorities from block B-1.
 		fn grandpa_authorities() -> AuthorityList;
 
+		/// Get the current GRANDPA authority set id. The set id is increased when a change is
+		/// scheduled, so it is only in sync with `grandpa_authorities` once the change has been
+		/// enacted.
+		fn current_set_id() -> SetId;
+
 		/// Submits an unsigned extrinsic to report an equivocation. The caller
 		/// must provide the equivocation proof and a key ownership proof
 		/// (should be obtained using `generate_key_ownership_proof`). The
@@ -30,7 +30,7 @@ use crate::traits::{
 	self, Member, Block as BlockT, Header as HeaderT, MaybeSerialize, MaybeMallocSizeOf,
 	NumberFor,
 };
-use crate::Justification;
+use crate::Justifications;
 
 /// Something to identify a block.
 #[derive(PartialEq, Eq, Clone, RuntimeDebug)]
@@ -111,6 +111,6 @@ where
 pub struct SignedBlock<Block> {
 	/// Full block.
 	pub block: Block,
-	/// Block justification.
-	pub justification: Option<Justification>,
+	/// Block justifications.
+	pub justifications: Option<Justifications>,
 }
@@ -26,6 +26,11 @@ use crate::ConsensusEngineId;
 use crate::codec::{Decode, Encode, Input, Error};
 use sp_core::{ChangesTrieConfiguration, RuntimeDebug};
 
+/// Payload of the `DigestItem::Other` entry that marks the state of a block as a checkpoint.
+///
+/// Nodes that prune state can be configured to keep the states of these blocks.
+pub const STATE_CHECKPOINT: &[u8] = b"state_checkpoint";
+
 /// Generic header digest.
 #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize, parity_util_mem::MallocSizeOf))]
@@ -257,6 +262,11 @@ impl<Hash> DigestItem<Hash> {
 		}
 	}
 
+	/// Returns true if this entry marks the state of the block as a checkpoint.
+	pub fn is_state_checkpoint(&self) -> bool {
+		self.as_other() == Some(STATE_CHECKPOINT)
+	}
+
 	/// Returns the opaque data contained in the item if `Some` if this entry has the id given.
 	pub fn try_as_raw(&self, id: OpaqueDigestItemId) -> Option<&[u8]> {
 		self.dref().try_as_raw(id)
@@ -34,7 +34,7 @@ pub use self::checked_extrinsic::CheckedExtrinsic;
 pub use self::header::Header;
 pub use self::block::{Block, SignedBlock, BlockId};
 pub use self::digest::{
-	Digest, DigestItem, DigestItemRef, OpaqueDigestItemId, ChangesTrieSignal,
+	Digest, DigestItem, DigestItemRef, OpaqueDigestItemId, ChangesTrieSignal, STATE_CHECKPOINT,
 };
 
 use crate::codec::Encode;
@@ -127,6 +127,14 @@ impl OpaqueKeys for UintAuthorityId {
 	fn get<T: Decode>(&self, _: KeyTypeId) -> Option<T> {
 		self.using_encoded(|mut x| T::decode(&mut x)).ok()
 	}
+
+	fn ownership_proof_is_valid(&self, owner: &[u8], mut proof: &[u8]) -> bool {
+		match TestSignature::decode(&mut proof) {
+			Ok(signature) => proof.is_empty() &&
+				sp_application_crypto::RuntimeAppPublic::verify(self, &owner, &signature),
+			Err(_) => false,
+		}
+	}
 }
 
 impl crate::BoundToRuntimeAppPublic for UintAuthorityId {
@@ -1021,7 +1021,11 @@ pub trait OpaqueKeys: Clone {
 		T::decode(&mut self.get_raw(i)).ok()
 	}
 	/// Verify a proof of ownership for the keys.
-	fn ownership_proof_is_valid(&self, _proof: &[u8]) -> bool { true }
+	///
+	/// `owner` is the SCALE-encoded account the keys are registered for and `proof` is
+	/// expected to prove possession of the private keys, i.e. consist of signatures over
+	/// `owner` by each of the keys.
+	fn ownership_proof_is_valid(&self, owner: &[u8], proof: &[u8]) -> bool;
 }
 
 /// Input that adds infinite number of zero after wrapped input.
@@ -1233,6 +1237,40 @@ macro_rules! impl_opaque_keys {
 				$crate::codec::Encode::encode(&keys)
 			}
 
+			/// Generate a set of keys with optionally using the given seed and a proof of
+			/// ownership of these keys for `owner`.
+			///
+			/// The generated key pairs are stored in the keystore. The proof consists of the
+			/// concatenated SCALE encoded signatures of `owner` by each key, in the order of
+			/// the fields.
+			///
+			/// Returns the concatenated SCALE encoded public keys and the proof.
+			pub fn generate_with_proof(
+				owner: &[u8],
+				seed: Option<$crate::sp_std::vec::Vec<u8>>,
+			) -> ($crate::sp_std::vec::Vec<u8>, $crate::sp_std::vec::Vec<u8>) {
+				let keys = Self{
+					$(
+						$field: <
+							<
+								$type as $crate::BoundToRuntimeAppPublic
+							>::Public as $crate::RuntimeAppPublic
+						>::generate_pair(seed.clone()),
+					)*
+				};
+
+				let mut proof = $crate::sp_std::vec::Vec::new();
+				$(
+					$crate::codec::Encode::encode_to(
+						&$crate::RuntimeAppPublic::sign(&keys.$field, &owner)
+							.expect("Key pair was just generated and stored in the keystore; qed"),
+						&mut proof,
+					);
+				)*
+
+				($crate::codec::Encode::encode(&keys), proof)
+			}
+
 			/// Converts `Self` into a `Vec` of `(raw public key, KeyTypeId)`.
 			pub fn into_raw_public_keys(
 				self,
@@ -1293,6 +1331,27 @@ macro_rules! impl_opaque_keys {
 					_ => &[],
 				}
 			}
+
+			fn ownership_proof_is_valid(&self, owner: &[u8], mut proof: &[u8]) -> bool {
+				$(
+					let signature = match <
+						<
+							<
+								$type as $crate::BoundToRuntimeAppPublic
+							>::Public as $crate::RuntimeAppPublic
+						>::Signature as $crate::codec::Decode
+					>::decode(&mut proof) {
+						Ok(signature) => signature,
+						Err(_) => return false,
+					};
+
+					if !$crate::RuntimeAppPublic::verify(&self.$field, &owner, &signature) {
+						return false;
+					}
+				)*
+
+				proof.is_empty()
+			}
 		}
 	};
 }
@@ -1483,4 +1542,23 @@ mod tests {
 		assert!(signature.verify(msg, &pair.public()));
 		assert!(signature.verify(msg, &pair.public()));
 	}
+
+	crate::impl_opaque_keys! {
+		pub struct TestKeys {
+			pub first: crate::testing::UintAuthorityId,
+			pub second: crate::testing::UintAuthorityId,
+		}
+	}
+
+	#[test]
+	fn opaque_keys_ownership_proof_works() {
+		let (keys, proof) = TestKeys::generate_with_proof(b"owner", None);
+		let keys = TestKeys::decode(&mut &keys[..]).unwrap();
+
+		assert!(keys.ownership_proof_is_valid(b"owner", &proof));
+		assert!(!keys.ownership_proof_is_valid(b"other", &proof));
+		assert!(!keys.ownership_proof_is_valid(b"owner", &proof[..proof.len() - 1]));
+		assert!(!keys.ownership_proof_is_valid(b"owner", &[&proof[..], &[0]].concat()));
+		assert!(!keys.ownership_proof_is_valid(b"owner", &[]));
+	}
 }
@@ -24,7 +24,7 @@ use codec::{Encode, Decode};
 #[cfg(feature = "std")]
 use sp_runtime::{generic::BlockId, traits::Block as BlockT};
 #[cfg(feature = "std")]
-use sp_api::ProvideRuntimeApi;
+use sp_api::{ApiExt, ProvideRuntimeApi};
 
 use sp_core::RuntimeDebug;
 use sp_core::crypto::KeyTypeId;
@@ -33,6 +33,7 @@ use sp_std::vec::Vec;
 
 sp_api::decl_runtime_apis! {
 	/// Session keys runtime api.
+	#[api_version(2)]
 	pub trait SessionKeys {
 		/// Generate a set of session keys with optionally using the given seed.
 		/// The keys should be stored within the keystore exposed via runtime
@@ -41,8 +42,21 @@ sp_api::decl_runtime_apis! {
 		/// The seed needs to be a valid `utf8` string.
 		///
 		/// Returns the concatenated SCALE encoded public keys.
+		#[changed_in(2)]
 		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8>;
 
+		/// Generate a set of session keys with optionally using the given seed.
+		/// The keys should be stored within the keystore exposed via runtime
+		/// externalities.
+		///
+		/// The seed needs to be a valid `utf8` string.
+		///
+		/// `owner` is the SCALE encoded account the keys will be registered for. The returned
+		/// proof of ownership is created by signing `owner` with each of the generated keys.
+		///
+		/// Returns the concatenated SCALE encoded public keys and the proof of ownership.
+		fn generate_session_keys(owner: Vec<u8>, seed: Option<Vec<u8>>) -> OpaqueGeneratedSessionKeys;
+
 		/// Decode the given public session keys.
 		///
 		/// Returns the list of public raw public keys + key type.
@@ -50,6 +64,21 @@ sp_api::decl_runtime_apis! {
 	}
 }
 
+/// Opaque session keys generated by the runtime together with a proof of their ownership.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Default, RuntimeDebug)]
+pub struct OpaqueGeneratedSessionKeys {
+	/// The concatenated SCALE encoded public keys.
+	pub keys: Vec<u8>,
+	/// The proof of ownership of the keys, to be passed to `pallet_session::set_keys`.
+	pub proof: Vec<u8>,
+}
+
+impl From<(Vec<u8>, Vec<u8>)> for OpaqueGeneratedSessionKeys {
+	fn from((keys, proof): (Vec<u8>, Vec<u8>)) -> Self {
+		OpaqueGeneratedSessionKeys { keys, proof }
+	}
+}
+
 /// Number of validators in a given session.
 pub type ValidatorCount = u32;
 
@@ -119,11 +148,36 @@ where
 	T: ProvideRuntimeApi<Block>,
 	T::Api: SessionKeys<Block>,
 {
-	let runtime_api = client.runtime_api();
-
 	for seed in seeds {
-		runtime_api.generate_session_keys(at, Some(seed.as_bytes().to_vec()))?;
+		generate_session_keys(&*client, at, Vec::new(), Some(seed.as_bytes().to_vec()))?;
 	}
 
 	Ok(())
 }
+
+/// Generate a set of session keys at the given block and store them in the client's keystore.
+///
+/// Runtimes that implement a version of the [`SessionKeys`] api older than 2 are not able to
+/// create a proof of ownership, an empty proof is returned for them.
+#[cfg(feature = "std")]
+pub fn generate_session_keys<Block, T>(
+	client: &T,
+	at: &BlockId<Block>,
+	owner: Vec<u8>,
+	seed: Option<Vec<u8>>,
+) -> Result<OpaqueGeneratedSessionKeys, sp_api::ApiErrorFor<T, Block>>
+where
+	Block: BlockT,
+	T: ProvideRuntimeApi<Block>,
+	T::Api: SessionKeys<Block>,
+{
+	let runtime_api = client.runtime_api();
+
+	if runtime_api.has_api_with::<dyn SessionKeys<Block, Error = ()>, _>(at, |v| v >= 2)? {
+		runtime_api.generate_session_keys(at, owner, seed)
+	} else {
+		#[allow(deprecated)]
+		runtime_api.generate_session_keys_before_version_2(at, seed)
+			.map(|keys| OpaqueGeneratedSessionKeys { keys, proof: Vec::new() })
+	}
+}
@@ -36,6 +36,8 @@ pub(crate) mod overlayed_changes;
 mod proving_backend;
 mod trie_backend;
 mod trie_backend_essence;
+#[cfg(feature = "std")]
+mod trie_node_cache;
 mod stats;
 #[cfg(feature = "std")]
 mod read_only;
@@ -116,7 +118,7 @@ impl sp_std::fmt::Display for DefaultError {
 }
 
 pub use crate::overlayed_changes::{
-	OverlayedChanges, StorageKey, StorageValue,
+	OverlayedChanges, StorageKey, StorageValue, IndexOperation,
 	StorageCollection, ChildStorageCollection,
 	StorageChanges, StorageTransactionCache,
 };
@@ -162,6 +164,9 @@ mod std_reexport {
 	};
 	pub use crate::error::{Error, ExecutionError};
 	pub use crate::in_memory_backend::new_in_mem;
+	pub use crate::trie_node_cache::{
+		TrieNodeCache, TrieNodeCacheStats, SharedTrieNodeCache, new_shared_trie_node_cache,
+	};
 }
 
 #[cfg(feature = "std")]
@@ -791,6 +796,47 @@ mod execution {
 		Ok(proving_backend.extract_proof())
 	}
 
+	/// Generate a proof of the key-value pairs that follow `start_at` in the top trie, or in the
+	/// child trie if `child_info` is given. Without `start_at` the pairs are read from the first
+	/// key. Pairs are read until the encoded size of the proof reaches `size_limit`, which may
+	/// be exceeded by the nodes of the last pair.
+	///
+	/// Returns the proof and the number of pairs it covers.
+	pub fn prove_range_read_with_size<B, H>(
+		mut backend: B,
+		child_info: Option<&ChildInfo>,
+		start_at: Option<&[u8]>,
+		size_limit: usize,
+	) -> Result<(StorageProof, u32), Box<dyn Error>>
+	where
+		B: Backend<H>,
+		H: Hasher,
+		H::Out: Ord + Codec,
+	{
+		let trie_backend = backend.as_trie_backend()
+			.ok_or_else(|| Box::new(ExecutionError::UnableToGenerateProof) as Box<dyn Error>)?;
+		prove_range_read_with_size_on_trie_backend(trie_backend, child_info, start_at, size_limit)
+	}
+
+	/// Generate range storage read proof on pre-created trie backend.
+	pub fn prove_range_read_with_size_on_trie_backend<S, H>(
+		trie_backend: &TrieBackend<S, H>,
+		child_info: Option<&ChildInfo>,
+		start_at: Option<&[u8]>,
+		size_limit: usize,
+	) -> Result<(StorageProof, u32), Box<dyn Error>>
+	where
+		S: trie_backend_essence::TrieBackendStorage<H>,
+		H: Hasher,
+		H::Out: Ord + Codec,
+	{
+		let proving_backend = proving_backend::ProvingBackend::<_, H>::new(trie_backend);
+		let proof_size = || proving_backend.estimate_encoded_size();
+		let limit = Some((&proof_size as &dyn Fn() -> usize, size_limit));
+		let (values, _) = read_range(&proving_backend, child_info, start_at, limit)?;
+		Ok((proving_backend.extract_proof(), values.len() as u32))
+	}
+
 	/// Check storage read proof, generated by `prove_read` call.
 	pub fn read_proof_check<H, I>(
 		root: H::Out,
@@ -838,6 +884,24 @@ mod execution {
 		Ok(result)
 	}
 
+	/// Check a proof generated by `prove_range_read_with_size`.
+	///
+	/// Returns the key-value pairs covered by the proof and whether they end with the last key
+	/// of the trie.
+	pub fn read_range_proof_check<H>(
+		root: H::Out,
+		proof: StorageProof,
+		child_info: Option<&ChildInfo>,
+		start_at: Option<&[u8]>,
+	) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, bool), Box<dyn Error>>
+	where
+		H: Hasher,
+		H::Out: Ord + Codec,
+	{
+		let proving_backend = create_proof_check_backend::<H>(root, proof)?;
+		read_range_proof_check_on_proving_backend(&proving_backend, child_info, start_at)
+	}
+
 	/// Check storage read proof on pre-created proving backend.
 	pub fn read_proof_check_on_proving_backend<H>(
 		proving_backend: &TrieBackend<MemoryDB<H>, H>,
@@ -863,6 +927,75 @@ mod execution {
 		proving_backend.child_storage(child_info, key)
 			.map_err(|e| Box::new(e) as Box<dyn Error>)
 	}
+
+	/// Check range storage read proof on pre-created proving backend.
+	///
+	/// The pairs are read until a trie node is missing from the proof.
+	pub fn read_range_proof_check_on_proving_backend<H>(
+		proving_backend: &TrieBackend<MemoryDB<H>, H>,
+		child_info: Option<&ChildInfo>,
+		start_at: Option<&[u8]>,
+	) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, bool), Box<dyn Error>>
+	where
+		H: Hasher,
+		H::Out: Ord + Codec,
+	{
+		read_range(proving_backend, child_info, start_at, None)
+	}
+
+	/// Read the key-value pairs that follow `start_at`, until the proof size reported by the
+	/// function in `size_limit` reaches its limit. Without a limit, pairs are read until the
+	/// backend fails to find the next key, which is how a partial proof ends.
+	fn read_range<H, B>(
+		backend: &B,
+		child_info: Option<&ChildInfo>,
+		start_at: Option<&[u8]>,
+		size_limit: Option<(&dyn Fn() -> usize, usize)>,
+	) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, bool), Box<dyn Error>>
+	where
+		H: Hasher,
+		B: Backend<H>,
+	{
+		let next_key = |key: &[u8]| match child_info {
+			Some(child_info) => backend.next_child_storage_key(child_info, key),
+			None => backend.next_storage_key(key),
+		};
+		let value = |key: &[u8]| match child_info {
+			Some(child_info) => backend.child_storage(child_info, key),
+			None => backend.storage(key),
+		};
+		let proving = size_limit.is_some();
+		let mut values = Vec::new();
+		let mut key = match start_at {
+			Some(key) => key.to_vec(),
+			None => {
+				// The empty key can hold a value too, `next_key` never returns it.
+				if let Some(first) = value(&[]).map_err(|e| Box::new(e) as Box<dyn Error>)? {
+					values.push((Vec::new(), first));
+				}
+				Vec::new()
+			},
+		};
+		loop {
+			if size_limit.map_or(false, |(size, limit)| size() >= limit) {
+				return Ok((values, false))
+			}
+			let next = match next_key(&key) {
+				Ok(Some(next)) => next,
+				Ok(None) => return Ok((values, true)),
+				Err(e) if proving => return Err(Box::new(e)),
+				Err(_) => return Ok((values, false)),
+			};
+			let next_value = match value(&next) {
+				Ok(Some(next_value)) => next_value,
+				Ok(None) => return Err(Box::new(format!("No value at key {}", HexDisplay::from(&next)))),
+				Err(e) if proving => return Err(Box::new(e)),
+				Err(_) => return Ok((values, false)),
+			};
+			values.push((next.clone(), next_value));
+			key = next;
+		}
+	}
 }
 
 #[cfg(test)]
@@ -1487,6 +1620,58 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn prove_range_read_and_proof_check_works() {
+		let child_info = ChildInfo::new_default(b"sub1");
+		let remote_backend = trie_backend::tests::test_trie();
+		let remote_root = remote_backend.storage_root(::std::iter::empty()).0;
+
+		let read_all = |child_info: Option<&ChildInfo>| {
+			let mut values = Vec::new();
+			let mut start_at: Option<Vec<u8>> = None;
+			loop {
+				let (proof, count) = prove_range_read_with_size(
+					trie_backend::tests::test_trie(),
+					child_info,
+					start_at.as_ref().map(|k| k.as_slice()),
+					10,
+				).unwrap();
+				let (chunk, complete) = read_range_proof_check::<BlakeTwo256>(
+					remote_root,
+					proof,
+					child_info,
+					start_at.as_ref().map(|k| k.as_slice()),
+				).unwrap();
+				// Small nodes are inlined in their parents, so a proof may cover more pairs.
+				assert!(chunk.len() as u32 >= count);
+				start_at = chunk.last().map(|(k, _)| k.clone());
+				values.extend(chunk);
+				if complete {
+					return values
+				}
+			}
+		};
+
+		assert_eq!(read_all(None), remote_backend.pairs());
+		assert_eq!(
+			read_all(Some(&child_info)),
+			vec![(b"value3".to_vec(), vec![142]), (b"value4".to_vec(), vec![124])],
+		);
+	}
+
+	#[test]
+	fn prove_range_read_limits_proof_size() {
+		let pairs = (0u32..4096).map(|i| (i.encode(), vec![1])).collect::<BTreeMap<_, _>>();
+		let remote_backend = InMemoryBackend::<BlakeTwo256>::from(pairs);
+		let size_limit = 8 * 1024;
+
+		let (proof, count) = prove_range_read_with_size(remote_backend, None, None, size_limit).unwrap();
+		// The values are tiny, the proof is made of the trie nodes leading to them.
+		assert!(count > 0 && count < 4096);
+		// The last pair may push the proof over the limit by the nodes on its path.
+		assert!(proof.encode().len() < size_limit + 2 * 1024);
+	}
+
 	#[test]
 	fn child_storage_uuid() {
 
@@ -65,6 +65,27 @@ pub type StorageCollection = Vec<(StorageKey, Option<StorageValue>)>;
 /// In memory arrays of storage values for multiple child tries.
 pub type ChildStorageCollection = Vec<(StorageKey, StorageCollection)>;
 
+/// Transaction index operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexOperation {
+	/// Insert transaction into index.
+	Insert {
+		/// Extrinsic index in the current block.
+		extrinsic: u32,
+		/// Data content hash.
+		hash: Vec<u8>,
+		/// Indexed data size. The indexed data are the last `size` bytes of the encoded extrinsic.
+		size: u32,
+	},
+	/// Renew existing transaction storage.
+	Renew {
+		/// Extrinsic index in the current block.
+		extrinsic: u32,
+		/// Referenced index hash.
+		hash: Vec<u8>,
+	},
+}
+
 /// Keep trace of extrinsics index for a modified value.
 #[derive(Debug, Default, Eq, PartialEq, Clone)]
 pub struct Extrinsics(Vec<u32>);
@@ -101,6 +122,10 @@ pub struct OverlayedChanges {
 	collect_extrinsics: bool,
 	/// Collect statistic on this execution.
 	stats: StateMachineStats,
+	/// Transaction index changes.
+	transaction_index_ops: Vec<IndexOperation>,
+	/// Length of `transaction_index_ops` at the start of each open transaction.
+	transaction_index_marks: Vec<usize>,
 }
 
 /// A storage changes structure that can be generated by the data collected in [`OverlayedChanges`].
@@ -132,6 +157,8 @@ pub struct StorageChanges<Transaction, H: Hasher, N: BlockNumber> {
 	/// Phantom data for block number until change trie support no_std.
 	#[cfg(not(feature = "std"))]
 	pub _ph: sp_std::marker::PhantomData<N>,
+	/// Changes to the transaction index.
+	pub transaction_index_changes: Vec<IndexOperation>,
 }
 
 #[cfg(feature = "std")]
@@ -144,6 +171,7 @@ impl<Transaction, H: Hasher, N: BlockNumber> StorageChanges<Transaction, H, N> {
 		Transaction,
 		H::Out,
 		Option<ChangesTrieTransaction<H, N>>,
+		Vec<IndexOperation>,
 	) {
 		(
 			self.main_storage_changes,
@@ -152,6 +180,7 @@ impl<Transaction, H: Hasher, N: BlockNumber> StorageChanges<Transaction, H, N> {
 			self.transaction,
 			self.transaction_storage_root,
 			self.changes_trie_transaction,
+			self.transaction_index_changes,
 		)
 	}
 }
@@ -210,6 +239,7 @@ impl<Transaction: Default, H: Hasher, N: BlockNumber> Default for StorageChanges
 			changes_trie_transaction: None,
 			#[cfg(not(feature = "std"))]
 			_ph: Default::default(),
+			transaction_index_changes: Default::default(),
 		}
 	}
 }
@@ -372,6 +402,7 @@ impl OverlayedChanges {
 		for (_, (changeset, _)) in self.children.iter_mut() {
 			changeset.start_transaction();
 		}
+		self.transaction_index_marks.push(self.transaction_index_ops.len());
 	}
 
 	/// Rollback the last transaction started by `start_transaction`.
@@ -385,6 +416,9 @@ impl OverlayedChanges {
 				.expect("Top and children changesets are started in lockstep; qed");
 			!changeset.is_empty()
 		});
+		let mark = self.transaction_index_marks.pop()
+			.expect("Index marks are pushed in lockstep with the top changeset; qed");
+		self.transaction_index_ops.truncate(mark);
 		Ok(())
 	}
 
@@ -398,6 +432,7 @@ impl OverlayedChanges {
 			changeset.commit_transaction()
 				.expect("Top and children changesets are started in lockstep; qed");
 		}
+		self.transaction_index_marks.pop();
 		Ok(())
 	}
 
@@ -424,6 +459,8 @@ impl OverlayedChanges {
 			changeset.exit_runtime()
 				.expect("Top and children changesets are entering runtime in lockstep; qed");
 		}
+		// Dangling transactions are committed, which keeps their index operations.
+		self.transaction_index_marks.truncate(self.top.transaction_depth());
 		Ok(())
 	}
 
@@ -530,9 +567,17 @@ impl OverlayedChanges {
 			changes_trie_transaction,
 			#[cfg(not(feature = "std"))]
 			_ph: Default::default(),
+			transaction_index_changes: sp_std::mem::take(&mut self.transaction_index_ops),
 		})
 	}
 
+	/// Record an operation on the transaction index.
+	///
+	/// Can be rolled back or committed when called inside a transaction.
+	pub(crate) fn add_transaction_index(&mut self, op: IndexOperation) {
+		self.transaction_index_ops.push(op);
+	}
+
 	/// Inserts storage entry responsible for current extrinsic index.
 	#[cfg(test)]
 	pub(crate) fn set_extrinsic_index(&mut self, extrinsic_index: u32) {
@@ -17,9 +17,9 @@
 
 //! Proving state machine backend.
 
-use std::{sync::Arc, collections::HashMap};
+use std::{sync::Arc, collections::HashMap, sync::atomic::{AtomicUsize, Ordering}};
 use parking_lot::RwLock;
-use codec::{Decode, Codec};
+use codec::{Decode, Codec, Compact, CompactLen};
 use log::debug;
 use hash_db::{Hasher, HashDB, EMPTY_PREFIX, Prefix};
 use sp_trie::{
@@ -48,7 +48,7 @@ impl<'a, S, H> ProvingBackendRecorder<'a, S, H>
 	pub fn storage(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, String> {
 		let mut read_overlay = S::Overlay::default();
 		let eph = Ephemeral::new(
-			self.backend.backend_storage(),
+			self.backend,
 			&mut read_overlay,
 		);
 
@@ -75,7 +75,7 @@ impl<'a, S, H> ProvingBackendRecorder<'a, S, H>
 
 		let mut read_overlay = S::Overlay::default();
 		let eph = Ephemeral::new(
-			self.backend.backend_storage(),
+			self.backend,
 			&mut read_overlay,
 		);
 
@@ -94,7 +94,7 @@ impl<'a, S, H> ProvingBackendRecorder<'a, S, H>
 	pub fn record_all_keys(&mut self) {
 		let mut read_overlay = S::Overlay::default();
 		let eph = Ephemeral::new(
-			self.backend.backend_storage(),
+			self.backend,
 			&mut read_overlay,
 		);
 
@@ -121,8 +121,10 @@ pub struct ProvingBackend<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> (
 
 /// Trie backend storage with its proof recorder.
 pub struct ProofRecorderBackend<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> {
-	backend: &'a S,
+	backend: &'a TrieBackendEssence<S, H>,
 	proof_recorder: ProofRecorder<H>,
+	/// Encoded size of the recorded nodes, including their length prefixes.
+	encoded_size: AtomicUsize,
 }
 
 impl<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> ProvingBackend<'a, S, H>
@@ -141,9 +143,13 @@ impl<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> ProvingBackend<'a, S, H>
 	) -> Self {
 		let essence = backend.essence();
 		let root = essence.root().clone();
+		let encoded_size = proof_recorder.read().values()
+			.filter_map(|v| v.as_ref().map(|v| encoded_node_size(v)))
+			.sum();
 		let recorder = ProofRecorderBackend {
-			backend: essence.backend_storage(),
+			backend: essence,
 			proof_recorder,
+			encoded_size: AtomicUsize::new(encoded_size),
 		};
 		ProvingBackend(TrieBackend::new(recorder, root))
 	}
@@ -157,6 +163,19 @@ impl<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> ProvingBackend<'a, S, H>
 			.collect();
 		StorageProof::new(trie_nodes)
 	}
+
+	/// Returns the size of the proof gathered so far once encoded. Nodes recorded through
+	/// other backends sharing the recorder are not accounted for.
+	pub fn estimate_encoded_size(&self) -> usize {
+		let storage = self.0.essence().backend_storage();
+		let nodes = storage.proof_recorder.read().len();
+		Compact::<u32>::compact_len(&(nodes as u32)) + storage.encoded_size.load(Ordering::Relaxed)
+	}
+}
+
+/// Size of an encoded trie node within a proof.
+fn encoded_node_size(node: &[u8]) -> usize {
+	Compact::<u32>::compact_len(&(node.len() as u32)) + node.len()
 }
 
 impl<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> TrieBackendStorage<H>
@@ -168,8 +187,12 @@ impl<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> TrieBackendStorage<H>
 		if let Some(v) = self.proof_recorder.read().get(key) {
 			return Ok(v.clone());
 		}
-		let backend_value =  self.backend.get(key, prefix)?;
-		self.proof_recorder.write().insert(key.clone(), backend_value.clone());
+		// Nodes served by the trie node cache still go through the recorder.
+		let backend_value = self.backend.storage_get(key, prefix)?;
+		let previous = self.proof_recorder.write().insert(key.clone(), backend_value.clone());
+		if let (None, Some(value)) = (previous, backend_value.as_ref()) {
+			self.encoded_size.fetch_add(encoded_node_size(value), Ordering::Relaxed);
+		}
 		Ok(backend_value)
 	}
 }
@@ -366,6 +389,7 @@ mod tests {
 		assert_eq!(proving.storage(&[42]).unwrap().unwrap(), vec![42]);
 
 		let proof = proving.extract_proof();
+		assert_eq!(proving.estimate_encoded_size(), codec::Encode::encode(&proof).len());
 
 		let proof_check = create_proof_check_backend::<BlakeTwo256>(in_memory_root.into(), proof).unwrap();
 		assert_eq!(proof_check.storage(&[42]).unwrap().unwrap(), vec![42]);
@@ -42,6 +42,16 @@ impl<S: TrieBackendStorage<H>, H: Hasher> TrieBackend<S, H> where H::Out: Codec
 		}
 	}
 
+	/// Read trie nodes through the given shared cache before going to the storage.
+	#[cfg(feature = "std")]
+	pub fn with_trie_node_cache(
+		mut self,
+		cache: Option<crate::trie_node_cache::SharedTrieNodeCache<H>>,
+	) -> Self {
+		self.essence = self.essence.with_trie_node_cache(cache);
+		self
+	}
+
 	/// Get backend essence reference.
 	pub fn essence(&self) -> &TrieBackendEssence<S, H> {
 		&self.essence
@@ -177,7 +187,7 @@ impl<S: TrieBackendStorage<H>, H: Hasher> Backend<H> for TrieBackend<S, H> where
 
 		{
 			let mut eph = Ephemeral::new(
-				self.essence.backend_storage(),
+				&self.essence,
 				&mut write_overlay,
 			);
 
@@ -212,7 +222,7 @@ impl<S: TrieBackendStorage<H>, H: Hasher> Backend<H> for TrieBackend<S, H> where
 
 		{
 			let mut eph = Ephemeral::new(
-				self.essence.backend_storage(),
+				&self.essence,
 				&mut write_overlay,
 			);
 
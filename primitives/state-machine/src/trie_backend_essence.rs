@@ -30,6 +30,8 @@ use sp_trie::trie_types::{TrieDB, TrieError, Layout};
 use crate::{backend::Consolidate, StorageKey, StorageValue};
 use sp_core::storage::ChildInfo;
 use codec::Encode;
+#[cfg(feature = "std")]
+use crate::trie_node_cache::SharedTrieNodeCache;
 
 #[cfg(not(feature = "std"))]
 macro_rules! format {
@@ -51,6 +53,8 @@ pub struct TrieBackendEssence<S: TrieBackendStorage<H>, H: Hasher> {
 	storage: S,
 	root: H::Out,
 	empty: H::Out,
+	#[cfg(feature = "std")]
+	trie_node_cache: Option<SharedTrieNodeCache<H>>,
 }
 
 impl<S: TrieBackendStorage<H>, H: Hasher> TrieBackendEssence<S, H> where H::Out: Encode {
@@ -60,9 +64,24 @@ impl<S: TrieBackendStorage<H>, H: Hasher> TrieBackendEssence<S, H> where H::Out:
 			storage,
 			root,
 			empty: H::hash(&[0u8]),
+			#[cfg(feature = "std")]
+			trie_node_cache: None,
 		}
 	}
 
+	/// Read trie nodes through the given shared cache before going to the storage.
+	#[cfg(feature = "std")]
+	pub fn with_trie_node_cache(mut self, cache: Option<SharedTrieNodeCache<H>>) -> Self {
+		self.trie_node_cache = cache;
+		self
+	}
+
+	/// Get the shared trie node cache, if any.
+	#[cfg(feature = "std")]
+	pub fn trie_node_cache(&self) -> Option<&SharedTrieNodeCache<H>> {
+		self.trie_node_cache.as_ref()
+	}
+
 	/// Get backend storage reference.
 	pub fn backend_storage(&self) -> &S {
 		&self.storage
@@ -276,8 +295,28 @@ impl<S: TrieBackendStorage<H>, H: Hasher> TrieBackendEssence<S, H> where H::Out:
 	}
 }
 
+impl<S: TrieBackendStorage<H>, H: Hasher> TrieBackendEssence<S, H> {
+	/// Get a trie node from the backend storage, consulting the trie node cache first.
+	pub(crate) fn storage_get(&self, key: &H::Out, prefix: Prefix) -> Result<Option<DBValue>> {
+		#[cfg(feature = "std")]
+		{
+			if let Some(cache) = &self.trie_node_cache {
+				if let Some(node) = cache.lock().get(key) {
+					return Ok(Some(node));
+				}
+				let node = self.storage.get(key, prefix)?;
+				if let Some(node) = &node {
+					cache.lock().insert(*key, node.clone());
+				}
+				return Ok(node);
+			}
+		}
+		self.storage.get(key, prefix)
+	}
+}
+
 pub(crate) struct Ephemeral<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> {
-	storage: &'a S,
+	storage: &'a TrieBackendEssence<S, H>,
 	overlay: &'a mut S::Overlay,
 }
 
@@ -289,7 +328,7 @@ impl<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> hash_db::AsHashDB<H, DBV
 }
 
 impl<'a, S: TrieBackendStorage<H>, H: Hasher> Ephemeral<'a, S, H> {
-	pub fn new(storage: &'a S, overlay: &'a mut S::Overlay) -> Self {
+	pub fn new(storage: &'a TrieBackendEssence<S, H>, overlay: &'a mut S::Overlay) -> Self {
 		Ephemeral {
 			storage,
 			overlay,
@@ -304,7 +343,7 @@ impl<'a, S: 'a + TrieBackendStorage<H>, H: Hasher> hash_db::HashDB<H, DBValue>
 		if let Some(val) = hash_db::HashDB::get(self.overlay, key, prefix) {
 			Some(val)
 		} else {
-			match self.storage.get(&key, prefix) {
+			match self.storage.storage_get(&key, prefix) {
 				Ok(x) => x,
 				Err(e) => {
 					warn!(target: "trie", "Failed to read from DB: {}", e);
@@ -392,7 +431,7 @@ impl<S: TrieBackendStorage<H>, H: Hasher> hash_db::HashDB<H, DBValue>
 		if *key == self.empty {
 			return Some([0u8].to_vec())
 		}
-		match self.storage.get(&key, prefix) {
+		match self.storage_get(&key, prefix) {
 			Ok(x) => x,
 			Err(e) => {
 				warn!(target: "trie", "Failed to read from DB: {}", e);
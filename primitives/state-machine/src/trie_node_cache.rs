@@ -0,0 +1,173 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Trie node cache shared between the states of different blocks.
+//!
+//! Trie nodes are addressed by the hash of their encoding, so a cached node is valid
+//! for any state that references it. Nothing has to be invalidated on reorgs or when
+//! a block is imported: a different state has a different root and only reaches nodes
+//! by their hash. Only nodes that were found in the backing storage are cached.
+
+use std::sync::Arc;
+use parking_lot::Mutex;
+use linked_hash_map::LinkedHashMap;
+use hash_db::Hasher;
+use sp_trie::DBValue;
+
+/// Trie node cache that can be shared between threads and states.
+pub type SharedTrieNodeCache<H> = Arc<Mutex<TrieNodeCache<H>>>;
+
+/// Create a new shared trie node cache with the given memory budget in bytes.
+pub fn new_shared_trie_node_cache<H: Hasher>(max_size: usize) -> SharedTrieNodeCache<H> {
+	Arc::new(Mutex::new(TrieNodeCache::new(max_size)))
+}
+
+/// Usage statistics of a trie node cache.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TrieNodeCacheStats {
+	/// Estimated memory used by the cached nodes in bytes.
+	pub used_size: usize,
+	/// Number of cached nodes.
+	pub nodes: usize,
+	/// Number of lookups that were served from the cache.
+	pub hits: u64,
+	/// Number of lookups that had to go to the backing storage.
+	pub misses: u64,
+}
+
+/// LRU cache of encoded trie nodes keyed by node hash.
+pub struct TrieNodeCache<H: Hasher> {
+	nodes: LinkedHashMap<H::Out, DBValue>,
+	used_size: usize,
+	max_size: usize,
+	hits: u64,
+	misses: u64,
+}
+
+impl<H: Hasher> TrieNodeCache<H> {
+	/// Create an empty cache that holds at most `max_size` bytes of nodes.
+	pub fn new(max_size: usize) -> Self {
+		TrieNodeCache {
+			nodes: LinkedHashMap::new(),
+			used_size: 0,
+			max_size,
+			hits: 0,
+			misses: 0,
+		}
+	}
+
+	/// Get the node with the given hash and mark it as recently used.
+	///
+	/// Counts a hit or a miss.
+	pub fn get(&mut self, hash: &H::Out) -> Option<DBValue> {
+		match self.nodes.get_refresh(hash) {
+			Some(node) => {
+				self.hits += 1;
+				Some(node.clone())
+			},
+			None => {
+				self.misses += 1;
+				None
+			},
+		}
+	}
+
+	/// Add a node, evicting the least recently used nodes if over budget.
+	///
+	/// Nodes larger than the whole budget are not cached.
+	pub fn insert(&mut self, hash: H::Out, node: DBValue) {
+		let size = Self::entry_size(&node);
+		if size > self.max_size {
+			return;
+		}
+		if let Some(old) = self.nodes.insert(hash, node) {
+			self.used_size -= Self::entry_size(&old);
+		}
+		self.used_size += size;
+
+		while self.used_size > self.max_size {
+			match self.nodes.pop_front() {
+				Some((_, node)) => self.used_size -= Self::entry_size(&node),
+				None => break,
+			}
+		}
+	}
+
+	/// Remove all cached nodes. Statistics are kept.
+	pub fn clear(&mut self) {
+		self.nodes.clear();
+		self.used_size = 0;
+	}
+
+	/// Current usage statistics.
+	pub fn stats(&self) -> TrieNodeCacheStats {
+		TrieNodeCacheStats {
+			used_size: self.used_size,
+			nodes: self.nodes.len(),
+			hits: self.hits,
+			misses: self.misses,
+		}
+	}
+
+	fn entry_size(node: &DBValue) -> usize {
+		node.capacity() + std::mem::size_of::<H::Out>()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::{Blake2Hasher, H256};
+
+	fn node(byte: u8, len: usize) -> (H256, DBValue) {
+		let value = vec![byte; len];
+		(Blake2Hasher::hash(&value), value)
+	}
+
+	#[test]
+	fn evicts_least_recently_used() {
+		let entry = 100 + std::mem::size_of::<H256>();
+		let mut cache = TrieNodeCache::<Blake2Hasher>::new(entry * 2);
+		let (h1, n1) = node(1, 100);
+		let (h2, n2) = node(2, 100);
+		let (h3, n3) = node(3, 100);
+
+		cache.insert(h1, n1.clone());
+		cache.insert(h2, n2);
+		assert_eq!(cache.get(&h1), Some(n1.clone()));
+		cache.insert(h3, n3.clone());
+
+		assert_eq!(cache.get(&h2), None);
+		assert_eq!(cache.get(&h1), Some(n1));
+		assert_eq!(cache.get(&h3), Some(n3));
+		assert_eq!(cache.stats(), TrieNodeCacheStats {
+			used_size: entry * 2,
+			nodes: 2,
+			hits: 3,
+			misses: 1,
+		});
+	}
+
+	#[test]
+	fn skips_nodes_over_budget() {
+		let mut cache = TrieNodeCache::<Blake2Hasher>::new(64);
+		let (hash, value) = node(1, 64);
+		cache.insert(hash, value);
+		assert_eq!(cache.get(&hash), None);
+		assert_eq!(cache.stats().used_size, 0);
+	}
+}
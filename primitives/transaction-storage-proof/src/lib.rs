@@ -0,0 +1,227 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage proof primitives. Contains types and basic code to extract storage
+//! proofs for indexed transactions.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_std::{result::Result, prelude::*};
+
+use codec::{Encode, Decode};
+use sp_inherents::{Error, InherentIdentifier, InherentData, IsFatalError};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+/// The identifier for the proof inherent.
+pub const INHERENT_IDENTIFIER: InherentIdentifier = *b"tx_proof";
+/// Storage period for data.
+pub const DEFAULT_STORAGE_PERIOD: u32 = 100800;
+/// Proof trie value size.
+pub const CHUNK_SIZE: usize = 256;
+
+/// Errors that can occur while checking the storage proof.
+#[derive(Encode, sp_runtime::RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode))]
+pub enum InherentError {
+	InvalidProof,
+	TrieError,
+}
+
+impl IsFatalError for InherentError {
+	fn is_fatal_error(&self) -> bool {
+		true
+	}
+}
+
+/// Proof that a chunk of an indexed transaction is stored.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, sp_runtime::RuntimeDebug)]
+pub struct TransactionStorageProof {
+	/// Data chunk that is proved to exist.
+	pub chunk: Vec<u8>,
+	/// Trie nodes that compose the proof.
+	pub proof: Vec<Vec<u8>>,
+}
+
+/// Auxiliary trait to extract storage proof.
+pub trait TransactionStorageProofInherentData {
+	/// Get the proof.
+	fn storage_proof(&self) -> Result<Option<TransactionStorageProof>, Error>;
+}
+
+impl TransactionStorageProofInherentData for InherentData {
+	fn storage_proof(&self) -> Result<Option<TransactionStorageProof>, Error> {
+		Ok(self.get_data(&INHERENT_IDENTIFIER)?)
+	}
+}
+
+/// Get chunk index for the given random hash and the total number of chunks.
+pub fn random_chunk(random_hash: &[u8], total_chunks: u32) -> u32 {
+	let mut buf = [0u8; 8];
+	buf.copy_from_slice(&random_hash[0..8]);
+	let random_u64 = u64::from_be_bytes(buf);
+	(random_u64 % total_chunks as u64) as u32
+}
+
+/// Create a key for the proof trie from a chunk index.
+pub fn encode_index(input: u32) -> Vec<u8> {
+	codec::Encode::encode(&codec::Compact(input))
+}
+
+/// Number of chunks the data of the given size is split into.
+pub fn num_chunks(size: u32) -> u32 {
+	((size as u64 + CHUNK_SIZE as u64 - 1) / CHUNK_SIZE as u64) as u32
+}
+
+/// An interface to request indexed data from the client.
+pub trait IndexedBody<B: BlockT> {
+	/// Get all indexed transactions for a block,
+	/// including renewed transactions.
+	///
+	/// Note that this will only fetch transactions
+	/// that are indexed by the runtime with `storage_index_transaction`.
+	fn block_indexed_body(
+		&self,
+		number: NumberFor<B>,
+	) -> Result<Option<Vec<Vec<u8>>>, Error>;
+
+	/// Get the block number for the given block hash.
+	fn number(
+		&self,
+		hash: B::Hash,
+	) -> Result<Option<NumberFor<B>>, Error>;
+}
+
+/// Provider for the storage proof inherent data.
+#[cfg(feature = "std")]
+pub struct InherentDataProvider<F> {
+	inner: F,
+}
+
+#[cfg(feature = "std")]
+impl<F> InherentDataProvider<F> {
+	/// Create a new provider that calls `proof_oracle` to get the proof for the block that is
+	/// being authored.
+	pub fn new(proof_oracle: F) -> Self {
+		InherentDataProvider { inner: proof_oracle }
+	}
+}
+
+#[cfg(feature = "std")]
+impl<F> sp_inherents::ProvideInherentData for InherentDataProvider<F>
+where F: Fn() -> Result<Option<TransactionStorageProof>, Error>
+{
+	fn inherent_identifier(&self) -> &'static InherentIdentifier {
+		&INHERENT_IDENTIFIER
+	}
+
+	fn provide_inherent_data(&self, inherent_data: &mut InherentData) -> Result<(), Error> {
+		match (self.inner)()? {
+			Some(proof) => inherent_data.put_data(INHERENT_IDENTIFIER, &proof),
+			None => Ok(()),
+		}
+	}
+
+	fn error_to_string(&self, error: &[u8]) -> Option<String> {
+		InherentError::decode(&mut &error[..]).map(|e| format!("{:?}", e)).ok()
+	}
+}
+
+/// Build the storage proof for the block authored on top of `parent`.
+///
+/// The proof is for the block that is `storage_period` blocks behind the new block.
+/// Returns `None` if there's no such block or it has no indexed transactions.
+#[cfg(feature = "std")]
+pub fn build_proof_at<B, C>(
+	client: &C,
+	parent: B::Hash,
+	storage_period: NumberFor<B>,
+) -> Result<Option<TransactionStorageProof>, Error>
+where
+	B: BlockT,
+	C: IndexedBody<B>,
+{
+	use sp_runtime::traits::{One, Saturating, Zero};
+
+	let number = client.number(parent)?
+		.ok_or_else(|| Error::from(format!("Unknown parent block {:?}", parent)))?
+		.saturating_add(One::one());
+	if number <= storage_period {
+		return Ok(None);
+	}
+	let target = number.saturating_sub(storage_period);
+	if target.is_zero() {
+		return Ok(None);
+	}
+	match client.block_indexed_body(target)? {
+		Some(transactions) => build_proof(parent.as_ref(), transactions),
+		None => Ok(None),
+	}
+}
+
+/// Build a proof for the chunk selected by `random_hash` from the given indexed transactions.
+///
+/// Each transaction is split into `CHUNK_SIZE` chunks that form a trie keyed by the
+/// `encode_index` of the chunk number within the transaction. The chunk is selected from all
+/// chunks of all transactions. Returns `None` if there are no chunks.
+#[cfg(feature = "std")]
+pub fn build_proof(
+	random_hash: &[u8],
+	transactions: Vec<Vec<u8>>,
+) -> Result<Option<TransactionStorageProof>, Error> {
+	use std::convert::TryFrom;
+	use sp_trie::TrieMut;
+	type TrieLayout = sp_trie::Layout<sp_runtime::traits::BlakeTwo256>;
+
+	let total_chunks: u64 = transactions.iter().map(|t| num_chunks(t.len() as u32) as u64).sum();
+	if total_chunks == 0 {
+		return Ok(None);
+	}
+	let total_chunks = u32::try_from(total_chunks)
+		.map_err(|_| Error::from("Too many chunks to build a storage proof"))?;
+	let mut target_chunk_index = random_chunk(random_hash, total_chunks);
+
+	for transaction in transactions {
+		let chunks = num_chunks(transaction.len() as u32);
+		if target_chunk_index >= chunks {
+			target_chunk_index -= chunks;
+			continue;
+		}
+
+		let mut db = sp_trie::MemoryDB::<sp_runtime::traits::BlakeTwo256>::default();
+		let mut root = Default::default();
+		let mut target_chunk = Vec::new();
+		{
+			let mut trie = sp_trie::TrieDBMut::<TrieLayout>::new(&mut db, &mut root);
+			for (index, chunk) in transaction.chunks(CHUNK_SIZE).enumerate() {
+				trie.insert(&encode_index(index as u32), chunk)
+					.map_err(|e| Error::from(format!("Error building storage proof trie: {}", e)))?;
+				if index as u32 == target_chunk_index {
+					target_chunk = chunk.to_vec();
+				}
+			}
+			trie.commit();
+		}
+		let proof = sp_trie::generate_trie_proof::<TrieLayout, _, _, _>(
+			&db,
+			root,
+			&[encode_index(target_chunk_index)],
+		).map_err(|e| Error::from(format!("Error generating storage proof: {}", e)))?;
+
+		return Ok(Some(TransactionStorageProof { chunk: target_chunk, proof }));
+	}
+	Ok(None)
+}
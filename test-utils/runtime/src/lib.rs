@@ -717,6 +717,23 @@ cfg_if! {
 						AuraId::from(authority)
 					}).collect()
 				}
+
+				fn generate_key_ownership_proof(
+					_slot_number: u64,
+					_authority_id: AuraId,
+				) -> Option<sp_consensus_aura::OpaqueKeyOwnershipProof> {
+					None
+				}
+
+				fn submit_report_equivocation_unsigned_extrinsic(
+					_equivocation_proof: sp_consensus_aura::AuraEquivocationProof<
+						<Block as BlockT>::Header,
+						AuraId,
+					>,
+					_key_owner_proof: sp_consensus_aura::OpaqueKeyOwnershipProof,
+				) -> Option<()> {
+					None
+				}
 			}
 
 			impl sp_consensus_babe::BabeApi<Block> for Runtime {
@@ -740,6 +757,10 @@ cfg_if! {
 					<pallet_babe::Module<Runtime>>::current_epoch()
 				}
 
+				fn author_vrf_randomness() -> Option<sp_consensus_babe::Randomness> {
+					<pallet_babe::Module<Runtime>>::author_vrf_randomness()
+				}
+
 				fn submit_report_equivocation_unsigned_extrinsic(
 					_equivocation_proof: sp_consensus_babe::EquivocationProof<
 						<Block as BlockT>::Header,
@@ -765,8 +786,8 @@ cfg_if! {
 			}
 
 			impl sp_session::SessionKeys<Block> for Runtime {
-				fn generate_session_keys(_: Option<Vec<u8>>) -> Vec<u8> {
-					SessionKeys::generate(None)
+				fn generate_session_keys(owner: Vec<u8>, _: Option<Vec<u8>>) -> sp_session::OpaqueGeneratedSessionKeys {
+					SessionKeys::generate_with_proof(&owner, None).into()
 				}
 
 				fn decode_session_keys(
@@ -781,6 +802,10 @@ cfg_if! {
 					Vec::new()
 				}
 
+				fn current_set_id() -> sp_finality_grandpa::SetId {
+					0
+				}
+
 				fn submit_report_equivocation_unsigned_extrinsic(
 					_equivocation_proof: sp_finality_grandpa::EquivocationProof<
 						<Block as BlockT>::Hash,
@@ -972,6 +997,23 @@ cfg_if! {
 						AuraId::from(authority)
 					}).collect()
 				}
+
+				fn generate_key_ownership_proof(
+					_slot_number: u64,
+					_authority_id: AuraId,
+				) -> Option<sp_consensus_aura::OpaqueKeyOwnershipProof> {
+					None
+				}
+
+				fn submit_report_equivocation_unsigned_extrinsic(
+					_equivocation_proof: sp_consensus_aura::AuraEquivocationProof<
+						<Block as BlockT>::Header,
+						AuraId,
+					>,
+					_key_owner_proof: sp_consensus_aura::OpaqueKeyOwnershipProof,
+				) -> Option<()> {
+					None
+				}
 			}
 
 			impl sp_consensus_babe::BabeApi<Block> for Runtime {
@@ -995,6 +1037,10 @@ cfg_if! {
 					<pallet_babe::Module<Runtime>>::current_epoch()
 				}
 
+				fn author_vrf_randomness() -> Option<sp_consensus_babe::Randomness> {
+					<pallet_babe::Module<Runtime>>::author_vrf_randomness()
+				}
+
 				fn submit_report_equivocation_unsigned_extrinsic(
 					_equivocation_proof: sp_consensus_babe::EquivocationProof<
 						<Block as BlockT>::Header,
@@ -1020,8 +1066,8 @@ cfg_if! {
 			}
 
 			impl sp_session::SessionKeys<Block> for Runtime {
-				fn generate_session_keys(_: Option<Vec<u8>>) -> Vec<u8> {
-					SessionKeys::generate(None)
+				fn generate_session_keys(owner: Vec<u8>, _: Option<Vec<u8>>) -> sp_session::OpaqueGeneratedSessionKeys {
+					SessionKeys::generate_with_proof(&owner, None).into()
 				}
 
 				fn decode_session_keys(